@@ -0,0 +1,69 @@
+/*
+ * C interface to the graph migrator engine (libgraph_migrator).
+ *
+ * - A scanned graph is an opaque GmGraph handle; release it with
+ *   gm_graph_free().
+ * - Results are UTF-8 JSON strings owned by the caller; release them with
+ *   gm_string_free().
+ * - Failures return NULL; gm_last_error() then describes the most recent
+ *   failure on the calling thread.
+ *
+ * Handles may be used from any thread, but not from two threads at once.
+ */
+
+#ifndef GRAPH_MIGRATOR_H
+#define GRAPH_MIGRATOR_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* `direction` for gm_closure(): everything the symbol depends on */
+#define GM_OUTGOING 0
+/* `direction` for gm_closure(): everything that depends on the symbol */
+#define GM_INCOMING 1
+
+typedef struct GmGraph GmGraph;
+
+/* Library version, e.g. "0.1.0"; static, do not free */
+const char *gm_version(void);
+
+/* Last failure on this thread, or NULL if the last call succeeded; valid
+ * until the next call on the same thread, do not free */
+const char *gm_last_error(void);
+
+/* Scan and resolve the project at `root`, honoring its migrator.toml */
+GmGraph *gm_scan(const char *root);
+
+/* Load a graph saved as JSON (scan --format json, or a state file) */
+GmGraph *gm_load(const char *path);
+
+/* Release a graph; NULL is ignored */
+void gm_graph_free(GmGraph *graph);
+
+/* Node and edge counts; 0 for NULL */
+size_t gm_node_count(const GmGraph *graph);
+size_t gm_edge_count(const GmGraph *graph);
+
+/* The whole graph as GraphData JSON */
+char *gm_export_json(const GmGraph *graph);
+
+/* Sorted JSON array of the IDs of nodes matching a filter expression,
+ * e.g. "type=Function AND file~\"legacy/\"" */
+char *gm_filter(const GmGraph *graph, const char *filter);
+
+/* Sorted JSON array of the IDs transitively reachable from `node_id` over
+ * dependency edges, in `direction` (GM_OUTGOING or GM_INCOMING) */
+char *gm_closure(const GmGraph *graph, const char *node_id, int direction);
+
+/* Release a string returned by any function above except gm_version() and
+ * gm_last_error(); NULL is ignored */
+void gm_string_free(char *text);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* GRAPH_MIGRATOR_H */
@@ -0,0 +1,329 @@
+//! C ABI for embedding the graph migrator engine
+//!
+//! Exposes scanning, queries and export behind a small, stable set of
+//! `extern "C"` functions declared in `include/graph_migrator.h`, so IDE
+//! plugins and tools in other languages can link `libgraph_migrator`
+//! without a Rust toolchain.
+//!
+//! Conventions, all spelled out again in the header:
+//!
+//! - A scanned graph is an opaque [`GmGraph`] handle, released with
+//!   [`gm_graph_free()`].
+//! - Results are UTF-8 JSON strings owned by the caller, released with
+//!   [`gm_string_free()`].
+//! - Failures return `NULL`; [`gm_last_error()`] then describes the most
+//!   recent failure on the calling thread.
+//! - Panics never cross the boundary; they are reported as failures.
+
+use graph_migrator_core::filter::Filter;
+use graph_migrator_core::graph::Direction;
+use graph_migrator_core::{queries, resolve, Graph, GraphData};
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+/// `direction` for [`gm_closure()`]: everything the symbol depends on
+pub const GM_OUTGOING: c_int = 0;
+/// `direction` for [`gm_closure()`]: everything that depends on the symbol
+pub const GM_INCOMING: c_int = 1;
+
+/// A scanned dependency graph, opaque to C
+pub struct GmGraph {
+    graph: Graph,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Run `body`, recording its error or panic for [`gm_last_error()`]
+fn guard<T>(body: impl FnOnce() -> Result<T, String>) -> Option<T> {
+    let outcome = panic::catch_unwind(AssertUnwindSafe(body))
+        .unwrap_or_else(|_| Err("internal error (panic)".to_string()));
+    LAST_ERROR.with(|last| {
+        *last.borrow_mut() = outcome
+            .as_ref()
+            .err()
+            .map(|message| CString::new(message.replace('\0', " ")).unwrap_or_default())
+    });
+    outcome.ok()
+}
+
+/// `ptr` as a string, or an error naming the argument
+///
+/// # Safety
+/// `ptr` must be null or point to a NUL-terminated string that outlives `'a`.
+unsafe fn str_arg<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, String> {
+    if ptr.is_null() {
+        return Err(format!("{} is NULL", name));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| format!("{} is not valid UTF-8", name))
+}
+
+/// `graph` as a reference, or an error if it is null
+///
+/// # Safety
+/// `graph` must be null or a live handle from [`gm_scan()`] or [`gm_load()`].
+unsafe fn graph_arg<'a>(graph: *const GmGraph) -> Result<&'a Graph, String> {
+    graph
+        .as_ref()
+        .map(|handle| &handle.graph)
+        .ok_or_else(|| "graph is NULL".to_string())
+}
+
+/// `value` as JSON in a caller-owned C string
+fn json_string(value: &impl serde::Serialize) -> Result<*mut c_char, String> {
+    let json = serde_json::to_string(value).map_err(|e| e.to_string())?;
+    CString::new(json)
+        .map(CString::into_raw)
+        .map_err(|e| e.to_string())
+}
+
+/// Version of the library, e.g. `"0.1.0"`; a static string, not to be freed
+#[no_mangle]
+pub extern "C" fn gm_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// Description of the last failure on this thread, or `NULL` if the last
+/// call succeeded
+///
+/// The string stays valid until the next call on the same thread; do not
+/// free it.
+#[no_mangle]
+pub extern "C" fn gm_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Scan and resolve the project at `root`, honoring its `migrator.toml`
+///
+/// Returns `NULL` on failure.
+///
+/// # Safety
+/// `root` must be a NUL-terminated UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn gm_scan(root: *const c_char) -> *mut GmGraph {
+    guard(|| {
+        let root = Path::new(str_arg(root, "root")?);
+        let output = resolve::resolve_directory(root).map_err(|e| e.to_string())?;
+        Ok(Box::into_raw(Box::new(GmGraph {
+            graph: output.graph.graph,
+        })))
+    })
+    .unwrap_or(std::ptr::null_mut())
+}
+
+/// Load a graph saved as JSON (`scan --format json` or a state file)
+///
+/// Returns `NULL` on failure.
+///
+/// # Safety
+/// `path` must be a NUL-terminated UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn gm_load(path: *const c_char) -> *mut GmGraph {
+    guard(|| {
+        let path = str_arg(path, "path")?;
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let data = GraphData::from_json(&text).map_err(|e| e.to_string())?;
+        let graph = Graph::try_from(data).map_err(|e| e.to_string())?;
+        Ok(Box::into_raw(Box::new(GmGraph { graph })))
+    })
+    .unwrap_or(std::ptr::null_mut())
+}
+
+/// Release a graph; `NULL` is ignored
+///
+/// # Safety
+/// `graph` must be null or a handle from [`gm_scan()`] or [`gm_load()`]
+/// that has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn gm_graph_free(graph: *mut GmGraph) {
+    if !graph.is_null() {
+        drop(Box::from_raw(graph));
+    }
+}
+
+/// Number of nodes in `graph`, or 0 if it is `NULL`
+///
+/// # Safety
+/// `graph` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn gm_node_count(graph: *const GmGraph) -> usize {
+    graph.as_ref().map_or(0, |handle| handle.graph.node_count())
+}
+
+/// Number of edges in `graph`, or 0 if it is `NULL`
+///
+/// # Safety
+/// `graph` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn gm_edge_count(graph: *const GmGraph) -> usize {
+    graph.as_ref().map_or(0, |handle| handle.graph.edge_count())
+}
+
+/// The whole graph as `GraphData` JSON, the format `scan --format json` writes
+///
+/// Returns `NULL` on failure; free the result with [`gm_string_free()`].
+///
+/// # Safety
+/// `graph` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn gm_export_json(graph: *const GmGraph) -> *mut c_char {
+    guard(|| json_string(&GraphData::from(graph_arg(graph)?))).unwrap_or(std::ptr::null_mut())
+}
+
+/// IDs of the nodes matching a filter expression (`type=Function AND
+/// file~"legacy/"`), as a sorted JSON array
+///
+/// Returns `NULL` on failure, including an invalid expression; free the
+/// result with [`gm_string_free()`].
+///
+/// # Safety
+/// `graph` must be a live handle and `filter` a NUL-terminated UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn gm_filter(graph: *const GmGraph, filter: *const c_char) -> *mut c_char {
+    guard(|| {
+        let graph = graph_arg(graph)?;
+        let filter = Filter::parse(str_arg(filter, "filter")?).map_err(|e| e.to_string())?;
+        let mut ids: Vec<&str> = graph
+            .nodes()
+            .filter(|node| filter.matches(node))
+            .map(|node| node.id.as_str())
+            .collect();
+        ids.sort_unstable();
+        json_string(&ids)
+    })
+    .unwrap_or(std::ptr::null_mut())
+}
+
+/// IDs of every node transitively reachable from `node_id` over dependency
+/// edges, as a sorted JSON array
+///
+/// `direction` is [`GM_OUTGOING`] (what the symbol depends on) or
+/// [`GM_INCOMING`] (what depends on it). Returns `NULL` on failure; free
+/// the result with [`gm_string_free()`].
+///
+/// # Safety
+/// `graph` must be a live handle and `node_id` a NUL-terminated UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn gm_closure(
+    graph: *const GmGraph,
+    node_id: *const c_char,
+    direction: c_int,
+) -> *mut c_char {
+    guard(|| {
+        let graph = graph_arg(graph)?;
+        let node_id = str_arg(node_id, "node_id")?;
+        let direction = match direction {
+            GM_OUTGOING => Direction::Outgoing,
+            GM_INCOMING => Direction::Incoming,
+            other => return Err(format!("unknown direction {}", other)),
+        };
+        let reached =
+            queries::closure(graph, node_id, direction, &[]).map_err(|e| e.to_string())?;
+        json_string(&reached)
+    })
+    .unwrap_or(std::ptr::null_mut())
+}
+
+/// Release a string returned by this library; `NULL` is ignored
+///
+/// # Safety
+/// `text` must be null or a string returned by a `gm_` function other than
+/// [`gm_version()`] and [`gm_last_error()`], not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn gm_string_free(text: *mut c_char) {
+    if !text.is_null() {
+        drop(CString::from_raw(text));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn owned(text: *mut c_char) -> String {
+        assert!(!text.is_null(), "call failed: {}", last_error());
+        let copy = unsafe { CStr::from_ptr(text) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        unsafe { gm_string_free(text) };
+        copy
+    }
+
+    fn last_error() -> String {
+        let message = gm_last_error();
+        if message.is_null() {
+            return String::new();
+        }
+        unsafe { CStr::from_ptr(message) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn test_scan_query_and_export() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("app.py"),
+            "def main():\n    helper()\n\ndef helper():\n    pass\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("migrator.toml"), "relative_ids = true\n").unwrap();
+
+        let root = CString::new(dir.path().to_str().unwrap()).unwrap();
+        let graph = unsafe { gm_scan(root.as_ptr()) };
+        assert!(!graph.is_null(), "{}", last_error());
+        assert!(gm_last_error().is_null());
+        assert_eq!(unsafe { gm_node_count(graph) }, 2);
+
+        let filter = CString::new("name=helper").unwrap();
+        let found = owned(unsafe { gm_filter(graph, filter.as_ptr()) });
+        assert_eq!(found, r#"["app.py::helper"]"#);
+
+        let helper = CString::new("app.py::helper").unwrap();
+        let callers = owned(unsafe { gm_closure(graph, helper.as_ptr(), GM_INCOMING) });
+        assert_eq!(callers, r#"["app.py::main"]"#);
+
+        let saved = dir.path().join("graph.json");
+        std::fs::write(&saved, owned(unsafe { gm_export_json(graph) })).unwrap();
+        let path = CString::new(saved.to_str().unwrap()).unwrap();
+        let loaded = unsafe { gm_load(path.as_ptr()) };
+        assert_eq!(unsafe { gm_edge_count(loaded) }, unsafe {
+            gm_edge_count(graph)
+        });
+
+        unsafe {
+            gm_graph_free(loaded);
+            gm_graph_free(graph);
+        }
+    }
+
+    #[test]
+    fn test_failures_set_last_error() {
+        let bad = CString::new("type=").unwrap();
+        assert!(unsafe { gm_filter(std::ptr::null(), bad.as_ptr()) }.is_null());
+        assert_eq!(last_error(), "graph is NULL");
+
+        let graph = Box::into_raw(Box::new(GmGraph {
+            graph: Graph::new(),
+        }));
+        assert!(unsafe { gm_filter(graph, bad.as_ptr()) }.is_null());
+        assert!(last_error().contains("expected a value"));
+        let missing = CString::new("nowhere.py::f").unwrap();
+        assert!(unsafe { gm_closure(graph, missing.as_ptr(), 7) }.is_null());
+        assert_eq!(last_error(), "unknown direction 7");
+
+        let version = unsafe { CStr::from_ptr(gm_version()) };
+        assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+        unsafe { gm_graph_free(graph) };
+    }
+}
@@ -0,0 +1,15 @@
+//! Compiles the gRPC schema when the `grpc` feature is enabled
+
+fn main() {
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto/migrator.proto");
+        // A bundled protoc, so building needs no system install
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("bundled protoc");
+        std::env::set_var("PROTOC", protoc);
+        tonic_prost_build::configure()
+            .build_client(false)
+            .compile_protos(&["proto/migrator.proto"], &["proto"])
+            .expect("compile proto/migrator.proto");
+    }
+}
@@ -0,0 +1,258 @@
+//! `migrator serve --grpc`: the remote analysis API of `proto/migrator.proto`
+//!
+//! Scans are heavyweight and belong on a build server; developers' tools
+//! then ask it for small pieces of the result:
+//!
+//! - `Scan` - rescan the served project, replacing the graph queries see
+//! - `Query` - the neighborhood of a symbol as a `Subgraph`
+//!
+//! The graph is scanned once at startup, like `serve`, with statuses and
+//! links overlaid from the state file. An unknown root is `NOT_FOUND`; a
+//! failed rescan is `INTERNAL` and keeps the previous graph.
+
+use graph_migrator_core::config::Config;
+use graph_migrator_core::queries::{self, Direction};
+use graph_migrator_core::{Graph, GraphMigratorError};
+use std::net::ToSocketAddrs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+use tonic::{Request, Response, Status};
+
+mod proto {
+    tonic::include_proto!("graph_migrator.v1");
+}
+
+use proto::migrator_server::{Migrator, MigratorServer};
+
+/// Neighborhood depth for `Query` when `depth` is not set
+const DEFAULT_DEPTH: u32 = 1;
+
+/// Scan `root` and answer calls on `host:port` until the process is killed
+pub fn run(root: &Path, config: &Config, host: &str, port: u16) -> anyhow::Result<()> {
+    let graph = load(root, config)?;
+    tracing::info!(nodes = graph.node_count(), "graph ready");
+
+    let addr = (host, port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("{} does not resolve to an address", host))?;
+    let service = Service {
+        root: root.to_path_buf(),
+        config: config.clone(),
+        graph: Arc::new(RwLock::new(graph)),
+    };
+    eprintln!("Serving {} over gRPC on {}", root.display(), addr);
+
+    tokio::runtime::Runtime::new()?.block_on(
+        tonic::transport::Server::builder()
+            .add_service(MigratorServer::new(service))
+            .serve(addr),
+    )?;
+    Ok(())
+}
+
+struct Service {
+    root: PathBuf,
+    config: Config,
+    graph: Arc<RwLock<Graph>>,
+}
+
+impl Service {
+    fn graph(&self) -> std::sync::RwLockReadGuard<'_, Graph> {
+        self.graph
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[tonic::async_trait]
+impl Migrator for Service {
+    async fn scan(
+        &self,
+        _request: Request<proto::ScanRequest>,
+    ) -> Result<Response<proto::ScanReply>, Status> {
+        let started = Instant::now();
+        let (root, config) = (self.root.clone(), self.config.clone());
+        let graph = tokio::task::spawn_blocking(move || load(&root, &config))
+            .await
+            .map_err(|err| Status::internal(err.to_string()))?
+            .map_err(|err| Status::internal(format!("{:#}", err)))?;
+        let reply = proto::ScanReply {
+            node_count: graph.node_count() as u64,
+            edge_count: graph.edge_count() as u64,
+            fingerprint: graph.fingerprint(),
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        };
+        tracing::info!(nodes = reply.node_count, ms = reply.elapsed_ms, "rescanned");
+        *self
+            .graph
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = graph;
+        Ok(Response::new(reply))
+    }
+
+    async fn query(
+        &self,
+        request: Request<proto::QueryRequest>,
+    ) -> Result<Response<proto::Subgraph>, Status> {
+        let request = request.into_inner();
+        let direction = match request.direction() {
+            proto::Direction::Both => None,
+            proto::Direction::Dependencies => Some(Direction::Outgoing),
+            proto::Direction::Dependents => Some(Direction::Incoming),
+        };
+        let depth = request.depth.unwrap_or(DEFAULT_DEPTH) as usize;
+        tracing::debug!(root = request.root, depth, "query");
+        match queries::subgraph(&self.graph(), &request.root, depth, direction) {
+            Ok(nearby) => Ok(Response::new(to_proto(&nearby))),
+            Err(GraphMigratorError::NodeNotFound(id)) => {
+                Err(Status::not_found(format!("node not found: {}", id)))
+            }
+            Err(err) => Err(Status::internal(err.to_string())),
+        }
+    }
+}
+
+/// The graph served for `root`, file paths relative to it like the IDs
+fn load(root: &Path, config: &Config) -> anyhow::Result<Graph> {
+    let mut graph = crate::load_view(root, config)?;
+    if config.relative_ids {
+        graph_migrator_core::parser::relativize_file_paths(&mut graph, root);
+    }
+    Ok(graph)
+}
+
+/// `graph` as a `Subgraph`, nodes ordered by ID and edges by endpoints and
+/// type, so equal graphs give equal replies
+fn to_proto(graph: &Graph) -> proto::Subgraph {
+    let mut nodes: Vec<proto::Node> = graph
+        .nodes()
+        .map(|node| {
+            let (start, end) = node.line_range.unwrap_or((0, 0));
+            proto::Node {
+                id: node.id.clone(),
+                name: node.name.clone(),
+                node_type: label(&node.node_type),
+                language: node.language.clone(),
+                file_path: node.file_path.display().to_string(),
+                start_line: start as u32,
+                end_line: end as u32,
+                status: label(&node.status),
+                external: node.external,
+            }
+        })
+        .collect();
+    nodes.sort_by(|a, b| a.id.cmp(&b.id));
+    let mut edges: Vec<proto::Edge> = graph
+        .edge_endpoints()
+        .filter_map(|(from, to, edge)| {
+            Some(proto::Edge {
+                source: graph.node_weight(from)?.id.clone(),
+                target: graph.node_weight(to)?.id.clone(),
+                edge_type: label(&edge.edge_type),
+            })
+        })
+        .collect();
+    edges.sort_by(|a, b| {
+        (&a.source, &a.target, &a.edge_type).cmp(&(&b.source, &b.target, &b.edge_type))
+    });
+    proto::Subgraph { nodes, edges }
+}
+
+/// `value`'s name in the JSON output, e.g. `"Function"` or `"Pending"`
+fn label(value: &impl serde::Serialize) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message;
+
+    fn service(root: &Path) -> Service {
+        let config = Config::default();
+        Service {
+            root: root.to_path_buf(),
+            graph: Arc::new(RwLock::new(load(root, &config).unwrap())),
+            config,
+        }
+    }
+
+    fn query(
+        service: &Service,
+        root: &str,
+        direction: proto::Direction,
+    ) -> Result<proto::Subgraph, Status> {
+        let request = proto::QueryRequest {
+            root: root.to_string(),
+            depth: None,
+            direction: direction as i32,
+        };
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let reply = runtime.block_on(service.query(Request::new(request)))?;
+        // Through the wire format and back
+        let bytes = reply.into_inner().encode_to_vec();
+        Ok(proto::Subgraph::decode(bytes.as_slice()).unwrap())
+    }
+
+    #[test]
+    fn test_query_round_trips_sorted_subgraph_and_sees_rescans() {
+        let project = tempfile::tempdir().unwrap();
+        let app = project.path().join("app.py");
+        std::fs::write(
+            &app,
+            "def main():\n    zeta()\n    alpha()\n\ndef zeta():\n    pass\n\ndef alpha():\n    pass\n",
+        )
+        .unwrap();
+        let service = service(project.path());
+
+        let subgraph = query(&service, "app.py::main", proto::Direction::Dependencies).unwrap();
+        let ids: Vec<&str> = subgraph.nodes.iter().map(|node| node.id.as_str()).collect();
+        assert_eq!(ids, ["app.py::alpha", "app.py::main", "app.py::zeta"]);
+        let edges: Vec<(&str, &str, &str)> = subgraph
+            .edges
+            .iter()
+            .map(|edge| {
+                (
+                    edge.source.as_str(),
+                    edge.target.as_str(),
+                    edge.edge_type.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            edges,
+            [
+                ("app.py::main", "app.py::alpha", "Calls"),
+                ("app.py::main", "app.py::zeta", "Calls"),
+            ]
+        );
+        let main = &subgraph.nodes[1];
+        assert_eq!(
+            (main.name.as_str(), main.node_type.as_str()),
+            ("main", "Function")
+        );
+        assert_eq!((main.file_path.as_str(), main.start_line), ("app.py", 1));
+
+        // Nothing depends on `main`; an unknown root is NOT_FOUND
+        let dependents = query(&service, "app.py::main", proto::Direction::Dependents).unwrap();
+        assert_eq!(dependents.nodes.len(), 1);
+        let missing = query(&service, "app.py::gone", proto::Direction::Both).unwrap_err();
+        assert_eq!(missing.code(), tonic::Code::NotFound);
+
+        // A rescan replaces the graph later queries see
+        std::fs::write(&app, "def main():\n    pass\n").unwrap();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let reply = runtime
+            .block_on(service.scan(Request::new(proto::ScanRequest {})))
+            .unwrap()
+            .into_inner();
+        assert_eq!((reply.node_count, reply.edge_count), (1, 0));
+        let subgraph = query(&service, "app.py::main", proto::Direction::Both).unwrap();
+        assert_eq!((subgraph.nodes.len(), subgraph.edges.len()), (1, 0));
+    }
+}
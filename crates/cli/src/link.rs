@@ -0,0 +1,195 @@
+//! `migrator link --interactive`: confirm MigratedTo links one legacy symbol
+//! at a time
+//!
+//! Legacy and target symbols are told apart by the `--from` and `--to`
+//! filters, or else by the files the `[[mapping]]` rules map from and to (see
+//! [`migration::split_by_mapping()`]). Walks the legacy symbols without a link
+//! that have candidate targets (see [`migration::match_candidates_with()`],
+//! which also applies the mapping rules), in ID order. For each, the prompt shows
+//! where the symbol is, its first line, and its best [`SHOWN`] candidates
+//! with their scores. Picking one records the link and, if asked, a new status
+//! for the legacy symbol; both are written to the state file straight away,
+//! so quitting midway keeps them.
+
+use graph_migrator_core::config::Config;
+use graph_migrator_core::events::Emitter;
+use graph_migrator_core::filter::Filter;
+use graph_migrator_core::migration::{self, MatchCandidate};
+use graph_migrator_core::{queries, store, MigrationStatus, Node};
+use std::collections::{BTreeMap, HashSet};
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+/// Candidates listed per legacy symbol
+const SHOWN: usize = 5;
+
+/// Scan `root`, then prompt on stdin for each unlinked legacy symbol
+///
+/// `sides` filters the legacy and the target symbols; without it the
+/// `[[mapping]]` rules decide, and there must be some.
+pub fn run(root: &Path, config: &Config, sides: Option<&(Filter, Filter)>) -> anyhow::Result<()> {
+    if sides.is_none() && config.mapping.is_empty() {
+        anyhow::bail!(
+            "link --interactive needs --from and --to, or [[mapping]] rules, to tell legacy symbols from their targets"
+        );
+    }
+    let emitter = Emitter::from_config(config, root)?;
+    let mut graph = crate::load_for_update(root, config)?;
+    let state_path = root.join(&config.output.graph);
+    // File paths are shown relative to the root they were resolved under
+    let root = config.path_resolver().resolve(root)?;
+    let root = root.as_path();
+
+    let unlinked = queries::migration_coverage(&graph).unlinked;
+    let mut by_legacy: BTreeMap<String, Vec<MatchCandidate>> = BTreeMap::new();
+    let (legacy, target) = match sides {
+        Some((from, to)) => {
+            let (mut legacy, mut target) = (graph.clone(), graph.clone());
+            from.retain(&mut legacy);
+            to.retain(&mut target);
+            (legacy, target)
+        }
+        None => migration::split_by_mapping(&graph, &config.mapping),
+    };
+    for candidate in migration::match_candidates_with(&legacy, &target, &config.mapping) {
+        if unlinked.binary_search(&candidate.legacy).is_ok() {
+            by_legacy
+                .entry(candidate.legacy.clone())
+                .or_default()
+                .push(candidate);
+        }
+    }
+    if by_legacy.is_empty() {
+        println!("No unlinked symbols with candidate targets");
+        return Ok(());
+    }
+
+    let stdin = std::io::stdin();
+    let mut input = stdin.lock();
+    let total = by_legacy.len();
+    let mut linked = 0;
+    let mut targets = HashSet::new();
+    for (position, (legacy, candidates)) in by_legacy.iter().enumerate() {
+        // Linked as the target of an earlier symbol, so not legacy after all
+        if targets.contains(legacy) {
+            continue;
+        }
+        let Some(node) = graph.get_by_id(legacy).and_then(|i| graph.node_weight(i)) else {
+            continue;
+        };
+        println!();
+        println!("[{}/{}] {}", position + 1, total, describe(node, root));
+        let shown = &candidates[..candidates.len().min(SHOWN)];
+        for (i, candidate) in shown.iter().enumerate() {
+            let arity = match candidate.arity {
+                Some(true) => ", same arity",
+                Some(false) => ", other arity",
+                None => "",
+            };
+            println!(
+                "  {}. {}  score {:.2} (name {:.2}, path {:.2}{})",
+                i + 1,
+                candidate.target,
+                candidate.score,
+                candidate.name_score,
+                candidate.path_score,
+                arity
+            );
+            if let Some(target) = graph
+                .get_by_id(&candidate.target)
+                .and_then(|i| graph.node_weight(i))
+            {
+                println!("     {}", describe(target, root));
+            }
+        }
+
+        let Some(answer) = prompt(
+            &mut input,
+            &format!("Link to [1-{}], s skip, q quit: ", shown.len()),
+        )?
+        else {
+            break;
+        };
+        let candidate = match answer.as_str() {
+            "q" => break,
+            "" | "s" => continue,
+            choice => match choice.parse::<usize>().ok().and_then(|n| n.checked_sub(1)) {
+                Some(i) if i < shown.len() => &shown[i],
+                _ => {
+                    println!("Skipped: no candidate {}", choice);
+                    continue;
+                }
+            },
+        };
+        if let Err(err) = migration::link(&mut graph, legacy, &candidate.target) {
+            println!("Not linked: {}", err);
+            continue;
+        }
+        linked += 1;
+        targets.insert(candidate.target.clone());
+
+        let status = prompt(
+            &mut input,
+            "Mark legacy m migrated, s superseded, enter to keep its status: ",
+        )?;
+        let status = match status.as_deref() {
+            Some("m") => Some(MigrationStatus::Migrated),
+            Some("s") => Some(MigrationStatus::Superseded),
+            _ => None,
+        };
+        let mut changed = false;
+        if let Some(status) = status {
+            match migration::transition(&mut graph, legacy, status) {
+                Ok(previous) => changed = previous != status,
+                Err(err) => println!("Status unchanged: {}", err),
+            }
+        }
+        store::commit(&state_path, &graph, "link")?;
+        println!("Linked {} -> {}", legacy, candidate.target);
+        if changed {
+            crate::emit_status_event(&emitter, &graph, legacy);
+        }
+    }
+    println!();
+    println!("Recorded {} links", linked);
+    Ok(())
+}
+
+/// `message`, then one trimmed line of input, or `None` at end of input
+fn prompt(input: &mut impl BufRead, message: &str) -> anyhow::Result<Option<String>> {
+    print!("{}", message);
+    std::io::stdout().flush()?;
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line.trim().to_string()))
+}
+
+/// ID, type, status and location of `node` (relative to `root`), with its
+/// first source line
+fn describe(node: &Node, root: &Path) -> String {
+    let mut text = format!("{} ({:?}, {:?})", node.id, node.node_type, node.status);
+    let Some((start, end)) = node.line_range else {
+        return text;
+    };
+    text.push_str(&format!(
+        "\n     {}:{}-{}",
+        node.file_path
+            .strip_prefix(root)
+            .unwrap_or(&node.file_path)
+            .display(),
+        start,
+        end
+    ));
+    let first_line = std::fs::read_to_string(root.join(&node.file_path))
+        .ok()
+        .and_then(|source| {
+            let line = source.lines().nth(start.checked_sub(1)?)?;
+            Some(line.trim().to_string())
+        });
+    if let Some(line) = first_line.filter(|line| !line.is_empty()) {
+        text.push_str(&format!("\n     | {}", line));
+    }
+    text
+}
@@ -0,0 +1,768 @@
+//! `migrator lsp`: the dependency graph inside the editor
+//!
+//! A language server over stdio, backed by the project graph held in memory:
+//!
+//! - **Find references** lists the symbols that depend on the one under the
+//!   cursor (callers, importers, subclasses), not just textual matches.
+//! - **Code lenses** show each symbol's dependent count and migration status;
+//!   the `migrator.dependents` command returns its transitive dependents.
+//! - **Diagnostics** flag symbols that are blocked or in progress, and
+//!   pending code that still depends on migrated or superseded symbols.
+//!
+//! Statuses and links are read from the state file (`[output] graph` in
+//! `migrator.toml`). The project is scanned at startup. A saved file is
+//! reparsed on its own: its symbols are added, removed and moved, and the
+//! edges among them redone, while its edges into other files stay as the
+//! last scan found them; saving a file the graph does not know yet rescans
+//! the project. Either way every file's diagnostics are republished. Between
+//! saves, open documents are held in memory and reparsed incrementally on
+//! each change, so lenses and lookups follow symbols as unsaved edits move
+//! them. Notifications with malformed parameters are logged and skipped.
+
+use graph_migrator_core::annotations;
+use graph_migrator_core::config::Config;
+use graph_migrator_core::parser::{self, Language, ParseSession};
+use graph_migrator_core::paths::PathResolver;
+use graph_migrator_core::queries::{self, Direction};
+use graph_migrator_core::{resolve, store, Graph, MigrationStatus, Node};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+    Notification as _, PublishDiagnostics,
+};
+use lsp_types::request::{CodeLensRequest, ExecuteCommand, References, Request as _};
+use lsp_types::{
+    CodeLens, CodeLensOptions, CodeLensParams, Command, Diagnostic, DiagnosticSeverity,
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    DidSaveTextDocumentParams, ExecuteCommandOptions, ExecuteCommandParams, Location, Position,
+    PublishDiagnosticsParams, Range, ReferenceParams, SaveOptions, ServerCapabilities,
+    TextDocumentContentChangeEvent, TextDocumentSyncCapability, TextDocumentSyncKind,
+    TextDocumentSyncOptions, TextDocumentSyncSaveOptions, Url,
+};
+use petgraph::stable_graph::NodeIndex;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Command behind each code lens; takes a node ID, returns dependent IDs
+const DEPENDENTS_COMMAND: &str = "migrator.dependents";
+
+/// Serve LSP on stdin/stdout until the client shuts the server down
+pub fn run(root: &Path, config: Config) -> anyhow::Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    serve(&connection, root, config)?;
+    // The writer thread exits once every sender is gone
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
+
+/// Answer requests and notifications on `connection` until the client shuts
+/// the server down
+fn serve(connection: &Connection, root: &Path, config: Config) -> anyhow::Result<()> {
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Options(
+            TextDocumentSyncOptions {
+                open_close: Some(true),
+                change: Some(TextDocumentSyncKind::INCREMENTAL),
+                save: Some(TextDocumentSyncSaveOptions::SaveOptions(SaveOptions {
+                    include_text: Some(false),
+                })),
+                ..TextDocumentSyncOptions::default()
+            },
+        )),
+        references_provider: Some(lsp_types::OneOf::Left(true)),
+        code_lens_provider: Some(CodeLensOptions {
+            resolve_provider: Some(false),
+        }),
+        execute_command_provider: Some(ExecuteCommandOptions {
+            commands: vec![DEPENDENTS_COMMAND.to_string()],
+            ..ExecuteCommandOptions::default()
+        }),
+        ..ServerCapabilities::default()
+    };
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+
+    let mut server = Server::new(root, config);
+    server.rescan();
+    server.publish_diagnostics(connection)?;
+
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    break;
+                }
+                let response = server.handle_request(request);
+                connection.sender.send(response.into())?;
+            }
+            Message::Notification(notification) => server.notify(connection, notification)?,
+            Message::Response(_) => {}
+        }
+    }
+    Ok(())
+}
+
+struct Server {
+    root: PathBuf,
+    config: Config,
+    graph: Graph,
+    /// Files that had diagnostics last time, so they can be cleared
+    published: Vec<Url>,
+    /// Source and syntax tree of each open document, by canonical path
+    buffers: ParseSession,
+}
+
+impl Server {
+    fn new(root: &Path, config: Config) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            graph: Graph::new(),
+            published: Vec::new(),
+            buffers: ParseSession::new().with_config(&config),
+            config,
+        }
+    }
+
+    /// Act on a notification; only failing to reply ends the server
+    fn notify(
+        &mut self,
+        connection: &Connection,
+        notification: Notification,
+    ) -> anyhow::Result<()> {
+        match notification.method.as_str() {
+            DidSaveTextDocument::METHOD => {
+                let Some(params) = params::<DidSaveTextDocumentParams>(notification) else {
+                    return Ok(());
+                };
+                let uri = params.text_document.uri;
+                let Some(path) = file_path(&uri, self.config.path_resolver()) else {
+                    return Ok(());
+                };
+                if is_python(&uri) {
+                    if !self.update_file(&path) {
+                        self.rescan();
+                    }
+                    self.publish_diagnostics(connection)?;
+                }
+            }
+            DidOpenTextDocument::METHOD => {
+                if let Some(params) = params::<DidOpenTextDocumentParams>(notification) {
+                    let document = params.text_document;
+                    self.open(&document.uri, document.text);
+                }
+            }
+            DidChangeTextDocument::METHOD => {
+                if let Some(params) = params::<DidChangeTextDocumentParams>(notification) {
+                    self.change(&params.text_document.uri, params.content_changes);
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                if let Some(params) = params::<DidCloseTextDocumentParams>(notification) {
+                    self.close(&params.text_document.uri);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Rebuild the graph from disk and reapply saved statuses and links, then
+    /// source annotations
+    fn rescan(&mut self) {
+        let (output, diagnostics) = resolve::resolve_directory_lenient(&self.root, &self.config);
+        for diagnostic in &diagnostics {
+            tracing::warn!(file = %diagnostic.file.display(), "{}", diagnostic.error);
+        }
+        self.graph = output.graph.graph;
+
+        let state = self.root.join(&self.config.output.graph);
+        let tracked = match self.config.path_resolver().id_root(&self.root) {
+            Ok(Some(id_root)) => store::load_relative(&state, &id_root),
+            _ => store::load(&state),
+        };
+        match tracked {
+            Ok(mut tracked) => {
+                tracked.follow_moves(&self.graph);
+                store::apply(&mut self.graph, &tracked);
+            }
+            Err(err) => tracing::warn!(path = %state.display(), %err, "ignoring state file"),
+        }
+        if self.config.annotations {
+            let found = annotations::scan(&self.graph, &self.root);
+            annotate(&mut self.graph, found);
+        }
+        tracing::info!(nodes = self.graph.node_count(), "graph ready");
+    }
+
+    /// Reparse the saved file `path` on its own and bring its symbols in the
+    /// graph up to date, see the [module docs](self)
+    ///
+    /// Returns `false`, leaving the graph as it was, if it has no symbols of
+    /// `path` yet or the file cannot be parsed.
+    fn update_file(&mut self, path: &Path) -> bool {
+        let existing: Vec<NodeIndex> = self
+            .graph
+            .node_indices()
+            .filter(|&idx| {
+                self.graph
+                    .node_weight(idx)
+                    .is_some_and(|node| node.file_path == path && !node.external)
+            })
+            .collect();
+        let Some(known) = existing
+            .first()
+            .and_then(|&idx| self.graph.node_weight(idx))
+            .cloned()
+        else {
+            return false;
+        };
+        // IDs from a scan of the file alone differ from the project's in
+        // their file part (root, namespace), so only the symbol part is kept
+        let Some(prefix) =
+            symbol_name(&known.id).map(|name| &known.id[..known.id.len() - name.len()])
+        else {
+            return false;
+        };
+        let mut session = ParseSession::uncached().with_config(&self.config);
+        let parsed = match parser::parse_files_in(&mut session, &[path]) {
+            Ok(parsed) => parsed.graph,
+            Err(err) => {
+                tracing::warn!(path = %path.display(), %err, "cannot reparse saved file");
+                return false;
+            }
+        };
+        let fresh: BTreeMap<String, NodeIndex> = parsed
+            .node_indices()
+            .filter_map(|idx| {
+                let name = symbol_name(&parsed.node_weight(idx)?.id)?;
+                Some((format!("{}{}", prefix, name), idx))
+            })
+            .collect();
+
+        for idx in existing {
+            let removed = self
+                .graph
+                .node_weight(idx)
+                .is_some_and(|node| !fresh.contains_key(&node.id));
+            if removed {
+                self.graph.remove_node(idx);
+            }
+        }
+        let mut indices: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+        for (id, &idx) in &fresh {
+            let Some(node) = parsed.node_weight(idx) else {
+                continue;
+            };
+            let index = match self.graph.get_by_id(id) {
+                Some(index) => {
+                    if let Some(current) = self.graph.node_weight_mut(index) {
+                        current.line_range = node.line_range;
+                        current.attributes.extend(node.attributes.clone());
+                    }
+                    index
+                }
+                None => {
+                    let mut node = node.clone();
+                    node.id.clone_from(id);
+                    node.file_path.clone_from(&known.file_path);
+                    node.namespace.clone_from(&known.namespace);
+                    self.graph.add_node(node)
+                }
+            };
+            indices.insert(idx, index);
+        }
+        let in_file: Vec<NodeIndex> = indices.values().copied().collect();
+        let internal: Vec<_> = self
+            .graph
+            .edge_indices()
+            .filter(|&edge| {
+                self.graph
+                    .edge_endpoints_for(edge)
+                    .is_some_and(|(from, to)| in_file.contains(&from) && in_file.contains(&to))
+            })
+            .collect();
+        for edge in internal {
+            self.graph.remove_edge(edge);
+        }
+        for (from, to, edge) in parsed.edge_endpoints() {
+            if let (Some(&from), Some(&to)) = (indices.get(&from), indices.get(&to)) {
+                self.graph.add_edge(from, to, edge.clone());
+            }
+        }
+
+        if self.config.annotations {
+            let mut file = Graph::new();
+            for &idx in &in_file {
+                if let Some(node) = self.graph.node_weight(idx) {
+                    file.add_node(node.clone());
+                }
+            }
+            let found = annotations::scan(&file, &self.root);
+            annotate(&mut self.graph, found);
+        }
+        tracing::info!(path = %path.display(), symbols = in_file.len(), "saved file updated");
+        true
+    }
+
+    /// Start tracking an open document's buffer
+    fn open(&mut self, uri: &Url, text: String) {
+        let Some(path) = self.buffer_path(uri) else {
+            return;
+        };
+        if let Err(err) = self.buffers.add_source(&path, text) {
+            tracing::warn!(path = %path.display(), %err, "cannot parse document");
+            return;
+        }
+        self.follow_buffer(&path);
+    }
+
+    /// Apply a document's edits to its buffer, reparsing incrementally
+    fn change(&mut self, uri: &Url, changes: Vec<TextDocumentContentChangeEvent>) {
+        let Some(path) = self.buffer_path(uri) else {
+            return;
+        };
+        for change in changes {
+            let edited = match change.range {
+                Some(range) => {
+                    let source = match self.buffers.parse(&path) {
+                        Ok(file) => &file.source,
+                        Err(err) => {
+                            tracing::warn!(path = %path.display(), %err, "document is not open");
+                            return;
+                        }
+                    };
+                    let (Some(start), Some(end)) = (
+                        byte_offset(source, range.start),
+                        byte_offset(source, range.end),
+                    ) else {
+                        tracing::warn!(path = %path.display(), ?range, "edit outside document");
+                        return;
+                    };
+                    self.buffers
+                        .edit_source(&path, start..end, &change.text)
+                        .map(|_| ())
+                }
+                None => self.buffers.update_source(&path, change.text).map(|_| ()),
+            };
+            if let Err(err) = edited {
+                tracing::warn!(path = %path.display(), %err, "cannot apply edit");
+                return;
+            }
+        }
+        self.follow_buffer(&path);
+    }
+
+    fn close(&mut self, uri: &Url) {
+        if let Some(path) = self.buffer_path(uri) {
+            self.buffers.remove(&path);
+        }
+    }
+
+    /// Path of a document whose language the scan parses
+    fn buffer_path(&self, uri: &Url) -> Option<PathBuf> {
+        let path = file_path(uri, self.config.path_resolver())?;
+        Language::detect(&path, &self.config.extensions).map(|_| path)
+    }
+
+    /// Move the symbols of an open document to their lines in its buffer, so
+    /// unsaved edits do not leave lenses on the wrong lines until the next
+    /// rescan; symbols added or removed since are picked up on save
+    fn follow_buffer(&mut self, path: &Path) {
+        let outline = match parser::parse_files_in(&mut self.buffers, &[path]) {
+            Ok(outline) => outline.graph,
+            Err(err) => {
+                tracing::warn!(path = %path.display(), %err, "cannot outline document");
+                return;
+            }
+        };
+        let lines: HashMap<&str, (usize, usize)> = outline
+            .node_indices()
+            .filter_map(|idx| {
+                let node = outline.node_weight(idx)?;
+                Some((symbol_name(&node.id)?, node.line_range?))
+            })
+            .collect();
+        let symbols: Vec<NodeIndex> = self.symbols_in(path).map(|(idx, _)| idx).collect();
+        for idx in symbols {
+            let Some(node) = self.graph.node_weight_mut(idx) else {
+                continue;
+            };
+            if let Some(range) = symbol_name(&node.id).and_then(|name| lines.get(name)) {
+                node.line_range = Some(*range);
+            }
+        }
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        let id = request.id.clone();
+        let result = match request.method.as_str() {
+            References::METHOD => parse(request).map(|p| json(self.references(p))),
+            CodeLensRequest::METHOD => parse(request).map(|p| json(self.code_lenses(p))),
+            ExecuteCommand::METHOD => parse(request).and_then(|p| self.execute_command(p)),
+            method => {
+                return Response::new_err(
+                    id,
+                    ErrorCode::MethodNotFound as i32,
+                    format!("unsupported request: {}", method),
+                )
+            }
+        };
+        match result {
+            Ok(value) => Response::new_ok(id, value),
+            Err(message) => Response::new_err(id, ErrorCode::InvalidParams as i32, message),
+        }
+    }
+
+    /// Symbols with a dependency edge to the symbol under the cursor
+    fn references(&self, params: ReferenceParams) -> Vec<Location> {
+        let position = params.text_document_position;
+        let Some(idx) = self.symbol_at(&position.text_document.uri, position.position) else {
+            return Vec::new();
+        };
+
+        let mut locations: Vec<Location> = self
+            .dependents(idx)
+            .into_iter()
+            .filter_map(|dependent| self.location(dependent))
+            .collect();
+        if params.context.include_declaration {
+            locations.extend(self.location(idx));
+        }
+        locations
+    }
+
+    fn code_lenses(&self, params: CodeLensParams) -> Vec<CodeLens> {
+        let Some(path) = file_path(&params.text_document.uri, self.config.path_resolver()) else {
+            return Vec::new();
+        };
+        self.symbols_in(&path)
+            .map(|(idx, node)| {
+                let title = format!(
+                    "{} dependents · {:?}",
+                    self.dependents(idx).len(),
+                    node.status
+                );
+                CodeLens {
+                    range: line_range(node),
+                    command: Some(Command {
+                        title,
+                        command: DEPENDENTS_COMMAND.to_string(),
+                        arguments: Some(vec![serde_json::Value::String(node.id.clone())]),
+                    }),
+                    data: None,
+                }
+            })
+            .collect()
+    }
+
+    fn execute_command(&self, params: ExecuteCommandParams) -> Result<serde_json::Value, String> {
+        if params.command != DEPENDENTS_COMMAND {
+            return Err(format!("unknown command: {}", params.command));
+        }
+        let id = params
+            .arguments
+            .first()
+            .and_then(|v| v.as_str())
+            .ok_or("expected a node ID argument")?;
+        let dependents = queries::closure(&self.graph, id, Direction::Incoming, &[])
+            .map_err(|e| e.to_string())?;
+        Ok(json(dependents))
+    }
+
+    /// Send diagnostics for every file, clearing files that no longer have any
+    fn publish_diagnostics(&mut self, connection: &Connection) -> anyhow::Result<()> {
+        let mut by_file: BTreeMap<Url, Vec<Diagnostic>> = self
+            .published
+            .drain(..)
+            .map(|uri| (uri, Vec::new()))
+            .collect();
+        for idx in self.graph.node_indices() {
+            let Some(node) = self.graph.node_weight(idx) else {
+                continue;
+            };
+            let Ok(uri) = Url::from_file_path(&node.file_path) else {
+                continue;
+            };
+            let diagnostics = self.diagnostics_for(idx, node);
+            if !diagnostics.is_empty() {
+                by_file.entry(uri).or_default().extend(diagnostics);
+            }
+        }
+
+        for (uri, diagnostics) in by_file {
+            if !diagnostics.is_empty() {
+                self.published.push(uri.clone());
+            }
+            let params = PublishDiagnosticsParams {
+                uri,
+                diagnostics,
+                version: None,
+            };
+            connection
+                .sender
+                .send(Message::Notification(Notification::new(
+                    PublishDiagnostics::METHOD.to_string(),
+                    params,
+                )))?;
+        }
+        Ok(())
+    }
+
+    fn diagnostics_for(&self, idx: NodeIndex, node: &Node) -> Vec<Diagnostic> {
+        let range = line_range(node);
+        let mut diagnostics = Vec::new();
+
+        let severity = match node.status {
+            MigrationStatus::Pending => None,
+            MigrationStatus::Blocked => Some(DiagnosticSeverity::WARNING),
+            MigrationStatus::InProgress => Some(DiagnosticSeverity::INFORMATION),
+            MigrationStatus::Migrated | MigrationStatus::Superseded => {
+                Some(DiagnosticSeverity::HINT)
+            }
+        };
+        if let Some(severity) = severity {
+            diagnostics.push(diagnostic(
+                range,
+                severity,
+                format!("{}: migration status {:?}", node.name, node.status),
+            ));
+        }
+
+        if node.status.is_pending() {
+            for target in self.graph.neighbors(idx, Direction::Outgoing) {
+                let Some(dependency) = self.graph.node_weight(target) else {
+                    continue;
+                };
+                if matches!(
+                    dependency.status,
+                    MigrationStatus::Migrated | MigrationStatus::Superseded
+                ) {
+                    diagnostics.push(diagnostic(
+                        range,
+                        DiagnosticSeverity::WARNING,
+                        format!(
+                            "{} depends on {} ({:?})",
+                            node.name, dependency.id, dependency.status
+                        ),
+                    ));
+                }
+            }
+        }
+        diagnostics
+    }
+
+    /// Direct dependents: sources of incoming dependency edges, deduplicated
+    fn dependents(&self, idx: NodeIndex) -> Vec<NodeIndex> {
+        let mut dependents: Vec<NodeIndex> = self
+            .graph
+            .edges_of(idx)
+            .filter(|(_, from, to, edge)| {
+                *to == idx && *from != idx && edge.edge_type.is_dependency()
+            })
+            .map(|(_, from, _, _)| from)
+            .collect();
+        dependents.sort();
+        dependents.dedup();
+        dependents
+    }
+
+    /// Innermost symbol in `uri` whose line range contains `position`
+    fn symbol_at(&self, uri: &Url, position: Position) -> Option<NodeIndex> {
+        let path = file_path(uri, self.config.path_resolver())?;
+        let line = position.line as usize + 1;
+        self.symbols_in(&path)
+            .filter_map(|(idx, node)| {
+                let (start, end) = node.line_range?;
+                (start..=end).contains(&line).then_some((end - start, idx))
+            })
+            .min()
+            .map(|(_, idx)| idx)
+    }
+
+    fn symbols_in<'a>(&'a self, path: &'a Path) -> impl Iterator<Item = (NodeIndex, &'a Node)> {
+        self.graph.node_indices().filter_map(move |idx| {
+            let node = self.graph.node_weight(idx)?;
+            (node.file_path == path && node.line_range.is_some()).then_some((idx, node))
+        })
+    }
+
+    fn location(&self, idx: NodeIndex) -> Option<Location> {
+        let node = self.graph.node_weight(idx)?;
+        let uri = Url::from_file_path(&node.file_path).ok()?;
+        Some(Location::new(uri, line_range(node)))
+    }
+}
+
+fn parse<P: serde::de::DeserializeOwned>(request: Request) -> Result<P, String> {
+    serde_json::from_value(request.params).map_err(|e| e.to_string())
+}
+
+/// Parameters of `notification`, or `None` (logged) if they are malformed
+fn params<P: serde::de::DeserializeOwned>(notification: Notification) -> Option<P> {
+    match serde_json::from_value(notification.params) {
+        Ok(params) => Some(params),
+        Err(err) => {
+            tracing::warn!(method = %notification.method, %err, "ignoring malformed notification");
+            None
+        }
+    }
+}
+
+/// Apply source annotations `found` by [`annotations::scan()`], logging
+/// their warnings
+fn annotate(
+    graph: &mut Graph,
+    found: (Vec<annotations::Annotation>, Vec<parser::ParseDiagnostic>),
+) {
+    let (found, mut diagnostics) = found;
+    match annotations::apply(graph, &found, None) {
+        Ok((_, warnings)) => diagnostics.extend(warnings),
+        Err(err) => tracing::warn!(%err, "ignoring source annotations"),
+    }
+    for diagnostic in &diagnostics {
+        tracing::warn!(file = %diagnostic.file.display(), "{}", diagnostic.error);
+    }
+}
+
+fn json<T: serde::Serialize>(value: T) -> serde_json::Value {
+    serde_json::to_value(value).unwrap_or(serde_json::Value::Null)
+}
+
+fn diagnostic(range: Range, severity: DiagnosticSeverity, message: String) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(severity),
+        source: Some("migrator".to_string()),
+        message,
+        ..Diagnostic::default()
+    }
+}
+
+/// First line of a symbol's definition (LSP lines are 0-based)
+fn line_range(node: &Node) -> Range {
+    let line = node
+        .line_range
+        .map_or(0, |(start, _)| start.saturating_sub(1)) as u32;
+    Range::new(Position::new(line, 0), Position::new(line + 1, 0))
+}
+
+/// Path of a `file://` URI as `paths` resolves it, matching node file paths
+fn file_path(uri: &Url, paths: PathResolver) -> Option<PathBuf> {
+    let path = uri.to_file_path().ok()?;
+    Some(paths.resolve(&path).unwrap_or(path))
+}
+
+/// Byte offset of an LSP position (UTF-16 columns) in `source`; columns
+/// past the end of a line mean its end
+fn byte_offset(source: &str, position: Position) -> Option<usize> {
+    let line_start = match position.line {
+        0 => 0,
+        line => source.match_indices('\n').nth(line as usize - 1)?.0 + 1,
+    };
+    let line = source[line_start..].split('\n').next().unwrap_or_default();
+    let mut units = 0;
+    for (offset, ch) in line.char_indices() {
+        if units >= position.character as usize {
+            return Some(line_start + offset);
+        }
+        units += ch.len_utf16();
+    }
+    Some(line_start + line.len())
+}
+
+/// The part of a node ID after the file, e.g. `Cart.total`
+fn symbol_name(id: &str) -> Option<&str> {
+    id.rsplit_once("::").map(|(_, name)| name)
+}
+
+fn is_python(uri: &Url) -> bool {
+    uri.path().ends_with(".py")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_server::RequestId;
+
+    /// A client talking to [`serve()`] over an in-memory connection
+    struct Client {
+        connection: Connection,
+        next_id: i32,
+    }
+
+    impl Client {
+        fn request(&mut self, method: &str, params: serde_json::Value) -> Response {
+            self.next_id += 1;
+            let id = RequestId::from(self.next_id);
+            let request = Request::new(id.clone(), method.to_string(), params);
+            self.connection.sender.send(request.into()).unwrap();
+            // Diagnostics may arrive first
+            loop {
+                match self.connection.receiver.recv().unwrap() {
+                    Message::Response(response) if response.id == id => return response,
+                    _ => continue,
+                }
+            }
+        }
+
+        fn notify(&self, method: &str, params: serde_json::Value) {
+            let notification = Notification::new(method.to_string(), params);
+            self.connection.sender.send(notification.into()).unwrap();
+        }
+
+        fn lens_ids(&mut self, uri: &Url) -> Vec<String> {
+            let response = self.request(
+                CodeLensRequest::METHOD,
+                serde_json::json!({ "textDocument": { "uri": uri } }),
+            );
+            let lenses: Vec<CodeLens> = serde_json::from_value(response.result.unwrap()).unwrap();
+            let mut ids: Vec<String> = lenses
+                .into_iter()
+                .filter_map(|lens| Some(lens.command?.arguments?[0].as_str()?.to_string()))
+                .collect();
+            ids.sort();
+            ids
+        }
+    }
+
+    #[test]
+    fn test_server_survives_bad_notifications_and_updates_saved_files() {
+        let project = tempfile::tempdir().unwrap();
+        let root = project.path().to_path_buf();
+        let app = root.join("app.py");
+        std::fs::write(
+            &app,
+            "def main():\n    helper()\n\ndef helper():\n    pass\n",
+        )
+        .unwrap();
+        let uri = Url::from_file_path(&app).unwrap();
+
+        let (connection, server) = Connection::memory();
+        let thread = std::thread::spawn(move || serve(&server, &root, Config::default()));
+        let mut client = Client {
+            connection,
+            next_id: 0,
+        };
+        client.request("initialize", serde_json::json!({ "capabilities": {} }));
+        client.notify("initialized", serde_json::json!({}));
+        assert_eq!(client.lens_ids(&uri), ["app.py::helper", "app.py::main"]);
+
+        // Malformed parameters are skipped, not fatal
+        client.notify(
+            DidOpenTextDocument::METHOD,
+            serde_json::json!({ "bogus": 1 }),
+        );
+        client.notify(DidSaveTextDocument::METHOD, serde_json::json!(null));
+
+        std::fs::write(&app, "def main():\n    run()\n\ndef run():\n    pass\n").unwrap();
+        client.notify(
+            DidSaveTextDocument::METHOD,
+            serde_json::json!({ "textDocument": { "uri": uri } }),
+        );
+        assert_eq!(client.lens_ids(&uri), ["app.py::main", "app.py::run"]);
+
+        client.request("shutdown", serde_json::Value::Null);
+        client.notify("exit", serde_json::Value::Null);
+        thread.join().unwrap().unwrap();
+    }
+}
@@ -1,4 +1,46 @@
-use clap::Parser;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod link;
+mod lsp;
+mod progress;
+mod serve;
+mod tui;
+
+use anyhow::Context;
+use clap::{ArgAction, CommandFactory, Parser, Subcommand, ValueEnum};
+use graph_migrator_core::annotations::{self, ApplySummary};
+use graph_migrator_core::cache::{self, ParseCache};
+use graph_migrator_core::config::Config;
+use graph_migrator_core::deps;
+use graph_migrator_core::enrich;
+use graph_migrator_core::events::{Emitter, StatusEvent};
+use graph_migrator_core::export::report::{self, ReportOptions};
+use graph_migrator_core::export::{jsonl, sarif};
+use graph_migrator_core::filter::Filter;
+use graph_migrator_core::graph::{Edge, EdgeType};
+use graph_migrator_core::impact;
+use graph_migrator_core::import::{self, FirstPassOutput, ImportStatement};
+use graph_migrator_core::journal;
+use graph_migrator_core::migration;
+use graph_migrator_core::parser::{
+    self, debug, GranularityLevel, Language, MultiFileGraph, ParseDiagnostic, ParseSession,
+    Severity,
+};
+use graph_migrator_core::progress::{self as migration_progress, BurndownScope, ProgressSnapshot};
+use graph_migrator_core::queries::Direction;
+use graph_migrator_core::queries::{self, NodeMetrics, PathOptions, RollupLevel};
+use graph_migrator_core::resolve::{self, ReferenceKind};
+use graph_migrator_core::rules;
+use graph_migrator_core::schema;
+use graph_migrator_core::testmap;
+use graph_migrator_core::{
+    remote, store, Centrality, Graph, GraphData, MemoryStats, MigrationStatus, Node,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 /// GraphMigrator - Visual task-tracking system for code migration
 #[derive(Parser)]
@@ -6,14 +48,3299 @@ use clap::Parser;
 #[command(author = "Henry Du")]
 #[command(version)] // Auto-pull version from Cargo.toml
 #[command(about = "Transform codebases into queryable dependency graphs", long_about = None)]
-struct Cli;
+#[command(arg_required_else_help = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+    /// Write man pages for migrator and each subcommand into DIR, then exit
+    #[arg(long, value_name = "DIR", exclusive = true)]
+    generate_man: Option<PathBuf>,
+    /// Log progress details instead of a progress bar (-v info, -vv debug, -vvv trace)
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    verbose: u8,
+    /// Only report errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Only consider nodes matching EXPR, e.g. 'type=Function AND file~"legacy/.*"'.
+    /// Commands that save state (link, rename, suggest-units, suggest-links, tui, undo,
+    /// snapshot, check --update-baseline) reject it; set-status changes every matching symbol
+    #[arg(long, global = true, value_name = "EXPR")]
+    filter: Option<Filter>,
+    /// Only consider edges matching EXPR, e.g. 'attr.weight > 2'. Edges only have
+    /// `attr.<name>` fields; commands that reject --filter reject this too
+    #[arg(long, global = true, value_name = "EXPR")]
+    edge_filter: Option<Filter>,
+}
+
+/// The global `--filter`, set once in [`main()`]
+static FILTER: OnceLock<Filter> = OnceLock::new();
+
+/// The global `--edge-filter`, set once in [`main()`]
+static EDGE_FILTER: OnceLock<Filter> = OnceLock::new();
+
+/// URL `scan --git` checked the project out from, set once in [`checkout_remote()`]
+static REMOTE: OnceLock<String> = OnceLock::new();
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Parse a project and print a summary of its dependency graph
+    Scan {
+        /// Project root to scan
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Config file (defaults to <path>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// Abort on the first file that fails to parse
+        #[arg(long)]
+        strict: bool,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ScanFormat::Summary)]
+        format: ScanFormat,
+        /// With --format jsonl, write each file's records as it is parsed, so memory
+        /// stays bounded by the largest file; only edges within a file are written
+        #[arg(long)]
+        stream: bool,
+        /// How much to extract; `imports` is much faster on large repositories
+        #[arg(long, value_enum, default_value_t = ScanLevel::Symbols)]
+        level: ScanLevel,
+        /// How deep symbols are extracted (overrides `granularity` in migrator.toml)
+        #[arg(long, value_enum)]
+        granularity: Option<GranularityArg>,
+        /// Write output to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Annotate nodes with git blame (last change, top authors); json, jsonl and parquet only
+        #[arg(long)]
+        blame: bool,
+        /// Annotate nodes with recent commit counts from git log; json, jsonl and parquet only
+        #[arg(long)]
+        churn: bool,
+        /// Record when and where the scan ran (time, absolute root, git commit) in the
+        /// graph metadata, so two scans of the same tree differ; all formats but summary
+        #[arg(long)]
+        provenance: bool,
+        /// Scan this git repository instead of PATH, from a shallow clone; IDs are
+        /// relative to its root and the metadata records the remote and commit
+        #[arg(long, value_name = "URL")]
+        git: Option<String>,
+        /// Commit, branch or tag to scan with --git (defaults to the remote's default branch)
+        #[arg(long, requires = "git")]
+        rev: Option<String>,
+        /// Keep the --git checkout under DIR and update it on later scans instead of
+        /// cloning into a temporary directory
+        #[arg(long, requires = "git", value_name = "DIR")]
+        git_cache: Option<PathBuf>,
+    },
+    /// Print graph statistics: fan-in/fan-out and centrality per symbol, density, longest chain
+    Stats {
+        /// Project root to scan
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Config file (defaults to <path>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// Column to sort the symbol table by (descending for counts)
+        #[arg(long, value_enum, default_value_t = StatsSort::FanIn)]
+        sort: StatsSort,
+        /// Maximum number of symbols to list
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+        /// Add each symbol's age from git blame (implied by --sort age)
+        #[arg(long)]
+        blame: bool,
+        /// Add each symbol's recent commit count from git log (implied by --sort churn)
+        #[arg(long)]
+        churn: bool,
+        /// Add each symbol's estimated migration effort (implied by --sort effort)
+        #[arg(long)]
+        effort: bool,
+        /// Summarize progress per group instead of listing symbols
+        #[arg(long, value_enum, conflicts_with_all = ["sort", "blame", "churn", "effort"])]
+        by: Option<StatsBy>,
+        /// Estimate the graph's memory use, and the compact copy's if built
+        /// with `compact`, instead of listing symbols; scanning still peaks
+        /// at the full graph
+        #[arg(long, conflicts_with_all = ["sort", "limit", "blame", "churn", "effort", "by"])]
+        memory: bool,
+    },
+    /// Show how one symbol depends on another
+    Path {
+        /// ID of the dependent symbol (e.g. app.py::main)
+        from: String,
+        /// ID of the symbol depended upon
+        to: String,
+        /// Project root to scan
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+        /// Config file (defaults to <root>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// List every simple path instead of one shortest path
+        #[arg(long)]
+        all: bool,
+        /// Maximum number of edges per path
+        #[arg(long)]
+        max_length: Option<usize>,
+        /// Only follow these edge types (repeatable; defaults to calls, imports, inherits)
+        #[arg(long = "edge-type", value_name = "TYPE")]
+        edge_types: Vec<EdgeType>,
+    },
+    /// Find symbols by name or ID: exact, prefix, substring, glob or fuzzy match
+    Find {
+        /// What to look for (e.g. `invoice`, `Inv*Builder`, `invbld`)
+        pattern: String,
+        /// Project root to scan
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+        /// Config file (defaults to <root>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// Maximum number of matches to list
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Collapse symbols into directories or modules and show how they depend on each other
+    Rollup {
+        /// Project root to scan
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Config file (defaults to <path>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// What to group symbols by
+        #[arg(long, value_enum, default_value_t = RollupArg::Directory)]
+        level: RollupArg,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = RollupFormat::Text)]
+        format: RollupFormat,
+    },
+    /// Propose cohesive clusters of symbols as migration units
+    SuggestUnits {
+        /// Project root to scan
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Config file (defaults to <path>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// Maximum number of suggestions to list
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+        /// Create a MigrationUnit from the suggestion ranked N (repeatable)
+        #[arg(long, value_name = "N")]
+        accept: Vec<usize>,
+    },
+    /// Propose MigratedTo links from the `[[mapping]]` rules of the config
+    SuggestLinks {
+        /// Project root to scan
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Config file (defaults to <path>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// Record every proposed link in the state file
+        #[arg(long)]
+        accept: bool,
+    },
+    /// Record today's status counts, per unit and owner, in the progress history
+    Snapshot {
+        /// Project root to scan
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Config file (defaults to <path>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// History file to append to; defaults to `[output] history`
+        #[arg(long)]
+        history: Option<PathBuf>,
+    },
+    /// Show migration progress over time from the progress history
+    Burndown {
+        /// Project root whose history to read
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Config file (defaults to <path>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// History file to read; defaults to `[output] history`
+        #[arg(long)]
+        history: Option<PathBuf>,
+        /// Only follow the members of this migration unit (by ID)
+        #[arg(long, conflicts_with = "owner")]
+        unit: Option<String>,
+        /// Only follow the symbols of this owner
+        #[arg(long)]
+        owner: Option<String>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = BurndownFormat::Text)]
+        format: BurndownFormat,
+    },
+    /// Create or update one GitHub or Jira issue per migration unit (`[integrations]`)
+    #[cfg(feature = "integrations")]
+    SyncIssues {
+        /// Project root whose state file to update
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Config file (defaults to <path>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// Tracker to sync to; required if both are configured
+        #[arg(long, value_enum)]
+        tracker: Option<TrackerArg>,
+        /// Print the issues instead of sending them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Write a Markdown status report: progress, blockers, cycles, recent work, units
+    Report {
+        /// Project root to scan
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Config file (defaults to <path>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// File to write; defaults to `[output] report`, else stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Maximum rows in the blocker, cycle and recently migrated sections
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+        /// How many days back counts as recently migrated
+        #[arg(long, default_value_t = 7)]
+        recent_days: u64,
+    },
+    /// List functions and classes not reachable from the given entry points
+    Unreachable {
+        /// Project root to scan
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Config file (defaults to <path>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// Entry point symbol ID (repeatable)
+        #[arg(
+            long = "entry",
+            value_name = "ID",
+            required_unless_present = "entry_files"
+        )]
+        entries: Vec<String>,
+        /// Treat every symbol defined in this file as an entry point (repeatable)
+        #[arg(long = "entry-file", value_name = "FILE")]
+        entry_files: Vec<PathBuf>,
+    },
+    /// Report calls and imports that could not be resolved to project symbols
+    Unresolved {
+        /// Project root to scan
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Config file (defaults to <path>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// List every unresolved reference and shadowed name instead of per-file counts
+        #[arg(long)]
+        list: bool,
+    },
+    /// List external packages the project uses, or the symbols using given packages
+    Deps {
+        /// Project root to scan
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Config file (defaults to <path>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// List symbols that import this package (repeatable)
+        #[arg(long = "package", value_name = "NAME")]
+        packages: Vec<String>,
+    },
+    /// Run a language server on stdio exposing dependents and migration status
+    Lsp {
+        /// Project root to serve
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Config file (defaults to <path>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+    /// Serve a read-only JSON API over the graph for dashboards
+    Serve {
+        /// Project root to serve
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Config file (defaults to <path>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// Port to listen on
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+        /// Address to bind
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+        /// Serve the gRPC API of proto/migrator.proto instead of JSON over HTTP
+        #[cfg(feature = "grpc")]
+        #[arg(long)]
+        grpc: bool,
+    },
+    /// Browse the graph and mark symbols migrated or blocked in the terminal
+    Tui {
+        /// Project root to browse
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Config file (defaults to <path>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+    /// Print a content hash of the dependency graph, for CI change detection
+    Fingerprint {
+        /// Project root to scan
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Config file (defaults to <path>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+    /// Show what a saved graph represents: scan time, root, commit, languages, config
+    Info {
+        /// Graph JSON file, or a project root to read the state file of
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Config file (defaults to <path>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+    /// Inspect or empty the parse cache under `cache_dir`
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Rewrite absolute node IDs in a JSONL export relative to a project root
+    RewriteIds {
+        /// JSONL export to convert
+        input: PathBuf,
+        /// Absolute project root the export was made from
+        #[arg(long)]
+        root: PathBuf,
+        /// Write output to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Rename a symbol, or every symbol of a moved file, in the state file
+    Rename {
+        /// Current ID (or file, with --file)
+        old: String,
+        /// New ID (or file, with --file)
+        new: String,
+        /// Project root whose state file to update
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+        /// Config file (defaults to <root>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// Treat OLD and NEW as file paths as they appear in IDs (e.g. src/utils.py)
+        #[arg(long)]
+        file: bool,
+    },
+    /// Change a symbol's migration status, enforcing the status lifecycle
+    ///
+    /// With --filter, changes every matching symbol, or none if one of them cannot make the change.
+    SetStatus {
+        /// Node ID
+        #[arg(required_unless_present = "filter", conflicts_with = "filter")]
+        id: Option<String>,
+        /// New status: pending, in-progress, blocked, migrated or superseded
+        #[arg(required_unless_present = "status_flag")]
+        status: Option<MigrationStatus>,
+        /// New status, as an option (`--filter EXPR --status migrated`)
+        #[arg(
+            long = "status",
+            id = "status_flag",
+            value_name = "STATUS",
+            conflicts_with = "status"
+        )]
+        status_flag: Option<MigrationStatus>,
+        /// List the changes --filter selects without saving them
+        #[arg(long, conflicts_with = "id")]
+        dry_run: bool,
+        /// Project root whose state file to update
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+        /// Config file (defaults to <root>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+    /// Revert the latest change to the state file that has not been undone yet
+    ///
+    /// Every command that changes statuses, links or units records what it changed in a journal
+    /// next to the state file; undo applies the inverse of the latest entry and records that too.
+    Undo {
+        /// Project root whose state file to update
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+        /// Config file (defaults to <root>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+    /// List the journaled changes involving a node, oldest first
+    History {
+        /// Node ID
+        id: String,
+        /// Project root whose state file to read
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+        /// Config file (defaults to <root>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+    /// List pending symbols whose dependencies are all migrated, i.e. ready to migrate next
+    Frontier {
+        /// Project root to scan
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Config file (defaults to <path>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// Coverage report (Cobertura XML or lcov); defaults to `[enrich] coverage`
+        #[arg(long)]
+        coverage: Option<PathBuf>,
+        /// Only list symbols with at most this line coverage (0 for untested)
+        #[arg(long, value_name = "PERCENT")]
+        max_coverage: Option<f64>,
+        /// Estimate each symbol's migration effort and show it; the list is
+        /// always cheapest first, going by saved estimates otherwise
+        #[arg(long)]
+        effort: bool,
+    },
+    /// Check the policies in `[rules]`; exits with an error if any are violated
+    Check {
+        /// Project root to scan
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Config file (defaults to <path>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: CheckFormat,
+        /// Write output to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Only report (and fail on) violations missing from the baseline (`[rules] baseline`)
+        #[arg(long)]
+        ratchet: bool,
+        /// Accept every current violation by writing it to the baseline, then exit
+        #[arg(long, conflicts_with = "ratchet")]
+        update_baseline: bool,
+        /// Only report violations involving files changed since this git revision, or their dependents
+        #[arg(long, value_name = "REV", conflicts_with = "update_baseline")]
+        diff_base: Option<String>,
+        /// Like --diff-base, with changed files listed one per line in FILE (`-` for stdin)
+        #[arg(
+            long,
+            value_name = "FILE",
+            conflicts_with_all = ["diff_base", "update_baseline"]
+        )]
+        changed_files: Option<PathBuf>,
+    },
+    /// Ask targeted questions about one symbol
+    Query {
+        #[command(subcommand)]
+        query: Query,
+    },
+    /// List superseded symbols that code still depends on
+    Incomplete {
+        /// Project root to scan
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Config file (defaults to <path>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+    /// Record that a legacy symbol was migrated to a target symbol
+    Link {
+        /// ID of the legacy symbol
+        #[arg(required_unless_present = "interactive")]
+        legacy: Option<String>,
+        /// ID of the symbol that replaces it
+        #[arg(required_unless_present = "interactive")]
+        target: Option<String>,
+        /// Project root whose state file to update
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+        /// Config file (defaults to <root>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// Remove the link instead of adding it
+        #[arg(long)]
+        remove: bool,
+        /// Walk through unlinked legacy symbols, picking targets from ranked candidates
+        #[arg(
+            short,
+            long,
+            conflicts_with_all = ["legacy", "target", "remove"]
+        )]
+        interactive: bool,
+        /// With --interactive, the legacy symbols to link (defaults to the files
+        /// matching a [[mapping]] `from` pattern)
+        #[arg(long, value_name = "EXPR", requires_all = ["interactive", "to"])]
+        from: Option<Filter>,
+        /// With --interactive, the symbols to link them to (defaults to the files
+        /// matching a [[mapping]] `to` pattern)
+        #[arg(long, value_name = "EXPR", requires_all = ["interactive", "from"])]
+        to: Option<Filter>,
+    },
+    /// Report which legacy symbols have been linked to their replacements
+    Links {
+        /// Project root to scan
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Config file (defaults to <path>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// Only list symbols without a link
+        #[arg(long)]
+        unlinked: bool,
+    },
+    /// Combine graphs exported with `scan --format json`, e.g. one per repository
+    Merge {
+        /// Graph files to combine; on ID collisions the earliest file wins
+        #[arg(required = true, num_args = 2..)]
+        inputs: Vec<PathBuf>,
+        /// Write output to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Run pass 1 on one file or snippet and print its nodes, edges and imports as JSON
+    Parse {
+        /// File to parse
+        #[arg(required_unless_present = "stdin")]
+        file: Option<PathBuf>,
+        /// Read the source from stdin instead of a file
+        #[arg(long, conflicts_with = "file")]
+        stdin: bool,
+        /// Language of the source (defaults to the file's, or Python)
+        #[arg(long, value_enum)]
+        lang: Option<LangArg>,
+        /// Path the source goes by in node IDs (defaults to FILE, or `<stdin>.py` and the like)
+        #[arg(long, value_name = "PATH")]
+        name: Option<PathBuf>,
+        /// Write output to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Show how the parsers see a file, for bug reports
+    Debug {
+        #[command(subcommand)]
+        action: DebugAction,
+    },
+    /// Print the JSON Schema for `scan --format json` output and the state file
+    Schema,
+    /// Print a shell completion script to stdout
+    Completions {
+        /// Shell to generate completions for
+        shell: clap_complete::Shell,
+    },
+}
+
+/// Actions of `migrator cache`
+#[derive(Subcommand)]
+enum CacheAction {
+    /// Show where the parse cache is and how large it is
+    Stats {
+        /// Project root
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Config file (defaults to <path>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+    /// Delete every cached parse result
+    Clear {
+        /// Project root
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Config file (defaults to <path>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+}
+
+/// Actions of `migrator debug`
+#[derive(Subcommand)]
+enum DebugAction {
+    /// Print the tree-sitter syntax tree of a file
+    Ast {
+        /// File to parse
+        file: PathBuf,
+        /// Language of the file (defaults to the one its extension maps to, or Python)
+        #[arg(long, value_enum)]
+        lang: Option<LangArg>,
+    },
+    /// List every definition in a file and whether it was extracted, or why not
+    Symbols {
+        /// File to parse
+        file: PathBuf,
+        /// Language of the file (defaults to the one its extension maps to, or Python)
+        #[arg(long, value_enum)]
+        lang: Option<LangArg>,
+        /// Extraction depth to explain (defaults to the standard extraction)
+        #[arg(long, value_enum)]
+        granularity: Option<GranularityArg>,
+    },
+}
+
+/// Questions answered by `migrator query`
+#[derive(Subcommand)]
+enum Query {
+    /// Tests that call a symbol, directly or through its callers
+    TestsFor {
+        /// Node ID of the symbol
+        id: String,
+        /// Project root to scan
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+        /// Config file (defaults to <root>/migrator.toml if present)
+        #[arg(long)]
+        config: Option<PathBuf>,
+    },
+    /// A symbol's location, status and edges, read from a snapshot without loading it
+    #[cfg(feature = "snapshot")]
+    Node {
+        /// Node ID of the symbol
+        id: String,
+        /// Snapshot written by `scan --format snapshot`
+        #[arg(long)]
+        snapshot: PathBuf,
+    },
+}
+
+/// One symbol in the `migrator stats` table
+struct StatsRow<'a> {
+    metrics: &'a NodeMetrics,
+    centrality: Centrality,
+    /// Days since the last change, if blamed
+    age_in_days: Option<u64>,
+    /// Commits in the churn window, if computed
+    churn: Option<u32>,
+    /// Effort score, if estimated
+    effort: Option<f64>,
+}
+
+/// Optional columns of the `migrator stats` table, each computed for it
+#[derive(Debug, Clone, Copy)]
+struct StatsColumns {
+    /// Days since the last change, from git blame
+    blame: bool,
+    /// Commits in the churn window, from git log
+    churn: bool,
+    /// Estimated migration effort
+    effort: bool,
+}
+
+/// Sort keys for `migrator stats`
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum StatsSort {
+    FanIn,
+    FanOut,
+    Pagerank,
+    Betweenness,
+    Name,
+    File,
+    /// Least recently changed first, from git blame
+    Age,
+    /// Most commits in the churn window first (`[enrich] churn_days`)
+    Churn,
+    /// Highest estimated migration effort first
+    Effort,
+}
+
+/// Groupings for `migrator stats --by`
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum StatsBy {
+    /// Owning team, from CODEOWNERS and `[enrich.owners]`
+    Owner,
+}
+
+/// Output formats for `migrator scan`
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ScanFormat {
+    /// Human-readable node/edge counts
+    Summary,
+    /// The whole graph as one JSON document (`GraphData`)
+    Json,
+    /// The json graph as JSON Lines: metadata, then one node or edge per line; see --stream
+    Jsonl,
+    /// `nodes.parquet` and `edges.parquet` in the --output directory
+    #[cfg(feature = "parquet")]
+    Parquet,
+    /// Memory-mappable binary snapshot in the --output file, with tracked statuses, for `query node --snapshot`
+    #[cfg(feature = "snapshot")]
+    Snapshot,
+}
+
+/// Extraction levels for `migrator scan`
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ScanLevel {
+    /// Functions and classes with their calls, imports and references
+    Symbols,
+    /// Only one node per file, linked by the modules it imports
+    Imports,
+}
+
+/// Symbol extraction depths for `migrator scan --granularity`
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum GranularityArg {
+    /// One node per file
+    Files,
+    /// Files and top-level classes
+    Classes,
+    /// Files, top-level classes and functions
+    Functions,
+    /// Also methods of top-level classes
+    Methods,
+    /// Also calls and type references
+    Calls,
+}
+
+impl From<GranularityArg> for GranularityLevel {
+    fn from(level: GranularityArg) -> Self {
+        match level {
+            GranularityArg::Files => GranularityLevel::Files,
+            GranularityArg::Classes => GranularityLevel::Classes,
+            GranularityArg::Functions => GranularityLevel::Functions,
+            GranularityArg::Methods => GranularityLevel::Methods,
+            GranularityArg::Calls => GranularityLevel::Calls,
+        }
+    }
+}
+
+/// Source languages for `migrator parse --lang`
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum LangArg {
+    Python,
+    Rust,
+    #[value(alias = "ts")]
+    Typescript,
+}
+
+impl From<LangArg> for Language {
+    fn from(lang: LangArg) -> Self {
+        match lang {
+            LangArg::Python => Language::Python,
+            LangArg::Rust => Language::Rust,
+            LangArg::Typescript => Language::TypeScript,
+        }
+    }
+}
+
+/// Grouping levels for `migrator rollup`
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum RollupArg {
+    /// One group per directory
+    Directory,
+    /// One group per module (file)
+    Module,
+}
+
+impl From<RollupArg> for RollupLevel {
+    fn from(level: RollupArg) -> Self {
+        match level {
+            RollupArg::Directory => RollupLevel::Directory,
+            RollupArg::Module => RollupLevel::Module,
+        }
+    }
+}
+
+/// Output formats for `migrator rollup`
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum RollupFormat {
+    /// Groups, then dependencies between them
+    Text,
+    /// The whole rollup as one JSON document
+    Json,
+}
+
+/// Issue trackers for `migrator sync-issues`
+#[cfg(feature = "integrations")]
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum TrackerArg {
+    /// `[integrations.github]`, token from GITHUB_TOKEN
+    Github,
+    /// `[integrations.jira]`, token from JIRA_API_TOKEN
+    Jira,
+}
+
+/// Output formats for `migrator burndown`
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum BurndownFormat {
+    /// One row per snapshot with a progress bar, and a projected finish date
+    Text,
+    /// `date,total,done,remaining` rows, for spreadsheets and charting tools
+    Csv,
+    /// The series as a JSON array
+    Json,
+}
+
+/// Output formats for `migrator check`
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum CheckFormat {
+    /// One `file:line: [rule] message` line per violation
+    Text,
+    /// SARIF 2.1.0, for GitHub code scanning and Azure DevOps
+    Sarif,
+}
+
+/// How `migrator check` uses the violation baseline
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum BaselineMode {
+    /// Report every violation
+    Ignore,
+    /// Report only violations the baseline does not contain
+    Ratchet,
+    /// Write the current violations to the baseline
+    Update,
+}
+
+/// Where `migrator check` learns which files changed
+enum ChangeSource {
+    /// `git diff` against a base revision (`--diff-base`)
+    DiffBase(String),
+    /// A file listing one path per line, or `-` for stdin (`--changed-files`)
+    List(PathBuf),
+}
+
+impl ChangeSource {
+    /// The changed files, resolved as node file paths are
+    fn files(&self, root: &Path, config: &Config) -> anyhow::Result<Vec<PathBuf>> {
+        let paths = config.path_resolver();
+        match self {
+            ChangeSource::DiffBase(base) => Ok(impact::changed_files(root, base, paths)?),
+            ChangeSource::List(list) => {
+                let text = if list == Path::new("-") {
+                    std::io::read_to_string(std::io::stdin())?
+                } else {
+                    std::fs::read_to_string(list)
+                        .with_context(|| format!("failed to read {}", list.display()))?
+                };
+                Ok(impact::parse_file_list(root, &text, paths))
+            }
+        }
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    progress::init(cli.verbose, cli.quiet);
+    if let Some(filter) = cli.filter {
+        let _ = FILTER.set(filter);
+    }
+    if let Some(filter) = cli.edge_filter {
+        let _ = EDGE_FILTER.set(filter);
+    }
+
+    if let Some(dir) = cli.generate_man {
+        return generate_man(&dir);
+    }
+    let Some(command) = cli.command else {
+        Cli::command().print_help()?;
+        return Ok(());
+    };
+    reject_filter(&command)?;
+
+    match command {
+        Commands::Scan {
+            path,
+            config,
+            strict,
+            format,
+            stream,
+            level,
+            granularity,
+            output,
+            blame,
+            churn,
+            provenance,
+            git,
+            rev,
+            git_cache,
+        } => {
+            let checkout = match git {
+                Some(url) => Some(checkout_remote(&url, rev.as_deref(), git_cache.as_deref())?),
+                None => None,
+            };
+            let path = checkout.as_ref().map_or(path, |c| c.path().to_path_buf());
+            let enrichment = Enrichment {
+                blame,
+                churn,
+                provenance: provenance || checkout.is_some(),
+                tracked: false,
+            };
+            let depth = ScanDepth { level, granularity };
+            match format {
+                #[cfg(feature = "snapshot")]
+                ScanFormat::Snapshot if blame || churn => {
+                    anyhow::bail!("--blame and --churn are not supported with this format")
+                }
+                #[cfg(feature = "snapshot")]
+                ScanFormat::Snapshot if strict => {
+                    anyhow::bail!("--strict is not supported with this format")
+                }
+                ScanFormat::Summary if blame || churn || provenance => {
+                    anyhow::bail!(
+                        "--blame, --churn and --provenance are not supported with this format"
+                    )
+                }
+                _ if level == ScanLevel::Imports && granularity.is_some() => {
+                    anyhow::bail!("--granularity is not supported with --level imports")
+                }
+                ScanFormat::Jsonl if stream => {
+                    if blame || churn || enrichment.provenance {
+                        anyhow::bail!(
+                            "--blame, --churn, --provenance and --git are not supported with --stream"
+                        );
+                    }
+                    if level == ScanLevel::Imports {
+                        anyhow::bail!("--level imports is not supported with --stream");
+                    }
+                    scan_jsonl_stream(
+                        &path,
+                        config.as_deref(),
+                        output.as_deref(),
+                        granularity,
+                        strict,
+                    )
+                }
+                _ if stream => anyhow::bail!("--stream is only supported with --format jsonl"),
+                ScanFormat::Summary => scan(&path, config.as_deref(), strict, depth),
+                ScanFormat::Json => scan_json(
+                    &path,
+                    config.as_deref(),
+                    output.as_deref(),
+                    depth,
+                    enrichment,
+                    strict,
+                ),
+                ScanFormat::Jsonl => scan_jsonl(
+                    &path,
+                    config.as_deref(),
+                    output.as_deref(),
+                    depth,
+                    enrichment,
+                    strict,
+                ),
+                #[cfg(feature = "parquet")]
+                ScanFormat::Parquet => {
+                    let Some(dir) = output else {
+                        anyhow::bail!("--format parquet needs an --output directory");
+                    };
+                    let graph = load_enriched(&path, config.as_deref(), depth, enrichment, strict)?;
+                    graph_migrator_core::export::parquet::write_dir(&graph, &dir)?;
+                    Ok(())
+                }
+                #[cfg(feature = "snapshot")]
+                ScanFormat::Snapshot => {
+                    let Some(file) = output else {
+                        anyhow::bail!("--format snapshot needs an --output file");
+                    };
+                    // Snapshots are read by `snapshot`, which shows statuses
+                    let enrichment = Enrichment {
+                        tracked: true,
+                        ..enrichment
+                    };
+                    let graph = load_enriched(&path, config.as_deref(), depth, enrichment, false)?;
+                    graph_migrator_core::snapshot::save(&graph, &file)?;
+                    Ok(())
+                }
+            }
+        }
+        Commands::Stats {
+            path,
+            config,
+            memory: true,
+            ..
+        } => memory(&path, config.as_deref()),
+        Commands::Stats {
+            path,
+            config,
+            limit,
+            by: Some(StatsBy::Owner),
+            ..
+        } => stats_by_owner(&path, config.as_deref(), limit),
+        Commands::Stats {
+            path,
+            config,
+            sort,
+            limit,
+            blame,
+            churn,
+            effort,
+            by: None,
+            memory: false,
+        } => stats(
+            &path,
+            config.as_deref(),
+            sort,
+            limit,
+            StatsColumns {
+                blame,
+                churn,
+                effort,
+            },
+        ),
+        Commands::Path {
+            from,
+            to,
+            root,
+            config,
+            all,
+            max_length,
+            edge_types,
+        } => {
+            let mut options = PathOptions::new().all_paths(all);
+            if let Some(max) = max_length {
+                options = options.max_length(max);
+            }
+            if !edge_types.is_empty() {
+                options = options.edge_types(&edge_types);
+            }
+            path(&root, config.as_deref(), &from, &to, &options)
+        }
+        Commands::Find {
+            pattern,
+            root,
+            config,
+            limit,
+        } => find(&root, config.as_deref(), &pattern, limit),
+        Commands::Rollup {
+            path,
+            config,
+            level,
+            format,
+        } => rollup(&path, config.as_deref(), level.into(), format),
+        Commands::SuggestUnits {
+            path,
+            config,
+            limit,
+            accept,
+        } => suggest_units(&path, config.as_deref(), limit, &accept),
+        Commands::SuggestLinks {
+            path,
+            config,
+            accept,
+        } => suggest_links(&path, config.as_deref(), accept),
+        Commands::Snapshot {
+            path,
+            config,
+            history,
+        } => snapshot(&path, config.as_deref(), history.as_deref()),
+        Commands::Burndown {
+            path,
+            config,
+            history,
+            unit,
+            owner,
+            format,
+        } => {
+            let scope = match (unit, owner) {
+                (Some(unit), _) => BurndownScope::Unit(unit),
+                (_, Some(owner)) => BurndownScope::Owner(owner),
+                _ => BurndownScope::All,
+            };
+            burndown(&path, config.as_deref(), history.as_deref(), &scope, format)
+        }
+        #[cfg(feature = "integrations")]
+        Commands::SyncIssues {
+            path,
+            config,
+            tracker,
+            dry_run,
+        } => sync_issues(&path, config.as_deref(), tracker, dry_run),
+        Commands::Report {
+            path,
+            config,
+            output,
+            limit,
+            recent_days,
+        } => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs());
+            let options = ReportOptions::new(now)
+                .limit(limit)
+                .recent_days(recent_days);
+            report(&path, config.as_deref(), output.as_deref(), &options)
+        }
+        Commands::Unreachable {
+            path,
+            config,
+            entries,
+            entry_files,
+        } => unreachable(&path, config.as_deref(), &entries, &entry_files),
+        Commands::Unresolved { path, config, list } => unresolved(&path, config.as_deref(), list),
+        Commands::Deps {
+            path,
+            config,
+            packages,
+        } => deps(&path, config.as_deref(), &packages),
+        Commands::Lsp { path, config } => lsp::run(&path, load_config(&path, config.as_deref())?),
+        Commands::Serve {
+            path,
+            config,
+            port,
+            host,
+            #[cfg(feature = "grpc")]
+            grpc,
+        } => {
+            let config = load_config(&path, config.as_deref())?;
+            #[cfg(feature = "grpc")]
+            if grpc {
+                return grpc::run(&path, &config, &host, port);
+            }
+            serve::run(&path, &config, &host, port)
+        }
+        Commands::Tui { path, config } => tui::run(&path, &load_config(&path, config.as_deref())?),
+        Commands::Fingerprint { path, config } => {
+            let multi = load_project(&path, config.as_deref())?;
+            println!("{}", multi.graph.fingerprint());
+            Ok(())
+        }
+        Commands::Info { path, config } => info(&path, config.as_deref()),
+        Commands::Cache { action } => cache_command(action),
+        Commands::RewriteIds {
+            input,
+            root,
+            output,
+        } => rewrite_ids(&input, &root, output.as_deref()),
+        Commands::Rename {
+            old,
+            new,
+            root,
+            config,
+            file,
+        } => rename(&root, config.as_deref(), &old, &new, file),
+        Commands::SetStatus {
+            id,
+            status,
+            status_flag,
+            dry_run,
+            root,
+            config,
+        } => {
+            let status = status.or(status_flag).context("no status given")?;
+            match id {
+                Some(id) => set_status(&root, config.as_deref(), &id, status),
+                None => set_status_matching(&root, config.as_deref(), status, dry_run),
+            }
+        }
+        Commands::Undo { root, config } => undo(&root, config.as_deref()),
+        Commands::History { id, root, config } => history(&root, config.as_deref(), &id),
+        Commands::Frontier {
+            path,
+            config,
+            coverage,
+            max_coverage,
+            effort,
+        } => frontier(
+            &path,
+            config.as_deref(),
+            coverage.as_deref(),
+            max_coverage,
+            effort,
+        ),
+        Commands::Check {
+            path,
+            config,
+            format,
+            output,
+            ratchet,
+            update_baseline,
+            diff_base,
+            changed_files,
+        } => {
+            let mode = match (ratchet, update_baseline) {
+                (true, _) => BaselineMode::Ratchet,
+                (_, true) => BaselineMode::Update,
+                _ => BaselineMode::Ignore,
+            };
+            let changes = match (diff_base, changed_files) {
+                (Some(base), _) => Some(ChangeSource::DiffBase(base)),
+                (_, Some(list)) => Some(ChangeSource::List(list)),
+                _ => None,
+            };
+            check(
+                &path,
+                config.as_deref(),
+                format,
+                output.as_deref(),
+                mode,
+                changes.as_ref(),
+            )
+        }
+        Commands::Query {
+            query: Query::TestsFor { id, root, config },
+        } => tests_for(&root, config.as_deref(), &id),
+        #[cfg(feature = "snapshot")]
+        Commands::Query {
+            query: Query::Node { id, snapshot },
+        } => query_node(&snapshot, &id),
+        Commands::Incomplete { path, config } => incomplete(&path, config.as_deref()),
+        Commands::Link {
+            legacy,
+            target,
+            root,
+            config,
+            remove,
+            interactive,
+            from,
+            to,
+        } => match (legacy, target) {
+            (Some(legacy), Some(target)) if !interactive => {
+                link(&root, config.as_deref(), &legacy, &target, remove)
+            }
+            _ => link::run(
+                &root,
+                &load_config(&root, config.as_deref())?,
+                from.zip(to).as_ref(),
+            ),
+        },
+        Commands::Links {
+            path,
+            config,
+            unlinked,
+        } => links(&path, config.as_deref(), unlinked),
+        Commands::Merge { inputs, output } => merge(&inputs, output.as_deref()),
+        Commands::Debug { action } => debug_command(action),
+        Commands::Parse {
+            file,
+            stdin: _,
+            lang,
+            name,
+            output,
+        } => parse_source(
+            file.as_deref(),
+            lang.map(Language::from),
+            name,
+            output.as_deref(),
+        ),
+        Commands::Schema => {
+            print!("{}", schema::GRAPH_SCHEMA);
+            Ok(())
+        }
+        Commands::Completions { shell } => {
+            clap_complete::generate(
+                shell,
+                &mut Cli::command(),
+                "migrator",
+                &mut std::io::stdout(),
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Render `migrator.1` plus one `migrator-<subcommand>.1` page per subcommand
+fn generate_man(dir: &Path) -> anyhow::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let command = Cli::command();
+
+    let mut pages = vec![("migrator".to_string(), command.clone())];
+    for sub in command.get_subcommands() {
+        let name = format!("migrator-{}", sub.get_name());
+        let page = sub
+            .clone()
+            .display_name(name.clone())
+            .version(env!("CARGO_PKG_VERSION"))
+            .bin_name(format!("migrator {}", sub.get_name()));
+        pages.push((name, page));
+    }
+
+    for (name, page) in pages {
+        let path = dir.join(format!("{}.1", name));
+        let mut file = std::fs::File::create(&path)?;
+        clap_mangen::Man::new(page).render(&mut file)?;
+        println!("{}", path.display());
+    }
+    Ok(())
+}
+
+/// Load the project configuration, preferring an explicit `--config` path
+fn load_config(root: &Path, explicit: Option<&Path>) -> anyhow::Result<Config> {
+    let config = match explicit {
+        Some(path) => Config::load(path)?,
+        None => Config::from_project_root(root)?,
+    };
+    Ok(config)
+}
+
+/// How much `migrator scan` extracts
+#[derive(Clone, Copy)]
+struct ScanDepth {
+    /// `--level`
+    level: ScanLevel,
+    /// `--granularity`, overriding `granularity` in migrator.toml
+    granularity: Option<GranularityArg>,
+}
+
+/// The configuration of `root`, with `granularity` overriding its own
+fn load_scan_config(
+    root: &Path,
+    config_path: Option<&Path>,
+    granularity: Option<GranularityArg>,
+) -> anyhow::Result<Config> {
+    let mut config = load_config(root, config_path)?;
+    if let Some(level) = granularity {
+        config.granularity = Some(level.into());
+    }
+    // A checkout's absolute path is temporary or host-specific
+    if REMOTE.get().is_some() {
+        config.relative_ids = true;
+    }
+    Ok(config)
+}
+
+/// Check out `url` for `scan --git`, recording where it came from
+fn checkout_remote(
+    url: &str,
+    rev: Option<&str>,
+    cache: Option<&Path>,
+) -> anyhow::Result<remote::Checkout> {
+    let checkout = remote::checkout(url, rev, cache)
+        .with_context(|| format!("could not check out {}", url))?;
+    eprintln!("Scanning {} at {}", url, checkout.commit());
+    let _ = REMOTE.set(url.to_string());
+    Ok(checkout)
+}
+
+fn scan(
+    root: &Path,
+    config_path: Option<&Path>,
+    strict: bool,
+    ScanDepth { level, granularity }: ScanDepth,
+) -> anyhow::Result<()> {
+    let config = load_scan_config(root, config_path, granularity)?;
+    let (output, diagnostics) = match (level, strict) {
+        (ScanLevel::Symbols, true) => (
+            resolve::resolve_directory_with_config(root, &config)?,
+            Vec::new(),
+        ),
+        (ScanLevel::Symbols, false) => resolve::resolve_directory_lenient(root, &config),
+        (ScanLevel::Imports, true) => (resolve::resolve_imports(root, &config)?, Vec::new()),
+        (ScanLevel::Imports, false) => resolve::resolve_imports_lenient(root, &config),
+    };
+    let mut multi = output.graph;
+    scope(&mut multi.graph);
+
+    println!(
+        "Parsed {} files: {} nodes, {} edges",
+        multi.file_nodes.len(),
+        multi.graph.node_count(),
+        multi.graph.edge_count()
+    );
+    let languages = multi.language_stats();
+    if languages.len() > 1 {
+        for (language, stats) in languages {
+            println!(
+                "  {}: {} files, {} nodes",
+                language, stats.files, stats.nodes
+            );
+        }
+    }
+    print_diagnostics(&diagnostics);
+
+    Ok(())
+}
+
+/// Parse and resolve a project leniently, reporting diagnostics on stderr
+/// Refuse `--filter` for commands that save state built from the whole project
+fn reject_filter(command: &Commands) -> anyhow::Result<()> {
+    let name = match command {
+        Commands::Link { .. } => "link",
+        Commands::Rename { .. } => "rename",
+        Commands::SuggestUnits { .. } => "suggest-units",
+        Commands::SuggestLinks { .. } => "suggest-links",
+        Commands::Tui { .. } => "tui",
+        Commands::Undo { .. } => "undo",
+        // Each file is written before the next is parsed, so nothing can be left out
+        Commands::Scan { stream: true, .. } => "scan --stream",
+        // The history holds whole-project counts; a scoped entry would read as a regression
+        Commands::Snapshot { .. } => "snapshot",
+        Commands::Check {
+            update_baseline: true,
+            ..
+        } => "check --update-baseline",
+        _ => return Ok(()),
+    };
+    if FILTER.get().is_some() {
+        anyhow::bail!("--filter is not supported with {}", name);
+    }
+    if EDGE_FILTER.get().is_some() {
+        anyhow::bail!("--edge-filter is not supported with {}", name);
+    }
+    Ok(())
+}
+
+fn load_project(root: &Path, config_path: Option<&Path>) -> anyhow::Result<MultiFileGraph> {
+    Ok(load_resolved(root, config_path)?.graph)
+}
+
+/// Like [`load_project()`], keeping imports and the unresolved report
+fn load_resolved(root: &Path, config_path: Option<&Path>) -> anyhow::Result<FirstPassOutput> {
+    let config = load_config(root, config_path)?;
+    Ok(resolve_lenient(root, &config))
+}
+
+/// Resolve a project leniently as configured, reporting diagnostics and applying `--filter`
+fn resolve_lenient(root: &Path, config: &Config) -> FirstPassOutput {
+    let (mut output, diagnostics) = resolve::resolve_directory_lenient(root, config);
+    for diagnostic in &diagnostics {
+        print_diagnostic(diagnostic);
+    }
+    scope(&mut output.graph.graph);
+    output
+}
+
+/// Resolve a project leniently and overlay statuses and links from its state file
+///
+/// Statuses from source annotations are set without history entries, as
+/// the graph is not saved; [`load_for_update()`] records them.
+fn load_tracked(root: &Path, config: &Config) -> anyhow::Result<Graph> {
+    Ok(load_state(root, config, None)?.graph)
+}
+
+/// [`load_tracked()`] for commands that save the state file
+///
+/// Changes read from source annotations are committed first, as their own
+/// `annotations` journal entry, so the command's entry only holds what the
+/// command did and undoing it leaves them alone. Status changes are
+/// recorded now, as `set-status` records them, and sent as status events.
+fn load_for_update(root: &Path, config: &Config) -> anyhow::Result<Graph> {
+    migrate_state(root, config)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let State {
+        graph,
+        annotated,
+        moves,
+    } = load_state(root, config, Some(now))?;
+    for (old, new) in moves {
+        eprintln!(
+            "Detected move of {} to {}; carrying its state over",
+            old, new
+        );
+    }
+    if annotated.statuses.len() + annotated.assignments + annotated.units_created > 0 {
+        let state = root.join(&config.output.graph);
+        store::commit(&state, &graph, journal::ANNOTATIONS_COMMAND)?;
+        let emitter = Emitter::from_config(config, root)?;
+        for id in &annotated.statuses {
+            emit_status_event(&emitter, &graph, id);
+        }
+    }
+    Ok(graph)
+}
+
+/// Rewrite a state file written with absolute node IDs to the root-relative
+/// IDs scans now produce; commands that save the state run this first, so
+/// it happens once and read-only commands never touch the file
+fn migrate_state(root: &Path, config: &Config) -> anyhow::Result<()> {
+    let Some(id_root) = config.path_resolver().id_root(root)? else {
+        return Ok(());
+    };
+    let state = root.join(&config.output.graph);
+    let rewritten = store::relativize_ids(&state, &id_root)?;
+    if rewritten > 0 {
+        eprintln!(
+            "Made {} absolute node IDs in {} relative to the project root",
+            rewritten,
+            state.display()
+        );
+    }
+    Ok(())
+}
+
+/// A project graph with its state file overlaid, from [`load_state()`]
+struct State {
+    graph: Graph,
+    /// What source annotations changed
+    annotated: ApplySummary,
+    /// `(old, new)` files the state was carried over to (see
+    /// [`store::TrackedState::follow_moves()`])
+    moves: Vec<(String, String)>,
+}
+
+/// [`load_tracked()`], with what source annotations changed and the moved
+/// files detected; status changes are recorded at `recorded_at` if given
+/// (see [`annotations::apply()`])
+fn load_state(root: &Path, config: &Config, recorded_at: Option<u64>) -> anyhow::Result<State> {
+    let (output, diagnostics) = resolve::resolve_directory_lenient(root, config);
+    for diagnostic in &diagnostics {
+        print_diagnostic(diagnostic);
+    }
+    let mut graph = output.graph.graph;
+    let state_path = root.join(&config.output.graph);
+    let mut state = match config.path_resolver().id_root(root)? {
+        Some(id_root) => store::load_relative(&state_path, &id_root)?,
+        None => store::load(&state_path)?,
+    };
+    let moves = state.follow_moves(&graph);
+    store::apply(&mut graph, &state);
+    let mut annotated = ApplySummary::default();
+    if config.annotations {
+        let (found, mut diagnostics) = annotations::scan(&graph, root);
+        let (summary, warnings) = annotations::apply(&mut graph, &found, recorded_at)?;
+        diagnostics.extend(warnings);
+        for diagnostic in &diagnostics {
+            print_diagnostic(diagnostic);
+        }
+        tracing::info!(
+            annotations = found.len(),
+            statuses = summary.statuses.len(),
+            assignments = summary.assignments,
+            "applied source annotations"
+        );
+        annotated = summary;
+    }
+    Ok(State {
+        graph,
+        annotated,
+        moves,
+    })
+}
+
+/// Like [`load_tracked()`], scoped by `--filter`; for commands that never save
+fn load_view(root: &Path, config: &Config) -> anyhow::Result<Graph> {
+    let mut graph = load_tracked(root, config)?;
+    scope(&mut graph);
+    Ok(graph)
+}
+
+/// Drop the nodes outside `--filter` and the edges outside `--edge-filter`,
+/// if given
+fn scope(graph: &mut Graph) {
+    if let Some(filter) = FILTER.get() {
+        let removed = filter.retain(graph);
+        tracing::info!(removed, filter = filter.as_str(), "applied --filter");
+    }
+    if let Some(filter) = EDGE_FILTER.get() {
+        let removed = filter.retain_edges(graph);
+        tracing::info!(removed, filter = filter.as_str(), "applied --edge-filter");
+    }
+}
+
+fn stats(
+    root: &Path,
+    config_path: Option<&Path>,
+    sort: StatsSort,
+    limit: usize,
+    columns: StatsColumns,
+) -> anyhow::Result<()> {
+    let config = load_config(root, config_path)?;
+    let mut multi = load_project(root, config_path)?;
+    let blame = columns.blame || sort == StatsSort::Age;
+    let churn = columns.churn || sort == StatsSort::Churn;
+    let effort = columns.effort || sort == StatsSort::Effort;
+    if blame {
+        let annotated = enrich::blame(&mut multi.graph)?;
+        tracing::info!(annotated, "git blame");
+    }
+    if churn {
+        let annotated = enrich::churn(&mut multi.graph, config.enrich.churn_days)?;
+        tracing::info!(annotated, days = config.enrich.churn_days, "git churn");
+    }
+    if effort {
+        let annotated = enrich::estimate_effort(&mut multi.graph);
+        tracing::info!(annotated, "effort");
+    }
+    let metrics = queries::metrics(&multi.graph);
+
+    println!("Nodes:            {}", metrics.node_count);
+    println!("Dependency edges: {}", metrics.dependency_edge_count);
+    println!("Density:          {:.4}", metrics.density);
+    println!("Files:            {}", metrics.nodes_per_file.len());
+    println!(
+        "Longest chain:    {} ({} steps)",
+        metrics.longest_chain.join(" -> "),
+        metrics.longest_chain.len().saturating_sub(1)
+    );
+    println!();
 
-fn main() {
-    let _cli = Cli::parse();
-    // Clap handles --version and --help automatically
-    // For now, just print a message to verify the CLI works
+    let scores = queries::centrality(&multi.graph);
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let mut rows: Vec<StatsRow> = metrics
+        .nodes
+        .iter()
+        .map(|row| {
+            let node = multi
+                .graph
+                .get_by_id(&row.id)
+                .and_then(|idx| multi.graph.node_weight(idx));
+            StatsRow {
+                metrics: row,
+                centrality: scores.get(&row.id).copied().unwrap_or(Centrality {
+                    pagerank: 0.0,
+                    betweenness: 0.0,
+                }),
+                age_in_days: node
+                    .and_then(|node| node.blame.as_ref())
+                    .map(|blame| now.saturating_sub(blame.last_modified) / 86_400),
+                churn: node.and_then(|node| node.churn),
+                effort: node.and_then(|node| node.effort).map(|e| e.score),
+            }
+        })
+        .collect();
+    rows.sort_by(|x, y| {
+        let (a, b) = (x.metrics, y.metrics);
+        let order = match sort {
+            StatsSort::FanIn => b.fan_in.cmp(&a.fan_in),
+            StatsSort::FanOut => b.fan_out.cmp(&a.fan_out),
+            StatsSort::Pagerank => y.centrality.pagerank.total_cmp(&x.centrality.pagerank),
+            StatsSort::Betweenness => y
+                .centrality
+                .betweenness
+                .total_cmp(&x.centrality.betweenness),
+            StatsSort::Name => a.name.cmp(&b.name),
+            StatsSort::File => a.file_path.cmp(&b.file_path),
+            // Oldest first; symbols without blame last
+            StatsSort::Age => y.age_in_days.cmp(&x.age_in_days),
+            StatsSort::Churn => y.churn.cmp(&x.churn),
+            StatsSort::Effort => y
+                .effort
+                .unwrap_or(-1.0)
+                .total_cmp(&x.effort.unwrap_or(-1.0)),
+        };
+        order.then_with(|| a.id.cmp(&b.id))
+    });
+    rows.truncate(limit);
+
+    let name_width = rows
+        .iter()
+        .map(|row| row.metrics.name.len())
+        .max()
+        .unwrap_or(0)
+        .max(6);
     println!(
-        "GraphMigrator CLI v{} - Workspace initialized!",
-        env!("CARGO_PKG_VERSION")
+        "{:<name_width$}  {:>6}  {:>7}  {:>8}  {:>11}{}{}{}  FILE",
+        "SYMBOL",
+        "FAN-IN",
+        "FAN-OUT",
+        "PAGERANK",
+        "BETWEENNESS",
+        optional_column(blame, Some("AGE (DAYS)")),
+        optional_column(churn, Some("CHURN")),
+        optional_column(effort, Some("EFFORT")),
+    );
+    for row in rows {
+        println!(
+            "{:<name_width$}  {:>6}  {:>7}  {:>8.4}  {:>11.4}{}{}{}  {}",
+            row.metrics.name,
+            row.metrics.fan_in,
+            row.metrics.fan_out,
+            row.centrality.pagerank,
+            row.centrality.betweenness,
+            optional_column(blame, row.age_in_days),
+            optional_column(churn, row.churn),
+            optional_column(effort, row.effort.map(|score| format!("{:.1}", score))),
+            row.metrics.file_path.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// `migrator stats --by owner`: migration progress per owning team
+fn stats_by_owner(root: &Path, config_path: Option<&Path>, limit: usize) -> anyhow::Result<()> {
+    let config = load_config(root, config_path)?;
+    let mut graph = load_project(root, config_path)?.graph;
+    annotate_owners(&mut graph, root, &config)?;
+
+    const UNOWNED: &str = "(unowned)";
+    let mut groups: HashMap<&str, BTreeMap<MigrationStatus, usize>> = HashMap::new();
+    for node in graph
+        .nodes()
+        .filter(|node| queries::migration::is_project_symbol(node))
+    {
+        let owners: Vec<&str> = if node.owners.is_empty() {
+            vec![UNOWNED]
+        } else {
+            node.owners.iter().map(String::as_str).collect()
+        };
+        for owner in owners {
+            *groups
+                .entry(owner)
+                .or_default()
+                .entry(node.status)
+                .or_default() += 1;
+        }
+    }
+    let total = |counts: &BTreeMap<MigrationStatus, usize>| counts.values().sum::<usize>();
+    let mut groups: Vec<_> = groups.into_iter().collect();
+    groups.sort_by(|(a, x), (b, y)| total(y).cmp(&total(x)).then_with(|| a.cmp(b)));
+    groups.truncate(limit);
+
+    let owner_width = groups
+        .iter()
+        .map(|(owner, _)| owner.len())
+        .max()
+        .unwrap_or(0)
+        .max(5);
+    println!(
+        "{:<owner_width$}  {:>7}  {:>7}  {:>11}  {:>7}  {:>8}  {:>10}  {:>5}",
+        "OWNER", "SYMBOLS", "PENDING", "IN-PROGRESS", "BLOCKED", "MIGRATED", "SUPERSEDED", "DONE"
+    );
+    for (owner, counts) in &groups {
+        let count = |status| counts.get(&status).copied().unwrap_or(0);
+        let done = count(MigrationStatus::Migrated) + count(MigrationStatus::Superseded);
+        println!(
+            "{:<owner_width$}  {:>7}  {:>7}  {:>11}  {:>7}  {:>8}  {:>10}  {:>4.0}%",
+            owner,
+            total(counts),
+            count(MigrationStatus::Pending),
+            count(MigrationStatus::InProgress),
+            count(MigrationStatus::Blocked),
+            count(MigrationStatus::Migrated),
+            count(MigrationStatus::Superseded),
+            100.0 * done as f64 / total(counts) as f64
+        );
+    }
+    Ok(())
+}
+
+/// Run [`enrich::owners()`] with the `[enrich]` CODEOWNERS and directory settings
+fn annotate_owners(graph: &mut Graph, root: &Path, config: &Config) -> anyhow::Result<()> {
+    let codeowners = config
+        .enrich
+        .codeowners
+        .as_ref()
+        .map(|path| config.resolve_path(root, path));
+    let annotated = enrich::owners(
+        graph,
+        root,
+        codeowners.as_deref(),
+        &config.enrich.owners,
+        config.path_resolver(),
+    )?;
+    tracing::info!(annotated, "owners");
+    Ok(())
+}
+
+/// `migrator stats --memory`: where the graph's memory goes
+fn memory(root: &Path, config_path: Option<&Path>) -> anyhow::Result<()> {
+    let graph = load_project(root, config_path)?.graph;
+    let columns = [
+        ("GRAPH", graph.memory_stats()),
+        #[cfg(feature = "compact")]
+        (
+            "COMPACT",
+            graph_migrator_core::compact::CompactGraph::from_graph(&graph).memory_stats(),
+        ),
+    ];
+
+    println!(
+        "Nodes: {}, edges: {}",
+        graph.node_count(),
+        graph.edge_count()
+    );
+    print!("{:<16}", "");
+    for (title, _) in &columns {
+        print!("{:>12}", title);
+    }
+    println!();
+    type Bytes = fn(&MemoryStats) -> usize;
+    let rows: [(&str, Bytes); 6] = [
+        ("Nodes", |s| s.node_bytes),
+        ("Edges", |s| s.edge_bytes),
+        ("Strings", |s| s.string_bytes),
+        ("  if interned", |s| s.unique_string_bytes),
+        ("ID index", |s| s.index_bytes),
+        ("Total", MemoryStats::total),
+    ];
+    for (label, bytes) in rows {
+        print!("{:<16}", label);
+        for (_, stats) in &columns {
+            print!("{:>12}", human_bytes(bytes(stats)));
+        }
+        println!();
+    }
+    Ok(())
+}
+
+/// `1536` as `1.5 KiB`
+fn human_bytes(bytes: usize) -> String {
+    let mut value = bytes as f64;
+    for unit in ["B", "KiB", "MiB"] {
+        if value < 1024.0 {
+            return format!("{:.1} {}", value, unit);
+        }
+        value /= 1024.0;
+    }
+    format!("{:.1} GiB", value)
+}
+
+/// A right-aligned `migrator stats` column, `-` when the value is missing,
+/// or nothing when the column is not shown
+fn optional_column(shown: bool, value: Option<impl std::fmt::Display>) -> String {
+    match (shown, value) {
+        (false, _) => String::new(),
+        (true, Some(value)) => format!("  {:>10}", value),
+        (true, None) => format!("  {:>10}", "-"),
+    }
+}
+
+fn path(
+    root: &Path,
+    config_path: Option<&Path>,
+    from: &str,
+    to: &str,
+    options: &PathOptions,
+) -> anyhow::Result<()> {
+    let multi = load_project(root, config_path)?;
+    let found = queries::paths(&multi.graph, from, to, options)?;
+
+    if found.is_empty() {
+        println!("No path from {} to {}", from, to);
+        return Ok(());
+    }
+    for path in found {
+        let mut line = path.nodes[0].clone();
+        for (edge, node) in path.edges.iter().zip(&path.nodes[1..]) {
+            line.push_str(&format!(" -[{:?}]-> {}", edge, node));
+        }
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+fn unreachable(
+    root: &Path,
+    config_path: Option<&Path>,
+    entries: &[String],
+    entry_files: &[PathBuf],
+) -> anyhow::Result<()> {
+    let config = load_config(root, config_path)?;
+    let multi = resolve_lenient(root, &config).graph;
+
+    let mut entry_ids: Vec<&str> = entries.iter().map(String::as_str).collect();
+    for file in entry_files {
+        let canonical = config
+            .path_resolver()
+            .resolve(file)
+            .map_err(|e| anyhow::anyhow!("{}: {}", file.display(), e))?;
+        entry_ids.extend(
+            multi
+                .graph
+                .nodes()
+                .filter(|n| n.file_path == canonical)
+                .map(|n| n.id.as_str()),
+        );
+    }
+
+    let dead = queries::unreachable_from(&multi.graph, &entry_ids)?;
+    for id in &dead {
+        println!("{}", id);
+    }
+    eprintln!("{} unreachable symbols", dead.len());
+    Ok(())
+}
+
+fn find(
+    root: &Path,
+    config_path: Option<&Path>,
+    pattern: &str,
+    limit: usize,
+) -> anyhow::Result<()> {
+    let graph = load_resolved(root, config_path)?.graph.graph;
+    let found = queries::search(&graph, pattern);
+    if found.is_empty() {
+        println!("No symbols match {}", pattern);
+        return Ok(());
+    }
+    for found in found.iter().take(limit) {
+        println!(
+            "{:<9} {:<14} {}",
+            format!("{:?}", found.kind).to_lowercase(),
+            format!("{:?}", found.node_type),
+            found.id
+        );
+    }
+    if found.len() > limit {
+        println!("... and {} more (use --limit)", found.len() - limit);
+    }
+    Ok(())
+}
+
+fn rollup(
+    root: &Path,
+    config_path: Option<&Path>,
+    level: RollupLevel,
+    format: RollupFormat,
+) -> anyhow::Result<()> {
+    let graph = load_project(root, config_path)?.graph;
+    let rollup = queries::rollup(&graph, level);
+    if format == RollupFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&rollup)?);
+        return Ok(());
+    }
+
+    println!("Groups ({}):", rollup.groups.len());
+    for group in &rollup.groups {
+        println!(
+            "  {:<40} {:>6} nodes {:>6} internal edges",
+            group.id, group.node_count, group.internal_edges
+        );
+    }
+    println!("Dependencies ({}):", rollup.edges.len());
+    for edge in &rollup.edges {
+        let types: Vec<String> = edge
+            .edge_types
+            .iter()
+            .map(|(edge_type, count)| format!("{:?} {}", edge_type, count))
+            .collect();
+        println!(
+            "  {} -> {}  {} ({})",
+            edge.from,
+            edge.to,
+            edge.weight,
+            types.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// `migrator snapshot`: append the current status counts to the history
+fn snapshot(root: &Path, config_path: Option<&Path>, history: Option<&Path>) -> anyhow::Result<()> {
+    let config = load_config(root, config_path)?;
+    let mut graph = load_view(root, &config)?;
+    annotate_owners(&mut graph, root, &config)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let snapshot = ProgressSnapshot::capture(&graph, now);
+    let path = history.map_or_else(
+        || config.resolve_path(root, &config.output.history),
+        Path::to_path_buf,
+    );
+    migration_progress::append(&path, &snapshot)?;
+
+    let total: usize = snapshot.total.values().sum();
+    let done = migration_progress::done(&snapshot.total);
+    println!(
+        "{}: {}/{} symbols done ({:.0}%), {} unit(s), {} owner(s) -> {}",
+        migration_progress::format_date(now),
+        done,
+        total,
+        percent(done, total),
+        snapshot.units.len(),
+        snapshot.owners.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+/// `migrator burndown`: remaining work per snapshot in the history
+fn burndown(
+    root: &Path,
+    config_path: Option<&Path>,
+    history: Option<&Path>,
+    scope: &BurndownScope,
+    format: BurndownFormat,
+) -> anyhow::Result<()> {
+    let config = load_config(root, config_path)?;
+    let path = history.map_or_else(
+        || config.resolve_path(root, &config.output.history),
+        Path::to_path_buf,
+    );
+    let points = migration_progress::burndown(&migration_progress::load_history(&path)?, scope);
+    match format {
+        BurndownFormat::Json => println!("{}", serde_json::to_string_pretty(&points)?),
+        BurndownFormat::Csv => {
+            println!("date,total,done,remaining");
+            for point in &points {
+                println!(
+                    "{},{},{},{}",
+                    migration_progress::format_date(point.at),
+                    point.total,
+                    point.done,
+                    point.remaining
+                );
+            }
+        }
+        BurndownFormat::Text => {
+            if points.is_empty() {
+                println!(
+                    "No snapshots in {}; record one with `migrator snapshot`",
+                    path.display()
+                );
+                return Ok(());
+            }
+            const BAR: usize = 30;
+            println!(
+                "{:<10}  {:>7}  {:>7}  {:>9}  PROGRESS",
+                "DATE", "TOTAL", "DONE", "REMAINING"
+            );
+            for point in &points {
+                let filled = point.done * BAR / point.total.max(1);
+                println!(
+                    "{:<10}  {:>7}  {:>7}  {:>9}  {}{} {:.0}%",
+                    migration_progress::format_date(point.at),
+                    point.total,
+                    point.done,
+                    point.remaining,
+                    "#".repeat(filled),
+                    ".".repeat(BAR - filled),
+                    percent(point.done, point.total)
+                );
+            }
+            match migration_progress::projected_completion(&points) {
+                Some(at) => println!(
+                    "Projected completion at the current rate: {}",
+                    migration_progress::format_date(at)
+                ),
+                None => println!("Projected completion: not enough progress recorded yet"),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `migrator sync-issues`: one tracker issue per migration unit
+#[cfg(feature = "integrations")]
+fn sync_issues(
+    root: &Path,
+    config_path: Option<&Path>,
+    tracker: Option<TrackerArg>,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    use graph_migrator_core::integrations::{self, GitHub, IssueTracker, Jira};
+
+    let config = load_config(root, config_path)?;
+    let mut graph = load_for_update(root, &config)?;
+    if dry_run {
+        let mut units: Vec<&Node> = graph
+            .nodes()
+            .filter(|node| node.node_type == graph_migrator_core::NodeType::MigrationUnit)
+            .collect();
+        units.sort_by(|a, b| a.id.cmp(&b.id));
+        for unit in units {
+            if let Some(issue) = integrations::unit_issue(&graph, &unit.id) {
+                let key = unit.issue.as_deref().unwrap_or("new issue");
+                println!(
+                    "# {} ({})
+
+{}",
+                    issue.title, key, issue.body
+                );
+            }
+        }
+        return Ok(());
+    }
+
+    let integrations = &config.integrations;
+    let tracker = match (tracker, &integrations.github, &integrations.jira) {
+        (Some(TrackerArg::Github), _, _) | (None, Some(_), None) => TrackerArg::Github,
+        (Some(TrackerArg::Jira), _, _) | (None, None, Some(_)) => TrackerArg::Jira,
+        (None, None, None) => {
+            anyhow::bail!(
+                "No issue tracker configured; add [integrations.github] or [integrations.jira]"
+            )
+        }
+        (None, Some(_), Some(_)) => anyhow::bail!("Both trackers are configured; pass --tracker"),
+    };
+    let client: Box<dyn IssueTracker> = match tracker {
+        TrackerArg::Github => Box::new(GitHub::from_env(
+            integrations
+                .github
+                .clone()
+                .context("[integrations.github] is not configured")?,
+        )?),
+        TrackerArg::Jira => Box::new(Jira::from_env(
+            integrations
+                .jira
+                .clone()
+                .context("[integrations.jira] is not configured")?,
+        )?),
+    };
+
+    let result = integrations::sync_units(&mut graph, client.as_ref());
+    // Keep the keys of issues created before any failure
+    store::commit(&root.join(&config.output.graph), &graph, "sync-issues")?;
+    let outcomes = result?;
+    for outcome in &outcomes {
+        let verb = if outcome.created {
+            "created"
+        } else {
+            "updated"
+        };
+        println!("{}: {} {}", outcome.unit, verb, outcome.issue);
+    }
+    if outcomes.is_empty() {
+        println!("No migration units; create some with `migrator suggest-units --accept`");
+    }
+    Ok(())
+}
+
+/// `migrator report`: the Markdown status report
+fn report(
+    root: &Path,
+    config_path: Option<&Path>,
+    output: Option<&Path>,
+    options: &ReportOptions,
+) -> anyhow::Result<()> {
+    let config = load_config(root, config_path)?;
+    let graph = load_view(root, &config)?;
+    let output = output.map(Path::to_path_buf).or_else(|| {
+        config
+            .output
+            .report
+            .as_ref()
+            .map(|path| config.resolve_path(root, path))
+    });
+    match output {
+        Some(path) => {
+            report::write(&graph, options, std::fs::File::create(&path)?)?;
+            eprintln!("Wrote {}", path.display());
+        }
+        None => report::write(&graph, options, std::io::stdout().lock())?,
+    }
+    Ok(())
+}
+
+/// `part` as a percentage of `whole`, 0 when `whole` is 0
+fn percent(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        100.0 * part as f64 / whole as f64
+    }
+}
+
+fn suggest_units(
+    root: &Path,
+    config_path: Option<&Path>,
+    limit: usize,
+    accept: &[usize],
+) -> anyhow::Result<()> {
+    let config = load_config(root, config_path)?;
+    let mut graph = load_for_update(root, &config)?;
+    let suggestions = queries::suggest_units(&graph);
+
+    if accept.is_empty() {
+        if suggestions.is_empty() {
+            println!("No clusters found");
+        }
+        for (rank, unit) in suggestions.iter().take(limit).enumerate() {
+            println!(
+                "{:>3}. {} ({} symbols, cohesion {:.2}, {} internal / {} boundary edges)",
+                rank + 1,
+                unit.name,
+                unit.members.len(),
+                unit.cohesion,
+                unit.internal_edges,
+                unit.boundary_edges
+            );
+            for member in &unit.members {
+                println!("       {}", member);
+            }
+        }
+        if suggestions.len() > limit {
+            println!("... and {} more (use --limit)", suggestions.len() - limit);
+        }
+        return Ok(());
+    }
+
+    for &rank in accept {
+        let Some(unit) = rank.checked_sub(1).and_then(|i| suggestions.get(i)) else {
+            anyhow::bail!(
+                "No suggestion ranked {} ({} found)",
+                rank,
+                suggestions.len()
+            );
+        };
+        let base = format!("unit::{}", unit.name);
+        let id = (1..)
+            .map(|n| match n {
+                1 => base.clone(),
+                n => format!("{}-{}", base, n),
+            })
+            .find(|id| graph.get_by_id(id).is_none())
+            .unwrap_or(base);
+        migration::create_unit(&mut graph, &id, &unit.name, &unit.members)?;
+        println!("Created {} with {} members", id, unit.members.len());
+    }
+    store::commit(&root.join(&config.output.graph), &graph, "suggest-units")?;
+    Ok(())
+}
+
+fn suggest_links(root: &Path, config_path: Option<&Path>, accept: bool) -> anyhow::Result<()> {
+    let config = load_config(root, config_path)?;
+    if config.mapping.is_empty() {
+        anyhow::bail!("No [[mapping]] rules in the config");
+    }
+    let mut graph = load_for_update(root, &config)?;
+    let proposed = migration::propose_links(&graph, &config.mapping);
+    if proposed.is_empty() {
+        println!("No new links found");
+        return Ok(());
+    }
+
+    if !accept {
+        for link in &proposed {
+            let rule = &config.mapping[link.rule];
+            println!(
+                "{} -> {} ({} -> {})",
+                link.legacy, link.target, rule.from, rule.to
+            );
+        }
+        println!(
+            "{} links proposed; record them with --accept",
+            proposed.len()
+        );
+        return Ok(());
+    }
+
+    let mut linked = 0;
+    for link in &proposed {
+        match migration::link(&mut graph, &link.legacy, &link.target) {
+            Ok(_) => linked += 1,
+            Err(err) => eprintln!("Skipped {} -> {}: {}", link.legacy, link.target, err),
+        }
+    }
+    store::commit(&root.join(&config.output.graph), &graph, "suggest-links")?;
+    println!("Linked {} of {} proposed pairs", linked, proposed.len());
+    Ok(())
+}
+
+fn unresolved(root: &Path, config_path: Option<&Path>, list: bool) -> anyhow::Result<()> {
+    let report = load_resolved(root, config_path)?.unresolved;
+    let canonical_root = root.canonicalize()?;
+    let display = |file: &Path| {
+        file.strip_prefix(&canonical_root)
+            .unwrap_or(file)
+            .display()
+            .to_string()
+    };
+
+    if list {
+        for reference in &report.references {
+            let kind = match reference.kind {
+                ReferenceKind::Call => "call",
+                ReferenceKind::Import => "import",
+            };
+            println!(
+                "{}:{}: {} {} ({:?})",
+                display(&reference.file),
+                reference.line,
+                kind,
+                reference.name,
+                reference.reason
+            );
+        }
+        for shadowed in &report.shadowed {
+            println!(
+                "{}:{}: shadowed {} ({} -> {})",
+                display(&shadowed.file),
+                shadowed.line,
+                shadowed.name,
+                shadowed.previous,
+                shadowed.current
+            );
+        }
+        return Ok(());
+    }
+
+    let by_file = report.counts_by_file();
+    let calls: usize = by_file.values().map(|c| c.calls).sum();
+    println!(
+        "{} unresolved references ({} calls, {} imports)",
+        report.len(),
+        calls,
+        report.len() - calls
+    );
+    for (reason, count) in report.counts_by_reason() {
+        println!("  {:<14} {:>6}", format!("{:?}", reason), count);
+    }
+    if !report.shadowed.is_empty() {
+        println!(
+            "{} names shadowed around star imports (see --list)",
+            report.shadowed.len()
+        );
+    }
+    if by_file.is_empty() {
+        return Ok(());
+    }
+
+    println!();
+    let mut rows: Vec<_> = by_file.into_iter().collect();
+    rows.sort_by(|(a, ac), (b, bc)| bc.total().cmp(&ac.total()).then_with(|| a.cmp(b)));
+    println!("{:>5}  {:>7}  FILE", "CALLS", "IMPORTS");
+    for (file, counts) in rows {
+        println!(
+            "{:>5}  {:>7}  {}",
+            counts.calls,
+            counts.imports,
+            display(&file)
+        );
+    }
+    Ok(())
+}
+
+fn deps(root: &Path, config_path: Option<&Path>, packages: &[String]) -> anyhow::Result<()> {
+    let config = Config {
+        external_modules: true,
+        ..load_config(root, config_path)?
+    };
+    let (mut output, diagnostics) = resolve::resolve_directory_lenient(root, &config);
+    for diagnostic in &diagnostics {
+        print_diagnostic(diagnostic);
+    }
+    scope(&mut output.graph.graph);
+    let graph = &output.graph.graph;
+
+    if !packages.is_empty() {
+        let names: Vec<&str> = packages.iter().map(String::as_str).collect();
+        for id in deps::users_of(graph, &names) {
+            println!("{}", id);
+        }
+        return Ok(());
+    }
+
+    let mut rows: Vec<(&Node, usize)> = graph
+        .node_indices()
+        .filter_map(|idx| {
+            let node = graph.node_weight(idx)?;
+            node.external
+                .then(|| (node, graph.neighbors(idx, Direction::Incoming).count()))
+        })
+        .collect();
+    rows.sort_by(|(a, au), (b, bu)| bu.cmp(au).then_with(|| a.name.cmp(&b.name)));
+
+    let width = rows
+        .iter()
+        .map(|(n, _)| n.name.len())
+        .max()
+        .unwrap_or(0)
+        .max(6);
+    println!("{:<width$}  {:>5}  PACKAGE", "IMPORT", "USERS");
+    for (node, users) in rows {
+        let package = match &node.package {
+            Some(p) => format!("{} {}", p.name, p.version.as_deref().unwrap_or("")),
+            None => "-".to_string(),
+        };
+        println!(
+            "{:<width$}  {:>5}  {}",
+            node.name,
+            users,
+            package.trim_end()
+        );
+    }
+    Ok(())
+}
+
+/// Write the parsed graph as a single `GraphData` JSON document
+fn scan_json(
+    root: &Path,
+    config_path: Option<&Path>,
+    output: Option<&Path>,
+    depth: ScanDepth,
+    enrichment: Enrichment,
+    strict: bool,
+) -> anyhow::Result<()> {
+    let graph = load_enriched(root, config_path, depth, enrichment, strict)?;
+    let data = GraphData::from(&graph);
+
+    let mut out: Box<dyn Write> = match output {
+        Some(path) => Box::new(std::io::BufWriter::new(std::fs::File::create(path)?)),
+        None => Box::new(std::io::BufWriter::new(std::io::stdout().lock())),
+    };
+    serde_json::to_writer_pretty(&mut out, &data)?;
+    writeln!(out)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// What [`load_enriched()`] adds to the scanned graph
+#[derive(Debug, Clone, Copy)]
+struct Enrichment {
+    /// Last change and top authors per symbol, from git blame
+    blame: bool,
+    /// Recent commit counts per symbol, from git log
+    churn: bool,
+    /// When and where the scan ran (see [`resolve::record_provenance()`])
+    provenance: bool,
+    /// Statuses and links from the state file and source annotations, as
+    /// [`load_tracked()`] applies them (symbol scans only)
+    tracked: bool,
+}
+
+/// The project graph at `depth`, with the requested `enrichment` applied
+///
+/// With `strict`, the first file that fails to parse is an error, as in
+/// [`scan()`]; tracked graphs are always read leniently.
+fn load_enriched(
+    root: &Path,
+    config_path: Option<&Path>,
+    ScanDepth { level, granularity }: ScanDepth,
+    enrichment: Enrichment,
+    strict: bool,
+) -> anyhow::Result<Graph> {
+    let mut graph = match level {
+        ScanLevel::Symbols if enrichment.tracked => {
+            let config = load_scan_config(root, config_path, granularity)?;
+            load_view(root, &config)?
+        }
+        ScanLevel::Symbols if strict => {
+            let config = load_scan_config(root, config_path, granularity)?;
+            let mut output = resolve::resolve_directory_with_config(root, &config)?;
+            scope(&mut output.graph.graph);
+            output.graph.graph
+        }
+        ScanLevel::Symbols => {
+            let config = load_scan_config(root, config_path, granularity)?;
+            resolve_lenient(root, &config).graph.graph
+        }
+        ScanLevel::Imports => {
+            let config = load_config(root, config_path)?;
+            let mut output = if strict {
+                resolve::resolve_imports(root, &config)?
+            } else {
+                let (output, diagnostics) = resolve::resolve_imports_lenient(root, &config);
+                for diagnostic in &diagnostics {
+                    print_diagnostic(diagnostic);
+                }
+                output
+            };
+            scope(&mut output.graph.graph);
+            output.graph.graph
+        }
+    };
+    if enrichment.provenance {
+        resolve::record_provenance(&mut graph, root, &load_config(root, config_path)?);
+    }
+    if let Some(url) = REMOTE.get() {
+        let mut metadata = graph.metadata().clone();
+        metadata.git_remote = Some(url.clone());
+        graph.set_metadata(metadata);
+    }
+    if enrichment.blame {
+        enrich::blame(&mut graph)?;
+    }
+    if enrichment.churn {
+        let days = load_config(root, config_path)?.enrich.churn_days;
+        enrich::churn(&mut graph, days)?;
+    }
+    // Last, as blame and churn read the files through the absolute paths
+    if load_config(root, config_path)?.relative_ids {
+        parser::relativize_file_paths(&mut graph, root);
+    }
+    Ok(graph)
+}
+
+/// Write the graph [`scan_json()`] writes as JSON Lines, metadata first
+fn scan_jsonl(
+    root: &Path,
+    config_path: Option<&Path>,
+    output: Option<&Path>,
+    depth: ScanDepth,
+    enrichment: Enrichment,
+    strict: bool,
+) -> anyhow::Result<()> {
+    // Diagnostics go to stderr, so stdout stays clean for piping
+    let graph = load_enriched(root, config_path, depth, enrichment, strict)?;
+
+    let out: Box<dyn Write> = match output {
+        Some(path) => Box::new(std::io::BufWriter::new(std::fs::File::create(path)?)),
+        None => Box::new(std::io::BufWriter::new(std::io::stdout().lock())),
+    };
+    let mut writer = jsonl::JsonlWriter::new(out);
+    writer.write_graph(&graph)?;
+    writer.into_inner()?;
+    Ok(())
+}
+
+/// [`scan_jsonl()`] without resolving the project first: each file's records
+/// are written as it is parsed, so calls and imports into other files are
+/// left out
+fn scan_jsonl_stream(
+    root: &Path,
+    config_path: Option<&Path>,
+    output: Option<&Path>,
+    granularity: Option<GranularityArg>,
+    strict: bool,
+) -> anyhow::Result<()> {
+    let config = load_scan_config(root, config_path, granularity)?;
+    let out: Box<dyn Write> = match output {
+        Some(path) => Box::new(std::io::BufWriter::new(std::fs::File::create(path)?)),
+        None => Box::new(std::io::BufWriter::new(std::io::stdout().lock())),
+    };
+    let diagnostics = jsonl::stream_discovered(root, &config, strict, out)?;
+    for diagnostic in &diagnostics {
+        print_diagnostic(diagnostic);
+    }
+    Ok(())
+}
+
+fn rename(
+    root: &Path,
+    config_path: Option<&Path>,
+    old: &str,
+    new: &str,
+    file: bool,
+) -> anyhow::Result<()> {
+    let config = load_config(root, config_path)?;
+    let state = root.join(&config.output.graph);
+    migrate_state(root, &config)?;
+
+    let map = if file {
+        let tracked = store::load(&state)?;
+        parser::file_move_map(tracked.ids(), old, new)
+    } else {
+        HashMap::from([(old.to_string(), new.to_string())])
+    };
+    let renamed = store::remap_ids(&state, &map)?;
+    println!("Renamed {} node(s) in {}", renamed, state.display());
+    Ok(())
+}
+
+fn set_status(
+    root: &Path,
+    config_path: Option<&Path>,
+    id: &str,
+    status: MigrationStatus,
+) -> anyhow::Result<()> {
+    let config = load_config(root, config_path)?;
+    let emitter = Emitter::from_config(&config, root)?;
+    let mut graph = load_for_update(root, &config)?;
+    let previous = migration::transition(&mut graph, id, status)?;
+    store::commit(&root.join(&config.output.graph), &graph, "set-status")?;
+    println!("{}: {:?} -> {:?}", id, previous, status);
+    if previous != status {
+        emit_status_event(&emitter, &graph, id);
+    }
+    Ok(())
+}
+
+/// Revert the latest journal entry that is not an undo and not undone yet
+fn undo(root: &Path, config_path: Option<&Path>) -> anyhow::Result<()> {
+    let config = load_config(root, config_path)?;
+    let emitter = Emitter::from_config(&config, root)?;
+    let state = root.join(&config.output.graph);
+    let entries = journal::load(&store::journal_path(&state))?;
+    let Some(entry) = journal::last_undoable(&entries) else {
+        println!("Nothing to undo");
+        return Ok(());
+    };
+    let renames = entry
+        .changes
+        .iter()
+        .any(|change| matches!(change, journal::Change::Rename { .. }));
+    let mut graph = load_for_update(root, &config)?;
+    if renames {
+        if store::undo_renames(&state, entry)? == 0 {
+            anyhow::bail!(
+                "cannot undo #{} ({}): none of its renamed nodes are in {}",
+                entry.seq,
+                entry.command,
+                state.display()
+            );
+        }
+    } else {
+        journal::revert(&mut graph, entry)
+            .with_context(|| format!("cannot undo #{} ({})", entry.seq, entry.command))?;
+        store::commit_undo(&state, &graph, entry)?;
+    }
+    println!(
+        "Undid #{} ({}, {})",
+        entry.seq,
+        entry.command,
+        migration_progress::format_date(entry.at)
+    );
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    for change in entry.changes.iter().rev() {
+        let inverse = change.inverse();
+        println!("  {}", inverse);
+        // Reverting pops the history entry, so the event comes from the change itself
+        if let journal::Change::Status { id, from, to } = &inverse {
+            let name = graph
+                .get_by_id(id)
+                .and_then(|idx| graph.node_weight(idx))
+                .map_or_else(String::new, |node| node.name.clone());
+            let event = StatusEvent {
+                id: id.clone(),
+                name,
+                from: *from,
+                to: *to,
+                at: now,
+            };
+            if let Err(err) = emitter.emit(&event) {
+                eprintln!("warning: status event for {} not delivered: {}", id, err);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Print the journaled changes involving `id`
+fn history(root: &Path, config_path: Option<&Path>, id: &str) -> anyhow::Result<()> {
+    let config = load_config(root, config_path)?;
+    let state = root.join(&config.output.graph);
+    let entries = journal::load(&store::journal_path(&state))?;
+    let undone: Vec<u64> = entries.iter().filter_map(|entry| entry.undoes).collect();
+    let changes = journal::history(&entries, id);
+    if changes.is_empty() {
+        println!("No recorded changes to {}", id);
+        return Ok(());
+    }
+    for (entry, change) in changes {
+        let mut note = match entry.undoes {
+            Some(seq) => format!(" (undoing #{})", seq),
+            None => String::new(),
+        };
+        if undone.contains(&entry.seq) {
+            note.push_str(" (undone)");
+        }
+        println!(
+            "#{} {} {}: {}{}",
+            entry.seq,
+            migration_progress::format_date(entry.at),
+            entry.command,
+            change,
+            note
+        );
+    }
+    Ok(())
+}
+
+/// Move every project symbol matching `--filter` to `status`, or none of them
+fn set_status_matching(
+    root: &Path,
+    config_path: Option<&Path>,
+    status: MigrationStatus,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let filter = FILTER
+        .get()
+        .context("set-status needs a node ID or --filter")?;
+    let config = load_config(root, config_path)?;
+    let emitter = Emitter::from_config(&config, root)?;
+    let mut graph = load_for_update(root, &config)?;
+
+    let mut changes = Vec::new();
+    let mut illegal = Vec::new();
+    let mut unchanged = 0;
+    for node in graph.nodes() {
+        if !queries::migration::is_project_symbol(node) || !filter.matches(node) {
+            continue;
+        }
+        if node.status == status {
+            unchanged += 1;
+        } else if node.status.can_transition_to(status) {
+            changes.push((node.id.clone(), node.status));
+        } else {
+            illegal.push((node.id.clone(), node.status));
+        }
+    }
+    changes.sort();
+    illegal.sort();
+
+    for (id, from) in &changes {
+        println!("{}: {:?} -> {:?}", id, from, status);
+    }
+    for (id, from) in &illegal {
+        eprintln!("{}: cannot move from {:?} to {:?}", id, from, status);
+    }
+    if unchanged > 0 {
+        println!("{} matching symbols are already {:?}", unchanged, status);
+    }
+    if dry_run {
+        println!(
+            "{} symbols would change (dry run, nothing saved)",
+            changes.len()
+        );
+        return Ok(());
+    }
+    if !illegal.is_empty() {
+        anyhow::bail!(
+            "{} symbols cannot move to {:?}; nothing changed",
+            illegal.len(),
+            status
+        );
+    }
+    if changes.is_empty() {
+        println!("No symbols to change");
+        return Ok(());
+    }
+
+    let ids: Vec<&str> = changes.iter().map(|(id, _)| id.as_str()).collect();
+    migration::transition_all(&mut graph, &ids, status)?;
+    store::commit(&root.join(&config.output.graph), &graph, "set-status")?;
+    println!("Changed {} symbols to {:?}", ids.len(), status);
+    for id in ids {
+        emit_status_event(&emitter, &graph, id);
+    }
+    Ok(())
+}
+
+/// Send the latest status change of `id` to `emitter`, warning on failure
+///
+/// The change is already saved, so a failed delivery does not fail the command.
+fn emit_status_event(emitter: &Emitter, graph: &Graph, id: &str) {
+    let Some(event) = StatusEvent::latest(graph, id) else {
+        return;
+    };
+    if let Err(err) = emitter.emit(&event) {
+        eprintln!("warning: status event for {} not delivered: {}", id, err);
+    }
+}
+
+fn frontier(
+    root: &Path,
+    config_path: Option<&Path>,
+    coverage: Option<&Path>,
+    max_coverage: Option<f64>,
+    effort: bool,
+) -> anyhow::Result<()> {
+    let config = load_config(root, config_path)?;
+    let mut graph = load_view(root, &config)?;
+    if effort {
+        let annotated = enrich::estimate_effort(&mut graph);
+        tracing::info!(annotated, "effort");
+    }
+    let report = coverage.map(Path::to_path_buf).or_else(|| {
+        config
+            .enrich
+            .coverage
+            .as_ref()
+            .map(|r| config.resolve_path(root, r))
+    });
+    match &report {
+        Some(report) => {
+            enrich::coverage(&mut graph, report, root, config.path_resolver())
+                .with_context(|| format!("reading coverage from {}", report.display()))?;
+        }
+        None if max_coverage.is_some() => {
+            anyhow::bail!(
+                "--max-coverage needs a coverage report (--coverage or [enrich] coverage)"
+            )
+        }
+        None => {}
+    }
+
+    for id in queries::migration_frontier(&graph) {
+        let Some(node) = graph.get_by_id(&id).and_then(|idx| graph.node_weight(idx)) else {
+            continue;
+        };
+        // Symbols the report does not measure count as untested
+        let percent = node.coverage.unwrap_or(0.0);
+        if max_coverage.is_some_and(|max| percent > max) {
+            continue;
+        }
+        if effort {
+            match node.effort {
+                Some(estimate) => print!("{:>6.1}  ", estimate.score),
+                None => print!("{:>6}  ", "-"),
+            }
+        }
+        match (&report, node.coverage) {
+            (None, _) => println!("{}", id),
+            (Some(_), Some(percent)) => println!("{:>6.1}%  {}", percent, id),
+            (Some(_), None) => println!("{:>7}  {}", "-", id),
+        }
+    }
+    Ok(())
+}
+
+fn check(
+    root: &Path,
+    config_path: Option<&Path>,
+    format: CheckFormat,
+    output: Option<&Path>,
+    mode: BaselineMode,
+    changes: Option<&ChangeSource>,
+) -> anyhow::Result<()> {
+    let config = load_config(root, config_path)?;
+    let changed = changes
+        .map(|changes| changes.files(root, &config))
+        .transpose()?;
+    let mut graph = match &changed {
+        Some(files) if files.is_empty() => {
+            eprintln!("No changed files");
+            return Ok(());
+        }
+        Some(_) if !config.cache => {
+            eprintln!(
+                "hint: set `cache = true` in migrator.toml so only changed files are reparsed"
+            );
+            load_view(root, &config)?
+        }
+        // Unchanged files come back from the parse cache; only changed ones are reparsed
+        _ => load_view(root, &config)?,
+    };
+    if !config.rules.owner_order.is_empty() {
+        annotate_owners(&mut graph, root, &config)?;
+    }
+    let mut violations = rules::check(&graph, &config.rules, root)?;
+
+    let baseline_path = config.resolve_path(root, &config.rules.baseline);
+    match mode {
+        BaselineMode::Ignore => {}
+        BaselineMode::Update => {
+            let count = violations.len();
+            rules::Baseline::new(violations).save(&baseline_path)?;
+            println!(
+                "Wrote {} violation(s) to {}",
+                count,
+                baseline_path.display()
+            );
+            return Ok(());
+        }
+        BaselineMode::Ratchet => {
+            let ratchet = rules::Baseline::load(&baseline_path)?.compare(&violations);
+            if !ratchet.fixed.is_empty() {
+                eprintln!(
+                    "{} baseline violation(s) fixed; run `migrator check --update-baseline` to lock in the progress",
+                    ratchet.fixed.len()
+                );
+            }
+            violations = ratchet.new;
+        }
+    }
+
+    if let Some(files) = &changed {
+        let impact = impact::impact(&graph, files);
+        eprintln!(
+            "{} changed file(s): {} symbol(s) changed, {} dependent(s) impacted",
+            files.len(),
+            impact.changed.len(),
+            impact.dependents.len()
+        );
+        for unit in &impact.units {
+            eprintln!("  migration unit {}", unit);
+        }
+        violations.retain(|violation| impact.touches(violation));
+    }
+
+    let mut out: Box<dyn Write> = match output {
+        Some(path) => Box::new(std::io::BufWriter::new(std::fs::File::create(path)?)),
+        None => Box::new(std::io::BufWriter::new(std::io::stdout().lock())),
+    };
+    match format {
+        CheckFormat::Text => {
+            for violation in &violations {
+                writeln!(
+                    out,
+                    "{}:{}: [{}] {}",
+                    violation.file_path.display(),
+                    violation.line.unwrap_or(1),
+                    violation.rule.id(),
+                    violation.message
+                )?;
+            }
+            out.flush()?;
+        }
+        CheckFormat::Sarif => sarif::write(&violations, &mut out)?,
+    }
+
+    if !violations.is_empty() {
+        let new = if mode == BaselineMode::Ratchet {
+            "new "
+        } else {
+            ""
+        };
+        anyhow::bail!("{} {}rule violation(s)", violations.len(), new);
+    }
+    Ok(())
+}
+
+fn tests_for(root: &Path, config_path: Option<&Path>, id: &str) -> anyhow::Result<()> {
+    let config = load_config(root, config_path)?;
+    let mut graph = load_view(root, &config)?;
+    // A no-op when the config already links tests
+    testmap::link_tests(&mut graph);
+    let guarding = testmap::tests_for(&graph, id)?;
+
+    if guarding.is_empty() {
+        println!("No tests reach {}", id);
+        return Ok(());
+    }
+    for (title, tests) in [
+        ("Direct", &guarding.direct),
+        ("Through callers", &guarding.transitive),
+    ] {
+        if !tests.is_empty() {
+            println!("{} ({}):", title, tests.len());
+            for test in tests {
+                println!("  {}", test);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "snapshot")]
+fn query_node(path: &Path, id: &str) -> anyhow::Result<()> {
+    let snapshot = graph_migrator_core::snapshot::Snapshot::open(path)?;
+    let Some(node) = snapshot.find(id) else {
+        anyhow::bail!("No node with ID {} in {}", id, path.display());
+    };
+
+    println!(
+        "{} ({:?}, {:?})",
+        id,
+        snapshot.node_type(node),
+        snapshot.status(node)
+    );
+    match snapshot.line_range(node) {
+        Some((start, end)) => {
+            println!("  {}:{}-{}", snapshot.file_path(node).display(), start, end)
+        }
+        None => println!("  {}", snapshot.file_path(node).display()),
+    }
+    let outgoing: Vec<_> = snapshot.outgoing(node).collect();
+    let incoming: Vec<_> = snapshot.incoming(node).collect();
+    for (title, edges) in [("Outgoing", outgoing), ("Incoming", incoming)] {
+        if !edges.is_empty() {
+            println!("{} ({}):", title, edges.len());
+            for (other, edge_type) in edges {
+                println!("  [{:?}] {}", edge_type, snapshot.id(other));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn incomplete(root: &Path, config_path: Option<&Path>) -> anyhow::Result<()> {
+    let config = load_config(root, config_path)?;
+    let graph = load_view(root, &config)?;
+    let incomplete = queries::superseded_with_live_dependents(&graph);
+    if incomplete.is_empty() {
+        println!("No superseded symbols are still in use");
+        return Ok(());
+    }
+    for (id, dependents) in &incomplete {
+        println!("{} ({} live dependents)", id, dependents.len());
+        for dependent in dependents {
+            println!("  {}", dependent);
+        }
+    }
+    Ok(())
+}
+
+fn link(
+    root: &Path,
+    config_path: Option<&Path>,
+    legacy: &str,
+    target: &str,
+    remove: bool,
+) -> anyhow::Result<()> {
+    let config = load_config(root, config_path)?;
+    let mut graph = load_for_update(root, &config)?;
+    if remove {
+        if !migration::unlink(&mut graph, legacy, target)? {
+            anyhow::bail!("{} is not linked to {}", legacy, target);
+        }
+        println!("Unlinked {} -> {}", legacy, target);
+    } else {
+        migration::link(&mut graph, legacy, target)?;
+        println!("Linked {} -> {}", legacy, target);
+    }
+    store::commit(&root.join(&config.output.graph), &graph, "link")?;
+    Ok(())
+}
+
+fn links(root: &Path, config_path: Option<&Path>, unlinked_only: bool) -> anyhow::Result<()> {
+    let config = load_config(root, config_path)?;
+    let graph = load_view(root, &config)?;
+    let coverage = queries::migration_coverage(&graph);
+
+    if !unlinked_only {
+        println!(
+            "{} of {} legacy symbols linked ({:.1}%)",
+            coverage.linked.len(),
+            coverage.total(),
+            coverage.ratio() * 100.0
+        );
+        for (legacy, targets) in &coverage.linked {
+            println!("{} -> {}", legacy, targets.join(", "));
+        }
+        if !coverage.unlinked.is_empty() {
+            println!();
+            println!("Unlinked:");
+        }
+    }
+    for id in &coverage.unlinked {
+        println!("{}", id);
+    }
+    Ok(())
+}
+
+fn info(path: &Path, config_path: Option<&Path>) -> anyhow::Result<()> {
+    let file = if path.is_file() {
+        path.to_path_buf()
+    } else {
+        let config = load_config(path, config_path)?;
+        path.join(&config.output.graph)
+    };
+    let text = std::fs::read_to_string(&file)
+        .with_context(|| format!("failed to read {}", file.display()))?;
+    let data = GraphData::from_json(&text)
+        .with_context(|| format!("failed to load {}", file.display()))?;
+    let metadata = &data.metadata;
+    let unknown = || "unknown".to_string();
+
+    println!("Graph:         {}", file.display());
+    println!("Nodes:         {}", data.nodes.len());
+    println!("Edges:         {}", data.edges.len());
+    println!(
+        "Scanned:       {}",
+        metadata.scanned_at.map_or_else(unknown, |at| format!(
+            "{} ({})",
+            migration_progress::format_date(at),
+            at
+        ))
+    );
+    println!(
+        "Root:          {}",
+        metadata
+            .root
+            .as_ref()
+            .map_or_else(unknown, |root| root.display().to_string())
+    );
+    if let Some(remote) = &metadata.git_remote {
+        println!("Remote:        {}", remote);
+    }
+    println!(
+        "Commit:        {}",
+        metadata.git_commit.clone().unwrap_or_else(unknown)
+    );
+    println!(
+        "Languages:     {}",
+        if metadata.languages.is_empty() {
+            unknown()
+        } else {
+            metadata.languages.join(", ")
+        }
+    );
+    if !metadata.workspace_roots.is_empty() {
+        println!("Workspace:     {}", metadata.workspace_roots.join(", "));
+    }
+    println!(
+        "Config hash:   {}",
+        metadata.config_hash.clone().unwrap_or_else(unknown)
+    );
+    println!(
+        "Tool version:  {}",
+        metadata.tool_version.clone().unwrap_or_else(unknown)
+    );
+    println!("Schema:        {}", data.schema_version);
+    Ok(())
+}
+
+fn cache_command(action: CacheAction) -> anyhow::Result<()> {
+    match action {
+        CacheAction::Stats { path, config } => {
+            let config = load_config(&path, config.as_deref())?;
+            let cache = ParseCache::open(&ParseCache::project_dir(&path, &config));
+            let stats = cache.stats()?;
+            println!("Location:  {}", cache.dir().display());
+            println!("Version:   {}", cache::version_key());
+            println!("Enabled:   {}", if config.cache { "yes" } else { "no" });
+            println!("Entries:   {}", stats.entries);
+            println!("Size:      {}", human_bytes(stats.bytes as usize));
+        }
+        CacheAction::Clear { path, config } => {
+            let config = load_config(&path, config.as_deref())?;
+            let cache = ParseCache::open(&ParseCache::project_dir(&path, &config));
+            let freed = cache.clear()?;
+            println!(
+                "Cleared {} ({} freed)",
+                cache.dir().display(),
+                human_bytes(freed as usize)
+            );
+        }
+    }
+    Ok(())
+}
+
+fn merge(inputs: &[PathBuf], output: Option<&Path>) -> anyhow::Result<()> {
+    let mut combined = MultiFileGraph::new();
+    for input in inputs {
+        let text = std::fs::read_to_string(input)
+            .with_context(|| format!("failed to read {}", input.display()))?;
+        let data = GraphData::from_json(&text)
+            .with_context(|| format!("failed to load {}", input.display()))?;
+        let summary = combined.merge(MultiFileGraph::from_graph(Graph::try_from(data)?));
+        eprintln!(
+            "{}: {} nodes added, {} already present, {} edges added",
+            input.display(),
+            summary.nodes_added,
+            summary.nodes_shared,
+            summary.edges_added
+        );
+    }
+
+    let mut out: Box<dyn Write> = match output {
+        Some(path) => Box::new(std::io::BufWriter::new(std::fs::File::create(path)?)),
+        None => Box::new(std::io::BufWriter::new(std::io::stdout().lock())),
+    };
+    serde_json::to_writer_pretty(&mut out, &GraphData::from(&combined.graph))?;
+    writeln!(out)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// `lang` if given, otherwise the language `file`'s extension maps to, or Python
+fn file_language(file: &Path, lang: Option<Language>) -> Language {
+    lang.or_else(|| Language::detect(file, &BTreeMap::new()))
+        .unwrap_or(Language::Python)
+}
+
+fn debug_command(action: DebugAction) -> anyhow::Result<()> {
+    match action {
+        DebugAction::Ast { file, lang } => {
+            let language = file_language(&file, lang.map(Language::from));
+            let mut session = ParseSession::for_file(&file, language);
+            let parsed = session.parse(&file)?;
+            print!("{}", debug::format_tree(&parsed.tree, &parsed.source));
+        }
+        DebugAction::Symbols {
+            file,
+            lang,
+            granularity,
+        } => {
+            let language = file_language(&file, lang.map(Language::from));
+            let mut session = ParseSession::for_file(&file, language)
+                .with_granularity(granularity.map(GranularityLevel::from));
+            let decisions = debug::explain_symbols(&mut session, &file)?;
+            let mut skipped = 0;
+            for decision in &decisions {
+                let outcome = match &decision.outcome {
+                    debug::Outcome::Extracted { id, node_type } => {
+                        format!("{:?} {}", node_type, id)
+                    }
+                    debug::Outcome::Skipped(reason) => {
+                        skipped += 1;
+                        format!("skipped: {}", reason)
+                    }
+                };
+                println!(
+                    "{:>5}  {:<30} {:<24} {}",
+                    decision.line,
+                    decision.kind,
+                    decision.name.as_deref().unwrap_or("-"),
+                    outcome
+                );
+            }
+            println!(
+                "{} definitions: {} extracted, {} skipped",
+                decisions.len(),
+                decisions.len() - skipped,
+                skipped
+            );
+        }
+    }
+    Ok(())
+}
+
+/// `migrator parse` output: what pass 1 extracts from one source
+#[derive(serde::Serialize)]
+struct ParsedSource {
+    path: PathBuf,
+    language: Language,
+    nodes: Vec<Node>,
+    edges: Vec<(String, String, Edge)>,
+    imports: Vec<ImportStatement>,
+}
+
+/// Parse `file`, or stdin when there is none, without reading anything else
+fn parse_source(
+    file: Option<&Path>,
+    lang: Option<Language>,
+    name: Option<PathBuf>,
+    output: Option<&Path>,
+) -> anyhow::Result<()> {
+    let (source, language, default_name) = match file {
+        Some(file) => {
+            let source = std::fs::read_to_string(file)
+                .with_context(|| format!("failed to read {}", file.display()))?;
+            (source, file_language(file, lang), file.to_path_buf())
+        }
+        None => {
+            let mut source = String::new();
+            std::io::Read::read_to_string(&mut std::io::stdin(), &mut source)
+                .context("failed to read stdin")?;
+            let language = lang.unwrap_or(Language::Python);
+            let name = format!("<stdin>.{}", language.extensions()[0]);
+            (source, language, PathBuf::from(name))
+        }
+    };
+    let path = name.unwrap_or(default_name);
+
+    // The source is handed to the session, so only `path`'s extension matters
+    let mut session = ParseSession::for_file(&path, language);
+    session.add_source(&path, source)?;
+    let multi = parser::parse_files_in(&mut session, &[&path])?;
+    let imports = import::extract_imports_in(&mut session, &path)?;
+    let data = GraphData::from(&multi.graph);
+    let parsed = ParsedSource {
+        path,
+        language,
+        nodes: data.nodes,
+        edges: data.edges,
+        imports,
+    };
+
+    let mut out: Box<dyn Write> = match output {
+        Some(path) => Box::new(std::io::BufWriter::new(std::fs::File::create(path)?)),
+        None => Box::new(std::io::BufWriter::new(std::io::stdout().lock())),
+    };
+    serde_json::to_writer_pretty(&mut out, &parsed)?;
+    writeln!(out)?;
+    out.flush()?;
+    Ok(())
+}
+
+fn rewrite_ids(input: &Path, root: &Path, output: Option<&Path>) -> anyhow::Result<()> {
+    let reader = std::io::BufReader::new(std::fs::File::open(input)?);
+    let out: Box<dyn Write> = match output {
+        Some(path) => Box::new(std::io::BufWriter::new(std::fs::File::create(path)?)),
+        None => Box::new(std::io::BufWriter::new(std::io::stdout().lock())),
+    };
+    let rewritten = jsonl::rewrite_ids(reader, out, root)?;
+    tracing::info!(rewritten, "rewrote node IDs");
+    Ok(())
+}
+
+/// Print a diagnostics summary followed by one line per diagnostic
+fn print_diagnostics(diagnostics: &[ParseDiagnostic]) {
+    if diagnostics.is_empty() {
+        return;
+    }
+
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    println!(
+        "{} files skipped, {} files with warnings",
+        errors,
+        diagnostics.len() - errors
+    );
+    for diagnostic in diagnostics {
+        print_diagnostic(diagnostic);
+    }
+}
+
+fn print_diagnostic(diagnostic: &ParseDiagnostic) {
+    let label = match diagnostic.severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    };
+    eprintln!(
+        "{}: {}: {}",
+        label,
+        diagnostic.file.display(),
+        diagnostic.error
     );
 }
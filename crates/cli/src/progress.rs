@@ -0,0 +1,122 @@
+//! Terminal output for core's tracing instrumentation
+//!
+//! Core only emits `tracing` spans and events; this module decides what the
+//! user sees. By default that is a progress bar driven by the
+//! `parse_files` / `parse_file` spans plus warnings. `-v` swaps the bar for
+//! log lines (info, then debug, then trace with span timings); `--quiet`
+//! shows errors only.
+
+use indicatif::{ProgressBar, ProgressStyle};
+use std::io::IsTerminal;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_subscriber::filter::{filter_fn, LevelFilter, Targets};
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+
+/// Install the global subscriber for the requested verbosity
+pub fn init(verbose: u8, quiet: bool) {
+    let level = match (quiet, verbose) {
+        (true, _) => LevelFilter::ERROR,
+        (false, 0) => LevelFilter::WARN,
+        (false, 1) => LevelFilter::INFO,
+        (false, 2) => LevelFilter::DEBUG,
+        (false, _) => LevelFilter::TRACE,
+    };
+    let span_events = if verbose >= 3 {
+        FmtSpan::CLOSE
+    } else {
+        FmtSpan::NONE
+    };
+
+    let bar = if quiet || verbose > 0 || !std::io::stderr().is_terminal() {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new(0).with_style(
+            ProgressStyle::with_template(
+                "{spinner} [{elapsed_precise}] {bar:40} {pos}/{len} {wide_msg}",
+            )
+            .expect("valid progress template"),
+        )
+    };
+
+    let log_layer = tracing_subscriber::fmt::layer()
+        .with_writer(std::io::stderr)
+        .with_target(false)
+        .with_span_events(span_events)
+        .with_filter(
+            // Dependencies' own debug output would drown ours
+            Targets::new()
+                .with_target("graph_migrator_core", level)
+                .with_target("graph_migrator_cli", level)
+                .with_default(level.min(LevelFilter::WARN)),
+        );
+    let progress_layer = ProgressLayer { bar }.with_filter(filter_fn(|metadata| {
+        metadata.is_span() && matches!(metadata.name(), "parse_files" | "parse_file")
+    }));
+
+    tracing_subscriber::registry()
+        .with(log_layer)
+        .with(progress_layer)
+        .init();
+}
+
+/// Advances a progress bar as core opens and closes parse spans
+struct ProgressLayer {
+    bar: ProgressBar,
+}
+
+impl<S> Layer<S> for ProgressLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+        let mut fields = SpanFields::default();
+        attrs.record(&mut fields);
+        match attrs.metadata().name() {
+            "parse_files" => {
+                self.bar.reset();
+                self.bar.set_length(fields.files.unwrap_or(0));
+            }
+            "parse_file" => {
+                if let Some(path) = fields.path {
+                    self.bar.set_message(path);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        match ctx.span(&id).map(|span| span.name()) {
+            Some("parse_file") => self.bar.inc(1),
+            Some("parse_files") => self.bar.finish_and_clear(),
+            _ => {}
+        }
+    }
+}
+
+/// The span fields the progress bar cares about
+#[derive(Default)]
+struct SpanFields {
+    files: Option<u64>,
+    path: Option<String>,
+}
+
+impl Visit for SpanFields {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == "files" {
+            self.files = Some(value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "path" {
+            self.path = Some(format!("{:?}", value));
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! `migrator serve`: a read-only HTTP API over the project graph
+//!
+//! Dashboards query migration state without shelling out to the CLI:
+//!
+//! - `GET /nodes` - every node, with its migration status
+//! - `GET /nodes/{id}/dependents` - direct and transitive dependents of a node
+//! - `GET /stats` - graph metrics and node counts per status
+//! - `GET /subgraph?root={id}&depth={n}` - the neighborhood of a node as `GraphData`
+//!
+//! Node IDs in paths and query strings are percent-decoded; IDs containing
+//! `/` may be sent unencoded. The graph is scanned once at startup, with
+//! statuses and links overlaid from the state file (`[output] graph`). Every
+//! response is JSON; errors are `{"error": "..."}` with a 4xx status.
+
+use graph_migrator_core::config::Config;
+use graph_migrator_core::queries::{self, Direction};
+use graph_migrator_core::{Graph, GraphData, GraphMigratorError, MigrationStatus};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Neighborhood depth for `/subgraph` when `depth` is not given
+const DEFAULT_DEPTH: usize = 1;
+
+/// Scan `root` and answer requests on `host:port` until the process is killed
+pub fn run(root: &Path, config: &Config, host: &str, port: u16) -> anyhow::Result<()> {
+    let graph = crate::load_view(root, config)?;
+
+    let server = Server::http((host, port)).map_err(|e| anyhow::anyhow!(e))?;
+    tracing::info!(nodes = graph.node_count(), "graph ready");
+    eprintln!("Serving {} on http://{}:{}", root.display(), host, port);
+
+    for request in server.incoming_requests() {
+        let (status, body) = if *request.method() == Method::Get {
+            route(&graph, request.url())
+        } else {
+            error(405, "only GET is supported")
+        };
+        tracing::debug!(url = request.url(), status, "request");
+        respond(request, status, &body);
+    }
+    Ok(())
+}
+
+/// Status code and JSON body for a GET of `url`
+fn route(graph: &Graph, url: &str) -> (u16, Value) {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let params = query_params(query);
+
+    if path == "/nodes" {
+        return (200, json!(GraphData::from(graph).nodes));
+    }
+    if path == "/stats" {
+        return (200, stats(graph));
+    }
+    if path == "/subgraph" {
+        return subgraph(graph, &params);
+    }
+    if let Some(id) = path
+        .strip_prefix("/nodes/")
+        .and_then(|rest| rest.strip_suffix("/dependents"))
+    {
+        return dependents(graph, &percent_decode(id));
+    }
+    error(404, &format!("no such endpoint: {}", path))
+}
+
+fn dependents(graph: &Graph, id: &str) -> (u16, Value) {
+    let Some(idx) = graph.find_node_by_id(id) else {
+        return not_found(id);
+    };
+    let direct: BTreeSet<&str> = graph
+        .edges_of(idx)
+        .filter(|(_, from, to, edge)| *to == idx && *from != idx && edge.edge_type.is_dependency())
+        .filter_map(|(_, from, _, _)| graph.node_weight(from))
+        .map(|node| node.id.as_str())
+        .collect();
+    match queries::closure(graph, id, Direction::Incoming, &[]) {
+        Ok(transitive) => (
+            200,
+            json!({ "id": id, "direct": direct, "transitive": transitive }),
+        ),
+        Err(err) => failure(err),
+    }
+}
+
+fn stats(graph: &Graph) -> Value {
+    let metrics = queries::metrics(graph);
+    let mut statuses: BTreeMap<MigrationStatus, usize> = BTreeMap::new();
+    for node in graph.nodes().filter(|node| !node.external) {
+        *statuses.entry(node.status).or_default() += 1;
+    }
+    json!({
+        "node_count": metrics.node_count,
+        "edge_count": graph.edge_count(),
+        "dependency_edge_count": metrics.dependency_edge_count,
+        "density": metrics.density,
+        "file_count": metrics.nodes_per_file.len(),
+        "longest_chain": metrics.longest_chain,
+        "statuses": statuses,
+    })
+}
+
+fn subgraph(graph: &Graph, params: &BTreeMap<String, String>) -> (u16, Value) {
+    let Some(root) = params.get("root") else {
+        return error(400, "missing query parameter: root");
+    };
+    let depth = match params.get("depth").map(|d| d.parse::<usize>()) {
+        None => DEFAULT_DEPTH,
+        Some(Ok(depth)) => depth,
+        Some(Err(_)) => return error(400, "depth must be a non-negative integer"),
+    };
+    match queries::subgraph(graph, root, depth, None) {
+        Ok(nearby) => (200, json!(GraphData::from(&nearby))),
+        Err(err) => failure(err),
+    }
+}
+
+fn failure(err: GraphMigratorError) -> (u16, Value) {
+    match err {
+        GraphMigratorError::NodeNotFound(id) => not_found(&id),
+        other => error(500, &other.to_string()),
+    }
+}
+
+fn not_found(id: &str) -> (u16, Value) {
+    error(404, &format!("node not found: {}", id))
+}
+
+fn error(status: u16, message: &str) -> (u16, Value) {
+    (status, json!({ "error": message }))
+}
+
+fn respond(request: Request, status: u16, body: &Value) {
+    let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+        .expect("static header is valid");
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(content_type);
+    if let Err(err) = request.respond(response) {
+        tracing::warn!(%err, "failed to send response");
+    }
+}
+
+/// `a=1&b=x%20y` as a map; later duplicates win
+fn query_params(query: &str) -> BTreeMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+/// Decode `%XX` escapes; malformed escapes are kept as written
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| text.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
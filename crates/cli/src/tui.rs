@@ -0,0 +1,349 @@
+//! `migrator tui`: browse the graph and track migration status in the terminal
+//!
+//! The left pane lists every project symbol with its status; `/` filters it
+//! by ID. The right pane shows the selected symbol's dependencies and
+//! dependents. Status keys (`m`, `b`, `i`, `p`, `s`) move the selected symbol
+//! through the status lifecycle and immediately write the graph back to the
+//! state file (`[output] graph`), where `lsp` and `serve` pick it up. Each
+//! change is also sent to the `[events]` sinks.
+
+use graph_migrator_core::config::Config;
+use graph_migrator_core::events::{Emitter, StatusEvent};
+use graph_migrator_core::{migration, store, Graph, MigrationStatus, Node};
+use petgraph::stable_graph::NodeIndex;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::path::{Path, PathBuf};
+
+/// Rows moved by PageUp/PageDown
+const PAGE: usize = 10;
+
+const HELP: &str =
+    "/ search  ↑↓ move  m migrated  b blocked  i in progress  p pending  s superseded  q quit";
+
+/// Scan `root`, then run the browser until the user quits
+pub fn run(root: &Path, config: &Config) -> anyhow::Result<()> {
+    let emitter = Emitter::from_config(config, root)?;
+    let graph = crate::load_for_update(root, config)?;
+    let mut app = App::new(graph, root, root.join(&config.output.graph), emitter);
+
+    let mut terminal = ratatui::init();
+    let result = app.event_loop(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+struct App {
+    graph: Graph,
+    /// Project root, stripped from displayed file paths
+    root: PathBuf,
+    state_path: PathBuf,
+    emitter: Emitter,
+    /// Project symbols, sorted by ID
+    symbols: Vec<NodeIndex>,
+    /// Symbols whose ID matches `query`
+    visible: Vec<NodeIndex>,
+    list: ListState,
+    query: String,
+    searching: bool,
+    /// Result of the last edit, shown in the footer
+    message: String,
+}
+
+impl App {
+    fn new(graph: Graph, root: &Path, state_path: PathBuf, emitter: Emitter) -> Self {
+        let mut symbols: Vec<NodeIndex> = graph
+            .node_indices()
+            .filter(|&idx| graph.node_weight(idx).is_some_and(|node| !node.external))
+            .collect();
+        symbols.sort_by(|&a, &b| {
+            let id = |idx| graph.node_weight(idx).map(|node| node.id.as_str());
+            id(a).cmp(&id(b))
+        });
+
+        let mut app = Self {
+            graph,
+            root: root.canonicalize().unwrap_or_else(|_| root.to_path_buf()),
+            state_path,
+            emitter,
+            visible: symbols.clone(),
+            symbols,
+            list: ListState::default(),
+            query: String::new(),
+            searching: false,
+            message: String::new(),
+        };
+        app.list.select((!app.visible.is_empty()).then_some(0));
+        app
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> anyhow::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !self.handle_key(key) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Apply a key press; returns `false` to quit
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if self.searching {
+            match key.code {
+                KeyCode::Char(c) => self.query.push(c),
+                KeyCode::Backspace => {
+                    self.query.pop();
+                }
+                KeyCode::Enter => self.searching = false,
+                KeyCode::Esc => {
+                    self.query.clear();
+                    self.searching = false;
+                }
+                _ => return true,
+            }
+            self.filter();
+            return true;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('/') => self.searching = true,
+            KeyCode::Down | KeyCode::Char('j') => self.move_by(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+            KeyCode::PageDown => self.move_by(PAGE as isize),
+            KeyCode::PageUp => self.move_by(-(PAGE as isize)),
+            KeyCode::Home => self.list.select_first(),
+            KeyCode::End => self.list.select_last(),
+            KeyCode::Char('m') => self.set_status(MigrationStatus::Migrated),
+            KeyCode::Char('b') => self.set_status(MigrationStatus::Blocked),
+            KeyCode::Char('i') => self.set_status(MigrationStatus::InProgress),
+            KeyCode::Char('p') => self.set_status(MigrationStatus::Pending),
+            KeyCode::Char('s') => self.set_status(MigrationStatus::Superseded),
+            _ => {}
+        }
+        true
+    }
+
+    /// Case-insensitive substring match on node IDs
+    fn filter(&mut self) {
+        let query = self.query.to_lowercase();
+        self.visible = self
+            .symbols
+            .iter()
+            .copied()
+            .filter(|&idx| {
+                self.graph
+                    .node_weight(idx)
+                    .is_some_and(|node| node.id.to_lowercase().contains(&query))
+            })
+            .collect();
+        self.list.select((!self.visible.is_empty()).then_some(0));
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        let Some(last) = self.visible.len().checked_sub(1) else {
+            return;
+        };
+        let current = self.list.selected().unwrap_or(0) as isize;
+        self.list
+            .select(Some((current + delta).clamp(0, last as isize) as usize));
+    }
+
+    fn selected(&self) -> Option<NodeIndex> {
+        self.list
+            .selected()
+            .and_then(|i| self.visible.get(i))
+            .copied()
+    }
+
+    /// Move the selected symbol to `status` and write the state file
+    fn set_status(&mut self, status: MigrationStatus) {
+        let Some((id, name)) = self
+            .selected()
+            .and_then(|idx| self.graph.node_weight(idx))
+            .map(|node| (node.id.clone(), node.name.clone()))
+        else {
+            return;
+        };
+        let previous = match migration::transition(&mut self.graph, &id, status) {
+            Ok(previous) => previous,
+            Err(err) => {
+                self.message = err.to_string();
+                return;
+            }
+        };
+        self.message = match store::commit(&self.state_path, &self.graph, "tui") {
+            Ok(_) => format!(
+                "{}: {:?} (saved to {})",
+                name,
+                status,
+                self.state_path.display()
+            ),
+            Err(err) => format!("{}: {:?} NOT SAVED: {}", name, status, err),
+        };
+        let event = StatusEvent::latest(&self.graph, &id).filter(|_| previous != status);
+        if let Some(Err(err)) = event.map(|event| self.emitter.emit(&event)) {
+            self.message = format!("{} (event not delivered: {})", self.message, err);
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [search, body, footer] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [list, detail] =
+            Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)])
+                .areas(body);
+
+        let search_style = if self.searching {
+            Style::new().fg(Color::Yellow)
+        } else {
+            Style::new()
+        };
+        frame.render_widget(
+            Paragraph::new(self.query.as_str()).block(
+                Block::new()
+                    .borders(Borders::ALL)
+                    .title(format!(
+                        " Search ({}/{}) ",
+                        self.visible.len(),
+                        self.symbols.len()
+                    ))
+                    .border_style(search_style),
+            ),
+            search,
+        );
+
+        let items: Vec<ListItem> = self
+            .visible
+            .iter()
+            .filter_map(|&idx| self.graph.node_weight(idx))
+            .map(|node| {
+                ListItem::new(Line::from(vec![
+                    status_span(node.status),
+                    Span::raw(" "),
+                    Span::raw(node.name.as_str()),
+                    Span::styled(
+                        format!("  {}", self.display_path(&node.file_path)),
+                        Style::new().fg(Color::DarkGray),
+                    ),
+                ]))
+            })
+            .collect();
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(Block::new().borders(Borders::ALL).title(" Symbols "))
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+            list,
+            &mut self.list,
+        );
+
+        frame.render_widget(
+            Paragraph::new(self.detail_lines())
+                .block(Block::new().borders(Borders::ALL).title(" Details "))
+                .wrap(Wrap { trim: false }),
+            detail,
+        );
+
+        let footer_text = if self.message.is_empty() {
+            HELP
+        } else {
+            &self.message
+        };
+        frame.render_widget(
+            Paragraph::new(footer_text).style(Style::new().fg(Color::DarkGray)),
+            footer,
+        );
+    }
+
+    fn detail_lines(&self) -> Vec<Line<'_>> {
+        let Some(idx) = self.selected() else {
+            return vec![Line::raw("No matching symbols")];
+        };
+        let Some(node) = self.graph.node_weight(idx) else {
+            return Vec::new();
+        };
+
+        let location = match node.line_range {
+            Some((start, _)) => format!("{}:{}", self.display_path(&node.file_path), start),
+            None => self.display_path(&node.file_path),
+        };
+        let mut lines = vec![
+            Line::from(Span::styled(
+                node.id.as_str(),
+                Style::new().add_modifier(Modifier::BOLD),
+            )),
+            Line::raw(format!("{:?} in {}", node.node_type, location)),
+            Line::from(vec![Span::raw("Status: "), status_span(node.status)]),
+        ];
+
+        let (dependencies, dependents) = self.neighbors(idx);
+        for (title, group) in [("Depends on", dependencies), ("Used by", dependents)] {
+            lines.push(Line::raw(""));
+            lines.push(Line::raw(format!("{} ({})", title, group.len())));
+            for neighbor in group {
+                lines.push(Line::from(vec![
+                    Span::raw("  "),
+                    status_span(neighbor.status),
+                    Span::raw(format!(" {}", neighbor.id)),
+                ]));
+            }
+        }
+        lines
+    }
+
+    /// Targets and sources of the node's dependency edges, deduplicated
+    fn neighbors(&self, idx: NodeIndex) -> (Vec<&Node>, Vec<&Node>) {
+        let mut dependencies = Vec::new();
+        let mut dependents = Vec::new();
+        for (_, from, to, edge) in self.graph.edges_of(idx) {
+            if !edge.edge_type.is_dependency() || from == to {
+                continue;
+            }
+            if from == idx {
+                dependencies.push(to);
+            } else {
+                dependents.push(from);
+            }
+        }
+        let nodes = |mut indices: Vec<NodeIndex>| {
+            indices.sort();
+            indices.dedup();
+            let mut nodes: Vec<&Node> = indices
+                .into_iter()
+                .filter_map(|i| self.graph.node_weight(i))
+                .collect();
+            nodes.sort_by(|a, b| a.id.cmp(&b.id));
+            nodes
+        };
+        (nodes(dependencies), nodes(dependents))
+    }
+
+    fn display_path(&self, path: &Path) -> String {
+        path.strip_prefix(&self.root)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    }
+}
+
+/// One-character status marker, colored by status
+fn status_span(status: MigrationStatus) -> Span<'static> {
+    let (marker, color) = match status {
+        MigrationStatus::Pending => ("·", Color::DarkGray),
+        MigrationStatus::InProgress => ("~", Color::Yellow),
+        MigrationStatus::Blocked => ("!", Color::Red),
+        MigrationStatus::Migrated => ("✓", Color::Green),
+        MigrationStatus::Superseded => ("×", Color::Blue),
+    };
+    Span::styled(marker, Style::new().fg(color))
+}
@@ -0,0 +1,58 @@
+//! Timing for Python call-edge extraction on a large, deeply nested file
+//!
+//! Run with `cargo bench -p graph-migrator-core --bench call_extraction`.
+//! Prints the mean time per `parse_file` call; there is no statistical
+//! harness, so compare runs on an otherwise idle machine.
+
+use graph_migrator_core::parser::python;
+use std::fmt::Write as _;
+use std::time::Instant;
+
+const FUNCTIONS: usize = 2_000;
+const NESTING: usize = 12;
+const ITERATIONS: usize = 10;
+
+/// Top-level functions whose calls sit `NESTING` blocks deep, plus a class per
+/// ten functions so method calls (which have no caller node) are exercised too
+fn source() -> String {
+    let mut source = String::new();
+    for i in 0..FUNCTIONS {
+        writeln!(source, "def f{}(x):", i).unwrap();
+        for depth in 1..=NESTING {
+            writeln!(source, "{}if x > {}:", "    ".repeat(depth), depth).unwrap();
+        }
+        let indent = "    ".repeat(NESTING + 1);
+        writeln!(source, "{}f{}(x)", indent, (i + 1) % FUNCTIONS).unwrap();
+        writeln!(source, "{}helper.run(f{}(x))", indent, i / 2).unwrap();
+        writeln!(source, "    return len(x)\n").unwrap();
+        if i % 10 == 0 {
+            writeln!(
+                source,
+                "class C{}:\n    def m(self):\n        f{}(self)\n",
+                i, i
+            )
+            .unwrap();
+        }
+    }
+    source
+}
+
+fn main() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let path = dir.path().join("big.py");
+    std::fs::write(&path, source()).expect("write source");
+
+    // Warm up the page cache and allocator
+    let graph = python::parse_file(&path).expect("parse");
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        python::parse_file(&path).expect("parse");
+    }
+    let mean = start.elapsed() / ITERATIONS as u32;
+    println!(
+        "call_extraction: {} nodes, {} edges, {:.2?} per parse",
+        graph.node_count(),
+        graph.edge_count(),
+        mean
+    );
+}
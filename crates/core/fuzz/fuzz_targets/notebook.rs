@@ -0,0 +1,23 @@
+//! Pass 1 on arbitrary text read as a Jupyter notebook
+//!
+//! Covers the JSON decoding and cell extraction that run before the Python
+//! parser sees a notebook's code:
+//!
+//! ```text
+//! cd crates/core
+//! cargo +nightly fuzz run notebook -- -rss_limit_mb=1024 -timeout=10
+//! ```
+
+#![no_main]
+
+use graph_migrator_core::parser::{self, ParseSession};
+use libfuzzer_sys::fuzz_target;
+use std::path::Path;
+
+fuzz_target!(|source: &str| {
+    let path = Path::new("fuzz.ipynb");
+    let mut session = ParseSession::new();
+    if session.add_source(path, source).is_ok() {
+        let _ = parser::parse_files_in(&mut session, &[path]);
+    }
+});
@@ -0,0 +1,33 @@
+//! Declaration outlines of arbitrary Rust and TypeScript source
+//!
+//! The first byte picks the grammar (Rust, TypeScript or TSX); the rest is
+//! the source:
+//!
+//! ```text
+//! cd crates/core
+//! cargo +nightly fuzz run outline -- -rss_limit_mb=1024 -timeout=10
+//! ```
+
+#![no_main]
+
+use graph_migrator_core::parser::{self, ParseSession};
+use libfuzzer_sys::fuzz_target;
+use std::path::Path;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&selector, source)) = data.split_first() else {
+        return;
+    };
+    let path = Path::new(match selector % 3 {
+        0 => "fuzz.rs",
+        1 => "fuzz.ts",
+        _ => "fuzz.tsx",
+    });
+    let mut session = ParseSession::new();
+    if session
+        .add_source(path, String::from_utf8_lossy(source))
+        .is_ok()
+    {
+        let _ = parser::parse_files_in(&mut session, &[path]);
+    }
+});
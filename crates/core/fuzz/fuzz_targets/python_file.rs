@@ -0,0 +1,37 @@
+//! Pass 1 on arbitrary bytes read as a Python file
+//!
+//! Covers decoding (non-UTF-8 and declared encodings), symbol, call and type
+//! reference extraction, and import extraction. Seed it with real code, for
+//! example the test fixtures, so mutations start from valid Python:
+//!
+//! ```text
+//! cd crates/core
+//! cargo +nightly fuzz run python_file fuzz/corpus/python_file tests/test-fixtures \
+//!     -- -rss_limit_mb=1024 -timeout=10
+//! ```
+//!
+//! `-rss_limit_mb` and `-timeout` turn runaway memory use or a hang on one
+//! input into a reported failure, like a panic.
+
+#![no_main]
+
+use graph_migrator_core::{import, parser::python};
+use libfuzzer_sys::fuzz_target;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// One file per fuzzing process, rewritten for every input
+fn scratch_file() -> &'static PathBuf {
+    static FILE: OnceLock<PathBuf> = OnceLock::new();
+    FILE.get_or_init(|| std::env::temp_dir().join(format!("fuzz-{}.py", std::process::id())))
+}
+
+fuzz_target!(|data: &[u8]| {
+    let path = scratch_file();
+    if std::fs::write(path, data).is_err() {
+        return;
+    }
+    // Errors are fine; panics, hangs and unbounded memory are not
+    let _ = python::parse_file_with_errors(path);
+    let _ = import::extract_imports(path);
+});
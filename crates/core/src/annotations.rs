@@ -0,0 +1,375 @@
+//! Migration annotations written in source comments
+//!
+//! Developers can record progress next to the code, where it is seen in
+//! review, instead of running `migrator set-status`:
+//!
+//! ```python
+//! # migrator: status=migrated unit=billing
+//! def total(invoice):
+//!     ...
+//!
+//! def render(invoice):  # migrator: status=in-progress
+//!     ...
+//! ```
+//!
+//! An annotation belongs to the definition on the same line, or to the
+//! definition right below its comment block.
+//! It holds space-separated `key=value` pairs:
+//!
+//! - `status` - a [`MigrationStatus`], written as for `set-status`
+//! - `unit` - a migration unit, by ID (`unit::billing`) or name (`billing`);
+//!   created if it does not exist yet
+//!
+//! [`scan()`] finds annotations and [`apply()`] makes them take effect.
+//! Apply them after the state file ([`store::apply()`](crate::store::apply())),
+//! so the code wins over older recorded statuses. Enabled by the
+//! `annotations` setting in `migrator.toml`.
+
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::{Graph, MigrationStatus, NodeType};
+use crate::migration;
+use crate::parser::{ParseDiagnostic, Severity};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Comment text that starts an annotation
+pub const MARKER: &str = "migrator:";
+
+/// One annotation, with the definition it belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    /// ID of the annotated node
+    pub node: String,
+    /// File containing the comment
+    pub file: PathBuf,
+    /// 1-indexed line of the comment
+    pub line: usize,
+    /// Requested status, if any
+    pub status: Option<MigrationStatus>,
+    /// Requested migration unit (ID or name), if any
+    pub unit: Option<String>,
+}
+
+/// What [`apply()`] changed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ApplySummary {
+    /// IDs of the nodes moved to a new status, in annotation order
+    pub statuses: Vec<String>,
+    /// Nodes newly assigned to a unit
+    pub assignments: usize,
+    /// Units created for names that did not exist
+    pub units_created: usize,
+}
+
+/// Comment prefix of a language's line comments, if annotations are supported
+fn comment_prefix(language: &str) -> Option<&'static str> {
+    match language {
+        "python" => Some("#"),
+        _ => None,
+    }
+}
+
+/// Find the annotations of every definition in `graph`
+///
+/// Source files are read again, relative to `root` if node paths are
+/// relative. Unreadable files are skipped; malformed annotations become
+/// warnings.
+pub fn scan(graph: &Graph, root: &Path) -> (Vec<Annotation>, Vec<ParseDiagnostic>) {
+    // Definitions by file, with their first line
+    let mut files: BTreeMap<&Path, Vec<(usize, &str, &'static str)>> = BTreeMap::new();
+    for node in graph.nodes().filter(|node| !node.external) {
+        let (Some((start, _)), Some(prefix)) = (node.line_range, comment_prefix(&node.language))
+        else {
+            continue;
+        };
+        // Notebook lines refer to the joined cells, not the file
+        if node.file_path.extension().is_some_and(|ext| ext == "ipynb") {
+            continue;
+        }
+        files
+            .entry(node.file_path.as_path())
+            .or_default()
+            .push((start, node.id.as_str(), prefix));
+    }
+
+    let mut annotations = Vec::new();
+    let mut diagnostics = Vec::new();
+    for (file, definitions) in files {
+        let path = if file.is_absolute() {
+            file.to_path_buf()
+        } else {
+            root.join(file)
+        };
+        let Ok(source) = std::fs::read_to_string(&path) else {
+            tracing::debug!(file = %path.display(), "unreadable, no annotations");
+            continue;
+        };
+        let lines: Vec<&str> = source.lines().collect();
+        for (start, id, prefix) in definitions {
+            for line in annotation_lines(&lines, start, prefix) {
+                let text = comment_text(lines[line - 1], prefix).unwrap_or_default();
+                match parse(text) {
+                    Ok((status, unit)) => annotations.push(Annotation {
+                        node: id.to_string(),
+                        file: file.to_path_buf(),
+                        line,
+                        status,
+                        unit,
+                    }),
+                    Err(error) => diagnostics.push(ParseDiagnostic {
+                        file: file.to_path_buf(),
+                        error: format!("line {}: {}", line, error),
+                        severity: Severity::Warning,
+                    }),
+                }
+            }
+        }
+    }
+    (annotations, diagnostics)
+}
+
+/// 1-indexed lines holding annotations for the definition starting at `start`
+///
+/// Looks at the definition line itself and the comment lines directly
+/// above it.
+fn annotation_lines(lines: &[&str], start: usize, prefix: &str) -> Vec<usize> {
+    let mut found = Vec::new();
+    let mut line = start;
+    while line > 1 {
+        let above = lines.get(line - 2).map_or("", |text| text.trim_start());
+        if !above.starts_with(prefix) {
+            break;
+        }
+        line -= 1;
+    }
+    for line in line..=start {
+        if lines
+            .get(line - 1)
+            .and_then(|text| comment_text(text, prefix))
+            .is_some()
+        {
+            found.push(line);
+        }
+    }
+    found
+}
+
+/// The annotation in `line` after the marker, if the line has one
+///
+/// Every comment prefix is tried, so one inside a string literal earlier
+/// on the line does not hide the comment.
+fn comment_text<'a>(line: &'a str, prefix: &str) -> Option<&'a str> {
+    line.match_indices(prefix)
+        .find_map(|(at, _)| line[at + prefix.len()..].trim_start().strip_prefix(MARKER))
+}
+
+/// Parse the `key=value` pairs of one annotation
+fn parse(text: &str) -> std::result::Result<(Option<MigrationStatus>, Option<String>), String> {
+    let mut status = None;
+    let mut unit = None;
+    for pair in text.split_whitespace() {
+        match pair.split_once('=') {
+            Some(("status", value)) => status = Some(value.parse::<MigrationStatus>()?),
+            Some(("unit", value)) if !value.is_empty() => unit = Some(value.to_string()),
+            _ => return Err(format!("unknown annotation `{}`", pair)),
+        }
+    }
+    if status.is_none() && unit.is_none() {
+        return Err("empty annotation; expected status=... or unit=...".to_string());
+    }
+    Ok((status, unit))
+}
+
+/// Apply `annotations` to `graph`
+///
+/// With `recorded_at`, statuses change through
+/// [`migration::transition_at()`], so each change is recorded in the node's
+/// history at that time. Without it they are only set: a graph that is not
+/// saved would otherwise get a fresh history entry every time it is loaded,
+/// until a command saves the change once. Setting the current status is a
+/// no-op either way. A transition the lifecycle forbids, or a unit name that
+/// is not a unit, becomes a warning and the rest still apply.
+///
+/// # Errors
+/// Returns an error only if a new unit cannot be created.
+pub fn apply(
+    graph: &mut Graph,
+    annotations: &[Annotation],
+    recorded_at: Option<u64>,
+) -> Result<(ApplySummary, Vec<ParseDiagnostic>)> {
+    let mut summary = ApplySummary::default();
+    let mut diagnostics = Vec::new();
+    let mut warn = |annotation: &Annotation, error: String| {
+        diagnostics.push(ParseDiagnostic {
+            file: annotation.file.clone(),
+            error: format!("line {}: {}", annotation.line, error),
+            severity: Severity::Warning,
+        });
+    };
+
+    for annotation in annotations {
+        if let Some(status) = annotation.status {
+            let changed = match recorded_at {
+                Some(at) => migration::transition_at(graph, &annotation.node, status, at),
+                None => set_status(graph, &annotation.node, status),
+            };
+            match changed {
+                Ok(previous) if previous != status => {
+                    summary.statuses.push(annotation.node.clone());
+                }
+                Ok(_) => {}
+                Err(err) => warn(annotation, err.to_string()),
+            }
+        }
+        let Some(unit) = &annotation.unit else {
+            continue;
+        };
+        let unit_id = if unit.contains("::") {
+            unit.clone()
+        } else {
+            format!("unit::{}", unit)
+        };
+        let existing = graph
+            .get_by_id(&unit_id)
+            .and_then(|idx| graph.node_weight(idx))
+            .map(|node| node.node_type == NodeType::MigrationUnit);
+        match existing {
+            None => {
+                let name = unit_id.rsplit("::").next().unwrap_or(&unit_id).to_string();
+                migration::create_unit(graph, &unit_id, &name, &[&annotation.node])?;
+                summary.units_created += 1;
+                summary.assignments += 1;
+            }
+            Some(true) => {
+                let members = migration::unit_members(graph, &unit_id);
+                if !members.contains(&annotation.node) {
+                    migration::assign(graph, &annotation.node, &unit_id)?;
+                    summary.assignments += 1;
+                }
+            }
+            Some(false) => warn(annotation, format!("{} is not a migration unit", unit_id)),
+        }
+    }
+    tracing::debug!(
+        statuses = summary.statuses.len(),
+        assignments = summary.assignments,
+        "applied annotations"
+    );
+    Ok((summary, diagnostics))
+}
+
+/// Move node `id` to `status` as the lifecycle allows, without recording it
+/// in the node's history; returns the previous status
+fn set_status(graph: &mut Graph, id: &str, status: MigrationStatus) -> Result<MigrationStatus> {
+    let node = graph
+        .get_by_id(id)
+        .and_then(|idx| graph.node_weight_mut(idx))
+        .ok_or_else(|| GraphMigratorError::NodeNotFound(id.to_string()))?;
+    let from = node.status;
+    if !from.can_transition_to(status) {
+        return Err(GraphMigratorError::IllegalTransition {
+            id: id.to_string(),
+            from,
+            to: status,
+        });
+    }
+    node.status = status;
+    Ok(from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_and_apply() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("app.py"),
+            "# migrator: status=migrated unit=billing\n\
+             def total():\n    pass\n\n\
+             # Renders the invoice\n\
+             # migrator: unit=billing\n\
+             def render(sep=\"#\"):  # migrator: status=in-progress\n    pass\n\n\
+             def plain():\n    pass\n\n\
+             # migrator: state=done\n\
+             def typo():\n    pass\n",
+        )
+        .unwrap();
+        let multi = crate::parser::parse_directory(dir.path()).unwrap();
+        let mut graph = multi.graph;
+        let id = |name: &str| {
+            graph
+                .nodes()
+                .find(|node| node.name == name)
+                .map(|node| node.id.clone())
+                .unwrap()
+        };
+        let (total, render, plain) = (id("total"), id("render"), id("plain"));
+
+        let (annotations, diagnostics) = scan(&graph, dir.path());
+        let found: Vec<(&str, usize)> = annotations
+            .iter()
+            .map(|a| (a.node.as_str(), a.line))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (total.as_str(), 1),
+                (render.as_str(), 6),
+                (render.as_str(), 7)
+            ]
+        );
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0]
+            .error
+            .contains("line 13: unknown annotation `state=done`"));
+
+        // Without a time, statuses change but no history is recorded
+        let mut preview = graph.clone();
+        let (summary, _) = apply(&mut preview, &annotations, None).unwrap();
+        assert_eq!(summary.statuses, vec![total.clone(), render.clone()]);
+        let history = |graph: &Graph, id: &str| {
+            graph
+                .node_weight(graph.get_by_id(id).unwrap())
+                .unwrap()
+                .history
+                .clone()
+        };
+        assert!(history(&preview, &total).is_empty());
+
+        let (summary, warnings) = apply(&mut graph, &annotations, Some(7)).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(
+            summary,
+            ApplySummary {
+                statuses: vec![total.clone(), render.clone()],
+                assignments: 2,
+                units_created: 1
+            }
+        );
+        assert_eq!(history(&graph, &total)[0].at, 7);
+        let status = |id: &str| {
+            graph
+                .node_weight(graph.get_by_id(id).unwrap())
+                .unwrap()
+                .status
+        };
+        assert_eq!(status(&total), MigrationStatus::Migrated);
+        assert_eq!(status(&render), MigrationStatus::InProgress);
+        assert_eq!(status(&plain), MigrationStatus::Pending);
+        assert_eq!(
+            migration::unit_members(&graph, "unit::billing"),
+            vec![render.clone(), total.clone()]
+        );
+
+        // Applying again changes nothing; going back is not allowed
+        let (summary, _) = apply(&mut graph, &annotations, Some(8)).unwrap();
+        assert_eq!(summary, ApplySummary::default());
+        assert_eq!(history(&graph, &total).len(), 1);
+        let mut backwards = annotations[0].clone();
+        backwards.status = Some(MigrationStatus::Pending);
+        let (_, warnings) = apply(&mut graph, &[backwards], None).unwrap();
+        assert_eq!(warnings.len(), 1);
+    }
+}
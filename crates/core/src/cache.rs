@@ -0,0 +1,384 @@
+//! Content-addressed cache of pass-1 results
+//!
+//! Parsing and symbol extraction dominate the time of a rescan, yet most
+//! files are unchanged since the last one. A [`ParseCache`] stores each
+//! file's [`FileParseResult`] under a key derived from everything that
+//! determines it:
+//!
+//! - the file's canonical path (node IDs embed it) and the SHA-256 of its
+//!   bytes,
+//! - the language it is parsed in and the session's extraction settings
+//!   (granularity, edge policy, generated-file tagging, limits, and the path
+//!   resolver that shapes node IDs),
+//!
+//! so an entry is only ever reused for identical input. Entries live in a
+//! directory named after the [`version_key()`]: the cache format, this
+//! crate's version and the version of every tree-sitter grammar. A cache
+//! opened by a different build finds none of its entries, and
+//! [`ParseCache::open()`] deletes the directories other versions left
+//! behind, so upgrading the tool invalidates the cache automatically.
+//!
+//! Directory scans only use a cache when `cache = true` is set in
+//! `migrator.toml`, so scanning a project writes nothing into it by default;
+//! the cache then lives under `<root>/<cache_dir>/parse` (see
+//! [`ParseCache::for_project()`]).
+//!
+//! # Example
+//! ```no_run
+//! use graph_migrator_core::cache::ParseCache;
+//! use std::path::Path;
+//!
+//! let cache = ParseCache::open(Path::new(".migrator/cache/parse"));
+//! let stats = cache.stats().unwrap();
+//! println!("{} entries, {} bytes", stats.entries, stats.bytes);
+//! cache.clear().unwrap();
+//! ```
+
+use crate::config::Config;
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::{Graph, GraphData};
+use crate::import::ImportStatement;
+use crate::parser::{
+    FileParseResult, Language, NotebookCells, SourceEncoding, TypeReference, UnresolvedCall,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Version of the entry format; bump when extraction changes without a
+/// version bump of the crate, or the entry layout changes
+pub const CACHE_FORMAT: u32 = 2;
+
+/// Subdirectory of `cache_dir` holding parse results
+pub const PARSE_CACHE_DIR: &str = "parse";
+
+/// Name of the entry directory of this build
+///
+/// Hex SHA-256 (first 16 digits) of [`CACHE_FORMAT`], the crate version and
+/// the ABI version, metadata and symbol counts of every grammar.
+pub fn version_key() -> &'static str {
+    static KEY: OnceLock<String> = OnceLock::new();
+    KEY.get_or_init(|| {
+        let mut hasher = Sha256::new();
+        hasher.update(format!("{}:{}", CACHE_FORMAT, env!("CARGO_PKG_VERSION")));
+        let grammars: [tree_sitter::Language; 4] = [
+            tree_sitter_python::LANGUAGE.into(),
+            tree_sitter_rust::LANGUAGE.into(),
+            tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            tree_sitter_typescript::LANGUAGE_TSX.into(),
+        ];
+        for grammar in grammars {
+            let version = grammar.metadata().map(|m| {
+                format!(
+                    "{}.{}.{}",
+                    m.major_version, m.minor_version, m.patch_version
+                )
+            });
+            hasher.update(format!(
+                ":{:?}/{}/{:?}/{}/{}",
+                grammar.name(),
+                grammar.abi_version(),
+                version,
+                grammar.node_kind_count(),
+                grammar.field_count()
+            ));
+        }
+        hex(&hasher.finalize())[..16].to_string()
+    })
+}
+
+/// Size of a cache on disk, from [`ParseCache::stats()`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheStats {
+    /// Entries usable by this build
+    pub entries: usize,
+    /// Total size of those entries
+    pub bytes: u64,
+}
+
+/// A directory of cached pass-1 results, see the [module docs](self)
+#[derive(Debug)]
+pub struct ParseCache {
+    dir: PathBuf,
+    hits: usize,
+    misses: usize,
+}
+
+impl ParseCache {
+    /// The cache in `dir`, after deleting entries of other versions
+    ///
+    /// Nothing is created until the first entry is stored.
+    pub fn open(dir: &Path) -> Self {
+        if let Ok(versions) = std::fs::read_dir(dir) {
+            for stale in versions.flatten() {
+                if stale.file_name() != version_key() && stale.path().is_dir() {
+                    tracing::debug!(path = %stale.path().display(), "removing stale parse cache");
+                    if let Err(err) = std::fs::remove_dir_all(stale.path()) {
+                        tracing::warn!(path = %stale.path().display(), %err, "could not remove stale cache");
+                    }
+                }
+            }
+        }
+        Self {
+            dir: dir.to_path_buf(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// The cache of the project at `root`: `<cache_dir>/parse`, or `None` if
+    /// `config` turns caching off
+    pub fn for_project(root: &Path, config: &Config) -> Option<Self> {
+        config
+            .cache
+            .then(|| Self::open(&Self::project_dir(root, config)))
+    }
+
+    /// Where [`for_project()`](Self::for_project) keeps the cache
+    pub fn project_dir(root: &Path, config: &Config) -> PathBuf {
+        config
+            .resolve_path(root, &config.cache_dir)
+            .join(PARSE_CACHE_DIR)
+    }
+
+    /// The cache directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Lookups answered from the cache so far
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Lookups that found no entry so far
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Count the entries of this version and their size
+    ///
+    /// # Errors
+    /// Returns [`GraphMigratorError::Io`] if the cache directory exists but
+    /// cannot be read.
+    pub fn stats(&self) -> Result<CacheStats> {
+        let mut stats = CacheStats::default();
+        let version_dir = self.dir.join(version_key());
+        if !version_dir.is_dir() {
+            return Ok(stats);
+        }
+        let shards =
+            std::fs::read_dir(&version_dir).map_err(|e| GraphMigratorError::io(&version_dir, e))?;
+        for shard in shards.flatten() {
+            let Ok(entries) = std::fs::read_dir(shard.path()) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                if entry.path().extension().is_some_and(|ext| ext == "json") {
+                    stats.entries += 1;
+                    stats.bytes += entry.metadata().map_or(0, |meta| meta.len());
+                }
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Delete every entry, returning the number of bytes freed
+    ///
+    /// # Errors
+    /// Returns [`GraphMigratorError::Io`] if the directory cannot be removed.
+    pub fn clear(&self) -> Result<u64> {
+        let freed = self.stats()?.bytes;
+        match std::fs::remove_dir_all(&self.dir) {
+            Ok(()) => Ok(freed),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(0),
+            Err(err) => Err(GraphMigratorError::io(&self.dir, err)),
+        }
+    }
+
+    /// Entry key of a file: hex SHA-256 of everything its parse depends on
+    pub(crate) fn key(canonical_path: &Path, settings: &str, bytes: &[u8]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(canonical_path.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher.update(settings.as_bytes());
+        hasher.update([0]);
+        hasher.update(Sha256::digest(bytes));
+        hex(&hasher.finalize())
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir
+            .join(version_key())
+            .join(&key[..2])
+            .join(format!("{}.json", key))
+    }
+
+    /// The cached result for `key`, as if `path` had just been parsed
+    ///
+    /// Unreadable entries count as misses and are removed.
+    pub(crate) fn get(&mut self, key: &str, path: &Path) -> Option<FileParseResult> {
+        let entry = self.entry_path(key);
+        let cached = std::fs::read_to_string(&entry)
+            .ok()
+            .and_then(|text| match CachedParse::from_json(&text, path) {
+                Ok(parsed) => Some(parsed),
+                Err(err) => {
+                    tracing::debug!(entry = %entry.display(), %err, "dropping unreadable cache entry");
+                    let _ = std::fs::remove_file(&entry);
+                    None
+                }
+            });
+        match cached {
+            Some(_) => self.hits += 1,
+            None => self.misses += 1,
+        }
+        cached
+    }
+
+    /// Store `parsed` under `key`; failures are logged, never fatal
+    pub(crate) fn put(&mut self, key: &str, parsed: &FileParseResult) {
+        let entry = self.entry_path(key);
+        let written = serde_json::to_string(&CachedParse::from(parsed))
+            .map_err(std::io::Error::other)
+            .and_then(|json| {
+                if let Some(shard) = entry.parent() {
+                    std::fs::create_dir_all(shard)?;
+                }
+                // Written aside and renamed, so concurrent scans never read half an entry
+                let tmp = entry.with_extension(format!("{}.tmp", std::process::id()));
+                std::fs::write(&tmp, json)?;
+                std::fs::rename(&tmp, &entry)
+            });
+        if let Err(err) = written {
+            tracing::debug!(entry = %entry.display(), %err, "could not write cache entry");
+        }
+    }
+}
+
+/// Stored form of a [`FileParseResult`], without its path
+#[derive(Serialize, Deserialize)]
+struct CachedParse {
+    language: Language,
+    graph: GraphData,
+    error_lines: Vec<usize>,
+    unresolved_calls: Vec<UnresolvedCall>,
+    type_references: Vec<TypeReference>,
+    type_aliases: HashMap<String, Vec<String>>,
+    imports: Vec<ImportStatement>,
+    exports: Option<Vec<String>>,
+    encoding: SourceEncoding,
+    notebook: Option<NotebookCells>,
+}
+
+impl From<&FileParseResult> for CachedParse {
+    fn from(parsed: &FileParseResult) -> Self {
+        Self {
+            language: parsed.language,
+            graph: GraphData::from(&parsed.graph),
+            error_lines: parsed.error_lines.clone(),
+            unresolved_calls: parsed.unresolved_calls.clone(),
+            type_references: parsed.type_references.clone(),
+            type_aliases: parsed.type_aliases.clone(),
+            imports: parsed.imports.clone(),
+            exports: parsed.exports.clone(),
+            encoding: parsed.encoding,
+            notebook: parsed.notebook.clone(),
+        }
+    }
+}
+
+impl CachedParse {
+    fn from_json(text: &str, path: &Path) -> Result<FileParseResult> {
+        let cached: CachedParse = serde_json::from_str(text)
+            .map_err(|e| GraphMigratorError::InvalidGraph(e.to_string()))?;
+        Ok(FileParseResult {
+            path: path.to_path_buf(),
+            language: cached.language,
+            graph: Graph::try_from(cached.graph)?,
+            error_lines: cached.error_lines,
+            unresolved_calls: cached.unresolved_calls,
+            type_references: cached.type_references,
+            type_aliases: cached.type_aliases,
+            imports: cached.imports,
+            exports: cached.exports,
+            encoding: cached.encoding,
+            notebook: cached.notebook,
+        })
+    }
+}
+
+/// Lowercase hex of `bytes`
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{self, ParseSession};
+
+    #[test]
+    fn test_cache_reuses_unchanged_files() {
+        let project = tempfile::tempdir().unwrap();
+        let app = project.path().join("app.py");
+        std::fs::write(
+            &app,
+            "def main():\n    helper()\n\ndef helper():\n    pass\n",
+        )
+        .unwrap();
+        let dir = project.path().join("cache");
+        let parse = || {
+            let mut session = ParseSession::uncached().with_cache(Some(ParseCache::open(&dir)));
+            let multi = parser::parse_files_in(&mut session, &[app.as_path()]).unwrap();
+            let cache = session.cache().unwrap();
+            (multi, cache.hits(), cache.misses())
+        };
+
+        let (first, hits, misses) = parse();
+        assert_eq!((hits, misses), (0, 1));
+        let (second, hits, _) = parse();
+        assert_eq!(hits, 1);
+        assert_eq!(second.graph.node_count(), first.graph.node_count());
+        assert_eq!(second.graph.edge_count(), 1);
+
+        std::fs::write(&app, "def main():\n    pass\n").unwrap();
+        let (changed, hits, misses) = parse();
+        assert_eq!((hits, misses), (0, 1));
+        assert_eq!(changed.graph.edge_count(), 0);
+
+        let cache = ParseCache::open(&dir);
+        assert_eq!(cache.stats().unwrap().entries, 2);
+        // Entries of another build are dropped when the cache is opened
+        std::fs::create_dir_all(dir.join("0123456789abcdef/00")).unwrap();
+        let cache = ParseCache::open(&dir);
+        assert!(!dir.join("0123456789abcdef").exists());
+        assert!(cache.clear().unwrap() > 0);
+        assert_eq!(cache.stats().unwrap(), CacheStats::default());
+    }
+
+    #[test]
+    fn test_cache_key_covers_session_settings() {
+        let project = tempfile::tempdir().unwrap();
+        let app = project.path().join("app.py");
+        std::fs::write(&app, "def main():\n    pass\n").unwrap();
+        let dir = project.path().join("cache");
+        let misses = |session: ParseSession| {
+            let mut session = session.with_cache(Some(ParseCache::open(&dir)));
+            parser::parse_files_in(&mut session, &[app.as_path()]).unwrap();
+            session.cache().unwrap().misses()
+        };
+
+        assert_eq!(misses(ParseSession::uncached()), 1);
+        assert_eq!(misses(ParseSession::uncached()), 0);
+        let limits = crate::config::LimitsConfig {
+            max_nodes: Some(100),
+            ..Default::default()
+        };
+        assert_eq!(misses(ParseSession::uncached().with_limits(limits)), 1);
+        let canonical = ParseSession::uncached().with_paths(crate::paths::PathResolver::Canonical);
+        assert_eq!(misses(canonical), 1);
+    }
+}
@@ -0,0 +1,416 @@
+//! Compact, read-only graph storage (`compact` feature)
+//!
+//! [`Graph`] keeps one heap-allocated `Node` per symbol, with its own `String`
+//! for every ID, name, language and path. On large monorepos the same file
+//! path and language are repeated thousands of times. [`CompactGraph`]
+//! stores the same nodes and edges as columns instead:
+//!
+//! - one interned string table, so each distinct string is stored once,
+//! - one `u32` per node and column (ID, name, language, file, lines),
+//! - edges in compressed sparse row form: per-node offsets into a flat
+//!   array of `u32` targets.
+//!
+//! Nodes are numbered in ID order, so lookups by ID are a binary search.
+//! Only identity, location, status, edges and the graph's
+//! [metadata](Graph::metadata) are kept; enrichment and tracking data
+//! (centrality, blame, churn, coverage, effort, owners, issue, history,
+//! packages, attributes) is dropped.
+//! Paths are stored as UTF-8 (non-UTF-8 paths are converted lossily).
+//!
+//! A [`CompactGraph`] is copied from a finished [`Graph`], so it does not
+//! lower peak memory during a scan: the full graph exists while the copy is
+//! made. It shrinks what stays resident afterwards, once the [`Graph`] is
+//! dropped, and is the layout snapshots (`snapshot` feature) map from disk
+//! without ever building a [`Graph`].
+
+use crate::graph::{
+    Edge, EdgeType, Graph, GraphMetadata, MemoryStats, MigrationStatus, Node, NodeType,
+};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Deduplicated strings, referenced by `u32` handle
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StringTable {
+    /// Every string, concatenated
+    pub(crate) text: String,
+    /// Start offset of each string in `text`, plus a final end offset
+    pub(crate) offsets: Vec<u32>,
+}
+
+impl StringTable {
+    pub(crate) fn get(&self, handle: u32) -> &str {
+        let handle = handle as usize;
+        &self.text[self.offsets[handle] as usize..self.offsets[handle + 1] as usize]
+    }
+
+    fn heap_bytes(&self) -> usize {
+        self.text.capacity() + self.offsets.capacity() * std::mem::size_of::<u32>()
+    }
+}
+
+/// Builds a [`StringTable`], handing out one handle per distinct string
+struct Interner<'a> {
+    table: StringTable,
+    handles: HashMap<Cow<'a, str>, u32>,
+}
+
+impl<'a> Interner<'a> {
+    fn new() -> Self {
+        Self {
+            table: StringTable {
+                text: String::new(),
+                offsets: vec![0],
+            },
+            handles: HashMap::new(),
+        }
+    }
+
+    fn intern(&mut self, text: impl Into<Cow<'a, str>>) -> u32 {
+        let text = text.into();
+        if let Some(&handle) = self.handles.get(text.as_ref()) {
+            return handle;
+        }
+        let handle = to_u32(self.table.offsets.len() - 1);
+        self.table.text.push_str(&text);
+        self.table.offsets.push(to_u32(self.table.text.len()));
+        self.handles.insert(text, handle);
+        handle
+    }
+
+    fn finish(mut self) -> StringTable {
+        self.table.text.shrink_to_fit();
+        self.table.offsets.shrink_to_fit();
+        self.table
+    }
+}
+
+/// Struct-of-arrays copy of a [`Graph`]; see the [module docs](self)
+#[derive(Debug, Clone, Default)]
+pub struct CompactGraph {
+    pub(crate) strings: StringTable,
+    pub(crate) ids: Vec<u32>,
+    pub(crate) names: Vec<u32>,
+    pub(crate) languages: Vec<u32>,
+    pub(crate) files: Vec<u32>,
+    /// `(start, end)`; `(0, 0)` when the node has no line range
+    pub(crate) lines: Vec<(u32, u32)>,
+    pub(crate) node_types: Vec<NodeType>,
+    pub(crate) statuses: Vec<MigrationStatus>,
+    pub(crate) external: Vec<bool>,
+    /// Node `n`'s outgoing edges are `edge_offsets[n]..edge_offsets[n + 1]`
+    pub(crate) edge_offsets: Vec<u32>,
+    pub(crate) edge_targets: Vec<u32>,
+    pub(crate) edge_types: Vec<EdgeType>,
+    pub(crate) metadata: GraphMetadata,
+}
+
+impl CompactGraph {
+    /// Copy a graph into compact form
+    ///
+    /// `graph` is only borrowed; drop it afterwards to get the memory back.
+    ///
+    /// # Panics
+    /// Panics if the graph has more than `u32::MAX` nodes, edges or string bytes.
+    pub fn from_graph(graph: &Graph) -> Self {
+        let mut order: Vec<_> = graph.node_indices().collect();
+        order.sort_by(|&a, &b| {
+            let id = |idx| graph.node_weight(idx).map(|node| node.id.as_str());
+            id(a).cmp(&id(b))
+        });
+        let position: HashMap<_, u32> = order
+            .iter()
+            .enumerate()
+            .map(|(i, &idx)| (idx, to_u32(i)))
+            .collect();
+
+        let mut compact = Self::default();
+        let mut interner = Interner::new();
+        let mut outgoing: Vec<Vec<(u32, EdgeType)>> = vec![Vec::new(); order.len()];
+        for (from, to, edge) in graph.edge_endpoints() {
+            outgoing[position[&from] as usize].push((position[&to], edge.edge_type));
+        }
+
+        compact.edge_offsets.push(0);
+        for (&idx, mut edges) in order.iter().zip(outgoing) {
+            let Some(node) = graph.node_weight(idx) else {
+                continue;
+            };
+            compact.ids.push(interner.intern(&node.id));
+            compact.names.push(interner.intern(&node.name));
+            compact.languages.push(interner.intern(&node.language));
+            compact
+                .files
+                .push(interner.intern(node.file_path.to_string_lossy()));
+            compact.lines.push(
+                node.line_range
+                    .map_or((0, 0), |(start, end)| (to_u32(start), to_u32(end))),
+            );
+            compact.node_types.push(node.node_type.clone());
+            compact.statuses.push(node.status);
+            compact.external.push(node.external);
+
+            edges.sort_unstable();
+            for (target, edge_type) in edges {
+                compact.edge_targets.push(target);
+                compact.edge_types.push(edge_type);
+            }
+            compact
+                .edge_offsets
+                .push(to_u32(compact.edge_targets.len()));
+        }
+
+        compact.strings = interner.finish();
+        compact.metadata = graph.metadata().clone();
+        compact
+    }
+
+    /// Number of nodes
+    pub fn node_count(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Number of edges
+    pub fn edge_count(&self) -> usize {
+        self.edge_targets.len()
+    }
+
+    /// Position of the node with ID `id`
+    pub fn find(&self, id: &str) -> Option<u32> {
+        self.ids
+            .binary_search_by(|&handle| self.strings.get(handle).cmp(id))
+            .ok()
+            .map(to_u32)
+    }
+
+    /// Node ID at `node`
+    pub fn id(&self, node: u32) -> &str {
+        self.strings.get(self.ids[node as usize])
+    }
+
+    /// Symbol name at `node`
+    pub fn name(&self, node: u32) -> &str {
+        self.strings.get(self.names[node as usize])
+    }
+
+    /// Source language at `node`
+    pub fn language(&self, node: u32) -> &str {
+        self.strings.get(self.languages[node as usize])
+    }
+
+    /// Defining file at `node`
+    pub fn file_path(&self, node: u32) -> &Path {
+        Path::new(self.strings.get(self.files[node as usize]))
+    }
+
+    /// Line range at `node`, if known
+    pub fn line_range(&self, node: u32) -> Option<(usize, usize)> {
+        match self.lines[node as usize] {
+            (0, 0) => None,
+            (start, end) => Some((start as usize, end as usize)),
+        }
+    }
+
+    /// Node type at `node`
+    pub fn node_type(&self, node: u32) -> NodeType {
+        self.node_types[node as usize].clone()
+    }
+
+    /// Migration status at `node`
+    pub fn status(&self, node: u32) -> MigrationStatus {
+        self.statuses[node as usize]
+    }
+
+    /// Whether `node` is an external (non-project) node
+    pub fn is_external(&self, node: u32) -> bool {
+        self.external[node as usize]
+    }
+
+    /// `(target, edge type)` of every edge leaving `node`, sorted
+    pub fn outgoing(&self, node: u32) -> impl Iterator<Item = (u32, EdgeType)> + '_ {
+        let node = node as usize;
+        let range = self.edge_offsets[node] as usize..self.edge_offsets[node + 1] as usize;
+        self.edge_targets[range.clone()]
+            .iter()
+            .copied()
+            .zip(self.edge_types[range].iter().copied())
+    }
+
+    /// `(source, edge type)` of every edge entering `node`
+    ///
+    /// Only outgoing edges are indexed, so this scans every edge.
+    pub fn incoming(&self, node: u32) -> impl Iterator<Item = (u32, EdgeType)> + '_ {
+        (0..to_u32(self.node_count())).flat_map(move |from| {
+            self.outgoing(from)
+                .filter(move |&(to, _)| to == node)
+                .map(move |(_, edge_type)| (from, edge_type))
+        })
+    }
+
+    /// Provenance of the copied graph
+    pub fn metadata(&self) -> &GraphMetadata {
+        &self.metadata
+    }
+
+    /// Rebuild a [`Graph`] with the kept fields
+    pub fn to_graph(&self) -> Graph {
+        let mut graph = Graph::new();
+        graph.set_metadata(self.metadata.clone());
+        let indices: Vec<_> = (0..to_u32(self.node_count()))
+            .map(|n| {
+                let mut node = Node::new(
+                    self.id(n),
+                    self.name(n),
+                    self.node_type(n),
+                    self.language(n),
+                    self.file_path(n),
+                );
+                node.line_range = self.line_range(n);
+                node.status = self.status(n);
+                node.external = self.is_external(n);
+                graph.add_node(node)
+            })
+            .collect();
+        for (from, &from_idx) in indices.iter().enumerate() {
+            for (to, edge_type) in self.outgoing(to_u32(from)) {
+                graph.add_edge(from_idx, indices[to as usize], Edge::new(edge_type));
+            }
+        }
+        graph
+    }
+
+    /// Memory held by the columns, comparable to [`Graph::memory_stats()`]
+    pub fn memory_stats(&self) -> MemoryStats {
+        use std::mem::size_of;
+
+        let columns = [&self.ids, &self.names, &self.languages, &self.files];
+        MemoryStats {
+            nodes: self.node_count(),
+            edges: self.edge_count(),
+            node_bytes: columns
+                .iter()
+                .map(|column| column.capacity() * size_of::<u32>())
+                .sum::<usize>()
+                + self.lines.capacity() * size_of::<(u32, u32)>()
+                + self.node_types.capacity() * size_of::<NodeType>()
+                + self.statuses.capacity() * size_of::<MigrationStatus>()
+                + self.external.capacity(),
+            edge_bytes: (self.edge_offsets.capacity() + self.edge_targets.capacity())
+                * size_of::<u32>()
+                + self.edge_types.capacity() * size_of::<EdgeType>(),
+            string_bytes: self.strings.heap_bytes(),
+            unique_string_bytes: self.strings.text.len(),
+            // IDs are sorted, so no separate index is needed
+            index_bytes: 0,
+        }
+    }
+}
+
+impl crate::view::GraphView for CompactGraph {
+    type NodeRef = u32;
+
+    fn node_count(&self) -> usize {
+        CompactGraph::node_count(self)
+    }
+
+    fn edge_count(&self) -> usize {
+        CompactGraph::edge_count(self)
+    }
+
+    fn node_refs(&self) -> impl Iterator<Item = u32> + '_ {
+        0..to_u32(self.node_count())
+    }
+
+    fn find(&self, id: &str) -> Option<u32> {
+        CompactGraph::find(self, id)
+    }
+
+    fn id(&self, node: u32) -> &str {
+        CompactGraph::id(self, node)
+    }
+
+    fn name(&self, node: u32) -> &str {
+        CompactGraph::name(self, node)
+    }
+
+    fn node_type(&self, node: u32) -> NodeType {
+        CompactGraph::node_type(self, node)
+    }
+
+    fn status(&self, node: u32) -> MigrationStatus {
+        CompactGraph::status(self, node)
+    }
+
+    fn file_path(&self, node: u32) -> &Path {
+        CompactGraph::file_path(self, node)
+    }
+
+    fn line_range(&self, node: u32) -> Option<(usize, usize)> {
+        CompactGraph::line_range(self, node)
+    }
+
+    fn is_external(&self, node: u32) -> bool {
+        CompactGraph::is_external(self, node)
+    }
+
+    fn outgoing(&self, node: u32) -> impl Iterator<Item = (u32, EdgeType)> + '_ {
+        CompactGraph::outgoing(self, node)
+    }
+
+    fn incoming(&self, node: u32) -> impl Iterator<Item = (u32, EdgeType)> + '_ {
+        CompactGraph::incoming(self, node)
+    }
+}
+
+impl From<&Graph> for CompactGraph {
+    fn from(graph: &Graph) -> Self {
+        Self::from_graph(graph)
+    }
+}
+
+fn to_u32(value: usize) -> u32 {
+    u32::try_from(value).expect("compact graphs hold at most u32::MAX items")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_and_smaller_footprint() {
+        let mut graph = Graph::new();
+        let mut previous = None;
+        for i in 0..50 {
+            let mut node = Node::new(
+                format!("/project/src/module.py::f{:02}", i),
+                format!("f{:02}", i),
+                NodeType::Function,
+                "python",
+                "/project/src/module.py",
+            );
+            node.line_range = Some((i * 3 + 1, i * 3 + 2));
+            let idx = graph.add_node(node);
+            if let Some(prev) = previous {
+                graph.add_edge(idx, prev, Edge::new(EdgeType::Calls));
+            }
+            previous = Some(idx);
+        }
+
+        let compact = CompactGraph::from_graph(&graph);
+        let f10 = compact.find("/project/src/module.py::f10").unwrap();
+        assert_eq!(compact.name(f10), "f10");
+        assert_eq!(compact.line_range(f10), Some((31, 32)));
+        assert_eq!(
+            compact.outgoing(f10).collect::<Vec<_>>(),
+            vec![(
+                compact.find("/project/src/module.py::f09").unwrap(),
+                EdgeType::Calls
+            )]
+        );
+        assert!(compact.find("missing").is_none());
+
+        assert_eq!(compact.to_graph().fingerprint(), graph.fingerprint());
+        assert!(compact.memory_stats().total() < graph.memory_stats().total());
+    }
+}
@@ -0,0 +1,899 @@
+//! Project configuration loaded from `migrator.toml`
+//!
+//! Passing every discovery and output setting as CLI flags doesn't scale to
+//! team usage, so projects can commit a `migrator.toml` at their root:
+//!
+//! ```toml
+//! languages = ["python"]
+//! cache_dir = ".migrator/cache"
+//! cache = true
+//! relative_ids = true
+//! external_modules = true
+//! link_tests = true
+//! frameworks = true
+//! annotations = true
+//! star_imports = "expand"
+//! granularity = "methods"
+//! edge_policy = "count"
+//! namespace = "billing"
+//! source_roots = ["src", "."]
+//!
+//! [extensions]
+//! pyw = "python"
+//! mts = "typescript"
+//!
+//! [interpreters]
+//! jython = "python"
+//!
+//! [[workspace]]
+//! path = "services/*/src"
+//! source_roots = [".", "../../../libs"]
+//!
+//! [virtualenv]
+//! path = ".venv"
+//! scan_editable = true
+//!
+//! [[mapping]]
+//! from = "legacy/billing/**/*.py"
+//! to = "services/billing_rs/src/**/*.rs"
+//!
+//! [discovery]
+//! include = ["src/**/*.py"]
+//! exclude = ["**/migrations/**", "**/*_pb2.py"]
+//! max_depth = 12
+//! follow_symlinks = false
+//! shebangs = true
+//! generated = "exclude"
+//!
+//! [limits]
+//! max_file_size = 4194304
+//! max_parse_ms = 5000
+//! max_nodes = 20000
+//!
+//! [output]
+//! graph = ".migrator/state.json"
+//! report = "migration-report.md"
+//! history = ".migrator/history.jsonl"
+//!
+//! [enrich]
+//! churn_days = 90
+//! coverage = "coverage.xml"
+//! codeowners = ".github/CODEOWNERS"
+//!
+//! [enrich.owners]
+//! "src/billing/legacy" = ["@payments-legacy"]
+//!
+//! [rules]
+//! cycles = true
+//! hot_path_pagerank = 0.02
+//! baseline = ".migrator/baseline.json"
+//!
+//! [[rules.forbid]]
+//! from = "src/billing/**"
+//! to = "src/legacy/**"
+//! reason = "new billing code must not call into the legacy package"
+//!
+//! [[rules.owner_order]]
+//! before = "@platform"
+//! after = "@payments"
+//!
+//! [events]
+//! log = ".migrator/events.jsonl"
+//! webhooks = ["https://hooks.slack.com/services/T000/B000/XXXX"]
+//!
+//! [integrations.github]
+//! repo = "example/billing"
+//! labels = ["migration"]
+//!
+//! [integrations.jira]
+//! url = "https://example.atlassian.net"
+//! project = "MIG"
+//! email = "migration-bot@example.com"
+//! ```
+//!
+//! Every field is optional; a missing file yields [`Config::default()`].
+//! Relative paths are interpreted relative to the project root.
+
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::EdgePolicy;
+use crate::parser::{GranularityLevel, Language};
+use crate::paths::PathResolver;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// File name looked up in the project root
+pub const CONFIG_FILE_NAME: &str = "migrator.toml";
+
+/// Top-level project configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Languages to scan (`"python"`, `"rust"`, `"typescript"`)
+    pub languages: Vec<String>,
+    /// File extensions (without the dot) parsed in a language other than
+    /// their built-in one, or not recognized at all, e.g. `pyw = "python"`
+    ///
+    /// Files are parsed in the language of their extension (see
+    /// [`Language::detect()`]); these entries take precedence.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub extensions: BTreeMap<String, Language>,
+    /// Interpreters run by `#!` lines, beyond the built-in ones, with the
+    /// language of their scripts, e.g. `jython = "python"`
+    ///
+    /// See [`Language::from_shebang()`] and [`DiscoveryConfig::shebangs`].
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub interpreters: BTreeMap<String, Language>,
+    /// Directory for parse caches, relative to the project root
+    pub cache_dir: PathBuf,
+    /// Reuse the parse results of unchanged files from `cache_dir` (see
+    /// [`cache`](crate::cache)); off by default, as it writes into the project
+    pub cache: bool,
+    /// Build node IDs from root-relative paths so graphs are portable across
+    /// machines (default); `false` keeps canonical absolute paths, see
+    /// [`Config::path_resolver()`]
+    pub relative_ids: bool,
+    /// Add a node per external (stdlib or third-party) module, linked from the symbols that use it
+    pub external_modules: bool,
+    /// Add `Tests` edges from pytest-style test functions to the symbols they call
+    pub link_tests: bool,
+    /// Add Django and Flask routes and model relations (see [`crate::frameworks`])
+    pub frameworks: bool,
+    /// Apply `# migrator: status=...` comments in source (see [`crate::annotations`])
+    pub annotations: bool,
+    /// How `from module import *` is resolved
+    pub star_imports: StarImports,
+    /// How deep symbols are extracted
+    ///
+    /// When unset, top-level classes and functions with their calls and type
+    /// references, but no file or method nodes.
+    pub granularity: Option<GranularityLevel>,
+    /// Whether the same relation found twice (e.g. two call sites) becomes
+    /// one edge, one edge with a count, or parallel edges
+    pub edge_policy: EdgePolicy,
+    /// Directories module names are relative to, like `sys.path` entries, highest priority first
+    ///
+    /// When empty, `src/` (if present and not a package) and then the project
+    /// root (see [`ModuleResolver`](crate::resolve::ModuleResolver)).
+    pub source_roots: Vec<PathBuf>,
+    /// Project roots of a monorepo, each with its own source roots (`[[workspace]]`)
+    ///
+    /// See [`resolve::workspace_members()`](crate::resolve::workspace_members()).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub workspace: Vec<WorkspaceRoot>,
+    /// The project's virtualenv, whose editable installs resolve to their source
+    pub virtualenv: VirtualenvConfig,
+    /// Where legacy files are being rewritten (`[[mapping]]`), for proposing
+    /// `MigratedTo` links
+    ///
+    /// See [`migration::propose_links()`](crate::migration::propose_links()).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub mapping: Vec<MappingRule>,
+    /// Prefix for node IDs (usually the repository name), so graphs of several repositories can be merged
+    pub namespace: Option<String>,
+    /// File discovery settings
+    pub discovery: DiscoveryConfig,
+    /// Per-file limits beyond which a file is skipped
+    pub limits: LimitsConfig,
+    /// Output locations
+    pub output: OutputConfig,
+    /// Settings for optional enrichment passes
+    pub enrich: EnrichConfig,
+    /// Policies checked by `migrator check`
+    pub rules: RulesConfig,
+    /// Where status change events are sent
+    pub events: EventsConfig,
+    /// Issue trackers that `migrator sync-issues` writes to
+    pub integrations: IntegrationsConfig,
+}
+
+/// How pass 2 handles `from module import *` (see [`crate::resolve`])
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StarImports {
+    /// Bind every name the module exports, so calls through them become edges
+    #[default]
+    Expand,
+    /// Link the importing module to the imported one with one `Imports` edge
+    Module,
+}
+
+/// `[discovery]` section: which files are part of the graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DiscoveryConfig {
+    /// Include globs, relative to the project root.
+    ///
+    /// When empty, defaults to the extensions of the configured languages
+    /// (see [`Config::include_patterns`]).
+    pub include: Vec<String>,
+    /// Exclude globs, applied after include globs and .gitignore
+    pub exclude: Vec<String>,
+    /// Maximum directory depth below the root (unlimited if unset)
+    pub max_depth: Option<usize>,
+    /// Follow symbolic links during traversal
+    pub follow_symlinks: bool,
+    /// Also scan files without an extension whose `#!` line runs an
+    /// interpreter of a configured language (default: on)
+    ///
+    /// Only applies with the default include globs; explicit `include`
+    /// globs select scripts like any other file.
+    pub shebangs: bool,
+    /// What happens to files whose header marks them as generated (see
+    /// [`parser::generated`](crate::parser::generated))
+    pub generated: GeneratedCode,
+}
+
+/// `[limits]` section: guards against pathological files
+///
+/// A huge generated module can stall a scan for minutes. A file over one of
+/// these limits is skipped with a diagnostic saying which limit it broke
+/// (a [`GraphMigratorError::LimitExceeded`]); the rest of the scan goes on.
+/// Every limit is off unless set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsConfig {
+    /// Skip files larger than this many bytes, before reading them
+    pub max_file_size: Option<u64>,
+    /// Give up on files tree-sitter takes longer than this many milliseconds to parse
+    pub max_parse_ms: Option<u64>,
+    /// Skip files that yield more than this many nodes
+    pub max_nodes: Option<usize>,
+}
+
+/// One `[[workspace]]` entry: the project roots at `path`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceRoot {
+    /// Directory relative to the project root; `*` in a component matches
+    /// one level (`services/*/src`)
+    pub path: String,
+    /// Directories module names under this root are relative to, relative
+    /// to it; when empty, `src/` (if present and not a package) and then
+    /// the root itself, as for [`Config::source_roots`]
+    #[serde(default)]
+    pub source_roots: Vec<PathBuf>,
+}
+
+/// `[virtualenv]` section: where first-party packages are installed
+///
+/// Packages installed in editable mode (`pip install -e`) are imported from
+/// their source directories; see [`resolve::editable_roots()`].
+///
+/// [`resolve::editable_roots()`]: crate::resolve::editable_roots
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VirtualenvConfig {
+    /// Virtualenv directory, relative to the project root (`.venv`); none
+    /// is read unless set
+    pub path: Option<PathBuf>,
+    /// Also parse the editable packages whose source is outside the project
+    ///
+    /// Their node IDs keep absolute paths, as they have no project-relative one.
+    pub scan_editable: bool,
+}
+
+/// One `[[mapping]]` entry: legacy files matching `from` are rewritten as
+/// the files `to` names
+///
+/// Patterns are globs like those of `[discovery]`, limited to the `*`, `**`
+/// and `?` wildcards, where `*` and `?` do not match `/`. Each wildcard
+/// of `to` stands for what the wildcard at the same place in `from` matched:
+/// with `legacy/**/*.py` mapped to `new/src/**/*.rs`,
+/// `legacy/billing/invoice.py` becomes `new/src/billing/invoice.rs`. Paths
+/// are those in node IDs, without namespace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MappingRule {
+    /// Legacy file pattern (`legacy/foo/**/*.py`)
+    pub from: String,
+    /// Target file pattern (`services/foo_rs/src/**/*.rs`)
+    pub to: String,
+}
+
+/// How scans treat generated files (`[discovery] generated`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GeneratedCode {
+    /// Scan them like any other file
+    Keep,
+    /// Scan them, setting the `generated` attribute on their nodes
+    #[default]
+    Tag,
+    /// Leave them out of discovery
+    Exclude,
+}
+
+impl Default for DiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            include: Vec::new(),
+            exclude: Vec::new(),
+            max_depth: None,
+            follow_symlinks: false,
+            shebangs: true,
+            generated: GeneratedCode::default(),
+        }
+    }
+}
+
+/// `[output]` section: where generated artifacts are written
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputConfig {
+    /// Persisted graph location
+    pub graph: PathBuf,
+    /// Optional report location
+    pub report: Option<PathBuf>,
+    /// Progress history appended to by `migrator snapshot`
+    /// (see [`progress`](crate::progress))
+    pub history: PathBuf,
+}
+
+/// Churn window, in days, used when none is configured
+pub const DEFAULT_CHURN_DAYS: u32 = 90;
+
+/// `[enrich]` section: settings for [`enrich`](crate::enrich) passes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EnrichConfig {
+    /// How many days of history [`enrich::churn()`](crate::enrich::churn()) counts commits over
+    pub churn_days: u32,
+    /// Cobertura or lcov report read by [`enrich::coverage()`](crate::enrich::coverage())
+    pub coverage: Option<PathBuf>,
+    /// CODEOWNERS file read by [`enrich::owners()`](crate::enrich::owners());
+    /// searched for in the usual places if unset
+    pub codeowners: Option<PathBuf>,
+    /// Owners per directory (relative to the project root), overriding CODEOWNERS
+    pub owners: BTreeMap<String, Vec<String>>,
+}
+
+/// `[rules]` section: policies checked by [`rules::check()`](crate::rules::check())
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RulesConfig {
+    /// Report dependency cycles between project symbols
+    pub cycles: bool,
+    /// Report unmigrated symbols whose PageRank is at least this (off if unset)
+    pub hot_path_pagerank: Option<f64>,
+    /// Dependencies that must not exist
+    pub forbid: Vec<ForbiddenDependency>,
+    /// Owners that must finish migrating before others may start
+    pub owner_order: Vec<OwnerOrder>,
+    /// Accepted violations, for `migrator check --ratchet`
+    pub baseline: PathBuf,
+}
+
+/// One `[[rules.forbid]]` entry
+///
+/// Globs are matched against root-relative file paths.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ForbiddenDependency {
+    /// Files whose symbols must not depend on `to`
+    pub from: String,
+    /// Files that must not be depended on from `from`
+    pub to: String,
+    /// Explanation shown with each violation
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// `[events]` section: sinks for [`events`](crate::events)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EventsConfig {
+    /// JSON Lines file each status change is appended to (off if unset)
+    pub log: Option<PathBuf>,
+    /// URLs each status change is POSTed to (`integrations` feature)
+    pub webhooks: Vec<String>,
+}
+
+/// `[integrations]` section: issue trackers for migration units
+///
+/// Credentials are never read from the file: the GitHub token comes from
+/// `GITHUB_TOKEN`, the Jira API token from `JIRA_API_TOKEN`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IntegrationsConfig {
+    /// `[integrations.github]`
+    pub github: Option<GitHubConfig>,
+    /// `[integrations.jira]`
+    pub jira: Option<JiraConfig>,
+}
+
+/// `[integrations.github]`: where unit issues are filed on GitHub
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GitHubConfig {
+    /// Repository as `owner/name`
+    pub repo: String,
+    /// REST API root, for GitHub Enterprise Server
+    #[serde(default = "default_github_api")]
+    pub api_url: String,
+    /// Labels added to created issues
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+fn default_github_api() -> String {
+    "https://api.github.com".to_string()
+}
+
+/// `[integrations.jira]`: where unit issues are filed in Jira
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JiraConfig {
+    /// Site URL, e.g. `https://example.atlassian.net`
+    pub url: String,
+    /// Project key issues are created in
+    pub project: String,
+    /// Account email, paired with `JIRA_API_TOKEN` for basic auth
+    pub email: String,
+    /// Issue type of created issues
+    #[serde(default = "default_jira_issue_type")]
+    pub issue_type: String,
+}
+
+fn default_jira_issue_type() -> String {
+    "Task".to_string()
+}
+
+/// One `[[rules.owner_order]]` entry
+///
+/// Owners are matched against [`Node::owners`](crate::Node::owners), as
+/// written in CODEOWNERS (e.g. `@org/team`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OwnerOrder {
+    /// Owner whose symbols must all be migrated first
+    pub before: String,
+    /// Owner whose symbols must stay pending until then
+    pub after: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            languages: vec!["python".to_string()],
+            extensions: BTreeMap::new(),
+            interpreters: BTreeMap::new(),
+            cache_dir: PathBuf::from(".migrator/cache"),
+            cache: false,
+            relative_ids: true,
+            external_modules: false,
+            link_tests: false,
+            frameworks: false,
+            annotations: false,
+            star_imports: StarImports::Expand,
+            granularity: None,
+            edge_policy: EdgePolicy::Dedupe,
+            source_roots: Vec::new(),
+            workspace: Vec::new(),
+            virtualenv: VirtualenvConfig::default(),
+            mapping: Vec::new(),
+            namespace: None,
+            discovery: DiscoveryConfig::default(),
+            limits: LimitsConfig::default(),
+            output: OutputConfig::default(),
+            enrich: EnrichConfig::default(),
+            rules: RulesConfig::default(),
+            events: EventsConfig::default(),
+            integrations: IntegrationsConfig::default(),
+        }
+    }
+}
+
+impl Default for RulesConfig {
+    fn default() -> Self {
+        Self {
+            cycles: true,
+            hot_path_pagerank: None,
+            forbid: Vec::new(),
+            owner_order: Vec::new(),
+            baseline: PathBuf::from(".migrator/baseline.json"),
+        }
+    }
+}
+
+impl Default for EnrichConfig {
+    fn default() -> Self {
+        Self {
+            churn_days: DEFAULT_CHURN_DAYS,
+            coverage: None,
+            codeowners: None,
+            owners: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            graph: PathBuf::from(".migrator/state.json"),
+            report: None,
+            history: PathBuf::from(".migrator/history.jsonl"),
+        }
+    }
+}
+
+impl Config {
+    /// Load configuration from an explicit `migrator.toml` path
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read, is not valid TOML,
+    /// contains unknown keys, or names an unsupported language.
+    pub fn load(path: &Path) -> Result<Config> {
+        let text = std::fs::read_to_string(path).map_err(|e| GraphMigratorError::io(path, e))?;
+        Self::from_toml_str(&text).map_err(|e| match e {
+            GraphMigratorError::Config(detail) => {
+                GraphMigratorError::Config(format!("{}: {}", path.display(), detail))
+            }
+            other => other,
+        })
+    }
+
+    /// Parse configuration from TOML text
+    pub fn from_toml_str(text: &str) -> Result<Config> {
+        let config: Config =
+            toml::from_str(text).map_err(|e| GraphMigratorError::Config(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Load `migrator.toml` from a project root, falling back to defaults
+    ///
+    /// A missing file is not an error: projects without configuration get
+    /// [`Config::default()`]. A present but invalid file is an error.
+    pub fn from_project_root(root: &Path) -> Result<Config> {
+        let path = root.join(CONFIG_FILE_NAME);
+        if path.is_file() {
+            Self::load(&path)
+        } else {
+            Ok(Config::default())
+        }
+    }
+
+    /// Effective include globs
+    ///
+    /// Returns `discovery.include` if set, otherwise one `**/*.<ext>` glob
+    /// per extension of the configured languages, including the ones mapped
+    /// to them in `[extensions]`.
+    pub fn include_patterns(&self) -> Vec<String> {
+        if !self.discovery.include.is_empty() {
+            return self.discovery.include.clone();
+        }
+        let mut patterns = Vec::new();
+        for language in self
+            .languages
+            .iter()
+            .filter_map(|lang| Language::from_name(lang))
+        {
+            let extensions = language.extensions().iter().copied().chain(
+                self.extensions
+                    .iter()
+                    .filter(|(_, mapped)| **mapped == language)
+                    .map(|(extension, _)| extension.as_str()),
+            );
+            for extension in extensions {
+                let pattern = format!("**/*.{}", extension);
+                if !patterns.contains(&pattern) {
+                    patterns.push(pattern);
+                }
+            }
+        }
+        patterns
+    }
+
+    /// How scans resolve file paths: [`PathResolver::Relative`] with
+    /// `relative_ids` (the default), [`PathResolver::Canonical`] without
+    pub fn path_resolver(&self) -> PathResolver {
+        if self.relative_ids {
+            PathResolver::Relative
+        } else {
+            PathResolver::Canonical
+        }
+    }
+
+    /// Build [`DiscoveryOptions`](crate::discovery::DiscoveryOptions) from the `[discovery]` section
+    #[cfg(feature = "std-fs")]
+    pub fn discovery_options(&self) -> crate::discovery::DiscoveryOptions {
+        let include = self.include_patterns();
+        let include_refs: Vec<&str> = include.iter().map(String::as_str).collect();
+        let exclude_refs: Vec<&str> = self.discovery.exclude.iter().map(String::as_str).collect();
+
+        let mut options = crate::discovery::DiscoveryOptions::new(&include_refs)
+            .exclude(&exclude_refs)
+            .follow_symlinks(self.discovery.follow_symlinks)
+            .paths(self.path_resolver());
+        if let Some(depth) = self.discovery.max_depth {
+            options = options.max_depth(depth);
+        }
+        if self.discovery.generated == GeneratedCode::Exclude {
+            options = options.skip_generated(true);
+        }
+        if self.discovery.shebangs && self.discovery.include.is_empty() {
+            let languages: Vec<Language> = self
+                .languages
+                .iter()
+                .filter_map(|lang| Language::from_name(lang))
+                .collect();
+            options = options
+                .shebangs(&languages)
+                .interpreters(self.interpreters.clone());
+        }
+        options
+    }
+
+    /// Resolve a config-relative path against the project root
+    pub fn resolve_path(&self, root: &Path, path: &Path) -> PathBuf {
+        if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            root.join(path)
+        }
+    }
+
+    /// SHA-256 of the effective settings, as lowercase hex
+    ///
+    /// Equal for configurations that differ only in formatting, comments or
+    /// spelled-out defaults, so it tells whether two scans were configured
+    /// alike (see [`GraphMetadata::config_hash`](crate::GraphMetadata::config_hash)).
+    pub fn fingerprint(&self) -> String {
+        use sha2::{Digest, Sha256};
+
+        let settings = serde_json::to_vec(self).expect("config serializes to JSON");
+        Sha256::digest(settings)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    fn validate(&self) -> Result<()> {
+        for lang in &self.languages {
+            if Language::from_name(lang).is_none() {
+                return Err(GraphMigratorError::Config(format!(
+                    "Unsupported language: {}",
+                    lang
+                )));
+            }
+        }
+        if let Some(namespace) = &self.namespace {
+            let valid = !namespace.is_empty()
+                && namespace
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+            if !valid {
+                return Err(GraphMigratorError::Config(format!(
+                    "Invalid namespace {:?}: use letters, digits, '-', '_' and '.'",
+                    namespace
+                )));
+            }
+        }
+        for rule in &self.rules.forbid {
+            for pattern in [&rule.from, &rule.to] {
+                globset::Glob::new(pattern).map_err(|e| {
+                    GraphMigratorError::Config(format!("Invalid [[rules.forbid]] glob: {}", e))
+                })?;
+            }
+        }
+        for rule in &self.mapping {
+            crate::migration::PathMap::new(rule)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_default_config() {
+        let config = Config::default();
+
+        assert_eq!(config.languages, vec!["python"]);
+        assert_eq!(config.include_patterns(), vec!["**/*.py"]);
+        assert!(config.discovery.exclude.is_empty());
+        assert_eq!(config.output.graph, PathBuf::from(".migrator/state.json"));
+    }
+
+    #[test]
+    fn test_parse_full_config() {
+        let config = Config::from_toml_str(
+            r#"
+            languages = ["python"]
+            cache_dir = "build/cache"
+            namespace = "billing"
+            star_imports = "module"
+            granularity = "functions"
+            edge_policy = "count"
+
+            [[workspace]]
+            path = "services/*"
+
+            [virtualenv]
+            path = ".venv"
+
+            [[mapping]]
+            from = "legacy/**/*.py"
+            to = "src/**/*.rs"
+
+            [discovery]
+            include = ["src/**/*.py"]
+            exclude = ["**/generated/**"]
+            max_depth = 4
+
+            [limits]
+            max_parse_ms = 250
+
+            [output]
+            graph = "out/graph.json"
+            report = "out/report.md"
+
+            [enrich]
+            churn_days = 30
+            coverage = "build/lcov.info"
+
+            [enrich.owners]
+            "src/legacy" = ["@legacy-team"]
+
+            [rules]
+            cycles = false
+
+            [[rules.owner_order]]
+            before = "@platform"
+            after = "@payments"
+
+            [events]
+            log = "events.jsonl"
+
+            [integrations.jira]
+            url = "https://example.atlassian.net"
+            project = "MIG"
+            email = "bot@example.com"
+
+            [[rules.forbid]]
+            from = "src/new/**"
+            to = "src/old/**"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.cache_dir, PathBuf::from("build/cache"));
+        assert_eq!(config.namespace.as_deref(), Some("billing"));
+        assert_eq!(config.star_imports, StarImports::Module);
+        assert_eq!(config.granularity, Some(GranularityLevel::Functions));
+        assert_eq!(config.edge_policy, EdgePolicy::Count);
+        assert_eq!(config.enrich.churn_days, 30);
+        assert_eq!(
+            config.enrich.coverage,
+            Some(PathBuf::from("build/lcov.info"))
+        );
+        assert_eq!(config.enrich.owners["src/legacy"], vec!["@legacy-team"]);
+        assert!(!config.rules.cycles);
+        assert_eq!(config.rules.owner_order[0].after, "@payments");
+        assert_eq!(config.events.log, Some(PathBuf::from("events.jsonl")));
+        assert!(config.events.webhooks.is_empty());
+        let jira = config.integrations.jira.as_ref().unwrap();
+        assert_eq!(
+            (jira.project.as_str(), jira.issue_type.as_str()),
+            ("MIG", "Task")
+        );
+        assert!(config.integrations.github.is_none());
+        assert_eq!(config.rules.forbid[0].to, "src/old/**");
+        assert_eq!(config.include_patterns(), vec!["src/**/*.py"]);
+        assert_eq!(config.discovery.exclude, vec!["**/generated/**"]);
+        assert_eq!(
+            config.discovery_options(),
+            crate::discovery::DiscoveryOptions::new(&["src/**/*.py"])
+                .exclude(&["**/generated/**"])
+                .max_depth(4)
+        );
+        assert_eq!(config.output.report, Some(PathBuf::from("out/report.md")));
+        assert_eq!(
+            config.workspace,
+            [WorkspaceRoot {
+                path: "services/*".to_string(),
+                source_roots: Vec::new(),
+            }]
+        );
+        assert_eq!(config.virtualenv.path, Some(PathBuf::from(".venv")));
+        assert!(!config.virtualenv.scan_editable);
+        assert_eq!(
+            config.mapping,
+            [MappingRule {
+                from: "legacy/**/*.py".to_string(),
+                to: "src/**/*.rs".to_string(),
+            }]
+        );
+        assert_eq!(
+            config.limits,
+            LimitsConfig {
+                max_parse_ms: Some(250),
+                ..LimitsConfig::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_partial_config_uses_defaults() {
+        let config = Config::from_toml_str("[discovery]\nexclude = [\"venv/**\"]\n").unwrap();
+
+        assert_eq!(config.languages, vec!["python"]);
+        assert_eq!(config.output, OutputConfig::default());
+    }
+
+    #[test]
+    fn test_rejects_unknown_language_and_keys() {
+        assert!(matches!(
+            Config::from_toml_str("languages = [\"cobol\"]"),
+            Err(GraphMigratorError::Config(_))
+        ));
+        assert!(Config::from_toml_str("unknown_key = 1").is_err());
+        assert!(matches!(
+            Config::from_toml_str("namespace = \"a:b\""),
+            Err(GraphMigratorError::Config(_))
+        ));
+        assert!(matches!(
+            Config::from_toml_str("[[rules.forbid]]\nfrom = \"a/[\"\nto = \"b\"\n"),
+            Err(GraphMigratorError::Config(_))
+        ));
+        assert!(matches!(
+            Config::from_toml_str("[[mapping]]\nfrom = \"a/*.py\"\nto = \"b/*/*.rs\"\n"),
+            Err(GraphMigratorError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn test_languages_and_extension_overrides() {
+        let config = Config::from_toml_str(
+            "languages = [\"python\", \"typescript\"]\n\
+             [extensions]\npyw = \"python\"\nrs = \"typescript\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.include_patterns(),
+            vec!["**/*.py", "**/*.pyw", "**/*.ts", "**/*.tsx", "**/*.rs"]
+        );
+        assert_eq!(config.extensions["pyw"], Language::Python);
+        assert!(Config::from_toml_str("[extensions]\nh = \"c\"\n").is_err());
+
+        let config = Config::from_toml_str("[discovery]\nshebangs = false\n").unwrap();
+        assert_eq!(
+            config.discovery_options(),
+            crate::discovery::DiscoveryOptions::new(&["**/*.py"])
+        );
+        assert_ne!(
+            Config::default().discovery_options(),
+            config.discovery_options()
+        );
+    }
+
+    #[test]
+    fn test_from_project_root() {
+        let temp_dir = TempDir::new().unwrap();
+
+        // Missing file falls back to defaults
+        let config = Config::from_project_root(temp_dir.path()).unwrap();
+        assert_eq!(config, Config::default());
+
+        fs::write(
+            temp_dir.path().join(CONFIG_FILE_NAME),
+            "cache_dir = \"tmp/cache\"\n",
+        )
+        .unwrap();
+        let config = Config::from_project_root(temp_dir.path()).unwrap();
+        assert_eq!(config.cache_dir, PathBuf::from("tmp/cache"));
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_formatting() {
+        let spelled_out = Config::from_toml_str("# defaults\nrelative_ids = true\n").unwrap();
+        assert_eq!(spelled_out.fingerprint(), Config::default().fingerprint());
+        let changed = Config::from_toml_str("relative_ids = false").unwrap();
+        assert_ne!(changed.fingerprint(), Config::default().fingerprint());
+    }
+}
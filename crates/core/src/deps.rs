@@ -0,0 +1,478 @@
+//! Package metadata from requirements and lock files
+//!
+//! External module nodes (see [`resolve::link_external_modules()`](crate::resolve::link_external_modules()))
+//! only know the name used in `import` statements. This module reads the
+//! project's dependency declarations so those nodes can carry the
+//! distribution name and version:
+//!
+//! - `requirements.txt`: one PEP 508 requirement per line
+//! - `pyproject.toml`: `[project]` dependencies and Poetry dependency tables
+//! - `poetry.lock`: exact locked versions, which take precedence
+//!
+//! With packages attached, [`users_of()`] answers "which functions use any
+//! package we plan to drop?".
+
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::{EdgeType, Graph, NodeType, PackageInfo};
+use crate::resolve::EXTERNAL_ID_PREFIX;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Files read by [`Dependencies::from_project()`], in precedence order (last wins)
+pub const MANIFEST_FILES: &[&str] = &["requirements.txt", "pyproject.toml", "poetry.lock"];
+
+/// Import names that differ from their distribution name
+const IMPORT_ALIASES: &[(&str, &str)] = &[
+    ("attr", "attrs"),
+    ("bs4", "beautifulsoup4"),
+    ("cv2", "opencv-python"),
+    ("dateutil", "python-dateutil"),
+    ("dotenv", "python-dotenv"),
+    ("jwt", "pyjwt"),
+    ("pil", "pillow"),
+    ("sklearn", "scikit-learn"),
+    ("yaml", "pyyaml"),
+];
+
+/// A declared dependency
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Package {
+    /// Distribution name as declared
+    pub name: String,
+    /// Version constraint as declared (e.g., `>=2.0,<3`)
+    pub requirement: Option<String>,
+    /// Exact version, from a lock file or an `==` pin
+    pub version: Option<String>,
+}
+
+/// Declared dependencies keyed by normalized distribution name
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Dependencies {
+    packages: BTreeMap<String, Package>,
+}
+
+impl Dependencies {
+    /// Read every file in [`MANIFEST_FILES`] present in `root`
+    ///
+    /// # Errors
+    /// Returns an error if a present file cannot be read or is not valid TOML.
+    #[cfg(feature = "std-fs")]
+    pub fn from_project(root: &Path) -> Result<Self> {
+        let mut deps = Self::default();
+        for name in MANIFEST_FILES {
+            let path = root.join(name);
+            if path.is_file() {
+                deps.load(&path)?;
+            }
+        }
+        Ok(deps)
+    }
+
+    /// Merge one manifest, choosing the format from its file name
+    ///
+    /// Files ending in `.txt` are read as requirements files.
+    #[cfg(feature = "std-fs")]
+    pub fn load(&mut self, path: &Path) -> Result<()> {
+        let text = std::fs::read_to_string(path).map_err(|e| GraphMigratorError::io(path, e))?;
+        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        match file_name {
+            "pyproject.toml" => self.add_pyproject(&text),
+            "poetry.lock" => self.add_poetry_lock(&text),
+            _ if file_name.ends_with(".txt") => {
+                self.add_requirements(&text);
+                Ok(())
+            }
+            _ => Err(GraphMigratorError::parse(path, "unknown manifest format")),
+        }
+        .map_err(|e| match e {
+            GraphMigratorError::Parse { detail, .. } => GraphMigratorError::parse(path, detail),
+            other => other,
+        })
+    }
+
+    /// Merge a requirements file
+    ///
+    /// Options (`-r`, `-e`, `--index-url`) and comments are ignored.
+    pub fn add_requirements(&mut self, text: &str) {
+        for line in text.lines() {
+            let line = line.split(" #").next().unwrap_or("").trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('-') {
+                continue;
+            }
+            if let Some(package) = parse_requirement(line) {
+                self.insert(package);
+            }
+        }
+    }
+
+    /// Merge `[project]` and `[tool.poetry]` dependencies from `pyproject.toml`
+    pub fn add_pyproject(&mut self, text: &str) -> Result<()> {
+        let doc = parse_toml(text)?;
+
+        if let Some(project) = doc.get("project") {
+            let optional = project
+                .get("optional-dependencies")
+                .and_then(|v| v.as_table())
+                .into_iter()
+                .flat_map(|groups| groups.values());
+            for list in project.get("dependencies").into_iter().chain(optional) {
+                for requirement in list.as_array().into_iter().flatten() {
+                    if let Some(package) = requirement.as_str().and_then(parse_requirement) {
+                        self.insert(package);
+                    }
+                }
+            }
+        }
+
+        if let Some(poetry) = doc.get("tool").and_then(|t| t.get("poetry")) {
+            let groups = poetry
+                .get("group")
+                .and_then(|g| g.as_table())
+                .into_iter()
+                .flat_map(|groups| groups.values())
+                .filter_map(|group| group.get("dependencies"));
+            let tables = [poetry.get("dependencies"), poetry.get("dev-dependencies")];
+            for table in tables.into_iter().flatten().chain(groups) {
+                for (name, spec) in table.as_table().into_iter().flatten() {
+                    if name != "python" {
+                        self.insert(poetry_package(name, spec));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Merge exact versions from `poetry.lock`
+    pub fn add_poetry_lock(&mut self, text: &str) -> Result<()> {
+        let doc = parse_toml(text)?;
+        for entry in doc
+            .get("package")
+            .and_then(|p| p.as_array())
+            .into_iter()
+            .flatten()
+        {
+            let name = entry.get("name").and_then(|v| v.as_str());
+            let version = entry.get("version").and_then(|v| v.as_str());
+            if let (Some(name), Some(version)) = (name, version) {
+                self.insert(Package {
+                    name: name.to_string(),
+                    requirement: None,
+                    version: Some(version.to_string()),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Add a package, keeping earlier details the new entry lacks
+    pub fn insert(&mut self, package: Package) {
+        match self.packages.get_mut(&normalize(&package.name)) {
+            Some(existing) => {
+                if package.requirement.is_some() {
+                    existing.requirement = package.requirement;
+                }
+                if package.version.is_some() {
+                    existing.version = package.version;
+                }
+            }
+            None => {
+                self.packages.insert(normalize(&package.name), package);
+            }
+        }
+    }
+
+    /// Look up a package by distribution name (case, `-`, `_` and `.` insensitive)
+    pub fn get(&self, name: &str) -> Option<&Package> {
+        self.packages.get(&normalize(name))
+    }
+
+    /// Package providing the top-level import `import_name` (e.g., `yaml`)
+    pub fn for_import(&self, import_name: &str) -> Option<&Package> {
+        let normalized = normalize(import_name);
+        self.packages.get(&normalized).or_else(|| {
+            IMPORT_ALIASES
+                .iter()
+                .find(|(import, _)| *import == normalized)
+                .and_then(|(_, dist)| self.packages.get(*dist))
+        })
+    }
+
+    /// All packages, ordered by normalized name
+    pub fn iter(&self) -> impl Iterator<Item = &Package> {
+        self.packages.values()
+    }
+
+    /// Number of packages
+    pub fn len(&self) -> usize {
+        self.packages.len()
+    }
+
+    /// Whether no packages were declared
+    pub fn is_empty(&self) -> bool {
+        self.packages.is_empty()
+    }
+
+    /// Set [`Node::package`](crate::graph::Node::package) on external module nodes
+    ///
+    /// Returns how many nodes were annotated.
+    pub fn annotate(&self, graph: &mut Graph) -> usize {
+        let indices: Vec<_> = graph.node_indices().collect();
+        let mut annotated = 0;
+        for idx in indices {
+            let Some(node) = graph.node_weight_mut(idx) else {
+                continue;
+            };
+            if !node.external || node.node_type != NodeType::Module {
+                continue;
+            }
+            if let Some(package) = self.for_import(&node.name) {
+                node.package = Some(PackageInfo {
+                    name: package.name.clone(),
+                    version: package.version.clone(),
+                });
+                annotated += 1;
+            }
+        }
+        annotated
+    }
+}
+
+/// IDs of symbols importing any of `packages`
+///
+/// Packages match an external node by distribution name if it was annotated,
+/// or by import name otherwise; matching ignores case, `-`, `_` and `.`.
+pub fn users_of(graph: &Graph, packages: &[&str]) -> BTreeSet<String> {
+    let wanted: BTreeSet<String> = packages.iter().map(|p| normalize(p)).collect();
+    let mut users = BTreeSet::new();
+    for (from, to, edge) in graph.edge_endpoints() {
+        if edge.edge_type != EdgeType::Imports {
+            continue;
+        }
+        let (Some(source), Some(target)) = (graph.node_weight(from), graph.node_weight(to)) else {
+            continue;
+        };
+        if !target.external || !target.id.starts_with(EXTERNAL_ID_PREFIX) {
+            continue;
+        }
+        let dist = target.package.as_ref().map(|p| normalize(&p.name));
+        if wanted.contains(&normalize(&target.name)) || dist.is_some_and(|d| wanted.contains(&d)) {
+            users.insert(source.id.clone());
+        }
+    }
+    users
+}
+
+/// PEP 503 normalization: lowercase, runs of `-`, `_`, `.` become `-`
+fn normalize(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !normalized.ends_with('-') {
+                normalized.push('-');
+            }
+        } else {
+            normalized.extend(c.to_lowercase());
+        }
+    }
+    normalized
+}
+
+/// Parse a PEP 508 requirement such as `requests[socks]>=2.0; python_version>"3"`
+fn parse_requirement(text: &str) -> Option<Package> {
+    let text = text.split(';').next().unwrap_or("").trim();
+    let name_len = text
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(text.len());
+    if name_len == 0 {
+        return None;
+    }
+    let (name, mut rest) = text.split_at(name_len);
+    rest = rest.trim_start();
+    if rest.starts_with('[') {
+        rest = rest.split_once(']').map(|(_, r)| r).unwrap_or("");
+    }
+    // `name @ https://...` pins a URL rather than a version
+    let requirement = rest.trim();
+    let requirement = (!requirement.is_empty() && !requirement.starts_with('@')).then(|| {
+        requirement
+            .trim_start_matches(['(', ' '])
+            .trim_end_matches(')')
+            .to_string()
+    });
+
+    let version = requirement.as_deref().and_then(|spec| {
+        let pinned = spec
+            .strip_prefix("===")
+            .or_else(|| spec.strip_prefix("=="))?;
+        (!pinned.contains(',') && !pinned.contains('*')).then(|| pinned.trim().to_string())
+    });
+
+    Some(Package {
+        name: name.to_string(),
+        requirement,
+        version,
+    })
+}
+
+/// A `[tool.poetry.dependencies]` entry: `"^2.0"` or `{ version = "^2.0", ... }`
+fn poetry_package(name: &str, spec: &toml::Value) -> Package {
+    let requirement = spec
+        .as_str()
+        .or_else(|| spec.get("version").and_then(|v| v.as_str()))
+        .map(str::to_string);
+    // Poetry treats a bare version as an exact pin
+    let version = requirement.as_deref().and_then(|r| {
+        let pinned = r.strip_prefix("==").unwrap_or(r);
+        pinned
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.')
+            .then(|| pinned.to_string())
+    });
+    Package {
+        name: name.to_string(),
+        requirement,
+        version,
+    }
+}
+
+fn parse_toml(text: &str) -> Result<toml::Value> {
+    text.parse::<toml::Value>()
+        .map_err(|e| GraphMigratorError::parse(Path::new(""), e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Edge, Node};
+
+    #[test]
+    fn test_requirements() {
+        let mut deps = Dependencies::default();
+        deps.add_requirements(
+            "# pinned\n\
+             requests[socks]==2.31.0  # http\n\
+             numpy>=1.24,<2 ; python_version >= \"3.9\"\n\
+             -r dev.txt\n\
+             PyYAML\n\
+             mylib @ https://example.com/mylib.zip\n",
+        );
+
+        assert_eq!(deps.len(), 4);
+        let requests = deps.get("requests").unwrap();
+        assert_eq!(requests.version.as_deref(), Some("2.31.0"));
+        let numpy = deps.get("NumPy").unwrap();
+        assert_eq!(numpy.requirement.as_deref(), Some(">=1.24,<2"));
+        assert_eq!(numpy.version, None);
+        assert_eq!(deps.for_import("yaml").unwrap().name, "PyYAML");
+        assert_eq!(deps.get("mylib").unwrap().requirement, None);
+    }
+
+    #[test]
+    fn test_pyproject_and_lock() {
+        let mut deps = Dependencies::default();
+        deps.add_pyproject(
+            r#"
+[project]
+dependencies = ["flask>=2", "scikit-learn"]
+
+[project.optional-dependencies]
+test = ["pytest==8.0.0"]
+
+[tool.poetry.dependencies]
+python = "^3.10"
+pandas = "2.1.0"
+celery = { version = "^5.3", extras = ["redis"] }
+
+[tool.poetry.group.dev.dependencies]
+black = "^24.1"
+"#,
+        )
+        .unwrap();
+        deps.add_poetry_lock(
+            r#"
+[[package]]
+name = "flask"
+version = "3.0.2"
+
+[[package]]
+name = "scikit-learn"
+version = "1.4.0"
+"#,
+        )
+        .unwrap();
+
+        assert!(deps.get("python").is_none());
+        assert_eq!(
+            deps.get("pytest").unwrap().version.as_deref(),
+            Some("8.0.0")
+        );
+        assert_eq!(
+            deps.get("pandas").unwrap().version.as_deref(),
+            Some("2.1.0")
+        );
+        assert_eq!(
+            deps.get("celery").unwrap().requirement.as_deref(),
+            Some("^5.3")
+        );
+        assert_eq!(deps.get("black").unwrap().version, None);
+
+        // Lock versions fill in, declared constraints are kept
+        let flask = deps.get("Flask").unwrap();
+        assert_eq!(flask.requirement.as_deref(), Some(">=2"));
+        assert_eq!(flask.version.as_deref(), Some("3.0.2"));
+        assert_eq!(
+            deps.for_import("sklearn").unwrap().version.as_deref(),
+            Some("1.4.0")
+        );
+
+        assert!(matches!(
+            deps.add_pyproject("[project"),
+            Err(GraphMigratorError::Parse { .. })
+        ));
+    }
+
+    #[test]
+    fn test_annotate_and_users_of() {
+        let mut graph = Graph::new();
+        let main = graph.add_node(Node::new(
+            "m.py::main",
+            "main",
+            NodeType::Function,
+            "python",
+            "m.py",
+        ));
+        let load = graph.add_node(Node::new(
+            "m.py::load",
+            "load",
+            NodeType::Function,
+            "python",
+            "m.py",
+        ));
+        let mut yaml = Node::new("external::yaml", "yaml", NodeType::Module, "python", "");
+        yaml.external = true;
+        let mut os = Node::new("external::os", "os", NodeType::Module, "python", "");
+        os.external = true;
+        let yaml = graph.add_node(yaml);
+        let os = graph.add_node(os);
+        let imports = Edge::new(EdgeType::Imports);
+        graph.add_edge(main, os, imports.clone());
+        graph.add_edge(load, yaml, imports);
+
+        let mut deps = Dependencies::default();
+        deps.add_requirements("PyYAML==6.0.1\n");
+        assert_eq!(deps.annotate(&mut graph), 1);
+
+        let package = graph.node_weight(yaml).unwrap().package.clone().unwrap();
+        assert_eq!(package.name, "PyYAML");
+        assert_eq!(package.version.as_deref(), Some("6.0.1"));
+
+        // Distribution and import names both match
+        assert_eq!(
+            users_of(&graph, &["pyyaml"]),
+            BTreeSet::from(["m.py::load".to_string()])
+        );
+        assert_eq!(users_of(&graph, &["yaml", "os"]).len(), 2);
+        assert!(users_of(&graph, &["numpy"]).is_empty());
+    }
+}
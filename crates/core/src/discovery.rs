@@ -3,10 +3,30 @@
 //! This module provides utilities for discovering files in a project directory
 //! while respecting .gitignore patterns. The API is generic and works with any
 //! language via glob patterns.
+//!
+//! In addition to git ignores, any `.migratorignore` file (same syntax as
+//! .gitignore) is honored, so teams can exclude test fixtures or generated
+//! code from the graph without touching .gitignore.
+//!
+//! Scripts often have no extension for a glob to match (`bin/migrate`
+//! starting with `#!/usr/bin/env python`). With
+//! [`DiscoveryOptions::shebangs()`], files without an extension are also
+//! returned when their `#!` line runs an interpreter of a chosen language.
+//!
+//! [`DiscoveryOptions::skip_generated()`] leaves out files whose header
+//! marks them as generated (see [`parser::generated`](crate::parser::generated)).
 
+use crate::config::Config;
+use crate::parser::{generated, Language};
+use crate::paths::PathResolver;
 use ignore::WalkBuilder;
+use std::collections::{BTreeMap, HashSet};
+use std::io::Read;
 use std::path::{Path, PathBuf};
 
+/// Per-directory ignore file layered on top of .gitignore
+pub const MIGRATOR_IGNORE_FILE: &str = ".migratorignore";
+
 /// Discover files matching glob patterns in a project directory
 ///
 /// # Arguments
@@ -28,26 +48,139 @@ use std::path::{Path, PathBuf};
 /// let src_tests = discovery::discover_files(std::path::Path::new("my_project"), &["src/**/*.py", "tests/**/*.py"]);
 /// ```
 pub fn discover_files(root: &Path, patterns: &[&str]) -> Vec<PathBuf> {
-    // Canonicalize root upfront to ensure all returned paths are absolute
-    // If root doesn't exist or can't be canonicalized, return empty vec
-    let canonical_root = match root.canonicalize() {
-        Ok(path) => path,
-        Err(_) => return Vec::new(),
+    discover_with_options(root, &DiscoveryOptions::new(patterns))
+}
+
+/// Options controlling file discovery beyond include globs
+///
+/// Built with a consuming builder so call sites read declaratively:
+///
+/// ```no_run
+/// use graph_migrator_core::discovery::{self, DiscoveryOptions};
+///
+/// let options = DiscoveryOptions::new(&["**/*.py"])
+///     .exclude(&["**/migrations/**", "**/*_pb2.py"])
+///     .max_depth(8)
+///     .max_file_size(1024 * 1024);
+/// let files = discovery::discover_with_options(std::path::Path::new("my_project"), &options);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiscoveryOptions {
+    include: Vec<String>,
+    exclude: Vec<String>,
+    max_depth: Option<usize>,
+    follow_symlinks: bool,
+    max_file_size: Option<u64>,
+    shebangs: Vec<Language>,
+    interpreters: BTreeMap<String, Language>,
+    skip_generated: bool,
+    paths: PathResolver,
+}
+
+impl DiscoveryOptions {
+    /// Create options with the given include glob patterns
+    pub fn new(patterns: &[&str]) -> Self {
+        Self {
+            include: patterns.iter().map(|p| p.to_string()).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Skip files matching these globs (relative to the root), even if included
+    pub fn exclude(mut self, patterns: &[&str]) -> Self {
+        self.exclude.extend(patterns.iter().map(|p| p.to_string()));
+        self
+    }
+
+    /// Limit directory recursion depth (the root itself is depth 0)
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Follow symbolic links to files and directories (default: skip them)
+    ///
+    /// Links that lead back into a directory being walked are reported and
+    /// skipped, and a file reachable through several links is returned once,
+    /// under the path that sorts first.
+    pub fn follow_symlinks(mut self, follow: bool) -> Self {
+        self.follow_symlinks = follow;
+        self
+    }
+
+    /// Skip files larger than `bytes`
+    pub fn max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = Some(bytes);
+        self
+    }
+
+    /// Also return files without an extension whose `#!` line runs an
+    /// interpreter of one of `languages` (see [`Language::from_shebang()`])
+    ///
+    /// Exclude globs and ignore files still apply.
+    pub fn shebangs(mut self, languages: &[Language]) -> Self {
+        self.shebangs = languages.to_vec();
+        self
+    }
+
+    /// Interpreter names recognized by [`shebangs()`](Self::shebangs) beyond
+    /// the built-in ones, with their language
+    pub fn interpreters(mut self, interpreters: BTreeMap<String, Language>) -> Self {
+        self.interpreters = interpreters;
+        self
+    }
+
+    /// Skip files whose header marks them as generated
+    /// (see [`generated::is_generated()`])
+    pub fn skip_generated(mut self, skip: bool) -> Self {
+        self.skip_generated = skip;
+        self
+    }
+
+    /// Resolve the root, and so every returned path, with `paths`
+    /// (default: [`PathResolver::Relative`], which keeps symlinks in the
+    /// root as given)
+    pub fn paths(mut self, paths: PathResolver) -> Self {
+        self.paths = paths;
+        self
+    }
+}
+
+/// Discover files in a project directory using explicit [`DiscoveryOptions`]
+///
+/// # Arguments
+/// * `root` - Root directory to search
+/// * `options` - Include/exclude globs, depth, symlink and size limits
+///
+/// # Returns
+/// Vector of absolute paths to matching files (and scripts, if enabled),
+/// under the root as resolved by [`DiscoveryOptions::paths()`], excluding
+/// those matched by .gitignore or by the exclude globs. Invalid glob
+/// patterns yield an empty result.
+pub fn discover_with_options(root: &Path, options: &DiscoveryOptions) -> Vec<PathBuf> {
+    // Resolve root upfront to ensure all returned paths are absolute
+    // If root doesn't exist or can't be resolved, return empty vec
+    let resolved_root = match options.paths.resolve(root) {
+        Ok(path) if path.exists() => path,
+        _ => return Vec::new(),
     };
 
     let mut files = Vec::new();
 
-    // Build a glob set from the provided patterns for efficient matching
-    let glob_matcher = match build_glob_matcher(patterns) {
-        Ok(matcher) => matcher,
-        Err(_) => {
+    // Build glob sets from the provided patterns for efficient matching
+    let (glob_matcher, exclude_matcher) = match (
+        build_glob_matcher(&options.include),
+        build_glob_matcher(&options.exclude),
+    ) {
+        (Ok(include), Ok(exclude)) => (include, exclude),
+        _ => {
             // If glob patterns are invalid, return empty results
             return Vec::new();
         }
     };
 
     // Use WalkBuilder for idiomatic gitignore-aware traversal
-    let walker = build_walker(&canonical_root);
+    let walker = build_walker(&resolved_root, options);
 
     for result in walker {
         match result {
@@ -55,10 +188,15 @@ pub fn discover_files(root: &Path, patterns: &[&str]) -> Vec<PathBuf> {
                 // Skip directories - we only want files
                 if let Some(ft) = entry.file_type() {
                     if ft.is_file() {
-                        // Get the path relative to canonical_root for glob matching
-                        if let Ok(rel_path) = entry.path().strip_prefix(&canonical_root) {
+                        // Get the path relative to resolved_root for glob matching
+                        if let Ok(rel_path) = entry.path().strip_prefix(&resolved_root) {
                             // Check if the file matches any of our patterns
-                            if glob_matcher.is_match(rel_path) {
+                            let included =
+                                glob_matcher.is_match(rel_path) || is_script(entry.path(), options);
+                            if included
+                                && !exclude_matcher.is_match(rel_path)
+                                && !(options.skip_generated && is_generated(entry.path()))
+                            {
                                 // WalkBuilder already gives us absolute paths
                                 files.push(entry.into_path());
                             }
@@ -68,11 +206,50 @@ pub fn discover_files(root: &Path, patterns: &[&str]) -> Vec<PathBuf> {
             }
             Err(err) => {
                 // Log walk errors but continue processing other files
-                eprintln!("Warning: Error walking directory: {}", err);
+                tracing::warn!(%err, "error walking directory");
             }
         }
     }
 
+    if options.follow_symlinks {
+        // Several links can lead to the same file, and would give it a node
+        // per path, so keep one of them
+        files.sort();
+        let mut seen = HashSet::new();
+        files.retain(|path| seen.insert(path.canonicalize().unwrap_or_else(|_| path.clone())));
+    }
+
+    tracing::debug!(root = %resolved_root.display(), files = files.len(), "discovered files");
+    files
+}
+
+/// Files of the project at `root`: those [`Config::discovery_options()`]
+/// selects, then, with `[virtualenv] scan_editable`, the Python files of the
+/// editable installs whose source is outside `root`
+///
+/// See [`resolve::editable_roots()`](crate::resolve::editable_roots()).
+/// The include and exclude globs of `[discovery]` are relative to the
+/// project root, so they do not apply to editable sources.
+pub fn discover_project(root: &Path, config: &Config) -> Vec<PathBuf> {
+    let mut files = discover_with_options(root, &config.discovery_options());
+    if !config.virtualenv.scan_editable {
+        return files;
+    }
+    let Some(venv) = &config.virtualenv.path else {
+        return files;
+    };
+    let project = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let options = DiscoveryOptions::new(&["**/*.py"])
+        .follow_symlinks(config.discovery.follow_symlinks)
+        .paths(config.path_resolver());
+    for source in crate::resolve::editable_roots(&root.join(venv)) {
+        let outside = source
+            .canonicalize()
+            .map_or(true, |source| !source.starts_with(&project));
+        if outside {
+            files.extend(discover_with_options(&source, &options));
+        }
+    }
     files
 }
 
@@ -95,27 +272,62 @@ pub fn discover_python_files(root: &Path) -> Vec<PathBuf> {
     discover_files(root, &["**/*.py"])
 }
 
+/// Whether `path` has no extension and a `#!` line running an interpreter
+/// of one of the languages of [`DiscoveryOptions::shebangs()`]
+fn is_script(path: &Path, options: &DiscoveryOptions) -> bool {
+    if options.shebangs.is_empty() || path.extension().is_some() {
+        return false;
+    }
+    // Long enough for any `#!` line worth recognizing
+    read_head(path, 256).is_some_and(|head| {
+        Language::from_shebang(&head, &options.interpreters)
+            .is_some_and(|language| options.shebangs.contains(&language))
+    })
+}
+
+/// Whether the header of the file at `path` marks it as generated
+fn is_generated(path: &Path) -> bool {
+    let generated = read_head(path, 4096).is_some_and(|head| generated::is_generated(&head));
+    if generated {
+        tracing::debug!(path = %path.display(), "skipping generated file");
+    }
+    generated
+}
+
+/// Up to the first `len` bytes of the file at `path`
+fn read_head(path: &Path, len: u64) -> Option<Vec<u8>> {
+    let mut head = Vec::new();
+    let file = std::fs::File::open(path).ok()?;
+    file.take(len).read_to_end(&mut head).ok()?;
+    Some(head)
+}
+
 /// Build a glob matcher from the provided patterns
 ///
 /// This converts the string patterns into a GlobSet for efficient matching.
-fn build_glob_matcher(patterns: &[&str]) -> Result<globset::GlobSet, globset::Error> {
+fn build_glob_matcher<S: AsRef<str>>(patterns: &[S]) -> Result<globset::GlobSet, globset::Error> {
     use globset::GlobSetBuilder;
 
     let mut builder = GlobSetBuilder::new();
     for pattern in patterns {
-        builder.add(globset::Glob::new(pattern)?);
+        builder.add(globset::Glob::new(pattern.as_ref())?);
     }
     builder.build()
 }
 
 /// Build a WalkBuilder with proper ignore configuration
-fn build_walker(root: &Path) -> ignore::Walk {
+fn build_walker(root: &Path, options: &DiscoveryOptions) -> ignore::Walk {
     let mut builder = WalkBuilder::new(root);
     builder
         .git_ignore(true)
         .git_exclude(true)
         .hidden(false)
-        .parents(true);  // Also check parent directories for .gitignore
+        .parents(true) // Also check parent directories for .gitignore
+        .max_depth(options.max_depth)
+        .follow_links(options.follow_symlinks)
+        .max_filesize(options.max_file_size)
+        // .migratorignore files take precedence over .gitignore in the same directory
+        .add_custom_ignore_filename(MIGRATOR_IGNORE_FILE);
 
     // Explicitly add .gitignore if it exists (needed for test environments
     // where WalkBuilder may not automatically discover it)
@@ -173,6 +385,47 @@ mod tests {
         assert!(!files.iter().any(|p| p.to_string_lossy().contains("venv")));
     }
 
+    #[test]
+    fn test_respect_migratorignore() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        let mut ignore_file = File::create(root.join(MIGRATOR_IGNORE_FILE)).unwrap();
+        ignore_file.write_all(b"fixtures/\n*_pb2.py\n").unwrap();
+
+        fs::create_dir_all(root.join("fixtures")).unwrap();
+        File::create(root.join("fixtures/sample.py")).unwrap();
+        File::create(root.join("api_pb2.py")).unwrap();
+        File::create(root.join("main.py")).unwrap();
+
+        let files = discover_python_files(root);
+
+        assert_eq!(files.len(), 1);
+        assert!(files[0].ends_with("main.py"));
+    }
+
+    #[test]
+    fn test_migratorignore_in_subdirectory() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        fs::create_dir_all(root.join("pkg")).unwrap();
+        fs::write(
+            root.join("pkg").join(MIGRATOR_IGNORE_FILE),
+            "generated.py\n",
+        )
+        .unwrap();
+        File::create(root.join("pkg/generated.py")).unwrap();
+        File::create(root.join("pkg/handwritten.py")).unwrap();
+        File::create(root.join("generated.py")).unwrap();
+
+        let files = discover_python_files(root);
+
+        // Only the nested generated.py is ignored; the root one is out of scope
+        assert_eq!(files.len(), 2);
+        assert!(!files.iter().any(|p| p.ends_with("pkg/generated.py")));
+    }
+
     #[test]
     fn test_custom_patterns() {
         let temp_dir = TempDir::new().unwrap();
@@ -194,6 +447,131 @@ mod tests {
         assert!(files.iter().any(|p| p.to_string_lossy().contains("tests/")));
     }
 
+    #[test]
+    fn test_exclude_patterns() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        fs::create_dir_all(root.join("src/generated")).unwrap();
+        File::create(root.join("src/main.py")).unwrap();
+        File::create(root.join("src/generated/api_pb2.py")).unwrap();
+
+        let options = DiscoveryOptions::new(&["**/*.py"]).exclude(&["**/generated/**"]);
+        let files = discover_with_options(root, &options);
+
+        assert_eq!(files.len(), 1);
+        assert!(files[0].ends_with("src/main.py"));
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        fs::create_dir_all(root.join("a/b")).unwrap();
+        File::create(root.join("top.py")).unwrap();
+        File::create(root.join("a/mid.py")).unwrap();
+        File::create(root.join("a/b/deep.py")).unwrap();
+
+        let options = DiscoveryOptions::new(&["**/*.py"]).max_depth(2);
+        let files = discover_with_options(root, &options);
+
+        assert_eq!(files.len(), 2);
+        assert!(!files.iter().any(|p| p.ends_with("deep.py")));
+    }
+
+    #[test]
+    fn test_max_file_size() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        File::create(root.join("small.py")).unwrap();
+        let mut big = File::create(root.join("big.py")).unwrap();
+        big.write_all(&[b'#'; 2048]).unwrap();
+
+        let options = DiscoveryOptions::new(&["**/*.py"]).max_file_size(1024);
+        let files = discover_with_options(root, &options);
+
+        assert_eq!(files.len(), 1);
+        assert!(files[0].ends_with("small.py"));
+    }
+
+    #[test]
+    fn test_shebang_scripts() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir(root.join("bin")).unwrap();
+        fs::write(
+            root.join("bin/migrate"),
+            "#!/usr/bin/env python3\nimport sys\n",
+        )
+        .unwrap();
+        fs::write(root.join("bin/deploy"), "#!/bin/bash\necho hi\n").unwrap();
+        fs::write(root.join("bin/tool"), "#!/opt/bin/jython\n").unwrap();
+        fs::write(root.join("bin/notes.txt"), "#!/usr/bin/env python\n").unwrap();
+        fs::write(root.join("app.py"), "").unwrap();
+
+        let options = DiscoveryOptions::new(&["**/*.py"]);
+        assert_eq!(discover_with_options(root, &options).len(), 1);
+
+        let interpreters = BTreeMap::from([("jython".to_string(), Language::Python)]);
+        let options = options
+            .shebangs(&[Language::Python])
+            .interpreters(interpreters);
+        let mut names: Vec<String> = discover_with_options(root, &options)
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["app.py", "migrate", "tool"]);
+
+        let options = options.exclude(&["bin/**"]);
+        assert_eq!(discover_with_options(root, &options).len(), 1);
+    }
+
+    #[test]
+    fn test_skip_generated() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("models.py"), "class Order:\n    pass\n").unwrap();
+        fs::write(
+            root.join("orders_pb2.py"),
+            "# -*- coding: utf-8 -*-\n# Generated by the protocol buffer compiler.  DO NOT EDIT!\n",
+        )
+        .unwrap();
+
+        let options = DiscoveryOptions::new(&["**/*.py"]);
+        assert_eq!(discover_with_options(root, &options).len(), 2);
+        let files = discover_with_options(root, &options.skip_generated(true));
+        assert_eq!(files.len(), 1);
+        assert!(files[0].ends_with("models.py"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_policy() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        fs::create_dir_all(root.join("real")).unwrap();
+        File::create(root.join("real/mod.py")).unwrap();
+        std::os::unix::fs::symlink(root.join("real"), root.join("linked")).unwrap();
+        // A loop back to the root, and a directory only reachable through a link
+        std::os::unix::fs::symlink(root, root.join("real/loop")).unwrap();
+        let outside = TempDir::new().unwrap();
+        File::create(outside.path().join("vendored.py")).unwrap();
+        std::os::unix::fs::symlink(outside.path(), root.join("vendor")).unwrap();
+
+        let skipped = discover_with_options(root, &DiscoveryOptions::new(&["**/*.py"]));
+        assert_eq!(skipped.len(), 1);
+
+        let options = DiscoveryOptions::new(&["**/*.py"]).follow_symlinks(true);
+        let followed = discover_with_options(root, &options);
+        assert_eq!(followed.len(), 2);
+        assert!(followed[0].ends_with("linked/mod.py"));
+        assert!(followed[1].ends_with("vendor/vendored.py"));
+    }
+
     #[test]
     fn test_empty_directory() {
         let temp_dir = TempDir::new().unwrap();
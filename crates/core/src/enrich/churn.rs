@@ -0,0 +1,224 @@
+//! Churn: how often each symbol changed recently
+//!
+//! Runs `git log -U0` once per file over a window of recent days and walks
+//! the commits newest first, tracking each node's line range back through
+//! every diff (as `git log -L` does) and counting the commits whose hunks
+//! overlap it. Code that keeps changing is risky to migrate while it is
+//! still moving; sorting by [`Node::churn`](crate::Node::churn) surfaces
+//! those hot spots.
+//!
+//! Commits are walked in the order `git log` lists them, without following
+//! renames, so counts for files with branchy or renamed history are
+//! approximate.
+
+use super::{git_for_file, nodes_by_file};
+use crate::error::Result;
+use crate::graph::Graph;
+
+/// One `@@ -old_start,old_len +new_start,new_len @@` hunk header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Hunk {
+    old_start: usize,
+    old_len: usize,
+    new_start: usize,
+    new_len: usize,
+}
+
+impl Hunk {
+    /// Whether the hunk changes any line of the inclusive range `start..=end`
+    /// (in post-image line numbers)
+    fn touches(&self, start: usize, end: usize) -> bool {
+        if self.new_len == 0 {
+            // Pure deletion between `new_start` and the following line
+            start <= self.new_start && self.new_start < end
+        } else {
+            self.new_start <= end && start < self.new_start + self.new_len
+        }
+    }
+}
+
+/// Set [`Node::churn`](crate::Node::churn) on every project node with a
+/// line range: the number of commits in the last `days` days that changed
+/// any of its lines
+///
+/// Returns how many nodes were annotated; nodes of files git cannot log are
+/// left unset.
+///
+/// # Errors
+/// Returns [`GraphMigratorError::Io`](crate::GraphMigratorError::Io) if
+/// `git` cannot be run at all. Per-file failures are logged and skipped.
+pub fn churn(graph: &mut Graph, days: u32) -> Result<usize> {
+    let since = format!("--since={} days ago", days);
+    let mut annotated = 0;
+    for (file, indices) in nodes_by_file(graph) {
+        let args = ["log", "--no-merges", "--no-color", "--no-ext-diff", "-U0"];
+        let format = "--format=commit %H";
+        let Some(output) = git_for_file(&file, args.iter().copied().chain([format, &since]))?
+        else {
+            continue;
+        };
+        let commits = parse_log(&output);
+        for idx in indices {
+            let Some(node) = graph.node_weight_mut(idx) else {
+                continue;
+            };
+            if let Some(range) = node.line_range {
+                node.churn = Some(count_touching(&commits, range));
+                annotated += 1;
+            }
+        }
+    }
+    Ok(annotated)
+}
+
+/// Hunks of each commit in `git log -U0` output, newest commit first
+fn parse_log(text: &str) -> Vec<Vec<Hunk>> {
+    let mut commits: Vec<Vec<Hunk>> = Vec::new();
+    for line in text.lines() {
+        if line.starts_with("commit ") {
+            commits.push(Vec::new());
+        } else if let Some(header) = line.strip_prefix("@@ -") {
+            if let (Some(commit), Some(hunk)) = (commits.last_mut(), parse_hunk(header)) {
+                commit.push(hunk);
+            }
+        }
+    }
+    commits
+}
+
+/// `12,3 +14,5 @@ ...` (the part after `@@ -`); a missing length means 1
+fn parse_hunk(header: &str) -> Option<Hunk> {
+    let (old, rest) = header.split_once(" +")?;
+    let (new, _) = rest.split_once(" @@")?;
+    let span = |text: &str| -> Option<(usize, usize)> {
+        match text.split_once(',') {
+            Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+            None => Some((text.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_len) = span(old)?;
+    let (new_start, new_len) = span(new)?;
+    Some(Hunk {
+        old_start,
+        old_len,
+        new_start,
+        new_len,
+    })
+}
+
+/// Commits, newest first, that changed the 1-indexed inclusive `range`
+/// as it stands after the newest commit
+fn count_touching(commits: &[Vec<Hunk>], (mut start, mut end): (usize, usize)) -> u32 {
+    let mut count = 0;
+    for hunks in commits {
+        if hunks.iter().any(|hunk| hunk.touches(start, end)) {
+            count += 1;
+        }
+        start = old_line(hunks, start, true);
+        end = old_line(hunks, end, false);
+        if start > end {
+            // Every line was added by this commit; it had no earlier history
+            break;
+        }
+    }
+    count
+}
+
+/// Where post-image `line` was before the commit with `hunks`
+///
+/// Lines inside a hunk map to its first old line (`is_start`) or its last,
+/// so a range shrinks over lines that did not exist yet.
+fn old_line(hunks: &[Hunk], line: usize, is_start: bool) -> usize {
+    let mut shift: isize = 0;
+    for hunk in hunks {
+        let new_end = hunk.new_start + hunk.new_len;
+        if hunk.new_len > 0 && (hunk.new_start..new_end).contains(&line) {
+            return if is_start {
+                // Pure additions have no old line; the range starts after them
+                hunk.old_start + usize::from(hunk.old_len == 0)
+            } else {
+                (hunk.old_start + hunk.old_len).saturating_sub(1)
+            };
+        }
+        let before = if hunk.new_len == 0 {
+            hunk.new_start < line
+        } else {
+            new_end <= line
+        };
+        if before {
+            shift += hunk.old_len as isize - hunk.new_len as isize;
+        }
+    }
+    line.saturating_add_signed(shift)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Newest first: a one-line edit, an insertion above, then the file's creation
+    const LOG: &str = "\
+commit 3333333333333333333333333333333333333333
+
+diff --git a/m.py b/m.py
+--- a/m.py
++++ b/m.py
+@@ -6 +6 @@ def g():
+-    return 1
++    return 2
+commit 2222222222222222222222222222222222222222
+
+diff --git a/m.py b/m.py
+--- a/m.py
++++ b/m.py
+@@ -0,0 +1,2 @@
++import os
++
+commit 1111111111111111111111111111111111111111
+
+diff --git a/m.py b/m.py
+new file mode 100644
+--- /dev/null
++++ b/m.py
+@@ -0,0 +1,4 @@
++def f():
++    pass
++def g():
++    return 1
+";
+
+    #[test]
+    fn test_churn_follows_ranges_through_history() {
+        let commits = parse_log(LOG);
+        assert_eq!(commits.len(), 3);
+        assert_eq!(
+            commits[0],
+            vec![Hunk {
+                old_start: 6,
+                old_len: 1,
+                new_start: 6,
+                new_len: 1
+            }]
+        );
+
+        // f is lines 3-4 now: shifted by the import, untouched by the edit
+        assert_eq!(count_touching(&commits, (3, 4)), 1);
+        // g is lines 5-6: edited, then created
+        assert_eq!(count_touching(&commits, (5, 6)), 2);
+        // The import was added in commit 2 and has no earlier history
+        assert_eq!(count_touching(&commits, (1, 1)), 1);
+    }
+
+    #[test]
+    fn test_deletion_inside_range_counts() {
+        let deletion = Hunk {
+            old_start: 4,
+            old_len: 2,
+            new_start: 3,
+            new_len: 0,
+        };
+        assert!(deletion.touches(2, 5));
+        assert!(!deletion.touches(4, 8));
+        assert_eq!(old_line(&[deletion], 4, true), 6);
+    }
+}
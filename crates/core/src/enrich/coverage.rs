@@ -0,0 +1,294 @@
+//! Test coverage enrichment: how much of each symbol the tests execute
+//!
+//! Reads a line coverage report and sets [`Node::coverage`](crate::Node::coverage)
+//! to the percentage of each node's measured lines that ran. Two formats are
+//! understood, detected from the content:
+//!
+//! - Cobertura XML, as written by `coverage xml` (coverage.py) and most CI tools
+//! - lcov tracefiles, as written by `coverage lcov` or `pytest --cov-report=lcov`
+//!
+//! Untested code is the riskiest to port: nothing tells you the rewrite
+//! behaves the same. Combined with
+//! [`queries::migration_frontier()`](crate::queries::migration_frontier()),
+//! this finds what needs tests before it is migrated.
+
+use super::nodes_by_file;
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::Graph;
+use crate::paths::PathResolver;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Execution count per 1-indexed line, for the lines a report measured
+type LineHits = BTreeMap<usize, u64>;
+
+/// Files in a report as written there, and extra directories to resolve them against
+#[derive(Debug, Default)]
+struct Report {
+    files: Vec<(PathBuf, LineHits)>,
+    /// Cobertura `<source>` directories
+    sources: Vec<PathBuf>,
+}
+
+/// Set [`Node::coverage`](crate::Node::coverage) on every project node whose
+/// lines appear in the coverage report at `report`
+///
+/// Relative file names in the report are resolved against the report's
+/// `<source>` directories (Cobertura), then `root`, then the report's own
+/// directory. Report and node file paths are both resolved with `paths`, the
+/// resolver the graph was scanned with, so they match under a symlinked
+/// root. Nodes without measured lines (or in files the report does not
+/// mention) are left unset. Returns how many nodes were annotated.
+///
+/// # Errors
+/// Returns [`GraphMigratorError::Io`] if the report cannot be read, or
+/// [`GraphMigratorError::Parse`] if it is neither valid Cobertura XML nor lcov.
+pub fn coverage(
+    graph: &mut Graph,
+    report: &Path,
+    root: &Path,
+    paths: PathResolver,
+) -> Result<usize> {
+    let text = std::fs::read_to_string(report).map_err(|e| GraphMigratorError::io(report, e))?;
+    let parsed = if text.trim_start().starts_with('<') {
+        parse_cobertura(&text).map_err(|detail| GraphMigratorError::parse(report, detail))?
+    } else {
+        parse_lcov(&text).map_err(|detail| GraphMigratorError::parse(report, detail))?
+    };
+
+    let mut bases = parsed.sources.clone();
+    bases.push(root.to_path_buf());
+    bases.extend(report.parent().map(Path::to_path_buf));
+    let mut by_file: HashMap<PathBuf, LineHits> = HashMap::new();
+    for (file, hits) in parsed.files {
+        let Some(path) = locate(&file, &bases, paths) else {
+            tracing::debug!(file = %file.display(), "coverage report names a missing file");
+            continue;
+        };
+        let merged = by_file.entry(path).or_default();
+        for (line, count) in hits {
+            *merged.entry(line).or_default() += count;
+        }
+    }
+
+    let mut annotated = 0;
+    for (file, indices) in nodes_by_file(graph) {
+        let file = paths.resolve(&file).unwrap_or(file);
+        let Some(hits) = by_file.get(&file) else {
+            continue;
+        };
+        for idx in indices {
+            let Some(node) = graph.node_weight_mut(idx) else {
+                continue;
+            };
+            let Some((start, end)) = node.line_range else {
+                continue;
+            };
+            node.coverage = percent_covered(hits, start, end);
+            annotated += usize::from(node.coverage.is_some());
+        }
+    }
+    Ok(annotated)
+}
+
+/// Percentage of measured lines in `start..=end` that ran, if any were measured
+fn percent_covered(hits: &LineHits, start: usize, end: usize) -> Option<f64> {
+    let (measured, covered) = hits
+        .range(start..=end)
+        .fold((0usize, 0usize), |(measured, covered), (_, &count)| {
+            (measured + 1, covered + usize::from(count > 0))
+        });
+    (measured > 0).then(|| covered as f64 * 100.0 / measured as f64)
+}
+
+/// Resolved path of an existing report file, trying each base for relative names
+fn locate(file: &Path, bases: &[PathBuf], paths: PathResolver) -> Option<PathBuf> {
+    let existing = |path: PathBuf| path.is_file().then(|| paths.resolve(&path).ok())?;
+    if file.is_absolute() {
+        return existing(file.to_path_buf());
+    }
+    bases.iter().find_map(|base| existing(base.join(file)))
+}
+
+/// Cobertura XML: `<class filename=...><lines><line number=... hits=.../>`
+fn parse_cobertura(text: &str) -> std::result::Result<Report, String> {
+    let document = roxmltree::Document::parse(text).map_err(|e| e.to_string())?;
+    let root = document.root_element();
+    if !root.has_tag_name("coverage") {
+        return Err(format!(
+            "expected a Cobertura <coverage> document, found <{}>",
+            root.tag_name().name()
+        ));
+    }
+
+    let mut report = Report::default();
+    for element in root.descendants().filter(|n| n.is_element()) {
+        if element.has_tag_name("source") {
+            if let Some(source) = element.text().map(str::trim).filter(|s| !s.is_empty()) {
+                report.sources.push(PathBuf::from(source));
+            }
+        } else if element.has_tag_name("class") {
+            let Some(filename) = element.attribute("filename") else {
+                continue;
+            };
+            // Only the class's own <lines>, not the per-method copies
+            let mut hits = LineHits::new();
+            for line in element
+                .children()
+                .filter(|n| n.has_tag_name("lines"))
+                .flat_map(|lines| lines.children())
+                .filter(|n| n.has_tag_name("line"))
+            {
+                let number = line.attribute("number").and_then(|n| n.parse().ok());
+                let count = line.attribute("hits").and_then(|h| h.parse::<u64>().ok());
+                if let (Some(number), Some(count)) = (number, count) {
+                    *hits.entry(number).or_default() += count;
+                }
+            }
+            report.files.push((PathBuf::from(filename), hits));
+        }
+    }
+    Ok(report)
+}
+
+/// lcov tracefile: `SF:<file>`, then `DA:<line>,<hits>` records, then `end_of_record`
+fn parse_lcov(text: &str) -> std::result::Result<Report, String> {
+    let mut report = Report::default();
+    let mut current: Option<(PathBuf, LineHits)> = None;
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if let Some(file) = line.strip_prefix("SF:") {
+            current = Some((PathBuf::from(file), LineHits::new()));
+        } else if let Some(record) = line.strip_prefix("DA:") {
+            let Some((_, hits)) = current.as_mut() else {
+                return Err(format!("line {}: DA record outside a file", number + 1));
+            };
+            let mut fields = record.split(',');
+            let line_number = fields.next().and_then(|n| n.parse().ok());
+            let count = fields.next().and_then(|h| h.parse::<u64>().ok());
+            match (line_number, count) {
+                (Some(line_number), Some(count)) => {
+                    *hits.entry(line_number).or_default() += count;
+                }
+                _ => return Err(format!("line {}: malformed DA record", number + 1)),
+            }
+        } else if line == "end_of_record" {
+            report.files.extend(current.take());
+        }
+    }
+    report.files.extend(current);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Node, NodeType};
+
+    const COBERTURA: &str = r#"<?xml version="1.0" ?>
+<coverage version="7.4" line-rate="0.5">
+    <sources>
+        <source>/nonexistent/project</source>
+    </sources>
+    <packages>
+        <package name="src">
+            <classes>
+                <class name="m.py" filename="src/m.py" line-rate="0.5">
+                    <methods>
+                        <method name="f"><lines><line number="2" hits="9"/></lines></method>
+                    </methods>
+                    <lines>
+                        <line number="1" hits="1"/>
+                        <line number="2" hits="0"/>
+                        <line number="4" hits="1"/>
+                        <line number="5" hits="0"/>
+                    </lines>
+                </class>
+            </classes>
+        </package>
+    </packages>
+</coverage>
+"#;
+
+    const LCOV: &str = "TN:\nSF:src/m.py\nDA:1,1\nDA:2,0\nDA:4,3\nDA:5,0\nend_of_record\n";
+
+    #[test]
+    fn test_parse_reports() {
+        let cobertura = parse_cobertura(COBERTURA).unwrap();
+        assert_eq!(
+            cobertura.sources,
+            vec![PathBuf::from("/nonexistent/project")]
+        );
+        let (file, hits) = &cobertura.files[0];
+        assert_eq!(file, Path::new("src/m.py"));
+        // Method lines are not counted twice
+        assert_eq!(hits[&2], 0);
+        assert_eq!(hits.len(), 4);
+
+        let lcov = parse_lcov(LCOV).unwrap();
+        assert_eq!(lcov.files[0].1[&4], 3);
+        assert!(parse_lcov("DA:1,1\n").is_err());
+        assert!(parse_cobertura("<report/>").is_err());
+    }
+
+    #[test]
+    fn test_coverage_per_node() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        let source = dir.path().join("src/m.py");
+        std::fs::write(&source, "def f():\n    pass\n\ndef g():\n    pass\n").unwrap();
+        let report = dir.path().join("lcov.info");
+        std::fs::write(&report, LCOV).unwrap();
+
+        let mut graph = Graph::new();
+        for (id, lines) in [
+            ("m.py::f", (1, 2)),
+            ("m.py::g", (4, 4)),
+            ("m.py::x", (3, 3)),
+        ] {
+            let mut node = Node::new(id, id, NodeType::Function, "python", &source);
+            node.line_range = Some(lines);
+            graph.add_node(node);
+        }
+
+        assert_eq!(
+            coverage(&mut graph, &report, dir.path(), PathResolver::Canonical).unwrap(),
+            2
+        );
+        let percent = |id: &str| {
+            let idx = graph.get_by_id(id).unwrap();
+            graph.node_weight(idx).unwrap().coverage
+        };
+        assert_eq!(percent("m.py::f"), Some(50.0));
+        assert_eq!(percent("m.py::g"), Some(100.0));
+        // Blank line: nothing measured
+        assert_eq!(percent("m.py::x"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_coverage_under_symlinked_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let real = dir.path().join("real");
+        std::fs::create_dir_all(real.join("src")).unwrap();
+        std::fs::write(real.join("src/m.py"), "def f():\n    pass\n").unwrap();
+        std::fs::write(real.join("lcov.info"), LCOV).unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        // Node paths as a relative-ID scan of the symlinked root stores them
+        let source = PathResolver::Relative
+            .resolve(&link.join("src/m.py"))
+            .unwrap();
+        let mut node = Node::new("src/m.py::f", "f", NodeType::Function, "python", source);
+        node.line_range = Some((1, 2));
+        let mut graph = Graph::new();
+        graph.add_node(node);
+
+        let report = link.join("lcov.info");
+        assert_eq!(
+            coverage(&mut graph, &report, &link, PathResolver::Relative).unwrap(),
+            1
+        );
+    }
+}
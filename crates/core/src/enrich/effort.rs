@@ -0,0 +1,197 @@
+//! Effort: a rough estimate of how much work migrating each symbol is
+//!
+//! Three things make a symbol expensive to migrate: how much code it is,
+//! how many paths through it need re-testing, and how entangled it is with
+//! the rest of the project. [`estimate_effort()`] measures each per symbol
+//! and stores them with a combined score in
+//! [`Node::effort`](crate::Node::effort):
+//!
+//! ```text
+//! score = (1 + branches) + lines / 25 + fan_out / 2 + fan_in / 4
+//! ```
+//!
+//! `1 + branches` approximates cyclomatic complexity, counting `if`/`elif`,
+//! loops, `except` clauses, `case` clauses, conditional expressions,
+//! comprehension filters and each `and`/`or`. Dependencies (fan-out) must
+//! be ported or bridged first; dependents (fan-in) must be re-verified
+//! after. The weights are a starting point for ranking, not a time
+//! estimate.
+//!
+//! Branches are counted over a symbol's whole line range, so a class
+//! includes its methods.
+
+use super::nodes_by_file;
+use crate::graph::{Effort, Graph};
+use crate::parser::ParseSession;
+use petgraph::stable_graph::NodeIndex;
+use std::collections::HashMap;
+
+/// Tree-sitter node kinds that add a path through the code
+const BRANCH_KINDS: &[&str] = &[
+    "if_statement",
+    "elif_clause",
+    "for_statement",
+    "while_statement",
+    "except_clause",
+    "case_clause",
+    "conditional_expression",
+    "boolean_operator",
+    "if_clause",
+];
+
+/// Set [`Node::effort`](crate::Node::effort) on every project node with a
+/// line range
+///
+/// Fan-in and fan-out count dependency edges
+/// ([`EdgeType::is_dependency`](crate::graph::EdgeType::is_dependency)), so
+/// run this after resolution. Returns how many nodes were annotated; nodes
+/// of files that cannot be read or parsed are left unset.
+///
+/// # Example
+/// ```no_run
+/// use graph_migrator_core::{enrich, resolve, config::Config};
+///
+/// let root = std::path::Path::new("my_project");
+/// let mut graph = resolve::resolve_directory_with_config(root, &Config::default())
+///     .unwrap()
+///     .graph
+///     .graph;
+/// enrich::estimate_effort(&mut graph);
+/// ```
+pub fn estimate_effort(graph: &mut Graph) -> usize {
+    let mut degree: HashMap<NodeIndex, (usize, usize)> = HashMap::new();
+    for (from, to, edge) in graph.edge_endpoints() {
+        if edge.edge_type.is_dependency() {
+            degree.entry(from).or_default().1 += 1;
+            degree.entry(to).or_default().0 += 1;
+        }
+    }
+
+    let mut session = ParseSession::uncached();
+    let mut annotated = 0;
+    for (file, indices) in nodes_by_file(graph) {
+        let branch_lines = match session.parse(&file) {
+            Ok(source) => branch_lines(&source.tree),
+            Err(err) => {
+                tracing::debug!(%err, "skipping effort for unparsable file");
+                continue;
+            }
+        };
+        for idx in indices {
+            let (fan_in, fan_out) = degree.get(&idx).copied().unwrap_or_default();
+            let Some(node) = graph.node_weight_mut(idx) else {
+                continue;
+            };
+            let Some((start, end)) = node.line_range else {
+                continue;
+            };
+            let branches = branch_lines.partition_point(|&line| line <= end)
+                - branch_lines.partition_point(|&line| line < start);
+            let lines = end.saturating_sub(start) + 1;
+            node.effort = Some(Effort {
+                lines,
+                branches,
+                fan_in,
+                fan_out,
+                score: score(lines, branches, fan_in, fan_out),
+            });
+            annotated += 1;
+        }
+    }
+    annotated
+}
+
+/// The combined score described in the [module docs](self)
+fn score(lines: usize, branches: usize, fan_in: usize, fan_out: usize) -> f64 {
+    (1 + branches) as f64 + lines as f64 / 25.0 + fan_out as f64 / 2.0 + fan_in as f64 / 4.0
+}
+
+/// Start line (1-indexed) of every branch point in `tree`, sorted
+fn branch_lines(tree: &tree_sitter::Tree) -> Vec<usize> {
+    let mut lines = Vec::new();
+    let mut cursor = tree.walk();
+    loop {
+        let node = cursor.node();
+        if BRANCH_KINDS.contains(&node.kind()) {
+            lines.push(node.start_position().row + 1);
+        }
+        if cursor.goto_first_child() {
+            continue;
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                lines.sort_unstable();
+                return lines;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Edge, EdgeType, Node, NodeType};
+
+    #[test]
+    fn test_estimate_effort_counts_branches_lines_and_edges() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("billing.py");
+        std::fs::write(
+            &file,
+            "def simple():\n\
+             \x20   return 1\n\
+             \n\
+             def branchy(x):\n\
+             \x20   if x and x > 1:\n\
+             \x20       return [y for y in x if y]\n\
+             \x20   elif x:\n\
+             \x20       return 2 if x else 3\n\
+             \x20   for _ in range(3):\n\
+             \x20       try:\n\
+             \x20           pass\n\
+             \x20       except ValueError:\n\
+             \x20           pass\n\
+             \x20   return simple()\n",
+        )
+        .unwrap();
+
+        let mut graph = Graph::new();
+        let mut add = |name: &str, range: (usize, usize)| {
+            let mut node = Node::new(
+                format!("billing.py::{}", name),
+                name,
+                NodeType::Function,
+                "python",
+                &file,
+            );
+            node.line_range = Some(range);
+            graph.add_node(node)
+        };
+        let simple = add("simple", (1, 2));
+        let branchy = add("branchy", (4, 14));
+        graph.add_node(Node::new(
+            "external::os",
+            "os",
+            NodeType::Module,
+            "python",
+            "",
+        ));
+        graph.add_edge(branchy, simple, Edge::new(EdgeType::Calls));
+
+        assert_eq!(estimate_effort(&mut graph), 2);
+        let effort = |idx| graph.node_weight(idx).unwrap().effort.unwrap();
+
+        let simple = effort(simple);
+        assert_eq!(
+            (simple.lines, simple.branches, simple.fan_in, simple.fan_out),
+            (2, 0, 1, 0)
+        );
+        assert_eq!(simple.score, 1.0 + 2.0 / 25.0 + 0.25);
+
+        // if, and, comprehension if, elif, conditional expression, for, except
+        let branchy = effort(branchy);
+        assert_eq!((branchy.lines, branchy.branches), (11, 7));
+        assert_eq!(branchy.score, 8.0 + 11.0 / 25.0 + 0.5);
+        assert!(branchy.score > simple.score);
+    }
+}
@@ -0,0 +1,83 @@
+//! Optional passes that annotate a scanned graph with data from outside the
+//! source code itself, or that is too costly to compute on every scan
+//!
+//! - [`blame()`] - Last-modified time and top authors per symbol, from git
+//! - [`churn()`] - Commits touching each symbol over a recent window, from git
+//! - [`coverage()`] - Line coverage per symbol, from a Cobertura or lcov report
+//! - [`estimate_effort()`] - Size, branching and coupling per symbol, as an effort score
+//! - [`owners()`] - Owning teams per symbol, from CODEOWNERS and `[enrich.owners]`
+//!
+//! The git passes run the `git` executable once per file. Files git does
+//! not track (or that are not in a repository) are skipped, leaving their
+//! nodes unannotated.
+
+pub mod blame;
+pub mod churn;
+pub mod coverage;
+pub mod effort;
+pub mod owners;
+
+pub use blame::blame;
+pub use churn::churn;
+pub use coverage::coverage;
+pub use effort::estimate_effort;
+pub use owners::owners;
+
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::Graph;
+use petgraph::stable_graph::NodeIndex;
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Project nodes with a line range, grouped by file
+fn nodes_by_file(graph: &Graph) -> BTreeMap<PathBuf, Vec<NodeIndex>> {
+    let mut by_file: BTreeMap<PathBuf, Vec<NodeIndex>> = BTreeMap::new();
+    for idx in graph.node_indices() {
+        if let Some(node) = graph.node_weight(idx) {
+            if !node.external && node.line_range.is_some() {
+                by_file.entry(node.file_path.clone()).or_default().push(idx);
+            }
+        }
+    }
+    by_file
+}
+
+/// Run `git <args> -- <file name>` in the file's directory
+///
+/// Returns stdout, or `None` if git fails for this file.
+///
+/// # Errors
+/// Returns [`GraphMigratorError::Io`] if `git` cannot be run at all.
+fn git_for_file<I, S>(file: &Path, args: I) -> Result<Option<String>>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let (Some(dir), Some(name)) = (file.parent(), file.file_name()) else {
+        return Ok(None);
+    };
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .arg("--")
+        .arg(name)
+        .output()
+        .map_err(|e| GraphMigratorError::io("git", e))?;
+    if !output.status.success() {
+        tracing::debug!(
+            file = %file.display(),
+            stderr = %String::from_utf8_lossy(&output.stderr).trim(),
+            "git failed; skipping"
+        );
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&output.stdout).into_owned()))
+}
@@ -0,0 +1,323 @@
+//! Ownership enrichment: which team owns each symbol
+//!
+//! Large migrations are split between teams, and the split usually already
+//! exists in a CODEOWNERS file. [`owners()`] reads it and sets
+//! [`Node::owners`](crate::Node::owners) from the last rule matching each
+//! node's file, as GitHub and GitLab do:
+//!
+//! ```text
+//! # Comment
+//! *                @org/platform
+//! /src/billing/    @org/payments @alice
+//! *.sql            @org/dba
+//! /src/generated/
+//! ```
+//!
+//! Patterns follow CODEOWNERS (gitignore-like) rules: a leading or inner `/`
+//! anchors the pattern to the root, otherwise it matches at any depth; a
+//! pattern naming a directory covers everything below it, except that
+//! `dir/*` covers only the files directly inside. A rule without owners
+//! leaves its files unowned. GitLab `[Section]` headers are skipped.
+//!
+//! Per-directory owners from `[enrich.owners]` in `migrator.toml` take
+//! precedence over CODEOWNERS; the deepest matching directory wins.
+
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::Graph;
+use crate::paths::PathResolver;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Component, Path, PathBuf};
+
+/// Where CODEOWNERS is looked for, relative to the root, in order
+pub const CODEOWNERS_LOCATIONS: [&str; 4] = [
+    ".github/CODEOWNERS",
+    "CODEOWNERS",
+    "docs/CODEOWNERS",
+    ".gitlab/CODEOWNERS",
+];
+
+/// Parsed CODEOWNERS rules
+#[derive(Debug, Clone, Default)]
+pub struct Codeowners {
+    /// Patterns and their owners, in file order
+    rules: Vec<(GlobSet, Vec<String>)>,
+}
+
+impl Codeowners {
+    /// Parse CODEOWNERS text; `path` is only used in error messages
+    ///
+    /// # Errors
+    /// Returns [`GraphMigratorError::Parse`] for a pattern that is not a valid glob.
+    pub fn parse(text: &str, path: &Path) -> Result<Self> {
+        let mut rules = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            let section = line.starts_with('[') || line.starts_with("^[");
+            if line.is_empty() || line.starts_with('#') || section {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let Some(pattern) = fields.next() else {
+                continue;
+            };
+            let owners = fields
+                .take_while(|field| !field.starts_with('#'))
+                .map(str::to_string)
+                .collect();
+            let globs = compile(&pattern.replace("\\#", "#")).map_err(|e| {
+                GraphMigratorError::parse(path, format!("line {}: {}", number + 1, e))
+            })?;
+            rules.push((globs, owners));
+        }
+        Ok(Self { rules })
+    }
+
+    /// Read and parse a CODEOWNERS file
+    ///
+    /// # Errors
+    /// Returns [`GraphMigratorError::Io`] if the file cannot be read, or
+    /// [`GraphMigratorError::Parse`] if a pattern is invalid.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).map_err(|e| GraphMigratorError::io(path, e))?;
+        Self::parse(&text, path)
+    }
+
+    /// Owners of the root-relative `path`: those of the last matching rule
+    ///
+    /// Returns `None` if no rule matches; an empty slice if the matching rule
+    /// lists no owners.
+    pub fn owners_of(&self, path: &Path) -> Option<&[String]> {
+        self.rules
+            .iter()
+            .rev()
+            .find(|(globs, _)| globs.is_match(path))
+            .map(|(_, owners)| owners.as_slice())
+    }
+}
+
+/// Set [`Node::owners`](crate::Node::owners) on every project node
+///
+/// Reads `codeowners`, or else the first of [`CODEOWNERS_LOCATIONS`] that
+/// exists under `root`; a project without one is only annotated from
+/// `directories` (`[enrich.owners]`). Node files are matched relative to
+/// `root` as resolved by `paths`, the resolver the graph was scanned with.
+/// Returns how many nodes were given at least one owner.
+///
+/// # Errors
+/// Returns an error if `codeowners` (or the file found) cannot be read or
+/// contains an invalid pattern.
+///
+/// # Example
+/// ```no_run
+/// use graph_migrator_core::paths::PathResolver;
+/// use graph_migrator_core::{enrich, parser};
+/// use std::collections::BTreeMap;
+/// use std::path::Path;
+///
+/// let root = Path::new("my_project");
+/// let mut graph = parser::parse_directory(root).unwrap().graph;
+/// enrich::owners(&mut graph, root, None, &BTreeMap::new(), PathResolver::default()).unwrap();
+/// ```
+pub fn owners(
+    graph: &mut Graph,
+    root: &Path,
+    codeowners: Option<&Path>,
+    directories: &BTreeMap<String, Vec<String>>,
+    paths: PathResolver,
+) -> Result<usize> {
+    let file = match codeowners {
+        Some(path) => Some(path.to_path_buf()),
+        None => CODEOWNERS_LOCATIONS
+            .iter()
+            .map(|location| root.join(location))
+            .find(|path| path.is_file()),
+    };
+    let rules = match &file {
+        Some(path) => Codeowners::load(path)?,
+        None => Codeowners::default(),
+    };
+    let mut directories: Vec<(PathBuf, &Vec<String>)> = directories
+        .iter()
+        .map(|(directory, owners)| (normalize(Path::new(directory)), owners))
+        .collect();
+    // Deepest first, so the first match is the most specific
+    directories.sort_by_key(|(directory, _)| std::cmp::Reverse(directory.components().count()));
+
+    let resolved_root = paths.resolve(root).ok();
+    let mut by_file: HashMap<PathBuf, Vec<String>> = HashMap::new();
+    let mut annotated = 0;
+    for idx in graph.node_indices().collect::<Vec<_>>() {
+        let Some(node) = graph.node_weight_mut(idx) else {
+            continue;
+        };
+        if node.external {
+            continue;
+        }
+        let owners = by_file.entry(node.file_path.clone()).or_insert_with(|| {
+            let relative = relative_path(&node.file_path, root, resolved_root.as_deref());
+            directories
+                .iter()
+                .find(|(directory, _)| relative.starts_with(directory))
+                .map(|(_, owners)| owners.to_vec())
+                .or_else(|| rules.owners_of(&relative).map(<[String]>::to_vec))
+                .unwrap_or_default()
+        });
+        node.owners = owners.clone();
+        annotated += usize::from(!node.owners.is_empty());
+    }
+    Ok(annotated)
+}
+
+/// Globs matching what a CODEOWNERS pattern covers
+fn compile(pattern: &str) -> std::result::Result<GlobSet, globset::Error> {
+    let directory_only = pattern.ends_with('/');
+    let trimmed = pattern.trim_matches('/');
+    let anchored = pattern.starts_with('/') || trimmed.contains('/');
+    let base = if anchored {
+        trimmed.to_string()
+    } else {
+        format!("**/{}", trimmed)
+    };
+    let glob = |pattern: &str| GlobBuilder::new(pattern).literal_separator(true).build();
+    let mut set = GlobSetBuilder::new();
+    if !directory_only {
+        set.add(glob(&base)?);
+    }
+    if !base.ends_with("/*") {
+        set.add(glob(&format!("{}/**", base))?);
+    }
+    set.build()
+}
+
+/// `path` relative to `root` (as given or resolved), without `.` components
+fn relative_path(path: &Path, root: &Path, resolved_root: Option<&Path>) -> PathBuf {
+    let relative = path
+        .strip_prefix(root)
+        .ok()
+        .or_else(|| resolved_root.and_then(|r| path.strip_prefix(r).ok()))
+        .unwrap_or(path);
+    normalize(relative)
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|c| matches!(c, Component::Normal(_)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Node, NodeType};
+
+    #[test]
+    fn test_owners_from_codeowners_and_directories() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join(".github")).unwrap();
+        std::fs::write(
+            root.join(".github/CODEOWNERS"),
+            "# Default owners\n\
+             *                 @org/platform\n\
+             /src/billing/     @org/payments @alice  # inline comment\n\
+             docs/*            @org/docs\n\
+             *.sql             @org/dba\n\
+             /src/generated/\n\
+             [Section]\n",
+        )
+        .unwrap();
+
+        let mut graph = Graph::new();
+        let files = [
+            "setup.py",
+            "src/billing/invoice.py",
+            "src/billing/legacy/tax.py",
+            "src/generated/api.py",
+            "docs/conf.py",
+            "docs/api/conf.py",
+            "db/schema.sql",
+        ];
+        for file in files {
+            let id = format!("{}::f", file);
+            graph.add_node(Node::new(
+                id,
+                "f",
+                NodeType::Function,
+                "python",
+                root.join(file),
+            ));
+        }
+        let mut external = Node::new("external::os", "os", NodeType::Module, "python", "");
+        external.external = true;
+        graph.add_node(external);
+
+        let directories = BTreeMap::from([(
+            "src/billing/legacy".to_string(),
+            vec!["@org/legacy".to_string()],
+        )]);
+        let annotated = owners(
+            &mut graph,
+            root,
+            None,
+            &directories,
+            PathResolver::Canonical,
+        )
+        .unwrap();
+        assert_eq!(annotated, 6);
+
+        let owners_of = |file: &str| {
+            let idx = graph.get_by_id(&format!("{}::f", file)).unwrap();
+            graph.node_weight(idx).unwrap().owners.join(" ")
+        };
+        assert_eq!(owners_of("setup.py"), "@org/platform");
+        assert_eq!(owners_of("src/billing/invoice.py"), "@org/payments @alice");
+        assert_eq!(owners_of("src/billing/legacy/tax.py"), "@org/legacy");
+        assert_eq!(owners_of("src/generated/api.py"), "");
+        assert_eq!(owners_of("docs/conf.py"), "@org/docs");
+        // `docs/*` does not reach into subdirectories
+        assert_eq!(owners_of("docs/api/conf.py"), "@org/platform");
+        assert_eq!(owners_of("db/schema.sql"), "@org/dba");
+
+        assert!(matches!(
+            Codeowners::parse("src/[ @team", Path::new("CODEOWNERS")),
+            Err(GraphMigratorError::Parse { .. })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_owners_under_symlinked_root() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let real = dir.path().join("real");
+        std::fs::create_dir_all(real.join("src")).unwrap();
+        std::fs::write(real.join("CODEOWNERS"), "/src/ @org/core\n").unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        // Node paths as a relative-ID scan of the symlinked root stores them
+        let file = PathResolver::Relative
+            .resolve(&link.join("src/m.py"))
+            .unwrap();
+        let mut graph = Graph::new();
+        graph.add_node(Node::new(
+            "src/m.py::f",
+            "f",
+            NodeType::Function,
+            "python",
+            file,
+        ));
+
+        // The root as typed on the command line, neither lexical nor canonical
+        let root = link.join("src/..");
+        let annotated = owners(
+            &mut graph,
+            &root,
+            None,
+            &BTreeMap::new(),
+            PathResolver::Relative,
+        )
+        .unwrap();
+        assert_eq!(annotated, 1);
+    }
+}
@@ -0,0 +1,136 @@
+//! Error types for the core library
+//!
+//! Core functions return [`Result<T>`] with a structured [`GraphMigratorError`]
+//! so library consumers can match on failure kinds (e.g., skip unreadable
+//! files but abort on invalid configuration). `anyhow` is only used at the
+//! CLI boundary.
+
+use crate::graph::MigrationStatus;
+use std::path::PathBuf;
+
+/// Errors produced by the core library
+#[derive(Debug, thiserror::Error)]
+pub enum GraphMigratorError {
+    /// A file or directory could not be read or written
+    #[error("I/O error on {}: {source}", path.display())]
+    Io {
+        /// Path being accessed
+        path: PathBuf,
+        /// Underlying I/O error
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// A source file could not be parsed
+    #[error("Failed to parse {}: {detail}", file.display())]
+    Parse {
+        /// File being parsed
+        file: PathBuf,
+        /// What went wrong
+        detail: String,
+    },
+
+    /// A source file was skipped for breaking a `[limits]` setting
+    #[error("Skipped {}: {detail}", file.display())]
+    LimitExceeded {
+        /// File being parsed
+        file: PathBuf,
+        /// Which limit it broke, and by how much
+        detail: String,
+    },
+
+    /// An import or reference could not be resolved
+    #[error("Resolution error: {0}")]
+    Resolution(String),
+
+    /// Configuration is missing, malformed, or names unsupported options
+    #[error("Invalid config: {0}")]
+    Config(String),
+
+    /// Writing exported output failed
+    #[error("Export failed: {0}")]
+    Export(String),
+
+    /// A node ID passed to a query does not exist in the graph
+    #[error("Node not found: {0}")]
+    NodeNotFound(String),
+
+    /// Graph invariants were violated (dangling indices, unknown node IDs)
+    #[error("Invalid graph: {0}")]
+    InvalidGraph(String),
+
+    /// A `--filter` expression could not be parsed
+    #[error("Invalid filter: {0}")]
+    InvalidFilter(String),
+
+    /// An issue tracker (GitHub, Jira) or webhook rejected or failed a request
+    #[error("Integration request failed: {0}")]
+    Integration(String),
+
+    /// An external `git` command failed
+    #[error("git failed: {0}")]
+    Git(String),
+
+    /// A status change not allowed by [`MigrationStatus::can_transition_to()`]
+    #[error("Cannot move {id} from {from:?} to {to:?}")]
+    IllegalTransition {
+        /// Node being updated
+        id: String,
+        /// Its current status
+        from: MigrationStatus,
+        /// The requested status
+        to: MigrationStatus,
+    },
+}
+
+impl GraphMigratorError {
+    /// Build an [`GraphMigratorError::Io`] for `path`
+    pub fn io(path: impl Into<PathBuf>, source: std::io::Error) -> Self {
+        Self::Io {
+            path: path.into(),
+            source,
+        }
+    }
+
+    /// Build a [`GraphMigratorError::Parse`] for `file`
+    pub fn parse(file: impl Into<PathBuf>, detail: impl Into<String>) -> Self {
+        Self::Parse {
+            file: file.into(),
+            detail: detail.into(),
+        }
+    }
+
+    /// Build a [`GraphMigratorError::LimitExceeded`] for `file`
+    pub fn limit(file: impl Into<PathBuf>, detail: impl Into<String>) -> Self {
+        Self::LimitExceeded {
+            file: file.into(),
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Result alias used throughout the core library
+pub type Result<T> = std::result::Result<T, GraphMigratorError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_display_includes_context() {
+        let err = GraphMigratorError::io(
+            "/tmp/missing.py",
+            std::io::Error::new(std::io::ErrorKind::NotFound, "not found"),
+        );
+        assert_eq!(err.to_string(), "I/O error on /tmp/missing.py: not found");
+
+        let err = GraphMigratorError::parse("main.py", "unexpected token");
+        assert_eq!(err.to_string(), "Failed to parse main.py: unexpected token");
+    }
+
+    #[test]
+    fn test_error_kinds_are_matchable() {
+        let err = GraphMigratorError::Config("Unsupported language: cobol".to_string());
+        assert!(matches!(err, GraphMigratorError::Config(_)));
+    }
+}
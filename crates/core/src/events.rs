@@ -0,0 +1,254 @@
+//! Status change events for dashboards and chat bots
+//!
+//! Anything watching a migration would otherwise have to poll the state
+//! file. Instead, tools that change statuses (`migrator set-status`, the
+//! TUI) hand each change to an [`Emitter`] built from the `[events]`
+//! section, which sends it to every configured [`EventSink`]:
+//!
+//! - [`EventLog`] - appends one JSON object per line to `[events] log`
+//! - `Webhook` - POSTs each event to the URLs in `[events] webhooks`
+//!   (`integrations` feature)
+//!
+//! Events are JSON objects ([`StatusEvent::payload()`]):
+//!
+//! ```json
+//! {"event": "status_changed", "id": "app.py::main", "name": "main",
+//!  "from": "InProgress", "to": "Migrated", "at": 1700000000,
+//!  "text": "`app.py::main`: InProgress -> Migrated"}
+//! ```
+//!
+//! The `text` field makes the payload a valid Slack incoming-webhook message.
+
+use crate::config::{Config, EventsConfig};
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::{Graph, MigrationStatus};
+use serde_json::{json, Value};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// A node moved from one migration status to another
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusEvent {
+    /// Node ID
+    pub id: String,
+    /// Node name
+    pub name: String,
+    /// Status before the change
+    pub from: MigrationStatus,
+    /// Status after the change
+    pub to: MigrationStatus,
+    /// When the change was made, in seconds since the Unix epoch
+    pub at: u64,
+}
+
+impl StatusEvent {
+    /// The last status change recorded in the history of node `id`, if any
+    ///
+    /// Call right after [`migration::transition()`](crate::migration::transition())
+    /// to get the event for the change it made.
+    pub fn latest(graph: &Graph, id: &str) -> Option<Self> {
+        let node = graph.get_by_id(id).and_then(|idx| graph.node_weight(idx))?;
+        let change = node.history.last()?;
+        Some(Self {
+            id: node.id.clone(),
+            name: node.name.clone(),
+            from: change.from,
+            to: change.to,
+            at: change.at,
+        })
+    }
+
+    /// The event as sent to sinks
+    pub fn payload(&self) -> Value {
+        json!({
+            "event": "status_changed",
+            "id": self.id,
+            "name": self.name,
+            "from": self.from,
+            "to": self.to,
+            "at": self.at,
+            "text": format!("`{}`: {:?} -> {:?}", self.id, self.from, self.to),
+        })
+    }
+}
+
+/// A destination for events
+pub trait EventSink {
+    /// Deliver `event`
+    ///
+    /// # Errors
+    /// Returns an error if the event could not be delivered.
+    fn send(&self, event: &StatusEvent) -> Result<()>;
+}
+
+/// Append-only JSON Lines event log
+#[derive(Debug, Clone)]
+pub struct EventLog {
+    path: PathBuf,
+}
+
+impl EventLog {
+    /// Log to `path`, created with its parent directories on first use
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl EventSink for EventLog {
+    fn send(&self, event: &StatusEvent) -> Result<()> {
+        let path = &self.path;
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(|e| GraphMigratorError::io(dir, e))?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| GraphMigratorError::io(path, e))?;
+        writeln!(file, "{}", event.payload()).map_err(|e| GraphMigratorError::io(path, e))
+    }
+}
+
+/// POSTs each event as JSON to one URL
+#[cfg(feature = "integrations")]
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    url: String,
+    agent: ureq::Agent,
+}
+
+#[cfg(feature = "integrations")]
+impl Webhook {
+    /// Hook posting to `url`
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            agent: ureq::AgentBuilder::new()
+                .timeout(std::time::Duration::from_secs(10))
+                .build(),
+        }
+    }
+}
+
+#[cfg(feature = "integrations")]
+impl EventSink for Webhook {
+    fn send(&self, event: &StatusEvent) -> Result<()> {
+        self.agent
+            .post(&self.url)
+            .send_json(event.payload())
+            .map(drop)
+            .map_err(|e| crate::integrations::request_error(&self.url, e))
+    }
+}
+
+/// Sends events to every configured sink
+#[derive(Default)]
+pub struct Emitter {
+    sinks: Vec<Box<dyn EventSink>>,
+}
+
+impl Emitter {
+    /// An emitter without sinks
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sinks from `config.events`, with paths relative to `root`
+    ///
+    /// # Errors
+    /// Returns [`GraphMigratorError::Config`] if webhooks are configured but
+    /// the `integrations` feature is disabled.
+    pub fn from_config(config: &Config, root: &Path) -> Result<Self> {
+        let EventsConfig { log, webhooks } = &config.events;
+        let mut emitter = Self::new();
+        if let Some(log) = log {
+            emitter = emitter.sink(EventLog::new(config.resolve_path(root, log)));
+        }
+        #[cfg(feature = "integrations")]
+        for url in webhooks {
+            emitter = emitter.sink(Webhook::new(url.clone()));
+        }
+        #[cfg(not(feature = "integrations"))]
+        if !webhooks.is_empty() {
+            return Err(GraphMigratorError::Config(
+                "[events] webhooks need the `integrations` feature".to_string(),
+            ));
+        }
+        Ok(emitter)
+    }
+
+    /// Add `sink`
+    pub fn sink(mut self, sink: impl EventSink + 'static) -> Self {
+        self.sinks.push(Box::new(sink));
+        self
+    }
+
+    /// Whether there are no sinks, so emitting does nothing
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+
+    /// Send `event` to every sink
+    ///
+    /// A failing sink does not stop delivery to the others.
+    ///
+    /// # Errors
+    /// Returns the first delivery error.
+    pub fn emit(&self, event: &StatusEvent) -> Result<()> {
+        tracing::debug!(id = %event.id, from = ?event.from, to = ?event.to, "status event");
+        let mut first_error = None;
+        for sink in &self.sinks {
+            if let Err(err) = sink.send(event) {
+                first_error.get_or_insert(err);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Node, NodeType};
+    use crate::migration;
+
+    #[test]
+    fn test_event_log() {
+        let mut graph = Graph::new();
+        graph.add_node(Node::new(
+            "app.py::main",
+            "main",
+            NodeType::Function,
+            "python",
+            "app.py",
+        ));
+        assert!(StatusEvent::latest(&graph, "app.py::main").is_none());
+        migration::transition_at(&mut graph, "app.py::main", MigrationStatus::InProgress, 5)
+            .unwrap();
+        let event = StatusEvent::latest(&graph, "app.py::main").unwrap();
+        assert_eq!(
+            (event.from, event.to, event.at),
+            (MigrationStatus::Pending, MigrationStatus::InProgress, 5)
+        );
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut config = Config::default();
+        config.events.log = Some(PathBuf::from("events/log.jsonl"));
+        let emitter = Emitter::from_config(&config, dir.path()).unwrap();
+        emitter.emit(&event).unwrap();
+        emitter.emit(&event).unwrap();
+
+        let text = std::fs::read_to_string(dir.path().join("events/log.jsonl")).unwrap();
+        let lines: Vec<Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["event"], "status_changed");
+        assert_eq!(lines[0]["to"], "InProgress");
+        assert_eq!(lines[0]["text"], "`app.py::main`: Pending -> InProgress");
+        assert!(Emitter::from_config(&Config::default(), dir.path())
+            .unwrap()
+            .is_empty());
+    }
+}
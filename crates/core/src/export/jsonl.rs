@@ -0,0 +1,481 @@
+//! JSON Lines (NDJSON) export
+//!
+//! Each line is one self-contained JSON record tagged with a `kind` field:
+//!
+//! ```text
+//! {"kind":"metadata","tool_version":"0.1.0","git_remote":"https://example.com/p.git",...}
+//! {"kind":"node","id":"/p/utils.py::helper","name":"helper","node_type":"Function",...}
+//! {"kind":"edge","from":"/p/utils.py::process","to":"/p/utils.py::helper","edge_type":"Calls"}
+//! ```
+//!
+//! A graph's [`GraphMetadata`] comes first, if it has any.
+//!
+//! Edges reference nodes by ID rather than petgraph index, so records can be
+//! loaded into jq/BigQuery without any graph context. [`stream_files()`]
+//! writes records file-by-file while parsing, so memory stays bounded by the
+//! largest single file instead of the whole repository, but it only holds
+//! what each file says on its own; [`stream_discovered()`] does the same for
+//! a configured project. [`stream_project()`] resolves the project first, so
+//! cross-file calls, imports and inheritance are included and the records
+//! match the `--format json` graph, at the cost of holding all of it in memory.
+//!
+//! Exports made before `relative_ids` was enabled can be converted with
+//! [`rewrite_ids()`] instead of re-parsing the project.
+
+#[cfg(feature = "std-fs")]
+use crate::config::Config;
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::{Edge, Graph, GraphMetadata, Node};
+use crate::parser::{self, ParseDiagnostic, ParseSession, Severity};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+/// One line of JSONL output
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum Record<'a> {
+    Metadata(&'a GraphMetadata),
+    Node(&'a Node),
+    Edge {
+        from: &'a str,
+        to: &'a str,
+        #[serde(flatten)]
+        edge: &'a Edge,
+    },
+}
+
+/// Writes nodes and edges as JSON Lines to any `Write` sink
+pub struct JsonlWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> JsonlWriter<W> {
+    /// Wrap a writer (wrap files in `BufWriter` for throughput)
+    pub fn new(out: W) -> Self {
+        Self { out }
+    }
+
+    /// Write the graph's provenance as a record
+    pub fn write_metadata(&mut self, metadata: &GraphMetadata) -> Result<()> {
+        self.write_record(&Record::Metadata(metadata))
+    }
+
+    /// Write a single node record
+    pub fn write_node(&mut self, node: &Node) -> Result<()> {
+        self.write_record(&Record::Node(node))
+    }
+
+    /// Write a single edge record, with endpoints given as node IDs
+    pub fn write_edge(&mut self, from: &str, to: &str, edge: &Edge) -> Result<()> {
+        self.write_record(&Record::Edge { from, to, edge })
+    }
+
+    /// Write the metadata unless empty, then every node, then every edge,
+    /// of a materialized graph
+    ///
+    /// Records are sorted by ID ([`Graph::nodes_sorted_by_id()`]), so equal
+    /// graphs produce identical output.
+    pub fn write_graph(&mut self, graph: &Graph) -> Result<()> {
+        if !graph.metadata().is_empty() {
+            self.write_metadata(graph.metadata())?;
+        }
+        for node in graph.nodes_sorted_by_id() {
+            self.write_node(node)?;
+        }
+        for (from, to, edge) in graph.edges_sorted() {
+            self.write_edge(&from.id, &to.id, edge)?;
+        }
+        Ok(())
+    }
+
+    /// Flush and return the underlying writer
+    pub fn into_inner(mut self) -> Result<W> {
+        self.out.flush().map_err(export_error)?;
+        Ok(self.out)
+    }
+
+    fn write_record(&mut self, record: &Record<'_>) -> Result<()> {
+        serde_json::to_writer(&mut self.out, record)
+            .map_err(|e| GraphMigratorError::Export(e.to_string()))?;
+        self.out.write_all(b"\n").map_err(export_error)
+    }
+}
+
+/// Parse files one at a time, streaming their nodes and edges as JSON Lines
+///
+/// Paths are sorted for deterministic output. A file reached under several
+/// paths is streamed once, and an ID repeated within a file is written once;
+/// IDs embed the file path, so different files never share one. Only the
+/// current file's graph is held in memory, so calls into other files are
+/// left out, as with [`parse_files()`](crate::parser::parse_files).
+///
+/// # Returns
+/// Per-file diagnostics (files that fail to parse are skipped, as in
+/// [`parse_files_lenient()`](crate::parser::parse_files_lenient)).
+///
+/// # Errors
+/// Returns [`GraphMigratorError::Export`] if writing to `out` fails.
+pub fn stream_files<W: Write>(paths: &[&Path], out: W) -> Result<Vec<ParseDiagnostic>> {
+    stream(paths, ParseSession::uncached(), None, None, false, out)
+}
+
+/// [`stream_files()`] over the files `config` discovers under `root`, parsed
+/// with its extraction and ID settings
+///
+/// Memory stays bounded by the largest file, so this suits repositories too
+/// big for [`stream_project()`]; in exchange, edges into other files are left
+/// out. IDs and file paths are made relative to `root` with `relative_ids`,
+/// and IDs are prefixed with `namespace`, as in [`stream_project()`].
+///
+/// # Errors
+/// Returns [`GraphMigratorError::Export`] if writing to `out` fails, or with
+/// `strict`, the error of the first file that cannot be read or parsed,
+/// instead of a diagnostic; what was streamed before it stays written. Files
+/// over a limit are diagnostics either way.
+#[cfg(feature = "std-fs")]
+pub fn stream_discovered<W: Write>(
+    root: &Path,
+    config: &Config,
+    strict: bool,
+    out: W,
+) -> Result<Vec<ParseDiagnostic>> {
+    let files = crate::discovery::discover_project(root, config);
+    let paths: Vec<&Path> = files.iter().map(std::path::PathBuf::as_path).collect();
+    let session = ParseSession::for_project(root, config);
+    let relative_to = config.relative_ids.then_some(root);
+    let namespace = config.namespace.as_deref();
+    stream(&paths, session, relative_to, namespace, strict, out)
+}
+
+/// Resolve a project as configured by `config` and write its graph as JSON Lines
+///
+/// The graph is the one [`resolve_directory_lenient()`] builds, with
+/// cross-file edges bound in the second pass, so the records hold the same
+/// nodes and edges as `scan --format json`, file paths made relative to
+/// `root` with `relative_ids` like the IDs. Resolving needs every file's
+/// symbols, so the whole graph is held in memory; records are written in
+/// [`JsonlWriter::write_graph()`] order.
+///
+/// [`resolve_directory_lenient()`]: crate::resolve::resolve_directory_lenient
+///
+/// # Returns
+/// Per-file diagnostics for files that failed to parse or were skipped.
+///
+/// # Errors
+/// Returns [`GraphMigratorError::Export`] if writing to `out` fails.
+#[cfg(feature = "std-fs")]
+pub fn stream_project<W: Write>(
+    root: &Path,
+    config: &Config,
+    out: W,
+) -> Result<Vec<ParseDiagnostic>> {
+    let (mut output, diagnostics) = crate::resolve::resolve_directory_lenient(root, config);
+    if config.relative_ids {
+        parser::relativize_file_paths(&mut output.graph.graph, root);
+    }
+    let mut writer = JsonlWriter::new(out);
+    writer.write_graph(&output.graph.graph)?;
+    writer.into_inner()?;
+    Ok(diagnostics)
+}
+
+/// Stream `paths` through `session`, with IDs and file paths made relative
+/// to `relative_to` and IDs prefixed with `namespace` if given; with
+/// `strict`, stop at the first file that fails
+fn stream<W: Write>(
+    paths: &[&Path],
+    mut session: ParseSession,
+    relative_to: Option<&Path>,
+    namespace: Option<&str>,
+    strict: bool,
+    out: W,
+) -> Result<Vec<ParseDiagnostic>> {
+    let _span = tracing::info_span!("parse_files", files = paths.len()).entered();
+    let mut writer = JsonlWriter::new(out);
+    let mut diagnostics = Vec::new();
+    let mut seen_files = HashSet::new();
+
+    let mut sorted_paths: Vec<&Path> = paths.to_vec();
+    sorted_paths.sort();
+
+    for path in sorted_paths {
+        // The same file under another path would repeat its symbols, so a file seen before
+        // contributes nothing new
+        let file_key = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        if !seen_files.insert(file_key) {
+            continue;
+        }
+
+        let (mut file_graph, error_lines) = match parser::parse_file_in(&mut session, path) {
+            Ok(parsed) => {
+                if let Some(warning) = parsed.encoding.warning() {
+                    diagnostics.push(ParseDiagnostic {
+                        file: path.to_path_buf(),
+                        error: warning.to_string(),
+                        severity: Severity::Warning,
+                    });
+                }
+                (parsed.graph, parsed.error_lines)
+            }
+            // Files over a limit are skipped even then, as in the batch parsers
+            Err(err) if strict && !matches!(err, GraphMigratorError::LimitExceeded { .. }) => {
+                return Err(err)
+            }
+            Err(err) => {
+                diagnostics.push(ParseDiagnostic {
+                    file: path.to_path_buf(),
+                    error: err.to_string(),
+                    severity: Severity::Error,
+                });
+                continue;
+            }
+        };
+        if let Some(root) = relative_to {
+            parser::relativize_ids(&mut file_graph, root);
+            parser::relativize_file_paths(&mut file_graph, root);
+        }
+        if let Some(namespace) = namespace {
+            parser::namespace_ids(&mut file_graph, namespace);
+        }
+        let mut seen_ids = HashSet::new();
+        for node in file_graph.nodes() {
+            if seen_ids.insert(node.id.as_str()) {
+                writer.write_node(node)?;
+            }
+        }
+        for (from, to, edge) in file_graph.edge_endpoints() {
+            if let (Some(from), Some(to)) =
+                (file_graph.node_weight(from), file_graph.node_weight(to))
+            {
+                writer.write_edge(&from.id, &to.id, edge)?;
+            }
+        }
+
+        if !error_lines.is_empty() {
+            let lines: Vec<String> = error_lines.iter().map(|l| l.to_string()).collect();
+            diagnostics.push(ParseDiagnostic {
+                file: path.to_path_buf(),
+                error: format!("syntax error at line(s) {}", lines.join(", ")),
+                severity: Severity::Warning,
+            });
+        }
+    }
+
+    writer.into_inner()?;
+    Ok(diagnostics)
+}
+
+/// Rewrite the node IDs of an existing JSONL export relative to `root`
+///
+/// Node `id` fields and edge `from`/`to` fields are converted with
+/// [`project_relative_id()`](crate::parser::project_relative_id); all other
+/// fields pass through untouched. `root` must be the absolute project root the
+/// export was made from (it is not canonicalized, since it may no longer exist
+/// on this machine). Returns how many IDs changed.
+pub fn rewrite_ids<R: BufRead, W: Write>(input: R, mut output: W, root: &Path) -> Result<usize> {
+    let mut rewritten = 0;
+    for line in input.lines() {
+        let line = line.map_err(export_error)?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut record: serde_json::Value = serde_json::from_str(&line)
+            .map_err(|e| GraphMigratorError::Export(format!("invalid JSONL record: {}", e)))?;
+
+        for key in ["id", "from", "to"] {
+            let Some(serde_json::Value::String(id)) = record.get_mut(key) else {
+                continue;
+            };
+            if let Some(relative) = crate::parser::project_relative_id(id, root) {
+                *id = relative;
+                rewritten += 1;
+            }
+        }
+
+        serde_json::to_writer(&mut output, &record)
+            .map_err(|e| GraphMigratorError::Export(e.to_string()))?;
+        output.write_all(b"\n").map_err(export_error)?;
+    }
+    output.flush().map_err(export_error)?;
+    Ok(rewritten)
+}
+
+fn export_error(err: std::io::Error) -> GraphMigratorError {
+    GraphMigratorError::Export(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    fn parse_lines(bytes: &[u8]) -> Vec<serde_json::Value> {
+        std::str::from_utf8(bytes)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_stream_files_matches_merged_graph() {
+        let files = vec![
+            Path::new("tests/test-fixtures/multi-file-project/module_a.py"),
+            Path::new("tests/test-fixtures/multi-file-project/main.py"),
+        ];
+
+        let mut out = Vec::new();
+        let diagnostics = stream_files(&files, &mut out).unwrap();
+        assert!(diagnostics.is_empty());
+
+        let records = parse_lines(&out);
+        let multi = parser::parse_files(&files).unwrap();
+
+        let nodes = records.iter().filter(|r| r["kind"] == "node").count();
+        let edges = records.iter().filter(|r| r["kind"] == "edge").count();
+        assert_eq!(nodes, multi.graph.node_count());
+        assert_eq!(edges, multi.graph.edge_count());
+    }
+
+    #[test]
+    fn test_edge_records_reference_node_ids() {
+        let files = vec![Path::new("tests/test-fixtures/calls.py")];
+
+        let mut out = Vec::new();
+        stream_files(&files, &mut out).unwrap();
+        let records = parse_lines(&out);
+
+        let edge = records.iter().find(|r| r["kind"] == "edge").unwrap();
+        assert_eq!(edge["edge_type"], "Calls");
+        assert!(edge["to"].as_str().unwrap().ends_with("calls.py::helper"));
+    }
+
+    #[test]
+    fn test_duplicate_ids_written_once() {
+        let files = vec![
+            Path::new("tests/test-fixtures/calls_edge_cases.py"),
+            Path::new("tests/test-fixtures/calls_edge_cases.py"),
+        ];
+
+        let mut out = Vec::new();
+        stream_files(&files, &mut out).unwrap();
+        let records = parse_lines(&out);
+
+        let multi = parser::parse_files(&files).unwrap();
+        let nodes = records.iter().filter(|r| r["kind"] == "node").count();
+        assert_eq!(nodes, multi.graph.node_count());
+    }
+
+    #[test]
+    fn test_stream_project_matches_json_graph() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("util.py"), "def helper():\n    pass\n").unwrap();
+        std::fs::write(
+            dir.path().join("app.py"),
+            "from util import helper\n\ndef main():\n    helper()\n",
+        )
+        .unwrap();
+        let config = Config::default();
+
+        let mut out = Vec::new();
+        stream_project(dir.path(), &config, &mut out).unwrap();
+        let records = parse_lines(&out);
+
+        let (mut output, _) = crate::resolve::resolve_directory_lenient(dir.path(), &config);
+        parser::relativize_file_paths(&mut output.graph.graph, dir.path());
+        let mut expected = JsonlWriter::new(Vec::new());
+        expected.write_graph(&output.graph.graph).unwrap();
+        assert_eq!(records, parse_lines(&expected.into_inner().unwrap()));
+        assert!(records.iter().any(|r| {
+            r["kind"] == "edge"
+                && r["from"] == "app.py::main"
+                && r["to"] == "util.py::helper"
+                && r["edge_type"] == "Calls"
+        }));
+        // No path of the checkout leaks into the records
+        assert!(records
+            .iter()
+            .filter(|r| r["kind"] == "node")
+            .all(|r| !r["file_path"].as_str().unwrap().starts_with('/')));
+    }
+
+    #[test]
+    fn test_stream_discovered_keeps_each_file_to_itself() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("util.py"), "def helper():\n    pass\n").unwrap();
+        std::fs::write(
+            dir.path().join("app.py"),
+            "from util import helper\n\ndef main():\n    helper()\n",
+        )
+        .unwrap();
+        let mut config = Config::default();
+
+        let mut out = Vec::new();
+        stream_discovered(dir.path(), &config, true, &mut out).unwrap();
+        let records = parse_lines(&out);
+        let mut ids: Vec<&str> = records
+            .iter()
+            .filter(|r| r["kind"] == "node")
+            .map(|r| r["id"].as_str().unwrap())
+            .collect();
+        ids.sort();
+        assert_eq!(ids, ["app.py::main", "util.py::helper"]);
+        // The call into util.py needs pass 2
+        assert!(records.iter().all(|r| r["kind"] != "edge"));
+        assert!(records
+            .iter()
+            .filter(|r| r["kind"] == "node")
+            .all(|r| !r["file_path"].as_str().unwrap().starts_with('/')));
+
+        // A file over a limit is a diagnostic even with strict
+        config.limits.max_nodes = Some(0);
+        let diagnostics = stream_discovered(dir.path(), &config, true, Vec::new()).unwrap();
+        assert_eq!(diagnostics.len(), 2);
+    }
+
+    #[test]
+    fn test_rewrite_ids() {
+        let input = concat!(
+            r#"{"kind":"node","id":"/old/box/app/a.py::f","name":"f","file_path":"/old/box/app/a.py"}"#,
+            "\n",
+            r#"{"kind":"edge","from":"/old/box/app/a.py::f","to":"/lib/b.py::g","edge_type":"Calls"}"#,
+            "\n",
+        );
+
+        let mut out = Vec::new();
+        let rewritten = rewrite_ids(input.as_bytes(), &mut out, Path::new("/old/box/app")).unwrap();
+
+        let records = parse_lines(&out);
+        assert_eq!(rewritten, 2);
+        assert_eq!(records[0]["id"], "a.py::f");
+        // Non-ID fields and IDs outside the root are untouched
+        assert_eq!(records[0]["file_path"], "/old/box/app/a.py");
+        assert_eq!(records[1]["from"], "a.py::f");
+        assert_eq!(records[1]["to"], "/lib/b.py::g");
+    }
+
+    #[test]
+    fn test_write_graph() {
+        let multi = parser::parse_files(&[Path::new("tests/test-fixtures/sample.py")]).unwrap();
+
+        let mut writer = JsonlWriter::new(Vec::new());
+        writer.write_graph(&multi.graph).unwrap();
+        let out = writer.into_inner().unwrap();
+
+        assert_eq!(parse_lines(&out).len(), 3);
+
+        let mut graph = multi.graph;
+        graph.set_metadata(GraphMetadata {
+            git_remote: Some("https://example.com/app.git".to_string()),
+            ..GraphMetadata::default()
+        });
+        let mut writer = JsonlWriter::new(Vec::new());
+        writer.write_graph(&graph).unwrap();
+        let records = parse_lines(&writer.into_inner().unwrap());
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[0]["kind"], "metadata");
+        assert_eq!(records[0]["git_remote"], "https://example.com/app.git");
+    }
+}
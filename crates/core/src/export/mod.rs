@@ -0,0 +1,17 @@
+//! Exporters for writing graphs to external formats
+//!
+//! - [`jsonl`] - Newline-delimited JSON, streamable while parsing
+//! - [`parquet`](mod@parquet) - Columnar node and edge tables (`parquet` feature)
+//! - [`report`] - Markdown migration status report
+//! - [`sarif`] - Rule violations for code scanning on pull requests
+//!
+//! Graph exports list nodes by ID and edges by source, target and type
+//! ([`Graph::nodes_sorted_by_id()`](crate::Graph::nodes_sorted_by_id()),
+//! [`Graph::edges_sorted()`](crate::Graph::edges_sorted())), never in
+//! insertion order, so CI can diff and cache them byte for byte.
+
+pub mod jsonl;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+pub mod report;
+pub mod sarif;
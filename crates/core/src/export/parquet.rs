@@ -0,0 +1,314 @@
+//! Apache Parquet export, for graphs too large to handle as JSON
+//!
+//! Writes two tables that analytics tools (DuckDB, pandas, Spark) can join
+//! on node ID:
+//!
+//! - **nodes**: `id`, `name`, `node_type`, `language`, `file_path`,
+//!   `line_start`, `line_end`, `external`, `status`, `namespace`,
+//!   `package_name`, `package_version`, `pagerank`, `betweenness`,
+//!   `last_modified`, `churn`, `coverage`, `effort`, `issue`, `attributes`
+//! - **edges**: `source`, `target`, `edge_type`, `attributes`
+//!
+//! Optional node fields become nullable columns; `attributes` holds a JSON
+//! object, or null when there are none. Per-node lists (status
+//! history, blame authors, owners) are not exported; use JSON for those. Rows are
+//! sorted by ID and written in batches of [`BATCH_ROWS`], one row group each.
+//! The graph's [`GraphMetadata`](crate::GraphMetadata), if any, is stored as
+//! JSON in the node table's schema metadata under [`METADATA_KEY`].
+//!
+//! Only built with the `parquet` feature.
+
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::{AttrValue, Graph, Node};
+use arrow_array::builder::{
+    BooleanBuilder, Float64Builder, StringBuilder, UInt32Builder, UInt64Builder,
+};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use parquet::arrow::ArrowWriter;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Rows per record batch and row group
+pub const BATCH_ROWS: usize = 65_536;
+
+/// Schema metadata key of the graph's provenance in the node table
+pub const METADATA_KEY: &str = "graph_migrator.metadata";
+
+/// File names written by [`write_dir()`]
+pub const NODES_FILE: &str = "nodes.parquet";
+/// See [`NODES_FILE`]
+pub const EDGES_FILE: &str = "edges.parquet";
+
+/// Write `nodes.parquet` and `edges.parquet` into `dir`, creating it if needed
+///
+/// # Errors
+/// Returns an error if the directory or files cannot be written.
+pub fn write_dir(graph: &Graph, dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir).map_err(|e| GraphMigratorError::io(dir, e))?;
+    let create = |name: &str| {
+        let path = dir.join(name);
+        std::fs::File::create(&path).map_err(|e| GraphMigratorError::io(&path, e))
+    };
+    write_nodes(graph, create(NODES_FILE)?)?;
+    write_edges(graph, create(EDGES_FILE)?)?;
+    Ok(())
+}
+
+/// Write the node table to `out`; returns the number of rows
+///
+/// # Errors
+/// Returns [`GraphMigratorError::Export`] if encoding or writing fails.
+pub fn write_nodes<W: Write + Send>(graph: &Graph, out: W) -> Result<usize> {
+    let mut schema = node_schema();
+    if !graph.metadata().is_empty() {
+        let metadata = serde_json::to_string(graph.metadata()).map_err(export_error)?;
+        let fields = schema.fields().clone();
+        schema = Arc::new(Schema::new_with_metadata(
+            fields,
+            HashMap::from([(METADATA_KEY.to_string(), metadata)]),
+        ));
+    }
+    let nodes = graph.nodes_sorted_by_id();
+    let mut writer = ArrowWriter::try_new(out, schema.clone(), None).map_err(export_error)?;
+    for chunk in nodes.chunks(BATCH_ROWS) {
+        writer
+            .write(&node_batch(&schema, chunk)?)
+            .map_err(export_error)?;
+        writer.flush().map_err(export_error)?;
+    }
+    writer.close().map_err(export_error)?;
+    Ok(nodes.len())
+}
+
+/// Write the edge table to `out`, with endpoints as node IDs; returns the
+/// number of rows
+///
+/// # Errors
+/// Returns [`GraphMigratorError::Export`] if encoding or writing fails.
+pub fn write_edges<W: Write + Send>(graph: &Graph, out: W) -> Result<usize> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("source", DataType::Utf8, false),
+        Field::new("target", DataType::Utf8, false),
+        Field::new("edge_type", DataType::Utf8, false),
+        Field::new("attributes", DataType::Utf8, true),
+    ]));
+    let edges: Vec<(&str, &str, String, Option<String>)> = graph
+        .edges_sorted()
+        .into_iter()
+        .map(|(from, to, edge)| {
+            (
+                from.id.as_str(),
+                to.id.as_str(),
+                format!("{:?}", edge.edge_type),
+                attributes_json(&edge.attributes),
+            )
+        })
+        .collect();
+
+    let mut writer = ArrowWriter::try_new(out, schema.clone(), None).map_err(export_error)?;
+    for chunk in edges.chunks(BATCH_ROWS) {
+        let mut source = StringBuilder::new();
+        let mut target = StringBuilder::new();
+        let mut edge_type = StringBuilder::new();
+        let mut attributes = StringBuilder::new();
+        for (from, to, kind, attrs) in chunk {
+            source.append_value(from);
+            target.append_value(to);
+            edge_type.append_value(kind);
+            attributes.append_option(attrs.as_deref());
+        }
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(source.finish()),
+            Arc::new(target.finish()),
+            Arc::new(edge_type.finish()),
+            Arc::new(attributes.finish()),
+        ];
+        let batch = RecordBatch::try_new(schema.clone(), columns).map_err(export_error)?;
+        writer.write(&batch).map_err(export_error)?;
+        writer.flush().map_err(export_error)?;
+    }
+    writer.close().map_err(export_error)?;
+    Ok(edges.len())
+}
+
+fn node_schema() -> SchemaRef {
+    let text = |name: &str, nullable: bool| Field::new(name, DataType::Utf8, nullable);
+    Arc::new(Schema::new(vec![
+        text("id", false),
+        text("name", false),
+        text("node_type", false),
+        text("language", false),
+        text("file_path", false),
+        Field::new("line_start", DataType::UInt64, true),
+        Field::new("line_end", DataType::UInt64, true),
+        Field::new("external", DataType::Boolean, false),
+        text("status", false),
+        text("namespace", true),
+        text("package_name", true),
+        text("package_version", true),
+        Field::new("pagerank", DataType::Float64, true),
+        Field::new("betweenness", DataType::Float64, true),
+        Field::new("last_modified", DataType::UInt64, true),
+        Field::new("churn", DataType::UInt32, true),
+        Field::new("coverage", DataType::Float64, true),
+        Field::new("effort", DataType::Float64, true),
+        text("issue", true),
+        text("attributes", true),
+    ]))
+}
+
+fn node_batch(schema: &SchemaRef, nodes: &[&Node]) -> Result<RecordBatch> {
+    let mut id = StringBuilder::new();
+    let mut name = StringBuilder::new();
+    let mut node_type = StringBuilder::new();
+    let mut language = StringBuilder::new();
+    let mut file_path = StringBuilder::new();
+    let mut line_start = UInt64Builder::new();
+    let mut line_end = UInt64Builder::new();
+    let mut external = BooleanBuilder::new();
+    let mut status = StringBuilder::new();
+    let mut namespace = StringBuilder::new();
+    let mut package_name = StringBuilder::new();
+    let mut package_version = StringBuilder::new();
+    let mut pagerank = Float64Builder::new();
+    let mut betweenness = Float64Builder::new();
+    let mut last_modified = UInt64Builder::new();
+    let mut churn = UInt32Builder::new();
+    let mut coverage = Float64Builder::new();
+    let mut effort = Float64Builder::new();
+    let mut issue = StringBuilder::new();
+    let mut attributes = StringBuilder::new();
+
+    for node in nodes {
+        id.append_value(&node.id);
+        name.append_value(&node.name);
+        node_type.append_value(format!("{:?}", node.node_type));
+        language.append_value(&node.language);
+        file_path.append_value(node.file_path.to_string_lossy());
+        line_start.append_option(node.line_range.map(|(start, _)| start as u64));
+        line_end.append_option(node.line_range.map(|(_, end)| end as u64));
+        external.append_value(node.external);
+        status.append_value(format!("{:?}", node.status));
+        namespace.append_option(node.namespace.as_deref());
+        package_name.append_option(node.package.as_ref().map(|p| p.name.as_str()));
+        package_version.append_option(node.package.as_ref().and_then(|p| p.version.as_deref()));
+        pagerank.append_option(node.centrality.map(|c| c.pagerank));
+        betweenness.append_option(node.centrality.map(|c| c.betweenness));
+        last_modified.append_option(node.blame.as_ref().map(|b| b.last_modified));
+        churn.append_option(node.churn);
+        coverage.append_option(node.coverage);
+        effort.append_option(node.effort.map(|e| e.score));
+        issue.append_option(node.issue.as_deref());
+        attributes.append_option(attributes_json(&node.attributes));
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(id.finish()),
+        Arc::new(name.finish()),
+        Arc::new(node_type.finish()),
+        Arc::new(language.finish()),
+        Arc::new(file_path.finish()),
+        Arc::new(line_start.finish()),
+        Arc::new(line_end.finish()),
+        Arc::new(external.finish()),
+        Arc::new(status.finish()),
+        Arc::new(namespace.finish()),
+        Arc::new(package_name.finish()),
+        Arc::new(package_version.finish()),
+        Arc::new(pagerank.finish()),
+        Arc::new(betweenness.finish()),
+        Arc::new(last_modified.finish()),
+        Arc::new(churn.finish()),
+        Arc::new(coverage.finish()),
+        Arc::new(effort.finish()),
+        Arc::new(issue.finish()),
+        Arc::new(attributes.finish()),
+    ];
+    RecordBatch::try_new(schema.clone(), columns).map_err(export_error)
+}
+
+/// Attributes as a JSON object, or `None` if there are none
+fn attributes_json(attributes: &BTreeMap<String, AttrValue>) -> Option<String> {
+    if attributes.is_empty() {
+        return None;
+    }
+    serde_json::to_string(attributes).ok()
+}
+
+fn export_error(err: impl std::fmt::Display) -> GraphMigratorError {
+    GraphMigratorError::Export(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Edge, EdgeType, GraphMetadata, MigrationStatus, NodeType};
+    use arrow_array::{Array, StringArray, UInt64Array};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    #[test]
+    fn test_parquet_round_trip() {
+        let mut graph = Graph::new();
+        let mut f = Node::new("m.py::f", "f", NodeType::Function, "python", "m.py");
+        f.line_range = Some((1, 3));
+        f.status = MigrationStatus::Migrated;
+        f.attributes.insert("team".to_string(), "billing".into());
+        let f = graph.add_node(f);
+        let g = graph.add_node(Node::new(
+            "m.py::g",
+            "g",
+            NodeType::Function,
+            "python",
+            "m.py",
+        ));
+        graph.add_edge(f, g, Edge::new(EdgeType::Calls));
+        graph.set_metadata(GraphMetadata {
+            git_commit: Some("0123abc".to_string()),
+            ..GraphMetadata::default()
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        write_dir(&graph, dir.path()).unwrap();
+
+        let read = |name: &str| {
+            let file = std::fs::File::open(dir.path().join(name)).unwrap();
+            let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+                .unwrap()
+                .build()
+                .unwrap();
+            reader.map(|batch| batch.unwrap()).collect::<Vec<_>>()
+        };
+
+        let nodes = &read(NODES_FILE)[0];
+        assert_eq!(nodes.num_rows(), 2);
+        let column = |name: &str| nodes.column_by_name(name).unwrap().clone();
+        let status = column("status");
+        let status = status.as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(status.value(0), "Migrated");
+        let line_start = column("line_start");
+        let line_start = line_start.as_any().downcast_ref::<UInt64Array>().unwrap();
+        assert_eq!(line_start.value(0), 1);
+        assert!(line_start.is_null(1));
+        let attributes = column("attributes");
+        let attributes = attributes.as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(attributes.value(0), r#"{"team":"billing"}"#);
+        assert!(attributes.is_null(1));
+        let file = std::fs::File::open(dir.path().join(NODES_FILE)).unwrap();
+        let schema = ParquetRecordBatchReaderBuilder::try_new(file)
+            .unwrap()
+            .schema()
+            .clone();
+        assert_eq!(
+            schema.metadata()[METADATA_KEY],
+            r#"{"git_commit":"0123abc"}"#
+        );
+
+        let edges = &read(EDGES_FILE)[0];
+        let edge_type = edges.column_by_name("edge_type").unwrap();
+        let edge_type = edge_type.as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(edge_type.value(0), "Calls");
+    }
+}
@@ -0,0 +1,346 @@
+//! Markdown migration report, for pasting into status updates
+//!
+//! [`to_markdown()`] summarizes a tracked graph in a few sections:
+//!
+//! - **Summary** - symbol counts per status, share done, and how many
+//!   symbols are ready to migrate now
+//! - **Top blockers** - unfinished symbols that the most other unfinished
+//!   symbols depend on directly
+//! - **Cycles** - groups of symbols that can only be migrated together
+//! - **Recently migrated** - symbols whose status became `Migrated` within
+//!   the last [`recent_days`](ReportOptions::recent_days)
+//! - **Migration units** - status counts per unit
+//!
+//! Sections with nothing to show say so rather than being left out, so
+//! consecutive reports line up.
+
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::{Graph, MigrationStatus, Node};
+use crate::progress::{self, StatusCounts};
+use crate::queries::{self, migration::is_project_symbol};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write as _;
+use std::io::Write;
+
+/// Settings for [`to_markdown()`]
+///
+/// # Example
+/// ```
+/// use graph_migrator_core::export::report::ReportOptions;
+///
+/// let options = ReportOptions::new(1_700_000_000).limit(5).recent_days(14);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportOptions {
+    now: u64,
+    limit: usize,
+    recent_days: u64,
+}
+
+impl ReportOptions {
+    /// Report as of `now` (seconds since the Unix epoch), listing 10 rows per
+    /// section and a week of recent migrations
+    pub fn new(now: u64) -> Self {
+        Self {
+            now,
+            limit: 10,
+            recent_days: 7,
+        }
+    }
+
+    /// List at most `limit` rows in the blocker, cycle and recent sections
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Count migrations within the last `days` days as recent
+    pub fn recent_days(mut self, days: u64) -> Self {
+        self.recent_days = days;
+        self
+    }
+}
+
+/// The report for `graph` as Markdown
+///
+/// # Example
+/// ```
+/// use graph_migrator_core::export::report::{self, ReportOptions};
+/// use graph_migrator_core::{Graph, Node, NodeType};
+///
+/// let mut graph = Graph::new();
+/// graph.add_node(Node::new("app.py::main", "main", NodeType::Function, "python", "app.py"));
+/// let markdown = report::to_markdown(&graph, &ReportOptions::new(0));
+/// assert!(markdown.contains("0 of 1 symbols done"));
+/// ```
+pub fn to_markdown(graph: &Graph, options: &ReportOptions) -> String {
+    let mut out = String::new();
+    let snapshot = progress::ProgressSnapshot::capture(graph, options.now);
+    // Writing to a String cannot fail
+    let _ = writeln!(out, "# Migration report\n");
+    let _ = writeln!(out, "As of {}.\n", progress::format_date(options.now));
+    summary(&mut out, graph, &snapshot.total);
+    blockers(&mut out, graph, options);
+    cycles(&mut out, graph, options);
+    recently_migrated(&mut out, graph, options);
+    units(&mut out, graph, &snapshot.units);
+    out
+}
+
+/// Write [`to_markdown()`] to `out`
+///
+/// # Errors
+/// Returns [`GraphMigratorError::Export`] if writing to `out` fails.
+pub fn write<W: Write>(graph: &Graph, options: &ReportOptions, mut out: W) -> Result<()> {
+    out.write_all(to_markdown(graph, options).as_bytes())
+        .and_then(|()| out.flush())
+        .map_err(|e| GraphMigratorError::Export(e.to_string()))
+}
+
+fn summary(out: &mut String, graph: &Graph, counts: &StatusCounts) {
+    let total: usize = counts.values().sum();
+    let done = progress::done(counts);
+    let _ = writeln!(out, "## Summary\n");
+    let _ = writeln!(
+        out,
+        "**{} of {} symbols done ({:.0}%)**; {} ready to migrate now.\n",
+        done,
+        total,
+        percent(done, total),
+        queries::migration_frontier(graph).len()
+    );
+    let _ = writeln!(out, "| Status | Symbols | Share |");
+    let _ = writeln!(out, "|--------|--------:|------:|");
+    for status in STATUSES {
+        let count = counts.get(&status).copied().unwrap_or(0);
+        let _ = writeln!(
+            out,
+            "| {:?} | {} | {:.0}% |",
+            status,
+            count,
+            percent(count, total)
+        );
+    }
+    let _ = writeln!(out);
+}
+
+fn blockers(out: &mut String, graph: &Graph, options: &ReportOptions) {
+    let mut blocked: HashMap<&str, BTreeSet<&str>> = HashMap::new();
+    for (from, to, edge) in graph.edge_endpoints() {
+        if from == to || !edge.edge_type.is_dependency() {
+            continue;
+        }
+        let (Some(dependent), Some(dependency)) = (graph.node_weight(from), graph.node_weight(to))
+        else {
+            continue;
+        };
+        if is_unfinished(dependent) && is_unfinished(dependency) {
+            blocked
+                .entry(dependency.id.as_str())
+                .or_default()
+                .insert(dependent.id.as_str());
+        }
+    }
+    let mut blocked: Vec<(&str, usize)> = blocked
+        .into_iter()
+        .map(|(id, dependents)| (id, dependents.len()))
+        .collect();
+    blocked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+    let _ = writeln!(out, "## Top blockers\n");
+    if blocked.is_empty() {
+        let _ = writeln!(out, "No unfinished symbol is waiting on another.\n");
+        return;
+    }
+    let _ = writeln!(out, "| Symbol | Status | Unfinished dependents |");
+    let _ = writeln!(out, "|--------|--------|----------------------:|");
+    for (id, count) in blocked.into_iter().take(options.limit) {
+        let status = graph
+            .get_by_id(id)
+            .and_then(|idx| graph.node_weight(idx))
+            .map(|node| node.status)
+            .unwrap_or_default();
+        let _ = writeln!(out, "| `{}` | {:?} | {} |", id, status, count);
+    }
+    let _ = writeln!(out);
+}
+
+fn cycles(out: &mut String, graph: &Graph, options: &ReportOptions) {
+    let condensation = queries::condense(graph);
+    let mut cycles: Vec<&queries::Component> = condensation
+        .cycles()
+        .filter(|c| c.members.len() > 1)
+        .collect();
+    cycles.sort_by(|a, b| {
+        b.members
+            .len()
+            .cmp(&a.members.len())
+            .then_with(|| a.members.cmp(&b.members))
+    });
+
+    let _ = writeln!(out, "## Cycles\n");
+    if cycles.is_empty() {
+        let _ = writeln!(out, "No dependency cycles.\n");
+        return;
+    }
+    let _ = writeln!(
+        out,
+        "{} group(s) of symbols depend on each other and must move together:\n",
+        cycles.len()
+    );
+    for cycle in cycles.into_iter().take(options.limit) {
+        let members: Vec<String> = cycle.members.iter().map(|m| format!("`{}`", m)).collect();
+        let _ = writeln!(
+            out,
+            "- {} symbols: {}",
+            cycle.members.len(),
+            members.join(", ")
+        );
+    }
+    let _ = writeln!(out);
+}
+
+fn recently_migrated(out: &mut String, graph: &Graph, options: &ReportOptions) {
+    let since = options
+        .now
+        .saturating_sub(options.recent_days.saturating_mul(86_400));
+    let mut recent: Vec<(u64, &str)> = graph
+        .nodes()
+        .filter(|node| node.status == MigrationStatus::Migrated)
+        .filter_map(|node| {
+            let change = node
+                .history
+                .iter()
+                .rev()
+                .find(|change| change.to == MigrationStatus::Migrated)?;
+            (change.at >= since).then_some((change.at, node.id.as_str()))
+        })
+        .collect();
+    recent.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+
+    let _ = writeln!(
+        out,
+        "## Recently migrated (last {} days)\n",
+        options.recent_days
+    );
+    if recent.is_empty() {
+        let _ = writeln!(out, "Nothing migrated in this period.\n");
+        return;
+    }
+    let count = recent.len();
+    for (at, id) in recent.into_iter().take(options.limit) {
+        let _ = writeln!(out, "- `{}` ({})", id, progress::format_date(at));
+    }
+    if count > options.limit {
+        let _ = writeln!(out, "- ...and {} more", count - options.limit);
+    }
+    let _ = writeln!(out);
+}
+
+fn units(out: &mut String, graph: &Graph, units: &BTreeMap<String, StatusCounts>) {
+    let _ = writeln!(out, "## Migration units\n");
+    if units.is_empty() {
+        let _ = writeln!(out, "No migration units defined.\n");
+        return;
+    }
+    let _ = writeln!(
+        out,
+        "| Unit | Symbols | Pending | In progress | Blocked | Migrated | Superseded | Done |"
+    );
+    let _ = writeln!(
+        out,
+        "|------|--------:|--------:|------------:|--------:|---------:|-----------:|-----:|"
+    );
+    for (id, counts) in units {
+        let total: usize = counts.values().sum();
+        let name = graph
+            .get_by_id(id)
+            .and_then(|idx| graph.node_weight(idx))
+            .map_or(id.as_str(), |unit| unit.name.as_str());
+        let _ = write!(out, "| {} | {} |", name, total);
+        for status in STATUSES {
+            let _ = write!(out, " {} |", counts.get(&status).copied().unwrap_or(0));
+        }
+        let _ = writeln!(out, " {:.0}% |", percent(progress::done(counts), total));
+    }
+    let _ = writeln!(out);
+}
+
+/// Statuses in report order
+const STATUSES: [MigrationStatus; 5] = [
+    MigrationStatus::Pending,
+    MigrationStatus::InProgress,
+    MigrationStatus::Blocked,
+    MigrationStatus::Migrated,
+    MigrationStatus::Superseded,
+];
+
+/// A project symbol that is neither migrated nor superseded
+fn is_unfinished(node: &Node) -> bool {
+    is_project_symbol(node)
+        && !matches!(
+            node.status,
+            MigrationStatus::Migrated | MigrationStatus::Superseded
+        )
+}
+
+fn percent(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        100.0 * part as f64 / whole as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Edge, EdgeType, NodeType};
+    use crate::migration;
+
+    #[test]
+    fn test_report_sections() {
+        let mut graph = Graph::new();
+        for name in ["db", "model", "view", "a", "b", "done"] {
+            let id = format!("app.py::{}", name);
+            graph.add_node(Node::new(id, name, NodeType::Function, "python", "app.py"));
+        }
+        let mut calls = |from: &str, to: &str| {
+            let from = graph.get_by_id(&format!("app.py::{}", from)).unwrap();
+            let to = graph.get_by_id(&format!("app.py::{}", to)).unwrap();
+            graph.add_edge(from, to, Edge::new(EdgeType::Calls));
+        };
+        calls("model", "db");
+        calls("view", "db");
+        calls("view", "model");
+        calls("a", "b");
+        calls("b", "a");
+        calls("view", "done");
+        let day = 86_400;
+        migration::transition_at(
+            &mut graph,
+            "app.py::done",
+            MigrationStatus::Migrated,
+            9 * day,
+        )
+        .unwrap();
+        migration::create_unit(&mut graph, "unit::web", "web", &["app.py::view"]).unwrap();
+
+        let markdown = to_markdown(&graph, &ReportOptions::new(10 * day));
+        assert!(markdown.starts_with("# Migration report\n\nAs of 1970-01-11."));
+        assert!(markdown.contains("**1 of 6 symbols done (17%)**"));
+        assert!(markdown.contains("| Pending | 5 | 83% |"));
+        // db blocks model and view; model blocks view
+        let blockers = markdown.split("## Top blockers").nth(1).unwrap();
+        assert!(
+            blockers.find("`app.py::db` | Pending | 2").unwrap()
+                < blockers.find("`app.py::model` | Pending | 1").unwrap()
+        );
+        assert!(markdown.contains("- 2 symbols: `app.py::a`, `app.py::b`"));
+        assert!(markdown.contains("- `app.py::done` (1970-01-10)"));
+        assert!(markdown.contains("| web | 1 | 1 | 0 | 0 | 0 | 0 | 0% |"));
+
+        let quiet = to_markdown(&graph, &ReportOptions::new(30 * day).recent_days(1));
+        assert!(quiet.contains("Nothing migrated in this period."));
+    }
+}
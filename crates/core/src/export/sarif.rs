@@ -0,0 +1,151 @@
+//! SARIF 2.1.0 export of rule violations
+//!
+//! GitHub code scanning and Azure DevOps show SARIF results inline on pull
+//! requests. Every [`Rule`] appears in the tool's rule table; each
+//! [`Violation`] becomes one result with an `error` level, a location
+//! relative to `%SRCROOT%` (the checked-out repository), and the violating
+//! node's ID as its logical location.
+//!
+//! Results carry a `partialFingerprints` entry from
+//! [`Violation::fingerprint()`], so code scanning can track a violation
+//! across commits even as line numbers shift.
+
+use crate::error::{GraphMigratorError, Result};
+use crate::rules::{Rule, Violation};
+use serde_json::{json, Value};
+use std::io::Write;
+use std::path::Path;
+
+/// `$schema` of the emitted log
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// The violations as a SARIF log with a single run
+pub fn to_sarif(violations: &[Violation]) -> Value {
+    let rules: Vec<Value> = Rule::ALL
+        .iter()
+        .map(|rule| {
+            json!({
+                "id": rule.id(),
+                "shortDescription": { "text": rule.description() },
+                "defaultConfiguration": { "level": "error" },
+            })
+        })
+        .collect();
+    let results: Vec<Value> = violations.iter().map(result).collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "migrator",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "originalUriBaseIds": {
+                "%SRCROOT%": { "description": { "text": "Project root" } }
+            },
+            "results": results,
+        }]
+    })
+}
+
+/// Write [`to_sarif()`] as pretty-printed JSON
+///
+/// # Errors
+/// Returns [`GraphMigratorError::Export`] if writing to `out` fails.
+pub fn write<W: Write>(violations: &[Violation], mut out: W) -> Result<()> {
+    serde_json::to_writer_pretty(&mut out, &to_sarif(violations))
+        .map_err(|e| GraphMigratorError::Export(e.to_string()))?;
+    writeln!(out)
+        .and_then(|()| out.flush())
+        .map_err(|e| GraphMigratorError::Export(e.to_string()))
+}
+
+fn result(violation: &Violation) -> Value {
+    let rule_index = Rule::ALL.iter().position(|r| *r == violation.rule);
+    let mut physical = json!({
+        "artifactLocation": {
+            "uri": uri(&violation.file_path),
+            "uriBaseId": "%SRCROOT%",
+        }
+    });
+    if let Some(line) = violation.line {
+        physical["region"] = json!({ "startLine": line.max(1) });
+    }
+
+    json!({
+        "ruleId": violation.rule.id(),
+        "ruleIndex": rule_index,
+        "level": "error",
+        "message": { "text": violation.message },
+        "locations": [{
+            "physicalLocation": physical,
+            "logicalLocations": [{ "fullyQualifiedName": violation.node }],
+        }],
+        "partialFingerprints": {
+            "migratorViolation/v1": violation.fingerprint(),
+        },
+    })
+}
+
+/// Forward-slashed relative URI, with characters URIs cannot hold escaped
+fn uri(path: &Path) -> String {
+    let text = path.to_string_lossy().replace('\\', "/");
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_results_reference_rules_and_locations() {
+        let violations = vec![Violation {
+            rule: Rule::ForbiddenDependency,
+            node: "src/new app.py::run".to_string(),
+            file_path: PathBuf::from("src/new app.py"),
+            line: Some(12),
+            related: vec!["src/old.py::legacy".to_string()],
+            message: "run depends on legacy".to_string(),
+        }];
+
+        let log = to_sarif(&violations);
+        let run = &log["runs"][0];
+        assert_eq!(log["version"], "2.1.0");
+        assert_eq!(
+            run["tool"]["driver"]["rules"].as_array().unwrap().len(),
+            Rule::ALL.len()
+        );
+
+        let result = &run["results"][0];
+        assert_eq!(result["ruleId"], "forbidden-dependency");
+        assert_eq!(
+            run["tool"]["driver"]["rules"][result["ruleIndex"].as_u64().unwrap() as usize]["id"],
+            "forbidden-dependency"
+        );
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "src/new%20app.py");
+        assert_eq!(location["region"]["startLine"], 12);
+        assert_eq!(
+            result["partialFingerprints"]["migratorViolation/v1"],
+            "forbidden-dependency:src/new app.py::run->src/old.py::legacy"
+        );
+
+        let mut out = Vec::new();
+        write(&[], &mut out).unwrap();
+        let empty: Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(empty["runs"][0]["results"], json!([]));
+    }
+}
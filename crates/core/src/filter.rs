@@ -0,0 +1,754 @@
+//! Filter expressions that scope queries and exports to matching nodes
+//!
+//! A [`Filter`] is parsed once from text such as
+//!
+//! ```text
+//! type=Function AND file~"legacy/.*" AND status!=Migrated
+//! ```
+//!
+//! and compiled into a predicate over [`Node`]s, so every command applies the
+//! same selection the same way. The grammar, loosest binding first:
+//!
+//! - `a OR b`
+//! - `a AND b`
+//! - `NOT a`
+//! - `( a )`
+//! - `field op value`
+//!
+//! Keywords are case-insensitive. Values are bare words or quoted strings
+//! (`"..."` or `'...'`, with `\` escaping the quote and itself).
+//!
+//! | Field | Value |
+//! |-------|-------|
+//! | `id`, `name`, `file`, `language`, `namespace` | text |
+//! | `type` | a [`NodeType`] (`function`, `GlobalVariable`, ...) |
+//! | `status` | a [`MigrationStatus`] (`pending`, `in-progress`, ...) |
+//! | `external` | `true` or `false` |
+//! | `churn`, `coverage`, `lines` | a number |
+//! | `attr.<name>` | the [`Node::attributes`] entry `<name>`, as text or a number |
+//!
+//! | Operator | Meaning |
+//! |----------|---------|
+//! | `=`, `!=` | equal, not equal |
+//! | `~`, `!~` | matches, does not match a regex (anywhere in the value; anchor with `^`/`$`) |
+//! | `<`, `<=`, `>`, `>=` | numeric comparison (numeric fields only) |
+//!
+//! Fields a node lacks (no namespace, no churn computed, no such attribute)
+//! never equal or match anything, so `!=` and `!~` select them.
+//!
+//! The same expressions select edges ([`Filter::retain_edges()`]), where
+//! `attr.<name>` reads [`Edge::attributes`] and every other field is lacking.
+
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::{AttrValue, Edge, Graph, MigrationStatus, Node, NodeType};
+use regex::Regex;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// A compiled filter expression
+///
+/// # Example
+/// ```no_run
+/// use graph_migrator_core::filter::Filter;
+/// use graph_migrator_core::parser;
+///
+/// let mut multi = parser::parse_directory(std::path::Path::new("my_project")).unwrap();
+/// let filter: Filter = r#"type=Function AND file~"legacy/.*""#.parse().unwrap();
+/// filter.retain(&mut multi.graph);
+/// println!("{} legacy functions", multi.graph.node_count());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Filter {
+    text: String,
+    expr: Expr,
+}
+
+impl Filter {
+    /// Parse and compile `text`
+    ///
+    /// # Errors
+    /// Returns [`GraphMigratorError::InvalidFilter`] for syntax errors,
+    /// unknown fields, values of the wrong kind, and invalid regexes.
+    pub fn parse(text: &str) -> Result<Self> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            position: 0,
+            end: text.chars().count() + 1,
+        };
+        let expr = parser.or()?;
+        if let Some((column, token)) = tokens.get(parser.position) {
+            return Err(invalid(*column, format!("unexpected {}", token)));
+        }
+        Ok(Self {
+            text: text.to_string(),
+            expr,
+        })
+    }
+
+    /// The expression this filter was parsed from
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Whether `node` is selected
+    pub fn matches(&self, node: &Node) -> bool {
+        self.expr.eval(Subject::Node(node))
+    }
+
+    /// Whether `edge` is selected
+    pub fn matches_edge(&self, edge: &Edge) -> bool {
+        self.expr.eval(Subject::Edge(edge))
+    }
+
+    /// Remove every node not selected, with its edges; returns how many
+    pub fn retain(&self, graph: &mut Graph) -> usize {
+        let rejected: Vec<_> = graph
+            .node_indices()
+            .filter(|&index| graph.node_weight(index).is_some_and(|n| !self.matches(n)))
+            .collect();
+        for &index in &rejected {
+            graph.remove_node(index);
+        }
+        rejected.len()
+    }
+
+    /// Remove every edge not selected; returns how many
+    pub fn retain_edges(&self, graph: &mut Graph) -> usize {
+        let rejected: Vec<_> = graph
+            .edge_indices()
+            .filter(|&index| {
+                graph
+                    .edge_weight(index)
+                    .is_some_and(|e| !self.matches_edge(e))
+            })
+            .collect();
+        for &index in &rejected {
+            graph.remove_edge(index);
+        }
+        rejected.len()
+    }
+}
+
+/// What an expression is evaluated on
+#[derive(Clone, Copy)]
+enum Subject<'a> {
+    Node(&'a Node),
+    Edge(&'a Edge),
+}
+
+impl<'a> Subject<'a> {
+    fn attributes(self) -> &'a BTreeMap<String, AttrValue> {
+        match self {
+            Subject::Node(node) => &node.attributes,
+            Subject::Edge(edge) => &edge.attributes,
+        }
+    }
+
+    fn node(self) -> Option<&'a Node> {
+        match self {
+            Subject::Node(node) => Some(node),
+            Subject::Edge(_) => None,
+        }
+    }
+}
+
+impl FromStr for Filter {
+    type Err = GraphMigratorError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Or(Vec<Expr>),
+    And(Vec<Expr>),
+    Not(Box<Expr>),
+    Test(Field, Test),
+}
+
+impl Expr {
+    fn eval(&self, subject: Subject) -> bool {
+        match self {
+            Expr::Or(terms) => terms.iter().any(|term| term.eval(subject)),
+            Expr::And(terms) => terms.iter().all(|term| term.eval(subject)),
+            Expr::Not(inner) => !inner.eval(subject),
+            Expr::Test(field, test) => test.eval(field, subject),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Field {
+    Id,
+    Name,
+    Type,
+    File,
+    Status,
+    Language,
+    Namespace,
+    External,
+    Churn,
+    Coverage,
+    Lines,
+    Attribute(String),
+}
+
+const FIELD_NAMES: &str = "id, name, type, file, status, language, namespace, external, churn, \
+                           coverage, lines, attr.<name>";
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        if let Some(key) = name.strip_prefix("attr.").filter(|key| !key.is_empty()) {
+            return Some(Field::Attribute(key.to_string()));
+        }
+        Some(match name.to_ascii_lowercase().as_str() {
+            "id" => Field::Id,
+            "name" => Field::Name,
+            "type" | "node_type" => Field::Type,
+            "file" | "file_path" => Field::File,
+            "status" => Field::Status,
+            "language" => Field::Language,
+            "namespace" => Field::Namespace,
+            "external" => Field::External,
+            "churn" => Field::Churn,
+            "coverage" => Field::Coverage,
+            "lines" => Field::Lines,
+            _ => return None,
+        })
+    }
+
+    fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            Field::Churn | Field::Coverage | Field::Lines | Field::Attribute(_)
+        )
+    }
+
+    /// The field as text, for `~` and for text equality
+    fn text<'a>(&self, subject: Subject<'a>) -> Option<Cow<'a, str>> {
+        if let Field::Attribute(key) = self {
+            return subject.attributes().get(key).map(|value| match value {
+                AttrValue::Text(text) => Cow::Borrowed(text.as_str()),
+                other => Cow::Owned(other.to_string()),
+            });
+        }
+        let node = subject.node()?;
+        match self {
+            Field::Id => Some(Cow::Borrowed(&node.id)),
+            Field::Name => Some(Cow::Borrowed(&node.name)),
+            Field::Type => Some(Cow::Owned(format!("{:?}", node.node_type))),
+            Field::File => Some(node.file_path.to_string_lossy()),
+            Field::Status => Some(Cow::Owned(format!("{:?}", node.status))),
+            Field::Language => Some(Cow::Borrowed(&node.language)),
+            Field::Namespace => node.namespace.as_deref().map(Cow::Borrowed),
+            Field::External => Some(Cow::Borrowed(if node.external { "true" } else { "false" })),
+            Field::Churn | Field::Coverage | Field::Lines | Field::Attribute(_) => {
+                self.number(subject).map(|n| Cow::Owned(n.to_string()))
+            }
+        }
+    }
+
+    fn number(&self, subject: Subject) -> Option<f64> {
+        if let Field::Attribute(key) = self {
+            return subject.attributes().get(key).and_then(AttrValue::as_f64);
+        }
+        let node = subject.node()?;
+        match self {
+            Field::Churn => node.churn.map(f64::from),
+            Field::Coverage => node.coverage,
+            Field::Lines => node
+                .line_range
+                .map(|(start, end)| (end.saturating_sub(start) + 1) as f64),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Match,
+    NotMatch,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Op {
+    fn symbol(self) -> &'static str {
+        match self {
+            Op::Eq => "=",
+            Op::Ne => "!=",
+            Op::Match => "~",
+            Op::NotMatch => "!~",
+            Op::Lt => "<",
+            Op::Le => "<=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Value {
+    Text(String),
+    Type(NodeType),
+    Status(MigrationStatus),
+    Bool(bool),
+    Number(f64),
+}
+
+#[derive(Debug, Clone)]
+enum Test {
+    Equals(Value),
+    NotEquals(Value),
+    Matches(Regex),
+    NotMatches(Regex),
+    Compare(Op, f64),
+}
+
+impl Test {
+    fn eval(&self, field: &Field, subject: Subject) -> bool {
+        match self {
+            Test::Equals(value) => equals(field, subject, value),
+            Test::NotEquals(value) => !equals(field, subject, value),
+            Test::Matches(regex) => field.text(subject).is_some_and(|t| regex.is_match(&t)),
+            Test::NotMatches(regex) => !field.text(subject).is_some_and(|t| regex.is_match(&t)),
+            Test::Compare(op, bound) => field.number(subject).is_some_and(|n| match op {
+                Op::Lt => n < *bound,
+                Op::Le => n <= *bound,
+                Op::Gt => n > *bound,
+                Op::Ge => n >= *bound,
+                _ => false,
+            }),
+        }
+    }
+}
+
+fn equals(field: &Field, subject: Subject, value: &Value) -> bool {
+    match value {
+        Value::Text(text) => match (field.number(subject), text.parse::<f64>()) {
+            (Some(number), Ok(expected)) => number == expected,
+            _ => field.text(subject).is_some_and(|t| t == text.as_str()),
+        },
+        Value::Type(node_type) => subject.node().is_some_and(|n| n.node_type == *node_type),
+        Value::Status(status) => subject.node().is_some_and(|n| n.status == *status),
+        Value::Bool(external) => subject.node().is_some_and(|n| n.external == *external),
+        Value::Number(number) => field.number(subject) == Some(*number),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    And,
+    Or,
+    Not,
+    Op(Op),
+    /// A bare word or quoted string
+    Word(String),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::LParen => write!(f, "`(`"),
+            Token::RParen => write!(f, "`)`"),
+            Token::And => write!(f, "AND"),
+            Token::Or => write!(f, "OR"),
+            Token::Not => write!(f, "NOT"),
+            Token::Op(op) => write!(f, "`{}`", op.symbol()),
+            Token::Word(word) => write!(f, "`{}`", word),
+        }
+    }
+}
+
+fn invalid(column: usize, detail: impl std::fmt::Display) -> GraphMigratorError {
+    GraphMigratorError::InvalidFilter(format!("{} at column {}", detail, column))
+}
+
+/// Split `text` into tokens, each with its 1-indexed column
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let column = i + 1;
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let (token, width) = match (c, next) {
+            _ if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('(', _) => (Token::LParen, 1),
+            (')', _) => (Token::RParen, 1),
+            ('!', Some('=')) => (Token::Op(Op::Ne), 2),
+            ('!', Some('~')) => (Token::Op(Op::NotMatch), 2),
+            ('<', Some('=')) => (Token::Op(Op::Le), 2),
+            ('>', Some('=')) => (Token::Op(Op::Ge), 2),
+            ('=', _) => (Token::Op(Op::Eq), 1),
+            ('~', _) => (Token::Op(Op::Match), 1),
+            ('<', _) => (Token::Op(Op::Lt), 1),
+            ('>', _) => (Token::Op(Op::Gt), 1),
+            ('!', _) => return Err(invalid(column, "expected `!=` or `!~`")),
+            ('"' | '\'', _) => {
+                let mut word = String::new();
+                let mut j = i + 1;
+                loop {
+                    match chars.get(j) {
+                        None => return Err(invalid(column, "unterminated string")),
+                        Some('\\') if matches!(chars.get(j + 1), Some(&e) if e == c || e == '\\') =>
+                        {
+                            word.push(chars[j + 1]);
+                            j += 2;
+                        }
+                        Some(&q) if q == c => break,
+                        Some(&other) => {
+                            word.push(other);
+                            j += 1;
+                        }
+                    }
+                }
+                (Token::Word(word), j + 1 - i)
+            }
+            _ => {
+                let end = (i..chars.len())
+                    .find(|&j| chars[j].is_whitespace() || "()=!~<>\"'".contains(chars[j]))
+                    .unwrap_or(chars.len());
+                let word: String = chars[i..end].iter().collect();
+                let token = match word.to_ascii_uppercase().as_str() {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "NOT" => Token::Not,
+                    _ => Token::Word(word),
+                };
+                (token, end - i)
+            }
+        };
+        tokens.push((column, token));
+        i += width;
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [(usize, Token)],
+    position: usize,
+    /// Column just past the end of the input, for "expected ..." at the end
+    end: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn column(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .map_or(self.end, |(column, _)| *column)
+    }
+
+    fn next(&mut self) -> Option<(usize, Token)> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut terms = vec![self.and()?];
+        while self.peek() == Some(&Token::Or) {
+            self.position += 1;
+            terms.push(self.and()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            Expr::Or(terms)
+        })
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut terms = vec![self.unary()?];
+        while self.peek() == Some(&Token::And) {
+            self.position += 1;
+            terms.push(self.unary()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            Expr::And(terms)
+        })
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        let column = self.column();
+        match self.next() {
+            Some((_, Token::Not)) => Ok(Expr::Not(Box::new(self.unary()?))),
+            Some((_, Token::LParen)) => {
+                let inner = self.or()?;
+                match self.next() {
+                    Some((_, Token::RParen)) => Ok(inner),
+                    _ => Err(invalid(column, "unclosed `(`")),
+                }
+            }
+            Some((_, Token::Word(name))) => {
+                let field = Field::from_name(&name).ok_or_else(|| {
+                    invalid(
+                        column,
+                        format!("unknown field `{}` (expected one of {})", name, FIELD_NAMES),
+                    )
+                })?;
+                self.test(field, &name)
+            }
+            Some((column, token)) => Err(invalid(
+                column,
+                format!("expected a field name, found {}", token),
+            )),
+            None => Err(invalid(column, "expected a field name")),
+        }
+    }
+
+    fn test(&mut self, field: Field, name: &str) -> Result<Expr> {
+        let column = self.column();
+        let op = match self.next() {
+            Some((_, Token::Op(op))) => op,
+            _ => {
+                return Err(invalid(
+                    column,
+                    format!("expected an operator after `{}`", name),
+                ))
+            }
+        };
+        let column = self.column();
+        let value = match self.next() {
+            Some((_, Token::Word(value))) => value,
+            _ => {
+                return Err(invalid(
+                    column,
+                    format!("expected a value after `{}`", op.symbol()),
+                ))
+            }
+        };
+
+        let test = match op {
+            Op::Match | Op::NotMatch => {
+                let regex = Regex::new(&value)
+                    .map_err(|e| invalid(column, format!("invalid regex: {}", e)))?;
+                if op == Op::Match {
+                    Test::Matches(regex)
+                } else {
+                    Test::NotMatches(regex)
+                }
+            }
+            Op::Eq | Op::Ne => {
+                let value = typed_value(&field, &value).map_err(|e| invalid(column, e))?;
+                if op == Op::Eq {
+                    Test::Equals(value)
+                } else {
+                    Test::NotEquals(value)
+                }
+            }
+            Op::Lt | Op::Le | Op::Gt | Op::Ge => {
+                if !field.is_numeric() {
+                    return Err(invalid(
+                        column,
+                        format!("`{}` needs a numeric field, not `{}`", op.symbol(), name),
+                    ));
+                }
+                let bound = value.parse().map_err(|_| {
+                    invalid(column, format!("expected a number, found `{}`", value))
+                })?;
+                Test::Compare(op, bound)
+            }
+        };
+        Ok(Expr::Test(field, test))
+    }
+}
+
+/// `value` parsed as the kind `field` holds
+///
+/// Attributes hold any kind of value, so they compare as numbers when both
+/// sides are numeric (`attr.tier=3` matches `3` and `3.0`) and as text
+/// otherwise.
+fn typed_value(field: &Field, value: &str) -> std::result::Result<Value, String> {
+    match field {
+        Field::Type => NodeType::from_str(value).map(Value::Type),
+        Field::Status => MigrationStatus::from_str(value).map(Value::Status),
+        Field::External => match value.to_ascii_lowercase().as_str() {
+            "true" => Ok(Value::Bool(true)),
+            "false" => Ok(Value::Bool(false)),
+            _ => Err(format!("expected true or false, found `{}`", value)),
+        },
+        Field::Churn | Field::Coverage | Field::Lines => value
+            .parse()
+            .map(Value::Number)
+            .map_err(|_| format!("expected a number, found `{}`", value)),
+        Field::Id
+        | Field::Name
+        | Field::File
+        | Field::Language
+        | Field::Namespace
+        | Field::Attribute(_) => Ok(Value::Text(value.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str, node_type: NodeType, status: MigrationStatus) -> Node {
+        let (file, name) = id.split_once("::").unwrap();
+        let mut node = Node::new(id, name, node_type, "python", file);
+        node.status = status;
+        node
+    }
+
+    fn graph() -> Graph {
+        let mut graph = Graph::new();
+        let mut legacy = node(
+            "legacy/billing.py::charge",
+            NodeType::Function,
+            MigrationStatus::Pending,
+        );
+        legacy.line_range = Some((10, 49));
+        legacy.churn = Some(7);
+        legacy
+            .attributes
+            .insert("team".to_string(), AttrValue::from("payments"));
+        legacy
+            .attributes
+            .insert("tier".to_string(), AttrValue::from(3));
+        graph.add_node(legacy);
+        graph.add_node(node(
+            "legacy/billing.py::refund",
+            NodeType::Function,
+            MigrationStatus::Migrated,
+        ));
+        graph.add_node(node(
+            "legacy/billing.py::Invoice",
+            NodeType::Class,
+            MigrationStatus::InProgress,
+        ));
+        graph.add_node(node(
+            "app/main.py::main",
+            NodeType::Function,
+            MigrationStatus::Pending,
+        ));
+        graph
+    }
+
+    fn selected(graph: &Graph, expr: &str) -> Vec<String> {
+        let filter = Filter::parse(expr).unwrap();
+        let mut ids: Vec<String> = graph
+            .nodes()
+            .filter(|n| filter.matches(n))
+            .map(|n| n.id.clone())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn test_filter_selects_nodes() {
+        let graph = graph();
+        assert_eq!(
+            selected(
+                &graph,
+                r#"type=Function AND file~"legacy/.*" AND status!=Migrated"#
+            ),
+            vec!["legacy/billing.py::charge"]
+        );
+        // AND binds tighter than OR; NOT and parentheses override
+        assert_eq!(
+            selected(&graph, "type=class or name=main and status=pending"),
+            vec!["app/main.py::main", "legacy/billing.py::Invoice"]
+        );
+        assert_eq!(
+            selected(&graph, "NOT (file ~ ^legacy/ OR status = 'in-progress')"),
+            vec!["app/main.py::main"]
+        );
+        // Nodes without a number are never compared
+        assert_eq!(
+            selected(&graph, "lines >= 40 AND churn < 10"),
+            vec!["legacy/billing.py::charge"]
+        );
+        assert_eq!(selected(&graph, "churn != 7").len(), 3);
+        assert_eq!(selected(&graph, "namespace !~ .").len(), 4);
+        assert_eq!(
+            selected(&graph, "attr.team=payments AND attr.tier <= 3"),
+            vec!["legacy/billing.py::charge"]
+        );
+        assert_eq!(selected(&graph, "attr.tier = 3.0").len(), 1);
+        assert_eq!(selected(&graph, "attr.team !~ pay").len(), 3);
+
+        let mut scoped = graph.clone();
+        let filter: Filter = "name ~ '^(charge|refund)$'".parse().unwrap();
+        assert_eq!(filter.retain(&mut scoped), 2);
+        assert_eq!(scoped.node_count(), 2);
+        assert!(scoped.get_by_id("app/main.py::main").is_none());
+    }
+
+    #[test]
+    fn test_filter_selects_edges_by_attribute() {
+        let mut graph = graph();
+        let charge = graph.get_by_id("legacy/billing.py::charge").unwrap();
+        let invoice = graph.get_by_id("legacy/billing.py::Invoice").unwrap();
+        let main = graph.get_by_id("app/main.py::main").unwrap();
+        let mut hot = Edge::new(crate::graph::EdgeType::Calls);
+        hot.attributes
+            .insert("calls_per_day".to_string(), 900.into());
+        graph.add_edge(main, charge, hot);
+        graph.add_edge(main, invoice, Edge::new(crate::graph::EdgeType::Calls));
+
+        let filter: Filter = "attr.calls_per_day > 100".parse().unwrap();
+        assert_eq!(filter.retain_edges(&mut graph), 1);
+        assert_eq!(graph.edge_count(), 1);
+        // Node fields are lacking on edges
+        assert!(!Filter::parse("type=function")
+            .unwrap()
+            .matches_edge(&Edge::new(crate::graph::EdgeType::Calls)));
+    }
+
+    #[test]
+    fn test_filter_errors_name_the_problem() {
+        let error = |expr: &str| match Filter::parse(expr) {
+            Err(GraphMigratorError::InvalidFilter(detail)) => detail,
+            other => panic!("expected an invalid filter, got {:?}", other),
+        };
+        assert!(error("colour=red").starts_with("unknown field `colour`"));
+        assert_eq!(
+            error("type=Widget"),
+            "Unknown node type: Widget at column 6"
+        );
+        assert_eq!(
+            error("status=done").split(" at ").next(),
+            Some("Unknown migration status: done")
+        );
+        assert_eq!(
+            error("name < 3"),
+            "`<` needs a numeric field, not `name` at column 8"
+        );
+        assert_eq!(
+            error("churn > many"),
+            "expected a number, found `many` at column 9"
+        );
+        assert_eq!(error("name ~ '('").split(':').next(), Some("invalid regex"));
+        assert_eq!(error("name = \"open"), "unterminated string at column 8");
+        assert_eq!(error("name = a AND"), "expected a field name at column 13");
+        assert_eq!(error("(name = a"), "unclosed `(` at column 1");
+        assert_eq!(error("name = a b"), "unexpected `b` at column 10");
+        assert_eq!(
+            error("name a"),
+            "expected an operator after `name` at column 6"
+        );
+    }
+}
@@ -0,0 +1,527 @@
+//! Runtime wiring declared through Django and Flask
+//!
+//! Web frameworks call view functions and load related models themselves, so
+//! static call edges miss how a web application hangs together: a view that
+//! only a URL table mentions looks unused, and a model looks independent of
+//! the models its foreign keys point at. [`extract_wiring()`] finds
+//!
+//! - Django route tables: `path()`, `re_path()` and `url()` entries in a
+//!   module-level `urlpatterns` list (patterns of inline `include([...])`
+//!   lists are prefixed with their parent's),
+//! - Flask-style route decorators (`@app.route("/x")`, `@bp.get("/x")`) on
+//!   top-level functions, and `add_url_rule()` calls,
+//! - Django model relations: class attributes assigned a `ForeignKey`,
+//!   `OneToOneField` or `ManyToManyField`.
+//!
+//! Views and relation targets are recorded as written. With
+//! [`Config::frameworks`](crate::config::Config::frameworks),
+//! [`resolve::link_frameworks()`](crate::resolve::link_frameworks()) resolves
+//! them and adds a `Route` node per URL pattern with a [`EdgeType::RoutesTo`]
+//! edge to its view, and a [`EdgeType::ForeignKey`] edge from each model to
+//! each model it relates to.
+//!
+//! [`EdgeType::RoutesTo`]: crate::graph::EdgeType::RoutesTo
+//! [`EdgeType::ForeignKey`]: crate::graph::EdgeType::ForeignKey
+
+use crate::error::Result;
+use crate::parser::{Language, ParseSession};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// A URL pattern and the view it dispatches to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Route {
+    /// URL pattern as registered (e.g., `users/<int:pk>/`, `/login`)
+    pub pattern: String,
+    /// View as written, possibly dotted (e.g., `views.user_detail`)
+    pub view: String,
+    /// 1-indexed line of the registration
+    pub line: usize,
+}
+
+/// A relation field from one model class to another
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelRelation {
+    /// Class declaring the field
+    pub model: String,
+    /// Field name
+    pub field: String,
+    /// Related model as written: a name, a dotted name, a lazy reference
+    /// such as `"billing.Invoice"`, or `"self"`
+    pub target: String,
+    /// 1-indexed line of the field
+    pub line: usize,
+}
+
+/// Routes and model relations declared in one file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrameworkWiring {
+    /// Routes, in source order
+    pub routes: Vec<Route>,
+    /// Model relations, in source order
+    pub relations: Vec<ModelRelation>,
+}
+
+impl FrameworkWiring {
+    /// Whether the file declares no routes or relations
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty() && self.relations.is_empty()
+    }
+}
+
+/// Django URL helpers taking `(pattern, view)`
+const DJANGO_ROUTE_FUNCTIONS: &[&str] = &["path", "re_path", "url"];
+
+/// Decorator methods that register a route
+const ROUTE_DECORATORS: &[&str] = &["route", "get", "post", "put", "patch", "delete"];
+
+/// Django fields that relate a model to another
+const RELATION_FIELDS: &[&str] = &["ForeignKey", "OneToOneField", "ManyToManyField"];
+
+/// Find the routes and model relations declared in a Python file
+///
+/// # Errors
+/// Returns an error if the file cannot be read or parsed.
+pub fn extract_wiring(path: &Path) -> Result<FrameworkWiring> {
+    extract_wiring_in(&mut ParseSession::uncached(), path)
+}
+
+/// [`extract_wiring()`] with the parser and source cache of `session`
+///
+/// Files the session parses in a language other than Python declare nothing.
+/// A file the session does not hold is only parsed if
+/// [`may_declare_wiring()`] accepts its contents.
+pub fn extract_wiring_in(session: &mut ParseSession, path: &Path) -> Result<FrameworkWiring> {
+    let Some(file) = session.parse_if(path, may_declare_wiring)? else {
+        return Ok(FrameworkWiring::default());
+    };
+    if file.language != Language::Python {
+        return Ok(FrameworkWiring::default());
+    }
+    Ok(wiring_from_tree(
+        &file.tree.root_node(),
+        file.source.as_bytes(),
+    ))
+}
+
+/// Whether a file with contents `source` can declare routes or relations
+///
+/// Every declaration [`extract_wiring()`] finds names `urlpatterns`,
+/// `add_url_rule`, a relation field, or a route decorator, so files without
+/// any of those words can be skipped without parsing them. Routes and
+/// words are ASCII, so the check holds in every encoding the parser decodes.
+pub fn may_declare_wiring(source: &[u8]) -> bool {
+    let mentions = |word: &str| {
+        source
+            .windows(word.len())
+            .any(|window| window == word.as_bytes())
+    };
+    mentions("urlpatterns")
+        || mentions("add_url_rule")
+        || RELATION_FIELDS.iter().any(|field| mentions(field))
+        || (mentions("@") && ROUTE_DECORATORS.iter().any(|method| mentions(method)))
+}
+
+fn wiring_from_tree(root: &tree_sitter::Node, source: &[u8]) -> FrameworkWiring {
+    let mut wiring = FrameworkWiring::default();
+    let mut cursor = root.walk();
+    for node in root.children(&mut cursor) {
+        match node.kind() {
+            "expression_statement" => {
+                let Some(statement) = node.named_child(0) else {
+                    continue;
+                };
+                url_patterns(&statement, source, &mut wiring.routes);
+                url_rules(&statement, source, &mut wiring.routes);
+            }
+            "decorated_definition" => {
+                decorated_routes(&node, source, &mut wiring.routes);
+                if let Some(class) = node.child_by_field_name("definition") {
+                    model_relations(&class, source, &mut wiring.relations);
+                }
+            }
+            "class_definition" => model_relations(&node, source, &mut wiring.relations),
+            _ => {}
+        }
+    }
+    wiring
+}
+
+/// Routes of `urlpatterns = [...]` or `urlpatterns += [...]`
+fn url_patterns(statement: &tree_sitter::Node, source: &[u8], routes: &mut Vec<Route>) {
+    if !matches!(statement.kind(), "assignment" | "augmented_assignment") {
+        return;
+    }
+    let target = statement.child_by_field_name("left");
+    if target.and_then(|left| text(&left, source)).as_deref() != Some("urlpatterns") {
+        return;
+    }
+    if let Some(value) = statement.child_by_field_name("right") {
+        django_routes(&value, source, "", routes);
+    }
+}
+
+/// Every `path(pattern, view)` call below `node`, with `prefix` prepended
+fn django_routes(node: &tree_sitter::Node, source: &[u8], prefix: &str, routes: &mut Vec<Route>) {
+    if node.kind() == "call" {
+        let function = node.child_by_field_name("function");
+        let name = function.and_then(|function| last_name(&function, source));
+        if name.is_some_and(|name| DJANGO_ROUTE_FUNCTIONS.contains(&name.as_str())) {
+            let args = positional_args(node);
+            let pattern = args.first().and_then(|arg| string_value(arg, source));
+            if let (Some(pattern), Some(view)) = (pattern, args.get(1)) {
+                let pattern = format!("{}{}", prefix, pattern);
+                match included_list(view, source) {
+                    Some(list) => django_routes(&list, source, &pattern, routes),
+                    None => routes.extend(view_name(view, source).map(|view| Route {
+                        pattern,
+                        view,
+                        line: node.start_position().row + 1,
+                    })),
+                }
+            }
+            return;
+        }
+    }
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        django_routes(&child, source, prefix, routes);
+    }
+}
+
+/// The list argument of `include([...])`
+fn included_list<'a>(node: &tree_sitter::Node<'a>, source: &[u8]) -> Option<tree_sitter::Node<'a>> {
+    if node.kind() != "call" {
+        return None;
+    }
+    let function = node.child_by_field_name("function")?;
+    if last_name(&function, source)? != "include" {
+        return None;
+    }
+    positional_args(node)
+        .into_iter()
+        .find(|arg| arg.kind() == "list")
+}
+
+/// `app.add_url_rule("/x", "endpoint", view)` or `..., view_func=view)`
+fn url_rules(statement: &tree_sitter::Node, source: &[u8], routes: &mut Vec<Route>) {
+    if statement.kind() != "call" {
+        return;
+    }
+    let Some(function) = statement.child_by_field_name("function") else {
+        return;
+    };
+    if function.kind() != "attribute"
+        || last_name(&function, source).as_deref() != Some("add_url_rule")
+    {
+        return;
+    }
+    let args = positional_args(statement);
+    let view = keyword_arg(statement, source, "view_func").or_else(|| args.get(2).copied());
+    let pattern = args.first().and_then(|arg| string_value(arg, source));
+    if let (Some(pattern), Some(view)) = (pattern, view.and_then(|v| view_name(&v, source))) {
+        routes.push(Route {
+            pattern,
+            view,
+            line: statement.start_position().row + 1,
+        });
+    }
+}
+
+/// `@<object>.route("/x")` and friends on a top-level function
+fn decorated_routes(node: &tree_sitter::Node, source: &[u8], routes: &mut Vec<Route>) {
+    let Some(definition) = node.child_by_field_name("definition") else {
+        return;
+    };
+    if definition.kind() != "function_definition" {
+        return;
+    }
+    let Some(view) = definition
+        .child_by_field_name("name")
+        .and_then(|name| text(&name, source))
+    else {
+        return;
+    };
+
+    let mut cursor = node.walk();
+    for decorator in node.named_children(&mut cursor) {
+        let Some(call) = decorator
+            .named_child(0)
+            .filter(|call| call.kind() == "call")
+        else {
+            continue;
+        };
+        let Some(function) = call.child_by_field_name("function") else {
+            continue;
+        };
+        let method = last_name(&function, source);
+        if function.kind() != "attribute"
+            || !method.is_some_and(|method| ROUTE_DECORATORS.contains(&method.as_str()))
+        {
+            continue;
+        }
+        let pattern = positional_args(&call)
+            .first()
+            .and_then(|arg| string_value(arg, source));
+        if let Some(pattern) = pattern {
+            routes.push(Route {
+                pattern,
+                view: view.clone(),
+                line: decorator.start_position().row + 1,
+            });
+        }
+    }
+}
+
+/// `field = models.ForeignKey(Target, ...)` in the body of `class`
+fn model_relations(class: &tree_sitter::Node, source: &[u8], relations: &mut Vec<ModelRelation>) {
+    if class.kind() != "class_definition" {
+        return;
+    }
+    let (Some(model), Some(body)) = (
+        class
+            .child_by_field_name("name")
+            .and_then(|name| text(&name, source)),
+        class.child_by_field_name("body"),
+    ) else {
+        return;
+    };
+
+    let mut cursor = body.walk();
+    for statement in body.named_children(&mut cursor) {
+        let Some(assignment) = statement
+            .named_child(0)
+            .filter(|a| a.kind() == "assignment")
+        else {
+            continue;
+        };
+        let (Some(field), Some(call)) = (
+            assignment.child_by_field_name("left"),
+            assignment.child_by_field_name("right"),
+        ) else {
+            continue;
+        };
+        if field.kind() != "identifier" || call.kind() != "call" {
+            continue;
+        }
+        let kind = call
+            .child_by_field_name("function")
+            .and_then(|function| last_name(&function, source));
+        if !kind.is_some_and(|kind| RELATION_FIELDS.contains(&kind.as_str())) {
+            continue;
+        }
+        let target = keyword_arg(&call, source, "to")
+            .or_else(|| positional_args(&call).first().copied())
+            .and_then(|target| string_value(&target, source).or_else(|| dotted(&target, source)));
+        if let (Some(field), Some(target)) = (text(&field, source), target) {
+            relations.push(ModelRelation {
+                model: model.clone(),
+                field,
+                target,
+                line: statement.start_position().row + 1,
+            });
+        }
+    }
+}
+
+/// Dotted name of a view expression: `f`, `views.f`, or `views.View.as_view()`
+fn view_name(node: &tree_sitter::Node, source: &[u8]) -> Option<String> {
+    if node.kind() == "call" {
+        let function = node.child_by_field_name("function")?;
+        if function.kind() == "attribute" && last_name(&function, source)? == "as_view" {
+            return dotted(&function.child_by_field_name("object")?, source);
+        }
+        return None;
+    }
+    dotted(node, source)
+}
+
+/// Text of an identifier or attribute chain
+fn dotted(node: &tree_sitter::Node, source: &[u8]) -> Option<String> {
+    match node.kind() {
+        "identifier" => text(node, source),
+        "attribute" => {
+            let object = dotted(&node.child_by_field_name("object")?, source)?;
+            let attribute = text(&node.child_by_field_name("attribute")?, source)?;
+            Some(format!("{}.{}", object, attribute))
+        }
+        _ => None,
+    }
+}
+
+/// Last component of an identifier or attribute (`path`, `models.ForeignKey`)
+fn last_name(node: &tree_sitter::Node, source: &[u8]) -> Option<String> {
+    match node.kind() {
+        "identifier" => text(node, source),
+        "attribute" => text(&node.child_by_field_name("attribute")?, source),
+        _ => None,
+    }
+}
+
+/// Contents of a plain string literal (no interpolation)
+fn string_value(node: &tree_sitter::Node, source: &[u8]) -> Option<String> {
+    if node.kind() != "string" {
+        return None;
+    }
+    let mut value = String::new();
+    let mut cursor = node.walk();
+    for part in node.named_children(&mut cursor) {
+        match part.kind() {
+            "string_start" | "string_end" => {}
+            "string_content" => value.push_str(part.utf8_text(source).ok()?),
+            _ => return None,
+        }
+    }
+    Some(value)
+}
+
+fn positional_args<'a>(call: &tree_sitter::Node<'a>) -> Vec<tree_sitter::Node<'a>> {
+    let Some(arguments) = call.child_by_field_name("arguments") else {
+        return Vec::new();
+    };
+    let mut cursor = arguments.walk();
+    arguments
+        .named_children(&mut cursor)
+        .filter(|arg| !matches!(arg.kind(), "keyword_argument" | "comment"))
+        .collect()
+}
+
+fn keyword_arg<'a>(
+    call: &tree_sitter::Node<'a>,
+    source: &[u8],
+    name: &str,
+) -> Option<tree_sitter::Node<'a>> {
+    let arguments = call.child_by_field_name("arguments")?;
+    let mut cursor = arguments.walk();
+    let found = arguments
+        .named_children(&mut cursor)
+        .filter(|arg| arg.kind() == "keyword_argument")
+        .find(|arg| {
+            arg.child_by_field_name("name")
+                .and_then(|key| text(&key, source))
+                .is_some_and(|key| key == name)
+        })?;
+    found.child_by_field_name("value")
+}
+
+fn text(node: &tree_sitter::Node, source: &[u8]) -> Option<String> {
+    node.utf8_text(source).ok().map(|s| s.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wiring(source: &str) -> FrameworkWiring {
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_python::LANGUAGE.into())
+            .unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let found = wiring_from_tree(&tree.root_node(), source.as_bytes());
+        // The text check never skips a file that declares something
+        assert!(found.is_empty() || may_declare_wiring(source.as_bytes()));
+        found
+    }
+
+    fn routes(wiring: &FrameworkWiring) -> Vec<(&str, &str)> {
+        wiring
+            .routes
+            .iter()
+            .map(|route| (route.pattern.as_str(), route.view.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_django_url_table() {
+        let found = wiring(
+            r#"
+from django.urls import include, path, re_path
+from . import views
+
+urlpatterns = [
+    path("", views.index, name="index"),
+    path("users/<int:pk>/", views.UserDetail.as_view()),
+    re_path(r"^legacy/$", legacy_view),
+    path("api/", include([path("items/", views.items)])),
+    path("blog/", include("blog.urls")),
+]
+urlpatterns += [path("health/", views.health)]
+other = [path("ignored/", views.ignored)]
+"#,
+        );
+        assert_eq!(
+            routes(&found),
+            vec![
+                ("", "views.index"),
+                ("users/<int:pk>/", "views.UserDetail"),
+                ("^legacy/$", "legacy_view"),
+                ("api/items/", "views.items"),
+                ("health/", "views.health"),
+            ]
+        );
+        assert_eq!(found.routes[1].line, 7);
+    }
+
+    #[test]
+    fn test_flask_routes_and_url_rules() {
+        let found = wiring(
+            r#"
+@app.route("/")
+def index():
+    pass
+
+@login_required
+@bp.post("/orders")
+def create_order():
+    pass
+
+@cache
+def not_a_view():
+    pass
+
+app.add_url_rule("/health", "health", health)
+app.add_url_rule("/status", view_func=views.status)
+"#,
+        );
+        assert_eq!(
+            routes(&found),
+            vec![
+                ("/", "index"),
+                ("/orders", "create_order"),
+                ("/health", "health"),
+                ("/status", "views.status"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_model_relations() {
+        let found = wiring(
+            r#"
+class Order(models.Model):
+    customer = models.ForeignKey(Customer, on_delete=models.CASCADE)
+    invoice = OneToOneField("billing.Invoice", on_delete=models.PROTECT)
+    tags = models.ManyToManyField(to="Tag")
+    parent = models.ForeignKey("self", null=True)
+    total = models.DecimalField()
+"#,
+        );
+        let relations: Vec<_> = found
+            .relations
+            .iter()
+            .map(|r| (r.model.as_str(), r.field.as_str(), r.target.as_str()))
+            .collect();
+        assert_eq!(
+            relations,
+            vec![
+                ("Order", "customer", "Customer"),
+                ("Order", "invoice", "billing.Invoice"),
+                ("Order", "tags", "Tag"),
+                ("Order", "parent", "self"),
+            ]
+        );
+        assert!(found.routes.is_empty());
+        assert!(!may_declare_wiring(b"class Order:\n    total = 1\n"));
+    }
+}
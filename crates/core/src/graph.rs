@@ -4,11 +4,18 @@
 //! even when nodes are removed—critical for migration tracking where
 //! nodes transition from Pending → Migrated → Superseded.
 
+use crate::error::GraphMigratorError;
+use crate::schema::{self, SCHEMA_VERSION};
 use petgraph::stable_graph::StableGraph;
 use petgraph::visit::{EdgeRef, IntoEdgeReferences};
+pub use petgraph::Direction;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 /// A node in the dependency graph representing a code element
+///
+/// Equality compares every field, floats by their bits, so that `Node` is
+/// `Eq`: a NaN score equals itself and `0.0` differs from `-0.0`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Node {
     /// Stable, language-agnostic identifier
@@ -23,6 +30,227 @@ pub struct Node {
     pub file_path: std::path::PathBuf,
     /// Line range (start, end) if applicable
     pub line_range: Option<(usize, usize)>,
+    /// Centrality scores, if computed by [`queries::centrality`](crate::queries::centrality())
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub centrality: Option<Centrality>,
+    /// Synthetic node for code outside the project (e.g., a third-party package)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub external: bool,
+    /// Distribution providing an external node, if known from [`deps`](crate::deps)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<PackageInfo>,
+    /// Migration progress; parsing always produces [`MigrationStatus::Pending`]
+    #[serde(default, skip_serializing_if = "MigrationStatus::is_pending")]
+    pub status: MigrationStatus,
+    /// Repository the node was scanned from, also prefixed to its ID
+    /// (see [`parser::namespace_ids()`](crate::parser::namespace_ids()))
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// Status changes made through [`migration::transition()`](crate::migration::transition()),
+    /// oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<StatusChange>,
+    /// Version control history, if computed by [`enrich::blame()`](crate::enrich::blame())
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blame: Option<Blame>,
+    /// Commits that changed the symbol within the churn window, if computed
+    /// by [`enrich::churn()`](crate::enrich::churn())
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub churn: Option<u32>,
+    /// Percentage of measured lines executed by tests, if computed by
+    /// [`enrich::coverage()`](crate::enrich::coverage())
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<f64>,
+    /// Estimated migration effort, if computed by
+    /// [`enrich::estimate_effort()`](crate::enrich::estimate_effort())
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effort: Option<Effort>,
+    /// Owning teams or users, if computed by [`enrich::owners()`](crate::enrich::owners())
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub owners: Vec<String>,
+    /// Issue tracking a migration unit (e.g. `org/repo#12` or `MIG-42`),
+    /// set when `integrations::sync_units()` creates it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issue: Option<String>,
+    /// Data attached by enrichment passes and tools without a field of its own,
+    /// keyed by a name of their choosing (queried as `attr.<name>` in
+    /// [`filter`](crate::filter))
+    ///
+    /// New passes should store their results here. The typed fields above
+    /// (`centrality` to `issue`) predate this map and are not moved into it:
+    /// the state file, exports, the JSON schema and the queries that rank by
+    /// them read them by name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, AttrValue>,
+}
+
+impl Node {
+    /// Create a node with the required identity fields; optional fields start empty
+    pub fn new(
+        id: impl Into<String>,
+        name: impl Into<String>,
+        node_type: NodeType,
+        language: impl Into<String>,
+        file_path: impl Into<std::path::PathBuf>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            node_type,
+            language: language.into(),
+            file_path: file_path.into(),
+            line_range: None,
+            centrality: None,
+            external: false,
+            package: None,
+            status: MigrationStatus::Pending,
+            namespace: None,
+            history: Vec::new(),
+            blame: None,
+            churn: None,
+            coverage: None,
+            effort: None,
+            owners: Vec::new(),
+            issue: None,
+            attributes: BTreeMap::new(),
+        }
+    }
+
+    /// File and symbol parts of the node's ID, without its namespace
+    ///
+    /// Directory scans write IDs relative to the project root, so unlike
+    /// [`file_path`](Self::file_path) the file part does not depend on where
+    /// the project is checked out. See [`split_id()`].
+    pub fn id_parts(&self) -> Option<(&str, &str)> {
+        split_id(&self.id, self.namespace.as_deref())
+    }
+}
+
+/// File and symbol parts of `id` (`src/utils.py::Class::method` gives
+/// `src/utils.py` and `Class::method`), with `namespace` and its separator
+/// taken off the front if `id` starts with them
+///
+/// Returns `None` if `id` has no `::`.
+pub fn split_id<'a>(id: &'a str, namespace: Option<&str>) -> Option<(&'a str, &'a str)> {
+    strip_namespace(id, namespace).split_once("::")
+}
+
+/// `id` without `namespace` and its separator, or all of `id` if it does not
+/// start with them
+pub fn strip_namespace<'a>(id: &'a str, namespace: Option<&str>) -> &'a str {
+    namespace
+        .and_then(|namespace| id.strip_prefix(namespace))
+        .and_then(|rest| rest.strip_prefix(crate::parser::NAMESPACE_SEPARATOR))
+        .unwrap_or(id)
+}
+
+/// Package metadata attached to external module nodes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackageInfo {
+    /// Distribution name as declared (e.g., `PyYAML` for `import yaml`)
+    pub name: String,
+    /// Locked or pinned version, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+/// Centrality scores used to rank symbols by migration risk
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Centrality {
+    /// PageRank over dependency edges (sums to 1.0 across the graph)
+    pub pagerank: f64,
+    /// Normalized betweenness centrality in `[0.0, 1.0]`
+    pub betweenness: f64,
+}
+
+/// Size and complexity measures behind a symbol's effort score
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Effort {
+    /// Lines in the symbol's line range
+    pub lines: usize,
+    /// Branch points (`if`, loops, `except`, boolean operators, ...) in those lines
+    pub branches: usize,
+    /// Incoming dependency edges
+    pub fan_in: usize,
+    /// Outgoing dependency edges
+    pub fan_out: usize,
+    /// Combined score; see [`enrich::effort`](crate::enrich::effort)
+    pub score: f64,
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        let Node {
+            id,
+            name,
+            node_type,
+            language,
+            file_path,
+            line_range,
+            centrality,
+            external,
+            package,
+            status,
+            namespace,
+            history,
+            blame,
+            churn,
+            coverage,
+            effort,
+            owners,
+            issue,
+            attributes,
+        } = self;
+        *id == other.id
+            && *name == other.name
+            && *node_type == other.node_type
+            && *language == other.language
+            && *file_path == other.file_path
+            && *line_range == other.line_range
+            && *centrality == other.centrality
+            && *external == other.external
+            && *package == other.package
+            && *status == other.status
+            && *namespace == other.namespace
+            && *history == other.history
+            && *blame == other.blame
+            && *churn == other.churn
+            && coverage.map(f64::to_bits) == other.coverage.map(f64::to_bits)
+            && *effort == other.effort
+            && *owners == other.owners
+            && *issue == other.issue
+            && *attributes == other.attributes
+    }
+}
+
+impl Eq for Node {}
+
+impl PartialEq for Centrality {
+    fn eq(&self, other: &Self) -> bool {
+        self.pagerank.to_bits() == other.pagerank.to_bits()
+            && self.betweenness.to_bits() == other.betweenness.to_bits()
+    }
+}
+
+impl Eq for Centrality {}
+
+impl PartialEq for Effort {
+    fn eq(&self, other: &Self) -> bool {
+        (self.lines, self.branches, self.fan_in, self.fan_out)
+            == (other.lines, other.branches, other.fan_in, other.fan_out)
+            && self.score.to_bits() == other.score.to_bits()
+    }
+}
+
+impl Eq for Effort {}
+
+/// Who last touched a symbol's lines, and when
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Blame {
+    /// Most recent commit time of any line, in seconds since the Unix epoch
+    pub last_modified: u64,
+    /// Authors with the most lines, most first
+    pub authors: Vec<String>,
 }
 
 /// Types of code elements that can be represented as nodes
@@ -38,6 +266,193 @@ pub enum NodeType {
     GlobalVariable,
     /// MigrationUnit represents a logical grouping of code being migrated together
     MigrationUnit,
+    /// URL pattern registered with a web framework (see [`frameworks`](crate::frameworks))
+    Route,
+}
+
+impl std::str::FromStr for NodeType {
+    type Err = String;
+
+    /// Parse a node type name case-insensitively, ignoring `_` and `-`
+    /// (`"function"`, `"GlobalVariable"`, `"migration-unit"`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized: String = s
+            .chars()
+            .filter(|c| *c != '_' && *c != '-')
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        match normalized.as_str() {
+            "file" => Ok(NodeType::File),
+            "module" => Ok(NodeType::Module),
+            "class" => Ok(NodeType::Class),
+            "interface" => Ok(NodeType::Interface),
+            "struct" => Ok(NodeType::Struct),
+            "function" => Ok(NodeType::Function),
+            "method" => Ok(NodeType::Method),
+            "globalvariable" => Ok(NodeType::GlobalVariable),
+            "migrationunit" => Ok(NodeType::MigrationUnit),
+            "route" => Ok(NodeType::Route),
+            _ => Err(format!("Unknown node type: {}", s)),
+        }
+    }
+}
+
+/// Where a symbol stands in the migration
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub enum MigrationStatus {
+    /// Not started
+    #[default]
+    Pending,
+    /// Being migrated
+    InProgress,
+    /// Cannot proceed until something else is migrated or decided
+    Blocked,
+    /// Done; the symbol now lives in the target codebase
+    Migrated,
+    /// Replaced by a different symbol and kept only for history
+    Superseded,
+}
+
+impl MigrationStatus {
+    /// Whether work on the symbol has not started
+    pub fn is_pending(&self) -> bool {
+        *self == MigrationStatus::Pending
+    }
+
+    /// Whether a symbol in this status may move to `next`
+    ///
+    /// Staying in the same status is always allowed. `Superseded` is a
+    /// tombstone and cannot be left; `Blocked` must be unblocked before the
+    /// symbol counts as migrated; a migrated symbol can be reopened or
+    /// superseded, but not sent back to `Pending` or `Blocked`.
+    pub fn can_transition_to(self, next: MigrationStatus) -> bool {
+        use MigrationStatus::*;
+        self == next
+            || match self {
+                Pending | InProgress => true,
+                Blocked => matches!(next, Pending | InProgress | Superseded),
+                Migrated => matches!(next, InProgress | Superseded),
+                Superseded => false,
+            }
+    }
+}
+
+/// One recorded change of a node's [`MigrationStatus`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusChange {
+    /// Status before the change
+    pub from: MigrationStatus,
+    /// Status after the change
+    pub to: MigrationStatus,
+    /// When the change was made, in seconds since the Unix epoch
+    pub at: u64,
+}
+
+impl std::str::FromStr for MigrationStatus {
+    type Err = String;
+
+    /// Parse a status name case-insensitively, ignoring `_` and `-`
+    /// (`"pending"`, `"InProgress"`, `"in-progress"`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized: String = s
+            .chars()
+            .filter(|c| *c != '_' && *c != '-')
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        match normalized.as_str() {
+            "pending" => Ok(MigrationStatus::Pending),
+            "inprogress" => Ok(MigrationStatus::InProgress),
+            "blocked" => Ok(MigrationStatus::Blocked),
+            "migrated" => Ok(MigrationStatus::Migrated),
+            "superseded" => Ok(MigrationStatus::Superseded),
+            _ => Err(format!("Unknown migration status: {}", s)),
+        }
+    }
+}
+
+/// Value of a node or edge attribute
+///
+/// Serialized as the plain JSON value (`true`, `3`, `0.5`, `"text"`).
+/// Floats are equal if their bits are, so NaN equals itself and the type is `Eq`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AttrValue {
+    /// A flag, e.g. `generated`
+    Bool(bool),
+    /// A whole number, e.g. an arity or a count
+    Int(i64),
+    /// A fractional number, e.g. a score or percentage
+    Float(f64),
+    /// Anything else, e.g. a team name
+    Text(String),
+}
+
+impl PartialEq for AttrValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (AttrValue::Bool(a), AttrValue::Bool(b)) => a == b,
+            (AttrValue::Int(a), AttrValue::Int(b)) => a == b,
+            (AttrValue::Float(a), AttrValue::Float(b)) => a.to_bits() == b.to_bits(),
+            (AttrValue::Text(a), AttrValue::Text(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for AttrValue {}
+
+impl AttrValue {
+    /// The value as a number, if it is one
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            AttrValue::Int(n) => Some(*n as f64),
+            AttrValue::Float(n) => Some(*n),
+            AttrValue::Bool(_) | AttrValue::Text(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for AttrValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttrValue::Bool(value) => write!(f, "{}", value),
+            AttrValue::Int(value) => write!(f, "{}", value),
+            AttrValue::Float(value) => write!(f, "{}", value),
+            AttrValue::Text(value) => f.write_str(value),
+        }
+    }
+}
+
+impl From<bool> for AttrValue {
+    fn from(value: bool) -> Self {
+        AttrValue::Bool(value)
+    }
+}
+
+impl From<i64> for AttrValue {
+    fn from(value: i64) -> Self {
+        AttrValue::Int(value)
+    }
+}
+
+impl From<f64> for AttrValue {
+    fn from(value: f64) -> Self {
+        AttrValue::Float(value)
+    }
+}
+
+impl From<&str> for AttrValue {
+    fn from(value: &str) -> Self {
+        AttrValue::Text(value.to_string())
+    }
+}
+
+impl From<String> for AttrValue {
+    fn from(value: String) -> Self {
+        AttrValue::Text(value)
+    }
 }
 
 /// An edge representing a relationship between two nodes
@@ -45,10 +460,58 @@ pub enum NodeType {
 pub struct Edge {
     /// Type of relationship
     pub edge_type: EdgeType,
+    /// How many times the relationship was found (e.g. call sites), when
+    /// parallel edges are aggregated ([`EdgePolicy::Count`])
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub count: u32,
+    /// Data attached by tools, as on [`Node::attributes`]; kept in the state
+    /// file across rescans, and selected with [`Filter::retain_edges()`](crate::filter::Filter::retain_edges())
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, AttrValue>,
+}
+
+impl Edge {
+    /// An edge of `edge_type`, found once
+    pub fn new(edge_type: EdgeType) -> Self {
+        Self {
+            edge_type,
+            count: 1,
+            attributes: BTreeMap::new(),
+        }
+    }
+}
+
+fn one() -> u32 {
+    1
+}
+
+fn is_one(count: &u32) -> bool {
+    *count == 1
+}
+
+/// What [`Graph::add_edge()`] does when an edge with the same source,
+/// target and type already exists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EdgePolicy {
+    /// Keep the existing edge and drop the new one
+    #[default]
+    Dedupe,
+    /// Keep the existing edge and add the new edge's `count` to it
+    Count,
+    /// Add a parallel edge
+    Multi,
+}
+
+impl EdgePolicy {
+    /// Whether this is the default, [`Dedupe`](Self::Dedupe)
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
 }
 
 /// Types of relationships between nodes
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum EdgeType {
     /// Structural hierarchy: File → Class → Method
     Contains,
@@ -62,6 +525,62 @@ pub enum EdgeType {
     MigratedTo,
     /// Groups related migrations: Node → MigrationUnit
     PartOfMigration,
+    /// Test coverage: test function → symbol it calls (see [`testmap`](crate::testmap))
+    Tests,
+    /// Request dispatch: Route → view function or class (see [`frameworks`](crate::frameworks))
+    RoutesTo,
+    /// Model relation field: model class → related model class
+    ForeignKey,
+    /// Type annotation: function → class named in its parameter or return types
+    References,
+}
+
+impl EdgeType {
+    /// Whether this edge expresses a code dependency (`Calls`, `Imports`,
+    /// `Inherits`, `References`, or the framework wiring `RoutesTo` and
+    /// `ForeignKey`)
+    ///
+    /// Structural (`Contains`), test and migration-tracking edges are excluded, so
+    /// dependency queries don't treat "file contains function" or
+    /// "legacy migrated to target" as one symbol depending on another.
+    pub fn is_dependency(&self) -> bool {
+        matches!(
+            self,
+            EdgeType::Calls
+                | EdgeType::Imports
+                | EdgeType::Inherits
+                | EdgeType::References
+                | EdgeType::RoutesTo
+                | EdgeType::ForeignKey
+        )
+    }
+}
+
+impl std::str::FromStr for EdgeType {
+    type Err = String;
+
+    /// Parse an edge type name case-insensitively, ignoring `_` and `-`
+    /// (`"calls"`, `"MigratedTo"`, `"part-of-migration"`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized: String = s
+            .chars()
+            .filter(|c| *c != '_' && *c != '-')
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        match normalized.as_str() {
+            "contains" => Ok(EdgeType::Contains),
+            "calls" => Ok(EdgeType::Calls),
+            "imports" => Ok(EdgeType::Imports),
+            "inherits" => Ok(EdgeType::Inherits),
+            "migratedto" => Ok(EdgeType::MigratedTo),
+            "partofmigration" => Ok(EdgeType::PartOfMigration),
+            "tests" => Ok(EdgeType::Tests),
+            "routesto" => Ok(EdgeType::RoutesTo),
+            "foreignkey" => Ok(EdgeType::ForeignKey),
+            "references" => Ok(EdgeType::References),
+            _ => Err(format!("Unknown edge type: {}", s)),
+        }
+    }
 }
 
 /// The dependency graph
@@ -71,11 +590,24 @@ pub enum EdgeType {
 ///
 /// Note: Does not derive `PartialEq`, `Serialize`, or `Deserialize` because
 /// `StableGraph` doesn't implement these traits. For equality checks,
-/// compare `node_count()` and `edge_count()` or iterate nodes/edges directly.
+/// compare `node_count()` and `edge_count()` or iterate nodes/edges directly
+/// (or use [`GraphData`] / [`Graph::fingerprint()`]).
+///
+/// Maintains an ID → index map so [`Graph::get_by_id()`] is O(1). IDs should
+/// be unique; if the same ID is added twice, lookups return the first node.
 #[derive(Debug, Clone)]
 pub struct Graph {
     /// The underlying stable graph (private to enforce encapsulation)
     inner: StableGraph<Node, Edge>,
+    /// Node ID → index of the first live node with that ID
+    by_id: HashMap<String, petgraph::stable_graph::NodeIndex>,
+    /// Node ID → number of live nodes sharing it beyond the indexed one;
+    /// only duplicated IDs have an entry
+    duplicates: HashMap<String, usize>,
+    /// How repeated edges are added
+    edge_policy: EdgePolicy,
+    /// Provenance, kept when the graph is persisted
+    metadata: GraphMetadata,
 }
 
 impl Graph {
@@ -83,21 +615,238 @@ impl Graph {
     pub fn new() -> Self {
         Self {
             inner: StableGraph::new(),
+            by_id: HashMap::new(),
+            duplicates: HashMap::new(),
+            edge_policy: EdgePolicy::default(),
+            metadata: GraphMetadata::default(),
         }
     }
 
+    /// This graph, adding edges under `policy` from now on
+    pub fn with_edge_policy(mut self, policy: EdgePolicy) -> Self {
+        self.edge_policy = policy;
+        self
+    }
+
+    /// How [`add_edge()`](Self::add_edge) treats repeated edges
+    pub fn edge_policy(&self) -> EdgePolicy {
+        self.edge_policy
+    }
+
+    /// Change the policy for edges added from now on; existing edges are kept
+    pub fn set_edge_policy(&mut self, policy: EdgePolicy) {
+        self.edge_policy = policy;
+    }
+
+    /// How the graph was produced; empty unless a scan or a loaded
+    /// [`GraphData`] recorded it
+    pub fn metadata(&self) -> &GraphMetadata {
+        &self.metadata
+    }
+
+    /// Replace the graph's provenance
+    pub fn set_metadata(&mut self, metadata: GraphMetadata) {
+        self.metadata = metadata;
+    }
+
     /// Add a node to the graph
     pub fn add_node(&mut self, node: Node) -> petgraph::stable_graph::NodeIndex {
-        self.inner.add_node(node)
+        let id = node.id.clone();
+        let index = self.inner.add_node(node);
+        self.index(id, index);
+        index
+    }
+
+    /// Remove a node and all of its edges, returning it
+    ///
+    /// Other indices stay valid (`StableGraph` semantics).
+    pub fn remove_node(&mut self, index: petgraph::stable_graph::NodeIndex) -> Option<Node> {
+        let node = self.inner.remove_node(index)?;
+        self.unindex(&node.id, index);
+        Some(node)
+    }
+
+    /// Remove an edge, returning its weight
+    pub fn remove_edge(&mut self, index: petgraph::stable_graph::EdgeIndex) -> Option<Edge> {
+        self.inner.remove_edge(index)
+    }
+
+    /// Change a node's ID, keeping the ID index consistent
+    ///
+    /// Returns `false` if `index` is not in the graph.
+    pub fn set_node_id(
+        &mut self,
+        index: petgraph::stable_graph::NodeIndex,
+        id: impl Into<String>,
+    ) -> bool {
+        let id = id.into();
+        let Some(node) = self.inner.node_weight_mut(index) else {
+            return false;
+        };
+        let old = std::mem::replace(&mut node.id, id.clone());
+        self.unindex(&old, index);
+        self.index(id, index);
+        true
+    }
+
+    /// Rename a node, keeping its edges and every other field
+    ///
+    /// Use this when a symbol or its file moves mid-migration, so its tracked
+    /// status follows it instead of being orphaned under the old ID.
+    ///
+    /// # Errors
+    /// Returns [`GraphMigratorError::NodeNotFound`] if `old_id` is not in the
+    /// graph, or [`GraphMigratorError::InvalidGraph`] if `new_id` already
+    /// belongs to another node.
+    pub fn rename_node(
+        &mut self,
+        old_id: &str,
+        new_id: &str,
+    ) -> Result<petgraph::stable_graph::NodeIndex, GraphMigratorError> {
+        let index = self
+            .get_by_id(old_id)
+            .ok_or_else(|| GraphMigratorError::NodeNotFound(old_id.to_string()))?;
+        if self
+            .get_by_id(new_id)
+            .is_some_and(|existing| existing != index)
+        {
+            return Err(id_in_use(new_id));
+        }
+        self.set_node_id(index, new_id);
+        Ok(index)
+    }
+
+    /// Rename many nodes at once, mapping old IDs to new ones
+    ///
+    /// Renames apply simultaneously, so swaps and chains (`a → b`, `b → c`)
+    /// work. Old IDs not in the graph are skipped. When a rename moves a
+    /// node to another file, its `file_path` follows. Returns how many nodes
+    /// were renamed.
+    ///
+    /// # Errors
+    /// Returns [`GraphMigratorError::InvalidGraph`], leaving the graph
+    /// unchanged, if two nodes would end up with the same ID.
+    pub fn remap_ids(
+        &mut self,
+        map: &HashMap<String, String>,
+    ) -> Result<usize, GraphMigratorError> {
+        let renames: Vec<(petgraph::stable_graph::NodeIndex, &str)> = map
+            .iter()
+            .filter(|(old, new)| old != new)
+            .filter_map(|(old, new)| Some((self.get_by_id(old)?, new.as_str())))
+            .collect();
+
+        let renamed: HashSet<_> = renames.iter().map(|&(index, _)| index).collect();
+        let mut targets = HashSet::new();
+        for &(_, new_id) in &renames {
+            let taken = self
+                .get_by_id(new_id)
+                .is_some_and(|existing| !renamed.contains(&existing));
+            if taken || !targets.insert(new_id) {
+                return Err(id_in_use(new_id));
+            }
+        }
+
+        for &(index, new_id) in &renames {
+            let node = &mut self.inner[index];
+            if let Some(path) = moved_file_path(node, new_id) {
+                node.file_path = path;
+            }
+            node.id = new_id.to_string();
+        }
+        self.rebuild_id_index();
+        Ok(renames.len())
+    }
+
+    /// Look up a node index by ID in O(1)
+    pub fn get_by_id(&self, id: &str) -> Option<petgraph::stable_graph::NodeIndex> {
+        self.by_id.get(id).copied()
+    }
+
+    /// Rebuild the ID index from scratch
+    ///
+    /// Only needed after changing `Node::id` through [`Graph::node_weight_mut()`];
+    /// prefer [`Graph::set_node_id()`], which keeps the index current.
+    pub fn rebuild_id_index(&mut self) {
+        self.by_id.clear();
+        self.duplicates.clear();
+        let indices: Vec<_> = self.inner.node_indices().collect();
+        for index in indices {
+            self.index(self.inner[index].id.clone(), index);
+        }
+    }
+
+    /// Add `index` to the ID index, or count it as a duplicate if the ID is taken
+    fn index(&mut self, id: String, index: petgraph::stable_graph::NodeIndex) {
+        match self.by_id.entry(id) {
+            std::collections::hash_map::Entry::Occupied(entry) => {
+                *self.duplicates.entry(entry.key().clone()).or_insert(0) += 1;
+            }
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                entry.insert(index);
+            }
+        }
+    }
+
+    /// Drop `index` from the ID index, promoting another node with the same ID
+    ///
+    /// Only scans the graph when the ID is known to be duplicated, so renaming
+    /// or removing nodes with unique IDs stays O(1).
+    fn unindex(&mut self, id: &str, index: petgraph::stable_graph::NodeIndex) {
+        let Some(&indexed) = self.by_id.get(id) else {
+            return;
+        };
+        let duplicated = match self.duplicates.get_mut(id) {
+            Some(count) => {
+                *count -= 1;
+                if *count == 0 {
+                    self.duplicates.remove(id);
+                }
+                true
+            }
+            None => false,
+        };
+        if indexed != index {
+            return;
+        }
+        self.by_id.remove(id);
+        if !duplicated {
+            return;
+        }
+        if let Some(other) = self
+            .inner
+            .node_indices()
+            .find(|&i| i != index && self.inner[i].id == id)
+        {
+            self.by_id.insert(id.to_string(), other);
+        }
     }
 
     /// Add an edge between two nodes
+    ///
+    /// If an edge of the same type already connects them, the graph's
+    /// [`EdgePolicy`] decides whether that edge is returned unchanged,
+    /// returned with its count increased, or a parallel edge is added.
     pub fn add_edge(
         &mut self,
         from: petgraph::stable_graph::NodeIndex,
         to: petgraph::stable_graph::NodeIndex,
         edge: Edge,
     ) -> petgraph::stable_graph::EdgeIndex {
+        if self.edge_policy != EdgePolicy::Multi {
+            let existing = self
+                .inner
+                .edges_connecting(from, to)
+                .find(|e| e.weight().edge_type == edge.edge_type)
+                .map(|e| e.id());
+            if let Some(existing) = existing {
+                if self.edge_policy == EdgePolicy::Count {
+                    let weight = &mut self.inner[existing];
+                    weight.count = weight.count.saturating_add(edge.count);
+                }
+                return existing;
+            }
+        }
         self.inner.add_edge(from, to, edge)
     }
 
@@ -106,11 +855,61 @@ impl Graph {
         self.inner.node_weight(index)
     }
 
+    /// Get a mutable node by index
+    ///
+    /// Intended for enrichment passes (line ranges, scores). To change a
+    /// node's ID use [`Graph::set_node_id()`]; changing `node.id` here leaves
+    /// the ID index stale until [`Graph::rebuild_id_index()`] is called.
+    pub fn node_weight_mut(
+        &mut self,
+        index: petgraph::stable_graph::NodeIndex,
+    ) -> Option<&mut Node> {
+        self.inner.node_weight_mut(index)
+    }
+
     /// Get an edge by index
     pub fn edge_weight(&self, index: petgraph::stable_graph::EdgeIndex) -> Option<&Edge> {
         self.inner.edge_weight(index)
     }
 
+    /// Get a mutable edge by index
+    pub fn edge_weight_mut(
+        &mut self,
+        index: petgraph::stable_graph::EdgeIndex,
+    ) -> Option<&mut Edge> {
+        self.inner.edge_weight_mut(index)
+    }
+
+    /// Apply `update` to the node with the given ID
+    ///
+    /// Returns the closure's result, or `None` if no node has that ID.
+    ///
+    /// # Example
+    /// ```
+    /// use graph_migrator_core::{Graph, Node, NodeType};
+    ///
+    /// let mut graph = Graph::new();
+    /// graph.add_node(Node::new("m.py::f", "f", NodeType::Function, "python", "m.py"));
+    ///
+    /// let found = graph.update_node_by_id("m.py::f", |node| node.line_range = Some((3, 9)));
+    /// assert!(found.is_some());
+    /// ```
+    pub fn update_node_by_id<R>(
+        &mut self,
+        id: &str,
+        update: impl FnOnce(&mut Node) -> R,
+    ) -> Option<R> {
+        let index = self.get_by_id(id)?;
+        let node = self.inner.node_weight_mut(index)?;
+        let result = update(node);
+        if node.id != id {
+            let new_id = node.id.clone();
+            self.unindex(id, index);
+            self.index(new_id, index);
+        }
+        Some(result)
+    }
+
     /// Get the number of nodes in the graph
     pub fn node_count(&self) -> usize {
         self.inner.node_count()
@@ -131,6 +930,34 @@ impl Graph {
         self.inner.edge_weights()
     }
 
+    /// Every node, sorted by ID
+    ///
+    /// [`nodes()`](Self::nodes) follows petgraph's index order, which depends
+    /// on the order files were merged and on slots freed by removed nodes.
+    /// Exporters iterate in this order instead, so the same graph content
+    /// always produces byte-identical output.
+    pub fn nodes_sorted_by_id(&self) -> Vec<&Node> {
+        let mut nodes: Vec<&Node> = self.nodes().collect();
+        nodes.sort_by(|a, b| a.id.cmp(&b.id));
+        nodes
+    }
+
+    /// Every edge as `(from, to, edge)`, sorted by source ID, target ID and edge type
+    ///
+    /// The edge counterpart of [`nodes_sorted_by_id()`](Self::nodes_sorted_by_id).
+    pub fn edges_sorted(&self) -> Vec<(&Node, &Node, &Edge)> {
+        let mut edges: Vec<(&Node, &Node, &Edge)> = self
+            .edge_endpoints()
+            .filter_map(|(from, to, edge)| {
+                Some((self.node_weight(from)?, self.node_weight(to)?, edge))
+            })
+            .collect();
+        edges.sort_by(|a, b| {
+            (&a.0.id, &a.1.id, a.2.edge_type).cmp(&(&b.0.id, &b.1.id, b.2.edge_type))
+        });
+        edges
+    }
+
     /// Get edge endpoints for testing verification
     ///
     /// Returns an iterator of (from_node_index, to_node_index, edge_weight) tuples
@@ -150,6 +977,99 @@ impl Graph {
             .map(|e| (e.source(), e.target(), e.weight()))
     }
 
+    /// Nodes adjacent to `index` in `direction`
+    ///
+    /// [`Direction::Outgoing`] yields edge targets, [`Direction::Incoming`]
+    /// yields edge sources. A neighbor appears once per connecting edge.
+    pub fn neighbors(
+        &self,
+        index: petgraph::stable_graph::NodeIndex,
+        direction: Direction,
+    ) -> impl Iterator<Item = petgraph::stable_graph::NodeIndex> + '_ {
+        self.inner.neighbors_directed(index, direction)
+    }
+
+    /// Edges on `index`'s side `direction`, as `(other endpoint, weight)`
+    ///
+    /// Like [`Graph::neighbors()`], with the edge that leads to each neighbor.
+    pub fn edges_directed(
+        &self,
+        index: petgraph::stable_graph::NodeIndex,
+        direction: Direction,
+    ) -> impl Iterator<Item = (petgraph::stable_graph::NodeIndex, &Edge)> + '_ {
+        self.inner
+            .edges_directed(index, direction)
+            .map(move |e| match direction {
+                Direction::Outgoing => (e.target(), e.weight()),
+                Direction::Incoming => (e.source(), e.weight()),
+            })
+    }
+
+    /// Every edge touching `index`, outgoing first, as `(edge, from, to, weight)`
+    ///
+    /// Self-loops are reported once.
+    pub fn edges_of(
+        &self,
+        index: petgraph::stable_graph::NodeIndex,
+    ) -> impl Iterator<
+        Item = (
+            petgraph::stable_graph::EdgeIndex,
+            petgraph::stable_graph::NodeIndex,
+            petgraph::stable_graph::NodeIndex,
+            &Edge,
+        ),
+    > + '_ {
+        let outgoing = self.inner.edges_directed(index, Direction::Outgoing);
+        let incoming = self
+            .inner
+            .edges_directed(index, Direction::Incoming)
+            .filter(move |e| e.source() != index);
+        outgoing
+            .chain(incoming)
+            .map(|e| (e.id(), e.source(), e.target(), e.weight()))
+    }
+
+    /// Edges from `from` to `to` (one direction only), as `(edge, weight)`
+    pub fn edges_between(
+        &self,
+        from: petgraph::stable_graph::NodeIndex,
+        to: petgraph::stable_graph::NodeIndex,
+    ) -> impl Iterator<Item = (petgraph::stable_graph::EdgeIndex, &Edge)> + '_ {
+        self.inner
+            .edges_connecting(from, to)
+            .map(|e| (e.id(), e.weight()))
+    }
+
+    /// The underlying petgraph graph, for running petgraph algorithms
+    /// (dominators, min-cut, A*, ...) directly
+    ///
+    /// Node and edge indices are the ones this graph hands out. Access is
+    /// read-only so the ID index cannot go stale; modify through `Graph`.
+    ///
+    /// # Example
+    /// ```
+    /// use graph_migrator_core::graph::EdgeType;
+    /// use graph_migrator_core::petgraph::algo::dominators;
+    /// use graph_migrator_core::{Edge, Graph, Node, NodeType};
+    ///
+    /// let mut graph = Graph::new();
+    /// let mut add = |name: &str| {
+    ///     let id = format!("m.py::{}", name);
+    ///     graph.add_node(Node::new(id, name, NodeType::Function, "python", "m.py"))
+    /// };
+    /// let (main, load, parse, save) = (add("main"), add("load"), add("parse"), add("save"));
+    /// for (from, to) in [(main, load), (main, save), (load, parse), (save, parse)] {
+    ///     graph.add_edge(from, to, Edge::new(EdgeType::Calls));
+    /// }
+    ///
+    /// // Every path from main to parse goes through main only
+    /// let doms = dominators::simple_fast(graph.as_petgraph(), main);
+    /// assert_eq!(doms.immediate_dominator(parse), Some(main));
+    /// ```
+    pub fn as_petgraph(&self) -> &StableGraph<Node, Edge> {
+        &self.inner
+    }
+
     /// Get all node indices in the graph
     pub fn node_indices(&self) -> impl Iterator<Item = petgraph::stable_graph::NodeIndex> + '_ {
         self.inner.node_indices()
@@ -175,18 +1095,680 @@ impl Graph {
 
     /// Find a node by its ID
     ///
-    /// Returns the node index if found, None otherwise.
-    ///
-    /// **Note**: This performs a linear scan over all nodes and has O(N) complexity.
-    /// For performance-sensitive code, consider maintaining a separate ID-to-index map.
+    /// Returns the node index if found, None otherwise. Equivalent to
+    /// [`Graph::get_by_id()`]; both are O(1).
     pub fn find_node_by_id(&self, id: &str) -> Option<petgraph::stable_graph::NodeIndex> {
-        self.node_indices()
-            .find(|&idx| self.node_weight(idx).map(|n| n.id.as_str()) == Some(id))
+        self.get_by_id(id)
+    }
+
+    /// Stable SHA-256 fingerprint of the graph's content, as lowercase hex
+    ///
+    /// Covers every node (all fields, sorted by ID) and every edge (as
+    /// `(from ID, to ID, edge)`, sorted), so two graphs have the same
+    /// fingerprint exactly when they hold the same content, regardless of
+    /// insertion order or petgraph indices. A file path ending in the
+    /// root-relative path of the node's ID ([`Node::id_parts()`]) is hashed
+    /// as that path, and the [`metadata()`](Self::metadata) is left out, so
+    /// scans of the same code checked out in different directories agree.
+    /// The value is stable across runs and platforms, making it suitable for
+    /// CI change detection and cache keys.
+    pub fn fingerprint(&self) -> String {
+        use sha2::{Digest, Sha256};
+
+        let mut nodes: Vec<Vec<u8>> = self
+            .nodes()
+            .map(|node| {
+                let relative = node
+                    .id_parts()
+                    .map(|(file, _)| std::path::Path::new(file))
+                    .filter(|file| file.is_relative() && node.file_path.ends_with(file));
+                let node = match relative {
+                    Some(path) => std::borrow::Cow::Owned(Node {
+                        file_path: path.to_path_buf(),
+                        ..node.clone()
+                    }),
+                    None => std::borrow::Cow::Borrowed(node),
+                };
+                serde_json::to_vec(&node).expect("nodes serialize to JSON")
+            })
+            .collect();
+        nodes.sort();
+
+        let mut edges: Vec<Vec<u8>> = self
+            .edge_endpoints()
+            .filter_map(|(from, to, edge)| {
+                let from = &self.node_weight(from)?.id;
+                let to = &self.node_weight(to)?.id;
+                Some(serde_json::to_vec(&(from, to, edge)).expect("edges serialize to JSON"))
+            })
+            .collect();
+        edges.sort();
+
+        let mut hasher = Sha256::new();
+        for (section, records) in [(b"nodes", &nodes), (b"edges", &edges)] {
+            hasher.update(section);
+            hasher.update((records.len() as u64).to_le_bytes());
+            for record in records {
+                // Length-prefix each record so boundaries can't be shifted
+                hasher.update((record.len() as u64).to_le_bytes());
+                hasher.update(record);
+            }
+        }
+        hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+/// Estimated heap and inline memory held by a graph, in bytes
+///
+/// Figures are estimates from type sizes and string capacities; allocator
+/// overhead and petgraph's free lists are not counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct MemoryStats {
+    /// Live nodes
+    pub nodes: usize,
+    /// Live edges
+    pub edges: usize,
+    /// Node slots, including the inline part of every field
+    pub node_bytes: usize,
+    /// Edge slots
+    pub edge_bytes: usize,
+    /// Heap bytes of node strings, paths and vectors
+    pub string_bytes: usize,
+    /// Heap bytes of distinct strings, i.e. what interning would keep
+    pub unique_string_bytes: usize,
+    /// The ID → index map
+    pub index_bytes: usize,
+}
+
+impl MemoryStats {
+    /// Every counted byte
+    pub fn total(&self) -> usize {
+        self.node_bytes + self.edge_bytes + self.string_bytes + self.index_bytes
+    }
+}
+
+impl Graph {
+    /// Estimate how much memory the graph holds, and where
+    pub fn memory_stats(&self) -> MemoryStats {
+        use std::mem::size_of;
+        type Index = petgraph::stable_graph::DefaultIx;
+
+        // (contents, heap capacity) of every string-like field
+        let mut strings: Vec<(&[u8], usize)> = Vec::new();
+        let mut vector_bytes = 0;
+        for node in self.nodes() {
+            strings.push((node.id.as_bytes(), node.id.capacity()));
+            strings.push((node.name.as_bytes(), node.name.capacity()));
+            strings.push((node.language.as_bytes(), node.language.capacity()));
+            let path = node.file_path.as_os_str();
+            strings.push((path.as_encoded_bytes(), path.len()));
+            if let Some(namespace) = &node.namespace {
+                strings.push((namespace.as_bytes(), namespace.capacity()));
+            }
+            if let Some(package) = &node.package {
+                strings.push((package.name.as_bytes(), package.name.capacity()));
+                if let Some(version) = &package.version {
+                    strings.push((version.as_bytes(), version.capacity()));
+                }
+            }
+            if let Some(blame) = &node.blame {
+                for author in &blame.authors {
+                    strings.push((author.as_bytes(), author.capacity()));
+                }
+                vector_bytes += blame.authors.capacity() * size_of::<String>();
+            }
+            if let Some(issue) = &node.issue {
+                strings.push((issue.as_bytes(), issue.capacity()));
+            }
+            for owner in &node.owners {
+                strings.push((owner.as_bytes(), owner.capacity()));
+            }
+            vector_bytes += node.owners.capacity() * size_of::<String>();
+            vector_bytes += node.history.capacity() * size_of::<StatusChange>();
+        }
+        let string_bytes =
+            strings.iter().map(|(_, capacity)| capacity).sum::<usize>() + vector_bytes;
+        let unique: HashSet<&[u8]> = strings.iter().map(|(bytes, _)| *bytes).collect();
+        let unique_string_bytes = unique.iter().map(|bytes| bytes.len()).sum();
+
+        let ids: usize = self.by_id.keys().map(String::capacity).sum();
+        MemoryStats {
+            nodes: self.node_count(),
+            edges: self.edge_count(),
+            // Each slot also holds the heads of its outgoing and incoming edge lists
+            node_bytes: self.node_count() * (size_of::<Option<Node>>() + 2 * size_of::<Index>()),
+            // ...and each edge its endpoints and next-edge links
+            edge_bytes: self.edge_count() * (size_of::<Option<Edge>>() + 4 * size_of::<Index>()),
+            string_bytes,
+            unique_string_bytes,
+            index_bytes: ids
+                + self.by_id.capacity()
+                    * (size_of::<String>() + size_of::<petgraph::stable_graph::NodeIndex>() + 1),
+        }
     }
 }
 
+/// Where `node` lives once renamed to `new_id`, if the rename changes the
+/// file part of its ID
+///
+/// The file part of the old ID is swapped for the new one at the end of
+/// `node.file_path`, so absolute paths stay absolute when IDs are
+/// root-relative.
+fn moved_file_path(node: &Node, new_id: &str) -> Option<std::path::PathBuf> {
+    let namespace = node.namespace.as_deref();
+    let (old_file, _) = split_id(&node.id, namespace)?;
+    let (new_file, _) = split_id(new_id, namespace)?;
+    if old_file == new_file || !node.file_path.ends_with(old_file) {
+        return None;
+    }
+    let depth = std::path::Path::new(&old_file).components().count();
+    let base = node
+        .file_path
+        .ancestors()
+        .nth(depth)
+        .unwrap_or(std::path::Path::new(""));
+    Some(base.join(new_file))
+}
+
+fn id_in_use(id: &str) -> GraphMigratorError {
+    GraphMigratorError::InvalidGraph(format!("node ID already in use: {}", id))
+}
+
 impl Default for Graph {
     fn default() -> Self {
         Self::new()
     }
 }
+
+/// Serializable interchange form of a [`Graph`]
+///
+/// `Graph` wraps a petgraph `StableGraph` and can't derive serde traits, so
+/// tools that persist or exchange graphs go through this struct instead.
+/// Edges reference nodes by ID, never by petgraph index. The JSON shape is
+/// versioned and described by [`schema::GRAPH_SCHEMA`](crate::schema::GRAPH_SCHEMA);
+/// read documents with [`GraphData::from_json()`] so older versions are
+/// upgraded.
+///
+/// # Example
+/// ```
+/// use graph_migrator_core::{Graph, GraphData};
+///
+/// let graph = Graph::new();
+/// let json = serde_json::to_string(&GraphData::from(&graph)).unwrap();
+/// let data = GraphData::from_json(&json).unwrap();
+/// let restored = Graph::try_from(data).unwrap();
+/// assert_eq!(restored.node_count(), 0);
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphData {
+    /// Format version; documents written before versioning read as 0
+    #[serde(default)]
+    pub schema_version: u32,
+    /// How the graph was produced (see [`Graph::metadata()`])
+    #[serde(default, skip_serializing_if = "GraphMetadata::is_empty")]
+    pub metadata: GraphMetadata,
+    /// What adding a duplicate edge does in the graph (see [`Graph::edge_policy()`])
+    #[serde(default, skip_serializing_if = "EdgePolicy::is_default")]
+    pub edge_policy: EdgePolicy,
+    /// Every node, sorted by ID
+    pub nodes: Vec<Node>,
+    /// `(from ID, to ID, edge)` for every edge, sorted (see [`Graph::edges_sorted()`])
+    pub edges: Vec<(String, String, Edge)>,
+}
+
+impl From<&Graph> for GraphData {
+    fn from(graph: &Graph) -> Self {
+        let nodes = graph.nodes_sorted_by_id().into_iter().cloned().collect();
+        let edges = graph
+            .edges_sorted()
+            .into_iter()
+            .map(|(from, to, edge)| (from.id.clone(), to.id.clone(), edge.clone()))
+            .collect();
+        Self {
+            schema_version: SCHEMA_VERSION,
+            metadata: graph.metadata.clone(),
+            edge_policy: graph.edge_policy,
+            nodes,
+            edges,
+        }
+    }
+}
+
+impl Default for GraphData {
+    fn default() -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            metadata: GraphMetadata::default(),
+            edge_policy: EdgePolicy::default(),
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+}
+
+/// Provenance of a graph: what was scanned, when, how and by which build
+///
+/// Directory scans in [`resolve`](crate::resolve) fill it in, the graph
+/// carries it through edits, and [`GraphData`] stores it, so a saved graph
+/// can always be traced back to its source. Fields that were not known stay
+/// unset.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphMetadata {
+    /// Version of the library that scanned the project
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_version: Option<String>,
+    /// When the scan ran, in seconds since the Unix epoch; only recorded on
+    /// request (see [`resolve::record_provenance()`](crate::resolve::record_provenance()))
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scanned_at: Option<u64>,
+    /// Project root that was scanned; only recorded on request, like `scanned_at`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<std::path::PathBuf>,
+    /// Repository URL the project was cloned from, for remote scans
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_remote: Option<String>,
+    /// Commit checked out at the root, if it is a git working tree; only
+    /// recorded on request, like `scanned_at`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_commit: Option<String>,
+    /// Languages of the project's nodes, sorted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<String>,
+    /// Workspace roots of a monorepo scan, relative to `root` (see
+    /// [`resolve::workspace_members()`](crate::resolve::workspace_members()))
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub workspace_roots: Vec<String>,
+    /// [`Config::fingerprint()`](crate::config::Config::fingerprint()) of the
+    /// configuration the scan used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_hash: Option<String>,
+    /// SHA-256 of each project file's content, keyed by the file part of its
+    /// node IDs; recorded in state files to recognize moved files (see
+    /// `store::TrackedState::follow_moves()`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub file_hashes: BTreeMap<String, String>,
+}
+
+impl GraphMetadata {
+    /// Whether nothing is recorded
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl GraphData {
+    /// Parse a JSON document, upgrading it from older schema versions
+    ///
+    /// # Errors
+    /// Returns [`GraphMigratorError::InvalidGraph`] if the text is not a
+    /// `GraphData` document or was written by a newer, unsupported version.
+    pub fn from_json(text: &str) -> Result<Self, GraphMigratorError> {
+        let mut value: serde_json::Value = serde_json::from_str(text)
+            .map_err(|e| GraphMigratorError::InvalidGraph(e.to_string()))?;
+        schema::upgrade(&mut value)?;
+        serde_json::from_value(value).map_err(|e| GraphMigratorError::InvalidGraph(e.to_string()))
+    }
+}
+
+impl TryFrom<GraphData> for Graph {
+    type Error = GraphMigratorError;
+
+    /// Rebuild a graph, rejecting duplicate node IDs and edges to unknown IDs
+    ///
+    /// Edges are restored as stored, parallel ones included; the rebuilt
+    /// graph then uses the stored [`EdgePolicy`].
+    fn try_from(data: GraphData) -> Result<Self, Self::Error> {
+        let mut graph = Graph::new().with_edge_policy(EdgePolicy::Multi);
+        for node in data.nodes {
+            if graph.get_by_id(&node.id).is_some() {
+                return Err(GraphMigratorError::InvalidGraph(format!(
+                    "duplicate node ID: {}",
+                    node.id
+                )));
+            }
+            graph.add_node(node);
+        }
+
+        for (from, to, edge) in data.edges {
+            let endpoint = |id: &str| {
+                graph.get_by_id(id).ok_or_else(|| {
+                    GraphMigratorError::InvalidGraph(format!(
+                        "edge references unknown node: {}",
+                        id
+                    ))
+                })
+            };
+            let (from, to) = (endpoint(&from)?, endpoint(&to)?);
+            graph.add_edge(from, to, edge);
+        }
+
+        graph.set_edge_policy(data.edge_policy);
+        graph.metadata = data.metadata;
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn function(file: &str, name: &str) -> Node {
+        Node::new(
+            format!("{}::{}", file, name),
+            name,
+            NodeType::Function,
+            "python",
+            file,
+        )
+    }
+
+    fn calls() -> Edge {
+        Edge::new(EdgeType::Calls)
+    }
+
+    #[test]
+    fn test_mutation_apis() {
+        let mut graph = Graph::new();
+        let a = graph.add_node(function("m.py", "a"));
+        let b = graph.add_node(function("m.py", "b"));
+        let edge = graph.add_edge(a, b, calls());
+
+        let updated = graph.update_node_by_id("m.py::b", |node| {
+            node.line_range = Some((4, 7));
+            node.name.clone()
+        });
+        assert_eq!(updated.as_deref(), Some("b"));
+        assert_eq!(graph.node_weight(b).unwrap().line_range, Some((4, 7)));
+        assert!(graph.update_node_by_id("m.py::missing", |_| ()).is_none());
+
+        graph.edge_weight_mut(edge).unwrap().edge_type = EdgeType::Imports;
+        assert_eq!(
+            graph.edge_weight(edge).unwrap().edge_type,
+            EdgeType::Imports
+        );
+    }
+
+    #[test]
+    fn test_id_index_tracks_add_remove_and_rename() {
+        let mut graph = Graph::new();
+        let a = graph.add_node(function("m.py", "a"));
+        let b = graph.add_node(function("m.py", "b"));
+        graph.add_edge(a, b, calls());
+        assert_eq!(graph.get_by_id("m.py::b"), Some(b));
+
+        // Removing a node drops it (and its edges) from lookups
+        assert_eq!(graph.remove_node(b).unwrap().name, "b");
+        assert_eq!(graph.get_by_id("m.py::b"), None);
+        assert_eq!(graph.edge_count(), 0);
+        assert_eq!(graph.get_by_id("m.py::a"), Some(a));
+
+        assert!(graph.set_node_id(a, "n.py::a"));
+        assert_eq!(graph.get_by_id("m.py::a"), None);
+        assert_eq!(graph.find_node_by_id("n.py::a"), Some(a));
+
+        graph.update_node_by_id("n.py::a", |node| node.id = "o.py::a".to_string());
+        assert_eq!(graph.get_by_id("o.py::a"), Some(a));
+        assert_eq!(graph.get_by_id("n.py::a"), None);
+    }
+
+    #[test]
+    fn test_rename_and_remap_ids() {
+        let mut graph = Graph::new();
+        let a = graph.add_node(function("m.py", "a"));
+        let b = graph.add_node(function("m.py", "b"));
+        graph.add_edge(a, b, calls());
+
+        assert_eq!(graph.rename_node("m.py::a", "n.py::a").unwrap(), a);
+        assert_eq!(graph.get_by_id("n.py::a"), Some(a));
+        assert_eq!(graph.edges_between(a, b).count(), 1);
+        assert!(matches!(
+            graph.rename_node("m.py::gone", "x"),
+            Err(GraphMigratorError::NodeNotFound(_))
+        ));
+        assert!(graph.rename_node("n.py::a", "m.py::b").is_err());
+
+        // Swaps apply simultaneously; unknown old IDs are skipped
+        let swap = HashMap::from([
+            ("n.py::a".to_string(), "m.py::b".to_string()),
+            ("m.py::b".to_string(), "n.py::a".to_string()),
+            ("m.py::gone".to_string(), "m.py::c".to_string()),
+        ]);
+        assert_eq!(graph.remap_ids(&swap).unwrap(), 2);
+        assert_eq!(graph.get_by_id("m.py::b"), Some(a));
+        assert_eq!(graph.get_by_id("n.py::a"), Some(b));
+
+        // Collisions leave the graph untouched
+        let collide = HashMap::from([("m.py::b".to_string(), "n.py::a".to_string())]);
+        assert!(graph.remap_ids(&collide).is_err());
+        assert_eq!(graph.get_by_id("m.py::b"), Some(a));
+
+        // A node renamed into another file takes its file path along
+        let mut node = function("src/old.py", "f");
+        node.file_path = std::path::PathBuf::from("/work/app/src/old.py");
+        let moved = graph.add_node(node);
+        let map = HashMap::from([("src/old.py::f".to_string(), "lib/new.py::f".to_string())]);
+        assert_eq!(graph.remap_ids(&map).unwrap(), 1);
+        assert_eq!(
+            graph.node_weight(moved).unwrap().file_path,
+            std::path::Path::new("/work/app/lib/new.py")
+        );
+        assert_eq!(
+            graph.node_weight(a).unwrap().file_path,
+            std::path::Path::new("m.py")
+        );
+    }
+
+    #[test]
+    fn test_duplicate_ids_resolve_to_first_live_node() {
+        let mut graph = Graph::new();
+        let first = graph.add_node(function("m.py", "f"));
+        let second = graph.add_node(function("m.py", "f"));
+        assert_eq!(graph.get_by_id("m.py::f"), Some(first));
+
+        graph.remove_node(first);
+        assert_eq!(graph.get_by_id("m.py::f"), Some(second));
+
+        // Renaming the remaining duplicate away leaves the ID unindexed
+        let third = graph.add_node(function("m.py", "f"));
+        graph.set_node_id(third, "m.py::g");
+        assert_eq!(graph.get_by_id("m.py::f"), Some(second));
+        graph.set_node_id(second, "m.py::h");
+        assert_eq!(graph.get_by_id("m.py::f"), None);
+        assert_eq!(graph.get_by_id("m.py::g"), Some(third));
+    }
+
+    #[test]
+    fn test_neighbor_and_edge_accessors() {
+        let mut graph = Graph::new();
+        let a = graph.add_node(function("m.py", "a"));
+        let b = graph.add_node(function("m.py", "b"));
+        let c = graph.add_node(function("m.py", "c"));
+        let ab = graph.add_edge(a, b, calls());
+        graph.add_edge(a, b, Edge::new(EdgeType::Imports));
+        graph.add_edge(c, a, calls());
+        graph.add_edge(a, a, calls());
+
+        let mut out: Vec<_> = graph.neighbors(a, Direction::Outgoing).collect();
+        out.sort();
+        assert_eq!(out, vec![a, b, b]);
+        let incoming: Vec<_> = graph.neighbors(b, Direction::Incoming).collect();
+        assert_eq!(incoming, vec![a, a]);
+
+        // Two a->b edges, c->a, and the self-loop counted once
+        assert_eq!(graph.edges_of(a).count(), 4);
+        assert!(graph
+            .edges_of(a)
+            .any(|(_, from, to, _)| from == c && to == a));
+
+        let between: Vec<_> = graph.edges_between(a, b).map(|(e, _)| e).collect();
+        assert_eq!(between.len(), 2);
+        assert!(between.contains(&ab));
+        assert_eq!(graph.edges_between(b, a).count(), 0);
+    }
+
+    #[test]
+    fn test_graph_data_round_trip() {
+        let mut graph = Graph::new();
+        let a = graph.add_node(function("m.py", "a"));
+        let b = graph.add_node(function("m.py", "b"));
+        graph.add_edge(a, b, calls());
+
+        let json = serde_json::to_string(&GraphData::from(&graph)).unwrap();
+        let data: GraphData = serde_json::from_str(&json).unwrap();
+        assert_eq!(data.edges[0].0, "m.py::a");
+
+        let restored = Graph::try_from(data).unwrap();
+        assert_eq!(restored.fingerprint(), graph.fingerprint());
+        assert_eq!(restored.edge_policy(), EdgePolicy::Dedupe);
+
+        graph.set_edge_policy(EdgePolicy::Count);
+        let restored = Graph::try_from(GraphData::from(&graph)).unwrap();
+        assert_eq!(restored.edge_policy(), EdgePolicy::Count);
+    }
+
+    #[test]
+    fn test_float_fields_compare_by_bits() {
+        let mut node = function("m.py", "a");
+        node.coverage = Some(f64::NAN);
+        node.attributes
+            .insert("score".to_string(), AttrValue::Float(f64::NAN));
+        assert_eq!(node, node.clone());
+
+        let mut other = node.clone();
+        other.coverage = Some(0.0);
+        node.coverage = Some(-0.0);
+        assert_ne!(node, other);
+        assert_ne!(AttrValue::Float(1.0), AttrValue::Int(1));
+    }
+
+    #[test]
+    fn test_graph_data_rejects_invalid_input() {
+        let duplicate = GraphData {
+            nodes: vec![function("m.py", "a"), function("m.py", "a")],
+            ..GraphData::default()
+        };
+        assert!(matches!(
+            Graph::try_from(duplicate),
+            Err(GraphMigratorError::InvalidGraph(_))
+        ));
+
+        let dangling = GraphData {
+            nodes: vec![function("m.py", "a")],
+            edges: vec![("m.py::a".to_string(), "m.py::gone".to_string(), calls())],
+            ..GraphData::default()
+        };
+        let err = Graph::try_from(dangling).unwrap_err();
+        assert!(err.to_string().contains("m.py::gone"));
+    }
+
+    #[test]
+    fn test_memory_stats_counts_repeated_strings() {
+        let mut graph = Graph::new();
+        assert_eq!(graph.memory_stats().total(), 0);
+        let a = graph.add_node(function("m.py", "a"));
+        let b = graph.add_node(function("m.py", "b"));
+        graph.add_edge(a, b, calls());
+
+        let stats = graph.memory_stats();
+        assert_eq!((stats.nodes, stats.edges), (2, 1));
+        assert!(stats.node_bytes >= 2 * std::mem::size_of::<Node>());
+        // "m.py" and "python" are stored twice but only needed once
+        assert!(stats.string_bytes >= stats.unique_string_bytes + "m.py".len() + "python".len());
+        assert!(stats.index_bytes > 0);
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_insertion_order() {
+        let mut first = Graph::new();
+        let a = first.add_node(function("m.py", "a"));
+        let b = first.add_node(function("m.py", "b"));
+        first.add_edge(a, b, calls());
+
+        let mut second = Graph::new();
+        let b = second.add_node(function("m.py", "b"));
+        let a = second.add_node(function("m.py", "a"));
+        second.add_edge(a, b, calls());
+
+        assert_eq!(first.fingerprint(), second.fingerprint());
+        assert_eq!(first.fingerprint().len(), 64);
+    }
+
+    #[test]
+    fn test_exports_ignore_insertion_order() {
+        let build = |names: &[&str]| {
+            let mut graph = Graph::new();
+            // A removed node frees an index slot that later nodes reuse
+            let gone = graph.add_node(function("m.py", "gone"));
+            for name in names {
+                graph.add_node(function("m.py", name));
+            }
+            graph.remove_node(gone);
+            let idx = |name| graph.get_by_id(&format!("m.py::{}", name)).unwrap();
+            let (a, b, c) = (idx("a"), idx("b"), idx("c"));
+            for (from, to) in [(c, a), (a, c), (a, b)] {
+                graph.add_edge(from, to, calls());
+            }
+            graph
+        };
+        let first = build(&["a", "b", "c"]);
+        let second = build(&["c", "b", "a"]);
+
+        let ids: Vec<&str> = second
+            .nodes_sorted_by_id()
+            .iter()
+            .map(|n| n.id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["m.py::a", "m.py::b", "m.py::c"]);
+        let edges: Vec<(&str, &str)> = second
+            .edges_sorted()
+            .iter()
+            .map(|(from, to, _)| (from.name.as_str(), to.name.as_str()))
+            .collect();
+        assert_eq!(edges, vec![("a", "b"), ("a", "c"), ("c", "a")]);
+
+        let json = |graph: &Graph| serde_json::to_string(&GraphData::from(graph)).unwrap();
+        assert_eq!(json(&first), json(&second));
+    }
+
+    #[test]
+    fn test_fingerprint_detects_changes() {
+        let mut graph = Graph::new();
+        let a = graph.add_node(function("m.py", "a"));
+        let b = graph.add_node(function("m.py", "b"));
+        let empty = Graph::new().fingerprint();
+        let nodes_only = graph.fingerprint();
+        assert_ne!(empty, nodes_only);
+
+        // Edge direction matters
+        let mut reversed = graph.clone();
+        reversed.add_edge(b, a, calls());
+        graph.add_edge(a, b, calls());
+        assert_ne!(graph.fingerprint(), nodes_only);
+        assert_ne!(graph.fingerprint(), reversed.fingerprint());
+
+        // So do node attributes
+        let before = graph.fingerprint();
+        graph.node_weight_mut(a).unwrap().line_range = Some((1, 2));
+        assert_ne!(graph.fingerprint(), before);
+    }
+
+    #[test]
+    fn test_migration_status() {
+        assert_eq!("in-progress".parse(), Ok(MigrationStatus::InProgress));
+        assert_eq!("Superseded".parse(), Ok(MigrationStatus::Superseded));
+        assert!("done".parse::<MigrationStatus>().is_err());
+
+        // Pending is implied, so unmigrated graphs serialize as before
+        let mut node = function("a.py", "a");
+        assert!(serde_json::to_value(&node).unwrap().get("status").is_none());
+        node.status = MigrationStatus::Blocked;
+        let json = serde_json::to_value(&node).unwrap();
+        assert_eq!(json["status"], "Blocked");
+        let back: Node = serde_json::from_value(json).unwrap();
+        assert_eq!(back.status, MigrationStatus::Blocked);
+    }
+}
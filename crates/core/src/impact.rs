@@ -0,0 +1,252 @@
+//! Which parts of the graph a change touches
+//!
+//! Pull request checks care about the code a branch changes and everything
+//! that depends on it, not about problems elsewhere in the repository.
+//! [`changed_files()`] asks git which files differ from a base revision, and
+//! [`impact()`] maps a list of changed files to:
+//!
+//! - the symbols defined in those files,
+//! - every symbol that transitively depends on them, and
+//! - the migration units (`PartOfMigration` targets) any of them belong to.
+//!
+//! [`Impact::touches()`] then narrows [`rules::check()`](crate::rules::check())
+//! output to violations involving impacted symbols.
+//!
+//! The graph itself is still resolved in full: a changed file's callers
+//! live in unchanged files, so their edges are needed to find dependents.
+//! `migrator check` resolves it through the [parse cache](crate::cache), so
+//! unchanged files are read back from their cached pass-1 results and only
+//! the changed files are reparsed.
+
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::{EdgeType, Graph, NodeType};
+use crate::paths::PathResolver;
+use crate::queries::Adjacency;
+use crate::rules::Violation;
+use std::collections::{BTreeSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Symbols and migration units affected by a set of changed files
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Impact {
+    /// Project nodes defined in the changed files, sorted
+    pub changed: Vec<String>,
+    /// Nodes that transitively depend on a changed node, sorted
+    pub dependents: Vec<String>,
+    /// Migration units a changed or dependent node is part of, sorted
+    pub units: Vec<String>,
+}
+
+impl Impact {
+    /// Whether the violation is reported on, or involves, an impacted node
+    pub fn touches(&self, violation: &Violation) -> bool {
+        let impacted = |id: &String| {
+            self.changed.binary_search(id).is_ok() || self.dependents.binary_search(id).is_ok()
+        };
+        impacted(&violation.node) || violation.related.iter().any(impacted)
+    }
+}
+
+/// Files under `root` that differ between `base` and the working tree
+///
+/// Runs `git diff --name-only` in `root`, so both committed and uncommitted
+/// changes on the branch count; untracked files do not. Deleted files are
+/// included; they simply match no nodes. Paths are resolved with `paths`
+/// (see [`parse_file_list()`]).
+///
+/// # Errors
+/// Returns [`GraphMigratorError::Io`] if `git` cannot be run, and
+/// [`GraphMigratorError::Git`] if it fails (e.g. `base` is not a revision)
+/// or `base` starts with `-`, which git would take for an option.
+pub fn changed_files(root: &Path, base: &str, paths: PathResolver) -> Result<Vec<PathBuf>> {
+    if base.starts_with('-') {
+        return Err(GraphMigratorError::Git(format!(
+            "invalid revision {:?}: must not start with '-'",
+            base
+        )));
+    }
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args([
+            "diff",
+            "--name-only",
+            "--relative",
+            "--end-of-options",
+            base,
+            "--",
+        ])
+        .output()
+        .map_err(|e| GraphMigratorError::io("git", e))?;
+    if !output.status.success() {
+        return Err(GraphMigratorError::Git(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(parse_file_list(
+        root,
+        &String::from_utf8_lossy(&output.stdout),
+        paths,
+    ))
+}
+
+/// Paths listed one per line, relative to `root` unless absolute
+///
+/// Blank lines are ignored. Paths are resolved with `paths`, the resolver
+/// the graph was scanned with, so they compare equal to node file paths; a
+/// path that cannot be resolved (e.g. a deleted file under
+/// [`PathResolver::Canonical`]) is kept joined to the resolved root.
+pub fn parse_file_list(root: &Path, list: &str, paths: PathResolver) -> Vec<PathBuf> {
+    let root = paths.resolve(root).unwrap_or_else(|_| root.to_path_buf());
+    list.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let path = root.join(line);
+            paths.resolve(&path).unwrap_or(path)
+        })
+        .collect()
+}
+
+/// Nodes in `files`, their transitive dependents, and the units they belong to
+pub fn impact(graph: &Graph, files: &[PathBuf]) -> Impact {
+    let files: BTreeSet<&Path> = files.iter().map(PathBuf::as_path).collect();
+    let changed: BTreeSet<String> = graph
+        .nodes()
+        .filter(|node| {
+            !node.external
+                && node.node_type != NodeType::MigrationUnit
+                && files.contains(node.file_path.as_path())
+        })
+        .map(|node| node.id.clone())
+        .collect();
+
+    // One reverse traversal from every changed node at once
+    let incoming = Adjacency::dependencies(graph).reversed();
+    let mut seen = vec![false; incoming.len()];
+    let mut queue: VecDeque<usize> = changed
+        .iter()
+        .filter_map(|id| incoming.position_of(graph, id))
+        .collect();
+    for &start in &queue {
+        seen[start] = true;
+    }
+    let mut dependents = BTreeSet::new();
+    while let Some(current) = queue.pop_front() {
+        for &(next, _) in &incoming.outgoing[current] {
+            if !seen[next] {
+                seen[next] = true;
+                dependents.insert(incoming.id(graph, next).to_string());
+                queue.push_back(next);
+            }
+        }
+    }
+
+    let mut units = BTreeSet::new();
+    for id in changed.iter().chain(&dependents) {
+        let Some(idx) = graph.get_by_id(id) else {
+            continue;
+        };
+        for (_, from, to, edge) in graph.edges_of(idx) {
+            if from == idx && edge.edge_type == EdgeType::PartOfMigration {
+                units.extend(graph.node_weight(to).map(|unit| unit.id.clone()));
+            }
+        }
+    }
+
+    Impact {
+        changed: changed.into_iter().collect(),
+        dependents: dependents.into_iter().collect(),
+        units: units.into_iter().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Edge, Node};
+    use crate::rules::Rule;
+
+    #[test]
+    fn test_impact_follows_dependents_and_units() {
+        let mut graph = Graph::new();
+        let mut add = |id: &str, file: &str, node_type| {
+            graph.add_node(Node::new(id, id, node_type, "python", file))
+        };
+        let db = add("/p/db.py::save", "/p/db.py", NodeType::Function);
+        let api = add("/p/api.py::handler", "/p/api.py", NodeType::Function);
+        add("/p/other.py::unrelated", "/p/other.py", NodeType::Function);
+        let unit = add("unit::storage", "/p/db.py", NodeType::MigrationUnit);
+        for (from, to, edge_type) in [
+            (api, db, EdgeType::Calls),
+            (api, unit, EdgeType::PartOfMigration),
+        ] {
+            graph.add_edge(from, to, Edge::new(edge_type));
+        }
+
+        let impact = impact(&graph, &[PathBuf::from("/p/db.py")]);
+        assert_eq!(impact.changed, vec!["/p/db.py::save"]);
+        assert_eq!(impact.dependents, vec!["/p/api.py::handler"]);
+        assert_eq!(impact.units, vec!["unit::storage"]);
+
+        let violation = |node: &str| Violation {
+            rule: Rule::UnmigratedHotPath,
+            node: node.to_string(),
+            file_path: PathBuf::new(),
+            line: None,
+            related: Vec::new(),
+            message: String::new(),
+        };
+        assert!(impact.touches(&violation("/p/api.py::handler")));
+        assert!(!impact.touches(&violation("/p/other.py::unrelated")));
+    }
+
+    #[test]
+    fn test_parse_file_list() {
+        let files = parse_file_list(
+            Path::new("/nonexistent"),
+            "a.py\n\n  b/c.py \n/abs.py\n",
+            PathResolver::Canonical,
+        );
+        assert_eq!(
+            files,
+            vec![
+                PathBuf::from("/nonexistent/a.py"),
+                PathBuf::from("/nonexistent/b/c.py"),
+                PathBuf::from("/abs.py"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_changed_files_rejects_option_like_base() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let target = temp_dir.path().join("pwned");
+        let base = format!("--output={}", target.display());
+        let err = changed_files(temp_dir.path(), &base, PathResolver::Relative).unwrap_err();
+        assert!(matches!(err, GraphMigratorError::Git(_)));
+        assert!(!target.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_changed_files_match_under_symlinked_root() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let real = temp_dir.path().join("real");
+        std::fs::create_dir(&real).unwrap();
+        std::fs::write(real.join("util.py"), "def helper():\n    pass\n").unwrap();
+        std::fs::write(
+            real.join("app.py"),
+            "from util import helper\n\ndef main():\n    helper()\n",
+        )
+        .unwrap();
+        let link = temp_dir.path().join("link");
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        let graph = crate::parser::parse_directory(&link).unwrap().graph;
+        let files = parse_file_list(&link, "util.py\n", PathResolver::Relative);
+        let impact = impact(&graph, &files);
+        assert_eq!(impact.changed, vec!["util.py::helper"]);
+    }
+}
@@ -16,16 +16,16 @@
 //! use std::path::Path;
 //! use graph_migrator_core::import::{self, ImportStatement};
 //!
-//! # fn main() -> Result<(), anyhow::Error> {
-//! let imports = import::extract_imports(Path::new("test/fixtures/imports/basic.py"))?;
+//! # fn main() -> graph_migrator_core::Result<()> {
+//! let imports = import::extract_imports(Path::new("tests/test-fixtures/import-project/main.py"))?;
 //!
 //! for import in &imports {
 //!     match import {
-//!         ImportStatement::Import { items, range } => {
+//!         ImportStatement::Import { items, range, .. } => {
 //!             println!("Line {}: import {}", range.start_line,
 //!                 items.iter().map(|m| m.name.clone()).collect::<Vec<_>>().join(", "));
 //!         }
-//!         ImportStatement::ImportFrom { module, level, names, range } => {
+//!         ImportStatement::ImportFrom { module, level, names, range, .. } => {
 //!             let dots = ".".repeat(*level as usize);
 //!             println!("Line {}: from {}{} import {}",
 //!                 range.start_line, dots,
@@ -42,7 +42,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
-use crate::parser::MultiFileGraph;
+use crate::error::Result;
+use crate::parser::{Language, MultiFileGraph, ParseSession};
+use crate::resolve::UnresolvedReport;
 
 /// Map of file paths to their import statements.
 ///
@@ -69,6 +71,22 @@ pub struct FirstPassOutput {
     /// Epic 7 uses this to resolve cross-file dependencies by correlating
     /// import data with node provenance from `graph.node_locations`.
     pub imports: ImportMap,
+
+    /// Calls and imports pass 2 could not resolve.
+    ///
+    /// Empty until [`crate::resolve::bind_calls()`] runs.
+    pub unresolved: UnresolvedReport,
+}
+
+impl FirstPassOutput {
+    /// Combine a parsed graph with its imports; nothing is resolved yet.
+    pub fn new(graph: MultiFileGraph, imports: ImportMap) -> Self {
+        Self {
+            graph,
+            imports,
+            unresolved: UnresolvedReport::default(),
+        }
+    }
 }
 
 /// A single import statement from a Python file.
@@ -85,6 +103,9 @@ pub enum ImportStatement {
         items: Vec<ImportedModule>,
         /// Statement-level source location (MVP: per-item ranges deferred).
         range: SourceRange,
+        /// Where in the file the statement runs.
+        #[serde(default)]
+        scope: ImportScope,
     },
 
     /// `from module import name [as alias]`
@@ -100,9 +121,50 @@ pub enum ImportStatement {
         names: Vec<ImportedName>,
         /// Statement-level source location (MVP: per-item ranges deferred).
         range: SourceRange,
+        /// Where in the file the statement runs.
+        #[serde(default)]
+        scope: ImportScope,
     },
 }
 
+impl ImportStatement {
+    /// Where in the file the statement runs.
+    pub fn scope(&self) -> ImportScope {
+        match self {
+            ImportStatement::Import { scope, .. } | ImportStatement::ImportFrom { scope, .. } => {
+                *scope
+            }
+        }
+    }
+
+    /// Where the statement is in its file.
+    pub fn range(&self) -> &SourceRange {
+        match self {
+            ImportStatement::Import { range, .. } | ImportStatement::ImportFrom { range, .. } => {
+                range
+            }
+        }
+    }
+}
+
+/// Where an import statement sits, and so when it runs.
+///
+/// Lazy imports are common: inside functions to break cycles or defer heavy
+/// modules, under `if TYPE_CHECKING:` for annotations, and in `try`/`except`
+/// blocks for optional dependencies. Resolution treats all of them as
+/// dependencies of the file; the scope tells them apart.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ImportScope {
+    /// Directly in the module body; runs on import.
+    #[default]
+    Module,
+    /// In a function or method body; runs when it is called.
+    Function,
+    /// In a block outside any function (`if`, `try`/`except`, `with`, a
+    /// class body, ...); may or may not run on import.
+    Conditional,
+}
+
 /// A single module imported via `import` statement.
 ///
 /// Represents one item in `import x, y, z` syntax.
@@ -176,20 +238,293 @@ pub struct SourceRange {
 /// use std::path::Path;
 /// use graph_migrator_core::import;
 ///
-/// # fn main() -> Result<(), anyhow::Error> {
-/// let imports = import::extract_imports(Path::new("test/fixtures/imports/complex.py"))?;
+/// # fn main() -> graph_migrator_core::Result<()> {
+/// let imports = import::extract_imports(Path::new("tests/test-fixtures/import-project/pkg/tools.py"))?;
 /// println!("Found {} import statements", imports.len());
 /// # Ok(())
 /// # }
 /// ```
-pub fn extract_imports(_path: &Path) -> anyhow::Result<Vec<ImportStatement>> {
-    todo!("Tree-sitter parsing implementation pending")
+pub fn extract_imports(path: &Path) -> Result<Vec<ImportStatement>> {
+    extract_imports_in(&mut ParseSession::uncached(), path)
+}
+
+/// [`extract_imports()`] on in-memory Python source
+///
+/// # Errors
+/// Returns an error if tree-sitter fails to parse the source at all.
+///
+/// # Example
+/// ```
+/// use graph_migrator_core::import;
+///
+/// let imports = import::extract_imports_from_source("import os\nfrom . import models\n").unwrap();
+/// assert_eq!(imports.len(), 2);
+/// ```
+pub fn extract_imports_from_source(source: &str) -> Result<Vec<ImportStatement>> {
+    let path = Path::new("<source>.py");
+    let mut session = ParseSession::uncached();
+    session.add_source(path, source)?;
+    extract_imports_in(&mut session, path)
+}
+
+/// [`extract_imports()`] reusing the parser and cached tree of `session`
+///
+/// After symbol extraction with the same session, this neither reads nor
+/// parses the file again. Files the session parses in a language other
+/// than Python have no imports.
+pub fn extract_imports_in(session: &mut ParseSession, path: &Path) -> Result<Vec<ImportStatement>> {
+    let file = session.parse(path)?;
+    if file.language != Language::Python {
+        return Ok(Vec::new());
+    }
+    Ok(extract_imports_from_tree(
+        &file.tree.root_node(),
+        file.source.as_bytes(),
+    ))
+}
+
+/// Statement kinds that can contain import statements
+const IMPORT_CONTAINERS: &[&str] = &[
+    "block",
+    "if_statement",
+    "elif_clause",
+    "else_clause",
+    "try_statement",
+    "except_clause",
+    "except_group_clause",
+    "finally_clause",
+    "with_statement",
+    "for_statement",
+    "while_statement",
+    "match_statement",
+    "case_clause",
+    "class_definition",
+    "function_definition",
+    "decorated_definition",
+];
+
+/// Extract every import statement from an already-parsed tree, in source order
+///
+/// Imports nested in functions and blocks are included, tagged with their
+/// [`ImportScope`].
+pub(crate) fn extract_imports_from_tree(
+    root_node: &tree_sitter::Node,
+    source: &[u8],
+) -> Vec<ImportStatement> {
+    let mut imports = Vec::new();
+    collect_imports(root_node, source, ImportScope::Module, &mut imports);
+    imports
+}
+
+fn collect_imports(
+    parent: &tree_sitter::Node,
+    source: &[u8],
+    scope: ImportScope,
+    imports: &mut Vec<ImportStatement>,
+) {
+    let mut cursor = parent.walk();
+    for node in parent.named_children(&mut cursor) {
+        match node.kind() {
+            "import_statement" => imports.push(ImportStatement::Import {
+                items: imported_modules(&node, source),
+                range: source_range(&node),
+                scope,
+            }),
+            "import_from_statement" => imports.extend(import_from(&node, source, scope)),
+            "function_definition" => collect_imports(&node, source, ImportScope::Function, imports),
+            kind if IMPORT_CONTAINERS.contains(&kind) => {
+                let nested = match scope {
+                    ImportScope::Module => ImportScope::Conditional,
+                    scope => scope,
+                };
+                collect_imports(&node, source, nested, imports);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Names listed in the module's `__all__`, if it declares one
+///
+/// Understands module-level `__all__ = [...]` (or a tuple), `__all__ += [...]`,
+/// `__all__.extend([...])` and `__all__.append("name")`. Entries that are not
+/// string literals are skipped.
+pub(crate) fn extract_exports_from_tree(
+    root_node: &tree_sitter::Node,
+    source: &[u8],
+) -> Option<Vec<String>> {
+    let mut exports: Option<Vec<String>> = None;
+    let mut cursor = root_node.walk();
+    for statement in root_node.named_children(&mut cursor) {
+        if statement.kind() != "expression_statement" {
+            continue;
+        }
+        let Some(expression) = statement.named_child(0) else {
+            continue;
+        };
+        let (replace, values) = match expression.kind() {
+            "assignment" | "augmented_assignment" => {
+                let target = expression.child_by_field_name("left");
+                if target.and_then(|t| text(&t, source)).as_deref() != Some("__all__") {
+                    continue;
+                }
+                let Some(value) = expression.child_by_field_name("right") else {
+                    continue;
+                };
+                (expression.kind() == "assignment", value)
+            }
+            "call" => {
+                let function = expression.child_by_field_name("function");
+                let method = function.and_then(|f| text(&f, source));
+                if !matches!(method.as_deref(), Some("__all__.extend" | "__all__.append")) {
+                    continue;
+                }
+                let Some(arguments) = expression.child_by_field_name("arguments") else {
+                    continue;
+                };
+                (false, arguments)
+            }
+            _ => continue,
+        };
+
+        let names = exports.get_or_insert_with(Vec::new);
+        if replace {
+            names.clear();
+        }
+        names.extend(string_literals(&values, source));
+    }
+    exports
+}
+
+/// String literals in a list, tuple or argument list (or the node itself)
+fn string_literals(node: &tree_sitter::Node, source: &[u8]) -> Vec<String> {
+    let strings: Vec<tree_sitter::Node> = if node.kind() == "string" {
+        vec![*node]
+    } else {
+        let mut cursor = node.walk();
+        node.named_children(&mut cursor)
+            .flat_map(|child| match child.kind() {
+                "list" | "tuple" => {
+                    let mut inner = child.walk();
+                    child.named_children(&mut inner).collect::<Vec<_>>()
+                }
+                _ => vec![child],
+            })
+            .filter(|child| child.kind() == "string")
+            .collect()
+    };
+    strings
+        .iter()
+        .filter_map(|string| {
+            let mut cursor = string.walk();
+            let content = string
+                .named_children(&mut cursor)
+                .find(|part| part.kind() == "string_content");
+            content.and_then(|content| text(&content, source))
+        })
+        .collect()
+}
+
+/// `import a.b, c as d` → one item per name
+fn imported_modules(node: &tree_sitter::Node, source: &[u8]) -> Vec<ImportedModule> {
+    let mut cursor = node.walk();
+    node.children_by_field_name("name", &mut cursor)
+        .filter_map(|name| {
+            let (name, alias) = name_and_alias(&name, source)?;
+            Some(ImportedModule { name, alias })
+        })
+        .collect()
+}
+
+/// `from ..pkg import a, b as c` / `from x import *`
+fn import_from(
+    node: &tree_sitter::Node,
+    source: &[u8],
+    scope: ImportScope,
+) -> Option<ImportStatement> {
+    let module_node = node.child_by_field_name("module_name")?;
+    let (module, level) = if module_node.kind() == "relative_import" {
+        let mut level = 0;
+        let mut module = None;
+        let mut cursor = module_node.walk();
+        for child in module_node.children(&mut cursor) {
+            match child.kind() {
+                "import_prefix" => level = text(&child, source)?.matches('.').count() as u8,
+                "dotted_name" => module = text(&child, source),
+                _ => {}
+            }
+        }
+        (module, level)
+    } else {
+        (text(&module_node, source), 0)
+    };
+
+    let mut names: Vec<ImportedName> = {
+        let mut cursor = node.walk();
+        node.children_by_field_name("name", &mut cursor)
+            .filter_map(|name| {
+                let (name, alias) = name_and_alias(&name, source)?;
+                Some(ImportedName {
+                    name,
+                    alias,
+                    is_star: false,
+                })
+            })
+            .collect()
+    };
+    let mut cursor = node.walk();
+    if node
+        .children(&mut cursor)
+        .any(|child| child.kind() == "wildcard_import")
+    {
+        names.push(ImportedName {
+            name: "*".to_string(),
+            alias: None,
+            is_star: true,
+        });
+    }
+
+    Some(ImportStatement::ImportFrom {
+        module,
+        level,
+        names,
+        range: source_range(node),
+        scope,
+    })
+}
+
+/// Split a `dotted_name` or `aliased_import` node into name and optional alias
+fn name_and_alias(node: &tree_sitter::Node, source: &[u8]) -> Option<(String, Option<String>)> {
+    if node.kind() == "aliased_import" {
+        let name = text(&node.child_by_field_name("name")?, source)?;
+        let alias = node
+            .child_by_field_name("alias")
+            .and_then(|alias| text(&alias, source));
+        Some((name, alias))
+    } else {
+        Some((text(node, source)?, None))
+    }
+}
+
+fn text(node: &tree_sitter::Node, source: &[u8]) -> Option<String> {
+    node.utf8_text(source).ok().map(|s| s.to_string())
+}
+
+fn source_range(node: &tree_sitter::Node) -> SourceRange {
+    SourceRange {
+        start_byte: node.start_byte(),
+        end_byte: node.end_byte(),
+        start_line: node.start_position().row + 1,
+        end_line: node.end_position().row + 1,
+    }
 }
 
 /// Parse all Python files in a directory and extract both graph and imports.
 ///
 /// This convenience function combines Epic 5's `parse_directory()` with
 /// Epic 6's `extract_imports()` to produce a unified `FirstPassOutput`.
+/// Symbols and imports come from a single parse of each file (see
+/// [`python::parse_file_full()`](crate::parser::python::parse_file_full())).
 ///
 /// # Arguments
 ///
@@ -205,8 +540,8 @@ pub fn extract_imports(_path: &Path) -> anyhow::Result<Vec<ImportStatement>> {
 /// use std::path::Path;
 /// use graph_migrator_core::import;
 ///
-/// # fn main() -> Result<(), anyhow::Error> {
-/// let output = import::parse_directory_with_imports(Path::new("my_project"))?;
+/// # fn main() -> graph_migrator_core::Result<()> {
+/// let output = import::parse_directory_with_imports(Path::new("tests/test-fixtures/import-project"))?;
 ///
 /// println!("Parsed {} nodes from {} files",
 ///     output.graph.graph.node_count(),
@@ -217,33 +552,220 @@ pub fn extract_imports(_path: &Path) -> anyhow::Result<Vec<ImportStatement>> {
 /// # Ok(())
 /// # }
 /// ```
-pub fn parse_directory_with_imports(root: &Path) -> anyhow::Result<FirstPassOutput> {
+#[cfg(feature = "std-fs")]
+pub fn parse_directory_with_imports(root: &Path) -> Result<FirstPassOutput> {
     use crate::parser;
 
-    let graph = parser::parse_directory(root)?;
-
+    let config = crate::config::Config::from_project_root(root)?;
     let mut imports = ImportMap::new();
-    for file_path in &graph.file_nodes {
-        let file_imports = extract_imports(file_path)?;
-        imports.insert(file_path.clone(), file_imports);
-    }
-
-    Ok(FirstPassOutput { graph, imports })
+    let graph = parser::parse_directory_with_config_in(
+        &mut ParseSession::uncached(),
+        root,
+        &config,
+        Some(&mut imports),
+    )?;
+
+    Ok(FirstPassOutput::new(graph, imports))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::GraphMigratorError;
     use std::fs;
     use tempfile::TempDir;
 
-    #[allow(dead_code)]
     fn create_test_file(dir: &TempDir, name: &str, content: &str) -> PathBuf {
         let path = dir.path().join(name);
         fs::write(&path, content).unwrap();
         path
     }
 
+    fn module_names(statement: &ImportStatement) -> Vec<(String, Option<String>)> {
+        match statement {
+            ImportStatement::Import { items, .. } => items
+                .iter()
+                .map(|m| (m.name.clone(), m.alias.clone()))
+                .collect(),
+            ImportStatement::ImportFrom { names, .. } => names
+                .iter()
+                .map(|n| (n.name.clone(), n.alias.clone()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_extract_plain_imports() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = create_test_file(
+            &temp_dir,
+            "plain.py",
+            "import os, sys as system\nimport os.path\n\ndef f():\n    import json\n",
+        );
+
+        let imports = extract_imports(&path).unwrap();
+
+        assert_eq!(imports.len(), 3);
+        assert_eq!(imports[2].scope(), ImportScope::Function);
+        assert_eq!(
+            module_names(&imports[0]),
+            vec![
+                ("os".to_string(), None),
+                ("sys".to_string(), Some("system".to_string()))
+            ]
+        );
+        assert_eq!(
+            module_names(&imports[1]),
+            vec![("os.path".to_string(), None)]
+        );
+        match &imports[1] {
+            ImportStatement::Import { range, .. } => assert_eq!(range.start_line, 2),
+            _ => panic!("Expected Import variant"),
+        }
+    }
+
+    #[test]
+    fn test_extract_from_imports() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = create_test_file(
+            &temp_dir,
+            "from.py",
+            concat!(
+                "from os import path as p, sep\n",
+                "from . import helper\n",
+                "from ..pkg.mod import (\n    a,\n    b as c,\n)\n",
+                "from typing import *\n",
+            ),
+        );
+
+        let imports = extract_imports(&path).unwrap();
+        assert_eq!(imports.len(), 4);
+
+        let expected = [
+            (Some("os"), 0),
+            (None, 1),
+            (Some("pkg.mod"), 2),
+            (Some("typing"), 0),
+        ];
+        for (statement, (expected_module, expected_level)) in imports.iter().zip(expected) {
+            match statement {
+                ImportStatement::ImportFrom { module, level, .. } => {
+                    assert_eq!(module.as_deref(), expected_module);
+                    assert_eq!(*level, expected_level);
+                }
+                _ => panic!("Expected ImportFrom variant"),
+            }
+        }
+
+        assert_eq!(
+            module_names(&imports[0]),
+            vec![
+                ("path".to_string(), Some("p".to_string())),
+                ("sep".to_string(), None)
+            ]
+        );
+        assert_eq!(
+            module_names(&imports[2]),
+            vec![
+                ("a".to_string(), None),
+                ("b".to_string(), Some("c".to_string()))
+            ]
+        );
+        match &imports[2] {
+            ImportStatement::ImportFrom { range, .. } => {
+                assert_eq!((range.start_line, range.end_line), (3, 6))
+            }
+            _ => panic!("Expected ImportFrom variant"),
+        }
+        match &imports[3] {
+            ImportStatement::ImportFrom { names, .. } => {
+                assert_eq!(names.len(), 1);
+                assert!(names[0].is_star);
+            }
+            _ => panic!("Expected ImportFrom variant"),
+        }
+    }
+
+    #[test]
+    fn test_extract_nested_imports_with_scope() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = create_test_file(
+            &temp_dir,
+            "nested.py",
+            concat!(
+                "from typing import TYPE_CHECKING\n",
+                "if TYPE_CHECKING:\n    from models import User\n",
+                "try:\n    import ujson as json\nexcept ImportError:\n    import json\n",
+                "class Repo:\n    def load(self):\n        if True:\n            import db\n",
+                "@cache\ndef settings():\n    from . import config\n",
+            ),
+        );
+
+        let imports = extract_imports(&path).unwrap();
+        let scopes: Vec<(usize, ImportScope)> = imports
+            .iter()
+            .map(|statement| match statement {
+                ImportStatement::Import { range, scope, .. }
+                | ImportStatement::ImportFrom { range, scope, .. } => (range.start_line, *scope),
+            })
+            .collect();
+        assert_eq!(
+            scopes,
+            vec![
+                (1, ImportScope::Module),
+                (3, ImportScope::Conditional),
+                (5, ImportScope::Conditional),
+                (7, ImportScope::Conditional),
+                (11, ImportScope::Function),
+                (14, ImportScope::Function),
+            ]
+        );
+
+        // Documents written before scopes existed load as module-level
+        let mut json = serde_json::to_value(&imports[1]).unwrap();
+        json["ImportFrom"].as_object_mut().unwrap().remove("scope");
+        let legacy: ImportStatement = serde_json::from_value(json).unwrap();
+        assert_eq!(legacy.scope(), ImportScope::Module);
+    }
+
+    #[test]
+    fn test_extract_exports() {
+        let parse = |source: &str| {
+            let mut parser = tree_sitter::Parser::new();
+            parser
+                .set_language(&tree_sitter_python::LANGUAGE.into())
+                .unwrap();
+            let tree = parser.parse(source, None).unwrap();
+            extract_exports_from_tree(&tree.root_node(), source.as_bytes())
+        };
+
+        assert_eq!(parse("from .a import f\n"), None);
+        assert_eq!(
+            parse(concat!(
+                "__all__ = ['stale']\n",
+                "__all__ = ('f', \"g\", name)\n",
+                "__all__ += ['h']\n",
+                "__all__.extend(['i', 'j'])\n",
+                "__all__.append('k')\n",
+                "def f():\n    __all__ = ['local']\n",
+            )),
+            Some(
+                vec!["f", "g", "h", "i", "j", "k"]
+                    .into_iter()
+                    .map(String::from)
+                    .collect()
+            )
+        );
+    }
+
+    #[test]
+    fn test_extract_imports_missing_file() {
+        assert!(matches!(
+            extract_imports(Path::new("does/not/exist.py")),
+            Err(GraphMigratorError::Io { .. })
+        ));
+    }
+
     #[test]
     fn test_import_statement_structure() {
         let stmt = ImportStatement::Import {
@@ -263,10 +785,11 @@ mod tests {
                 start_line: 1,
                 end_line: 1,
             },
+            scope: ImportScope::Module,
         };
 
         match stmt {
-            ImportStatement::Import { items, range } => {
+            ImportStatement::Import { items, range, .. } => {
                 assert_eq!(items.len(), 2);
                 assert_eq!(items[0].name, "os");
                 assert_eq!(items[1].alias, Some("system".to_string()));
@@ -292,6 +815,7 @@ mod tests {
                 start_line: 1,
                 end_line: 1,
             },
+            scope: ImportScope::Module,
         };
 
         match stmt {
@@ -326,6 +850,7 @@ mod tests {
                 start_line: 1,
                 end_line: 1,
             },
+            scope: ImportScope::Module,
         };
 
         match stmt {
@@ -359,6 +884,7 @@ mod tests {
                 start_line: 1,
                 end_line: 1,
             },
+            scope: ImportScope::Module,
         };
 
         match stmt {
@@ -387,13 +913,11 @@ mod tests {
 
     #[test]
     fn test_first_pass_output_structure() {
-        let output = FirstPassOutput {
-            graph: MultiFileGraph::default(),
-            imports: ImportMap::new(),
-        };
+        let output = FirstPassOutput::new(MultiFileGraph::default(), ImportMap::new());
 
         assert_eq!(output.graph.graph.node_count(), 0);
         assert!(output.imports.is_empty());
+        assert!(output.unresolved.is_empty());
     }
 
     #[test]
@@ -412,6 +936,7 @@ mod tests {
                 start_line: 1,
                 end_line: 1,
             },
+            scope: ImportScope::Module,
         }];
 
         map.insert(path.clone(), imports.clone());
@@ -433,6 +958,7 @@ mod tests {
                 start_line: 1,
                 end_line: 1,
             },
+            scope: ImportScope::Module,
         };
 
         let serialized = serde_json::to_string(&stmt).unwrap();
@@ -457,6 +983,7 @@ mod tests {
                 start_line: 1,
                 end_line: 1,
             },
+            scope: ImportScope::Module,
         };
 
         let serialized = serde_json::to_string(&stmt).unwrap();
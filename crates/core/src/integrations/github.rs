@@ -0,0 +1,87 @@
+//! GitHub Issues as an [`IssueTracker`]
+//!
+//! Issues are created with `POST /repos/{repo}/issues` and updated with
+//! `PATCH /repos/{repo}/issues/{number}`. Keys have the form
+//! `owner/name#number`, so they link in GitHub comments.
+
+use super::{request_error, IssueContent, IssueTracker};
+use crate::config::GitHubConfig;
+use crate::error::{GraphMigratorError, Result};
+use serde_json::{json, Value};
+
+/// Environment variable holding the API token
+pub const TOKEN_VAR: &str = "GITHUB_TOKEN";
+
+/// Client for one GitHub repository
+#[derive(Debug, Clone)]
+pub struct GitHub {
+    config: GitHubConfig,
+    token: String,
+    agent: ureq::Agent,
+}
+
+impl GitHub {
+    /// Client for `config`, authenticating with `token`
+    pub fn new(config: GitHubConfig, token: impl Into<String>) -> Self {
+        Self {
+            config,
+            token: token.into(),
+            agent: ureq::Agent::new(),
+        }
+    }
+
+    /// Client for `config`, with the token from [`TOKEN_VAR`]
+    ///
+    /// # Errors
+    /// Returns [`GraphMigratorError::Config`] if the variable is not set.
+    pub fn from_env(config: GitHubConfig) -> Result<Self> {
+        let token = std::env::var(TOKEN_VAR)
+            .map_err(|_| GraphMigratorError::Config(format!("{} is not set", TOKEN_VAR)))?;
+        Ok(Self::new(config, token))
+    }
+
+    fn issues_url(&self) -> String {
+        format!(
+            "{}/repos/{}/issues",
+            self.config.api_url.trim_end_matches('/'),
+            self.config.repo
+        )
+    }
+
+    fn send(&self, method: &str, url: &str, payload: Value) -> Result<Value> {
+        self.agent
+            .request(method, url)
+            .set("Authorization", &format!("Bearer {}", self.token))
+            .set("Accept", "application/vnd.github+json")
+            .set("User-Agent", "graph-migrator")
+            .send_json(payload)
+            .map_err(|e| request_error("GitHub", e))?
+            .into_json()
+            .map_err(|e| GraphMigratorError::Integration(format!("GitHub: {}", e)))
+    }
+}
+
+impl IssueTracker for GitHub {
+    fn create(&self, issue: &IssueContent) -> Result<String> {
+        let payload = json!({
+            "title": issue.title,
+            "body": issue.body,
+            "labels": self.config.labels,
+        });
+        let response = self.send("POST", &self.issues_url(), payload)?;
+        let number = response["number"].as_u64().ok_or_else(|| {
+            GraphMigratorError::Integration("GitHub: response has no issue number".to_string())
+        })?;
+        Ok(format!("{}#{}", self.config.repo, number))
+    }
+
+    fn update(&self, key: &str, issue: &IssueContent) -> Result<()> {
+        let number = key
+            .rsplit_once('#')
+            .map_or(key, |(_, number)| number)
+            .trim();
+        let url = format!("{}/{}", self.issues_url(), number);
+        let payload = json!({ "title": issue.title, "body": issue.body });
+        self.send("PATCH", &url, payload).map(drop)
+    }
+}
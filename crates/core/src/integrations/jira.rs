@@ -0,0 +1,90 @@
+//! Jira as an [`IssueTracker`]
+//!
+//! Issues are created with `POST /rest/api/2/issue` and updated with
+//! `PUT /rest/api/2/issue/{key}`, authenticating with the account email and
+//! an API token (basic auth). Keys are Jira's own (`MIG-42`). The Markdown
+//! body is sent as the plain-text description.
+
+use super::{request_error, IssueContent, IssueTracker};
+use crate::config::JiraConfig;
+use crate::error::{GraphMigratorError, Result};
+use base64::Engine;
+use serde_json::{json, Value};
+
+/// Environment variable holding the API token
+pub const TOKEN_VAR: &str = "JIRA_API_TOKEN";
+
+/// Client for one Jira project
+#[derive(Debug, Clone)]
+pub struct Jira {
+    config: JiraConfig,
+    authorization: String,
+    agent: ureq::Agent,
+}
+
+impl Jira {
+    /// Client for `config`, authenticating as `config.email` with `token`
+    pub fn new(config: JiraConfig, token: &str) -> Self {
+        let credentials = format!("{}:{}", config.email, token);
+        Self {
+            authorization: format!(
+                "Basic {}",
+                base64::engine::general_purpose::STANDARD.encode(credentials)
+            ),
+            config,
+            agent: ureq::Agent::new(),
+        }
+    }
+
+    /// Client for `config`, with the token from [`TOKEN_VAR`]
+    ///
+    /// # Errors
+    /// Returns [`GraphMigratorError::Config`] if the variable is not set.
+    pub fn from_env(config: JiraConfig) -> Result<Self> {
+        let token = std::env::var(TOKEN_VAR)
+            .map_err(|_| GraphMigratorError::Config(format!("{} is not set", TOKEN_VAR)))?;
+        Ok(Self::new(config, &token))
+    }
+
+    fn issue_url(&self) -> String {
+        format!("{}/rest/api/2/issue", self.config.url.trim_end_matches('/'))
+    }
+
+    fn send(&self, method: &str, url: &str, payload: Value) -> Result<ureq::Response> {
+        self.agent
+            .request(method, url)
+            .set("Authorization", &self.authorization)
+            .set("Accept", "application/json")
+            .send_json(payload)
+            .map_err(|e| request_error("Jira", e))
+    }
+}
+
+impl IssueTracker for Jira {
+    fn create(&self, issue: &IssueContent) -> Result<String> {
+        let payload = json!({
+            "fields": {
+                "project": { "key": self.config.project },
+                "issuetype": { "name": self.config.issue_type },
+                "summary": issue.title,
+                "description": issue.body,
+            }
+        });
+        let response: Value = self
+            .send("POST", &self.issue_url(), payload)?
+            .into_json()
+            .map_err(|e| GraphMigratorError::Integration(format!("Jira: {}", e)))?;
+        response["key"].as_str().map(str::to_string).ok_or_else(|| {
+            GraphMigratorError::Integration("Jira: response has no issue key".to_string())
+        })
+    }
+
+    fn update(&self, key: &str, issue: &IssueContent) -> Result<()> {
+        let url = format!("{}/{}", self.issue_url(), key);
+        let payload = json!({
+            "fields": { "summary": issue.title, "description": issue.body }
+        });
+        // Jira answers 204 No Content
+        self.send("PUT", &url, payload).map(drop)
+    }
+}
@@ -0,0 +1,321 @@
+//! Issue tracker sync for migration units (`integrations` feature)
+//!
+//! Program managers track work in GitHub or Jira, not in the graph.
+//! [`sync_units()`] keeps one issue per [`MigrationUnit`](crate::NodeType::MigrationUnit)
+//! up to date: the first sync creates the issue and stores its key in the
+//! unit's [`Node::issue`](crate::Node::issue); later syncs update that issue
+//! in place. Each issue ([`unit_issue()`]) has:
+//!
+//! - the title `Migrate <unit name>`
+//! - progress counts over the unit's members
+//! - what the unit depends on outside itself: other units, or symbols not
+//!   assigned to any unit
+//! - a checklist of member symbols, ticked once migrated or superseded
+//!
+//! Trackers implement [`IssueTracker`]:
+//!
+//! - [`GitHub`] - issues in one repository, via the REST API
+//! - [`Jira`] - issues in one project, via the REST API (v2)
+//!
+//! Persist the graph (e.g. with [`store::save_graph()`](crate::store::save_graph()))
+//! after syncing, or the stored keys are lost and the next sync files
+//! duplicates.
+
+pub mod github;
+pub mod jira;
+
+pub use github::GitHub;
+pub use jira::Jira;
+
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::{Graph, MigrationStatus, NodeType};
+use crate::migration;
+use crate::progress;
+use crate::queries::migration::is_project_symbol;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+
+/// Dependencies listed per issue before the rest are summarized
+const MAX_DEPENDENCIES: usize = 50;
+
+/// Title and Markdown body of a unit's issue
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssueContent {
+    pub title: String,
+    pub body: String,
+}
+
+/// A service that can file and edit issues
+pub trait IssueTracker {
+    /// Create an issue, returning the key to store on the unit
+    ///
+    /// # Errors
+    /// Returns [`GraphMigratorError::Integration`] if the request fails.
+    fn create(&self, issue: &IssueContent) -> Result<String>;
+
+    /// Replace the title and body of the issue with `key`
+    ///
+    /// # Errors
+    /// Returns [`GraphMigratorError::Integration`] if the request fails.
+    fn update(&self, key: &str, issue: &IssueContent) -> Result<()>;
+}
+
+/// What [`sync_units()`] did for one unit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncOutcome {
+    /// Unit node ID
+    pub unit: String,
+    /// Issue key
+    pub issue: String,
+    /// Whether the issue was created (rather than updated)
+    pub created: bool,
+}
+
+/// The issue describing migration unit `unit_id`, or `None` if there is no
+/// such unit
+pub fn unit_issue(graph: &Graph, unit_id: &str) -> Option<IssueContent> {
+    let unit = graph
+        .get_by_id(unit_id)
+        .and_then(|idx| graph.node_weight(idx))
+        .filter(|node| node.node_type == NodeType::MigrationUnit)?;
+    let memberships = migration::memberships(graph);
+    let members: BTreeSet<&str> = memberships
+        .iter()
+        .filter(|(_, unit)| unit == unit_id)
+        .map(|(member, _)| member.as_str())
+        .collect();
+    let mut unit_of: BTreeMap<&str, &str> = BTreeMap::new();
+    for (member, unit) in &memberships {
+        unit_of.entry(member.as_str()).or_insert(unit.as_str());
+    }
+    let node = |id: &str| graph.get_by_id(id).and_then(|idx| graph.node_weight(idx));
+
+    let mut counts = progress::StatusCounts::new();
+    for member in members.iter().filter_map(|id| node(id)) {
+        *counts.entry(member.status).or_default() += 1;
+    }
+
+    // Dependencies outside the unit, by the unit they belong to if any
+    let mut units: BTreeSet<&str> = BTreeSet::new();
+    let mut symbols: BTreeSet<&str> = BTreeSet::new();
+    for (from, to, edge) in graph.edge_endpoints() {
+        if !edge.edge_type.is_dependency() {
+            continue;
+        }
+        let (Some(source), Some(target)) = (graph.node_weight(from), graph.node_weight(to)) else {
+            continue;
+        };
+        if !members.contains(source.id.as_str())
+            || members.contains(target.id.as_str())
+            || !is_project_symbol(target)
+        {
+            continue;
+        }
+        match unit_of.get(target.id.as_str()) {
+            Some(other) => units.insert(other),
+            None => symbols.insert(target.id.as_str()),
+        };
+    }
+
+    let mut body = String::new();
+    let total: usize = counts.values().sum();
+    let done = progress::done(&counts);
+    // Writing to a String cannot fail
+    let _ = writeln!(
+        body,
+        "Migration unit `{}`: {} of {} symbols done.\n",
+        unit.id, done, total
+    );
+    for (status, count) in &counts {
+        let _ = writeln!(body, "- {:?}: {}", status, count);
+    }
+    let _ = writeln!(body, "\n## Depends on\n");
+    if units.is_empty() && symbols.is_empty() {
+        let _ = writeln!(body, "Nothing outside this unit.");
+    }
+    for other in &units {
+        let name = node(other).map_or(*other, |n| n.name.as_str());
+        let issue = node(other).and_then(|n| n.issue.as_deref());
+        match issue {
+            Some(issue) => {
+                let _ = writeln!(body, "- Unit {} ({})", name, issue);
+            }
+            None => {
+                let _ = writeln!(body, "- Unit {}", name);
+            }
+        }
+    }
+    for id in symbols.iter().take(MAX_DEPENDENCIES) {
+        let status = node(id).map(|n| n.status).unwrap_or_default();
+        let _ = writeln!(body, "- `{}` ({:?})", id, status);
+    }
+    if symbols.len() > MAX_DEPENDENCIES {
+        let _ = writeln!(
+            body,
+            "- ...and {} more symbols",
+            symbols.len() - MAX_DEPENDENCIES
+        );
+    }
+    let _ = writeln!(body, "\n## Symbols\n");
+    for id in &members {
+        let finished = node(id).is_some_and(|n| {
+            matches!(
+                n.status,
+                MigrationStatus::Migrated | MigrationStatus::Superseded
+            )
+        });
+        let _ = writeln!(body, "- [{}] `{}`", if finished { "x" } else { " " }, id);
+    }
+    let _ = writeln!(
+        body,
+        "\n_Updated by `migrator sync-issues`; edits are overwritten._"
+    );
+
+    Some(IssueContent {
+        title: format!("Migrate {}", unit.name),
+        body,
+    })
+}
+
+/// Create or update the issue of every migration unit, in ID order
+///
+/// Units without [`Node::issue`](crate::Node::issue) get a new issue, whose
+/// key is stored on the unit; the others are updated. Stops at the first
+/// failed request; units synced before it keep their new keys.
+///
+/// # Errors
+/// Returns [`GraphMigratorError::Integration`] if the tracker rejects a request.
+pub fn sync_units(graph: &mut Graph, tracker: &dyn IssueTracker) -> Result<Vec<SyncOutcome>> {
+    let mut units: Vec<(String, Option<String>)> = graph
+        .nodes()
+        .filter(|node| node.node_type == NodeType::MigrationUnit)
+        .map(|node| (node.id.clone(), node.issue.clone()))
+        .collect();
+    units.sort();
+
+    let mut outcomes = Vec::with_capacity(units.len());
+    for (unit, existing) in units {
+        let Some(content) = unit_issue(graph, &unit) else {
+            continue;
+        };
+        let outcome = match existing {
+            Some(issue) => {
+                tracker.update(&issue, &content)?;
+                SyncOutcome {
+                    unit,
+                    issue,
+                    created: false,
+                }
+            }
+            None => {
+                let issue = tracker.create(&content)?;
+                graph.update_node_by_id(&unit, |node| node.issue = Some(issue.clone()));
+                SyncOutcome {
+                    unit,
+                    issue,
+                    created: true,
+                }
+            }
+        };
+        tracing::info!(unit = %outcome.unit, issue = %outcome.issue, created = outcome.created, "synced issue");
+        outcomes.push(outcome);
+    }
+    Ok(outcomes)
+}
+
+/// [`GraphMigratorError::Integration`] for a failed request
+pub(crate) fn request_error(service: &str, err: ureq::Error) -> GraphMigratorError {
+    let detail = match err {
+        ureq::Error::Status(code, response) => {
+            let text = response.into_string().unwrap_or_default();
+            format!("{} returned {}: {}", service, code, text.trim())
+        }
+        ureq::Error::Transport(transport) => format!("{}: {}", service, transport),
+    };
+    GraphMigratorError::Integration(detail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Edge, EdgeType, Node};
+    use std::cell::RefCell;
+
+    /// Records requests instead of sending them
+    #[derive(Default)]
+    struct Recorder {
+        created: RefCell<Vec<IssueContent>>,
+        updated: RefCell<Vec<(String, IssueContent)>>,
+    }
+
+    impl IssueTracker for Recorder {
+        fn create(&self, issue: &IssueContent) -> Result<String> {
+            self.created.borrow_mut().push(issue.clone());
+            Ok(format!("MIG-{}", self.created.borrow().len()))
+        }
+
+        fn update(&self, key: &str, issue: &IssueContent) -> Result<()> {
+            self.updated
+                .borrow_mut()
+                .push((key.to_string(), issue.clone()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_sync_units_creates_then_updates() {
+        let mut graph = Graph::new();
+        for name in ["build", "total", "query", "helper"] {
+            let id = format!("app.py::{}", name);
+            graph.add_node(Node::new(id, name, NodeType::Function, "python", "app.py"));
+        }
+        let mut calls = |from: &str, to: &str| {
+            let from = graph.get_by_id(&format!("app.py::{}", from)).unwrap();
+            let to = graph.get_by_id(&format!("app.py::{}", to)).unwrap();
+            graph.add_edge(from, to, Edge::new(EdgeType::Calls));
+        };
+        calls("build", "total");
+        calls("build", "query");
+        calls("total", "helper");
+        migration::create_unit(
+            &mut graph,
+            "unit::billing",
+            "billing",
+            &["app.py::build", "app.py::total"],
+        )
+        .unwrap();
+        migration::create_unit(&mut graph, "unit::db", "db", &["app.py::query"]).unwrap();
+        migration::transition(&mut graph, "app.py::total", MigrationStatus::Migrated).unwrap();
+
+        let tracker = Recorder::default();
+        let outcomes = sync_units(&mut graph, &tracker).unwrap();
+        let keys: Vec<(&str, &str, bool)> = outcomes
+            .iter()
+            .map(|o| (o.unit.as_str(), o.issue.as_str(), o.created))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                ("unit::billing", "MIG-1", true),
+                ("unit::db", "MIG-2", true)
+            ]
+        );
+
+        let billing = &tracker.created.borrow()[0];
+        assert_eq!(billing.title, "Migrate billing");
+        assert!(billing.body.contains("1 of 2 symbols done"));
+        // The db unit had no issue yet when billing was created
+        assert!(billing.body.contains("- Unit db\n"));
+        assert!(billing.body.contains("- `app.py::helper` (Pending)"));
+        assert!(billing.body.contains("- [ ] `app.py::build`"));
+        assert!(billing.body.contains("- [x] `app.py::total`"));
+
+        // Second sync updates the stored issues, now linking db's
+        sync_units(&mut graph, &tracker).unwrap();
+        let updated = tracker.updated.borrow();
+        assert_eq!(updated[0].0, "MIG-1");
+        assert!(updated[0].1.body.contains("- Unit db (MIG-2)"));
+        assert_eq!(tracker.created.borrow().len(), 2);
+        assert!(unit_issue(&graph, "app.py::build").is_none());
+    }
+}
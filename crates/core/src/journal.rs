@@ -0,0 +1,360 @@
+//! Undo log of changes to the tracked state
+//!
+//! Every save through [`store::commit()`] compares the graph being saved
+//! with the state file it replaces and appends the differences (statuses,
+//! `MigratedTo` links, migration units and their members) as one
+//! [`JournalEntry`] to a JSON Lines journal next to the state file
+//! ([`store::journal_path()`]). The journal is only ever appended to:
+//! [`revert()`] applies the inverse of an entry to a graph, and saving the
+//! result with [`store::commit_undo()`] records an entry naming the one it
+//! undid. `migrator undo` and `migrator history <node>` read it.
+//!
+//! Saving keeps what the state file records about symbols a scan did not
+//! reach, so tracked state only disappears through a recorded change.
+//! Renames through [`store::remap_ids()`] are journaled as [`Change::Rename`].
+//! Changes read from source annotations are recorded under
+//! [`ANNOTATIONS_COMMAND`] and are not undoable: the next load would apply
+//! them again.
+//!
+//! [`store::commit()`]: crate::store::commit
+//! [`store::journal_path()`]: crate::store::journal_path
+//! [`store::commit_undo()`]: crate::store::commit_undo
+//! [`store::remap_ids()`]: crate::store::remap_ids
+
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::{Graph, MigrationStatus, Node, NodeType};
+use crate::migration;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+use std::io::Write;
+use std::path::Path;
+
+/// Command name of entries holding changes made by source annotations
+pub const ANNOTATIONS_COMMAND: &str = "annotations";
+
+/// One save of the state file; one line of the journal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Position in the journal, from 1
+    pub seq: u64,
+    /// When the state was saved, in seconds since the Unix epoch
+    pub at: u64,
+    /// What made the changes (`set-status`, `link`, `tui`)
+    pub command: String,
+    /// Sequence number of the entry this one reverted, for undos
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub undoes: Option<u64>,
+    /// What changed, in the order it is applied
+    pub changes: Vec<Change>,
+}
+
+/// One mutation of the tracked state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Change {
+    /// A node's status changed
+    Status {
+        id: String,
+        from: MigrationStatus,
+        to: MigrationStatus,
+    },
+    /// A `MigratedTo` link was added
+    Link { legacy: String, target: String },
+    /// A `MigratedTo` link was removed
+    Unlink { legacy: String, target: String },
+    /// A migration unit was created
+    AddUnit { unit: Box<Node> },
+    /// A migration unit was removed
+    RemoveUnit { unit: Box<Node> },
+    /// A symbol was assigned to a unit
+    Assign { member: String, unit: String },
+    /// A symbol was taken out of a unit
+    Unassign { member: String, unit: String },
+    /// A node's ID changed
+    Rename { old: String, new: String },
+}
+
+impl Change {
+    /// IDs of the nodes the change is about
+    pub fn ids(&self) -> Vec<&str> {
+        match self {
+            Change::Status { id, .. } => vec![id],
+            Change::Link { legacy, target } | Change::Unlink { legacy, target } => {
+                vec![legacy, target]
+            }
+            Change::AddUnit { unit } | Change::RemoveUnit { unit } => vec![&unit.id],
+            Change::Assign { member, unit } | Change::Unassign { member, unit } => {
+                vec![member, unit]
+            }
+            Change::Rename { old, new } => vec![old, new],
+        }
+    }
+
+    /// The change that undoes this one
+    pub fn inverse(&self) -> Change {
+        match self.clone() {
+            Change::Status { id, from, to } => Change::Status {
+                id,
+                from: to,
+                to: from,
+            },
+            Change::Link { legacy, target } => Change::Unlink { legacy, target },
+            Change::Unlink { legacy, target } => Change::Link { legacy, target },
+            Change::AddUnit { unit } => Change::RemoveUnit { unit },
+            Change::RemoveUnit { unit } => Change::AddUnit { unit },
+            Change::Assign { member, unit } => Change::Unassign { member, unit },
+            Change::Unassign { member, unit } => Change::Assign { member, unit },
+            Change::Rename { old, new } => Change::Rename { old: new, new: old },
+        }
+    }
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::Status { id, from, to } => write!(f, "{}: {:?} -> {:?}", id, from, to),
+            Change::Link { legacy, target } => write!(f, "linked {} -> {}", legacy, target),
+            Change::Unlink { legacy, target } => write!(f, "unlinked {} -> {}", legacy, target),
+            Change::AddUnit { unit } => write!(f, "created unit {}", unit.id),
+            Change::RemoveUnit { unit } => write!(f, "removed unit {}", unit.id),
+            Change::Assign { member, unit } => write!(f, "assigned {} to {}", member, unit),
+            Change::Unassign { member, unit } => write!(f, "took {} out of {}", member, unit),
+            Change::Rename { old, new } => write!(f, "renamed {} -> {}", old, new),
+        }
+    }
+}
+
+/// What changed in the tracked state from `before` to `after`
+///
+/// A node missing from `after` had its status reset to `Pending`, and its
+/// links and memberships removed. Units are compared by ID.
+pub fn diff(before: &Graph, after: &Graph) -> Vec<Change> {
+    let mut changes = Vec::new();
+    let in_after = |id: &str| after.get_by_id(id).is_some();
+
+    let added_units: Vec<&Node> = after
+        .nodes()
+        .filter(|node| node.node_type == NodeType::MigrationUnit)
+        .filter(|node| before.get_by_id(&node.id).is_none())
+        .collect();
+    changes.extend(added_units.into_iter().map(|unit| Change::AddUnit {
+        unit: Box::new(unit.clone()),
+    }));
+
+    let mut statuses: Vec<Change> = after
+        .nodes()
+        .filter_map(|node| {
+            let from = before
+                .get_by_id(&node.id)
+                .and_then(|i| before.node_weight(i))
+                .map_or(MigrationStatus::Pending, |old| old.status);
+            (from != node.status).then(|| Change::Status {
+                id: node.id.clone(),
+                from,
+                to: node.status,
+            })
+        })
+        .collect();
+    statuses.extend(
+        before
+            .nodes()
+            .filter(|node| !node.status.is_pending() && !in_after(&node.id))
+            .map(|node| Change::Status {
+                id: node.id.clone(),
+                from: node.status,
+                to: MigrationStatus::Pending,
+            }),
+    );
+    statuses.sort_by(|a, b| a.ids().cmp(&b.ids()));
+    changes.extend(statuses);
+
+    let old_links: BTreeSet<_> = migration::links(before).into_iter().collect();
+    let new_links: BTreeSet<_> = migration::links(after).into_iter().collect();
+    for (legacy, target) in new_links.difference(&old_links) {
+        changes.push(Change::Link {
+            legacy: legacy.clone(),
+            target: target.clone(),
+        });
+    }
+    for (legacy, target) in old_links.difference(&new_links) {
+        changes.push(Change::Unlink {
+            legacy: legacy.clone(),
+            target: target.clone(),
+        });
+    }
+
+    let old_members: BTreeSet<_> = migration::memberships(before).into_iter().collect();
+    let new_members: BTreeSet<_> = migration::memberships(after).into_iter().collect();
+    for (member, unit) in new_members.difference(&old_members) {
+        changes.push(Change::Assign {
+            member: member.clone(),
+            unit: unit.clone(),
+        });
+    }
+    for (member, unit) in old_members.difference(&new_members) {
+        changes.push(Change::Unassign {
+            member: member.clone(),
+            unit: unit.clone(),
+        });
+    }
+
+    changes.extend(
+        before
+            .nodes()
+            .filter(|node| node.node_type == NodeType::MigrationUnit && !in_after(&node.id))
+            .map(|unit| Change::RemoveUnit {
+                unit: Box::new(unit.clone()),
+            }),
+    );
+    changes
+}
+
+/// Append `entry` to the journal at `path`, creating it if needed
+///
+/// # Errors
+/// Returns an error if the file cannot be written.
+pub fn append(path: &Path, entry: &JournalEntry) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(|e| GraphMigratorError::io(dir, e))?;
+    }
+    let line =
+        serde_json::to_string(entry).map_err(|e| GraphMigratorError::Export(e.to_string()))?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| GraphMigratorError::io(path, e))?;
+    writeln!(file, "{}", line).map_err(|e| GraphMigratorError::io(path, e))
+}
+
+/// Every entry of the journal at `path`, oldest first
+///
+/// A missing file is an empty journal. Blank lines are skipped.
+///
+/// # Errors
+/// Returns an error if the file exists but cannot be read, or a line is
+/// not an entry.
+pub fn load(path: &Path) -> Result<Vec<JournalEntry>> {
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+    let text = std::fs::read_to_string(path).map_err(|e| GraphMigratorError::io(path, e))?;
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            serde_json::from_str(line)
+                .map_err(|e| GraphMigratorError::parse(path, format!("line {}: {}", number + 1, e)))
+        })
+        .collect()
+}
+
+/// The latest entry of `entries` that is neither an undo, undone, nor
+/// recorded from source annotations
+pub fn last_undoable(entries: &[JournalEntry]) -> Option<&JournalEntry> {
+    let undone: HashSet<u64> = entries.iter().filter_map(|entry| entry.undoes).collect();
+    entries.iter().rev().find(|entry| {
+        entry.undoes.is_none()
+            && !undone.contains(&entry.seq)
+            && entry.command != ANNOTATIONS_COMMAND
+    })
+}
+
+/// Changes in `entries` involving node `id`, oldest first, with their entry
+pub fn history<'a>(entries: &'a [JournalEntry], id: &str) -> Vec<(&'a JournalEntry, &'a Change)> {
+    entries
+        .iter()
+        .flat_map(|entry| entry.changes.iter().map(move |change| (entry, change)))
+        .filter(|(_, change)| change.ids().contains(&id))
+        .collect()
+}
+
+/// Apply the inverses of `entry`'s changes to `graph`, last change first
+///
+/// Statuses are set back directly, without the lifecycle check of
+/// [`migration::transition()`]; the status change being undone is dropped
+/// from the node's history.
+///
+/// # Errors
+/// Returns [`GraphMigratorError::NodeNotFound`] if a node the entry changed
+/// is gone, or [`GraphMigratorError::InvalidGraph`] if a link cannot be
+/// restored; `graph` may then be partly reverted.
+pub fn revert(graph: &mut Graph, entry: &JournalEntry) -> Result<()> {
+    for change in entry.changes.iter().rev() {
+        apply(graph, &change.inverse())?;
+    }
+    Ok(())
+}
+
+/// Make `change` to `graph`
+fn apply(graph: &mut Graph, change: &Change) -> Result<()> {
+    match change {
+        Change::Status { id, from, to } => {
+            graph
+                .update_node_by_id(id, |node| {
+                    node.status = *to;
+                    let last = node.history.last();
+                    if last.is_some_and(|last| last.from == *to && last.to == *from) {
+                        node.history.pop();
+                    }
+                })
+                .ok_or_else(|| GraphMigratorError::NodeNotFound(id.clone()))?;
+        }
+        Change::Link { legacy, target } => {
+            migration::link(graph, legacy, target)?;
+        }
+        Change::Unlink { legacy, target } => {
+            migration::unlink(graph, legacy, target)?;
+        }
+        Change::AddUnit { unit } => {
+            if graph.get_by_id(&unit.id).is_none() {
+                graph.add_node((**unit).clone());
+            }
+        }
+        Change::RemoveUnit { unit } => {
+            if let Some(index) = graph.get_by_id(&unit.id) {
+                graph.remove_node(index);
+            }
+        }
+        Change::Assign { member, unit } => {
+            migration::assign(graph, member, unit)?;
+        }
+        Change::Unassign { member, unit } => {
+            migration::unassign(graph, member, unit)?;
+        }
+        Change::Rename { old, new } => {
+            graph.rename_node(old, new)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_records_state_of_dropped_nodes() {
+        let mut before = Graph::new();
+        for id in ["a.py::f", "b.py::f"] {
+            before.add_node(Node::new(id, "f", NodeType::Function, "python", "a.py"));
+        }
+        migration::transition(&mut before, "a.py::f", MigrationStatus::InProgress).unwrap();
+        migration::link(&mut before, "a.py::f", "b.py::f").unwrap();
+        let mut after = before.clone();
+        after.remove_node(after.get_by_id("a.py::f").unwrap());
+
+        let changes: Vec<String> = diff(&before, &after)
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                "a.py::f: InProgress -> Pending",
+                "unlinked a.py::f -> b.py::f"
+            ]
+        );
+        assert!(diff(&before, &before).is_empty());
+    }
+}
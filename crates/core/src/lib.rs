@@ -3,11 +3,57 @@
 //! This library provides the core data structures and functionality for
 //! building and querying dependency graphs from source code.
 
+#[cfg(feature = "std-fs")]
+pub mod annotations;
+pub mod cache;
+#[cfg(feature = "compact")]
+pub mod compact;
+pub mod config;
+pub mod deps;
+#[cfg(feature = "std-fs")]
 pub mod discovery;
+#[cfg(feature = "std-fs")]
+pub mod enrich;
+pub mod error;
+#[cfg(feature = "std-fs")]
+pub mod events;
+pub mod export;
+pub mod filter;
+pub mod frameworks;
 pub mod graph;
+pub mod impact;
 pub mod import;
+#[cfg(feature = "integrations")]
+pub mod integrations;
+#[cfg(feature = "std-fs")]
+pub mod journal;
+pub mod migration;
 pub mod parser;
+pub mod paths;
+pub mod progress;
 pub mod queries;
+#[cfg(feature = "std-fs")]
+pub mod remote;
+pub mod resolve;
+pub mod rules;
+pub mod schema;
+#[cfg(feature = "snapshot")]
+pub mod snapshot;
+#[cfg(feature = "std-fs")]
+pub mod store;
+#[cfg(test)]
+mod test_util;
+pub mod testmap;
+pub mod view;
 
 // Re-export commonly used types
-pub use graph::{Edge, Graph, Node, NodeType};
+pub use error::{GraphMigratorError, Result};
+pub use graph::{
+    AttrValue, Blame, Centrality, Edge, EdgePolicy, Effort, Graph, GraphData, GraphMetadata,
+    MemoryStats, MigrationStatus, Node, NodeType, PackageInfo, StatusChange,
+};
+pub use view::GraphView;
+
+/// The petgraph version [`Graph::as_petgraph()`] returns, for running its
+/// algorithms without a separate (and possibly mismatched) dependency
+pub use petgraph;
@@ -0,0 +1,1195 @@
+//! Links from legacy symbols to their replacements
+//!
+//! A [`EdgeType::MigratedTo`] edge records that a legacy symbol has been
+//! rewritten as a target symbol, typically in another namespace of a merged
+//! graph or a new module of the same project. One legacy symbol may be split
+//! into several targets, and several may be folded into one, but links never
+//! form a cycle: a target cannot also be the legacy code of its own legacy
+//! code.
+//!
+//! [`queries::migration_coverage()`](crate::queries::migration_coverage())
+//! reports which legacy symbols have been linked so far.
+//!
+//! Statuses move through a lifecycle checked by
+//! [`MigrationStatus::can_transition_to()`]; [`transition()`] enforces it and
+//! records each change with a timestamp in [`Node::history`](crate::Node::history).
+//! A fully replaced symbol ends as `Superseded`: a tombstone that is kept,
+//! with its history and links, even after its source is deleted (see
+//! [`store::apply()`](crate::store::apply())).
+//!
+//! [`propose_links()`] suggests links from the `[[mapping]]` rules of
+//! `migrator.toml`, which say where each legacy file is being rewritten.
+//! [`match_candidates()`] goes further and ranks every plausible pair by
+//! name, parameter count and file path, for a person to confirm.
+//!
+//! Symbols migrated together are grouped under a [`NodeType::MigrationUnit`]
+//! node by [`EdgeType::PartOfMigration`] edges; [`create_unit()`] makes one,
+//! typically from a [`queries::suggest_units()`](crate::queries::suggest_units())
+//! suggestion.
+
+use crate::config::MappingRule;
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::{
+    AttrValue, Direction, Edge, EdgeType, Graph, MigrationStatus, Node, NodeType, StatusChange,
+};
+use crate::parser::signature::ARITY_ATTRIBUTE;
+use globset::{GlobBuilder, GlobMatcher};
+use petgraph::stable_graph::{EdgeIndex, NodeIndex};
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Record that `legacy_id` was migrated to `target_id`
+///
+/// Linking an already linked pair returns the existing edge.
+///
+/// # Errors
+/// Returns [`GraphMigratorError::NodeNotFound`] if either node is missing,
+/// or [`GraphMigratorError::InvalidGraph`] if the link would point a node at
+/// itself or close a cycle of `MigratedTo` edges.
+///
+/// # Example
+/// ```
+/// use graph_migrator_core::{migration, Graph, Node, NodeType};
+///
+/// let mut graph = Graph::new();
+/// graph.add_node(Node::new("old.py::f", "f", NodeType::Function, "python", "old.py"));
+/// graph.add_node(Node::new("new.py::f", "f", NodeType::Function, "python", "new.py"));
+///
+/// migration::link(&mut graph, "old.py::f", "new.py::f").unwrap();
+/// assert!(migration::link(&mut graph, "new.py::f", "old.py::f").is_err());
+/// ```
+pub fn link(graph: &mut Graph, legacy_id: &str, target_id: &str) -> Result<EdgeIndex> {
+    let legacy = node(graph, legacy_id)?;
+    let target = node(graph, target_id)?;
+    if legacy == target {
+        return Err(GraphMigratorError::InvalidGraph(format!(
+            "cannot migrate {} to itself",
+            legacy_id
+        )));
+    }
+    if let Some((existing, _)) = graph
+        .edges_between(legacy, target)
+        .find(|(_, edge)| edge.edge_type == EdgeType::MigratedTo)
+    {
+        return Ok(existing);
+    }
+
+    let downstream = crate::queries::closure(
+        graph,
+        target_id,
+        Direction::Outgoing,
+        &[EdgeType::MigratedTo],
+    )?;
+    if downstream.contains(legacy_id) {
+        return Err(GraphMigratorError::InvalidGraph(format!(
+            "linking {} to {} would create a MigratedTo cycle",
+            legacy_id, target_id
+        )));
+    }
+
+    Ok(graph.add_edge(legacy, target, Edge::new(EdgeType::MigratedTo)))
+}
+
+/// Remove the link from `legacy_id` to `target_id`
+///
+/// Returns whether a link existed.
+///
+/// # Errors
+/// Returns [`GraphMigratorError::NodeNotFound`] if either node is missing.
+pub fn unlink(graph: &mut Graph, legacy_id: &str, target_id: &str) -> Result<bool> {
+    let legacy = node(graph, legacy_id)?;
+    let target = node(graph, target_id)?;
+    let links: Vec<EdgeIndex> = graph
+        .edges_between(legacy, target)
+        .filter(|(_, edge)| edge.edge_type == EdgeType::MigratedTo)
+        .map(|(index, _)| index)
+        .collect();
+    for &index in &links {
+        graph.remove_edge(index);
+    }
+    Ok(!links.is_empty())
+}
+
+/// `(legacy ID, target ID)` for every `MigratedTo` edge, sorted
+pub fn links(graph: &Graph) -> Vec<(String, String)> {
+    let mut links: Vec<(String, String)> = graph
+        .edge_endpoints()
+        .filter(|(_, _, edge)| edge.edge_type == EdgeType::MigratedTo)
+        .filter_map(|(from, to, _)| {
+            Some((
+                graph.node_weight(from)?.id.clone(),
+                graph.node_weight(to)?.id.clone(),
+            ))
+        })
+        .collect();
+    links.sort();
+    links.dedup();
+    links
+}
+
+/// A `MigratedTo` link suggested by a [`MappingRule`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProposedLink {
+    /// ID of the legacy symbol
+    pub legacy: String,
+    /// ID of the symbol of the same name in the file its file maps to
+    pub target: String,
+    /// Position of the rule that mapped the files
+    pub rule: usize,
+}
+
+/// Links from legacy symbols to the symbols of the same name in the files
+/// `rules` map their files to, sorted by legacy and target ID
+///
+/// The first rule matching a symbol's file applies. Names match when equal
+/// up to case, underscores, and `.` or `::` between nested names, so
+/// `Cart.total_due` matches `Cart::totalDue`. Pairs that are already linked
+/// are left out.
+///
+/// # Example
+/// ```
+/// use graph_migrator_core::config::MappingRule;
+/// use graph_migrator_core::{migration, Graph, Node, NodeType};
+///
+/// let mut graph = Graph::new();
+/// graph.add_node(Node::new("old/tax.py::rate", "rate", NodeType::Function, "python", "old/tax.py"));
+/// graph.add_node(Node::new("src/tax.rs::rate", "rate", NodeType::Function, "rust", "src/tax.rs"));
+/// let rules = [MappingRule { from: "old/*.py".into(), to: "src/*.rs".into() }];
+///
+/// let proposed = migration::propose_links(&graph, &rules);
+/// assert_eq!(proposed[0].target, "src/tax.rs::rate");
+/// ```
+pub fn propose_links(graph: &Graph, rules: &[MappingRule]) -> Vec<ProposedLink> {
+    let symbols: Vec<(&str, &str, &str)> = graph
+        .nodes()
+        .filter(|node| crate::queries::migration::is_project_symbol(node))
+        .filter_map(|node| {
+            let (file, symbol) = split_id(node)?;
+            Some((node.id.as_str(), file, symbol))
+        })
+        .collect();
+    let mut by_file: HashMap<&str, Vec<(String, &str)>> = HashMap::new();
+    for &(id, file, symbol) in &symbols {
+        by_file
+            .entry(file)
+            .or_default()
+            .push((normalize_name(symbol), id));
+    }
+    let linked: HashSet<(String, String)> = links(graph).into_iter().collect();
+
+    let maps: Vec<(usize, PathMap)> = rules
+        .iter()
+        .enumerate()
+        .filter_map(|(i, rule)| Some((i, PathMap::new(rule).ok()?)))
+        .collect();
+
+    let mut proposed = Vec::new();
+    for &(legacy, file, symbol) in &symbols {
+        let Some((rule, target_file)) = maps
+            .iter()
+            .find_map(|(i, map)| Some((*i, map.apply(file)?)))
+        else {
+            continue;
+        };
+        let Some(targets) = by_file.get(target_file.as_str()) else {
+            continue;
+        };
+        let name = normalize_name(symbol);
+        for &(ref target_name, target) in targets {
+            let pair = (legacy.to_string(), target.to_string());
+            if *target_name == name && legacy != target && !linked.contains(&pair) {
+                proposed.push(ProposedLink {
+                    legacy: pair.0,
+                    target: pair.1,
+                    rule,
+                });
+            }
+        }
+    }
+    proposed.sort_by(|a, b| (&a.legacy, &a.target).cmp(&(&b.legacy, &b.target)));
+    proposed
+}
+
+/// A target symbol that may be the rewrite of a legacy symbol, from
+/// [`match_candidates()`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchCandidate {
+    /// ID of the legacy symbol
+    pub legacy: String,
+    /// ID of the target symbol
+    pub target: String,
+    /// Overall likelihood from 0 to 1: name similarity weighs most, then
+    /// path similarity, then agreeing parameter counts
+    pub score: f64,
+    /// Share of the words of the two qualified names they have in common,
+    /// 1 for names equal up to case and underscores
+    pub name_score: f64,
+    /// Share of the words of the two file paths they have in common, 1 when
+    /// a mapping rule maps one file to the other
+    pub path_score: f64,
+    /// Whether the parameter counts agree, if both are known (see
+    /// [`ARITY_ATTRIBUTE`])
+    pub arity: Option<bool>,
+}
+
+/// Candidates below this name similarity are not reported
+const MIN_NAME_SIMILARITY: f64 = 0.5;
+
+/// Likely `(legacy, target)` pairs between the symbols of `legacy` and
+/// those of `target`, best first for each legacy symbol
+///
+/// Same as [`match_candidates_with()`] without mapping rules.
+///
+/// # Example
+/// ```
+/// use graph_migrator_core::{migration, Graph, Node, NodeType};
+///
+/// let mut legacy = Graph::new();
+/// legacy.add_node(Node::new("billing.py::send_invoice", "send_invoice", NodeType::Function, "python", "billing.py"));
+/// let mut target = Graph::new();
+/// target.add_node(Node::new("billing.ts::sendInvoice", "sendInvoice", NodeType::Function, "typescript", "billing.ts"));
+/// target.add_node(Node::new("billing.ts::Invoice", "Invoice", NodeType::Class, "typescript", "billing.ts"));
+///
+/// let candidates = migration::match_candidates(&legacy, &target);
+/// assert_eq!(candidates.len(), 1);
+/// assert_eq!(candidates[0].target, "billing.ts::sendInvoice");
+/// ```
+pub fn match_candidates(legacy: &Graph, target: &Graph) -> Vec<MatchCandidate> {
+    match_candidates_with(legacy, target, &[])
+}
+
+/// Copies of `graph` keeping only the symbols in files that match the
+/// `from` pattern of one of `rules`, and only those matching a `to` pattern,
+/// for [`match_candidates_with()`] when legacy and target code are scanned
+/// together
+///
+/// Symbols matching neither side, and invalid rules, are left out.
+pub fn split_by_mapping(graph: &Graph, rules: &[MappingRule]) -> (Graph, Graph) {
+    let maps: Vec<PathMap> = rules
+        .iter()
+        .filter_map(|rule| PathMap::new(rule).ok())
+        .collect();
+    let side = |matches: &dyn Fn(&PathMap, &str) -> bool| {
+        let mut side = graph.clone();
+        let rejected: Vec<NodeIndex> = side
+            .node_indices()
+            .filter(|&index| {
+                let file = side
+                    .node_weight(index)
+                    .and_then(split_id)
+                    .map(|(file, _)| file);
+                !file.is_some_and(|file| maps.iter().any(|map| matches(map, file)))
+            })
+            .collect();
+        for index in rejected {
+            side.remove_node(index);
+        }
+        side
+    };
+    (
+        side(&|map, file| map.from.is_match(file)),
+        side(&|map, file| map.target.is_match(file)),
+    )
+}
+
+/// Likely `(legacy, target)` pairs, sorted by legacy ID and then best first,
+/// with the files `rules` map onto each other counting as the same path
+///
+/// Only project symbols of compatible kinds are paired: functions and
+/// methods, classes, interfaces and structs, or global variables. A pair
+/// must share at least half the words of their qualified names, split at
+/// `_`, `.`, `::` and case changes (`sendInvoiceEmail` is `send invoice
+/// email`). Pairs already linked in `legacy` are left out, as is a symbol
+/// paired with itself when both graphs are the same.
+pub fn match_candidates_with(
+    legacy: &Graph,
+    target: &Graph,
+    rules: &[MappingRule],
+) -> Vec<MatchCandidate> {
+    let targets: Vec<Profile> = target.nodes().filter_map(Profile::new).collect();
+    let mut by_word: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, profile) in targets.iter().enumerate() {
+        for word in &profile.words {
+            by_word.entry(word.as_str()).or_default().push(i);
+        }
+    }
+    let linked: HashSet<(String, String)> = links(legacy).into_iter().collect();
+    let maps: Vec<PathMap> = rules
+        .iter()
+        .filter_map(|rule| PathMap::new(rule).ok())
+        .collect();
+
+    let mut candidates = Vec::new();
+    for from in legacy.nodes().filter_map(Profile::new) {
+        let mut seen = HashSet::new();
+        let others = from
+            .words
+            .iter()
+            .filter_map(|word| by_word.get(word.as_str()))
+            .flatten();
+        for &i in others {
+            let to = &targets[i];
+            if !seen.insert(i) || from.id == to.id || from.kind != to.kind {
+                continue;
+            }
+            if linked.contains(&(from.id.to_string(), to.id.to_string())) {
+                continue;
+            }
+            let name_score = if from.name == to.name {
+                1.0
+            } else {
+                overlap(&from.words, &to.words)
+            };
+            if name_score < MIN_NAME_SIMILARITY {
+                continue;
+            }
+            let mapped = maps
+                .iter()
+                .any(|map| map.apply(from.file).as_deref() == Some(to.file));
+            let path_score = if mapped {
+                1.0
+            } else {
+                overlap(&from.path_words, &to.path_words)
+            };
+            let arity = from.arity.zip(to.arity).map(|(a, b)| a == b);
+            let arity_score = match arity {
+                Some(true) => 1.0,
+                Some(false) => 0.0,
+                None => 0.5,
+            };
+            candidates.push(MatchCandidate {
+                legacy: from.id.to_string(),
+                target: to.id.to_string(),
+                score: 0.6 * name_score + 0.25 * path_score + 0.15 * arity_score,
+                name_score,
+                path_score,
+                arity,
+            });
+        }
+    }
+    candidates.sort_by(|a, b| {
+        a.legacy
+            .cmp(&b.legacy)
+            .then(b.score.total_cmp(&a.score))
+            .then_with(|| a.target.cmp(&b.target))
+    });
+    candidates
+}
+
+/// What [`match_candidates_with()`] compares of one symbol
+struct Profile<'g> {
+    id: &'g str,
+    file: &'g str,
+    /// Normalized qualified name (see [`normalize_name()`])
+    name: String,
+    words: HashSet<String>,
+    path_words: HashSet<String>,
+    kind: SymbolKind,
+    arity: Option<i64>,
+}
+
+/// Kinds of symbols, which [`match_candidates_with()`] pairs only among themselves
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SymbolKind {
+    Callable,
+    Type,
+    Other,
+}
+
+impl<'g> Profile<'g> {
+    fn new(node: &'g Node) -> Option<Self> {
+        if !crate::queries::migration::is_project_symbol(node) {
+            return None;
+        }
+        let kind = match node.node_type {
+            NodeType::Function | NodeType::Method => SymbolKind::Callable,
+            NodeType::Class | NodeType::Interface | NodeType::Struct => SymbolKind::Type,
+            _ => SymbolKind::Other,
+        };
+        let (file, symbol) = split_id(node)?;
+        let stem = file.rsplit_once('.').map_or(file, |(stem, _)| stem);
+        let arity = match node.attributes.get(ARITY_ATTRIBUTE) {
+            Some(AttrValue::Int(arity)) => Some(*arity),
+            _ => None,
+        };
+        Some(Self {
+            id: &node.id,
+            file,
+            name: normalize_name(symbol),
+            words: words(symbol),
+            path_words: words(stem),
+            kind,
+            arity,
+        })
+    }
+}
+
+/// Lowercase words of `text`, split at non-alphanumeric characters and
+/// case changes (`HTTPServer.send_all` is `http server send all`)
+fn words(text: &str) -> HashSet<String> {
+    let mut words = HashSet::new();
+    let chars: Vec<char> = text.chars().collect();
+    let mut word = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !word.is_empty() {
+                words.insert(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        let boundary = c.is_uppercase()
+            && i > 0
+            && (chars[i - 1].is_lowercase()
+                || chars[i - 1].is_uppercase()
+                    && chars.get(i + 1).is_some_and(|next| next.is_lowercase()));
+        if boundary && !word.is_empty() {
+            words.insert(std::mem::take(&mut word));
+        }
+        word.extend(c.to_lowercase());
+    }
+    if !word.is_empty() {
+        words.insert(word);
+    }
+    words
+}
+
+/// Words `a` and `b` have in common, as a share of the words of either
+fn overlap(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// File and symbol parts of `node`'s ID, without its namespace
+fn split_id(node: &Node) -> Option<(&str, &str)> {
+    crate::graph::split_id(&node.id, node.namespace.as_deref())
+}
+
+/// `symbol` lowercased, without underscores, with `.` between nested names
+fn normalize_name(symbol: &str) -> String {
+    symbol
+        .replace("::", ".")
+        .chars()
+        .filter(|&c| c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// A `[[mapping]]` rule, compiled to rewrite paths
+pub(crate) struct PathMap<'a> {
+    from: GlobMatcher,
+    /// `to` as a glob, for telling target files apart
+    target: GlobMatcher,
+    /// `from` as a regex with a group per wildcard
+    captures: Regex,
+    to: Vec<Piece<'a>>,
+}
+
+/// A literal run or wildcard of a mapping pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Piece<'a> {
+    Literal(&'a str),
+    /// `*`: part of one path component
+    Star,
+    /// `?`: one character of a path component
+    Question,
+    /// `**/`: zero or more whole directories
+    Directories,
+    /// A trailing `**`: all the rest of the path
+    Rest,
+}
+
+impl<'a> PathMap<'a> {
+    /// Compile `rule`
+    ///
+    /// # Errors
+    /// Returns [`GraphMigratorError::Config`] if a pattern is not a glob,
+    /// uses wildcards other than `*`, `**` and `?`, or `to` has more
+    /// wildcards than `from`.
+    pub(crate) fn new(rule: &'a MappingRule) -> Result<Self> {
+        let invalid = |reason: String| {
+            GraphMigratorError::Config(format!(
+                "Invalid [[mapping]] {:?} -> {:?}: {}",
+                rule.from, rule.to, reason
+            ))
+        };
+        for pattern in [&rule.from, &rule.to] {
+            if pattern.contains(['[', '{', '\\']) {
+                return Err(invalid(format!(
+                    "{:?} may only use the `*`, `**` and `?` wildcards",
+                    pattern
+                )));
+            }
+        }
+        let glob = |pattern: &str| -> Result<GlobMatcher> {
+            Ok(GlobBuilder::new(pattern)
+                .literal_separator(true)
+                .build()
+                .map_err(|e| invalid(e.to_string()))?
+                .compile_matcher())
+        };
+        let (from, target) = (glob(&rule.from)?, glob(&rule.to)?);
+        let from_pieces = pieces(&rule.from);
+        let to = pieces(&rule.to);
+        let wildcards = |pieces: &[Piece]| {
+            pieces
+                .iter()
+                .filter(|piece| !matches!(piece, Piece::Literal(_)))
+                .count()
+        };
+        if wildcards(&to) > wildcards(&from_pieces) {
+            return Err(invalid("`to` has more wildcards than `from`".to_string()));
+        }
+        let mut pattern = String::from("^");
+        for piece in &from_pieces {
+            match piece {
+                Piece::Literal(text) => pattern.push_str(&regex::escape(text)),
+                Piece::Star => pattern.push_str("([^/]*)"),
+                Piece::Question => pattern.push_str("([^/])"),
+                Piece::Directories => pattern.push_str("((?:.*/)?)"),
+                Piece::Rest => pattern.push_str("(.*)"),
+            }
+        }
+        pattern.push('$');
+        let captures = Regex::new(&pattern).map_err(|e| invalid(e.to_string()))?;
+        Ok(PathMap {
+            from,
+            target,
+            captures,
+            to,
+        })
+    }
+
+    /// `file` rewritten to `to`, if it matches `from`
+    ///
+    /// Each wildcard of `to` stands for what the wildcard at the same place
+    /// in `from` matched.
+    pub(crate) fn apply(&self, file: &str) -> Option<String> {
+        if !self.from.is_match(file) {
+            return None;
+        }
+        let captures = self.captures.captures(file)?;
+        let mut groups = captures.iter().skip(1);
+        let mut path = String::new();
+        for piece in &self.to {
+            match piece {
+                Piece::Literal(text) => path.push_str(text),
+                _ => path.push_str(groups.next()??.as_str()),
+            }
+        }
+        Some(path)
+    }
+}
+
+/// The literal runs and wildcards of `pattern`, read as [`globset`] does
+fn pieces(pattern: &str) -> Vec<Piece<'_>> {
+    let bytes = pattern.as_bytes();
+    let mut pieces = Vec::new();
+    let (mut start, mut i) = (0, 0);
+    while i < bytes.len() {
+        let (piece, len) = match bytes[i] {
+            b'?' => (Piece::Question, 1),
+            b'*' if bytes.get(i + 1) == Some(&b'*') => {
+                let at_boundary = i == 0 || bytes[i - 1] == b'/';
+                match bytes.get(i + 2) {
+                    Some(b'/') if at_boundary => (Piece::Directories, 3),
+                    None if at_boundary => (Piece::Rest, 2),
+                    // Elsewhere `**` is a `*`
+                    _ => (Piece::Star, 2),
+                }
+            }
+            b'*' => (Piece::Star, 1),
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        if start < i {
+            pieces.push(Piece::Literal(&pattern[start..i]));
+        }
+        pieces.push(piece);
+        i += len;
+        start = i;
+    }
+    if start < bytes.len() {
+        pieces.push(Piece::Literal(&pattern[start..]));
+    }
+    pieces
+}
+
+/// Add a migration unit `unit_id` named `name` with `members` assigned to it
+///
+/// # Errors
+/// Returns [`GraphMigratorError::InvalidGraph`] if `unit_id` is already
+/// taken, or [`GraphMigratorError::NodeNotFound`] if a member is missing (in
+/// which case the graph is left unchanged).
+///
+/// # Example
+/// ```
+/// use graph_migrator_core::{migration, Graph, Node, NodeType};
+///
+/// let mut graph = Graph::new();
+/// graph.add_node(Node::new("db.py::connect", "connect", NodeType::Function, "python", "db.py"));
+///
+/// migration::create_unit(&mut graph, "unit::storage", "storage", &["db.py::connect"]).unwrap();
+/// assert_eq!(migration::unit_members(&graph, "unit::storage"), vec!["db.py::connect"]);
+/// ```
+pub fn create_unit<S: AsRef<str>>(
+    graph: &mut Graph,
+    unit_id: &str,
+    name: &str,
+    members: &[S],
+) -> Result<NodeIndex> {
+    if graph.get_by_id(unit_id).is_some() {
+        return Err(GraphMigratorError::InvalidGraph(format!(
+            "{} already exists",
+            unit_id
+        )));
+    }
+    let members = members
+        .iter()
+        .map(|id| node(graph, id.as_ref()))
+        .collect::<Result<Vec<_>>>()?;
+    let language = members
+        .first()
+        .and_then(|&member| graph.node_weight(member))
+        .map_or_else(String::new, |member| member.language.clone());
+    let unit = graph.add_node(Node::new(
+        unit_id,
+        name,
+        NodeType::MigrationUnit,
+        language,
+        std::path::PathBuf::new(),
+    ));
+    for member in members {
+        graph.add_edge(member, unit, Edge::new(EdgeType::PartOfMigration));
+    }
+    Ok(unit)
+}
+
+/// Assign `member_id` to the existing unit `unit_id`
+///
+/// Assigning a member twice returns the existing edge.
+///
+/// # Errors
+/// Returns [`GraphMigratorError::NodeNotFound`] if either node is missing,
+/// or [`GraphMigratorError::InvalidGraph`] if `unit_id` is not a
+/// migration unit.
+pub fn assign(graph: &mut Graph, member_id: &str, unit_id: &str) -> Result<EdgeIndex> {
+    let member = node(graph, member_id)?;
+    let unit = node(graph, unit_id)?;
+    if graph
+        .node_weight(unit)
+        .is_some_and(|n| n.node_type != NodeType::MigrationUnit)
+    {
+        return Err(GraphMigratorError::InvalidGraph(format!(
+            "{} is not a migration unit",
+            unit_id
+        )));
+    }
+    if let Some((existing, _)) = graph
+        .edges_between(member, unit)
+        .find(|(_, edge)| edge.edge_type == EdgeType::PartOfMigration)
+    {
+        return Ok(existing);
+    }
+    Ok(graph.add_edge(member, unit, Edge::new(EdgeType::PartOfMigration)))
+}
+
+/// Take `member_id` out of unit `unit_id`
+///
+/// Returns whether it was a member.
+///
+/// # Errors
+/// Returns [`GraphMigratorError::NodeNotFound`] if either node is missing.
+pub fn unassign(graph: &mut Graph, member_id: &str, unit_id: &str) -> Result<bool> {
+    let member = node(graph, member_id)?;
+    let unit = node(graph, unit_id)?;
+    let edges: Vec<EdgeIndex> = graph
+        .edges_between(member, unit)
+        .filter(|(_, edge)| edge.edge_type == EdgeType::PartOfMigration)
+        .map(|(index, _)| index)
+        .collect();
+    for &index in &edges {
+        graph.remove_edge(index);
+    }
+    Ok(!edges.is_empty())
+}
+
+/// IDs of the members of unit `unit_id`, sorted
+pub fn unit_members(graph: &Graph, unit_id: &str) -> Vec<String> {
+    let mut members: Vec<String> = memberships(graph)
+        .into_iter()
+        .filter(|(_, unit)| unit == unit_id)
+        .map(|(member, _)| member)
+        .collect();
+    members.dedup();
+    members
+}
+
+/// `(member ID, unit ID)` for every `PartOfMigration` edge, sorted
+pub fn memberships(graph: &Graph) -> Vec<(String, String)> {
+    let mut memberships: Vec<(String, String)> = graph
+        .edge_endpoints()
+        .filter(|(_, _, edge)| edge.edge_type == EdgeType::PartOfMigration)
+        .filter_map(|(from, to, _)| {
+            Some((
+                graph.node_weight(from)?.id.clone(),
+                graph.node_weight(to)?.id.clone(),
+            ))
+        })
+        .collect();
+    memberships.sort();
+    memberships.dedup();
+    memberships
+}
+
+/// Move `id` to `status` now, recording the change in its history
+///
+/// Returns the previous status. Setting the current status again is a
+/// no-op and records nothing.
+///
+/// # Errors
+/// Returns [`GraphMigratorError::NodeNotFound`] if the node is missing, or
+/// [`GraphMigratorError::IllegalTransition`] if the lifecycle does not allow
+/// the change.
+///
+/// # Example
+/// ```
+/// use graph_migrator_core::{migration, Graph, MigrationStatus, Node, NodeType};
+///
+/// let mut graph = Graph::new();
+/// graph.add_node(Node::new("old.py::f", "f", NodeType::Function, "python", "old.py"));
+///
+/// migration::transition(&mut graph, "old.py::f", MigrationStatus::Superseded).unwrap();
+/// assert!(migration::transition(&mut graph, "old.py::f", MigrationStatus::Pending).is_err());
+/// ```
+pub fn transition(graph: &mut Graph, id: &str, status: MigrationStatus) -> Result<MigrationStatus> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    transition_at(graph, id, status, now)
+}
+
+/// Move every node of `ids` to `status` now, or leave them all unchanged
+///
+/// Returns the previous status of each node, in the order of `ids`.
+///
+/// # Errors
+/// Same as [`transition()`], for the first node that cannot make the
+/// change; the graph is then left as it was.
+///
+/// # Example
+/// ```
+/// use graph_migrator_core::{migration, Graph, MigrationStatus, Node, NodeType};
+///
+/// let mut graph = Graph::new();
+/// graph.add_node(Node::new("old.py::f", "f", NodeType::Function, "python", "old.py"));
+/// graph.add_node(Node::new("old.py::g", "g", NodeType::Function, "python", "old.py"));
+/// migration::transition(&mut graph, "old.py::g", MigrationStatus::Superseded).unwrap();
+///
+/// // Superseded is final, so neither node changes
+/// let ids = ["old.py::f", "old.py::g"];
+/// assert!(migration::transition_all(&mut graph, &ids, MigrationStatus::InProgress).is_err());
+/// let f = graph.get_by_id("old.py::f").and_then(|i| graph.node_weight(i)).unwrap();
+/// assert_eq!(f.status, MigrationStatus::Pending);
+/// ```
+pub fn transition_all<S: AsRef<str>>(
+    graph: &mut Graph,
+    ids: &[S],
+    status: MigrationStatus,
+) -> Result<Vec<MigrationStatus>> {
+    for id in ids {
+        let id = id.as_ref();
+        let from = graph
+            .node_weight(node(graph, id)?)
+            .map_or(status, |node| node.status);
+        if !from.can_transition_to(status) {
+            return Err(GraphMigratorError::IllegalTransition {
+                id: id.to_string(),
+                from,
+                to: status,
+            });
+        }
+    }
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    ids.iter()
+        .map(|id| transition_at(graph, id.as_ref(), status, now))
+        .collect()
+}
+
+/// [`transition()`] with an explicit timestamp, in seconds since the Unix epoch
+///
+/// # Errors
+/// Same as [`transition()`].
+pub fn transition_at(
+    graph: &mut Graph,
+    id: &str,
+    status: MigrationStatus,
+    at: u64,
+) -> Result<MigrationStatus> {
+    let index = node(graph, id)?;
+    let node = graph
+        .node_weight_mut(index)
+        .ok_or_else(|| GraphMigratorError::NodeNotFound(id.to_string()))?;
+    let from = node.status;
+    if !from.can_transition_to(status) {
+        return Err(GraphMigratorError::IllegalTransition {
+            id: id.to_string(),
+            from,
+            to: status,
+        });
+    }
+    if from != status {
+        node.status = status;
+        node.history.push(StatusChange {
+            from,
+            to: status,
+            at,
+        });
+    }
+    Ok(from)
+}
+
+fn node(graph: &Graph, id: &str) -> Result<NodeIndex> {
+    graph
+        .get_by_id(id)
+        .ok_or_else(|| GraphMigratorError::NodeNotFound(id.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Node, NodeType};
+
+    fn sample() -> Graph {
+        let mut graph = Graph::new();
+        for id in ["old.py::a", "old.py::b", "new.py::a", "new.py::b"] {
+            graph.add_node(Node::new(id, id, NodeType::Function, "python", "x.py"));
+        }
+        graph
+    }
+
+    #[test]
+    fn test_link_is_idempotent_and_acyclic() {
+        let mut graph = sample();
+
+        let first = link(&mut graph, "old.py::a", "new.py::a").unwrap();
+        assert_eq!(link(&mut graph, "old.py::a", "new.py::a").unwrap(), first);
+        // One legacy symbol split in two
+        link(&mut graph, "old.py::a", "new.py::b").unwrap();
+        link(&mut graph, "new.py::a", "old.py::b").unwrap();
+        assert_eq!(links(&graph).len(), 3);
+
+        // old.py::a -> new.py::a -> old.py::b, so old.py::b -> old.py::a closes a cycle
+        assert!(matches!(
+            link(&mut graph, "old.py::b", "old.py::a"),
+            Err(GraphMigratorError::InvalidGraph(_))
+        ));
+        assert!(link(&mut graph, "old.py::a", "old.py::a").is_err());
+        assert!(matches!(
+            link(&mut graph, "old.py::a", "missing"),
+            Err(GraphMigratorError::NodeNotFound(_))
+        ));
+
+        assert!(unlink(&mut graph, "old.py::a", "new.py::b").unwrap());
+        assert!(!unlink(&mut graph, "old.py::a", "new.py::b").unwrap());
+        assert_eq!(links(&graph).len(), 2);
+    }
+
+    #[test]
+    fn test_propose_links_from_mapping_rules() {
+        let mut graph = Graph::new();
+        for (id, name) in [
+            ("legacy/billing/invoice.py::Invoice.total_due", "total_due"),
+            ("legacy/billing/invoice.py::send_reminder", "send_reminder"),
+            ("legacy/tax.py::rate", "rate"),
+            ("legacy/tax.py::unmapped", "unmapped"),
+            ("new:src/billing/invoice.rs::Invoice::totalDue", "totalDue"),
+            ("new:src/billing/invoice.rs::send_reminder", "send_reminder"),
+            ("new:src/billing/other.rs::send_reminder", "send_reminder"),
+            ("new:lib/tax.rs::rate", "rate"),
+        ] {
+            let mut node = Node::new(id, name, NodeType::Function, "python", "x.py");
+            node.namespace = id.starts_with("new:").then(|| "new".to_string());
+            graph.add_node(node);
+        }
+        link(
+            &mut graph,
+            "legacy/billing/invoice.py::send_reminder",
+            "new:src/billing/invoice.rs::send_reminder",
+        )
+        .unwrap();
+        let rules = [
+            MappingRule {
+                from: "legacy/billing/**/*.py".to_string(),
+                to: "src/billing/**/*.rs".to_string(),
+            },
+            MappingRule {
+                from: "legacy/tax.py".to_string(),
+                to: "lib/tax.rs".to_string(),
+            },
+        ];
+
+        let proposed = propose_links(&graph, &rules);
+        let pairs: Vec<(&str, &str, usize)> = proposed
+            .iter()
+            .map(|link| (link.legacy.as_str(), link.target.as_str(), link.rule))
+            .collect();
+        assert_eq!(
+            pairs,
+            [
+                (
+                    "legacy/billing/invoice.py::Invoice.total_due",
+                    "new:src/billing/invoice.rs::Invoice::totalDue",
+                    0
+                ),
+                ("legacy/tax.py::rate", "new:lib/tax.rs::rate", 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_path_map_uses_glob_wildcards() {
+        let map = |from: &str, to: &str, file: &str| {
+            let rule = MappingRule {
+                from: from.to_string(),
+                to: to.to_string(),
+            };
+            PathMap::new(&rule).unwrap().apply(file)
+        };
+
+        assert_eq!(map("legacy/*.py", "src/*.rs", "legacy/a/b.py"), None);
+        assert_eq!(
+            map("legacy/*/*.py", "src/*/mod_*.rs", "legacy/a/b.py").as_deref(),
+            Some("src/a/mod_b.rs")
+        );
+        assert_eq!(
+            map("legacy/**/*.py", "src/**/*.rs", "legacy/b.py").as_deref(),
+            Some("src/b.rs")
+        );
+        assert_eq!(
+            map("legacy/**", "src/**", "legacy/a/b.py").as_deref(),
+            Some("src/a/b.py")
+        );
+        assert_eq!(
+            map("legacy/v?/*.py", "src/*.rs", "legacy/v1/b.py").as_deref(),
+            Some("src/1.rs")
+        );
+        assert_eq!(map("legacy/x.py", "src/x.rs", "legacy/x.pyc"), None);
+    }
+
+    #[test]
+    fn test_split_by_mapping_separates_legacy_and_target() {
+        let mut graph = Graph::new();
+        for id in [
+            "legacy/billing.py::send_invoice",
+            "src/billing.rs::send_invoice",
+            "tools/billing.py::send_invoice",
+        ] {
+            let file = id.split_once("::").unwrap().0;
+            graph.add_node(Node::new(
+                id,
+                "send_invoice",
+                NodeType::Function,
+                "python",
+                file,
+            ));
+        }
+        let rules = [MappingRule {
+            from: "legacy/*.py".to_string(),
+            to: "src/*.rs".to_string(),
+        }];
+
+        let (legacy, target) = split_by_mapping(&graph, &rules);
+        let ids = |graph: &Graph| graph.nodes().map(|n| n.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&legacy), ["legacy/billing.py::send_invoice"]);
+        assert_eq!(ids(&target), ["src/billing.rs::send_invoice"]);
+        let pairs: Vec<(String, String)> = match_candidates_with(&legacy, &target, &rules)
+            .into_iter()
+            .map(|candidate| (candidate.legacy, candidate.target))
+            .collect();
+        assert_eq!(
+            pairs,
+            [(
+                "legacy/billing.py::send_invoice".to_string(),
+                "src/billing.rs::send_invoice".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_match_candidates_rank_by_name_path_and_arity() {
+        let node = |id: &str, name: &str, node_type: NodeType, arity: i64| {
+            let mut node = Node::new(id, name, node_type, "python", "x.py");
+            node.attributes
+                .insert(ARITY_ATTRIBUTE.to_string(), AttrValue::Int(arity));
+            node
+        };
+        let mut legacy = Graph::new();
+        legacy.add_node(node(
+            "legacy/billing.py::send_invoice",
+            "send_invoice",
+            NodeType::Function,
+            2,
+        ));
+        legacy.add_node(node(
+            "legacy/billing.py::Invoice",
+            "Invoice",
+            NodeType::Class,
+            0,
+        ));
+        let mut target = Graph::new();
+        for (id, name, node_type, arity) in [
+            (
+                "src/billing.ts::sendInvoice",
+                "sendInvoice",
+                NodeType::Function,
+                2,
+            ),
+            (
+                "src/mail.ts::sendInvoice",
+                "sendInvoice",
+                NodeType::Function,
+                3,
+            ),
+            (
+                "src/mail.ts::sendInvoiceEmail",
+                "sendInvoiceEmail",
+                NodeType::Function,
+                2,
+            ),
+            ("src/mail.ts::send", "send", NodeType::Function, 2),
+            // Not paired with the class of the same name
+            ("src/billing.ts::invoice", "invoice", NodeType::Function, 0),
+            ("src/billing.ts::Invoice", "Invoice", NodeType::Class, 0),
+        ] {
+            target.add_node(node(id, name, node_type, arity));
+        }
+
+        let candidates = match_candidates(&legacy, &target);
+        let ranked: Vec<(&str, &str)> = candidates
+            .iter()
+            .map(|c| (c.legacy.as_str(), c.target.as_str()))
+            .collect();
+        assert_eq!(
+            ranked,
+            [
+                ("legacy/billing.py::Invoice", "src/billing.ts::Invoice"),
+                (
+                    "legacy/billing.py::send_invoice",
+                    "src/billing.ts::sendInvoice"
+                ),
+                (
+                    "legacy/billing.py::send_invoice",
+                    "src/mail.ts::sendInvoice"
+                ),
+                (
+                    "legacy/billing.py::send_invoice",
+                    "src/mail.ts::sendInvoiceEmail"
+                ),
+                ("legacy/billing.py::send_invoice", "src/mail.ts::send"),
+                ("legacy/billing.py::send_invoice", "src/billing.ts::invoice"),
+            ]
+        );
+        assert_eq!(candidates[1].name_score, 1.0);
+        assert_eq!(candidates[1].arity, Some(true));
+        assert_eq!(candidates[2].arity, Some(false));
+
+        // A mapping rule makes the mapped file the same path
+        let rules = [MappingRule {
+            from: "legacy/billing.py".to_string(),
+            to: "src/mail.ts".to_string(),
+        }];
+        let candidates = match_candidates_with(&legacy, &target, &rules);
+        assert_eq!(candidates[1].target, "src/mail.ts::sendInvoice");
+        assert_eq!(candidates[1].path_score, 1.0);
+    }
+
+    #[test]
+    fn test_units_group_members() {
+        let mut graph = sample();
+
+        create_unit(&mut graph, "unit::old", "old", &["old.py::a", "old.py::b"]).unwrap();
+        assert_eq!(
+            unit_members(&graph, "unit::old"),
+            vec!["old.py::a", "old.py::b"]
+        );
+        assign(&mut graph, "new.py::a", "unit::old").unwrap();
+        assign(&mut graph, "new.py::a", "unit::old").unwrap();
+        assert_eq!(memberships(&graph).len(), 3);
+
+        assert!(matches!(
+            create_unit(&mut graph, "unit::old", "old", &["new.py::b"]),
+            Err(GraphMigratorError::InvalidGraph(_))
+        ));
+        assert!(matches!(
+            create_unit(&mut graph, "unit::new", "new", &["new.py::b", "missing"]),
+            Err(GraphMigratorError::NodeNotFound(_))
+        ));
+        assert!(graph.get_by_id("unit::new").is_none());
+        assert!(assign(&mut graph, "new.py::b", "old.py::a").is_err());
+    }
+
+    #[test]
+    fn test_transitions_are_validated_and_recorded() {
+        use MigrationStatus::*;
+        let mut graph = sample();
+
+        assert_eq!(
+            transition_at(&mut graph, "old.py::a", Blocked, 10).unwrap(),
+            Pending
+        );
+        assert!(matches!(
+            transition_at(&mut graph, "old.py::a", Migrated, 20),
+            Err(GraphMigratorError::IllegalTransition {
+                from: Blocked,
+                to: Migrated,
+                ..
+            })
+        ));
+        transition_at(&mut graph, "old.py::a", InProgress, 30).unwrap();
+        transition_at(&mut graph, "old.py::a", Migrated, 40).unwrap();
+        // Repeating a status records nothing
+        transition_at(&mut graph, "old.py::a", Migrated, 45).unwrap();
+        transition_at(&mut graph, "old.py::a", Superseded, 50).unwrap();
+        for next in [Pending, InProgress, Blocked, Migrated] {
+            assert!(transition_at(&mut graph, "old.py::a", next, 60).is_err());
+        }
+
+        let index = graph.get_by_id("old.py::a").unwrap();
+        let history = &graph.node_weight(index).unwrap().history;
+        let steps: Vec<_> = history
+            .iter()
+            .map(|change| (change.to, change.at))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                (Blocked, 10),
+                (InProgress, 30),
+                (Migrated, 40),
+                (Superseded, 50)
+            ]
+        );
+        assert!(matches!(
+            transition(&mut graph, "missing", Migrated),
+            Err(GraphMigratorError::NodeNotFound(_))
+        ));
+
+        // All or nothing
+        assert!(transition_all(&mut graph, &["old.py::b", "old.py::a"], Blocked).is_err());
+        assert!(transition_all(&mut graph, &["old.py::b", "missing"], Blocked).is_err());
+        let index = graph.get_by_id("old.py::b").unwrap();
+        assert!(graph.node_weight(index).unwrap().history.is_empty());
+        assert_eq!(
+            transition_all(&mut graph, &["old.py::b", "new.py::a"], Blocked).unwrap(),
+            [Pending, Pending]
+        );
+    }
+}
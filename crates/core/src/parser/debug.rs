@@ -0,0 +1,402 @@
+//! Parse trees and extraction decisions, for debugging the parsers
+//!
+//! When a symbol is missing from the graph, the question is whether
+//! tree-sitter saw it and, if so, why extraction passed it over.
+//! [`format_tree()`] prints the syntax tree of a file, and
+//! [`explain_symbols()`] lists every definition in it with the node it
+//! became or the reason it was skipped. `migrator debug ast` and
+//! `migrator debug symbols` print them, for pasting into bug reports.
+
+use super::{parse_file_in, GranularityLevel, Language, ParseSession};
+use crate::error::Result;
+use crate::graph::NodeType;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+use tree_sitter::{Node as TsNode, Tree};
+
+/// Leaf text longer than this is cut short in [`format_tree()`]
+const MAX_TEXT: usize = 40;
+
+/// Node kinds that wrap definitions without scoping them: bodies and
+/// `export`/`const` statements
+const TRANSPARENT: &[&str] = &[
+    "block",
+    "declaration_list",
+    "class_body",
+    "export_statement",
+    "lexical_declaration",
+];
+
+/// Kinds of root node, one per grammar
+const ROOTS: &[&str] = &["module", "source_file", "program"];
+
+/// Kinds of the types whose methods are extracted
+const OWNERS: &[&str] = &[
+    "class_definition",
+    "impl_item",
+    "class_declaration",
+    "abstract_class_declaration",
+];
+
+/// What happened to one definition found in the syntax tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolDecision {
+    /// 1-indexed line the definition starts on
+    pub line: usize,
+    /// Tree-sitter node kind, e.g. `function_definition`
+    pub kind: &'static str,
+    /// The definition's name, if it has one
+    pub name: Option<String>,
+    /// Whether it became a node
+    pub outcome: Outcome,
+}
+
+/// Result of extraction for a [`SymbolDecision`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// Added to the graph as this node
+    Extracted {
+        /// ID of the node
+        id: String,
+        /// Type the node was given
+        node_type: NodeType,
+    },
+    /// Left out of the graph
+    Skipped(SkipReason),
+}
+
+/// Why a definition did not become a node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The definition has no name (e.g. a syntax error where it should be)
+    Unnamed,
+    /// Defined inside a node of this kind rather than at the top level or
+    /// in the body of a top-level type
+    Nested { container: &'static str },
+    /// The session's granularity does not extract this kind of symbol
+    Granularity,
+    /// A method of a type that was itself skipped
+    OwnerSkipped,
+    /// An earlier definition with the same name was kept
+    Duplicate,
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipReason::Unnamed => write!(f, "no name"),
+            SkipReason::Nested { container } => write!(f, "nested in {}", container),
+            SkipReason::Granularity => write!(f, "below the granularity level"),
+            SkipReason::OwnerSkipped => write!(f, "its type was skipped"),
+            SkipReason::Duplicate => write!(f, "an earlier definition with this name was kept"),
+        }
+    }
+}
+
+/// Indented outline of `tree`: one line per named node with its field name,
+/// kind and `row:column` range (1-indexed rows), and the text of leaves
+///
+/// # Example
+/// ```
+/// use graph_migrator_core::parser::{debug, ParseSession};
+/// use std::path::Path;
+///
+/// let mut session = ParseSession::new();
+/// let file = session.add_source(Path::new("app.py"), "x = 1\n").unwrap();
+/// let outline = debug::format_tree(&file.tree, &file.source);
+/// assert!(outline.contains("left: identifier [1:0-1:1] \"x\""));
+/// ```
+pub fn format_tree(tree: &Tree, source: &str) -> String {
+    let mut out = String::new();
+    let mut cursor = tree.walk();
+    let mut depth = 0;
+    loop {
+        let node = cursor.node();
+        if node.is_named() || node.is_missing() {
+            let indent = "  ".repeat(depth);
+            let field = cursor
+                .field_name()
+                .map_or(String::new(), |field| format!("{}: ", field));
+            let kind = if node.is_missing() {
+                format!("MISSING {}", node.kind())
+            } else {
+                node.kind().to_string()
+            };
+            let start = node.start_position();
+            let end = node.end_position();
+            let _ = write!(
+                out,
+                "{}{}{} [{}:{}-{}:{}]",
+                indent,
+                field,
+                kind,
+                start.row + 1,
+                start.column,
+                end.row + 1,
+                end.column
+            );
+            if node.named_child_count() == 0 {
+                let text = node.utf8_text(source.as_bytes()).unwrap_or_default();
+                let mut shown: String = text.chars().take(MAX_TEXT).collect();
+                if shown.len() < text.len() {
+                    shown.push('…');
+                }
+                let _ = write!(out, " {:?}", shown);
+            }
+            out.push('\n');
+        }
+        if cursor.goto_first_child() {
+            depth += 1;
+            continue;
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                return out;
+            }
+            depth -= 1;
+        }
+    }
+}
+
+/// Every definition in `path` with the node extraction made of it, or why
+/// it made none, in source order
+///
+/// Extraction runs through `session`, so its granularity and language
+/// settings apply.
+///
+/// # Errors
+/// Returns an error if the file cannot be read or parsed.
+pub fn explain_symbols(session: &mut ParseSession, path: &Path) -> Result<Vec<SymbolDecision>> {
+    let level = session.granularity();
+    let parsed = parse_file_in(session, path)?;
+    let extracted: HashMap<(String, usize), (String, NodeType)> = parsed
+        .graph
+        .nodes()
+        .filter_map(|node| {
+            let (start, _) = node.line_range?;
+            let key = (node.name.clone(), start);
+            Some((key, (node.id.clone(), node.node_type.clone())))
+        })
+        .collect();
+
+    let file = session.parse(path)?;
+    let source = file.source.as_bytes();
+    let mut definitions = Vec::new();
+    let mut stack = vec![file.tree.root_node()];
+    while let Some(node) = stack.pop() {
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+        if is_definition(&node, file.language) {
+            definitions.push(node);
+        }
+    }
+    definitions.sort_by_key(|node| node.start_byte());
+
+    let name_of = |node: &TsNode| {
+        let name = node.child_by_field_name("name")?;
+        name.utf8_text(source).ok().map(str::to_string)
+    };
+    let is_extracted = |node: &TsNode| {
+        name_of(node)
+            .is_some_and(|name| extracted.contains_key(&(name, node.start_position().row + 1)))
+    };
+    let decisions = definitions
+        .into_iter()
+        .map(|node| {
+            let name = name_of(&node);
+            let line = node.start_position().row + 1;
+            let found = name
+                .as_ref()
+                .and_then(|name| extracted.get(&(name.clone(), line)));
+            let outcome = match (found, &name) {
+                (Some((id, node_type)), _) => Outcome::Extracted {
+                    id: id.clone(),
+                    node_type: node_type.clone(),
+                },
+                (None, None) => Outcome::Skipped(SkipReason::Unnamed),
+                (None, Some(_)) => Outcome::Skipped(match scope(&node) {
+                    Scope::Nested(container) => SkipReason::Nested { container },
+                    Scope::Member(_) if level < Some(GranularityLevel::Methods) => {
+                        SkipReason::Granularity
+                    }
+                    Scope::Member(owner) if !is_extracted(&owner) => SkipReason::OwnerSkipped,
+                    Scope::TopLevel if !granularity_allows(level, node.kind()) => {
+                        SkipReason::Granularity
+                    }
+                    Scope::Member(_) | Scope::TopLevel => SkipReason::Duplicate,
+                }),
+            };
+            SymbolDecision {
+                line,
+                kind: node.kind(),
+                name,
+                outcome,
+            }
+        })
+        .collect();
+    Ok(decisions)
+}
+
+/// Where a definition sits, as far as extraction is concerned
+enum Scope<'tree> {
+    TopLevel,
+    /// In the body of this type, itself at the top level
+    Member(TsNode<'tree>),
+    /// Inside a node of this kind
+    Nested(&'static str),
+}
+
+fn scope<'tree>(node: &TsNode<'tree>) -> Scope<'tree> {
+    let Some(parent) = container(node) else {
+        return Scope::TopLevel;
+    };
+    if ROOTS.contains(&parent.kind()) {
+        return Scope::TopLevel;
+    }
+    if OWNERS.contains(&parent.kind())
+        && container(&parent).is_some_and(|outer| ROOTS.contains(&outer.kind()))
+    {
+        return Scope::Member(parent);
+    }
+    Scope::Nested(parent.kind())
+}
+
+/// Nearest ancestor that is not [`TRANSPARENT`]
+fn container<'tree>(node: &TsNode<'tree>) -> Option<TsNode<'tree>> {
+    let mut parent = node.parent()?;
+    while TRANSPARENT.contains(&parent.kind()) {
+        parent = parent.parent()?;
+    }
+    Some(parent)
+}
+
+/// Whether granularity `level` extracts top-level definitions of `kind`
+fn granularity_allows(level: Option<GranularityLevel>, kind: &str) -> bool {
+    let Some(level) = level else {
+        return true;
+    };
+    let function = matches!(
+        kind,
+        "function_definition"
+            | "function_item"
+            | "function_declaration"
+            | "generator_function_declaration"
+            | "variable_declarator"
+    );
+    level
+        >= if function {
+            GranularityLevel::Functions
+        } else {
+            GranularityLevel::Classes
+        }
+}
+
+/// Whether `node` is a definition the parsers of `language` can extract
+fn is_definition(node: &TsNode, language: Language) -> bool {
+    match language {
+        Language::Python => matches!(node.kind(), "function_definition" | "class_definition"),
+        Language::Rust => matches!(
+            node.kind(),
+            "function_item" | "struct_item" | "enum_item" | "union_item" | "trait_item"
+        ),
+        Language::TypeScript => match node.kind() {
+            "function_declaration"
+            | "generator_function_declaration"
+            | "interface_declaration"
+            | "class_declaration"
+            | "abstract_class_declaration"
+            | "method_definition" => true,
+            "variable_declarator" => node.child_by_field_name("value").is_some_and(|value| {
+                matches!(value.kind(), "arrow_function" | "function_expression")
+            }),
+            _ => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain_symbols_gives_skip_reasons() {
+        let path = Path::new("app.py");
+        let source = "\
+def main():
+    def inner():
+        pass
+
+class Cart:
+    def total(self):
+        pass
+
+def main():
+    pass
+
+@cached
+def lookup():
+    pass
+";
+        let mut session = ParseSession::new().with_granularity(Some(GranularityLevel::Methods));
+        session.add_source(path, source).unwrap();
+        let decisions = explain_symbols(&mut session, path).unwrap();
+        let outcomes: Vec<(usize, &str, String)> = decisions
+            .iter()
+            .map(|decision| {
+                let outcome = match &decision.outcome {
+                    Outcome::Extracted { id, .. } => id.clone(),
+                    Outcome::Skipped(reason) => reason.to_string(),
+                };
+                (decision.line, decision.name.as_deref().unwrap(), outcome)
+            })
+            .collect();
+        assert_eq!(
+            outcomes,
+            [
+                (1, "main", "app.py::main".to_string()),
+                (2, "inner", "nested in function_definition".to_string()),
+                (5, "Cart", "app.py::Cart".to_string()),
+                (6, "total", "app.py::Cart.total".to_string()),
+                // Python keeps redefinitions as nodes; the ID finds the first
+                (9, "main", "app.py::main".to_string()),
+                (13, "lookup", "nested in decorated_definition".to_string()),
+            ]
+        );
+
+        let mut session = ParseSession::new().with_granularity(Some(GranularityLevel::Classes));
+        session.add_source(path, source).unwrap();
+        let decisions = explain_symbols(&mut session, path).unwrap();
+        assert_eq!(
+            decisions[0].outcome,
+            Outcome::Skipped(SkipReason::Granularity)
+        );
+        assert_eq!(
+            decisions[3].outcome,
+            Outcome::Skipped(SkipReason::Granularity)
+        );
+
+        // Outlines keep the first of several definitions, and need the owner of methods
+        let path = Path::new("lib.rs");
+        let mut session = ParseSession::new().with_granularity(Some(GranularityLevel::Methods));
+        session
+            .add_source(
+                path,
+                "fn a() {}\nfn a() {}\nimpl Missing {\n    fn m() {}\n}\n",
+            )
+            .unwrap();
+        let reasons: Vec<Outcome> = explain_symbols(&mut session, path)
+            .unwrap()
+            .into_iter()
+            .skip(1)
+            .map(|decision| decision.outcome)
+            .collect();
+        assert_eq!(
+            reasons,
+            [
+                Outcome::Skipped(SkipReason::Duplicate),
+                Outcome::Skipped(SkipReason::OwnerSkipped),
+            ]
+        );
+    }
+}
@@ -0,0 +1,180 @@
+//! Decoding Python source that is not valid UTF-8
+//!
+//! Python 3 reads source as UTF-8 unless a [PEP 263] comment on the first or
+//! second line (`# -*- coding: latin-1 -*-`) declares otherwise. Older code
+//! bases also contain undeclared latin-1 files, and the occasional file with
+//! a few corrupt bytes. Rather than skipping such files, [`decode()`]:
+//!
+//! - honors a latin-1 or Windows-1252 coding declaration,
+//! - decodes undeclared files as latin-1 when no byte sequence in them is
+//!   valid multi-byte UTF-8 (so they were almost certainly not meant as UTF-8),
+//! - otherwise replaces the invalid bytes with U+FFFD.
+//!
+//! The last two cases carry a warning ([`SourceEncoding::warning()`]), which
+//! the lenient parsers report as a [`ParseDiagnostic`](super::ParseDiagnostic).
+//! Line numbers are unaffected, but byte offsets in non-UTF-8 files refer to
+//! the decoded text.
+//!
+//! [PEP 263]: https://peps.python.org/pep-0263/
+
+use serde::{Deserialize, Serialize};
+
+/// Single-byte encodings recognized in coding declarations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LegacyEncoding {
+    /// ISO-8859-1: every byte is the code point of the same value
+    Latin1,
+    /// Windows-1252: latin-1 with printable characters in `0x80..=0x9F`
+    Windows1252,
+}
+
+/// How a source file's bytes were turned into text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SourceEncoding {
+    /// Valid UTF-8
+    #[default]
+    Utf8,
+    /// Decoded as declared by a coding comment
+    Declared(LegacyEncoding),
+    /// Undeclared and not UTF-8; decoded as latin-1
+    DetectedLatin1,
+    /// Not valid UTF-8; invalid bytes were replaced with U+FFFD
+    Lossy,
+}
+
+impl SourceEncoding {
+    /// Why the decoded text may not match what the author intended, if it may not
+    pub fn warning(&self) -> Option<&'static str> {
+        match self {
+            SourceEncoding::Utf8 | SourceEncoding::Declared(_) => None,
+            SourceEncoding::DetectedLatin1 => {
+                Some("not valid UTF-8 and no coding declaration; decoded as latin-1")
+            }
+            SourceEncoding::Lossy => Some("invalid UTF-8; undecodable bytes were replaced"),
+        }
+    }
+}
+
+/// Characters for Windows-1252 bytes `0x80..=0x9F` (undefined bytes map to latin-1)
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž', '\u{8F}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}', 'ž', 'Ÿ',
+];
+
+/// Decode source bytes; see the [module docs](self)
+pub fn decode(bytes: Vec<u8>) -> (String, SourceEncoding) {
+    let bytes = match String::from_utf8(bytes) {
+        Ok(text) => return (text, SourceEncoding::Utf8),
+        Err(err) => err.into_bytes(),
+    };
+
+    if let Some(encoding) = declared_encoding(&bytes) {
+        return (
+            decode_single_byte(&bytes, encoding),
+            SourceEncoding::Declared(encoding),
+        );
+    }
+    if !has_multibyte_utf8(&bytes) {
+        return (
+            decode_single_byte(&bytes, LegacyEncoding::Latin1),
+            SourceEncoding::DetectedLatin1,
+        );
+    }
+    (
+        String::from_utf8_lossy(&bytes).into_owned(),
+        SourceEncoding::Lossy,
+    )
+}
+
+fn decode_single_byte(bytes: &[u8], encoding: LegacyEncoding) -> String {
+    bytes
+        .iter()
+        .map(|&byte| match (encoding, byte) {
+            (LegacyEncoding::Windows1252, 0x80..=0x9F) => WINDOWS_1252_HIGH[byte as usize - 0x80],
+            _ => char::from(byte),
+        })
+        .collect()
+}
+
+/// The single-byte encoding named by a PEP 263 comment on line 1 or 2
+///
+/// Declarations of other encodings (including UTF-8) return `None`.
+fn declared_encoding(bytes: &[u8]) -> Option<LegacyEncoding> {
+    for line in bytes.split(|&b| b == b'\n').take(2) {
+        let line = String::from_utf8_lossy(line);
+        let Some(comment) = line.trim_start().strip_prefix('#') else {
+            continue;
+        };
+        let Some(at) = comment.find("coding") else {
+            continue;
+        };
+        let rest = &comment[at + "coding".len()..];
+        let Some(rest) = rest.strip_prefix([':', '=']) else {
+            continue;
+        };
+        let name: String = rest
+            .trim_start()
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            .collect();
+        return match name.to_ascii_lowercase().replace('_', "-").as_str() {
+            "latin-1" | "latin1" | "iso-8859-1" | "iso8859-1" | "l1" | "iso-latin-1" | "cp819" => {
+                Some(LegacyEncoding::Latin1)
+            }
+            "cp1252" | "windows-1252" => Some(LegacyEncoding::Windows1252),
+            _ => None,
+        };
+    }
+    None
+}
+
+/// Whether `bytes` contains at least one well-formed multi-byte UTF-8 sequence
+fn has_multibyte_utf8(bytes: &[u8]) -> bool {
+    let mut rest = bytes;
+    loop {
+        match std::str::from_utf8(rest) {
+            Ok(valid) => return !valid.is_ascii(),
+            Err(err) => {
+                // Non-ASCII bytes in the valid prefix are multi-byte sequences
+                let (valid, after) = rest.split_at(err.valid_up_to());
+                if !valid.is_ascii() {
+                    return true;
+                }
+                rest = &after[err.error_len().unwrap_or(after.len())..];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_declared_detected_and_lossy() {
+        let utf8 = "name = 'café'\n".as_bytes().to_vec();
+        assert_eq!(
+            decode(utf8),
+            ("name = 'café'\n".to_string(), SourceEncoding::Utf8)
+        );
+
+        let declared = b"# -*- coding: cp1252 -*-\nquote = '\x93hi\x94 caf\xe9'\n".to_vec();
+        let (text, encoding) = decode(declared);
+        assert_eq!(
+            encoding,
+            SourceEncoding::Declared(LegacyEncoding::Windows1252)
+        );
+        assert!(text.ends_with("quote = '“hi” café'\n"));
+        assert!(encoding.warning().is_none());
+
+        let (text, encoding) = decode(b"# caf\xe9\ndef f():\n    pass\n".to_vec());
+        assert_eq!(encoding, SourceEncoding::DetectedLatin1);
+        assert_eq!(text.lines().next(), Some("# café"));
+        assert!(encoding.warning().is_some());
+
+        // Mostly UTF-8 with one stray byte: keep the UTF-8, replace the byte
+        let (text, encoding) = decode("é = 1 # \u{2014}\n".bytes().chain([0xff]).collect());
+        assert_eq!(encoding, SourceEncoding::Lossy);
+        assert!(text.starts_with("é = 1") && text.ends_with('\u{fffd}'));
+    }
+}
@@ -24,20 +24,241 @@
 //! This property is critical for the graph merging strategy: deduplication by ID
 //! works correctly because IDs incorporate the file path.
 //!
+//! The path is the one the session's [`PathResolver`] resolves. By default
+//! the directory functions rewrite IDs relative to the project root
+//! (`src/utils.py::helper`), so graphs built on two machines share IDs;
+//! with `relative_ids = false` in `migrator.toml` they keep the canonical
+//! absolute path. [`project_relative_id()`] and
+//! [`MultiFileGraph::relativize_ids()`] rewrite existing graphs.
+//!
+//! Graphs of several repositories can still collide on relative IDs. Setting
+//! `namespace = "billing"` prefixes every ID with the repository name
+//! (`billing:src/utils.py::helper`) and records it on the node; see
+//! [`namespace_ids()`].
+//!
 //! # Multi-File Parsing API
 //!
 //! - [`parse_files()`] - Parse multiple specific files into a unified graph
 //! - [`parse_directory()`] - Discover and parse all Python files in a directory
+//! - [`parse_directory_with_config()`] - Same, driven by an explicit [`Config`](crate::config::Config)
 //! - [`MultiFileGraph`] - Result structure with graph + provenance metadata
-
-use std::collections::{HashMap, HashSet};
+//! - [`MultiFileGraph::merge()`] - Union graphs built per repository
+//! - [`parse_directory_stream()`] - Per-file results, parsed lazily as the iterator advances
+//!
+//! Jupyter notebooks are parsed as the Python in their code cells; see [`notebook`].
+//!
+//! Each file is parsed in the [`Language`] of its extension, so mixed
+//! Python/Rust/TypeScript repositories are scanned in one run. Rust and
+//! TypeScript files get a declaration outline: their functions, types and
+//! methods, without calls or imports. [`MultiFileGraph::language_stats()`]
+//! breaks a scan down by language.
+//!
+//! The strict functions abort on the first unreadable file. For large scans,
+//! [`parse_files_lenient()`] and [`parse_directory_lenient()`] skip bad files
+//! and return a [`ParseDiagnostic`] per problem instead.
+//!
+//! The `_in` variants ([`parse_files_in()`], [`parse_files_lenient_in()`])
+//! parse through a caller's [`ParseSession`], so a later pass such as
+//! [`import::extract_imports_in()`](crate::import::extract_imports_in()) can
+//! reuse the cached sources and trees.
+
+use crate::error::{GraphMigratorError, Result};
+use crate::import::ImportMap;
+use crate::paths::PathResolver;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
+pub mod debug;
+pub mod encoding;
+pub mod generated;
+pub mod notebook;
+mod outline;
 pub mod python;
+mod session;
+pub mod signature;
+mod stream;
+
+pub use encoding::SourceEncoding;
+pub use notebook::NotebookCells;
+pub use python::FileParseResult;
+pub use session::{ParseSession, SourceFile};
+#[cfg(feature = "std-fs")]
+pub use stream::{parse_directory_stream, parse_directory_stream_with_config};
+pub use stream::{parse_files_stream, ParseStream};
+
+/// How serious a [`ParseDiagnostic`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Severity {
+    /// The file was parsed, but parts of it may be missing from the graph
+    Warning,
+    /// The file was skipped entirely
+    Error,
+}
+
+/// A problem encountered while parsing one file in lenient mode
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParseDiagnostic {
+    /// File the diagnostic refers to
+    pub file: PathBuf,
+    /// Human-readable description of the problem
+    pub error: String,
+    /// Whether the file was skipped or only partially parsed
+    pub severity: Severity,
+}
+
+/// How deep symbol extraction goes
+///
+/// Each level includes everything extracted by the levels before it. A
+/// coarse level makes scans of large repositories faster and their graphs
+/// smaller, at the cost of detail:
+///
+/// - `Files` - one `File` node per source file
+/// - `Classes` - top-level classes, contained in their file
+/// - `Functions` - top-level functions
+/// - `Methods` - methods of top-level classes, contained in their class
+/// - `Calls` - `Calls` and `References` edges, including those made from methods
+///
+/// Without a level ([`Config::granularity`](crate::config::Config::granularity)
+/// unset), top-level classes and functions are extracted with their calls
+/// and type references, but no file or method nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GranularityLevel {
+    /// One `File` node per source file
+    Files,
+    /// Top-level classes, contained in their file
+    Classes,
+    /// Top-level functions
+    Functions,
+    /// Methods of top-level classes, contained in their class
+    Methods,
+    /// `Calls` and `References` edges, including those made from methods
+    Calls,
+}
 
 /// Supported programming languages for parsing
+///
+/// Serialized, and written in `migrator.toml`, in lowercase (`"typescript"`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Language {
     Python,
+    Rust,
+    TypeScript,
+}
+
+impl Language {
+    /// Every supported language
+    pub const ALL: [Language; 3] = [Language::Python, Language::Rust, Language::TypeScript];
+
+    /// Name used in `migrator.toml` and on nodes, e.g. `"python"`
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::Python => "python",
+            Language::Rust => "rust",
+            Language::TypeScript => "typescript",
+        }
+    }
+
+    /// The language called `name` (see [`Language::name()`])
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|lang| lang.name() == name)
+    }
+
+    /// Source file extensions of the language, without the dot
+    ///
+    /// Notebooks (`ipynb`) are also Python, but are only scanned when
+    /// included explicitly.
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            Language::Python => &["py"],
+            Language::Rust => &["rs"],
+            Language::TypeScript => &["ts", "tsx"],
+        }
+    }
+
+    /// The language of `path`, by its extension
+    ///
+    /// `overrides` maps extensions to languages ahead of the built-in ones,
+    /// as the `[extensions]` table of `migrator.toml` does (`""` stands for
+    /// files without one). Returns `None` for extensions no language claims.
+    ///
+    /// # Example
+    /// ```
+    /// use graph_migrator_core::parser::Language;
+    /// use std::collections::BTreeMap;
+    /// use std::path::Path;
+    ///
+    /// let overrides = BTreeMap::from([("pyw".to_string(), Language::Python)]);
+    /// assert_eq!(Language::detect(Path::new("src/lib.rs"), &overrides), Some(Language::Rust));
+    /// assert_eq!(Language::detect(Path::new("gui.pyw"), &overrides), Some(Language::Python));
+    /// assert_eq!(Language::detect(Path::new("README.md"), &overrides), None);
+    /// ```
+    pub fn detect(path: &Path, overrides: &BTreeMap<String, Language>) -> Option<Self> {
+        let extension = path.extension().map_or(Some(""), |ext| ext.to_str())?;
+        if let Some(&language) = overrides.get(extension) {
+            return Some(language);
+        }
+        if extension == "ipynb" {
+            return Some(Language::Python);
+        }
+        Self::ALL
+            .into_iter()
+            .find(|lang| lang.extensions().contains(&extension))
+    }
+
+    /// The language of a script, from the interpreter its `#!` line runs
+    ///
+    /// `source` is the start of the file. The interpreter is the program of
+    /// the line, or the one `env` starts, with any version suffix dropped
+    /// (`python3.11` is `python`); Python (`python`, `pypy`), TypeScript
+    /// (`ts-node`, `deno`) and Rust (`rust-script`) interpreters are known.
+    /// `interpreters` maps more names, matched before the suffix is dropped,
+    /// as the `[interpreters]` table of `migrator.toml` does.
+    ///
+    /// # Example
+    /// ```
+    /// use graph_migrator_core::parser::Language;
+    /// use std::collections::BTreeMap;
+    ///
+    /// let none = BTreeMap::new();
+    /// let script = b"#!/usr/bin/env python3\nimport sys\n";
+    /// assert_eq!(Language::from_shebang(script, &none), Some(Language::Python));
+    /// assert_eq!(Language::from_shebang(b"#!/bin/sh\n", &none), None);
+    ///
+    /// let custom = BTreeMap::from([("jython".to_string(), Language::Python)]);
+    /// let script = b"#!/opt/bin/jython -u\n";
+    /// assert_eq!(Language::from_shebang(script, &custom), Some(Language::Python));
+    /// ```
+    pub fn from_shebang(source: &[u8], interpreters: &BTreeMap<String, Language>) -> Option<Self> {
+        let line = source.strip_prefix(b"#!")?;
+        let line = &line[..line.iter().position(|&b| b == b'\n').unwrap_or(line.len())];
+        let line = std::str::from_utf8(line).ok()?;
+        let mut words = line.split_whitespace();
+        let program = |word: &str| word.rsplit('/').next().unwrap_or(word).to_string();
+        let mut interpreter = program(words.next()?);
+        if interpreter == "env" {
+            // Skip `env`'s options (`-S`) and variable assignments
+            let word = words.find(|word| !word.starts_with('-') && !word.contains('='))?;
+            interpreter = program(word);
+        }
+        if let Some(&language) = interpreters.get(&interpreter) {
+            return Some(language);
+        }
+        match interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.') {
+            "python" | "pypy" => Some(Language::Python),
+            "ts-node" | "deno" => Some(Language::TypeScript),
+            "rust-script" => Some(Language::Rust),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
 }
 
 /// Parser for building dependency graphs from source code
@@ -57,10 +278,8 @@ impl Parser {
     ///
     /// # Returns
     /// A `Graph` containing nodes for extracted symbols
-    pub fn parse_file(&self, path: &Path, lang: &Language) -> anyhow::Result<crate::Graph> {
-        match lang {
-            Language::Python => python::parse_file(path),
-        }
+    pub fn parse_file(&self, path: &Path, lang: &Language) -> Result<crate::Graph> {
+        parse_file_in(&mut ParseSession::for_file(path, *lang), path).map(|parsed| parsed.graph)
     }
 }
 
@@ -77,18 +296,12 @@ impl Default for Parser {
 /// needed for future cross-file edge creation.
 ///
 /// Note: Does not derive `PartialEq`, `Serialize`, or `Deserialize` because
-/// internal fields (`node_id_map`, `StableGraph`) don't implement these traits.
+/// the underlying `StableGraph` doesn't implement these traits.
 #[derive(Debug, Clone)]
 pub struct MultiFileGraph {
     /// The unified graph containing all nodes and edges from parsed files
     pub graph: crate::Graph,
 
-    /// Maps node IDs to their NodeIndex for O(1) lookups
-    ///
-    /// This internal cache avoids the O(N) linear scan that `find_node_by_id()` performs.
-    /// Essential for performance when merging large graphs.
-    node_id_map: HashMap<String, petgraph::stable_graph::NodeIndex>,
-
     /// Maps each node ID to its source file path
     ///
     /// **Key format**: Node IDs use `file_path::symbol_name` format (e.g., `src/utils.py::helper`).
@@ -115,6 +328,116 @@ pub struct MultiFileGraph {
     /// In future epics, this can be extended to map to file-node indices
     /// when File nodes are added to the parser.
     pub file_nodes: HashSet<PathBuf>,
+
+    /// Calls that could not be resolved within their own file
+    ///
+    /// Pass 2 ([`crate::resolve`]) binds the ones that go through imports
+    /// to cross-file `Calls` edges and removes them from this list.
+    pub unresolved_calls: Vec<UnresolvedCall>,
+
+    /// Annotated types that are not classes of the annotating file
+    ///
+    /// Pass 2 binds the ones that go through imports to `References` edges
+    /// and removes them from this list.
+    pub type_references: Vec<TypeReference>,
+
+    /// Module-level type aliases of each parsed file that has any, keyed like `file_nodes`
+    ///
+    /// Each alias maps to the type names in its definition, so pass 2 can
+    /// follow an imported alias to the classes behind it.
+    pub type_aliases: HashMap<PathBuf, HashMap<String, Vec<String>>>,
+
+    /// Cell layout of each parsed notebook, keyed like `file_nodes`
+    ///
+    /// Line numbers of a notebook's nodes and calls refer to its joined code
+    /// cells; [`NotebookCells::locate()`] maps them back to a cell.
+    pub notebooks: HashMap<PathBuf, NotebookCells>,
+
+    /// `__all__` of each parsed module that declares one, keyed like `file_nodes`
+    ///
+    /// Pass 2 uses these to follow package facades to the defining module.
+    pub exports: HashMap<PathBuf, Vec<String>>,
+
+    /// Language each parsed file was parsed in, keyed like `file_nodes`
+    ///
+    /// Summarized by [`language_stats()`](Self::language_stats).
+    pub file_languages: HashMap<PathBuf, Language>,
+
+    /// Edges each merged file added or added to, keyed like `file_nodes`,
+    /// so merging it again withdraws exactly those
+    edge_sources: HashMap<PathBuf, Vec<EdgeContribution>>,
+
+    /// How many contributions each edge has: one per merge that added it or
+    /// added to it, plus one for an edge that was there before any merge did
+    edge_refs: HashMap<petgraph::stable_graph::EdgeIndex, usize>,
+}
+
+/// One edge a file merge added, or added its `count` to
+#[derive(Debug, Clone, Copy)]
+struct EdgeContribution {
+    edge: petgraph::stable_graph::EdgeIndex,
+    from: petgraph::stable_graph::NodeIndex,
+    to: petgraph::stable_graph::NodeIndex,
+    edge_type: crate::graph::EdgeType,
+    count: u32,
+}
+
+/// Files and symbols of one language in a [`MultiFileGraph`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LanguageStats {
+    /// Files parsed in the language
+    pub files: usize,
+    /// Non-external nodes whose `language` is the language
+    pub nodes: usize,
+}
+
+/// A call captured in pass 1 whose target is not defined in the same file
+///
+/// Examples: `utils.helper()`, `helper()` after `from utils import helper`,
+/// `os.getcwd()`, `self.save()`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct UnresolvedCall {
+    /// ID of the calling function
+    pub caller: String,
+    /// Called name as written, possibly dotted (e.g., `utils.helper`)
+    pub callee: String,
+    /// File containing the call
+    pub file: PathBuf,
+    /// 1-indexed line of the call
+    pub line: usize,
+}
+
+/// A type named in a function's annotations, not defined in the same file
+///
+/// Examples: `def total(invoice: Invoice)` after `from billing import
+/// Invoice`, `-> models.Order`, `list[str]`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TypeReference {
+    /// ID of the annotated function
+    pub function: String,
+    /// Type name as written, possibly dotted (e.g., `models.Order`)
+    pub name: String,
+    /// File containing the annotation
+    pub file: PathBuf,
+    /// 1-indexed line of the annotation
+    pub line: usize,
+}
+
+/// Append the `new` items not already in `items`, in order
+fn extend_unique<T: Clone + Eq + std::hash::Hash>(items: &mut Vec<T>, new: Vec<T>) {
+    let mut seen: HashSet<T> = items.iter().cloned().collect();
+    items.extend(new.into_iter().filter(|item| seen.insert(item.clone())));
+}
+
+/// What [`MultiFileGraph::merge()`] added
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MergeSummary {
+    /// Nodes whose ID was new
+    pub nodes_added: usize,
+    /// Nodes whose ID was already present (the existing node was kept)
+    pub nodes_shared: usize,
+    /// Edges not already present with the same endpoints and type
+    pub edges_added: usize,
 }
 
 impl MultiFileGraph {
@@ -122,10 +445,137 @@ impl MultiFileGraph {
     pub fn new() -> Self {
         Self {
             graph: crate::Graph::new(),
-            node_id_map: HashMap::new(),
             node_locations: HashMap::new(),
             file_nodes: HashSet::new(),
+            unresolved_calls: Vec::new(),
+            type_references: Vec::new(),
+            type_aliases: HashMap::new(),
+            notebooks: HashMap::new(),
+            exports: HashMap::new(),
+            file_languages: HashMap::new(),
+            edge_sources: HashMap::new(),
+            edge_refs: HashMap::new(),
+        }
+    }
+
+    /// Files and nodes per language, for every language with a parsed file
+    ///
+    /// # Example
+    /// ```
+    /// use graph_migrator_core::parser::{self, Language, ParseSession};
+    /// use std::path::Path;
+    ///
+    /// let mut session = ParseSession::new();
+    /// session.add_source(Path::new("app.py"), "def main():\n    pass\n").unwrap();
+    /// session.add_source(Path::new("lib.rs"), "pub fn parse() {}\nstruct Token;\n").unwrap();
+    /// let paths = [Path::new("app.py"), Path::new("lib.rs")];
+    /// let multi = parser::parse_files_in(&mut session, &paths).unwrap();
+    /// let stats = multi.language_stats();
+    /// assert_eq!(stats[&Language::Rust].files, 1);
+    /// assert_eq!(stats[&Language::Rust].nodes, 2);
+    /// assert_eq!(stats[&Language::Python].nodes, 1);
+    /// ```
+    pub fn language_stats(&self) -> BTreeMap<Language, LanguageStats> {
+        let mut stats: BTreeMap<Language, LanguageStats> = BTreeMap::new();
+        for language in self.file_languages.values() {
+            stats.entry(*language).or_default().files += 1;
+        }
+        for node in self.graph.nodes().filter(|node| !node.external) {
+            let language = Language::from_name(&node.language);
+            if let Some(entry) = language.and_then(|language| stats.get_mut(&language)) {
+                entry.nodes += 1;
+            }
         }
+        stats
+    }
+
+    /// Wrap a loaded graph, rebuilding provenance from each node's `file_path`
+    ///
+    /// Used for graphs read back from JSON, which don't carry `node_locations`.
+    /// External nodes have no source file and get no location.
+    pub fn from_graph(graph: crate::Graph) -> Self {
+        let mut multi = Self::new();
+        for node in graph.nodes().filter(|node| !node.external) {
+            multi
+                .node_locations
+                .insert(node.id.clone(), node.file_path.clone());
+            multi.file_nodes.insert(node.file_path.clone());
+        }
+        multi.graph = graph;
+        multi
+    }
+
+    /// Union another multi-file graph into this one
+    ///
+    /// Combines graphs built per repository into one cross-repository view.
+    /// Nodes are matched by ID, as in [`merge_file_graph()`](Self::merge_file_graph):
+    /// when both graphs have a node, this graph's copy is kept but edges
+    /// from `other` still attach to it. Edges already present with the same
+    /// endpoints and type are not duplicated. `other`'s provenance,
+    /// unresolved calls and type references are carried over.
+    pub fn merge(&mut self, other: MultiFileGraph) -> MergeSummary {
+        use petgraph::stable_graph::NodeIndex;
+
+        let mut summary = MergeSummary::default();
+        let mut index_map: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+        for node_idx in other.graph.node_indices() {
+            let Some(node) = other.graph.node_weight(node_idx) else {
+                continue;
+            };
+            let merged_idx = match self.graph.get_by_id(&node.id) {
+                Some(existing_idx) => {
+                    summary.nodes_shared += 1;
+                    existing_idx
+                }
+                None => {
+                    summary.nodes_added += 1;
+                    self.graph.add_node(node.clone())
+                }
+            };
+            index_map.insert(node_idx, merged_idx);
+        }
+
+        for (source, target, edge) in other.graph.edge_endpoints() {
+            let (Some(&source), Some(&target)) = (index_map.get(&source), index_map.get(&target))
+            else {
+                continue;
+            };
+            let exists = self
+                .graph
+                .edges_between(source, target)
+                .any(|(_, existing)| existing.edge_type == edge.edge_type);
+            if !exists {
+                self.graph.add_edge(source, target, edge.clone());
+                summary.edges_added += 1;
+            }
+        }
+
+        for (id, file) in other.node_locations {
+            self.node_locations.entry(id).or_insert(file);
+        }
+        self.file_nodes.extend(other.file_nodes);
+        for (file, cells) in other.notebooks {
+            self.notebooks.entry(file).or_insert(cells);
+        }
+        for (file, names) in other.exports {
+            self.exports.entry(file).or_insert(names);
+        }
+        for (file, aliases) in other.type_aliases {
+            self.type_aliases.entry(file).or_insert(aliases);
+        }
+        for (file, language) in other.file_languages {
+            self.file_languages.entry(file).or_insert(language);
+        }
+        extend_unique(&mut self.unresolved_calls, other.unresolved_calls);
+        extend_unique(&mut self.type_references, other.type_references);
+
+        tracing::debug!(
+            nodes_added = summary.nodes_added,
+            nodes_shared = summary.nodes_shared,
+            edges_added = summary.edges_added,
+            "merged graphs"
+        );
+        summary
     }
 
     /// Merge a single-file graph into this multi-file graph
@@ -141,68 +591,312 @@ impl MultiFileGraph {
     ///   node is used (first occurrence wins). This is safe because NodeId format is
     ///   `file_path::symbol_name`, making globally unique.
     /// - Edge remapping: Edge endpoints are remapped to use the correct node indices
-    ///   in the merged graph.
+    ///   in the merged graph. Edges the graph already has are handled by its
+    ///   [`EdgePolicy`](crate::EdgePolicy). Merging a file again first withdraws
+    ///   what its last merge contributed: edges only it added are removed and
+    ///   counts it added are taken back, so stale edges go and counts and
+    ///   parallel edges are not doubled under any policy. Edges other files
+    ///   share, or that were there before the file's merge, stay.
     /// - Provenance tracking: `node_locations` maps each node ID to its source file.
-    pub fn merge_file_graph(
-        &mut self,
-        file_graph: crate::Graph,
-        source_file: &Path,
-    ) -> anyhow::Result<()> {
+    pub fn merge_file_graph(&mut self, file_graph: crate::Graph, source_file: &Path) -> Result<()> {
         use petgraph::stable_graph::NodeIndex;
 
         let mut index_map: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+        let mut duplicates = 0;
 
         // Track all files that have been merged
-        self.file_nodes.insert(source_file.to_path_buf());
+        let first_visit = self.file_nodes.insert(source_file.to_path_buf());
 
         // Add nodes (skip duplicates, track index mappings)
         for node_idx in file_graph.node_indices() {
             let node = file_graph
                 .node_weight(node_idx)
-                .ok_or_else(|| anyhow::anyhow!("Invalid node index in file graph"))?;
+                .ok_or_else(|| invalid_graph("Invalid node index in file graph"))?;
 
-            if let Some(&existing_idx) = self.node_id_map.get(&node.id) {
+            if let Some(existing_idx) = self.graph.get_by_id(&node.id) {
                 // Duplicate: use existing node
                 index_map.insert(node_idx, existing_idx);
+                duplicates += 1;
             } else {
                 // New node: add to graph
                 let new_idx = self.graph.add_node(node.clone());
                 index_map.insert(node_idx, new_idx);
 
-                // Track in our ID map for O(1) lookups
-                self.node_id_map.insert(node.id.clone(), new_idx);
-
                 // Track provenance
                 self.node_locations
                     .insert(node.id.clone(), source_file.to_path_buf());
             }
         }
 
-        // Add edges (remap indices)
+        // Remap edge endpoints to the merged graph's indices
+        let mut edges = Vec::with_capacity(file_graph.edge_count());
         for edge_idx in file_graph.edge_indices() {
             let (source, target) = file_graph
                 .edge_endpoints_for(edge_idx)
-                .ok_or_else(|| anyhow::anyhow!("Invalid edge index in file graph"))?;
+                .ok_or_else(|| invalid_graph("Invalid edge index in file graph"))?;
 
             let new_source = index_map
                 .get(&source)
                 .copied()
-                .ok_or_else(|| anyhow::anyhow!("Source node index not in mapping"))?;
+                .ok_or_else(|| invalid_graph("Source node index not in mapping"))?;
             let new_target = index_map
                 .get(&target)
                 .copied()
-                .ok_or_else(|| anyhow::anyhow!("Target node index not in mapping"))?;
+                .ok_or_else(|| invalid_graph("Target node index not in mapping"))?;
 
             let edge_weight = file_graph
                 .edge_weight(edge_idx)
-                .ok_or_else(|| anyhow::anyhow!("Invalid edge weight"))?;
+                .ok_or_else(|| invalid_graph("Invalid edge weight"))?;
 
-            self.graph
-                .add_edge(new_source, new_target, edge_weight.clone());
+            edges.push((new_source, new_target, edge_weight.clone()));
         }
 
+        // A file merged again replaces what it contributed the last time
+        if !first_visit {
+            self.withdraw_edges(source_file);
+        }
+        let mut contributions = Vec::with_capacity(edges.len());
+        for (source, target, edge) in edges {
+            let (edge_type, count) = (edge.edge_type, edge.count);
+            let before = self.graph.edge_count();
+            let edge_idx = self.graph.add_edge(source, target, edge);
+            if self.graph.edge_count() > before {
+                self.edge_refs.insert(edge_idx, 1);
+            } else {
+                *self.edge_refs.entry(edge_idx).or_insert(1) += 1;
+            }
+            contributions.push(EdgeContribution {
+                edge: edge_idx,
+                from: source,
+                to: target,
+                edge_type,
+                count,
+            });
+        }
+        self.edge_sources
+            .insert(source_file.to_path_buf(), contributions);
+
+        tracing::trace!(
+            file = %source_file.display(),
+            nodes = file_graph.node_count(),
+            duplicates,
+            edges = file_graph.edge_count(),
+            "merged file graph"
+        );
         Ok(())
     }
+
+    /// Take back the edges the last merge of `file` contributed
+    ///
+    /// An edge loses the count the merge added to it under
+    /// [`EdgePolicy::Count`](crate::EdgePolicy::Count), and is removed once
+    /// no contribution is left. Contributions whose edge has since been
+    /// removed are skipped.
+    fn withdraw_edges(&mut self, file: &Path) {
+        let count_policy = self.graph.edge_policy() == crate::EdgePolicy::Count;
+        for contribution in self.edge_sources.remove(file).unwrap_or_default() {
+            let edge_idx = contribution.edge;
+            let current = self
+                .graph
+                .edge_endpoints_for(edge_idx)
+                .zip(self.graph.edge_weight(edge_idx));
+            let Some(((from, to), edge)) = current else {
+                continue;
+            };
+            if (from, to, edge.edge_type)
+                != (contribution.from, contribution.to, contribution.edge_type)
+            {
+                continue;
+            }
+            let refs = self.edge_refs.remove(&edge_idx).unwrap_or(1);
+            if refs <= 1 {
+                self.graph.remove_edge(edge_idx);
+                continue;
+            }
+            self.edge_refs.insert(edge_idx, refs - 1);
+            if count_policy {
+                if let Some(edge) = self.graph.edge_weight_mut(edge_idx) {
+                    edge.count = edge.count.saturating_sub(contribution.count);
+                }
+            }
+        }
+    }
+
+    /// Remove everything merged from `path`, returning the number of nodes removed
+    ///
+    /// Uses provenance: the nodes whose [`node_locations`](Self::node_locations)
+    /// entry is `path` go, with every edge touching them, including edges
+    /// other files (or pass 2) attached to them. The file's unresolved calls,
+    /// type references, aliases, exports, notebook cells and language are
+    /// dropped too.
+    /// `path` is matched as given and as resolved by each [`PathResolver`].
+    pub fn remove_file(&mut self, path: &Path) -> usize {
+        let resolved: Vec<PathBuf> = [PathResolver::Relative, PathResolver::Canonical]
+            .iter()
+            .filter_map(|paths| paths.resolve(path).ok())
+            .collect();
+        let is_file = |file: &Path| file == path || resolved.iter().any(|r| r == file);
+
+        let ids: Vec<String> = self
+            .node_locations
+            .iter()
+            .filter(|(_, file)| is_file(file))
+            .map(|(id, _)| id.clone())
+            .collect();
+        let mut removed = 0;
+        for id in ids {
+            self.node_locations.remove(&id);
+            if let Some(idx) = self.graph.get_by_id(&id) {
+                self.graph.remove_node(idx);
+                removed += 1;
+            }
+        }
+
+        self.file_nodes.retain(|file| !is_file(file));
+        self.edge_sources.retain(|file, _| !is_file(file));
+        let graph = &self.graph;
+        self.edge_refs
+            .retain(|&edge_idx, _| graph.edge_weight(edge_idx).is_some());
+        self.unresolved_calls.retain(|call| !is_file(&call.file));
+        self.type_references
+            .retain(|reference| !is_file(&reference.file));
+        self.type_aliases.retain(|file, _| !is_file(file));
+        self.exports.retain(|file, _| !is_file(file));
+        self.notebooks.retain(|file, _| !is_file(file));
+        self.file_languages.retain(|file, _| !is_file(file));
+        tracing::trace!(file = %path.display(), removed, "removed file");
+        removed
+    }
+
+    /// Replace what was merged from `path` with `file_graph`, a fresh parse of it
+    ///
+    /// [`merge_file_graph()`](Self::merge_file_graph) keeps the first copy of
+    /// each node, so merging a changed file again would keep its old nodes
+    /// and add edges between them. This removes the file first
+    /// ([`remove_file()`](Self::remove_file)), so it can be called every time
+    /// the file changes. Statuses are not carried over; reapply the state
+    /// file ([`store::apply()`](crate::store::apply())) afterwards, and run
+    /// pass 2 again to restore cross-file edges.
+    ///
+    /// # Errors
+    /// Returns an error if `file_graph` is malformed.
+    pub fn replace_file(&mut self, path: &Path, file_graph: crate::Graph) -> Result<()> {
+        self.remove_file(path);
+        self.merge_file_graph(file_graph, path)
+    }
+
+    /// [`replace_file()`](Self::replace_file) with a whole pass-1 result,
+    /// also replacing the file's unresolved calls and type references
+    ///
+    /// # Errors
+    /// Returns an error if the parsed graph is malformed.
+    pub fn replace_file_parse(&mut self, parsed: FileParseResult) -> Result<Vec<usize>> {
+        self.remove_file(&parsed.path);
+        self.merge_file_parse(parsed)
+    }
+
+    /// Merge one file's pass-1 output, returning its syntax error lines
+    ///
+    /// Imports are not kept here; see [`FirstPassOutput`](crate::import::FirstPassOutput).
+    pub fn merge_file_parse(&mut self, parsed: FileParseResult) -> Result<Vec<usize>> {
+        // A file merged twice contributes no new calls, just as it adds no new nodes
+        let first_visit = !self.file_nodes.contains(&parsed.path);
+        self.merge_file_graph(parsed.graph, &parsed.path)?;
+        if first_visit {
+            self.file_languages
+                .insert(parsed.path.clone(), parsed.language);
+            self.unresolved_calls.extend(parsed.unresolved_calls);
+            self.type_references.extend(parsed.type_references);
+            if !parsed.type_aliases.is_empty() {
+                self.type_aliases
+                    .insert(parsed.path.clone(), parsed.type_aliases);
+            }
+            if let Some(names) = parsed.exports {
+                self.exports.insert(parsed.path.clone(), names);
+            }
+            if let Some(cells) = parsed.notebook {
+                self.notebooks.insert(parsed.path, cells);
+            }
+        }
+        Ok(parsed.error_lines)
+    }
+
+    /// Rewrite absolute-path node IDs to be relative to `root`
+    ///
+    /// `root` matches IDs built under either [`PathResolver`]. IDs outside
+    /// `root` are left unchanged. Returns how many IDs changed.
+    pub fn relativize_ids(&mut self, root: &Path) -> usize {
+        let rewritten = relativize_ids(&mut self.graph, root);
+
+        let roots = id_roots(root);
+        self.node_locations = std::mem::take(&mut self.node_locations)
+            .into_iter()
+            .map(|(id, file)| (relative_id(&id, &roots).unwrap_or(id), file))
+            .collect();
+        for call in &mut self.unresolved_calls {
+            if let Some(caller) = relative_id(&call.caller, &roots) {
+                call.caller = caller;
+            }
+        }
+        for reference in &mut self.type_references {
+            if let Some(function) = relative_id(&reference.function, &roots) {
+                reference.function = function;
+            }
+        }
+
+        rewritten
+    }
+
+    /// Rename nodes by ID (see [`Graph::remap_ids()`](crate::Graph::remap_ids()))
+    ///
+    /// Provenance keys, unresolved call sites and type references follow the
+    /// new IDs.
+    ///
+    /// # Errors
+    /// Returns [`GraphMigratorError::InvalidGraph`], changing nothing, if two
+    /// nodes would end up with the same ID.
+    pub fn remap_ids(&mut self, map: &HashMap<String, String>) -> Result<usize> {
+        let renamed = self.graph.remap_ids(map)?;
+
+        self.node_locations = std::mem::take(&mut self.node_locations)
+            .into_iter()
+            .map(|(id, file)| (map.get(&id).cloned().unwrap_or(id), file))
+            .collect();
+        for call in &mut self.unresolved_calls {
+            if let Some(caller) = map.get(&call.caller) {
+                call.caller = caller.clone();
+            }
+        }
+        for reference in &mut self.type_references {
+            if let Some(function) = map.get(&reference.function) {
+                reference.function = function.clone();
+            }
+        }
+
+        Ok(renamed)
+    }
+
+    /// Prefix node IDs with `namespace` (see [`namespace_ids()`])
+    ///
+    /// Provenance keys, unresolved call sites and type references are
+    /// rewritten to match.
+    /// Returns how many IDs changed.
+    pub fn namespace_ids(&mut self, namespace: &str) -> usize {
+        let rewritten = namespace_ids(&mut self.graph, namespace);
+
+        self.node_locations = std::mem::take(&mut self.node_locations)
+            .into_iter()
+            .map(|(id, file)| (namespaced_id(namespace, &id), file))
+            .collect();
+        for call in &mut self.unresolved_calls {
+            call.caller = namespaced_id(namespace, &call.caller);
+        }
+        for reference in &mut self.type_references {
+            reference.function = namespaced_id(namespace, &reference.function);
+        }
+
+        rewritten
+    }
 }
 
 impl Default for MultiFileGraph {
@@ -211,6 +905,176 @@ impl Default for MultiFileGraph {
     }
 }
 
+/// Convert an absolute-path node ID into one relative to `root`
+///
+/// `root` should be canonical, like the paths embedded in IDs. Path
+/// separators are normalized to `/` so IDs match across platforms.
+/// Returns `None` if the ID's file is not under `root`.
+///
+/// # Example
+/// ```
+/// use graph_migrator_core::parser::project_relative_id;
+/// use std::path::Path;
+///
+/// let id = project_relative_id("/work/app/src/utils.py::helper", Path::new("/work/app"));
+/// assert_eq!(id.as_deref(), Some("src/utils.py::helper"));
+/// ```
+pub fn project_relative_id(id: &str, root: &Path) -> Option<String> {
+    let (file, symbol) = id.split_once("::")?;
+    let relative = Path::new(file).strip_prefix(root).ok()?;
+    let components: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    Some(format!("{}::{}", components.join("/"), symbol))
+}
+
+/// Rewrite every node ID in `graph` relative to `root` (see [`project_relative_id()`])
+///
+/// `root` matches IDs built under either [`PathResolver`].
+/// Returns how many IDs changed.
+pub fn relativize_ids(graph: &mut crate::Graph, root: &Path) -> usize {
+    let roots = id_roots(root);
+    let indices: Vec<_> = graph.node_indices().collect();
+    let mut rewritten = 0;
+    for idx in indices {
+        let relative = graph
+            .node_weight(idx)
+            .and_then(|node| relative_id(&node.id, &roots));
+        if let Some(id) = relative {
+            graph.set_node_id(idx, id);
+            rewritten += 1;
+        }
+    }
+    rewritten
+}
+
+/// Rewrite the `file_path` of every node of `graph` under `root` relative to it
+///
+/// Scans keep absolute file paths, which blame, the LSP server and the TUI
+/// read files through, so only exports call this: with relative IDs, a file
+/// path naming the checkout would still tie the export to one machine.
+/// Paths outside `root` (e.g. external modules) are left unchanged. Returns
+/// how many paths changed.
+pub fn relativize_file_paths(graph: &mut crate::Graph, root: &Path) -> usize {
+    let roots = id_roots(root);
+    let indices: Vec<_> = graph.node_indices().collect();
+    let mut rewritten = 0;
+    for idx in indices {
+        let Some(node) = graph.node_weight_mut(idx) else {
+            continue;
+        };
+        let relative = roots
+            .iter()
+            .find_map(|root| node.file_path.strip_prefix(root).ok())
+            .map(Path::to_path_buf);
+        if let Some(relative) = relative {
+            node.file_path = relative;
+            rewritten += 1;
+        }
+    }
+    rewritten
+}
+
+/// `root` as each [`PathResolver`] resolves it, the form IDs embed
+pub(crate) fn id_roots(root: &Path) -> Vec<PathBuf> {
+    let mut roots = vec![root.to_path_buf()];
+    for paths in [PathResolver::Relative, PathResolver::Canonical] {
+        if let Ok(resolved) = paths.resolve(root) {
+            if !roots.contains(&resolved) {
+                roots.push(resolved);
+            }
+        }
+    }
+    roots
+}
+
+/// [`project_relative_id()`] against the first of `roots` holding the ID's file
+pub(crate) fn relative_id(id: &str, roots: &[PathBuf]) -> Option<String> {
+    roots.iter().find_map(|root| project_relative_id(id, root))
+}
+
+/// Old-to-new ID map for symbols of a file moved from `from` to `to`
+///
+/// `from` and `to` are written as they appear in IDs (e.g., the
+/// root-relative `src/utils.py` with `relative_ids`); namespaced IDs keep
+/// their namespace. Feed the result to [`MultiFileGraph::remap_ids()`] or
+/// [`store::remap_ids()`](crate::store::remap_ids()).
+///
+/// # Example
+/// ```
+/// use graph_migrator_core::parser::file_move_map;
+///
+/// let ids = ["src/old.py::f", "billing:src/old.py::C::m", "src/other.py::g"];
+/// let map = file_move_map(ids, "src/old.py", "src/new.py");
+/// assert_eq!(map["src/old.py::f"], "src/new.py::f");
+/// assert_eq!(map["billing:src/old.py::C::m"], "billing:src/new.py::C::m");
+/// assert_eq!(map.len(), 2);
+/// ```
+pub fn file_move_map<'a>(
+    ids: impl IntoIterator<Item = &'a str>,
+    from: &str,
+    to: &str,
+) -> HashMap<String, String> {
+    ids.into_iter()
+        .filter_map(|id| {
+            let (file, symbol) = id.split_once("::")?;
+            let prefix = file.strip_suffix(from)?;
+            (prefix.is_empty() || prefix.ends_with(NAMESPACE_SEPARATOR))
+                .then(|| (id.to_string(), format!("{}{}::{}", prefix, to, symbol)))
+        })
+        .collect()
+}
+
+/// Separator between a namespace and the rest of a node ID
+pub const NAMESPACE_SEPARATOR: char = ':';
+
+/// `id` prefixed with `namespace`, unless it already is
+///
+/// # Example
+/// ```
+/// use graph_migrator_core::parser::namespaced_id;
+///
+/// let id = namespaced_id("billing", "src/utils.py::helper");
+/// assert_eq!(id, "billing:src/utils.py::helper");
+/// assert_eq!(namespaced_id("billing", &id), id);
+/// ```
+pub fn namespaced_id(namespace: &str, id: &str) -> String {
+    match id.strip_prefix(namespace) {
+        Some(rest) if rest.starts_with(NAMESPACE_SEPARATOR) => id.to_string(),
+        _ => format!("{}{}{}", namespace, NAMESPACE_SEPARATOR, id),
+    }
+}
+
+/// Put every node of `graph` in `namespace`, prefixing its ID
+///
+/// Apply after [`relativize_ids()`], which only recognizes bare path IDs.
+/// External nodes are left alone so the same package unifies across
+/// repositories. Returns how many IDs changed.
+pub fn namespace_ids(graph: &mut crate::Graph, namespace: &str) -> usize {
+    let indices: Vec<_> = graph.node_indices().collect();
+    let mut rewritten = 0;
+    for idx in indices {
+        let Some(node) = graph.node_weight_mut(idx) else {
+            continue;
+        };
+        if node.external {
+            continue;
+        }
+        node.namespace = Some(namespace.to_string());
+        let id = namespaced_id(namespace, &node.id);
+        if id != node.id {
+            graph.set_node_id(idx, id);
+            rewritten += 1;
+        }
+    }
+    rewritten
+}
+
+fn invalid_graph(detail: &str) -> GraphMigratorError {
+    GraphMigratorError::InvalidGraph(detail.to_string())
+}
+
 /// Parse multiple Python files into a unified multi-file graph
 ///
 /// # Arguments
@@ -239,27 +1103,289 @@ impl Default for MultiFileGraph {
 ///          multi.graph.node_count(),
 ///          multi.file_nodes.len());
 /// ```
-pub fn parse_files(paths: &[&Path]) -> anyhow::Result<MultiFileGraph> {
+pub fn parse_files(paths: &[&Path]) -> Result<MultiFileGraph> {
+    parse_files_in(&mut ParseSession::uncached(), paths)
+}
+
+/// [`parse_files()`], extracting symbols only down to `level`
+pub fn parse_files_with_granularity(
+    paths: &[&Path],
+    level: GranularityLevel,
+) -> Result<MultiFileGraph> {
+    parse_files_in(
+        &mut ParseSession::uncached().with_granularity(Some(level)),
+        paths,
+    )
+}
+
+/// [`parse_files()`] through `session`, which keeps each file's source and tree
+pub fn parse_files_in(session: &mut ParseSession, paths: &[&Path]) -> Result<MultiFileGraph> {
+    parse_paths(session, paths, None)
+}
+
+/// Pass 1 on one file, with the extractor for the language `session` parses it in
+///
+/// Python files go to [`python`]; Rust and TypeScript files only get a
+/// declaration outline. If the session tags generated files and this is
+/// one, every node gets the [`generated::GENERATED_ATTRIBUTE`] attribute.
+/// Functions and methods get their [`signature::ARITY_ATTRIBUTE`]. A file
+/// with more nodes than the session's `max_nodes` limit is refused with
+/// [`GraphMigratorError::LimitExceeded`], as soon as the extractor has added
+/// one node too many.
+pub(crate) fn parse_file_in(session: &mut ParseSession, path: &Path) -> Result<FileParseResult> {
+    let cache_key = match session.cache_lookup(path) {
+        Some((_, Some(cached))) => return check_node_limit(session, path, cached),
+        Some((key, None)) => Some(key),
+        None => None,
+    };
+    let tags_generated = session.tags_generated();
+    let file = session.parse(path)?;
+    let language = file.language;
+    let tag = tags_generated && generated::is_generated(file.source.as_bytes());
+    let mut parsed = match language {
+        Language::Python => python::parse_file_in(session, path)?,
+        Language::Rust | Language::TypeScript => outline::parse_file_in(session, path)?,
+    };
+    let file = session.parse(path)?;
+    signature::tag_arity(&mut parsed.graph, &file.tree, &file.source);
+    if tag {
+        let indices: Vec<_> = parsed.graph.node_indices().collect();
+        for idx in indices {
+            if let Some(node) = parsed.graph.node_weight_mut(idx) {
+                let attribute = generated::GENERATED_ATTRIBUTE.to_string();
+                node.attributes
+                    .insert(attribute, crate::graph::AttrValue::Bool(true));
+            }
+        }
+    }
+    if let Some(key) = cache_key {
+        session.cache_store(&key, &parsed);
+    }
+    Ok(parsed)
+}
+
+/// `parsed`, unless it has more nodes than the session's `max_nodes` limit
+fn check_node_limit(
+    session: &ParseSession,
+    path: &Path,
+    parsed: FileParseResult,
+) -> Result<FileParseResult> {
+    check_node_count(path, &parsed.graph, session.limits().max_nodes)?;
+    Ok(parsed)
+}
+
+/// Refuse `path` once `graph` has more than `max_nodes` nodes
+///
+/// Extractors call this as they add nodes, so a file over the limit is
+/// given up on before its edges are extracted.
+fn check_node_count(path: &Path, graph: &crate::Graph, max_nodes: Option<usize>) -> Result<()> {
+    let nodes = graph.node_count();
+    match max_nodes {
+        Some(max) if nodes > max => Err(GraphMigratorError::limit(
+            path,
+            format!("file has more than the limit of {max} nodes"),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Parse `paths` in order, adding each file's imports to `imports` if given
+///
+/// Files over one of the session's limits are skipped with a warning rather
+/// than failing the whole parse.
+fn parse_paths(
+    session: &mut ParseSession,
+    paths: &[&Path],
+    mut imports: Option<&mut ImportMap>,
+) -> Result<MultiFileGraph> {
+    let _span = tracing::info_span!("parse_files", files = paths.len()).entered();
+    let started = parse_started();
     let mut multi_graph = MultiFileGraph::new();
+    multi_graph.graph.set_edge_policy(session.edge_policy());
 
     // Sort paths for deterministic merging
     let mut sorted_paths: Vec<&Path> = paths.to_vec();
     sorted_paths.sort();
 
-    // Create parser once outside the loop
-    let parser = Parser::new();
+    let mut skipped = 0;
     for path in sorted_paths {
-        let file_graph = parser.parse_file(path, &Language::Python)?;
-        multi_graph.merge_file_graph(file_graph, path)?;
+        let mut parsed = match parse_file_in(session, path) {
+            Err(err @ GraphMigratorError::LimitExceeded { .. }) => {
+                tracing::warn!(file = %path.display(), %err, "skipping file");
+                skipped += 1;
+                continue;
+            }
+            result => result?,
+        };
+        if let Some(warning) = parsed.encoding.warning() {
+            tracing::warn!(file = %path.display(), warning, "decoded non-UTF-8 source");
+        }
+        let file_imports = std::mem::take(&mut parsed.imports);
+        multi_graph.merge_file_parse(parsed)?;
+        if let Some(imports) = imports.as_deref_mut() {
+            imports.insert(path.to_path_buf(), file_imports);
+        }
     }
 
+    log_parse_summary(session, &multi_graph, skipped, started);
     Ok(multi_graph)
 }
 
+/// Parse multiple Python files, skipping files that fail instead of aborting
+///
+/// Unlike [`parse_files()`], one unreadable file doesn't kill a 10k-file scan.
+///
+/// # Returns
+/// The merged graph of every file that could be parsed, plus diagnostics:
+/// - [`Severity::Error`] for files that could not be read or parsed (skipped)
+/// - [`Severity::Warning`] for files with syntax errors (partially extracted)
+///
+/// Diagnostics are ordered by file path, matching the deterministic merge order.
+pub fn parse_files_lenient(paths: &[&Path]) -> (MultiFileGraph, Vec<ParseDiagnostic>) {
+    parse_files_lenient_in(&mut ParseSession::uncached(), paths)
+}
+
+/// [`parse_files_lenient()`] through `session`, which keeps each file's source and tree
+pub fn parse_files_lenient_in(
+    session: &mut ParseSession,
+    paths: &[&Path],
+) -> (MultiFileGraph, Vec<ParseDiagnostic>) {
+    parse_paths_lenient(session, paths, None)
+}
+
+/// Lenient [`parse_paths()`]: failed files are skipped and have no imports entry
+fn parse_paths_lenient(
+    session: &mut ParseSession,
+    paths: &[&Path],
+    mut imports: Option<&mut ImportMap>,
+) -> (MultiFileGraph, Vec<ParseDiagnostic>) {
+    let _span = tracing::info_span!("parse_files", files = paths.len()).entered();
+    let started = parse_started();
+    let mut multi_graph = MultiFileGraph::new();
+    multi_graph.graph.set_edge_policy(session.edge_policy());
+    let mut diagnostics = Vec::new();
+
+    let mut sorted_paths: Vec<&Path> = paths.to_vec();
+    sorted_paths.sort();
+
+    for path in sorted_paths {
+        let result = parse_file_in(session, path).and_then(|mut parsed| {
+            let file_imports = std::mem::take(&mut parsed.imports);
+            let encoding = parsed.encoding;
+            let error_lines = multi_graph.merge_file_parse(parsed)?;
+            if let Some(imports) = imports.as_deref_mut() {
+                imports.insert(path.to_path_buf(), file_imports);
+            }
+            Ok((error_lines, encoding))
+        });
+
+        match result {
+            Ok((error_lines, encoding)) => {
+                if let Some(warning) = encoding.warning() {
+                    diagnostics.push(ParseDiagnostic {
+                        file: path.to_path_buf(),
+                        error: warning.to_string(),
+                        severity: Severity::Warning,
+                    });
+                }
+                if !error_lines.is_empty() {
+                    let lines: Vec<String> = error_lines.iter().map(|l| l.to_string()).collect();
+                    diagnostics.push(ParseDiagnostic {
+                        file: path.to_path_buf(),
+                        error: format!("syntax error at line(s) {}", lines.join(", ")),
+                        severity: Severity::Warning,
+                    });
+                }
+            }
+            Err(err) => {
+                tracing::debug!(file = %path.display(), %err, "skipping file");
+                diagnostics.push(ParseDiagnostic {
+                    file: path.to_path_buf(),
+                    error: err.to_string(),
+                    severity: Severity::Error,
+                });
+            }
+        }
+    }
+
+    let skipped = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    log_parse_summary(session, &multi_graph, skipped, started);
+    (multi_graph, diagnostics)
+}
+
+/// When a multi-file parse started, if the platform has a clock
+/// (`Instant::now()` panics on wasm32-unknown-unknown)
+fn parse_started() -> Option<std::time::Instant> {
+    let has_clock = !cfg!(all(target_arch = "wasm32", target_os = "unknown"));
+    has_clock.then(std::time::Instant::now)
+}
+
+/// Report totals and timing for a multi-file parse
+fn log_parse_summary(
+    session: &ParseSession,
+    multi_graph: &MultiFileGraph,
+    skipped: usize,
+    started: Option<std::time::Instant>,
+) {
+    tracing::info!(
+        files = multi_graph.file_nodes.len(),
+        skipped,
+        nodes = multi_graph.graph.node_count(),
+        edges = multi_graph.graph.edge_count(),
+        elapsed_ms = started.map(|started| started.elapsed().as_millis() as u64),
+        "parsed files"
+    );
+    for (language, stats) in multi_graph.language_stats() {
+        tracing::debug!(%language, files = stats.files, nodes = stats.nodes, "parsed language");
+    }
+    if let Some(cache) = session.cache() {
+        tracing::debug!(hits = cache.hits(), misses = cache.misses(), "parse cache");
+    }
+}
+
+/// Lenient counterpart of [`parse_directory_with_config()`]
+///
+/// Discovery itself cannot fail per file; parse failures are reported as
+/// diagnostics (see [`parse_files_lenient()`]).
+#[cfg(feature = "std-fs")]
+pub fn parse_directory_lenient(
+    root: &Path,
+    config: &crate::config::Config,
+) -> (MultiFileGraph, Vec<ParseDiagnostic>) {
+    let mut session = ParseSession::for_project(root, config);
+    parse_directory_lenient_in(&mut session, root, config, None)
+}
+
+/// [`parse_directory_lenient()`] through `session`, collecting imports if asked
+#[cfg(feature = "std-fs")]
+pub(crate) fn parse_directory_lenient_in(
+    session: &mut ParseSession,
+    root: &Path,
+    config: &crate::config::Config,
+    imports: Option<&mut ImportMap>,
+) -> (MultiFileGraph, Vec<ParseDiagnostic>) {
+    let files = crate::discovery::discover_project(root, config);
+    let file_refs: Vec<&Path> = files.iter().map(|p| p.as_path()).collect();
+
+    let (mut multi, diagnostics) = parse_paths_lenient(session, &file_refs, imports);
+    if config.relative_ids {
+        multi.relativize_ids(root);
+    }
+    if let Some(namespace) = &config.namespace {
+        multi.namespace_ids(namespace);
+    }
+    (multi, diagnostics)
+}
+
 /// Parse all Python files in a directory
 ///
 /// This is a convenience wrapper that combines Epic 4's file discovery
-/// with Epic 5's multi-file parsing.
+/// with Epic 5's multi-file parsing. If the directory contains a
+/// `migrator.toml`, its discovery settings are honored; otherwise the
+/// defaults apply (all `**/*.py` files not matched by .gitignore).
 ///
 /// # Arguments
 /// * `root` - Root directory to search and parse
@@ -274,15 +1400,52 @@ pub fn parse_files(paths: &[&Path]) -> anyhow::Result<MultiFileGraph> {
 /// let multi = parser::parse_directory(std::path::Path::new("my_project")).unwrap();
 /// println!("Parsed {} nodes", multi.graph.node_count());
 /// ```
-pub fn parse_directory(root: &Path) -> anyhow::Result<MultiFileGraph> {
+#[cfg(feature = "std-fs")]
+pub fn parse_directory(root: &Path) -> Result<MultiFileGraph> {
+    let config = crate::config::Config::from_project_root(root)?;
+    parse_directory_with_config(root, &config)
+}
+
+/// Parse all matching files in a directory using an explicit configuration
+///
+/// # Arguments
+/// * `root` - Root directory to search and parse
+/// * `config` - Discovery settings (include/exclude globs, depth and size limits)
+///
+/// # Returns
+/// A `MultiFileGraph` containing all files selected by the configuration
+#[cfg(feature = "std-fs")]
+pub fn parse_directory_with_config(
+    root: &Path,
+    config: &crate::config::Config,
+) -> Result<MultiFileGraph> {
+    let mut session = ParseSession::for_project(root, config);
+    parse_directory_with_config_in(&mut session, root, config, None)
+}
+
+/// [`parse_directory_with_config()`] through `session`, collecting imports if asked
+#[cfg(feature = "std-fs")]
+pub(crate) fn parse_directory_with_config_in(
+    session: &mut ParseSession,
+    root: &Path,
+    config: &crate::config::Config,
+    imports: Option<&mut ImportMap>,
+) -> Result<MultiFileGraph> {
     use crate::discovery;
 
-    let files = discovery::discover_python_files(root);
+    let files = discovery::discover_project(root, config);
 
     // Convert Vec<PathBuf> to Vec<&Path>
     let file_refs: Vec<&Path> = files.iter().map(|p| p.as_path()).collect();
 
-    parse_files(&file_refs)
+    let mut multi = parse_paths(session, &file_refs, imports)?;
+    if config.relative_ids {
+        multi.relativize_ids(root);
+    }
+    if let Some(namespace) = &config.namespace {
+        multi.namespace_ids(namespace);
+    }
+    Ok(multi)
 }
 
 #[cfg(test)]
@@ -302,6 +1465,56 @@ mod tests {
         assert!(!multi.node_locations.is_empty());
     }
 
+    #[test]
+    fn test_merge_keeps_one_copy_of_unresolved_calls() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("app.py");
+        std::fs::write(&path, "def main():\n    utils.helper()\n    os.getcwd()\n").unwrap();
+        let mut first = parse_files(&[path.as_path()]).unwrap();
+        assert_eq!(first.unresolved_calls.len(), 2);
+
+        let mut twice = first.clone();
+        twice
+            .unresolved_calls
+            .extend(first.unresolved_calls.clone());
+        first.merge(twice);
+        let callees: Vec<&str> = first
+            .unresolved_calls
+            .iter()
+            .map(|call| call.callee.as_str())
+            .collect();
+        assert_eq!(callees, ["utils.helper", "os.getcwd"]);
+    }
+
+    #[test]
+    fn test_merge_unions_nodes_and_edges() {
+        let mut first = parse_files(&[Path::new("tests/test-fixtures/sample.py")]).unwrap();
+        let second = parse_files(&[
+            Path::new("tests/test-fixtures/sample.py"),
+            Path::new("tests/test-fixtures/nested.py"),
+        ])
+        .unwrap();
+        let (nodes, edges) = (first.graph.node_count(), first.graph.edge_count());
+
+        let summary = first.merge(second.clone());
+        assert_eq!(summary.nodes_shared, nodes);
+        assert_eq!(first.graph.node_count(), second.graph.node_count());
+        assert_eq!(first.graph.edge_count(), edges + summary.edges_added);
+        assert_eq!(first.file_nodes.len(), 2);
+        assert_eq!(first.node_locations.len(), second.node_locations.len());
+
+        // Merging again adds nothing
+        let again = first.merge(second);
+        assert_eq!((again.nodes_added, again.edges_added), (0, 0));
+
+        // Provenance is rebuilt from node file paths
+        let restored = MultiFileGraph::from_graph(first.graph.clone());
+        let ids =
+            |multi: &MultiFileGraph| multi.node_locations.keys().cloned().collect::<HashSet<_>>();
+        assert_eq!(ids(&restored), ids(&first));
+        assert_eq!(restored.file_nodes.len(), 2);
+    }
+
     #[test]
     fn test_parse_files_multiple_files() {
         let files = vec![
@@ -332,7 +1545,7 @@ mod tests {
         assert!(multi.graph.node_count() >= 6);
 
         // Verify all files are in node_locations
-        for (_, file_path) in &multi.node_locations {
+        for file_path in multi.node_locations.values() {
             assert!(multi.file_nodes.contains(file_path));
         }
     }
@@ -431,6 +1644,161 @@ mod tests {
         assert!(calls_count >= 1, "Should have at least 1 Calls edge");
     }
 
+    #[test]
+    fn test_repeated_merge_edge_policies() {
+        use crate::graph::EdgePolicy;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("app.py");
+        std::fs::write(
+            &path,
+            "def helper():\n    pass\n\ndef main():\n    helper()\n    helper()\n",
+        )
+        .unwrap();
+
+        // (edges, total count) after one parse; merging the file again keeps them
+        for (policy, first) in [
+            (EdgePolicy::Dedupe, (1, 1)),
+            (EdgePolicy::Count, (1, 2)),
+            (EdgePolicy::Multi, (2, 2)),
+        ] {
+            let mut session = ParseSession::uncached().with_edge_policy(policy);
+            let mut multi = parse_files_in(&mut session, &[path.as_path()]).unwrap();
+            let totals = |multi: &MultiFileGraph| {
+                let counts: Vec<u32> = multi.graph.edges().map(|edge| edge.count).collect();
+                (counts.len(), counts.iter().sum::<u32>())
+            };
+            assert_eq!(totals(&multi), first, "{:?}", policy);
+
+            for _ in 0..2 {
+                let parsed = python::parse_file_in(&mut session, &path).unwrap();
+                multi.merge_file_parse(parsed).unwrap();
+                assert_eq!(totals(&multi), first, "{:?}", policy);
+            }
+            assert_eq!(multi.graph.node_count(), 2);
+        }
+    }
+
+    #[test]
+    fn test_repeated_merge_withdraws_only_own_edges() {
+        use crate::graph::{Edge, EdgePolicy, EdgeType, Node, NodeType};
+
+        // A file graph over `a.py::main`, `b.py::helper` and `b.py::other`
+        let file_graph = |edges: &[(&str, &str)]| {
+            let mut graph = crate::Graph::new();
+            for (id, file) in [
+                ("a.py::main", "a.py"),
+                ("b.py::helper", "b.py"),
+                ("b.py::other", "b.py"),
+            ] {
+                let name = id.rsplit("::").next().unwrap();
+                graph.add_node(Node::new(id, name, NodeType::Function, "python", file));
+            }
+            for (from, to) in edges {
+                let (from, to) = (graph.get_by_id(from).unwrap(), graph.get_by_id(to).unwrap());
+                graph.add_edge(from, to, Edge::new(EdgeType::Calls));
+            }
+            graph
+        };
+        let calls = |multi: &MultiFileGraph, from: &str, to: &str| {
+            let from = multi.graph.get_by_id(from).unwrap();
+            let to = multi.graph.get_by_id(to).unwrap();
+            multi
+                .graph
+                .edges_of(from)
+                .filter(|&(_, source, target, _)| (source, target) == (from, to))
+                .map(|(.., edge)| edge.count)
+                .sum::<u32>()
+        };
+
+        for policy in [EdgePolicy::Dedupe, EdgePolicy::Count, EdgePolicy::Multi] {
+            let mut multi = MultiFileGraph::new();
+            multi.graph.set_edge_policy(policy);
+            let both = [
+                ("a.py::main", "b.py::helper"),
+                ("a.py::main", "b.py::other"),
+            ];
+            multi
+                .merge_file_graph(file_graph(&both), Path::new("a.py"))
+                .unwrap();
+            multi
+                .merge_file_graph(file_graph(&both[..1]), Path::new("b.py"))
+                .unwrap();
+            let shared = if policy == EdgePolicy::Dedupe { 1 } else { 2 };
+            assert_eq!(
+                calls(&multi, "a.py::main", "b.py::helper"),
+                shared,
+                "{:?}",
+                policy
+            );
+
+            // `a.py` no longer calls `other`: its edge goes, the shared one stays
+            multi
+                .merge_file_graph(file_graph(&both[1..]), Path::new("a.py"))
+                .unwrap();
+            assert_eq!(
+                calls(&multi, "a.py::main", "b.py::other"),
+                1,
+                "{:?}",
+                policy
+            );
+            multi
+                .merge_file_graph(file_graph(&[]), Path::new("a.py"))
+                .unwrap();
+            assert_eq!(
+                calls(&multi, "a.py::main", "b.py::other"),
+                0,
+                "{:?}",
+                policy
+            );
+            assert_eq!(
+                calls(&multi, "a.py::main", "b.py::helper"),
+                1,
+                "{:?}",
+                policy
+            );
+            assert_eq!(multi.graph.edge_count(), 1, "{:?}", policy);
+        }
+    }
+
+    #[test]
+    fn test_replace_file_is_idempotent() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let app = temp_dir.path().join("app.py");
+        let other = temp_dir.path().join("other.py");
+        std::fs::write(
+            &app,
+            "def old():\n    missing()\n\ndef keep():\n    old()\n",
+        )
+        .unwrap();
+        std::fs::write(&other, "def user():\n    pass\n").unwrap();
+        let mut multi = parse_files(&[app.as_path(), other.as_path()]).unwrap();
+        assert_eq!((multi.graph.node_count(), multi.graph.edge_count()), (3, 1));
+
+        // Replacing with the same parse changes nothing, however often it is done
+        for _ in 0..2 {
+            let parsed = python::parse_file_detailed(&app).unwrap();
+            multi.replace_file_parse(parsed).unwrap();
+        }
+        assert_eq!((multi.graph.node_count(), multi.graph.edge_count()), (3, 1));
+        assert_eq!(multi.unresolved_calls.len(), 1);
+        assert_eq!(multi.file_nodes.len(), 2);
+
+        // A changed file loses its old symbols and their edges
+        std::fs::write(&app, "def keep():\n    new()\n\ndef new():\n    pass\n").unwrap();
+        let graph = python::parse_file(&app).unwrap();
+        multi.replace_file(&app, graph).unwrap();
+        let mut names: Vec<&str> = multi.graph.nodes().map(|n| n.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["keep", "new", "user"]);
+        assert_eq!(multi.graph.edge_count(), 1);
+        assert!(multi.node_locations.keys().all(|id| !id.ends_with("::old")));
+
+        assert_eq!(multi.remove_file(&app), 2);
+        assert_eq!(multi.graph.node_count(), 1);
+        assert!(multi.unresolved_calls.is_empty());
+    }
+
     #[test]
     fn test_parse_directory() {
         let root = Path::new("tests/test-fixtures/multi-file-project");
@@ -441,6 +1809,218 @@ mod tests {
         assert!(multi.graph.node_count() >= 6);
     }
 
+    #[test]
+    fn test_parse_directory_with_config_excludes() {
+        let root = Path::new("tests/test-fixtures/multi-file-project");
+        let mut config = crate::config::Config::default();
+        config.discovery.exclude = vec!["main.py".to_string()];
+
+        let multi = parse_directory_with_config(root, &config).unwrap();
+
+        assert_eq!(multi.file_nodes.len(), 2);
+        assert!(!multi.file_nodes.iter().any(|p| p.ends_with("main.py")));
+    }
+
+    #[test]
+    fn test_relative_ids_are_machine_independent() {
+        let root = Path::new("tests/test-fixtures/multi-file-project");
+        let config = crate::config::Config {
+            relative_ids: true,
+            ..Default::default()
+        };
+
+        let multi = parse_directory_with_config(root, &config).unwrap();
+
+        let mut ids: Vec<&str> = multi.graph.nodes().map(|n| n.id.as_str()).collect();
+        ids.sort();
+        assert!(ids.contains(&"main.py::main"));
+        assert!(ids.iter().all(|id| !id.starts_with('/')));
+        // Lookups and provenance follow the new IDs
+        assert!(multi.graph.get_by_id("main.py::main").is_some());
+        assert!(multi.node_locations["main.py::main"].ends_with("main.py"));
+    }
+
+    #[test]
+    fn test_relativize_file_paths() {
+        let root = Path::new("tests/test-fixtures/multi-file-project");
+        let mut multi = parse_directory(root).unwrap();
+        let nodes = multi.graph.node_count();
+
+        assert_eq!(relativize_file_paths(&mut multi.graph, root), nodes);
+        let main = multi.graph.nodes().find(|n| n.name == "main").unwrap();
+        assert_eq!(main.file_path, Path::new("main.py"));
+        assert!(multi.graph.nodes().all(|n| n.file_path.is_relative()));
+    }
+
+    #[test]
+    fn test_namespaced_ids() {
+        let root = Path::new("tests/test-fixtures/multi-file-project");
+        let config = crate::config::Config {
+            relative_ids: true,
+            namespace: Some("billing".to_string()),
+            ..Default::default()
+        };
+
+        let multi = parse_directory_with_config(root, &config).unwrap();
+
+        let main = multi.graph.get_by_id("billing:main.py::main").unwrap();
+        let node = multi.graph.node_weight(main).unwrap();
+        assert_eq!(node.namespace.as_deref(), Some("billing"));
+        assert!(multi.node_locations["billing:main.py::main"].ends_with("main.py"));
+        assert!(multi.graph.nodes().all(|n| n.id.starts_with("billing:")));
+        assert!(multi
+            .unresolved_calls
+            .iter()
+            .all(|call| call.caller.starts_with("billing:")));
+
+        // Applying the same namespace again changes nothing
+        let mut again = multi.clone();
+        assert_eq!(again.namespace_ids("billing"), 0);
+    }
+
+    #[test]
+    fn test_remap_ids_after_file_move() {
+        let root = Path::new("tests/test-fixtures/multi-file-project");
+        let config = crate::config::Config {
+            relative_ids: true,
+            ..Default::default()
+        };
+        let mut multi = parse_directory_with_config(root, &config).unwrap();
+        let ids: Vec<String> = multi.graph.nodes().map(|n| n.id.clone()).collect();
+
+        let map = file_move_map(ids.iter().map(String::as_str), "main.py", "app/main.py");
+        assert!(!map.is_empty());
+        assert_eq!(multi.remap_ids(&map).unwrap(), map.len());
+
+        assert!(multi.graph.get_by_id("app/main.py::main").is_some());
+        assert!(multi.node_locations.contains_key("app/main.py::main"));
+        assert!(!multi.node_locations.contains_key("main.py::main"));
+        assert!(multi
+            .unresolved_calls
+            .iter()
+            .all(|call| !call.caller.starts_with("main.py::")));
+    }
+
+    #[test]
+    fn test_project_relative_id() {
+        let root = Path::new("/work/app");
+
+        assert_eq!(
+            project_relative_id("/work/app/pkg/mod.py::Class::method", root).as_deref(),
+            Some("pkg/mod.py::Class::method")
+        );
+        assert_eq!(project_relative_id("/elsewhere/mod.py::f", root), None);
+    }
+
+    #[test]
+    fn test_parse_files_lenient_skips_bad_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let broken = temp_dir.path().join("broken.py");
+        std::fs::write(&broken, "def broken(:\n    pass\n").unwrap();
+        let missing = temp_dir.path().join("missing.py");
+        let legacy = temp_dir.path().join("legacy.py");
+        std::fs::write(&legacy, b"# caf\xe9\ndef legacy():\n    pass\n").unwrap();
+
+        let files = vec![
+            Path::new("tests/test-fixtures/sample.py"),
+            broken.as_path(),
+            missing.as_path(),
+            legacy.as_path(),
+        ];
+
+        // Strict mode aborts on the missing file
+        assert!(parse_files(&files).is_err());
+
+        let (multi, diagnostics) = parse_files_lenient(&files);
+
+        // sample.py, broken.py and legacy.py are merged; missing.py is skipped
+        assert_eq!(multi.file_nodes.len(), 3);
+        assert_eq!(diagnostics.len(), 3);
+
+        let error = diagnostics.iter().find(|d| d.file == missing).unwrap();
+        assert_eq!(error.severity, Severity::Error);
+        let warning = diagnostics.iter().find(|d| d.file == broken).unwrap();
+        assert_eq!(warning.severity, Severity::Warning);
+        assert!(warning.error.contains("line(s) 1"));
+        let decoded = diagnostics.iter().find(|d| d.file == legacy).unwrap();
+        assert_eq!(decoded.severity, Severity::Warning);
+        assert!(decoded.error.contains("latin-1"));
+        assert!(multi.graph.nodes().any(|node| node.name == "legacy"));
+    }
+
+    #[test]
+    fn test_files_over_limits_are_skipped() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let small = temp_dir.path().join("small.py");
+        std::fs::write(&small, "def f():\n    pass\n").unwrap();
+        let huge = temp_dir.path().join("huge_pb2.py");
+        let body: String = (0..2000)
+            .map(|i| format!("def f{i}():\n    pass\n"))
+            .collect();
+        std::fs::write(&huge, body).unwrap();
+        let files = [small.as_path(), huge.as_path()];
+
+        let skipped = |limits: crate::config::LimitsConfig| {
+            let mut session = ParseSession::uncached().with_limits(limits);
+            let multi = parse_files_in(&mut session, &files).unwrap();
+            assert_eq!(multi.file_nodes.len(), 1);
+            let (_, diagnostics) = parse_files_lenient_in(&mut session, &files);
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0].file, huge);
+            assert_eq!(diagnostics[0].severity, Severity::Error);
+            diagnostics[0].error.clone()
+        };
+        let size = skipped(crate::config::LimitsConfig {
+            max_file_size: Some(1024),
+            ..Default::default()
+        });
+        assert!(size.contains("over the limit of 1024"), "{size}");
+        let time = skipped(crate::config::LimitsConfig {
+            max_parse_ms: Some(0),
+            ..Default::default()
+        });
+        assert!(time.contains("longer than 0 ms"), "{time}");
+        let nodes = skipped(crate::config::LimitsConfig {
+            max_nodes: Some(100),
+            ..Default::default()
+        });
+        assert!(
+            nodes.contains("more than the limit of 100 nodes"),
+            "{nodes}"
+        );
+    }
+
+    #[test]
+    fn test_parse_notebook_with_cell_provenance() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let notebook = temp_dir.path().join("analysis.ipynb");
+        let cells = r#"{"cells": [
+            {"cell_type": "code", "source": ["!pip install pandas\n", "import pandas"]},
+            {"cell_type": "markdown", "source": "Loading"},
+            {"cell_type": "code", "source": ["def load():\n", "    return clean()\n"]},
+            {"cell_type": "code", "source": ["def clean():\n", "    pass"]}
+        ]}"#;
+        std::fs::write(&notebook, cells).unwrap();
+
+        let multi = parse_files(&[notebook.as_path()]).unwrap();
+        let id = |name: &str| {
+            let node = multi.graph.nodes().find(|node| node.name == name).unwrap();
+            multi.graph.get_by_id(&node.id).unwrap()
+        };
+        let load = multi.graph.node_weight(id("load")).unwrap();
+        assert!(load.id.ends_with("analysis.ipynb::load"));
+        assert_eq!(load.line_range, Some((3, 4)));
+        assert!(multi
+            .graph
+            .edges_between(id("load"), id("clean"))
+            .next()
+            .is_some());
+
+        let cells = &multi.notebooks[&notebook];
+        let (cell, line) = cells.locate(5).unwrap();
+        assert_eq!((cell.index, line), (3, 1));
+    }
+
     #[test]
     fn test_empty_file_list() {
         let files: Vec<&Path> = vec![];
@@ -468,4 +2048,28 @@ mod tests {
         assert_eq!(multi.file_nodes.len(), 0);
         assert!(multi.node_locations.is_empty());
     }
+
+    #[test]
+    fn test_generated_files_are_tagged() {
+        let stub = "# Generated by the protocol buffer compiler.  DO NOT EDIT!\n\
+                    class Order:\n    pass\n";
+        let paths = [Path::new("orders_pb2.py"), Path::new("app.py")];
+        let parse = |tag: bool| {
+            let mut session = ParseSession::new().with_generated_tags(tag);
+            session.add_source(paths[0], stub).unwrap();
+            session
+                .add_source(paths[1], "def main():\n    pass\n")
+                .unwrap();
+            parse_files_in(&mut session, &paths).unwrap().graph
+        };
+        let is_tagged = |graph: &crate::Graph, id: &str| {
+            let node = graph.node_weight(graph.get_by_id(id).unwrap()).unwrap();
+            node.attributes.contains_key(generated::GENERATED_ATTRIBUTE)
+        };
+
+        let graph = parse(true);
+        assert!(is_tagged(&graph, "orders_pb2.py::Order"));
+        assert!(!is_tagged(&graph, "app.py::main"));
+        assert!(!is_tagged(&parse(false), "orders_pb2.py::Order"));
+    }
 }
@@ -0,0 +1,165 @@
+//! Jupyter notebooks (`.ipynb`) as Python sources
+//!
+//! Analysis notebooks import and call project code like any module, so they
+//! belong in the dependency graph. A notebook is JSON; [`extract_code()`]
+//! joins its code cells into one Python source, in notebook order, and
+//! records which lines came from which cell. The session parses that text
+//! like a `.py` file, so a notebook's symbols get IDs such as
+//! `analysis.ipynb::load_data` and line ranges in the joined source.
+//! [`NotebookCells::locate()`] turns those lines back into cell positions.
+//!
+//! IPython line magics and shell escapes (`%matplotlib inline`, `!pip
+//! install ...`) are not Python, so they are turned into comments; cell
+//! magics (`%%time`) comment out their whole cell. Markdown and raw cells
+//! are skipped.
+//!
+//! Notebooks are not discovered by default. Add `"**/*.ipynb"` to
+//! `[discovery] include` in `migrator.toml` to scan them.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+/// One code cell's place in the joined source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeCell {
+    /// Position in the notebook's cell list (0-indexed, counting all cells)
+    pub index: usize,
+    /// First line of the cell in the joined source (1-indexed)
+    pub first_line: usize,
+    /// Number of lines the cell contributes
+    pub line_count: usize,
+}
+
+/// Where a notebook's code cells were placed in the joined source
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotebookCells {
+    /// Code cells, in notebook order
+    pub cells: Vec<CodeCell>,
+}
+
+impl NotebookCells {
+    /// The cell containing joined-source `line`, and the line within it
+    ///
+    /// Both lines are 1-indexed. Returns `None` past the last cell.
+    pub fn locate(&self, line: usize) -> Option<(&CodeCell, usize)> {
+        let position = self
+            .cells
+            .partition_point(|cell| cell.first_line + cell.line_count <= line);
+        let cell = self.cells.get(position)?;
+        (line >= cell.first_line).then(|| (cell, line - cell.first_line + 1))
+    }
+}
+
+/// Whether `path` names a notebook (by extension)
+pub fn is_notebook(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "ipynb")
+}
+
+/// Join the code cells of notebook JSON into one Python source
+///
+/// # Errors
+/// Returns a description of the problem if `json` is not an nbformat 4
+/// notebook or its kernel is not Python.
+pub fn extract_code(json: &str) -> std::result::Result<(String, NotebookCells), String> {
+    let notebook: Value =
+        serde_json::from_str(json).map_err(|e| format!("invalid notebook JSON: {}", e))?;
+    if let Some(language) = kernel_language(&notebook) {
+        if !language.eq_ignore_ascii_case("python") {
+            return Err(format!(
+                "notebook kernel language is {}, not Python",
+                language
+            ));
+        }
+    }
+    let cells = notebook
+        .get("cells")
+        .and_then(Value::as_array)
+        .ok_or("notebook has no cells array (nbformat 4 is required)")?;
+
+    let mut source = String::new();
+    let mut layout = NotebookCells::default();
+    let mut next_line = 1;
+    for (index, cell) in cells.iter().enumerate() {
+        if cell.get("cell_type").and_then(Value::as_str) != Some("code") {
+            continue;
+        }
+        let text = cell_source(cell.get("source"))
+            .ok_or_else(|| format!("cell {} has no source text", index))?;
+        let cell_magic = text.trim_start().starts_with("%%");
+
+        let mut line_count = 0;
+        for line in text.lines() {
+            let trimmed = line.trim_start();
+            if cell_magic || trimmed.starts_with('%') || trimmed.starts_with('!') {
+                source.push('#');
+            }
+            source.push_str(line);
+            source.push('\n');
+            line_count += 1;
+        }
+        layout.cells.push(CodeCell {
+            index,
+            first_line: next_line,
+            line_count,
+        });
+        next_line += line_count;
+    }
+    Ok((source, layout))
+}
+
+/// A cell's `source`, which nbformat allows as a string or a list of lines
+fn cell_source(source: Option<&Value>) -> Option<String> {
+    match source? {
+        Value::String(text) => Some(text.clone()),
+        Value::Array(lines) => lines.iter().map(Value::as_str).collect(),
+        _ => None,
+    }
+}
+
+fn kernel_language(notebook: &Value) -> Option<&str> {
+    let metadata = notebook.get("metadata")?;
+    metadata
+        .pointer("/kernelspec/language")
+        .or_else(|| metadata.pointer("/language_info/name"))
+        .and_then(Value::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_code_maps_lines_to_cells() {
+        let json = r##"{
+            "metadata": {"kernelspec": {"language": "python", "name": "python3"}},
+            "nbformat": 4,
+            "cells": [
+                {"cell_type": "markdown", "source": ["# Report"]},
+                {"cell_type": "code", "source": ["%matplotlib inline\n", "import pandas as pd"]},
+                {"cell_type": "code", "source": "def load():\n    return pd.read_csv('x')\n"},
+                {"cell_type": "code", "source": ["%%time\n", "load()"]}
+            ]
+        }"##;
+        let (source, layout) = extract_code(json).unwrap();
+        assert_eq!(
+            source,
+            "#%matplotlib inline\nimport pandas as pd\n\
+             def load():\n    return pd.read_csv('x')\n\
+             #%%time\n#load()\n"
+        );
+        assert_eq!(
+            layout.cells.iter().map(|c| c.index).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+
+        let (cell, line) = layout.locate(4).unwrap();
+        assert_eq!((cell.index, line), (2, 2));
+        assert_eq!(layout.locate(1).unwrap().0.index, 1);
+        assert!(layout.locate(7).is_none());
+
+        let r_kernel = r#"{"metadata": {"kernelspec": {"language": "R"}}, "cells": []}"#;
+        assert!(extract_code(r_kernel).unwrap_err().contains("R"));
+        assert!(extract_code("[]").is_err());
+    }
+}
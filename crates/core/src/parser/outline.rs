@@ -0,0 +1,344 @@
+//! Declaration outlines of Rust and TypeScript files
+//!
+//! Python code being migrated often sits next to a Rust extension or a
+//! TypeScript frontend. Those files are not analysed in depth: pass 1
+//! records their top-level functions and types, and the methods of those
+//! types, so they are tracked and reported on like the Python symbols.
+//! Calls, imports and type references are not extracted, so they have no
+//! dependency edges.
+//!
+//! Node types follow the declarations: Rust structs, enums and unions are
+//! `Struct`s and traits are `Interface`s; TypeScript classes are `Class`es,
+//! interfaces are `Interface`s, and `const f = () => ...` is a `Function`.
+//! Methods are Rust `impl` block functions and TypeScript class methods,
+//! with IDs like `lib.rs::Parser.next`.
+
+use super::python::{collect_error_lines, file_node};
+use super::{FileParseResult, GranularityLevel, Language, ParseSession};
+use crate::error::Result;
+use crate::graph::{Edge, EdgeType, Graph, Node, NodeType};
+use std::path::Path;
+
+/// A named declaration found in the syntax tree
+struct Declaration<'tree> {
+    name: String,
+    node_type: NodeType,
+    node: tree_sitter::Node<'tree>,
+}
+
+/// A method, with the name of the type it belongs to
+struct Method<'tree> {
+    owner: String,
+    declaration: Declaration<'tree>,
+}
+
+/// Pass 1 on a Rust or TypeScript file, as parsed by `session`
+pub(crate) fn parse_file_in(session: &mut ParseSession, path: &Path) -> Result<FileParseResult> {
+    let _span = tracing::debug_span!("parse_file", path = %path.display()).entered();
+    let level = session.granularity();
+    let edge_policy = session.edge_policy();
+    let max_nodes = session.limits().max_nodes;
+
+    let file = session.parse(path)?;
+    let language = file.language;
+    let file_path = file.path.as_path();
+    let root_node = file.tree.root_node();
+    let source = file.source.as_bytes();
+
+    let (declarations, methods) = match language {
+        Language::Rust => rust_outline(&root_node, source),
+        Language::TypeScript => typescript_outline(&root_node, source),
+        Language::Python => Default::default(),
+    };
+
+    let mut graph = Graph::new().with_edge_policy(edge_policy);
+    let file_idx = level.map(|_| graph.add_node(file_node(file_path, &root_node, language.name())));
+    for declaration in declarations {
+        let wanted = match (level, &declaration.node_type) {
+            (None, _) => true,
+            (Some(level), NodeType::Function) => level >= GranularityLevel::Functions,
+            (Some(level), _) => level >= GranularityLevel::Classes,
+        };
+        let id = format!("{}::{}", file_path.display(), declaration.name);
+        // Keep the first of several declarations with one name, like the Python parser
+        if !wanted || graph.get_by_id(&id).is_some() {
+            continue;
+        }
+        let idx = graph.add_node(symbol(id, &declaration, language, file_path));
+        if let Some(file_idx) = file_idx {
+            graph.add_edge(file_idx, idx, Edge::new(EdgeType::Contains));
+        }
+        super::check_node_count(path, &graph, max_nodes)?;
+    }
+    if level >= Some(GranularityLevel::Methods) {
+        for method in methods {
+            let owner_id = format!("{}::{}", file_path.display(), method.owner);
+            let Some(owner_idx) = graph.get_by_id(&owner_id) else {
+                continue;
+            };
+            let id = format!("{}.{}", owner_id, method.declaration.name);
+            if graph.get_by_id(&id).is_some() {
+                continue;
+            }
+            let idx = graph.add_node(symbol(id, &method.declaration, language, file_path));
+            graph.add_edge(owner_idx, idx, Edge::new(EdgeType::Contains));
+            super::check_node_count(path, &graph, max_nodes)?;
+        }
+    }
+
+    let error_lines = if root_node.has_error() {
+        collect_error_lines(&root_node)
+    } else {
+        Vec::new()
+    };
+
+    tracing::trace!(
+        %language,
+        nodes = graph.node_count(),
+        syntax_errors = error_lines.len(),
+        "outlined file"
+    );
+    Ok(FileParseResult {
+        path: path.to_path_buf(),
+        language,
+        graph,
+        error_lines,
+        unresolved_calls: Vec::new(),
+        type_references: Vec::new(),
+        type_aliases: Default::default(),
+        imports: Vec::new(),
+        exports: None,
+        encoding: file.encoding,
+        notebook: None,
+    })
+}
+
+/// The graph node of `declaration`
+fn symbol(id: String, declaration: &Declaration, language: Language, file_path: &Path) -> Node {
+    let name = declaration.name.clone();
+    let node_type = declaration.node_type.clone();
+    let mut node = Node::new(id, name, node_type, language.name(), file_path);
+    // 1-indexed, inclusive, like the Python parser's ranges
+    node.line_range = Some((
+        declaration.node.start_position().row + 1,
+        declaration.node.end_position().row + 1,
+    ));
+    node
+}
+
+/// Text of `node`'s child in `field`
+fn field_text(node: &tree_sitter::Node, field: &str, source: &[u8]) -> Option<String> {
+    let child = node.child_by_field_name(field)?;
+    child.utf8_text(source).ok().map(str::to_string)
+}
+
+/// A declaration named by `node`'s `name` field, if it has one
+fn declaration<'tree>(
+    node: tree_sitter::Node<'tree>,
+    node_type: NodeType,
+    source: &[u8],
+) -> Option<Declaration<'tree>> {
+    Some(Declaration {
+        name: field_text(&node, "name", source)?,
+        node_type,
+        node,
+    })
+}
+
+/// Top-level items of a Rust file, and the functions of its `impl` blocks
+fn rust_outline<'tree>(
+    root_node: &tree_sitter::Node<'tree>,
+    source: &[u8],
+) -> (Vec<Declaration<'tree>>, Vec<Method<'tree>>) {
+    let mut declarations = Vec::new();
+    let mut methods = Vec::new();
+    let mut cursor = root_node.walk();
+    for item in root_node.named_children(&mut cursor) {
+        let node_type = match item.kind() {
+            "function_item" => NodeType::Function,
+            "struct_item" | "enum_item" | "union_item" => NodeType::Struct,
+            "trait_item" => NodeType::Interface,
+            "impl_item" => {
+                methods.extend(rust_impl_methods(item, source));
+                continue;
+            }
+            _ => continue,
+        };
+        declarations.extend(declaration(item, node_type, source));
+    }
+    (declarations, methods)
+}
+
+/// Functions of an `impl` block, owned by the implementing type
+///
+/// Generic arguments are dropped from the type, so `impl<T> Stack<T>` and
+/// `impl Display for Stack<u8>` both add methods to `Stack`.
+fn rust_impl_methods<'tree>(item: tree_sitter::Node<'tree>, source: &[u8]) -> Vec<Method<'tree>> {
+    let Some(owner) = field_text(&item, "type", source) else {
+        return Vec::new();
+    };
+    let owner = owner
+        .split('<')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    let Some(body) = item.child_by_field_name("body") else {
+        return Vec::new();
+    };
+    let mut cursor = body.walk();
+    body.named_children(&mut cursor)
+        .filter(|child| child.kind() == "function_item")
+        .filter_map(|child| declaration(child, NodeType::Method, source))
+        .map(|declaration| Method {
+            owner: owner.clone(),
+            declaration,
+        })
+        .collect()
+}
+
+/// Top-level declarations of a TypeScript file, exported or not, and the
+/// methods of its classes
+fn typescript_outline<'tree>(
+    root_node: &tree_sitter::Node<'tree>,
+    source: &[u8],
+) -> (Vec<Declaration<'tree>>, Vec<Method<'tree>>) {
+    let mut declarations = Vec::new();
+    let mut methods = Vec::new();
+    let mut cursor = root_node.walk();
+    for statement in root_node.named_children(&mut cursor) {
+        let item = if statement.kind() == "export_statement" {
+            match statement.child_by_field_name("declaration") {
+                Some(declaration) => declaration,
+                None => continue,
+            }
+        } else {
+            statement
+        };
+        match item.kind() {
+            "function_declaration" | "generator_function_declaration" => {
+                declarations.extend(declaration(item, NodeType::Function, source));
+            }
+            "interface_declaration" => {
+                declarations.extend(declaration(item, NodeType::Interface, source));
+            }
+            "class_declaration" | "abstract_class_declaration" => {
+                let Some(class) = declaration(item, NodeType::Class, source) else {
+                    continue;
+                };
+                methods.extend(typescript_class_methods(item, &class.name, source));
+                declarations.push(class);
+            }
+            "lexical_declaration" => {
+                declarations.extend(typescript_function_constants(item, source));
+            }
+            _ => {}
+        }
+    }
+    (declarations, methods)
+}
+
+/// Methods in the body of a class
+fn typescript_class_methods<'tree>(
+    class: tree_sitter::Node<'tree>,
+    owner: &str,
+    source: &[u8],
+) -> Vec<Method<'tree>> {
+    let Some(body) = class.child_by_field_name("body") else {
+        return Vec::new();
+    };
+    let mut cursor = body.walk();
+    body.named_children(&mut cursor)
+        .filter(|member| member.kind() == "method_definition")
+        .filter_map(|member| declaration(member, NodeType::Method, source))
+        .map(|declaration| Method {
+            owner: owner.to_string(),
+            declaration,
+        })
+        .collect()
+}
+
+/// Variables of a `const`/`let` declaration that hold a function
+/// (`const total = (items: Item[]) => ...`)
+fn typescript_function_constants<'tree>(
+    item: tree_sitter::Node<'tree>,
+    source: &[u8],
+) -> Vec<Declaration<'tree>> {
+    let mut cursor = item.walk();
+    item.named_children(&mut cursor)
+        .filter(|declarator| declarator.kind() == "variable_declarator")
+        .filter(|declarator| {
+            declarator
+                .child_by_field_name("value")
+                .is_some_and(|value| {
+                    matches!(value.kind(), "arrow_function" | "function_expression")
+                })
+        })
+        .filter_map(|declarator| declaration(declarator, NodeType::Function, source))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    #[test]
+    fn test_mixed_languages_parse_in_one_run() {
+        let mut session = ParseSession::new().with_granularity(Some(GranularityLevel::Methods));
+        session
+            .add_source(
+                Path::new("app.py"),
+                "class Invoice:\n    def total(self):\n        pass\n",
+            )
+            .unwrap();
+        session
+            .add_source(
+                Path::new("src/lib.rs"),
+                "pub struct Stack<T>(Vec<T>);\n\nimpl<T> Stack<T> {\n    pub fn push(&mut self) {}\n}\n\
+                 \npub trait Shape {}\n\nfn helper() {}\n",
+            )
+            .unwrap();
+        session
+            .add_source(
+                Path::new("web/cart.tsx"),
+                "export interface Item { price: number }\n\
+                 export class Cart {\n  add(item: Item) { return <li>{item.price}</li>; }\n}\n\
+                 export const total = (items: Item[]) => 0;\nfunction render() {}\n",
+            )
+            .unwrap();
+        let paths = [
+            Path::new("app.py"),
+            Path::new("src/lib.rs"),
+            Path::new("web/cart.tsx"),
+        ];
+        let multi = parser::parse_files_in(&mut session, &paths).unwrap();
+
+        let node = |id: &str| {
+            let idx = multi
+                .graph
+                .get_by_id(id)
+                .unwrap_or_else(|| panic!("no node {}", id));
+            multi.graph.node_weight(idx).unwrap().clone()
+        };
+        assert_eq!(node("src/lib.rs::Stack").node_type, NodeType::Struct);
+        assert_eq!(node("src/lib.rs::Stack.push").node_type, NodeType::Method);
+        assert_eq!(node("src/lib.rs::Shape").node_type, NodeType::Interface);
+        assert_eq!(node("src/lib.rs::helper").line_range, Some((9, 9)));
+        assert_eq!(node("web/cart.tsx::Item").node_type, NodeType::Interface);
+        assert_eq!(node("web/cart.tsx::Cart.add").language, "typescript");
+        assert_eq!(node("web/cart.tsx::total").node_type, NodeType::Function);
+        assert_eq!(node("web/cart.tsx::render").node_type, NodeType::Function);
+
+        let stats = multi.language_stats();
+        assert_eq!(stats.len(), 3);
+        // Files, plus classes, functions and methods
+        assert_eq!(stats[&Language::Python].nodes, 3);
+        assert_eq!(stats[&Language::Rust].nodes, 5);
+        assert_eq!(stats[&Language::TypeScript].nodes, 6);
+        assert!(stats.values().all(|stats| stats.files == 1));
+        // Only the Python file has syntax the import pass understands
+        let imports = crate::import::extract_imports_in(&mut session, paths[1]).unwrap();
+        assert!(imports.is_empty());
+    }
+}
@@ -1,14 +1,21 @@
 //! Python parser using tree-sitter
 //!
 //! This module parses Python source files and extracts top-level
-//! functions and classes into graph nodes.
+//! functions and classes into graph nodes. A session's
+//! [`GranularityLevel`] can narrow this to files and classes, or widen it
+//! to a file node and the methods of top-level classes.
 
-use tree_sitter::{Parser as TsParser};
-use tree_sitter_python::LANGUAGE;
+use crate::error::Result;
 use crate::graph::{Edge, EdgeType, Graph, Node, NodeType};
-use std::collections::HashMap;
-use std::path::Path;
+use crate::import::{extract_exports_from_tree, extract_imports_from_tree, ImportStatement};
+use crate::parser::{GranularityLevel, Language, NotebookCells, ParseSession, SourceEncoding};
+use crate::parser::{TypeReference, UnresolvedCall};
 use petgraph::stable_graph::NodeIndex;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tree_sitter::{Query, QueryCursor, StreamingIterator};
+use tree_sitter_python::LANGUAGE;
 
 /// Parse a Python source file and extract its structure
 ///
@@ -17,29 +24,136 @@ use petgraph::stable_graph::NodeIndex;
 ///
 /// # Returns
 /// A `Graph` containing nodes for extracted functions and classes
-pub fn parse_file(path: &Path) -> anyhow::Result<Graph> {
-    // 1. Canonicalize path for stable node IDs (prevents duplicate IDs from relative/absolute paths)
-    let canonical_path = std::fs::canonicalize(path)?;
+pub fn parse_file(path: &Path) -> Result<Graph> {
+    parse_file_with_errors(path).map(|(graph, _)| graph)
+}
+
+/// Parse a Python source file, also reporting recoverable syntax errors
+///
+/// Tree-sitter always produces a tree, inserting `ERROR`/`MISSING` nodes
+/// where the source is malformed. Extraction still runs on the valid parts;
+/// the returned line numbers (1-indexed, ascending) tell callers where the
+/// graph may be incomplete.
+///
+/// # Errors
+/// Returns an error only if the file cannot be read or parsed at all.
+pub fn parse_file_with_errors(path: &Path) -> Result<(Graph, Vec<usize>)> {
+    parse_file_detailed(path).map(|parsed| (parsed.graph, parsed.error_lines))
+}
+
+/// Parse a Python source file into its symbols and its imports at once
+///
+/// Equivalent to [`parse_file()`] plus
+/// [`import::extract_imports()`](crate::import::extract_imports()), but the
+/// file is read and parsed only once.
+///
+/// # Errors
+/// Returns an error if the file cannot be read or parsed at all.
+pub fn parse_file_full(path: &Path) -> Result<(Graph, Vec<ImportStatement>)> {
+    parse_file_detailed(path).map(|parsed| (parsed.graph, parsed.imports))
+}
+
+/// Parse Python `source` as the contents of `virtual_path`, without
+/// touching the filesystem
+///
+/// For editor buffers, tests and source read from stdin. `virtual_path`
+/// only names the file: node IDs are built from it as given (it is not
+/// canonicalized) and it need not exist.
+///
+/// # Errors
+/// Returns an error if tree-sitter fails to parse the source at all.
+///
+/// # Example
+/// ```
+/// use graph_migrator_core::parser::python;
+/// use std::path::Path;
+///
+/// let graph = python::parse_source("class Cart:\n    pass\n", Path::new("shop/cart.py")).unwrap();
+/// assert!(graph.get_by_id("shop/cart.py::Cart").is_some());
+/// ```
+pub fn parse_source(source: &str, virtual_path: &Path) -> Result<Graph> {
+    parse_source_detailed(source, virtual_path).map(|parsed| parsed.graph)
+}
+
+/// [`parse_file_full()`] for in-memory source, see [`parse_source()`]
+///
+/// # Errors
+/// Returns an error if tree-sitter fails to parse the source at all.
+pub fn parse_source_full(
+    source: &str,
+    virtual_path: &Path,
+) -> Result<(Graph, Vec<ImportStatement>)> {
+    parse_source_detailed(source, virtual_path).map(|parsed| (parsed.graph, parsed.imports))
+}
 
-    // 2. Read file contents to String
-    let source = std::fs::read_to_string(&canonical_path)?;
+/// Everything pass 1 extracts from a single file
+#[derive(Debug)]
+pub struct FileParseResult {
+    /// The file, as passed to the parser
+    pub path: PathBuf,
+    /// Language the file was parsed in
+    pub language: Language,
+    /// Symbols and same-file `Calls` and `References` edges
+    pub graph: Graph,
+    /// Lines where tree-sitter recovered from syntax errors
+    pub error_lines: Vec<usize>,
+    /// Calls whose target is not defined in this file, kept for pass 2
+    pub unresolved_calls: Vec<UnresolvedCall>,
+    /// Annotated types not defined in this file, kept for pass 2
+    pub type_references: Vec<TypeReference>,
+    /// Module-level type aliases, each with the type names it refers to
+    pub type_aliases: HashMap<String, Vec<String>>,
+    /// Import statements at any scope, from the same syntax tree
+    pub imports: Vec<ImportStatement>,
+    /// Names listed in the module's `__all__`, if it declares one
+    pub exports: Option<Vec<String>>,
+    /// How the file's bytes were decoded
+    pub encoding: SourceEncoding,
+    /// For a notebook, where each code cell sits in the parsed source
+    pub notebook: Option<NotebookCells>,
+}
 
-    // 3. Create tree-sitter parser
-    let mut parser = TsParser::new();
-    parser.set_language(&LANGUAGE.into())?;
+/// Parse a file, keeping the unresolved calls that [`parse_file_with_errors()`] drops
+///
+/// The file is parsed as Python whatever its extension.
+pub(crate) fn parse_file_detailed(path: &Path) -> Result<FileParseResult> {
+    parse_file_in(&mut ParseSession::for_file(path, Language::Python), path)
+}
 
-    // 4. Parse source code
-    let tree = parser.parse(&source, None)
-        .ok_or_else(|| anyhow::anyhow!("Failed to parse Python file: {}", canonical_path.display()))?;
+/// [`parse_file_detailed()`] for in-memory source, see [`parse_source()`]
+pub(crate) fn parse_source_detailed(source: &str, virtual_path: &Path) -> Result<FileParseResult> {
+    let mut session = ParseSession::for_file(virtual_path, Language::Python);
+    session.add_source(virtual_path, source)?;
+    parse_file_in(&mut session, virtual_path)
+}
 
-    // 5. Extract top-level nodes only (functions and classes)
-    let root_node = tree.root_node();
-    let source_bytes = source.as_bytes();
-    let nodes = extract_top_level_nodes(&root_node, &canonical_path, source_bytes);
+/// [`parse_file_detailed()`] with the parser and source cache of `session`
+pub(crate) fn parse_file_in(session: &mut ParseSession, path: &Path) -> Result<FileParseResult> {
+    let _span = tracing::debug_span!("parse_file", path = %path.display()).entered();
+    let level = session.granularity();
+    let edge_policy = session.edge_policy();
+    let max_nodes = session.limits().max_nodes;
 
-    // 6. Build graph with nodes
-    let mut graph = Graph::new();
+    // 1-4. Canonicalize path for stable node IDs (prevents duplicate IDs from
+    // relative/absolute paths), then read and parse it, or reuse the session's tree
+    let file = session.parse(path)?;
+    let canonical_path = file.path.clone();
+
+    // 5. Extract top-level nodes only (functions and classes), as deep as the level allows
+    let root_node = file.tree.root_node();
+    let source_bytes = file.source.as_bytes();
+    let nodes = extract_top_level_nodes(&root_node, &canonical_path, source_bytes)
+        .into_iter()
+        .filter(|node| match (level, &node.node_type) {
+            (None, _) => true,
+            (Some(level), NodeType::Class) => level >= GranularityLevel::Classes,
+            (Some(level), _) => level >= GranularityLevel::Functions,
+        });
+
+    // 6. Build graph with nodes, inside a file node when a level is set
+    let mut graph = Graph::new().with_edge_policy(edge_policy);
     let mut node_map: HashMap<(std::path::PathBuf, String), NodeIndex> = HashMap::new();
+    let file_idx = level.map(|_| graph.add_node(file_node(&canonical_path, &root_node, "python")));
 
     for node in nodes {
         // Clone the fields we need for the key before moving node
@@ -49,22 +163,138 @@ pub fn parse_file(path: &Path) -> anyhow::Result<Graph> {
         // Use (file_path, name) as key for file-scoped resolution
         // Use .entry().or_insert() to keep the FIRST definition for duplicate names
         node_map.entry((file_path, name)).or_insert(idx);
+        if let Some(file_idx) = file_idx {
+            graph.add_edge(file_idx, idx, Edge::new(EdgeType::Contains));
+        }
+        super::check_node_count(path, &graph, max_nodes)?;
     }
+    let methods = if level >= Some(GranularityLevel::Methods) {
+        add_methods(
+            &mut graph,
+            &root_node,
+            &canonical_path,
+            source_bytes,
+            &node_map,
+        )
+    } else {
+        HashMap::new()
+    };
+    super::check_node_count(path, &graph, max_nodes)?;
 
     // 7. Extract and add calls edges
-    let edges = extract_calls_edges(&root_node, &canonical_path, source_bytes, &node_map);
+    let edges_wanted = level.is_none_or(|level| level >= GranularityLevel::Calls);
+    let (edges, unresolved) = if edges_wanted {
+        extract_calls_edges(
+            &root_node,
+            &canonical_path,
+            source_bytes,
+            &node_map,
+            &methods,
+        )
+    } else {
+        Default::default()
+    };
     for (from, to) in edges {
-        graph.add_edge(from, to, Edge { edge_type: EdgeType::Calls });
+        graph.add_edge(from, to, Edge::new(EdgeType::Calls));
+    }
+    let unresolved_calls = unresolved
+        .into_iter()
+        .filter_map(|(caller, callee, line)| {
+            Some(UnresolvedCall {
+                caller: graph.node_weight(caller)?.id.clone(),
+                callee,
+                file: canonical_path.clone(),
+                line,
+            })
+        })
+        .collect();
+
+    // 8. Link functions to the classes named in their annotations
+    let annotations = if edges_wanted {
+        extract_type_references(&root_node, &canonical_path, source_bytes, &node_map)
+    } else {
+        Annotations::default()
+    };
+    for (from, to) in annotations.edges {
+        graph.add_edge(from, to, Edge::new(EdgeType::References));
     }
+    let type_references = annotations
+        .pending
+        .into_iter()
+        .filter_map(|(function, name, line)| {
+            Some(TypeReference {
+                function: graph.node_weight(function)?.id.clone(),
+                name,
+                file: canonical_path.clone(),
+                line,
+            })
+        })
+        .collect();
+
+    // 9. Collect imports from the same tree, so pass 2 needn't parse the file again
+    let imports = extract_imports_from_tree(&root_node, source_bytes);
+    let exports = extract_exports_from_tree(&root_node, source_bytes);
 
-    Ok(graph)
+    // 10. Record where tree-sitter had to recover from malformed source
+    let error_lines = if root_node.has_error() {
+        collect_error_lines(&root_node)
+    } else {
+        Vec::new()
+    };
+
+    tracing::trace!(
+        nodes = graph.node_count(),
+        edges = graph.edge_count(),
+        syntax_errors = error_lines.len(),
+        "parsed file"
+    );
+    Ok(FileParseResult {
+        path: path.to_path_buf(),
+        language: Language::Python,
+        graph,
+        error_lines,
+        unresolved_calls,
+        type_references,
+        type_aliases: annotations.aliases,
+        imports,
+        exports,
+        encoding: file.encoding,
+        notebook: file.notebook.clone(),
+    })
+}
+
+/// Collect 1-indexed start lines of `ERROR` and `MISSING` nodes
+///
+/// Does not descend into error nodes, so one malformed region is reported once.
+pub(super) fn collect_error_lines(root_node: &tree_sitter::Node) -> Vec<usize> {
+    let mut lines = Vec::new();
+    let mut stack = vec![*root_node];
+
+    while let Some(node) = stack.pop() {
+        if node.is_error() || node.is_missing() {
+            lines.push(node.start_position().row + 1);
+            continue;
+        }
+        if node.has_error() {
+            let mut cursor = node.walk();
+            stack.extend(node.children(&mut cursor));
+        }
+    }
+
+    lines.sort_unstable();
+    lines.dedup();
+    lines
 }
 
 /// Extract top-level function and class definitions from the syntax tree
 ///
 /// Only iterates over direct children of the root node, ensuring we only
 /// extract top-level definitions and not nested functions/classes.
-fn extract_top_level_nodes(root_node: &tree_sitter::Node, file_path: &Path, source: &[u8]) -> Vec<Node> {
+fn extract_top_level_nodes(
+    root_node: &tree_sitter::Node,
+    file_path: &Path,
+    source: &[u8],
+) -> Vec<Node> {
     let mut nodes = Vec::new();
     let mut cursor = root_node.walk();
 
@@ -77,20 +307,99 @@ fn extract_top_level_nodes(root_node: &tree_sitter::Node, file_path: &Path, sour
         };
 
         if let (Some(node_type), Some(name)) = (node_type_opt, name_opt) {
-            nodes.push(Node {
-                id: format!("{}::{}", file_path.display(), name),
+            let mut symbol = Node::new(
+                format!("{}::{}", file_path.display(), name),
                 name,
                 node_type,
-                language: "python".to_string(),
-                file_path: file_path.to_path_buf(),
-                line_range: None,
-            });
+                "python",
+                file_path,
+            );
+            // 1-indexed, inclusive, like the lines in diagnostics
+            symbol.line_range = Some((node.start_position().row + 1, node.end_position().row + 1));
+            nodes.push(symbol);
         }
     }
 
     nodes
 }
 
+/// The `File` node of `file_path`, spanning the whole file
+pub(super) fn file_node(file_path: &Path, root_node: &tree_sitter::Node, language: &str) -> Node {
+    let name = file_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut node = Node::new(
+        format!("{}::file:{}", file_path.display(), name),
+        name,
+        NodeType::File,
+        language,
+        file_path,
+    );
+    node.line_range = Some((1, root_node.end_position().row + 1));
+    node
+}
+
+/// Add a `Method` node for each method of the top-level classes in `node_map`
+///
+/// Methods get IDs like `app.py::Invoice.total` and a `Contains` edge from
+/// their class. They are not added to `node_map`, so bare calls never
+/// resolve to them. Returns the method nodes by the start byte of their
+/// definition, for attributing the calls made inside them.
+fn add_methods(
+    graph: &mut Graph,
+    root_node: &tree_sitter::Node,
+    file_path: &Path,
+    source: &[u8],
+    node_map: &HashMap<(PathBuf, String), NodeIndex>,
+) -> HashMap<usize, NodeIndex> {
+    let file_path = file_path.to_path_buf();
+    let mut methods = HashMap::new();
+    let mut cursor = root_node.walk();
+    for class in root_node.children(&mut cursor) {
+        if class.kind() != "class_definition" {
+            continue;
+        }
+        let Some(class_idx) = extract_node_name(&class, source)
+            .and_then(|name| node_map.get(&(file_path.clone(), name)).copied())
+        else {
+            continue;
+        };
+        // A duplicate class name keeps the first definition, like node_map
+        let Some(owner) = graph.node_weight(class_idx).cloned() else {
+            continue;
+        };
+        if owner.line_range.map(|(start, _)| start) != Some(class.start_position().row + 1) {
+            continue;
+        }
+        let Some(body) = class.child_by_field_name("body") else {
+            continue;
+        };
+        let mut body_cursor = body.walk();
+        for method in body.children(&mut body_cursor) {
+            if method.kind() != "function_definition" {
+                continue;
+            }
+            let Some(name) = extract_node_name(&method, source) else {
+                continue;
+            };
+            let id = format!("{}.{}", owner.id, name);
+            if graph.get_by_id(&id).is_some() {
+                continue;
+            }
+            let mut node = Node::new(id, name, NodeType::Method, "python", &owner.file_path);
+            node.line_range = Some((
+                method.start_position().row + 1,
+                method.end_position().row + 1,
+            ));
+            let idx = graph.add_node(node);
+            graph.add_edge(class_idx, idx, Edge::new(EdgeType::Contains));
+            methods.insert(method.start_byte(), idx);
+        }
+    }
+    methods
+}
+
 /// Extract the name from a function_definition or class_definition node
 ///
 /// Uses tree-sitter's named field API to robustly extract the "name" field.
@@ -100,59 +409,291 @@ fn extract_node_name(node: &tree_sitter::Node, source: &[u8]) -> Option<String>
         .map(|s| s.to_string())
 }
 
+/// Call that did not resolve within its file: (caller, callee name, line)
+type PendingCall = (NodeIndex, String, usize);
+
+/// Named function definitions and call sites, matched in one pass
+const CALLS_QUERY: &str = r#"
+(function_definition name: (identifier) @function.name) @function
+(call) @call
+"#;
+
+/// [`CALLS_QUERY`], compiled once, with its capture indices
+struct CallsQuery {
+    query: Query,
+    function: u32,
+    function_name: u32,
+    call: u32,
+}
+
+fn calls_query() -> &'static CallsQuery {
+    static QUERY: OnceLock<CallsQuery> = OnceLock::new();
+    QUERY.get_or_init(|| {
+        let query = Query::new(&LANGUAGE.into(), CALLS_QUERY).expect("calls query is valid");
+        let index = |name| {
+            query
+                .capture_index_for_name(name)
+                .expect("capture is defined")
+        };
+        CallsQuery {
+            function: index("function"),
+            function_name: index("function.name"),
+            call: index("call"),
+            query,
+        }
+    })
+}
+
 /// Extract calls edges from the syntax tree
 ///
-/// Walks the AST to find `call` nodes and creates edges between
-/// caller and callee functions. Only creates edges within the same file
-/// using file-scoped resolution.
+/// Runs [`CALLS_QUERY`] over the tree, taking captures in document order so
+/// the enclosing functions of each call are a stack: a definition is pushed
+/// when it starts and popped once a later capture starts past its end. The
+/// caller is the innermost named definition; calls inside nested functions
+/// have no caller node and are skipped, as are module-level calls. Calls
+/// inside methods are attributed to the method if it is in `methods` (by
+/// definition start byte), and skipped otherwise.
+///
+/// Only creates edges within the same file using file-scoped resolution;
+/// calls that don't resolve locally are returned as [`PendingCall`]s for
+/// cross-file binding.
 fn extract_calls_edges(
     root_node: &tree_sitter::Node,
     file_path: &Path,
     source: &[u8],
     node_map: &HashMap<(std::path::PathBuf, String), NodeIndex>,
-) -> Vec<(NodeIndex, NodeIndex)> {
+    methods: &HashMap<usize, NodeIndex>,
+) -> (Vec<(NodeIndex, NodeIndex)>, Vec<PendingCall>) {
+    let calls = calls_query();
     let mut edges = Vec::new();
-    let mut cursor = root_node.walk();
+    let mut unresolved = Vec::new();
+    // (end byte, caller) of each definition enclosing the current capture
+    let mut enclosing: Vec<(usize, Option<NodeIndex>)> = Vec::new();
     // Create PathBuf once for cheaper clone() in loop (avoid repeated to_path_buf())
     let file_path_buf = file_path.to_path_buf();
 
-    // Walk the entire tree using tree-sitter's cursor traversal
-    loop {
-        let node = cursor.node();
-
-        if node.kind() == "call" {
-            // Extract the function name being called
-            if let Some(callee_name) = extract_call_name(&node, source) {
-                // Find the parent function_definition (caller)
-                if let Some(caller_idx) = find_parent_function(&node, root_node, source, &file_path_buf, node_map) {
-                    // Look up the callee in the node map (same file only)
-                    let key = (file_path_buf.clone(), callee_name);
-                    if let Some(&callee_idx) = node_map.get(&key) {
-                        edges.push((caller_idx, callee_idx));
-                    }
-                    // Unresolved calls are silently skipped (best-effort)
+    let mut cursor = QueryCursor::new();
+    let mut captures = cursor.captures(&calls.query, *root_node, source);
+    while let Some((found, capture_index)) = captures.next() {
+        let capture = found.captures[*capture_index];
+        let node = capture.node;
+        while enclosing
+            .last()
+            .is_some_and(|&(end, _)| end <= node.start_byte())
+        {
+            enclosing.pop();
+        }
+
+        if capture.index == calls.function {
+            enclosing.push((node.end_byte(), methods.get(&node.start_byte()).copied()));
+        } else if capture.index == calls.function_name {
+            // Captured right after its definition, before any call in the body
+            let top = enclosing.last_mut().filter(|top| top.1.is_none());
+            if let (Some(top), Ok(name)) = (top, node.utf8_text(source)) {
+                top.1 = node_map
+                    .get(&(file_path_buf.clone(), name.to_string()))
+                    .copied();
+            }
+        } else if capture.index == calls.call {
+            let Some(caller_idx) = enclosing.last().and_then(|&(_, caller)| caller) else {
+                continue;
+            };
+            let Some(callee_name) = extract_call_name(&node, source) else {
+                continue;
+            };
+            // Look up the callee in the node map (same file only)
+            let key = (file_path_buf.clone(), callee_name);
+            if let Some(&callee_idx) = node_map.get(&key) {
+                edges.push((caller_idx, callee_idx));
+            } else {
+                // Left for pass 2, which knows this file's imports
+                unresolved.push((caller_idx, key.1, node.start_position().row + 1));
+            }
+        }
+    }
+
+    (edges, unresolved)
+}
+
+/// Annotated type not defined in the file: (function, name, line)
+type PendingReference = (NodeIndex, String, usize);
+
+/// What [`extract_type_references()`] found in one file
+#[derive(Default)]
+struct Annotations {
+    /// Function → class defined in the same file
+    edges: Vec<(NodeIndex, NodeIndex)>,
+    /// Names left for pass 2, which knows this file's imports
+    pending: Vec<PendingReference>,
+    /// Module-level type aliases and the names in their definitions
+    aliases: HashMap<String, Vec<String>>,
+}
+
+/// Extract the types named in the annotations of top-level functions
+///
+/// Parameter and return annotations are searched for type names, including
+/// inside generics (`list[Invoice]`) and string forward references
+/// (`"Invoice"`). Module-level aliases (`Money: TypeAlias = Decimal | int`,
+/// `type Money = Decimal | int`) are replaced by the names they refer to.
+/// Classes of the same file become edges, other names defined in the file
+/// are dropped, and the rest are returned as pending.
+fn extract_type_references(
+    root_node: &tree_sitter::Node,
+    file_path: &Path,
+    source: &[u8],
+    node_map: &HashMap<(PathBuf, String), NodeIndex>,
+) -> Annotations {
+    let mut aliases = HashMap::new();
+    let mut classes = HashSet::new();
+    let mut functions = Vec::new();
+    let mut cursor = root_node.walk();
+    for node in root_node.children(&mut cursor) {
+        match node.kind() {
+            "function_definition" => functions.push(node),
+            "class_definition" => classes.extend(extract_node_name(&node, source)),
+            // `type Pair[T] = tuple[T, T]` names the alias `Pair`
+            "type_alias_statement" => {
+                let mut alias = Vec::new();
+                let mut targets = Vec::new();
+                if let Some(left) = node.child_by_field_name("left") {
+                    collect_type_names(&left, source, &mut alias);
+                }
+                if let Some(right) = node.child_by_field_name("right") {
+                    collect_type_names(&right, source, &mut targets);
+                }
+                if let Some(alias) = alias.into_iter().next() {
+                    aliases.insert(alias, targets);
+                }
+            }
+            "expression_statement" => {
+                if let Some((alias, targets)) = type_alias_assignment(&node, source) {
+                    aliases.insert(alias, targets);
                 }
             }
+            _ => {}
         }
+    }
 
-        // Depth-first traversal: try children first, then siblings, then go up
-        if cursor.goto_first_child() {
+    let file_path = file_path.to_path_buf();
+    let mut edges = Vec::new();
+    let mut pending = Vec::new();
+    for function in functions {
+        let Some(function_idx) = extract_node_name(&function, source)
+            .and_then(|name| node_map.get(&(file_path.clone(), name)).copied())
+        else {
             continue;
+        };
+        let mut annotations = Vec::new();
+        if let Some(parameters) = function.child_by_field_name("parameters") {
+            let mut cursor = parameters.walk();
+            annotations.extend(
+                parameters
+                    .named_children(&mut cursor)
+                    .filter_map(|parameter| parameter.child_by_field_name("type")),
+            );
         }
-        if cursor.goto_next_sibling() {
-            continue;
+        annotations.extend(function.child_by_field_name("return_type"));
+
+        // Each type once per function, at its first annotation
+        let mut seen = BTreeSet::new();
+        for annotation in annotations {
+            let line = annotation.start_position().row + 1;
+            let mut names = Vec::new();
+            collect_type_names(&annotation, source, &mut names);
+            for name in expand_aliases(names, &aliases) {
+                if !seen.insert(name.clone()) {
+                    continue;
+                }
+                let key = (file_path.clone(), name);
+                match node_map.get(&key) {
+                    Some(&class) if classes.contains(&key.1) => edges.push((function_idx, class)),
+                    Some(_) => {}
+                    None => pending.push((function_idx, key.1, line)),
+                }
+            }
+        }
+    }
+
+    Annotations {
+        edges,
+        pending,
+        aliases,
+    }
+}
+
+/// Alias and target names of `Name: TypeAlias = ...`
+fn type_alias_assignment(
+    statement: &tree_sitter::Node,
+    source: &[u8],
+) -> Option<(String, Vec<String>)> {
+    let assignment = statement
+        .named_child(0)
+        .filter(|n| n.kind() == "assignment")?;
+    let annotation = assignment
+        .child_by_field_name("type")?
+        .utf8_text(source)
+        .ok()?;
+    if annotation != "TypeAlias" && !annotation.ends_with(".TypeAlias") {
+        return None;
+    }
+    let alias = assignment
+        .child_by_field_name("left")?
+        .utf8_text(source)
+        .ok()?;
+    let mut targets = Vec::new();
+    collect_type_names(
+        &assignment.child_by_field_name("right")?,
+        source,
+        &mut targets,
+    );
+    Some((alias.to_string(), targets))
+}
+
+/// Collect the possibly dotted type names used in an annotation
+fn collect_type_names(node: &tree_sitter::Node, source: &[u8], names: &mut Vec<String>) {
+    match node.kind() {
+        "identifier" => names.extend(node.utf8_text(source).ok().map(str::to_string)),
+        "attribute" => names.extend(extract_full_call_name(node, source)),
+        // `models.Invoice` inside a generic, and forward references (`"Invoice"`)
+        "member_type" | "string" => {
+            let text = node.utf8_text(source).unwrap_or_default();
+            let text = text.trim_matches(|c| c == '"' || c == '\'');
+            let is_identifier = |part: &str| {
+                part.chars()
+                    .next()
+                    .is_some_and(|c| c.is_alphabetic() || c == '_')
+                    && part.chars().all(|c| c.is_alphanumeric() || c == '_')
+            };
+            if text.split('.').all(is_identifier) {
+                names.push(text.to_string());
+            }
         }
-        // No more children or siblings at this level, go up
-        loop {
-            if !cursor.goto_parent() {
-                // Reached the root, we're done
-                return edges;
+        _ => {
+            let mut cursor = node.walk();
+            for child in node.named_children(&mut cursor) {
+                collect_type_names(&child, source, names);
             }
-            if cursor.goto_next_sibling() {
-                break;
+        }
+    }
+}
+
+/// Replace the names of type aliases with the names they stand for
+fn expand_aliases(names: Vec<String>, aliases: &HashMap<String, Vec<String>>) -> Vec<String> {
+    let mut expanded = Vec::new();
+    let mut visited = HashSet::new();
+    let mut stack: Vec<String> = names.into_iter().rev().collect();
+    while let Some(name) = stack.pop() {
+        match aliases.get(&name) {
+            Some(targets) => {
+                // Aliases defined in terms of each other expand once
+                if visited.insert(name) {
+                    stack.extend(targets.iter().rev().cloned());
+                }
             }
+            None => expanded.push(name),
         }
     }
+    expanded
 }
 
 /// Extract the function name from a call node
@@ -162,15 +703,14 @@ fn extract_calls_edges(
 /// extracts the full dotted name (which likely won't resolve).
 fn extract_call_name(call_node: &tree_sitter::Node, source: &[u8]) -> Option<String> {
     // The function being called is typically the first child
-    call_node.child(0)
-        .and_then(|child| match child.kind() {
-            // Simple identifier: foo()
-            "identifier" => child.utf8_text(source).ok().map(|s| s.to_string()),
-            // Dotted/attribute access: obj.method() or module.func()
-            // We extract the full dotted name, which likely won't resolve to a node
-            "attribute" | "call" => extract_full_call_name(&child, source),
-            _ => None,
-        })
+    call_node.child(0).and_then(|child| match child.kind() {
+        // Simple identifier: foo()
+        "identifier" => child.utf8_text(source).ok().map(|s| s.to_string()),
+        // Dotted/attribute access: obj.method() or module.func()
+        // We extract the full dotted name, which likely won't resolve to a node
+        "attribute" | "call" => extract_full_call_name(&child, source),
+        _ => None,
+    })
 }
 
 /// Helper to extract full dotted call names recursively
@@ -202,45 +742,6 @@ fn extract_full_call_name(node: &tree_sitter::Node, source: &[u8]) -> Option<Str
     }
 }
 
-/// Find the parent function_definition containing a node
-///
-/// Walks up the tree to find the enclosing function_definition.
-/// Returns None if the call is not inside a function (e.g., top-level module code).
-fn find_parent_function(
-    node: &tree_sitter::Node,
-    root_node: &tree_sitter::Node,
-    source: &[u8],
-    file_path: &std::path::PathBuf,  // Changed from &Path to &PathBuf for cheaper clone()
-    node_map: &HashMap<(std::path::PathBuf, String), NodeIndex>,
-) -> Option<NodeIndex> {
-    let mut current = *node;
-
-    // Walk up the tree
-    loop {
-        // Try to get the parent
-        if let Some(parent) = current.parent() {
-            current = parent;
-
-            if current.kind() == "function_definition" {
-                // Found the enclosing function, extract its name
-                if let Some(func_name) = extract_node_name(&current, source) {
-                    let key = (file_path.clone(), func_name);
-                    return node_map.get(&key).copied();
-                }
-            }
-
-            // Stop if we've reached the root
-            if current == *root_node {
-                break;
-            }
-        } else {
-            break;
-        }
-    }
-
-    None
-}
-
 #[cfg(test)]
 mod tests {
 
@@ -251,18 +752,18 @@ mod tests {
     #[test]
     fn test_parse_python_file() {
         let parser = crate::parser::Parser::new();
-        let graph = parser.parse_file(
-            Path::new("tests/test-fixtures/sample.py"),
-            &Language::Python,
-        ).unwrap();
+        let graph = parser
+            .parse_file(
+                Path::new("tests/test-fixtures/sample.py"),
+                &Language::Python,
+            )
+            .unwrap();
 
         // Should extract 2 functions + 1 class = 3 nodes
         assert_eq!(graph.node_count(), 3);
 
         // Verify nodes have correct properties
-        let node_names: Vec<&str> = graph.nodes()
-            .map(|n| n.name.as_str())
-            .collect();
+        let node_names: Vec<&str> = graph.nodes().map(|n| n.name.as_str()).collect();
 
         assert!(node_names.contains(&"hello_world"));
         assert!(node_names.contains(&"another_function"));
@@ -277,23 +778,53 @@ mod tests {
         for node in graph.nodes() {
             assert!(node.file_path.is_absolute());
         }
+
+        // Line ranges are 1-indexed and inclusive
+        let greeter = graph.nodes().find(|n| n.name == "Greeter").unwrap();
+        assert_eq!(greeter.line_range, Some((6, 8)));
+    }
+
+    #[test]
+    fn test_parse_source_matches_parse_file() {
+        let path = Path::new("tests/test-fixtures/sample.py");
+        let source = std::fs::read_to_string(path).unwrap();
+        let from_file = super::parse_file(path).unwrap();
+        let (graph, imports) = super::parse_source_full(&source, path).unwrap();
+
+        let names = |graph: &crate::Graph| {
+            let mut names: Vec<(String, Option<(usize, usize)>)> = graph
+                .nodes()
+                .map(|n| (n.name.clone(), n.line_range))
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names(&graph), names(&from_file));
+        assert_eq!(
+            imports.len(),
+            crate::import::extract_imports(path).unwrap().len()
+        );
+        // IDs use the virtual path as given
+        assert!(graph
+            .get_by_id("tests/test-fixtures/sample.py::Greeter")
+            .is_some());
     }
 
     #[test]
     fn test_nested_symbols_not_extracted() {
         let parser = crate::parser::Parser::new();
-        let graph = parser.parse_file(
-            Path::new("tests/test-fixtures/nested.py"),
-            &Language::Python,
-        ).unwrap();
+        let graph = parser
+            .parse_file(
+                Path::new("tests/test-fixtures/nested.py"),
+                &Language::Python,
+            )
+            .unwrap();
 
         // Should extract 2 top-level symbols (outer_function, OuterClass)
         // inner_function and InnerClass should NOT be extracted
         assert_eq!(graph.node_count(), 2);
 
-        let node_names: Vec<&str> = graph.nodes()
-            .map(|n| n.name.as_str())
-            .collect();
+        let node_names: Vec<&str> = graph.nodes().map(|n| n.name.as_str()).collect();
 
         assert!(node_names.contains(&"outer_function"));
         assert!(node_names.contains(&"OuterClass"));
@@ -304,10 +835,9 @@ mod tests {
     #[test]
     fn test_extract_calls_edges() {
         let parser = crate::parser::Parser::new();
-        let graph = parser.parse_file(
-            Path::new("tests/test-fixtures/calls.py"),
-            &Language::Python,
-        ).unwrap();
+        let graph = parser
+            .parse_file(Path::new("tests/test-fixtures/calls.py"), &Language::Python)
+            .unwrap();
 
         // Should have 4 nodes from Epic 2
         assert_eq!(graph.node_count(), 4);
@@ -326,10 +856,12 @@ mod tests {
         // Verify that unresolved calls don't crash parsing
         // and that only resolvable edges are created
         let parser = crate::parser::Parser::new();
-        let graph = parser.parse_file(
-            Path::new("tests/test-fixtures/calls_with_unresolved.py"),
-            &Language::Python,
-        ).unwrap();
+        let graph = parser
+            .parse_file(
+                Path::new("tests/test-fixtures/calls_with_unresolved.py"),
+                &Language::Python,
+            )
+            .unwrap();
 
         // Should have 2 nodes (my_func, helper)
         assert_eq!(graph.node_count(), 2);
@@ -342,23 +874,213 @@ mod tests {
     fn test_no_calls_no_edges() {
         // Verify that functions without calls create no edges
         let parser = crate::parser::Parser::new();
-        let graph = parser.parse_file(
-            Path::new("tests/test-fixtures/sample.py"),
-            &Language::Python,
-        ).unwrap();
+        let graph = parser
+            .parse_file(
+                Path::new("tests/test-fixtures/sample.py"),
+                &Language::Python,
+            )
+            .unwrap();
 
         // sample.py has no function calls
         assert_eq!(graph.edge_count(), 0);
     }
 
+    #[test]
+    fn test_calls_attributed_to_innermost_definition() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("nesting.py");
+        let source = "def helper():\n    pass\n\n\
+                      def outer():\n    def inner():\n        helper()\n    return helper()\n\n\
+                      class Service:\n    def run(self):\n        helper()\n\n\
+                      helper()\n";
+        std::fs::write(&path, source).unwrap();
+
+        let parsed = super::parse_file_detailed(&path).unwrap();
+        let graph = &parsed.graph;
+        let edges: Vec<_> = graph
+            .edge_endpoints()
+            .map(|(from, to, _)| {
+                let name = |idx| graph.node_weight(idx).unwrap().name.clone();
+                (name(from), name(to))
+            })
+            .collect();
+
+        // Only the call after the nested definition belongs to `outer`; calls in
+        // `inner`, in the method and at module level have no caller node
+        assert_eq!(edges, vec![("outer".to_string(), "helper".to_string())]);
+        assert!(parsed.unresolved_calls.is_empty());
+    }
+
+    #[test]
+    fn test_granularity_levels() {
+        use crate::graph::NodeType;
+        use crate::parser::{GranularityLevel, ParseSession};
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("levels.py");
+        let source = "def helper():\n    pass\n\n\
+                      class Service:\n    def run(self):\n        helper()\n\n\
+                      def main(service: Service):\n    helper()\n";
+        std::fs::write(&path, source).unwrap();
+
+        let parse = |level| {
+            let mut session = ParseSession::uncached().with_granularity(level);
+            let graph = super::parse_file_in(&mut session, &path).unwrap().graph;
+            let mut nodes: Vec<(NodeType, String)> = graph
+                .nodes()
+                .map(|node| (node.node_type.clone(), node.name.clone()))
+                .collect();
+            nodes.sort_by(|a, b| a.1.cmp(&b.1));
+            let mut edges: Vec<(String, EdgeType, String)> = graph
+                .edge_endpoints()
+                .map(|(from, to, edge)| {
+                    let name = |idx| graph.node_weight(idx).unwrap().name.clone();
+                    (name(from), edge.edge_type, name(to))
+                })
+                .collect();
+            edges.sort_by(|a, b| (&a.0, &a.2).cmp(&(&b.0, &b.2)));
+            (nodes, edges)
+        };
+        let edge = |from: &str, kind, to: &str| (from.to_string(), kind, to.to_string());
+        let contains = |from, to| edge(from, EdgeType::Contains, to);
+        let calls = |from, to| edge(from, EdgeType::Calls, to);
+
+        let (nodes, edges) = parse(Some(GranularityLevel::Files));
+        assert_eq!(nodes, vec![(NodeType::File, "levels.py".to_string())]);
+        assert!(edges.is_empty());
+
+        let (nodes, edges) = parse(Some(GranularityLevel::Classes));
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(edges, vec![contains("levels.py", "Service")]);
+
+        let (nodes, edges) = parse(Some(GranularityLevel::Methods));
+        assert_eq!(nodes.len(), 5);
+        assert!(nodes.contains(&(NodeType::Method, "run".to_string())));
+        assert!(edges.contains(&contains("Service", "run")));
+        assert!(!edges.iter().any(|edge| edge.1 == EdgeType::Calls));
+
+        // Calls made inside methods are attributed to them
+        let (_, edges) = parse(Some(GranularityLevel::Calls));
+        assert!(edges.contains(&calls("run", "helper")));
+        assert!(edges.contains(&calls("main", "helper")));
+        assert!(edges.contains(&edge("main", EdgeType::References, "Service")));
+
+        // Without a level: no file or method nodes, and the method's call is skipped
+        let (nodes, edges) = parse(None);
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(
+            edges
+                .iter()
+                .filter(|edge| edge.1 == EdgeType::Calls)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_parse_file_full_returns_symbols_and_imports() {
+        let (graph, imports) =
+            super::parse_file_full(Path::new("tests/test-fixtures/calls.py")).unwrap();
+        assert_eq!(graph.node_count(), 4);
+        assert!(imports.is_empty());
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("both.py");
+        std::fs::write(
+            &path,
+            "import os\nfrom . import util\n\ndef f():\n    util.g()\n",
+        )
+        .unwrap();
+        let (graph, imports) = super::parse_file_full(&path).unwrap();
+        assert_eq!(graph.node_count(), 1);
+        assert_eq!(imports, crate::import::extract_imports(&path).unwrap());
+    }
+
+    #[test]
+    fn test_syntax_errors_reported() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("broken.py");
+        std::fs::write(&path, "def ok():\n    pass\n\ndef broken(:\n    pass\n").unwrap();
+
+        let (graph, error_lines) = super::parse_file_with_errors(&path).unwrap();
+
+        // The valid function is still extracted
+        assert!(graph.nodes().any(|n| n.name == "ok"));
+        assert_eq!(error_lines, vec![4]);
+
+        let (_, clean) =
+            super::parse_file_with_errors(Path::new("tests/test-fixtures/sample.py")).unwrap();
+        assert!(clean.is_empty());
+    }
+
+    #[test]
+    fn test_extract_type_references() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("typed.py");
+        let source = "from typing import TypeAlias\nfrom billing import Invoice\n\n\
+                      class Customer:\n    pass\n\n\
+                      Customers: TypeAlias = list[Customer]\n\
+                      type Lookup = dict[str, \"Customer\"]\n\n\
+                      def load(ids: list[int], by: Lookup = None) -> Customers:\n    pass\n\n\
+                      def bill(customer: Customer, *, invoice: Invoice) -> \"models.Receipt\":\n\
+                      \x20   pass\n\n\
+                      def apply(fn: load) -> None:\n    pass\n";
+        std::fs::write(&path, source).unwrap();
+
+        let parsed = super::parse_file_detailed(&path).unwrap();
+        let graph = &parsed.graph;
+        let mut edges: Vec<_> = graph
+            .edge_endpoints()
+            .filter(|(_, _, edge)| edge.edge_type == EdgeType::References)
+            .map(|(from, to, _)| {
+                let name = |idx| graph.node_weight(idx).unwrap().name.clone();
+                (name(from), name(to))
+            })
+            .collect();
+        edges.sort();
+        assert_eq!(
+            edges,
+            vec![
+                ("bill".to_string(), "Customer".to_string()),
+                ("load".to_string(), "Customer".to_string())
+            ]
+        );
+
+        // Builtins and imported types wait for pass 2; the function `load` is dropped
+        let pending = |function: &str| {
+            parsed
+                .type_references
+                .iter()
+                .filter(|r| r.function.ends_with(&format!("::{}", function)))
+                .map(|r| (r.name.as_str(), r.line))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            pending("bill"),
+            vec![("Invoice", 13), ("models.Receipt", 13)]
+        );
+        assert_eq!(
+            pending("load"),
+            vec![("list", 10), ("int", 10), ("dict", 10), ("str", 10)]
+        );
+        assert!(pending("apply").is_empty());
+        assert_eq!(parsed.type_aliases["Customers"], vec!["list", "Customer"]);
+        assert_eq!(
+            parsed.type_aliases["Lookup"],
+            vec!["dict", "str", "Customer"]
+        );
+    }
+
     #[test]
     fn test_edge_case_duplicate_functions() {
         // Verify behavior with duplicate function names
         let parser = crate::parser::Parser::new();
-        let graph = parser.parse_file(
-            Path::new("tests/test-fixtures/calls_edge_cases.py"),
-            &Language::Python,
-        ).unwrap();
+        let graph = parser
+            .parse_file(
+                Path::new("tests/test-fixtures/calls_edge_cases.py"),
+                &Language::Python,
+            )
+            .unwrap();
 
         // Should have 5 top-level nodes: helper (2x), caller, dotted_caller, method_caller
         assert_eq!(graph.node_count(), 5);
@@ -369,7 +1091,9 @@ mod tests {
         // Verify the edge is from caller to a helper
         let mut found_caller_to_helper = false;
         for (from, to, edge) in graph.edge_endpoints() {
-            if let (Some(from_node), Some(to_node)) = (graph.node_weight(from), graph.node_weight(to)) {
+            if let (Some(from_node), Some(to_node)) =
+                (graph.node_weight(from), graph.node_weight(to))
+            {
                 if from_node.name == "caller" && to_node.name == "helper" {
                     assert_eq!(edge.edge_type, EdgeType::Calls);
                     found_caller_to_helper = true;
@@ -0,0 +1,758 @@
+//! Shared parser state for one scan ([`ParseSession`])
+
+use super::encoding::{self, SourceEncoding};
+use super::notebook::{self, NotebookCells};
+use super::{GranularityLevel, Language};
+use crate::cache::ParseCache;
+use crate::config::{Config, GeneratedCode, LimitsConfig};
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::EdgePolicy;
+use crate::paths::PathResolver;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::{ControlFlow, Range};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tree_sitter::{InputEdit, ParseOptions, ParseState, Parser as TsParser, Point, Tree};
+
+/// A parsed source file
+#[derive(Debug)]
+pub struct SourceFile {
+    /// Path of the file as resolved by the session's [`PathResolver`], or
+    /// the path given to [`ParseSession::add_source()`]
+    pub path: PathBuf,
+    /// File contents, decoded as described by `encoding`
+    pub source: String,
+    /// Language the file was parsed as
+    pub language: Language,
+    /// How the file's bytes were decoded
+    pub encoding: SourceEncoding,
+    /// For a notebook, where each code cell was placed in `source`
+    pub notebook: Option<NotebookCells>,
+    /// Syntax tree of `source`
+    pub tree: Tree,
+}
+
+/// Parser and source cache shared by the passes of one run
+///
+/// Symbol extraction ([`python`](super::python)) and import extraction
+/// ([`import`](crate::import)) both need a file's source and syntax tree.
+/// Without a session each pass creates its own tree-sitter parser and reads
+/// and parses every file again. A session keeps one parser per grammar for
+/// the whole run and caches each file's source and tree, keyed by the path
+/// its [`PathResolver`] resolves (see [`ParseSession::with_paths()`]), so
+/// later passes reuse them.
+///
+/// Each file is parsed in the language its extension maps to (see
+/// [`Language::detect()`]); a file whose extension no language claims, such
+/// as a script without one, in the language of its `#!` line (see
+/// [`Language::from_shebang()`]), and otherwise as Python.
+///
+/// Cached trees stay in memory until the session is dropped or the file is
+/// [removed](ParseSession::remove) (see also [`ParseSession::retain()`]); use
+/// [`ParseSession::uncached()`] when only one pass will read each file (it
+/// keeps just the most recent one).
+///
+/// Source text can also be handed over directly with
+/// [`ParseSession::add_source()`]; the passes then read it instead of the
+/// file, which is how builds without filesystem access (the `std-fs`
+/// feature off, e.g. WebAssembly) parse code. Editors and watchers keep
+/// that source current with [`ParseSession::edit_source()`] and
+/// [`ParseSession::update_source()`], which reparse incrementally.
+#[derive(Default)]
+pub struct ParseSession {
+    /// Created on first use of each grammar, since setting the language can fail
+    parsers: HashMap<&'static str, TsParser>,
+    files: HashMap<PathBuf, SourceFile>,
+    /// Paths whose source came from [`ParseSession::add_source()`]
+    added: HashSet<PathBuf>,
+    uncached: bool,
+    granularity: Option<GranularityLevel>,
+    edge_policy: EdgePolicy,
+    extensions: BTreeMap<String, Language>,
+    interpreters: BTreeMap<String, Language>,
+    tag_generated: bool,
+    cache: Option<ParseCache>,
+    limits: LimitsConfig,
+    paths: PathResolver,
+}
+
+impl ParseSession {
+    /// A session that caches every file it parses
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A session that reuses its parser but keeps only the last file parsed
+    pub fn uncached() -> Self {
+        Self {
+            uncached: true,
+            ..Self::default()
+        }
+    }
+
+    /// An [uncached](Self::uncached) session for scanning the project at
+    /// `root`, configured by `config` and using the project's parse cache
+    /// (see [`ParseCache::for_project()`])
+    pub fn for_project(root: &Path, config: &Config) -> Self {
+        Self::uncached()
+            .with_config(config)
+            .with_cache(ParseCache::for_project(root, config))
+    }
+
+    /// Apply `config`'s extraction settings (`granularity`, `edge_policy`,
+    /// `extensions`, `interpreters`, `discovery.generated`, `limits`,
+    /// `relative_ids`)
+    pub fn with_config(self, config: &Config) -> Self {
+        self.with_granularity(config.granularity)
+            .with_paths(config.path_resolver())
+            .with_limits(config.limits.clone())
+            .with_edge_policy(config.edge_policy)
+            .with_extensions(config.extensions.clone())
+            .with_interpreters(config.interpreters.clone())
+            .with_generated_tags(config.discovery.generated == GeneratedCode::Tag)
+    }
+
+    /// Extract symbols down to `level` (`None` for the default extraction)
+    pub fn with_granularity(mut self, level: Option<GranularityLevel>) -> Self {
+        self.granularity = level;
+        self
+    }
+
+    /// How deep symbol extraction goes; see [`GranularityLevel`]
+    pub fn granularity(&self) -> Option<GranularityLevel> {
+        self.granularity
+    }
+
+    /// Build graphs that add repeated edges under `policy`
+    pub fn with_edge_policy(mut self, policy: EdgePolicy) -> Self {
+        self.edge_policy = policy;
+        self
+    }
+
+    /// How graphs built through this session treat repeated edges
+    pub fn edge_policy(&self) -> EdgePolicy {
+        self.edge_policy
+    }
+
+    /// Parse files with these extensions (without the dot) in the given
+    /// language, whatever their built-in language
+    pub fn with_extensions(mut self, extensions: BTreeMap<String, Language>) -> Self {
+        self.extensions = extensions;
+        self
+    }
+
+    /// Mark the nodes of generated files with the
+    /// [`GENERATED_ATTRIBUTE`](super::generated::GENERATED_ATTRIBUTE) attribute
+    pub fn with_generated_tags(mut self, tag: bool) -> Self {
+        self.tag_generated = tag;
+        self
+    }
+
+    /// Whether nodes of generated files are marked
+    pub fn tags_generated(&self) -> bool {
+        self.tag_generated
+    }
+
+    /// Skip files that break `limits`: [`parse()`](Self::parse) refuses
+    /// files over `max_file_size` or `max_parse_ms`, and the
+    /// [pass-1 functions](super::parse_files_in) files with over `max_nodes`
+    /// nodes, with [`GraphMigratorError::LimitExceeded`]
+    pub fn with_limits(mut self, limits: LimitsConfig) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// The per-file limits in force
+    pub fn limits(&self) -> &LimitsConfig {
+        &self.limits
+    }
+
+    /// Identify files by the paths `paths` resolves them to (default:
+    /// [`PathResolver::Relative`]); node IDs and file paths embed them
+    pub fn with_paths(mut self, paths: PathResolver) -> Self {
+        self.paths = paths;
+        self
+    }
+
+    /// How the session resolves file paths
+    pub fn paths(&self) -> PathResolver {
+        self.paths
+    }
+
+    /// Reuse and store pass-1 results in `cache` (see [`crate::cache`])
+    pub fn with_cache(mut self, cache: Option<ParseCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// The parse cache, if one is attached
+    pub fn cache(&self) -> Option<&ParseCache> {
+        self.cache.as_ref()
+    }
+
+    /// Cache key of `path` and its cached pass-1 result, if a cache is
+    /// attached and `path` is a readable file (not an added source)
+    pub(crate) fn cache_lookup(
+        &mut self,
+        path: &Path,
+    ) -> Option<(String, Option<super::FileParseResult>)> {
+        if self.cache.is_none() || self.added.contains(path) {
+            return None;
+        }
+        let resolved = self.paths.resolve(path).ok()?;
+        // Leave oversized files to parse(), which reports them
+        self.check_size(&resolved).ok()?;
+        let bytes = std::fs::read(&resolved).ok()?;
+        let language = self.detect(&resolved, &bytes);
+        let settings = format!(
+            "{}/{:?}/{:?}/{}/{:?}/{:?}",
+            language,
+            self.granularity,
+            self.edge_policy,
+            self.tag_generated,
+            self.limits,
+            self.paths
+        );
+        let key = ParseCache::key(&resolved, &settings, &bytes);
+        let cached = self.cache.as_mut()?.get(&key, path);
+        if cached.is_none() && !self.files.contains_key(&resolved) {
+            // Parse the bytes already read; on failure parse() reads the
+            // file again and reports the error
+            if let Ok(file) = self.parse_text(resolved.clone(), bytes) {
+                self.insert_file(resolved, file);
+            }
+        }
+        Some((key, cached))
+    }
+
+    /// Store `parsed` under `key` from [`cache_lookup()`](Self::cache_lookup)
+    pub(crate) fn cache_store(&mut self, key: &str, parsed: &super::FileParseResult) {
+        if let Some(cache) = &mut self.cache {
+            cache.put(key, parsed);
+        }
+    }
+
+    /// Language `path` with contents `bytes` is parsed in
+    fn detect(&self, path: &Path, bytes: &[u8]) -> Language {
+        Language::detect(path, &self.extensions)
+            .or_else(|| Language::from_shebang(bytes, &self.interpreters))
+            .unwrap_or(Language::Python)
+    }
+
+    /// A session that parses `path`, and every file with its extension, in
+    /// `language`
+    pub fn for_file(path: &Path, language: Language) -> Self {
+        let extension = path.extension().unwrap_or_default().to_string_lossy();
+        Self::uncached().with_extensions(BTreeMap::from([(extension.into_owned(), language)]))
+    }
+
+    /// Parse scripts whose `#!` line runs one of these interpreters in the
+    /// given language
+    pub fn with_interpreters(mut self, interpreters: BTreeMap<String, Language>) -> Self {
+        self.interpreters = interpreters;
+        self
+    }
+
+    /// The source and syntax tree of `path`, parsing it on first request
+    ///
+    /// A path given to [`ParseSession::add_source()`] gets that source.
+    ///
+    /// # Errors
+    /// Returns [`GraphMigratorError::Io`] if the file's path cannot be
+    /// resolved or the file cannot be read, [`GraphMigratorError::Parse`] if tree-sitter fails, and
+    /// [`GraphMigratorError::LimitExceeded`] if the file is larger than
+    /// `max_file_size` or takes longer than `max_parse_ms` to parse (see
+    /// [`with_limits()`](Self::with_limits)).
+    pub fn parse(&mut self, path: &Path) -> Result<&SourceFile> {
+        if self.added.contains(path) {
+            return Ok(&self.files[path]);
+        }
+        let resolved = self
+            .paths
+            .resolve(path)
+            .map_err(|e| GraphMigratorError::io(path, e))?;
+        if !self.files.contains_key(&resolved) {
+            let file = self.parse_uncached(resolved.clone())?;
+            self.insert_file(resolved.clone(), file);
+        }
+        Ok(&self.files[&resolved])
+    }
+
+    /// [`parse()`](Self::parse) `path` if the session holds it already or
+    /// `wanted` accepts its bytes, and otherwise leave it unparsed
+    ///
+    /// A pass that only needs a few files can skip building the trees of
+    /// the rest this way.
+    ///
+    /// # Errors
+    /// Returns the errors of [`parse()`](Self::parse).
+    pub fn parse_if(
+        &mut self,
+        path: &Path,
+        wanted: impl FnOnce(&[u8]) -> bool,
+    ) -> Result<Option<&SourceFile>> {
+        if self.cached(path).is_none() {
+            let resolved = self
+                .paths
+                .resolve(path)
+                .map_err(|e| GraphMigratorError::io(path, e))?;
+            self.check_size(&resolved)?;
+            let bytes =
+                std::fs::read(&resolved).map_err(|e| GraphMigratorError::io(&resolved, e))?;
+            if !wanted(&bytes) {
+                return Ok(None);
+            }
+            let file = self.parse_text(resolved.clone(), bytes)?;
+            self.insert_file(resolved, file);
+        }
+        self.parse(path).map(Some)
+    }
+
+    /// Hold `file` for `path`; an uncached session drops every other file
+    fn insert_file(&mut self, path: PathBuf, file: SourceFile) {
+        // An uncached session still serves repeated requests for its last file
+        if self.uncached {
+            self.files.clear();
+            self.added.clear();
+        }
+        self.files.insert(path, file);
+    }
+
+    /// Parse `source` as the contents of `path`, without touching the filesystem
+    ///
+    /// Later requests for exactly `path` (it is not resolved) get this
+    /// source, so the passes run on it as on a file read from disk. Adding
+    /// a path again replaces its source.
+    ///
+    /// # Errors
+    /// Returns [`GraphMigratorError::Parse`] if tree-sitter fails, or a
+    /// notebook path does not hold notebook JSON.
+    ///
+    /// # Example
+    /// ```
+    /// use graph_migrator_core::parser::{self, ParseSession};
+    /// use std::path::Path;
+    ///
+    /// let mut session = ParseSession::new();
+    /// session.add_source(Path::new("snippet.py"), "def f():\n    g()\n\ndef g():\n    pass\n").unwrap();
+    /// let multi = parser::parse_files_in(&mut session, &[Path::new("snippet.py")]).unwrap();
+    /// assert!(multi.graph.get_by_id("snippet.py::f").is_some());
+    /// ```
+    pub fn add_source(&mut self, path: &Path, source: impl Into<String>) -> Result<&SourceFile> {
+        if self.uncached {
+            self.files.clear();
+            self.added.clear();
+        }
+        let file = self.parse_text(path.to_path_buf(), source.into().into_bytes())?;
+        self.files.insert(path.to_path_buf(), file);
+        self.added.insert(path.to_path_buf());
+        Ok(&self.files[path])
+    }
+
+    /// Replace bytes `range` of `path`'s source with `text` and reparse it
+    /// incrementally
+    ///
+    /// The previous tree is edited to match and handed back to tree-sitter,
+    /// which reuses every subtree the edit did not touch, so a keystroke in
+    /// a large file costs a fraction of a full parse. A file not parsed yet
+    /// is parsed first. Afterwards `path` is treated as a path given to
+    /// [`add_source()`](Self::add_source): the passes read the edited
+    /// source, not the file on disk.
+    ///
+    /// # Errors
+    /// Returns the errors of [`parse()`](Self::parse), and
+    /// [`GraphMigratorError::Parse`] if `range` is out of bounds or splits a
+    /// character, or `path` is a notebook (its source is extracted from JSON,
+    /// so offsets into the file do not apply).
+    ///
+    /// # Example
+    /// ```
+    /// use graph_migrator_core::parser::ParseSession;
+    /// use std::path::Path;
+    ///
+    /// let mut session = ParseSession::new();
+    /// let path = Path::new("buffer.py");
+    /// session.add_source(path, "def f():\n    pass\n").unwrap();
+    /// let file = session.edit_source(path, 5..5, "etch").unwrap();
+    /// assert_eq!(file.source, "def fetch():\n    pass\n");
+    /// ```
+    pub fn edit_source(
+        &mut self,
+        path: &Path,
+        range: Range<usize>,
+        text: &str,
+    ) -> Result<&SourceFile> {
+        let file = self.parse(path)?;
+        if file.notebook.is_some() {
+            return Err(GraphMigratorError::parse(
+                path,
+                "notebooks cannot be edited in place",
+            ));
+        }
+        if range.start > range.end
+            || range.end > file.source.len()
+            || !file.source.is_char_boundary(range.start)
+            || !file.source.is_char_boundary(range.end)
+        {
+            return Err(GraphMigratorError::parse(
+                path,
+                format!(
+                    "edit range {:?} is outside the source or splits a character",
+                    range
+                ),
+            ));
+        }
+        let key = file.path.clone();
+        let mut file = self
+            .files
+            .remove(&key)
+            .expect("parse() caches the file it returns");
+        self.added.remove(&key);
+
+        let start_position = point(&file.source, range.start);
+        let old_end_position = point(&file.source, range.end);
+        file.source.replace_range(range.clone(), text);
+        let new_end_byte = range.start + text.len();
+        file.tree.edit(&InputEdit {
+            start_byte: range.start,
+            old_end_byte: range.end,
+            new_end_byte,
+            start_position,
+            old_end_position,
+            new_end_position: point(&file.source, new_end_byte),
+        });
+        file.tree = self.run_parser(file.language, path, &file.source, Some(&file.tree))?;
+        file.path = path.to_path_buf();
+        file.encoding = SourceEncoding::Utf8;
+
+        self.files.insert(path.to_path_buf(), file);
+        self.added.insert(path.to_path_buf());
+        Ok(&self.files[path])
+    }
+
+    /// Replace `path`'s source with `source`, reparsing incrementally when
+    /// the session already holds a tree of it
+    ///
+    /// The change is taken to be the span between the longest common prefix
+    /// and suffix of the old and new source, and applied with
+    /// [`edit_source()`](Self::edit_source); for a watcher that only sees a
+    /// file's new contents, that is usually the lines just typed. A path the
+    /// session has not parsed, a notebook, or a source that now parses in
+    /// another language is parsed from scratch, like
+    /// [`add_source()`](Self::add_source).
+    ///
+    /// # Errors
+    /// Returns [`GraphMigratorError::Parse`] if tree-sitter fails, or a
+    /// notebook path does not hold notebook JSON.
+    pub fn update_source(&mut self, path: &Path, source: impl Into<String>) -> Result<&SourceFile> {
+        let source = source.into();
+        let language = self.detect(path, source.as_bytes());
+        let old = match self.cached(path) {
+            Some(file) if file.notebook.is_none() && file.language == language => &file.source,
+            _ => return self.add_source(path, source),
+        };
+        let (prefix, suffix) = common_affixes(old, &source);
+        let range = prefix..old.len() - suffix;
+        let text = source[prefix..source.len() - suffix].to_string();
+        self.edit_source(path, range, &text)
+    }
+
+    /// The file the session holds for `path`, without parsing it
+    fn cached(&self, path: &Path) -> Option<&SourceFile> {
+        if self.added.contains(path) {
+            return self.files.get(path);
+        }
+        self.files.get(&self.paths.resolve(path).ok()?)
+    }
+
+    fn parse_uncached(&mut self, path: PathBuf) -> Result<SourceFile> {
+        self.check_size(&path)?;
+        let bytes = std::fs::read(&path).map_err(|e| GraphMigratorError::io(&path, e))?;
+        self.parse_text(path, bytes)
+    }
+
+    fn parse_text(&mut self, path: PathBuf, bytes: Vec<u8>) -> Result<SourceFile> {
+        let language = self.detect(&path, &bytes);
+        let (mut source, encoding) = encoding::decode(bytes);
+        if let Some(warning) = encoding.warning() {
+            tracing::debug!(path = %path.display(), warning, "decoded non-UTF-8 source");
+        }
+        let notebook = if language == Language::Python && notebook::is_notebook(&path) {
+            let (code, cells) = notebook::extract_code(&source)
+                .map_err(|detail| GraphMigratorError::parse(&path, detail))?;
+            source = code;
+            Some(cells)
+        } else {
+            None
+        };
+        let tree = self.run_parser(language, &path, &source, None)?;
+        Ok(SourceFile {
+            path,
+            source,
+            language,
+            encoding,
+            notebook,
+            tree,
+        })
+    }
+
+    /// Refuse `path` if it is larger than `max_file_size`, before reading it
+    fn check_size(&self, path: &Path) -> Result<()> {
+        let Some(max) = self.limits.max_file_size else {
+            return Ok(());
+        };
+        let size = std::fs::metadata(path)
+            .map_err(|e| GraphMigratorError::io(path, e))?
+            .len();
+        if size > max {
+            return Err(GraphMigratorError::limit(
+                path,
+                format!("file is {size} bytes, over the limit of {max}"),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Parse `source` in `language`, reusing `old` for an incremental
+    /// reparse, and give up once `max_parse_ms` has passed
+    fn run_parser(
+        &mut self,
+        language: Language,
+        path: &Path,
+        source: &str,
+        old: Option<&Tree>,
+    ) -> Result<Tree> {
+        let max_parse_ms = self.limits.max_parse_ms;
+        let parser = self.parser(language, path)?;
+        let Some(max_parse_ms) = max_parse_ms else {
+            return parser
+                .parse(source, old)
+                .ok_or_else(|| GraphMigratorError::parse(path, "tree-sitter returned no tree"));
+        };
+        let deadline = Instant::now() + Duration::from_millis(max_parse_ms);
+        let mut timed_out = false;
+        let mut progress = |_: &ParseState| {
+            timed_out = Instant::now() >= deadline;
+            if timed_out {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        };
+        let bytes = source.as_bytes();
+        let tree = parser.parse_with_options(
+            &mut |offset, _| bytes.get(offset..).unwrap_or_default(),
+            old,
+            Some(ParseOptions::new().progress_callback(&mut progress)),
+        );
+        match tree {
+            Some(tree) => Ok(tree),
+            None => {
+                // A cancelled parse would otherwise resume on the next call
+                parser.reset();
+                if timed_out {
+                    Err(GraphMigratorError::limit(
+                        path,
+                        format!("parsing took longer than {max_parse_ms} ms"),
+                    ))
+                } else {
+                    Err(GraphMigratorError::parse(
+                        path,
+                        "tree-sitter returned no tree",
+                    ))
+                }
+            }
+        }
+    }
+
+    /// The parser of `language`'s grammar for `path`, created on first use
+    fn parser(&mut self, language: Language, path: &Path) -> Result<&mut TsParser> {
+        let (name, grammar) = grammar(language, path);
+        Ok(match self.parsers.entry(name) {
+            std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                let mut parser = TsParser::new();
+                parser
+                    .set_language(&grammar)
+                    .map_err(|e| GraphMigratorError::parse(path, e.to_string()))?;
+                entry.insert(parser)
+            }
+        })
+    }
+
+    /// Drop the cached source and tree of `path`, if any
+    pub fn remove(&mut self, path: &Path) -> Option<SourceFile> {
+        if self.added.remove(path) {
+            return self.files.remove(path);
+        }
+        let resolved = self.paths.resolve(path).ok()?;
+        self.files.remove(&resolved)
+    }
+
+    /// Drop the cached source and tree of every file `keep` rejects
+    pub fn retain(&mut self, mut keep: impl FnMut(&SourceFile) -> bool) {
+        self.files.retain(|_, file| keep(file));
+        let files = &self.files;
+        self.added.retain(|path| files.contains_key(path));
+    }
+
+    /// Number of files currently cached
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Whether no files are cached
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+/// Row and byte column of byte offset `byte` in `source`
+fn point(source: &str, byte: usize) -> Point {
+    let before = &source.as_bytes()[..byte];
+    let line_start = before
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |newline| newline + 1);
+    Point::new(
+        before.iter().filter(|&&b| b == b'\n').count(),
+        byte - line_start,
+    )
+}
+
+/// Byte lengths of the longest common prefix and, after it, suffix of `old`
+/// and `new`, both ending on character boundaries
+fn common_affixes(old: &str, new: &str) -> (usize, usize) {
+    let (old_bytes, new_bytes) = (old.as_bytes(), new.as_bytes());
+    let mut prefix = old_bytes
+        .iter()
+        .zip(new_bytes)
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old.is_char_boundary(prefix) || !new.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+    let mut suffix = old_bytes[prefix..]
+        .iter()
+        .rev()
+        .zip(new_bytes[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old.is_char_boundary(old.len() - suffix) || !new.is_char_boundary(new.len() - suffix) {
+        suffix -= 1;
+    }
+    (prefix, suffix)
+}
+
+/// Name and tree-sitter grammar for `path` in `language`
+///
+/// TypeScript has two grammars; `.tsx` files need the one that accepts JSX.
+fn grammar(language: Language, path: &Path) -> (&'static str, tree_sitter::Language) {
+    match language {
+        Language::Python => ("python", tree_sitter_python::LANGUAGE.into()),
+        Language::Rust => ("rust", tree_sitter_rust::LANGUAGE.into()),
+        Language::TypeScript if path.extension().is_some_and(|ext| ext == "tsx") => {
+            ("tsx", tree_sitter_typescript::LANGUAGE_TSX.into())
+        }
+        Language::TypeScript => (
+            "typescript",
+            tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_caches_by_resolved_path() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("cached.py");
+        std::fs::write(&path, "import os\n\ndef f():\n    pass\n").unwrap();
+
+        let mut session = ParseSession::new();
+        let first = session.parse(&path).unwrap().tree.root_node().id();
+        // A different spelling of the same file hits the cache, even after the file changes
+        std::fs::write(&path, "").unwrap();
+        let dotted = temp_dir.path().join(".").join("cached.py");
+        assert_eq!(session.parse(&dotted).unwrap().tree.root_node().id(), first);
+        assert_eq!(session.len(), 1);
+
+        assert!(session.remove(&path).is_some());
+        assert!(session.parse(&path).unwrap().source.is_empty());
+
+        let other = temp_dir.path().join("other.py");
+        std::fs::write(&other, "x = 1\n").unwrap();
+        let mut uncached = ParseSession::uncached();
+        uncached.parse(&path).unwrap();
+        uncached.parse(&other).unwrap();
+        assert_eq!(uncached.len(), 1);
+
+        // Files parse_if() turns down are not parsed, files it holds always served
+        let mut session = ParseSession::new();
+        let starts_with_def = |bytes: &[u8]| bytes.starts_with(b"def");
+        assert!(session.parse_if(&other, starts_with_def).unwrap().is_none());
+        assert!(session.is_empty());
+        assert!(session.parse_if(&other, |_| true).unwrap().is_some());
+        assert!(session.parse_if(&other, |_| false).unwrap().is_some());
+        session.parse(&path).unwrap();
+        session.retain(|file| !file.source.is_empty());
+        assert_eq!(session.len(), 1);
+    }
+
+    #[test]
+    fn test_added_source_shadows_the_filesystem() {
+        let mut session = ParseSession::new();
+        let path = Path::new("no/such/dir/buffer.py");
+        session.add_source(path, "def f():\n    pass\n").unwrap();
+        let file = session.parse(path).unwrap();
+        assert_eq!(file.path, path);
+        assert_eq!(file.tree.root_node().named_child_count(), 1);
+
+        session.add_source(path, "").unwrap();
+        assert!(session.parse(path).unwrap().source.is_empty());
+        assert_eq!(session.len(), 1);
+        assert!(session.remove(path).is_some());
+        assert!(session.parse(path).is_err());
+    }
+
+    #[test]
+    fn test_edits_reparse_incrementally() {
+        let fresh = |source: &str| {
+            let mut session = ParseSession::uncached();
+            let file = session.add_source(Path::new("fresh.py"), source).unwrap();
+            file.tree.root_node().to_sexp()
+        };
+        let mut session = ParseSession::new();
+        let path = Path::new("buffer.py");
+        session.add_source(path, "def f():\n    pass\n").unwrap();
+
+        let file = session.edit_source(path, 5..5, "etch").unwrap();
+        assert_eq!(file.source, "def fetch():\n    pass\n");
+        assert_eq!(file.tree.root_node().to_sexp(), fresh(&file.source));
+
+        let source = "# é\ndef fetch():\n    pass\n\nclass Cart:\n    pass\n";
+        let file = session.update_source(path, source).unwrap();
+        assert_eq!(file.tree.root_node().to_sexp(), fresh(source));
+        assert!(session.edit_source(path, 3..100, "").is_err());
+        // Inside the two-byte "é"
+        assert!(session.edit_source(path, 3..3, "x").is_err());
+
+        // A file read from disk is shadowed by its updated source
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let disk = temp_dir.path().join("disk.py");
+        std::fs::write(&disk, "x = 1\n").unwrap();
+        session.parse(&disk).unwrap();
+        session.update_source(&disk, "x = 2\n").unwrap();
+        assert_eq!(session.parse(&disk).unwrap().source, "x = 2\n");
+        assert_eq!(std::fs::read_to_string(&disk).unwrap(), "x = 1\n");
+    }
+
+    #[test]
+    fn test_common_affixes_respect_char_boundaries() {
+        assert_eq!(common_affixes("abc", "abc"), (3, 0));
+        assert_eq!(common_affixes("a(b)", "a(xb)"), (2, 2));
+        assert_eq!(common_affixes("é", "è"), (0, 0));
+    }
+}
@@ -0,0 +1,169 @@
+//! Parameter counts of functions and methods
+//!
+//! Matching a legacy function to its rewrite is easier when the signatures
+//! agree (see [`migration::match_candidates()`]). Every function and method
+//! node gets the [`ARITY_ATTRIBUTE`] attribute: the number of parameters it
+//! declares, not counting the receiver (`self`, `cls`, `this`). Variadic
+//! parameters (`*args`, `...rest`) count as one.
+//!
+//! [`migration::match_candidates()`]: crate::migration::match_candidates
+
+use crate::graph::{AttrValue, Graph, NodeType};
+use std::collections::HashMap;
+use tree_sitter::{Node as TsNode, Tree};
+
+/// Node attribute holding the number of parameters of a function or method
+pub const ARITY_ATTRIBUTE: &str = "arity";
+
+/// Parameter list kinds that are not parameters
+const NOT_PARAMETERS: &[&str] = &[
+    "comment",
+    "keyword_separator",
+    "positional_separator",
+    "attribute_item",
+    "self_parameter",
+];
+
+/// Receiver names, skipped when they are the first parameter
+const RECEIVERS: &[&str] = &["self", "cls", "this"];
+
+/// Set [`ARITY_ATTRIBUTE`] on the functions and methods of `graph` defined
+/// in `tree`
+///
+/// A definition is matched to its node by name and first line.
+pub(crate) fn tag_arity(graph: &mut Graph, tree: &Tree, source: &str) {
+    let mut symbols: HashMap<(String, usize), Vec<_>> = HashMap::new();
+    for idx in graph.node_indices().collect::<Vec<_>>() {
+        let Some(node) = graph.node_weight(idx) else {
+            continue;
+        };
+        if !matches!(node.node_type, NodeType::Function | NodeType::Method) {
+            continue;
+        }
+        if let Some((start, _)) = node.line_range {
+            symbols
+                .entry((node.name.clone(), start))
+                .or_default()
+                .push(idx);
+        }
+    }
+    if symbols.is_empty() {
+        return;
+    }
+
+    let source = source.as_bytes();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+        let Some(name) = node
+            .child_by_field_name("name")
+            .and_then(|name| name.utf8_text(source).ok())
+        else {
+            continue;
+        };
+        let Some(arity) = arity(&node, source) else {
+            continue;
+        };
+        let key = (name.to_string(), node.start_position().row + 1);
+        for &idx in symbols.get(&key).into_iter().flatten() {
+            if let Some(symbol) = graph.node_weight_mut(idx) {
+                symbol
+                    .attributes
+                    .insert(ARITY_ATTRIBUTE.to_string(), AttrValue::Int(arity as i64));
+            }
+        }
+    }
+}
+
+/// Number of parameters of the function `node` defines, directly or as the
+/// value of a declaration (`const f = (a, b) => ...`)
+fn arity(node: &TsNode, source: &[u8]) -> Option<usize> {
+    let function = match node.child_by_field_name("value") {
+        Some(value) if matches!(value.kind(), "arrow_function" | "function_expression") => value,
+        _ => *node,
+    };
+    if function.child_by_field_name("parameter").is_some() {
+        // `x => ...`
+        return Some(1);
+    }
+    let parameters = function.child_by_field_name("parameters")?;
+    let mut cursor = parameters.walk();
+    let count = parameters
+        .named_children(&mut cursor)
+        .filter(|parameter| !NOT_PARAMETERS.contains(&parameter.kind()))
+        .enumerate()
+        .filter(|(i, parameter)| *i > 0 || !is_receiver(parameter, source))
+        .count();
+    Some(count)
+}
+
+/// Whether `parameter` is `self`, `cls` or `this`
+fn is_receiver(parameter: &TsNode, source: &[u8]) -> bool {
+    let name = parameter
+        .child_by_field_name("pattern")
+        .unwrap_or(*parameter);
+    name.utf8_text(source)
+        .is_ok_and(|text| RECEIVERS.contains(&text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_file_in, GranularityLevel, ParseSession};
+    use std::path::Path;
+
+    fn arities(path: &str, source: &str) -> Vec<(String, i64)> {
+        let path = Path::new(path);
+        let mut session = ParseSession::new().with_granularity(Some(GranularityLevel::Methods));
+        session.add_source(path, source).unwrap();
+        let parsed = parse_file_in(&mut session, path).unwrap();
+        let mut arities: Vec<(String, i64)> = parsed
+            .graph
+            .nodes()
+            .filter_map(|node| match node.attributes.get(ARITY_ATTRIBUTE) {
+                Some(AttrValue::Int(arity)) => Some((node.name.clone(), *arity)),
+                _ => None,
+            })
+            .collect();
+        arities.sort();
+        arities
+    }
+
+    #[test]
+    fn test_arity_skips_receivers() {
+        let python = "\
+class Cart:
+    def add(self, item, *, qty=1):
+        pass
+
+def total(cart, /, *discounts, **options):
+    pass
+";
+        assert_eq!(
+            arities("cart.py", python),
+            [("add".to_string(), 2), ("total".to_string(), 3)]
+        );
+
+        let rust =
+            "struct Cart;\nimpl Cart {\n    fn add(&mut self, item: u32) {}\n}\nfn new() {}\n";
+        assert_eq!(
+            arities("cart.rs", rust),
+            [("add".to_string(), 1), ("new".to_string(), 0)]
+        );
+
+        let typescript = "\
+function total(this: Cart, items: Item[], ...rest: number[]) {}
+const double = x => x * 2;
+const add = (a: number, b?: number) => a;
+";
+        assert_eq!(
+            arities("cart.ts", typescript),
+            [
+                ("add".to_string(), 2),
+                ("double".to_string(), 1),
+                ("total".to_string(), 2)
+            ]
+        );
+    }
+}
@@ -0,0 +1,184 @@
+//! Lazily parsed files, one at a time
+//!
+//! [`parse_directory()`](super::parse_directory) returns nothing until every
+//! file has been parsed. [`parse_directory_stream()`] discovers the files up
+//! front but parses each one only when the iterator is advanced, so the
+//! language server or a watcher can merge results
+//! ([`MultiFileGraph::merge_file_parse()`](super::MultiFileGraph::merge_file_parse))
+//! and show them while the rest of the repository is still being parsed.
+
+use super::{namespace_ids, namespaced_id, project_relative_id, relativize_ids};
+use super::{parse_file_in, FileParseResult, ParseSession};
+#[cfg(feature = "std-fs")]
+use crate::config::Config;
+#[cfg(feature = "std-fs")]
+use crate::error::GraphMigratorError;
+use crate::error::Result;
+use std::path::{Path, PathBuf};
+
+/// Iterator over per-file parse results; see [`parse_directory_stream()`]
+///
+/// Files are yielded in sorted order, the same order the batch functions
+/// merge them in. A file that cannot be read or parsed yields an `Err`, and
+/// the stream carries on with the next file.
+pub struct ParseStream {
+    files: std::vec::IntoIter<PathBuf>,
+    session: ParseSession,
+    /// Canonical root when IDs are made root-relative
+    id_root: Option<PathBuf>,
+    namespace: Option<String>,
+}
+
+impl ParseStream {
+    fn new(mut files: Vec<PathBuf>, id_root: Option<PathBuf>, namespace: Option<String>) -> Self {
+        files.sort();
+        Self {
+            files: files.into_iter(),
+            session: ParseSession::uncached(),
+            id_root,
+            namespace,
+        }
+    }
+
+    /// Apply the configured ID rewrites to one file's graph, call sites and
+    /// type references
+    fn rewrite_ids(&self, parsed: &mut FileParseResult) {
+        if let Some(root) = &self.id_root {
+            relativize_ids(&mut parsed.graph, root);
+            for call in &mut parsed.unresolved_calls {
+                if let Some(caller) = project_relative_id(&call.caller, root) {
+                    call.caller = caller;
+                }
+            }
+            for reference in &mut parsed.type_references {
+                if let Some(function) = project_relative_id(&reference.function, root) {
+                    reference.function = function;
+                }
+            }
+        }
+        if let Some(namespace) = &self.namespace {
+            namespace_ids(&mut parsed.graph, namespace);
+            for call in &mut parsed.unresolved_calls {
+                call.caller = namespaced_id(namespace, &call.caller);
+            }
+            for reference in &mut parsed.type_references {
+                reference.function = namespaced_id(namespace, &reference.function);
+            }
+        }
+    }
+}
+
+impl Iterator for ParseStream {
+    type Item = Result<FileParseResult>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let path = self.files.next()?;
+        let _span = tracing::debug_span!("parse_stream", path = %path.display()).entered();
+        Some(parse_file_in(&mut self.session, &path).map(|mut parsed| {
+            self.rewrite_ids(&mut parsed);
+            parsed
+        }))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.files.size_hint()
+    }
+}
+
+impl ExactSizeIterator for ParseStream {}
+
+/// Parse `paths` lazily, one file per call to `next()`
+pub fn parse_files_stream(paths: &[&Path]) -> ParseStream {
+    ParseStream::new(
+        paths.iter().map(|path| path.to_path_buf()).collect(),
+        None,
+        None,
+    )
+}
+
+/// Discover a project's files and parse them lazily
+///
+/// Honors `<root>/migrator.toml` like [`parse_directory()`](super::parse_directory).
+///
+/// # Errors
+/// Returns an error if the project's configuration cannot be loaded.
+///
+/// # Example
+/// ```no_run
+/// use graph_migrator_core::parser::{self, MultiFileGraph};
+///
+/// let mut multi = MultiFileGraph::new();
+/// for parsed in parser::parse_directory_stream(std::path::Path::new("my_project")).unwrap() {
+///     match parsed {
+///         Ok(parsed) => {
+///             multi.merge_file_parse(parsed).unwrap();
+///             println!("{} nodes so far", multi.graph.node_count());
+///         }
+///         Err(err) => eprintln!("skipped: {}", err),
+///     }
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn parse_directory_stream(root: &Path) -> Result<ParseStream> {
+    let config = Config::from_project_root(root)?;
+    parse_directory_stream_with_config(root, &config)
+}
+
+/// [`parse_directory_stream()`] using an explicit configuration
+///
+/// Node IDs (and unresolved callers) are made root-relative when
+/// `relative_ids` is set and prefixed when `namespace` is set, file by file.
+/// Extraction follows `granularity` and `edge_policy`.
+///
+/// # Errors
+/// Returns [`GraphMigratorError::Io`] if `relative_ids` is set and `root`
+/// cannot be resolved.
+#[cfg(feature = "std-fs")]
+pub fn parse_directory_stream_with_config(root: &Path, config: &Config) -> Result<ParseStream> {
+    let files = crate::discovery::discover_project(root, config);
+    let id_root = config
+        .path_resolver()
+        .id_root(root)
+        .map_err(|e| GraphMigratorError::io(root, e))?;
+    let mut stream = ParseStream::new(files, id_root, config.namespace.clone());
+    stream.session = ParseSession::for_project(root, config);
+    Ok(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_directory_with_config, MultiFileGraph};
+
+    #[test]
+    fn test_stream_merges_to_batch_graph() {
+        let root = Path::new("tests/test-fixtures/multi-file-project");
+        let config = Config {
+            relative_ids: true,
+            namespace: Some("app".to_string()),
+            ..Config::default()
+        };
+
+        let stream = parse_directory_stream_with_config(root, &config).unwrap();
+        let files = stream.len();
+        assert!(files > 1);
+        let mut streamed = MultiFileGraph::new();
+        let mut seen = 0;
+        for parsed in stream {
+            let parsed = parsed.unwrap();
+            assert!(parsed.graph.nodes().all(|node| node.id.starts_with("app:")));
+            streamed.merge_file_parse(parsed).unwrap();
+            seen += 1;
+        }
+        assert_eq!(seen, files);
+
+        let batch = parse_directory_with_config(root, &config).unwrap();
+        assert_eq!(streamed.graph.fingerprint(), batch.graph.fingerprint());
+        assert_eq!(streamed.unresolved_calls, batch.unresolved_calls);
+        assert_eq!(streamed.type_references, batch.type_references);
+
+        let mut missing = parse_files_stream(&[Path::new("does/not/exist.py")]);
+        assert!(missing.next().unwrap().is_err());
+        assert!(missing.next().is_none());
+    }
+}
@@ -0,0 +1,126 @@
+//! How file paths are identified in the graph ([`PathResolver`])
+//!
+//! Node IDs and file paths embed the path a file was parsed under, so the
+//! way paths are resolved decides whether two scans of the same checkout
+//! agree. Resolving with [`std::fs::canonicalize`] follows symlinks, fails
+//! on broken ones, and yields machine-specific paths (`/private/tmp/...`,
+//! a CI runner's workspace) that end up in every ID.
+//!
+//! The default, [`PathResolver::Relative`], only makes paths absolute and
+//! tidies `.` and `..`, without touching the filesystem, and directory scans
+//! then write IDs relative to the project root (`src/utils.py::helper`).
+//! [`PathResolver::Canonical`] keeps the old canonical, absolute IDs; select
+//! it with `relative_ids = false` in `migrator.toml`. State files saved with
+//! absolute IDs are read as relative ones (`store::load_relative()`) and
+//! rewritten by the next command that saves them (`store::relativize_ids()`).
+//! Node file paths stay absolute in memory; exports make them relative too
+//! (`parser::relativize_file_paths()`).
+
+use std::path::{Component, Path, PathBuf};
+
+/// Policy for turning the paths files are found at into the paths the graph
+/// knows them by
+///
+/// Handed to discovery ([`DiscoveryOptions::paths()`]) and parsing
+/// ([`ParseSession::with_paths()`]); [`Config::path_resolver()`] picks it
+/// from the configuration.
+///
+/// [`DiscoveryOptions::paths()`]: crate::discovery::DiscoveryOptions::paths
+/// [`ParseSession::with_paths()`]: crate::parser::ParseSession::with_paths
+/// [`Config::path_resolver()`]: crate::config::Config::path_resolver
+///
+/// # Example
+/// ```
+/// use graph_migrator_core::paths::PathResolver;
+/// use std::path::Path;
+///
+/// let resolver = PathResolver::default();
+/// let path = resolver.resolve(Path::new("/work/app/src/../lib/./utils.py")).unwrap();
+/// assert_eq!(path, Path::new("/work/app/lib/utils.py"));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PathResolver {
+    /// Absolute paths normalized lexically, symlinks left as found; directory
+    /// scans make IDs relative to the project root
+    #[default]
+    Relative,
+    /// Canonical paths, symlinks resolved; IDs keep the absolute path
+    Canonical,
+}
+
+impl PathResolver {
+    /// The path `path` is known by
+    ///
+    /// # Errors
+    /// Returns the I/O error of [`std::fs::canonicalize`] for
+    /// [`Canonical`](Self::Canonical) (e.g. the file or a symlink target is
+    /// missing), or of [`std::path::absolute`] for a relative path when the
+    /// working directory is unavailable.
+    pub fn resolve(&self, path: &Path) -> std::io::Result<PathBuf> {
+        match self {
+            Self::Relative => normalize(path),
+            Self::Canonical => std::fs::canonicalize(path),
+        }
+    }
+
+    /// Root to make node IDs relative to for a scan of `root`, if IDs are
+    /// relative under this policy
+    ///
+    /// # Errors
+    /// Returns the errors of [`resolve()`](Self::resolve).
+    pub fn id_root(&self, root: &Path) -> std::io::Result<Option<PathBuf>> {
+        match self {
+            Self::Relative => self.resolve(root).map(Some),
+            Self::Canonical => Ok(None),
+        }
+    }
+}
+
+/// `path` made absolute, with `.` dropped and `..` taking off the previous
+/// component
+///
+/// Lexical, so `link/..` is the directory holding `link`, not the parent of
+/// its target.
+fn normalize(path: &Path) -> std::io::Result<PathBuf> {
+    let absolute = std::path::absolute(path)?;
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_relative_resolves_broken_symlinks() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let link = temp_dir.path().join("link.py");
+        std::os::unix::fs::symlink(temp_dir.path().join("gone.py"), &link).unwrap();
+
+        assert!(PathResolver::Canonical.resolve(&link).is_err());
+        let resolved = PathResolver::Relative.resolve(&link).unwrap();
+        assert_eq!(resolved, std::path::absolute(&link).unwrap());
+    }
+
+    #[test]
+    fn test_relative_paths_are_made_absolute() {
+        let resolved = PathResolver::Relative
+            .resolve(Path::new("./src/../app.py"))
+            .unwrap();
+        assert_eq!(resolved, std::env::current_dir().unwrap().join("app.py"));
+        assert_eq!(
+            PathResolver::Canonical.id_root(Path::new(".")).unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,274 @@
+//! Migration progress over time
+//!
+//! A migration program is tracked by how the status counts move week to
+//! week. [`ProgressSnapshot::capture()`] summarizes a graph's statuses, in
+//! total and per migration unit and owner, and [`append()`] adds the
+//! summary to a JSON Lines history file (`[output] history`). Reading the
+//! history back with [`load_history()`], [`burndown()`] turns it into the
+//! remaining-work series of a burndown chart for the whole project or one
+//! unit or owner.
+//!
+//! Only project symbols are counted (see
+//! [`is_project_symbol()`](crate::queries::migration::is_project_symbol())).
+//! Owners are only included if [`enrich::owners()`](crate::enrich::owners())
+//! ran first.
+
+#[cfg(feature = "std-fs")]
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::{Graph, MigrationStatus};
+use crate::migration;
+use crate::queries::migration::is_project_symbol;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+#[cfg(feature = "std-fs")]
+use std::io::Write;
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+
+/// Symbol count per migration status
+pub type StatusCounts = BTreeMap<MigrationStatus, usize>;
+
+/// Status counts at one point in time; one line of the history file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgressSnapshot {
+    /// When the snapshot was taken, in seconds since the Unix epoch
+    pub at: u64,
+    /// Counts over all project symbols
+    pub total: StatusCounts,
+    /// Counts per migration unit ID, over the unit's members
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub units: BTreeMap<String, StatusCounts>,
+    /// Counts per owner; a symbol with several owners counts for each
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub owners: BTreeMap<String, StatusCounts>,
+}
+
+impl ProgressSnapshot {
+    /// Summarize the statuses in `graph` as of `at`
+    pub fn capture(graph: &Graph, at: u64) -> Self {
+        let mut snapshot = Self {
+            at,
+            total: StatusCounts::new(),
+            units: BTreeMap::new(),
+            owners: BTreeMap::new(),
+        };
+        let mut units: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (member, unit) in migration::memberships(graph) {
+            units.entry(member).or_default().push(unit);
+        }
+        for node in graph.nodes().filter(|node| is_project_symbol(node)) {
+            *snapshot.total.entry(node.status).or_default() += 1;
+            for unit in units.get(&node.id).into_iter().flatten() {
+                count(&mut snapshot.units, unit, node.status);
+            }
+            for owner in &node.owners {
+                count(&mut snapshot.owners, owner, node.status);
+            }
+        }
+        snapshot
+    }
+}
+
+fn count(groups: &mut BTreeMap<String, StatusCounts>, group: &str, status: MigrationStatus) {
+    *groups
+        .entry(group.to_string())
+        .or_default()
+        .entry(status)
+        .or_default() += 1;
+}
+
+/// Symbols in `counts` that are done: migrated or superseded
+pub fn done(counts: &StatusCounts) -> usize {
+    [MigrationStatus::Migrated, MigrationStatus::Superseded]
+        .iter()
+        .filter_map(|status| counts.get(status))
+        .sum()
+}
+
+/// Append `snapshot` as one line to the history file at `path`
+///
+/// Creates the file and its parent directories if needed.
+///
+/// # Errors
+/// Returns [`GraphMigratorError::Io`] if the file cannot be written.
+#[cfg(feature = "std-fs")]
+pub fn append(path: &Path, snapshot: &ProgressSnapshot) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(|e| GraphMigratorError::io(dir, e))?;
+    }
+    let line =
+        serde_json::to_string(snapshot).map_err(|e| GraphMigratorError::Export(e.to_string()))?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| GraphMigratorError::io(path, e))?;
+    writeln!(file, "{}", line).map_err(|e| GraphMigratorError::io(path, e))
+}
+
+/// Read every snapshot in the history file at `path`, oldest first
+///
+/// A missing file is an empty history. Blank lines are skipped.
+///
+/// # Errors
+/// Returns an error if the file exists but cannot be read, or a line is
+/// not a snapshot.
+#[cfg(feature = "std-fs")]
+pub fn load_history(path: &Path) -> Result<Vec<ProgressSnapshot>> {
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+    let text = std::fs::read_to_string(path).map_err(|e| GraphMigratorError::io(path, e))?;
+    let mut history = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            serde_json::from_str(line)
+                .map_err(|e| GraphMigratorError::parse(path, format!("line {}: {}", number + 1, e)))
+        })
+        .collect::<Result<Vec<ProgressSnapshot>>>()?;
+    history.sort_by_key(|snapshot| snapshot.at);
+    Ok(history)
+}
+
+/// Which counts of a snapshot a [`burndown()`] follows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BurndownScope {
+    /// All project symbols
+    All,
+    /// Members of one migration unit, by unit ID
+    Unit(String),
+    /// Symbols of one owner
+    Owner(String),
+}
+
+/// One point of a burndown series
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BurndownPoint {
+    /// Snapshot time, in seconds since the Unix epoch
+    pub at: u64,
+    /// Symbols in scope
+    pub total: usize,
+    /// Symbols migrated or superseded
+    pub done: usize,
+    /// Symbols not done yet
+    pub remaining: usize,
+}
+
+/// The remaining-work series of `history` for `scope`, oldest first
+///
+/// Snapshots that do not mention the unit or owner are skipped.
+pub fn burndown(history: &[ProgressSnapshot], scope: &BurndownScope) -> Vec<BurndownPoint> {
+    history
+        .iter()
+        .filter_map(|snapshot| {
+            let counts = match scope {
+                BurndownScope::All => &snapshot.total,
+                BurndownScope::Unit(unit) => snapshot.units.get(unit)?,
+                BurndownScope::Owner(owner) => snapshot.owners.get(owner)?,
+            };
+            let total: usize = counts.values().sum();
+            let done = done(counts);
+            Some(BurndownPoint {
+                at: snapshot.at,
+                total,
+                done,
+                remaining: total - done,
+            })
+        })
+        .collect()
+}
+
+/// When the remaining work would reach zero at the average rate so far
+///
+/// The rate is measured between the first and last points. Returns `None`
+/// with fewer than two points, or if nothing was completed in between.
+pub fn projected_completion(points: &[BurndownPoint]) -> Option<u64> {
+    let (first, last) = (points.first()?, points.last()?);
+    let elapsed = last.at.checked_sub(first.at).filter(|&e| e > 0)?;
+    let completed = last.done.checked_sub(first.done).filter(|&c| c > 0)?;
+    let rate = completed as f64 / elapsed as f64;
+    Some(last.at + (last.remaining as f64 / rate).ceil() as u64)
+}
+
+/// `at` (seconds since the Unix epoch) as a UTC `YYYY-MM-DD` date
+pub fn format_date(at: u64) -> String {
+    // Civil-from-days, counting 400-year eras from 0000-03-01
+    let days = (at / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Node, NodeType};
+
+    #[test]
+    fn test_history_and_burndown() {
+        let mut graph = Graph::new();
+        for name in ["a", "b", "c", "d"] {
+            let idx = graph.add_node(Node::new(
+                format!("app.py::{}", name),
+                name,
+                NodeType::Function,
+                "python",
+                "app.py",
+            ));
+            graph.node_weight_mut(idx).unwrap().owners = vec!["@team".to_string()];
+        }
+        graph.add_node(Node::new(
+            "app.py",
+            "app",
+            NodeType::File,
+            "python",
+            "app.py",
+        ));
+        migration::create_unit(&mut graph, "unit::ab", "ab", &["app.py::a", "app.py::b"]).unwrap();
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("nested/history.jsonl");
+        assert!(load_history(&path).unwrap().is_empty());
+
+        let day = 86_400;
+        append(&path, &ProgressSnapshot::capture(&graph, 10 * day)).unwrap();
+        migration::transition(&mut graph, "app.py::a", MigrationStatus::Migrated).unwrap();
+        migration::transition(&mut graph, "app.py::c", MigrationStatus::InProgress).unwrap();
+        append(&path, &ProgressSnapshot::capture(&graph, 12 * day)).unwrap();
+
+        let history = load_history(&path).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].total[&MigrationStatus::Pending], 2);
+        assert_eq!(done(&history[1].units["unit::ab"]), 1);
+
+        let all = burndown(&history, &BurndownScope::All);
+        let remaining: Vec<(usize, usize)> = all.iter().map(|p| (p.total, p.remaining)).collect();
+        assert_eq!(remaining, vec![(4, 4), (4, 3)]);
+        // One symbol per two days, three left
+        assert_eq!(projected_completion(&all), Some(18 * day));
+
+        let unit = burndown(&history, &BurndownScope::Unit("unit::ab".to_string()));
+        assert_eq!(unit[1].remaining, 1);
+        assert_eq!(
+            burndown(&history, &BurndownScope::Owner("@team".to_string())).len(),
+            2
+        );
+        assert!(burndown(&history, &BurndownScope::Owner("@nobody".to_string())).is_empty());
+
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(1_709_164_800), "2024-02-29");
+    }
+}
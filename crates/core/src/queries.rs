@@ -1,6 +0,0 @@
-//! Graph query functions
-//!
-//! This module will provide utilities for querying the dependency graph,
-//! such as finding upstream/downstream dependencies, leaf nodes, etc.
-
-// Placeholder for query functions (will be added in future epics)
@@ -0,0 +1,243 @@
+//! Centrality scoring for migration prioritization
+//!
+//! - **PageRank** rewards symbols that many (important) symbols depend on:
+//!   high scores mark load-bearing code that is risky to migrate early.
+//! - **Betweenness** rewards symbols that sit on many shortest dependency
+//!   paths: high scores mark chokepoints whose migration affects many flows.
+//!
+//! Both run over dependency edges only (calls, imports, inheritance).
+//! Scores can be attached to nodes with [`CentralityScores::attach()`], after
+//! which they are included by every serde-based exporter.
+
+use super::Adjacency;
+use crate::graph::{Centrality, Graph};
+use std::collections::{BTreeMap, VecDeque};
+
+/// Default PageRank damping factor
+pub const DEFAULT_DAMPING: f64 = 0.85;
+
+const PAGERANK_MAX_ITERATIONS: usize = 100;
+const PAGERANK_TOLERANCE: f64 = 1e-10;
+
+/// Centrality scores keyed by node ID
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CentralityScores {
+    scores: BTreeMap<String, Centrality>,
+}
+
+impl CentralityScores {
+    /// Scores for a single node
+    pub fn get(&self, id: &str) -> Option<&Centrality> {
+        self.scores.get(id)
+    }
+
+    /// All scores, ordered by PageRank (highest first, ties broken by ID)
+    pub fn ranked(&self) -> Vec<(&str, &Centrality)> {
+        let mut ranked: Vec<(&str, &Centrality)> =
+            self.scores.iter().map(|(id, c)| (id.as_str(), c)).collect();
+        ranked.sort_by(|a, b| {
+            b.1.pagerank
+                .total_cmp(&a.1.pagerank)
+                .then_with(|| a.0.cmp(b.0))
+        });
+        ranked
+    }
+
+    /// Store each score on its node's `centrality` field
+    pub fn attach(&self, graph: &mut Graph) {
+        let indices: Vec<_> = graph.node_indices().collect();
+        for idx in indices {
+            if let Some(node) = graph.node_weight_mut(idx) {
+                node.centrality = self.scores.get(&node.id).copied();
+            }
+        }
+    }
+}
+
+/// Compute PageRank (with [`DEFAULT_DAMPING`]) and betweenness for every node
+pub fn centrality(graph: &Graph) -> CentralityScores {
+    let pagerank = pagerank(graph, DEFAULT_DAMPING);
+    let betweenness = betweenness(graph);
+
+    let scores = pagerank
+        .into_iter()
+        .map(|(id, pr)| {
+            let bc = betweenness.get(&id).copied().unwrap_or(0.0);
+            (
+                id,
+                Centrality {
+                    pagerank: pr,
+                    betweenness: bc,
+                },
+            )
+        })
+        .collect();
+    CentralityScores { scores }
+}
+
+/// PageRank over dependency edges, keyed by node ID
+///
+/// An edge `A → B` ("A depends on B") transfers rank from A to B. Rank of
+/// nodes without outgoing dependencies is spread evenly over all nodes, so
+/// scores always sum to 1.0.
+pub fn pagerank(graph: &Graph, damping: f64) -> BTreeMap<String, f64> {
+    let adjacency = Adjacency::dependencies(graph);
+    let n = adjacency.len();
+    if n == 0 {
+        return BTreeMap::new();
+    }
+
+    let uniform = 1.0 / n as f64;
+    let mut rank = vec![uniform; n];
+
+    for _ in 0..PAGERANK_MAX_ITERATIONS {
+        let dangling: f64 = (0..n)
+            .filter(|&i| adjacency.outgoing[i].is_empty())
+            .map(|i| rank[i])
+            .sum();
+        let base = (1.0 - damping) * uniform + damping * dangling * uniform;
+
+        let mut next = vec![base; n];
+        for (i, targets) in adjacency.outgoing.iter().enumerate() {
+            if targets.is_empty() {
+                continue;
+            }
+            let share = damping * rank[i] / targets.len() as f64;
+            for &(t, _) in targets {
+                next[t] += share;
+            }
+        }
+
+        let delta: f64 = rank.iter().zip(&next).map(|(a, b)| (a - b).abs()).sum();
+        rank = next;
+        if delta < PAGERANK_TOLERANCE {
+            break;
+        }
+    }
+
+    to_id_map(graph, &adjacency, rank)
+}
+
+/// Normalized betweenness centrality over dependency edges, keyed by node ID
+///
+/// Uses Brandes' algorithm for unweighted directed graphs; scores are divided
+/// by `(n - 1)(n - 2)` so they fall in `[0.0, 1.0]`.
+pub fn betweenness(graph: &Graph) -> BTreeMap<String, f64> {
+    let adjacency = Adjacency::dependencies(graph);
+    let n = adjacency.len();
+    let mut centrality = vec![0.0; n];
+
+    for source in 0..n {
+        // Single-source shortest paths (BFS), counting shortest paths per node
+        let mut stack = Vec::with_capacity(n);
+        let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+        let mut paths = vec![0.0_f64; n];
+        let mut distance = vec![usize::MAX; n];
+        paths[source] = 1.0;
+        distance[source] = 0;
+
+        let mut queue = VecDeque::from([source]);
+        while let Some(v) = queue.pop_front() {
+            stack.push(v);
+            for &(w, _) in &adjacency.outgoing[v] {
+                if distance[w] == usize::MAX {
+                    distance[w] = distance[v] + 1;
+                    queue.push_back(w);
+                }
+                if distance[w] == distance[v] + 1 {
+                    paths[w] += paths[v];
+                    predecessors[w].push(v);
+                }
+            }
+        }
+
+        // Accumulate dependencies in order of non-increasing distance
+        let mut dependency = vec![0.0; n];
+        while let Some(w) = stack.pop() {
+            for &v in &predecessors[w] {
+                dependency[v] += paths[v] / paths[w] * (1.0 + dependency[w]);
+            }
+            if w != source {
+                centrality[w] += dependency[w];
+            }
+        }
+    }
+
+    if n > 2 {
+        let scale = 1.0 / ((n - 1) * (n - 2)) as f64;
+        for value in &mut centrality {
+            *value *= scale;
+        }
+    }
+
+    to_id_map(graph, &adjacency, centrality)
+}
+
+fn to_id_map(graph: &Graph, adjacency: &Adjacency, values: Vec<f64>) -> BTreeMap<String, f64> {
+    adjacency
+        .nodes
+        .iter()
+        .zip(values)
+        .filter_map(|(&idx, value)| graph.node_weight(idx).map(|n| (n.id.clone(), value)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::EdgeType;
+    use crate::test_util::{add, link};
+
+    /// a → hub, b → hub, c → hub, hub → leaf
+    fn hub_graph() -> Graph {
+        let mut graph = Graph::new();
+        let hub = add(&mut graph, "hub");
+        let leaf = add(&mut graph, "leaf");
+        for name in ["a", "b", "c"] {
+            let caller = add(&mut graph, name);
+            link(&mut graph, caller, hub, EdgeType::Calls);
+        }
+        link(&mut graph, hub, leaf, EdgeType::Calls);
+        graph
+    }
+
+    #[test]
+    fn test_pagerank_sums_to_one_and_ranks_dependencies_higher() {
+        let scores = pagerank(&hub_graph(), DEFAULT_DAMPING);
+
+        let total: f64 = scores.values().sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert!(scores["m.py::hub"] > scores["m.py::a"]);
+        assert!(scores["m.py::leaf"] > scores["m.py::a"]);
+    }
+
+    #[test]
+    fn test_betweenness_identifies_chokepoint() {
+        let scores = betweenness(&hub_graph());
+
+        // hub lies on the only path from a, b, c to leaf: 3 pairs / (4 * 3)
+        assert!((scores["m.py::hub"] - 0.25).abs() < 1e-9);
+        assert_eq!(scores["m.py::a"], 0.0);
+        assert_eq!(scores["m.py::leaf"], 0.0);
+    }
+
+    #[test]
+    fn test_attach_and_export() {
+        let mut graph = hub_graph();
+        let scores = centrality(&graph);
+        scores.attach(&mut graph);
+
+        let hub = graph.nodes().find(|n| n.name == "hub").unwrap();
+        assert_eq!(hub.centrality, scores.get("m.py::hub").copied());
+
+        let json = serde_json::to_value(hub).unwrap();
+        assert!(json["centrality"]["pagerank"].is_number());
+
+        assert_eq!(scores.ranked()[0].0, "m.py::leaf");
+    }
+
+    #[test]
+    fn test_empty_graph() {
+        assert!(centrality(&Graph::new()).ranked().is_empty());
+    }
+}
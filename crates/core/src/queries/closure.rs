@@ -0,0 +1,146 @@
+//! Transitive reachability in either direction
+//!
+//! "Everything that eventually calls X" (incoming) and "everything X
+//! eventually uses" (outgoing) are the two questions behind most migration
+//! impact analysis, so they are exposed directly instead of leaving every
+//! caller to write its own traversal.
+
+use super::Adjacency;
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::{Direction, EdgeType};
+use crate::view::GraphView;
+use std::collections::BTreeSet;
+
+/// IDs of every node reachable from `node_id` in `direction`
+///
+/// - [`Direction::Outgoing`]: everything `node_id` transitively depends on
+/// - [`Direction::Incoming`]: everything that transitively depends on `node_id`
+///
+/// Only edges whose type is in `edge_types` are followed; an empty slice
+/// means all dependency edges ([`EdgeType::is_dependency`]). The starting
+/// node is included only if it lies on a cycle. Runs on any [`GraphView`],
+/// such as a [`Graph`](crate::Graph) or a `compact::CompactGraph`.
+///
+/// # Errors
+/// Returns [`GraphMigratorError::NodeNotFound`] if `node_id` is not in the graph.
+///
+/// # Example
+/// ```no_run
+/// use graph_migrator_core::{parser, queries};
+/// use graph_migrator_core::queries::Direction;
+///
+/// let multi = parser::parse_directory(std::path::Path::new("my_project")).unwrap();
+/// let callers = queries::closure(&multi.graph, "db.py::connect", Direction::Incoming, &[]).unwrap();
+/// println!("{} symbols eventually depend on connect()", callers.len());
+/// ```
+pub fn closure<G: GraphView>(
+    graph: &G,
+    node_id: &str,
+    direction: Direction,
+    edge_types: &[EdgeType],
+) -> Result<BTreeSet<String>> {
+    let adjacency = Adjacency::filtered(graph, |t| {
+        if edge_types.is_empty() {
+            t.is_dependency()
+        } else {
+            edge_types.contains(t)
+        }
+    });
+    let adjacency = match direction {
+        Direction::Outgoing => adjacency,
+        Direction::Incoming => adjacency.reversed(),
+    };
+
+    let start = adjacency
+        .position_of(graph, node_id)
+        .ok_or_else(|| GraphMigratorError::NodeNotFound(node_id.to_string()))?;
+
+    let mut visited = vec![false; adjacency.len()];
+    let mut stack: Vec<usize> = adjacency.outgoing[start].iter().map(|&(t, _)| t).collect();
+    while let Some(v) = stack.pop() {
+        if visited[v] {
+            continue;
+        }
+        visited[v] = true;
+        stack.extend(adjacency.outgoing[v].iter().map(|&(t, _)| t));
+    }
+
+    Ok(visited
+        .iter()
+        .enumerate()
+        .filter(|(_, &seen)| seen)
+        .map(|(p, _)| adjacency.id(graph, p).to_string())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Graph;
+    use crate::test_util::{add, link};
+
+    fn ids(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|n| format!("m.py::{}", n)).collect()
+    }
+
+    /// main -> handler -> db, handler -> log, cli -Imports-> handler, file -Contains-> main
+    fn sample() -> Graph {
+        let mut graph = Graph::new();
+        let main = add(&mut graph, "main");
+        let handler = add(&mut graph, "handler");
+        let db = add(&mut graph, "db");
+        let log = add(&mut graph, "log");
+        let cli = add(&mut graph, "cli");
+        let file = add(&mut graph, "file");
+        link(&mut graph, main, handler, EdgeType::Calls);
+        link(&mut graph, handler, db, EdgeType::Calls);
+        link(&mut graph, handler, log, EdgeType::Calls);
+        link(&mut graph, cli, handler, EdgeType::Imports);
+        link(&mut graph, file, main, EdgeType::Contains);
+        graph
+    }
+
+    #[test]
+    fn test_outgoing_closure() {
+        let graph = sample();
+
+        let used = closure(&graph, "m.py::main", Direction::Outgoing, &[]).unwrap();
+
+        assert_eq!(used, ids(&["db", "handler", "log"]));
+    }
+
+    #[test]
+    fn test_incoming_closure_with_edge_filter() {
+        let graph = sample();
+
+        let dependents = closure(&graph, "m.py::db", Direction::Incoming, &[]).unwrap();
+        assert_eq!(dependents, ids(&["cli", "handler", "main"]));
+
+        // Structural edges are only followed when asked for
+        let callers = closure(
+            &graph,
+            "m.py::db",
+            Direction::Incoming,
+            &[EdgeType::Calls, EdgeType::Contains],
+        )
+        .unwrap();
+        assert_eq!(callers, ids(&["file", "handler", "main"]));
+    }
+
+    #[test]
+    fn test_cycle_includes_start_and_missing_node_errors() {
+        let mut graph = Graph::new();
+        let a = add(&mut graph, "a");
+        let b = add(&mut graph, "b");
+        link(&mut graph, a, b, EdgeType::Calls);
+        link(&mut graph, b, a, EdgeType::Calls);
+
+        let reachable = closure(&graph, "m.py::a", Direction::Outgoing, &[]).unwrap();
+        assert_eq!(reachable, ids(&["a", "b"]));
+
+        assert!(matches!(
+            closure(&graph, "m.py::missing", Direction::Outgoing, &[]),
+            Err(GraphMigratorError::NodeNotFound(_))
+        ));
+    }
+}
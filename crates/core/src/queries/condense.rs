@@ -0,0 +1,213 @@
+//! Condensation: mutually dependent symbols collapsed into one unit
+//!
+//! Migration order is "dependencies first", which is only well defined on a
+//! DAG. Mutually recursive functions and import cycles have to move
+//! together, so [`condense()`] collapses every strongly connected component
+//! into a single [`Component`] and returns the resulting DAG, ordered so
+//! each component comes after everything it depends on.
+
+use super::Adjacency;
+use crate::graph::Graph;
+use petgraph::graph::{DiGraph, NodeIndex};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap};
+
+/// One strongly connected component of the dependency graph
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Component {
+    /// Member node IDs, sorted
+    pub members: Vec<String>,
+    /// Whether the members depend on each other in a cycle (several
+    /// members, or one that depends on itself)
+    pub cyclic: bool,
+}
+
+impl Component {
+    /// Smallest member ID, used to name the component
+    pub fn representative(&self) -> &str {
+        self.members.first().map_or("", String::as_str)
+    }
+}
+
+/// The condensation DAG returned by [`condense()`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Condensation {
+    /// Components in dependency order: each comes after every component it
+    /// depends on; ties go to the smaller representative
+    pub components: Vec<Component>,
+    /// `(dependent, dependency)` positions in `components`, sorted and
+    /// deduplicated; always from a later component to an earlier one
+    pub edges: Vec<(usize, usize)>,
+    /// Position in `components` of each node ID
+    pub component_of: BTreeMap<String, usize>,
+}
+
+impl Condensation {
+    /// The component containing `id`
+    pub fn component(&self, id: &str) -> Option<&Component> {
+        self.component_of.get(id).map(|&c| &self.components[c])
+    }
+
+    /// Components that are cycles, in dependency order
+    pub fn cycles(&self) -> impl Iterator<Item = &Component> {
+        self.components.iter().filter(|c| c.cyclic)
+    }
+}
+
+/// Collapse the strongly connected components of the dependency graph
+///
+/// Only dependency edges ([`EdgeType::is_dependency`](crate::graph::EdgeType::is_dependency))
+/// are followed. Every node is in exactly one component.
+///
+/// # Example
+/// ```no_run
+/// use graph_migrator_core::{parser, queries};
+///
+/// let multi = parser::parse_directory(std::path::Path::new("my_project")).unwrap();
+/// let dag = queries::condense(&multi.graph);
+/// for cycle in dag.cycles() {
+///     println!("migrate together: {}", cycle.members.join(", "));
+/// }
+/// ```
+pub fn condense(graph: &Graph) -> Condensation {
+    let adjacency = Adjacency::dependencies(graph);
+    let mut dag: DiGraph<(), ()> = DiGraph::with_capacity(adjacency.len(), 0);
+    for _ in 0..adjacency.len() {
+        dag.add_node(());
+    }
+    let mut self_loops = vec![false; adjacency.len()];
+    for (from, targets) in adjacency.outgoing.iter().enumerate() {
+        for &(to, _) in targets {
+            if from == to {
+                self_loops[from] = true;
+            } else {
+                dag.add_edge(NodeIndex::new(from), NodeIndex::new(to), ());
+            }
+        }
+    }
+
+    let sccs = petgraph::algo::tarjan_scc(&dag);
+    let mut scc_of = vec![0; adjacency.len()];
+    for (c, members) in sccs.iter().enumerate() {
+        for member in members {
+            scc_of[member.index()] = c;
+        }
+    }
+    // Dense positions follow node-ID order, so the smallest is the representative
+    let representative: Vec<usize> = sccs
+        .iter()
+        .map(|members| members.iter().map(|m| m.index()).min().unwrap_or(0))
+        .collect();
+
+    let mut dependencies: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); sccs.len()];
+    let mut dependents: Vec<Vec<usize>> = vec![Vec::new(); sccs.len()];
+    for (from, targets) in adjacency.outgoing.iter().enumerate() {
+        for &(to, _) in targets {
+            let (a, b) = (scc_of[from], scc_of[to]);
+            if a != b && dependencies[a].insert(b) {
+                dependents[b].push(a);
+            }
+        }
+    }
+
+    // Kahn's algorithm, dependencies first, smallest representative among the ready
+    let mut waiting: Vec<usize> = dependencies.iter().map(BTreeSet::len).collect();
+    let mut ready: BinaryHeap<Reverse<(usize, usize)>> = (0..sccs.len())
+        .filter(|&c| waiting[c] == 0)
+        .map(|c| Reverse((representative[c], c)))
+        .collect();
+    let mut order = Vec::with_capacity(sccs.len());
+    let mut position = vec![0; sccs.len()];
+    while let Some(Reverse((_, c))) = ready.pop() {
+        position[c] = order.len();
+        order.push(c);
+        for &dependent in &dependents[c] {
+            waiting[dependent] -= 1;
+            if waiting[dependent] == 0 {
+                ready.push(Reverse((representative[dependent], dependent)));
+            }
+        }
+    }
+
+    let mut component_of = BTreeMap::new();
+    let components = order
+        .iter()
+        .map(|&c| {
+            let mut members: Vec<String> = sccs[c]
+                .iter()
+                .map(|m| adjacency.id(graph, m.index()).to_string())
+                .collect();
+            members.sort();
+            for id in &members {
+                component_of.insert(id.clone(), position[c]);
+            }
+            Component {
+                cyclic: members.len() > 1 || self_loops[sccs[c][0].index()],
+                members,
+            }
+        })
+        .collect();
+    let mut edges: Vec<(usize, usize)> = dependencies
+        .iter()
+        .enumerate()
+        .flat_map(|(a, deps)| deps.iter().map(move |&b| (a, b)))
+        .map(|(a, b)| (position[a], position[b]))
+        .collect();
+    edges.sort_unstable();
+
+    Condensation {
+        components,
+        edges,
+        component_of,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Edge, EdgeType, Node, NodeType};
+
+    #[test]
+    fn test_condense_collapses_cycles_in_dependency_order() {
+        let mut graph = Graph::new();
+        for name in ["main", "parse", "expr", "term", "log", "recurse"] {
+            let id = format!("app.py::{}", name);
+            graph.add_node(Node::new(id, name, NodeType::Function, "python", "app.py"));
+        }
+        let mut calls = |from: &str, to: &str| {
+            let from = graph.get_by_id(&format!("app.py::{}", from)).unwrap();
+            let to = graph.get_by_id(&format!("app.py::{}", to)).unwrap();
+            graph.add_edge(from, to, Edge::new(EdgeType::Calls));
+        };
+        // parse -> expr -> term -> expr is mutual recursion
+        calls("main", "parse");
+        calls("parse", "expr");
+        calls("expr", "term");
+        calls("term", "expr");
+        calls("term", "log");
+        calls("parse", "log");
+        calls("recurse", "recurse");
+
+        let dag = condense(&graph);
+        let order: Vec<&str> = dag.components.iter().map(|c| c.representative()).collect();
+        assert_eq!(
+            order,
+            vec![
+                "app.py::log",
+                "app.py::expr",
+                "app.py::parse",
+                "app.py::main",
+                "app.py::recurse"
+            ]
+        );
+        let cycle = dag.component("app.py::term").unwrap();
+        assert_eq!(cycle.members, vec!["app.py::expr", "app.py::term"]);
+        assert_eq!(dag.cycles().count(), 2);
+        assert!(!dag.component("app.py::log").unwrap().cyclic);
+
+        // Each edge points from a later component to an earlier one
+        assert_eq!(dag.edges, vec![(1, 0), (2, 0), (2, 1), (3, 2)]);
+        assert_eq!(dag.component_of.len(), 6);
+    }
+}
@@ -0,0 +1,276 @@
+//! Graph statistics for migration prioritization
+//!
+//! Answers "which modules should we migrate first?" with simple structural
+//! metrics: symbols with high fan-in are risky to change, symbols with zero
+//! fan-out are easy leaves, and long dependency chains bound how many
+//! migration waves are needed.
+//!
+//! Only dependency edges ([`EdgeType::is_dependency`](crate::graph::EdgeType::is_dependency))
+//! are counted.
+
+use crate::graph::Graph;
+use petgraph::stable_graph::NodeIndex;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+/// Per-node dependency counts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeMetrics {
+    /// Node ID
+    pub id: String,
+    /// Symbol name
+    pub name: String,
+    /// File where the symbol is defined
+    pub file_path: PathBuf,
+    /// Number of incoming dependency edges (how many things depend on this)
+    pub fan_in: usize,
+    /// Number of outgoing dependency edges (how many things this depends on)
+    pub fan_out: usize,
+}
+
+/// Whole-graph statistics returned by [`metrics()`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphMetrics {
+    /// Total number of nodes
+    pub node_count: usize,
+    /// Number of dependency edges
+    pub dependency_edge_count: usize,
+    /// Dependency edges divided by the maximum possible `n * (n - 1)`
+    pub density: f64,
+    /// Per-node metrics, sorted by node ID
+    pub nodes: Vec<NodeMetrics>,
+    /// Node count per source file (external nodes have no file and are not counted)
+    pub nodes_per_file: BTreeMap<PathBuf, usize>,
+    /// Node IDs along the longest dependency chain, dependent first
+    ///
+    /// Mutually dependent nodes (cycles) count as one step; the chain lists
+    /// one representative (smallest ID) per cycle.
+    pub longest_chain: Vec<String>,
+}
+
+impl GraphMetrics {
+    /// The `limit` nodes with the highest fan-in (ties broken by ID)
+    pub fn most_depended_upon(&self, limit: usize) -> Vec<&NodeMetrics> {
+        let mut sorted: Vec<&NodeMetrics> = self.nodes.iter().collect();
+        sorted.sort_by(|a, b| b.fan_in.cmp(&a.fan_in).then_with(|| a.id.cmp(&b.id)));
+        sorted.truncate(limit);
+        sorted
+    }
+}
+
+/// Compute statistics over a graph
+///
+/// # Example
+/// ```no_run
+/// use graph_migrator_core::{parser, queries};
+///
+/// let multi = parser::parse_directory(std::path::Path::new("my_project")).unwrap();
+/// let stats = queries::metrics(&multi.graph);
+/// for node in stats.most_depended_upon(10) {
+///     println!("{} ({} dependents)", node.id, node.fan_in);
+/// }
+/// ```
+pub fn metrics(graph: &Graph) -> GraphMetrics {
+    let mut fan_in: HashMap<NodeIndex, usize> = HashMap::new();
+    let mut fan_out: HashMap<NodeIndex, usize> = HashMap::new();
+    let mut dependency_edge_count = 0;
+
+    for (from, to, edge) in graph.edge_endpoints() {
+        if edge.edge_type.is_dependency() {
+            *fan_out.entry(from).or_default() += 1;
+            *fan_in.entry(to).or_default() += 1;
+            dependency_edge_count += 1;
+        }
+    }
+
+    let mut nodes = Vec::new();
+    let mut nodes_per_file = BTreeMap::new();
+    for idx in graph.node_indices() {
+        if let Some(node) = graph.node_weight(idx) {
+            if !node.external {
+                *nodes_per_file.entry(node.file_path.clone()).or_default() += 1;
+            }
+            nodes.push(NodeMetrics {
+                id: node.id.clone(),
+                name: node.name.clone(),
+                file_path: node.file_path.clone(),
+                fan_in: fan_in.get(&idx).copied().unwrap_or(0),
+                fan_out: fan_out.get(&idx).copied().unwrap_or(0),
+            });
+        }
+    }
+    nodes.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let node_count = graph.node_count();
+    let density = if node_count > 1 {
+        dependency_edge_count as f64 / (node_count * (node_count - 1)) as f64
+    } else {
+        0.0
+    };
+
+    GraphMetrics {
+        node_count,
+        dependency_edge_count,
+        density,
+        nodes,
+        nodes_per_file,
+        longest_chain: longest_chain(graph),
+    }
+}
+
+/// Longest dependency chain, with strongly connected components collapsed
+fn longest_chain(graph: &Graph) -> Vec<String> {
+    use petgraph::graph::DiGraph;
+
+    // Mirror the dependency subgraph so we can run petgraph's SCC algorithm
+    let mut dag: DiGraph<NodeIndex, ()> = DiGraph::new();
+    let mut local: HashMap<NodeIndex, petgraph::graph::NodeIndex> = HashMap::new();
+    for idx in graph.node_indices() {
+        local.insert(idx, dag.add_node(idx));
+    }
+    for (from, to, edge) in graph.edge_endpoints() {
+        if edge.edge_type.is_dependency() {
+            dag.add_edge(local[&from], local[&to], ());
+        }
+    }
+
+    let id_of = |idx: petgraph::graph::NodeIndex| -> &str {
+        graph
+            .node_weight(dag[idx])
+            .map(|n| n.id.as_str())
+            .unwrap_or("")
+    };
+
+    // tarjan_scc yields components in reverse topological order (sinks first)
+    let sccs = petgraph::algo::tarjan_scc(&dag);
+    let mut component = vec![0; dag.node_count()];
+    for (c, members) in sccs.iter().enumerate() {
+        for &member in members {
+            component[member.index()] = c;
+        }
+    }
+
+    // best[c] = (chain length starting at component c, next component on the chain)
+    let mut best: Vec<(usize, Option<usize>)> = vec![(0, None); sccs.len()];
+    for (c, members) in sccs.iter().enumerate() {
+        let mut longest: (usize, Option<usize>) = (1, None);
+        for &member in members {
+            for succ in dag.neighbors(member) {
+                let sc = component[succ.index()];
+                if sc == c {
+                    continue;
+                }
+                let candidate = best[sc].0 + 1;
+                let better = candidate > longest.0
+                    || (candidate == longest.0
+                        && longest.1.is_some_and(|cur| {
+                            representative(&sccs[sc], id_of) < representative(&sccs[cur], id_of)
+                        }));
+                if better {
+                    longest = (candidate, Some(sc));
+                }
+            }
+        }
+        best[c] = longest;
+    }
+
+    // Start from the longest chain, breaking ties by representative ID
+    let start = (0..sccs.len()).max_by(|&a, &b| {
+        best[a]
+            .0
+            .cmp(&best[b].0)
+            .then_with(|| representative(&sccs[b], id_of).cmp(representative(&sccs[a], id_of)))
+    });
+
+    let mut chain = Vec::new();
+    let mut current = start;
+    while let Some(c) = current {
+        chain.push(representative(&sccs[c], id_of).to_string());
+        current = best[c].1;
+    }
+    chain
+}
+
+/// Smallest node ID in a strongly connected component
+fn representative<'a>(
+    members: &[petgraph::graph::NodeIndex],
+    id_of: impl Fn(petgraph::graph::NodeIndex) -> &'a str,
+) -> &'a str {
+    members.iter().map(|&m| id_of(m)).min().unwrap_or("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{EdgeType, NodeType};
+    use crate::test_util::{add_node, link};
+
+    #[test]
+    fn test_fan_in_fan_out() {
+        let mut graph = Graph::new();
+        let main = add_node(&mut graph, "main.py::main", NodeType::Function);
+        let a = add_node(&mut graph, "a.py::a", NodeType::Function);
+        let helper = add_node(&mut graph, "a.py::helper", NodeType::Function);
+        link(&mut graph, main, helper, EdgeType::Calls);
+        link(&mut graph, a, helper, EdgeType::Calls);
+        // Structural edges are not dependencies
+        link(&mut graph, main, a, EdgeType::Contains);
+
+        let stats = metrics(&graph);
+
+        assert_eq!(stats.node_count, 3);
+        assert_eq!(stats.dependency_edge_count, 2);
+        assert!((stats.density - 2.0 / 6.0).abs() < 1e-9);
+
+        let top = stats.most_depended_upon(1);
+        assert_eq!(top[0].id, "a.py::helper");
+        assert_eq!(top[0].fan_in, 2);
+
+        let main_metrics = stats.nodes.iter().find(|n| n.name == "main").unwrap();
+        assert_eq!((main_metrics.fan_in, main_metrics.fan_out), (0, 1));
+
+        assert_eq!(stats.nodes_per_file[&PathBuf::from("a.py")], 2);
+        assert_eq!(stats.nodes_per_file[&PathBuf::from("main.py")], 1);
+    }
+
+    #[test]
+    fn test_longest_chain() {
+        let mut graph = Graph::new();
+        let a = add_node(&mut graph, "m.py::a", NodeType::Function);
+        let b = add_node(&mut graph, "m.py::b", NodeType::Function);
+        let c = add_node(&mut graph, "m.py::c", NodeType::Function);
+        let d = add_node(&mut graph, "m.py::d", NodeType::Function);
+        link(&mut graph, a, b, EdgeType::Calls);
+        link(&mut graph, b, c, EdgeType::Calls);
+        link(&mut graph, a, d, EdgeType::Calls);
+
+        let stats = metrics(&graph);
+
+        assert_eq!(stats.longest_chain, vec!["m.py::a", "m.py::b", "m.py::c"]);
+    }
+
+    #[test]
+    fn test_longest_chain_collapses_cycles() {
+        let mut graph = Graph::new();
+        let a = add_node(&mut graph, "m.py::a", NodeType::Function);
+        let b = add_node(&mut graph, "m.py::b", NodeType::Function);
+        let c = add_node(&mut graph, "m.py::c", NodeType::Function);
+        link(&mut graph, a, b, EdgeType::Calls);
+        link(&mut graph, b, a, EdgeType::Calls);
+        link(&mut graph, b, c, EdgeType::Calls);
+
+        let stats = metrics(&graph);
+
+        // {a, b} is one step represented by "a"
+        assert_eq!(stats.longest_chain, vec!["m.py::a", "m.py::c"]);
+    }
+
+    #[test]
+    fn test_empty_graph() {
+        let stats = metrics(&Graph::new());
+
+        assert_eq!(stats.node_count, 0);
+        assert_eq!(stats.density, 0.0);
+        assert!(stats.longest_chain.is_empty());
+    }
+}
@@ -0,0 +1,260 @@
+//! Migration coverage: which legacy symbols have a replacement yet
+//!
+//! Statuses say what people *claim* is done; `MigratedTo` links (see
+//! [`migration`](crate::migration)) say what each legacy symbol became. This
+//! query reports the second, so progress can be checked against the first.
+//!
+//! [`superseded_with_live_dependents()`] finds incomplete migrations: symbols
+//! marked `Superseded` that code still depends on. [`migration_frontier()`]
+//! lists what can be migrated next, cheapest first.
+
+use crate::graph::{EdgeType, Graph, MigrationStatus, Node, NodeType};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Legacy symbols split by whether they have been linked to a target
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationCoverage {
+    /// Legacy node ID → IDs of the nodes it was migrated to
+    pub linked: BTreeMap<String, Vec<String>>,
+    /// Legacy node IDs without a `MigratedTo` edge, sorted
+    pub unlinked: Vec<String>,
+}
+
+impl MigrationCoverage {
+    /// Number of legacy symbols considered
+    pub fn total(&self) -> usize {
+        self.linked.len() + self.unlinked.len()
+    }
+
+    /// Fraction of legacy symbols with a target, in `[0.0, 1.0]` (1.0 when there are none)
+    pub fn ratio(&self) -> f64 {
+        if self.total() == 0 {
+            1.0
+        } else {
+            self.linked.len() as f64 / self.total() as f64
+        }
+    }
+}
+
+/// Report which legacy symbols have `MigratedTo` links
+///
+/// Legacy symbols are the project's classes, functions, methods and
+/// variables that are not themselves the target of a `MigratedTo` edge.
+/// External, file, module and migration-unit nodes are not counted.
+///
+/// # Example
+/// ```no_run
+/// use graph_migrator_core::{parser, queries};
+///
+/// let multi = parser::parse_directory(std::path::Path::new("my_project")).unwrap();
+/// let coverage = queries::migration_coverage(&multi.graph);
+/// println!("{:.0}% linked", coverage.ratio() * 100.0);
+/// ```
+pub fn migration_coverage(graph: &Graph) -> MigrationCoverage {
+    let mut targets: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    let mut migrated_into: BTreeSet<&str> = BTreeSet::new();
+    for (from, to, edge) in graph.edge_endpoints() {
+        if edge.edge_type != EdgeType::MigratedTo {
+            continue;
+        }
+        if let (Some(from), Some(to)) = (graph.node_weight(from), graph.node_weight(to)) {
+            targets.entry(&from.id).or_default().push(to.id.clone());
+            migrated_into.insert(&to.id);
+        }
+    }
+
+    let mut coverage = MigrationCoverage::default();
+    for node in graph.nodes() {
+        if !is_project_symbol(node) || migrated_into.contains(node.id.as_str()) {
+            continue;
+        }
+        match targets.get(node.id.as_str()) {
+            Some(ids) => {
+                let mut ids = ids.clone();
+                ids.sort();
+                ids.dedup();
+                coverage.linked.insert(node.id.clone(), ids);
+            }
+            None => coverage.unlinked.push(node.id.clone()),
+        }
+    }
+    coverage.unlinked.sort();
+    coverage
+}
+
+/// `Superseded` nodes that are still used, mapped to the IDs of their direct
+/// dependents that are not `Superseded` themselves
+///
+/// A superseded symbol should have no live callers, importers or subclasses
+/// left; each entry here is a migration that is not finished.
+pub fn superseded_with_live_dependents(graph: &Graph) -> BTreeMap<String, Vec<String>> {
+    let mut incomplete: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (from, to, edge) in graph.edge_endpoints() {
+        if !edge.edge_type.is_dependency() || from == to {
+            continue;
+        }
+        let (Some(dependent), Some(dependency)) = (graph.node_weight(from), graph.node_weight(to))
+        else {
+            continue;
+        };
+        if dependency.status == MigrationStatus::Superseded
+            && dependent.status != MigrationStatus::Superseded
+        {
+            incomplete
+                .entry(dependency.id.clone())
+                .or_default()
+                .push(dependent.id.clone());
+        }
+    }
+    for dependents in incomplete.values_mut() {
+        dependents.sort();
+        dependents.dedup();
+    }
+    incomplete
+}
+
+/// Pending symbols whose symbol dependencies are all `Migrated` or
+/// `Superseded`, lowest [`Node::effort`] score first
+///
+/// Migrating bottom-up, these are ready now: nothing they call, import or
+/// inherit from is still waiting. Symbols with no dependencies are always
+/// on the frontier. Dependencies on external code, files and modules are
+/// ignored.
+///
+/// Symbols without an effort estimate (see
+/// [`enrich::estimate_effort()`](crate::enrich::estimate_effort())) come
+/// after those with one; ties are sorted by ID.
+pub fn migration_frontier(graph: &Graph) -> Vec<String> {
+    let mut frontier: Vec<(Option<f64>, &str)> = graph
+        .node_indices()
+        .filter_map(|idx| {
+            let node = graph.node_weight(idx)?;
+            if !is_project_symbol(node) || !node.status.is_pending() {
+                return None;
+            }
+            let ready = graph
+                .edges_of(idx)
+                .filter(|(_, from, to, edge)| {
+                    *from == idx && *to != idx && edge.edge_type.is_dependency()
+                })
+                .filter_map(|(_, _, to, _)| graph.node_weight(to))
+                .filter(|dependency| is_project_symbol(dependency))
+                .all(|dependency| {
+                    matches!(
+                        dependency.status,
+                        MigrationStatus::Migrated | MigrationStatus::Superseded
+                    )
+                });
+            ready.then(|| (node.effort.map(|effort| effort.score), node.id.as_str()))
+        })
+        .collect();
+    frontier.sort_by(|(a_score, a), (b_score, b)| {
+        let cheaper = match (a_score, b_score) {
+            (Some(a), Some(b)) => a.total_cmp(b),
+            _ => a_score.is_none().cmp(&b_score.is_none()),
+        };
+        cheaper.then_with(|| a.cmp(b))
+    });
+    frontier.into_iter().map(|(_, id)| id.to_string()).collect()
+}
+
+/// Classes, functions, methods and variables of the project itself
+pub fn is_project_symbol(node: &Node) -> bool {
+    !node.external
+        && !matches!(
+            node.node_type,
+            NodeType::File | NodeType::Module | NodeType::MigrationUnit | NodeType::Route
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Node;
+    use crate::migration;
+
+    #[test]
+    fn test_migration_coverage() {
+        let mut graph = Graph::new();
+        for id in ["old.py::a", "old.py::b", "new.py::a", "new.py::b"] {
+            graph.add_node(Node::new(id, id, NodeType::Function, "python", "x.py"));
+        }
+        graph.add_node(Node::new(
+            "old.py",
+            "old",
+            NodeType::Module,
+            "python",
+            "old.py",
+        ));
+        migration::link(&mut graph, "old.py::a", "new.py::a").unwrap();
+        migration::link(&mut graph, "old.py::a", "new.py::b").unwrap();
+
+        let coverage = migration_coverage(&graph);
+
+        assert_eq!(
+            coverage.linked["old.py::a"],
+            vec!["new.py::a".to_string(), "new.py::b".to_string()]
+        );
+        // Targets and modules are not legacy symbols
+        assert_eq!(coverage.unlinked, vec!["old.py::b".to_string()]);
+        assert_eq!(coverage.ratio(), 0.5);
+        assert_eq!(MigrationCoverage::default().ratio(), 1.0);
+    }
+
+    #[test]
+    fn test_superseded_with_live_dependents() {
+        use crate::graph::Edge;
+        let mut graph = Graph::new();
+        let ids = ["old.py::f", "old.py::g", "app.py::main", "old.py::h"];
+        let [f, g, main, h] =
+            ids.map(|id| graph.add_node(Node::new(id, id, NodeType::Function, "python", "x.py")));
+        for (from, to) in [(main, f), (g, f), (main, g), (h, main)] {
+            graph.add_edge(from, to, Edge::new(EdgeType::Calls));
+        }
+        for id in ["old.py::f", "old.py::g"] {
+            migration::transition(&mut graph, id, MigrationStatus::Superseded).unwrap();
+        }
+
+        let incomplete = superseded_with_live_dependents(&graph);
+
+        // old.py::g is superseded too, so it does not keep old.py::f alive
+        assert_eq!(incomplete["old.py::f"], vec!["app.py::main".to_string()]);
+        assert_eq!(incomplete["old.py::g"], vec!["app.py::main".to_string()]);
+        assert_eq!(incomplete.len(), 2);
+
+        // main's dependencies are both superseded; h still waits on main
+        assert_eq!(migration_frontier(&graph), vec!["app.py::main".to_string()]);
+        migration::transition(&mut graph, "app.py::main", MigrationStatus::Migrated).unwrap();
+        assert_eq!(migration_frontier(&graph), vec!["old.py::h".to_string()]);
+    }
+
+    #[test]
+    fn test_migration_frontier_cheapest_first() {
+        use crate::graph::Effort;
+        let mut graph = Graph::new();
+        for id in ["a.py::big", "a.py::small", "a.py::new", "a.py::tiny"] {
+            graph.add_node(Node::new(id, id, NodeType::Function, "python", "a.py"));
+        }
+        for (id, score) in [
+            ("a.py::big", 9.0),
+            ("a.py::small", 2.0),
+            ("a.py::tiny", 2.0),
+        ] {
+            graph.update_node_by_id(id, |node| {
+                node.effort = Some(Effort {
+                    lines: 1,
+                    branches: 0,
+                    fan_in: 0,
+                    fan_out: 0,
+                    score,
+                })
+            });
+        }
+
+        // Unestimated symbols last, ties by ID
+        assert_eq!(
+            migration_frontier(&graph),
+            ["a.py::small", "a.py::tiny", "a.py::big", "a.py::new"]
+        );
+    }
+}
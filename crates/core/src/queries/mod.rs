@@ -0,0 +1,138 @@
+//! Graph query functions
+//!
+//! This module provides utilities for querying the dependency graph,
+//! such as finding upstream/downstream dependencies, leaf nodes, etc.
+//!
+//! - [`metrics()`] - Fan-in/fan-out, density, longest chains, per-file counts
+//! - [`centrality()`] - PageRank and betweenness for migration-risk ranking
+//! - [`paths()`] - Shortest or all dependency paths between two symbols
+//! - [`closure()`] - Everything a symbol transitively depends on, or that depends on it
+//! - [`condense()`] - The dependency DAG with cycles collapsed, in migration order
+//! - [`unreachable_from()`] - Dead code: symbols no entry point can reach
+//! - [`subgraph()`] - Everything within N dependency hops of a symbol
+//! - [`migration_coverage()`] - Which legacy symbols have `MigratedTo` targets
+//! - [`superseded_with_live_dependents()`] - Superseded symbols that code still depends on
+//! - [`migration_frontier()`] - Pending symbols whose dependencies are all migrated
+//! - [`search()`] - Ranked substring, glob and fuzzy lookup of symbols by name or ID
+//! - [`rollup()`] - Symbols collapsed into directories or modules, with edge counts between them
+//! - [`suggest_units()`] - Cohesive clusters of symbols to propose as migration units
+
+pub mod centrality;
+pub mod closure;
+pub mod condense;
+pub mod metrics;
+pub mod migration;
+pub mod paths;
+pub mod rollup;
+pub mod search;
+pub mod subgraph;
+pub mod units;
+pub mod unreachable;
+
+pub use crate::graph::Direction;
+pub use centrality::{betweenness, centrality, pagerank, CentralityScores};
+pub use closure::closure;
+pub use condense::{condense, Component, Condensation};
+pub use metrics::{metrics, GraphMetrics, NodeMetrics};
+pub use migration::{
+    migration_coverage, migration_frontier, superseded_with_live_dependents, MigrationCoverage,
+};
+pub use paths::{paths, DependencyPath, PathOptions};
+pub use rollup::{rollup, Rollup, RollupEdge, RollupGroup, RollupLevel};
+pub use search::{search, MatchField, MatchKind, SearchMatch};
+pub use subgraph::subgraph;
+pub use units::{suggest_units, UnitSuggestion};
+pub use unreachable::unreachable_from;
+
+use crate::graph::EdgeType;
+use crate::view::GraphView;
+use petgraph::stable_graph::NodeIndex;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Dense adjacency view over a subset of a graph's edges
+///
+/// Nodes are numbered `0..n` in node-ID order so that algorithms iterating
+/// over them produce deterministic results regardless of insertion order.
+/// Built from any [`GraphView`]; `R` is its node handle.
+pub(crate) struct Adjacency<R = NodeIndex> {
+    /// Graph handle of each dense position
+    pub nodes: Vec<R>,
+    /// Dense position of each graph handle
+    pub position: HashMap<R, usize>,
+    /// Outgoing `(target, edge type)` pairs per dense position (sorted, may repeat)
+    pub outgoing: Vec<Vec<(usize, EdgeType)>>,
+}
+
+impl<R: Copy + Eq + Hash> Adjacency<R> {
+    /// Build from every edge where [`EdgeType::is_dependency`] holds
+    pub fn dependencies<G: GraphView<NodeRef = R>>(graph: &G) -> Self {
+        Self::filtered(graph, EdgeType::is_dependency)
+    }
+
+    /// Build from every edge whose type satisfies `include`
+    pub fn filtered<G: GraphView<NodeRef = R>>(
+        graph: &G,
+        include: impl Fn(&EdgeType) -> bool,
+    ) -> Self {
+        let mut nodes: Vec<R> = graph.node_refs().collect();
+        nodes.sort_by(|&a, &b| graph.id(a).cmp(graph.id(b)));
+        let position: HashMap<R, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, &node)| (node, i))
+            .collect();
+
+        let mut outgoing = vec![Vec::new(); nodes.len()];
+        for (from, to, edge_type) in graph.edge_refs() {
+            if include(&edge_type) {
+                outgoing[position[&from]].push((position[&to], edge_type));
+            }
+        }
+        for targets in &mut outgoing {
+            targets.sort_unstable();
+        }
+
+        Self {
+            nodes,
+            position,
+            outgoing,
+        }
+    }
+
+    /// Dense position of the node with the given ID
+    pub fn position_of<G: GraphView<NodeRef = R>>(&self, graph: &G, id: &str) -> Option<usize> {
+        graph
+            .find(id)
+            .and_then(|node| self.position.get(&node).copied())
+    }
+
+    /// ID of the node at a dense position
+    pub fn id<'g, G: GraphView<NodeRef = R>>(&self, graph: &'g G, position: usize) -> &'g str {
+        graph.id(self.nodes[position])
+    }
+
+    /// The same nodes with every edge reversed
+    pub fn reversed(&self) -> Self {
+        let mut incoming = vec![Vec::new(); self.len()];
+        for (from, targets) in self.outgoing.iter().enumerate() {
+            for &(to, edge_type) in targets {
+                incoming[to].push((from, edge_type));
+            }
+        }
+        for sources in &mut incoming {
+            sources.sort_unstable();
+        }
+
+        Self {
+            nodes: self.nodes.clone(),
+            position: self.position.clone(),
+            outgoing: incoming,
+        }
+    }
+
+    /// Number of nodes
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+}
@@ -0,0 +1,392 @@
+//! Dependency paths between two symbols
+//!
+//! Answers "how does `a` end up depending on `b`?" by returning the chain
+//! of edges connecting them: either one shortest path (breadth-first) or
+//! every simple path (depth-first, no repeated nodes).
+//!
+//! By default only dependency edges ([`EdgeType::is_dependency`]) are
+//! followed; [`PathOptions::edge_types`] narrows or widens that set.
+
+use super::Adjacency;
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::{EdgeType, Graph};
+use std::collections::VecDeque;
+
+/// Options for [`paths()`]
+///
+/// Built with a consuming builder:
+///
+/// ```
+/// use graph_migrator_core::graph::EdgeType;
+/// use graph_migrator_core::queries::PathOptions;
+///
+/// let options = PathOptions::new()
+///     .all_paths(true)
+///     .edge_types(&[EdgeType::Calls])
+///     .max_length(5);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PathOptions {
+    edge_types: Option<Vec<EdgeType>>,
+    max_length: Option<usize>,
+    all_paths: bool,
+    max_paths: Option<usize>,
+}
+
+impl PathOptions {
+    /// Shortest path over dependency edges, with no length limit
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only follow edges of these types (instead of all dependency edges)
+    pub fn edge_types(mut self, edge_types: &[EdgeType]) -> Self {
+        self.edge_types = Some(edge_types.to_vec());
+        self
+    }
+
+    /// Ignore paths with more than `max_length` edges
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    /// Return every simple path instead of a single shortest one
+    pub fn all_paths(mut self, all_paths: bool) -> Self {
+        self.all_paths = all_paths;
+        self
+    }
+
+    /// Stop enumerating after `max_paths` paths (only with [`all_paths`](Self::all_paths))
+    pub fn max_paths(mut self, max_paths: usize) -> Self {
+        self.max_paths = Some(max_paths);
+        self
+    }
+
+    fn follows(&self, edge_type: &EdgeType) -> bool {
+        match &self.edge_types {
+            Some(types) => types.contains(edge_type),
+            None => edge_type.is_dependency(),
+        }
+    }
+}
+
+/// One path through the graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyPath {
+    /// Node IDs from source to target, inclusive
+    pub nodes: Vec<String>,
+    /// Edge types between consecutive nodes (`nodes.len() - 1` entries)
+    pub edges: Vec<EdgeType>,
+}
+
+impl DependencyPath {
+    /// Number of edges on the path
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Whether the path has no edges (source and target are the same node)
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+}
+
+/// Find dependency paths from `from_id` to `to_id`
+///
+/// Returns an empty vector when the target is unreachable within the
+/// configured limits. With [`PathOptions::all_paths`], paths are sorted by
+/// length and then by node IDs; otherwise at most one shortest path is
+/// returned, choosing the smallest IDs among equally short candidates.
+///
+/// # Errors
+/// Returns [`GraphMigratorError::NodeNotFound`] if either ID is not in the graph.
+///
+/// # Example
+/// ```no_run
+/// use graph_migrator_core::{parser, queries};
+///
+/// let multi = parser::parse_directory(std::path::Path::new("my_project")).unwrap();
+/// let found = queries::paths(
+///     &multi.graph,
+///     "app.py::main",
+///     "db.py::connect",
+///     &queries::PathOptions::new(),
+/// )
+/// .unwrap();
+/// for path in found {
+///     println!("{}", path.nodes.join(" -> "));
+/// }
+/// ```
+pub fn paths(
+    graph: &Graph,
+    from_id: &str,
+    to_id: &str,
+    options: &PathOptions,
+) -> Result<Vec<DependencyPath>> {
+    let mut adjacency = Adjacency::filtered(graph, |t| options.follows(t));
+    for targets in &mut adjacency.outgoing {
+        // Parallel edges of the same type would only duplicate paths
+        targets.dedup();
+    }
+
+    let from = adjacency
+        .position_of(graph, from_id)
+        .ok_or_else(|| GraphMigratorError::NodeNotFound(from_id.to_string()))?;
+    let to = adjacency
+        .position_of(graph, to_id)
+        .ok_or_else(|| GraphMigratorError::NodeNotFound(to_id.to_string()))?;
+
+    let found = if options.all_paths {
+        all_simple_paths(&adjacency, from, to, options)
+    } else {
+        shortest_path(&adjacency, from, to, options.max_length)
+            .into_iter()
+            .collect()
+    };
+
+    Ok(found
+        .into_iter()
+        .map(|(nodes, edges)| DependencyPath {
+            nodes: nodes
+                .into_iter()
+                .map(|p| adjacency.id(graph, p).to_string())
+                .collect(),
+            edges,
+        })
+        .collect())
+}
+
+/// Dense positions and edge types along a path
+type RawPath = (Vec<usize>, Vec<EdgeType>);
+
+/// Breadth-first search; neighbours are visited in ID order for determinism
+fn shortest_path(
+    adjacency: &Adjacency,
+    from: usize,
+    to: usize,
+    max_length: Option<usize>,
+) -> Option<RawPath> {
+    let n = adjacency.len();
+    let mut parent: Vec<Option<(usize, EdgeType)>> = vec![None; n];
+    let mut distance = vec![usize::MAX; n];
+    distance[from] = 0;
+
+    let mut queue = VecDeque::from([from]);
+    while let Some(v) = queue.pop_front() {
+        if v == to {
+            break;
+        }
+        if max_length.is_some_and(|max| distance[v] >= max) {
+            continue;
+        }
+        for &(w, edge_type) in &adjacency.outgoing[v] {
+            if distance[w] == usize::MAX {
+                distance[w] = distance[v] + 1;
+                parent[w] = Some((v, edge_type));
+                queue.push_back(w);
+            }
+        }
+    }
+
+    if distance[to] == usize::MAX {
+        return None;
+    }
+
+    let mut nodes = vec![to];
+    let mut edges = Vec::new();
+    let mut current = to;
+    while let Some((prev, edge_type)) = parent[current] {
+        nodes.push(prev);
+        edges.push(edge_type);
+        current = prev;
+    }
+    nodes.reverse();
+    edges.reverse();
+    Some((nodes, edges))
+}
+
+/// Depth-first enumeration of simple paths
+fn all_simple_paths(
+    adjacency: &Adjacency,
+    from: usize,
+    to: usize,
+    options: &PathOptions,
+) -> Vec<RawPath> {
+    if from == to {
+        return vec![(vec![from], Vec::new())];
+    }
+
+    let limit = options.max_paths.unwrap_or(usize::MAX);
+    let mut found = Vec::new();
+    let mut on_path = vec![false; adjacency.len()];
+    let mut nodes = vec![from];
+    let mut edges = Vec::new();
+    on_path[from] = true;
+
+    // Each frame is (node, index of the next outgoing edge to try)
+    let mut stack = vec![(from, 0)];
+    while let Some((v, next)) = stack.last_mut() {
+        let v = *v;
+        let within_length = options.max_length.is_none_or(|max| edges.len() < max);
+        let step = adjacency.outgoing[v].get(*next).copied();
+        *next += 1;
+
+        match step {
+            Some((w, edge_type)) if within_length && !on_path[w] => {
+                if w == to {
+                    let mut path_nodes = nodes.clone();
+                    path_nodes.push(w);
+                    let mut path_edges = edges.clone();
+                    path_edges.push(edge_type);
+                    found.push((path_nodes, path_edges));
+                    if found.len() >= limit {
+                        break;
+                    }
+                } else {
+                    on_path[w] = true;
+                    nodes.push(w);
+                    edges.push(edge_type);
+                    stack.push((w, 0));
+                }
+            }
+            Some(_) if within_length => {}
+            _ => {
+                // Exhausted (or too long): backtrack
+                stack.pop();
+                on_path[v] = false;
+                nodes.pop();
+                edges.pop();
+            }
+        }
+    }
+
+    found.sort_by(|a, b| a.1.len().cmp(&b.1.len()).then_with(|| a.0.cmp(&b.0)));
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{add, link};
+
+    /// a -> b -> d, a -> c -> d, b -> c, plus a structural a -> d
+    fn diamond() -> Graph {
+        let mut graph = Graph::new();
+        let a = add(&mut graph, "a");
+        let b = add(&mut graph, "b");
+        let c = add(&mut graph, "c");
+        let d = add(&mut graph, "d");
+        link(&mut graph, a, b, EdgeType::Calls);
+        link(&mut graph, a, c, EdgeType::Imports);
+        link(&mut graph, b, d, EdgeType::Calls);
+        link(&mut graph, c, d, EdgeType::Calls);
+        link(&mut graph, b, c, EdgeType::Calls);
+        link(&mut graph, a, d, EdgeType::Contains);
+        graph
+    }
+
+    #[test]
+    fn test_shortest_path_skips_structural_edges() {
+        let graph = diamond();
+
+        let found = paths(&graph, "m.py::a", "m.py::d", &PathOptions::new()).unwrap();
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].nodes, vec!["m.py::a", "m.py::b", "m.py::d"]);
+        assert_eq!(found[0].edges, vec![EdgeType::Calls, EdgeType::Calls]);
+    }
+
+    #[test]
+    fn test_all_paths_sorted_by_length() {
+        let graph = diamond();
+
+        let found = paths(
+            &graph,
+            "m.py::a",
+            "m.py::d",
+            &PathOptions::new().all_paths(true),
+        )
+        .unwrap();
+
+        let nodes: Vec<Vec<String>> = found.into_iter().map(|p| p.nodes).collect();
+        assert_eq!(
+            nodes,
+            vec![
+                vec!["m.py::a", "m.py::b", "m.py::d"],
+                vec!["m.py::a", "m.py::c", "m.py::d"],
+                vec!["m.py::a", "m.py::b", "m.py::c", "m.py::d"],
+            ]
+        );
+
+        let short = paths(
+            &graph,
+            "m.py::a",
+            "m.py::d",
+            &PathOptions::new().all_paths(true).max_length(2),
+        )
+        .unwrap();
+        assert_eq!(short.len(), 2);
+    }
+
+    #[test]
+    fn test_edge_type_filter_and_max_length() {
+        let graph = diamond();
+
+        // Without Imports, a can only reach c through b
+        let found = paths(
+            &graph,
+            "m.py::a",
+            "m.py::c",
+            &PathOptions::new().edge_types(&[EdgeType::Calls]),
+        )
+        .unwrap();
+        assert_eq!(found[0].nodes, vec!["m.py::a", "m.py::b", "m.py::c"]);
+
+        let found = paths(
+            &graph,
+            "m.py::a",
+            "m.py::d",
+            &PathOptions::new().edge_types(&[EdgeType::Contains]),
+        )
+        .unwrap();
+        assert_eq!(found[0].edges, vec![EdgeType::Contains]);
+
+        let found = paths(
+            &graph,
+            "m.py::a",
+            "m.py::c",
+            &PathOptions::new()
+                .edge_types(&[EdgeType::Calls])
+                .max_length(1),
+        )
+        .unwrap();
+        assert!(found.is_empty());
+    }
+
+    #[test]
+    fn test_cycles_and_missing_nodes() {
+        let mut graph = Graph::new();
+        let a = add(&mut graph, "a");
+        let b = add(&mut graph, "b");
+        link(&mut graph, a, b, EdgeType::Calls);
+        link(&mut graph, b, a, EdgeType::Calls);
+
+        let found = paths(
+            &graph,
+            "m.py::a",
+            "m.py::b",
+            &PathOptions::new().all_paths(true),
+        )
+        .unwrap();
+        assert_eq!(found.len(), 1);
+
+        let found = paths(&graph, "m.py::a", "m.py::a", &PathOptions::new()).unwrap();
+        assert!(found[0].is_empty());
+
+        assert!(matches!(
+            paths(&graph, "m.py::a", "m.py::missing", &PathOptions::new()),
+            Err(GraphMigratorError::NodeNotFound(id)) if id == "m.py::missing"
+        ));
+    }
+}
@@ -0,0 +1,271 @@
+//! Architecture-level view: symbols collapsed into their packages
+//!
+//! A large project has hundreds of thousands of symbols but only hundreds
+//! of directories. [`rollup()`] groups every node by the directory or module
+//! its ID names, and counts the dependency edges between groups, so the
+//! result reads as a package diagram: "`billing` depends on `legacy/db`
+//! through 212 calls and 3 imports".
+//!
+//! Groups are named after the file part of node IDs (everything before the
+//! first `::`), so they follow `relative_ids` and namespaces the same way
+//! IDs do. External packages each stay a group of their own.
+
+use crate::graph::{strip_namespace, EdgeType, Graph, MigrationStatus, Node};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// What [`rollup()`] groups symbols by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RollupLevel {
+    /// The directory containing the file (`billing/models`)
+    Directory,
+    /// The file, as a dotted module name (`billing.models.invoice`)
+    Module,
+}
+
+/// One package in a [`Rollup`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RollupGroup {
+    /// Directory path or dotted module name; the node ID for external packages
+    pub id: String,
+    /// Whether the group is an external package
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub external: bool,
+    /// Number of nodes collapsed into the group
+    pub node_count: usize,
+    /// Node count per migration status
+    pub statuses: BTreeMap<MigrationStatus, usize>,
+    /// Dependency edges between nodes of the group
+    pub internal_edges: usize,
+}
+
+/// Dependency edges from one group to another, aggregated
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RollupEdge {
+    /// Dependent group
+    pub from: String,
+    /// Group depended upon
+    pub to: String,
+    /// Number of symbol-level edges
+    pub weight: usize,
+    /// Number of symbol-level edges per type
+    pub edge_types: BTreeMap<EdgeType, usize>,
+}
+
+/// The package-level graph returned by [`rollup()`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rollup {
+    /// What nodes were grouped by
+    pub level: RollupLevel,
+    /// Groups, sorted by ID
+    pub groups: Vec<RollupGroup>,
+    /// Edges between distinct groups, sorted by `(from, to)`
+    pub edges: Vec<RollupEdge>,
+}
+
+/// Collapse `graph` into one node per directory or module
+///
+/// Only dependency edges ([`EdgeType::is_dependency`]) are aggregated;
+/// edges within a group are counted in [`RollupGroup::internal_edges`].
+///
+/// # Example
+/// ```no_run
+/// use graph_migrator_core::{parser, queries};
+/// use graph_migrator_core::queries::RollupLevel;
+///
+/// let multi = parser::parse_directory(std::path::Path::new("my_project")).unwrap();
+/// let packages = queries::rollup(&multi.graph, RollupLevel::Directory);
+/// for edge in &packages.edges {
+///     println!("{} -> {} ({})", edge.from, edge.to, edge.weight);
+/// }
+/// ```
+pub fn rollup(graph: &Graph, level: RollupLevel) -> Rollup {
+    let mut groups: BTreeMap<String, RollupGroup> = BTreeMap::new();
+    let mut group_of = HashMap::new();
+    for index in graph.node_indices() {
+        let Some(node) = graph.node_weight(index) else {
+            continue;
+        };
+        let id = group_id(node, level);
+        let group = groups.entry(id.clone()).or_insert_with(|| RollupGroup {
+            id: id.clone(),
+            external: node.external,
+            node_count: 0,
+            statuses: BTreeMap::new(),
+            internal_edges: 0,
+        });
+        group.node_count += 1;
+        *group.statuses.entry(node.status).or_default() += 1;
+        group_of.insert(index, id);
+    }
+
+    let mut edges: BTreeMap<(String, String), RollupEdge> = BTreeMap::new();
+    for (from, to, edge) in graph.edge_endpoints() {
+        if !edge.edge_type.is_dependency() {
+            continue;
+        }
+        let (Some(from), Some(to)) = (group_of.get(&from), group_of.get(&to)) else {
+            continue;
+        };
+        if from == to {
+            if let Some(group) = groups.get_mut(from) {
+                group.internal_edges += 1;
+            }
+            continue;
+        }
+        let rolled = edges
+            .entry((from.clone(), to.clone()))
+            .or_insert_with(|| RollupEdge {
+                from: from.clone(),
+                to: to.clone(),
+                weight: 0,
+                edge_types: BTreeMap::new(),
+            });
+        rolled.weight += 1;
+        *rolled.edge_types.entry(edge.edge_type).or_default() += 1;
+    }
+
+    Rollup {
+        level,
+        groups: groups.into_values().collect(),
+        edges: edges.into_values().collect(),
+    }
+}
+
+/// The group `node` falls in at `level`
+pub(super) fn group_id(node: &Node, level: RollupLevel) -> String {
+    if node.external {
+        return node.id.clone();
+    }
+    let rest = strip_namespace(&node.id, node.namespace.as_deref());
+    let prefix = &node.id[..node.id.len() - rest.len()];
+    let file = rest.split("::").next().unwrap_or_default();
+    let group = match level {
+        RollupLevel::Directory => match file.rsplit_once('/') {
+            Some((directory, _)) => directory.to_string(),
+            None => ".".to_string(),
+        },
+        RollupLevel::Module => {
+            let module = file.strip_suffix(".py").unwrap_or(file);
+            let module = module.strip_suffix("/__init__").unwrap_or(module);
+            module.replace('/', ".")
+        }
+    };
+    format!("{}{}", prefix, group)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::NodeType;
+    use crate::test_util::{add_node, link_ids};
+
+    #[test]
+    fn test_rollup_aggregates_edges_between_packages() {
+        let mut graph = Graph::new();
+        for id in [
+            "billing/invoice.py::build",
+            "billing/invoice.py::total",
+            "billing/__init__.py::setup",
+            "legacy/db.py::query",
+            "legacy/db.py::connect",
+            "main.py::main",
+        ] {
+            add_node(&mut graph, id, NodeType::Function);
+        }
+        let mut requests = Node::new(
+            "external::requests",
+            "requests",
+            NodeType::Module,
+            "python",
+            "",
+        );
+        requests.external = true;
+        graph.add_node(requests);
+        graph.update_node_by_id("legacy/db.py::connect", |n| {
+            n.status = MigrationStatus::Migrated
+        });
+
+        link_ids(
+            &mut graph,
+            "billing/invoice.py::build",
+            "billing/invoice.py::total",
+            EdgeType::Calls,
+        );
+        link_ids(
+            &mut graph,
+            "billing/invoice.py::build",
+            "legacy/db.py::query",
+            EdgeType::Calls,
+        );
+        link_ids(
+            &mut graph,
+            "billing/invoice.py::total",
+            "legacy/db.py::query",
+            EdgeType::Calls,
+        );
+        link_ids(
+            &mut graph,
+            "billing/__init__.py::setup",
+            "legacy/db.py::connect",
+            EdgeType::Imports,
+        );
+        link_ids(
+            &mut graph,
+            "legacy/db.py::query",
+            "external::requests",
+            EdgeType::Imports,
+        );
+        link_ids(
+            &mut graph,
+            "main.py::main",
+            "billing/invoice.py::build",
+            EdgeType::Calls,
+        );
+        // Not a dependency, so not aggregated
+        link_ids(
+            &mut graph,
+            "legacy/db.py::query",
+            "billing/invoice.py::build",
+            EdgeType::Tests,
+        );
+
+        let packages = rollup(&graph, RollupLevel::Directory);
+        let ids: Vec<&str> = packages.groups.iter().map(|g| g.id.as_str()).collect();
+        assert_eq!(ids, vec![".", "billing", "external::requests", "legacy"]);
+        let billing = &packages.groups[1];
+        assert_eq!((billing.node_count, billing.internal_edges), (3, 1));
+        assert!(packages.groups[2].external);
+        assert_eq!(packages.groups[3].statuses[&MigrationStatus::Migrated], 1);
+
+        let edges: Vec<(&str, &str, usize)> = packages
+            .edges
+            .iter()
+            .map(|e| (e.from.as_str(), e.to.as_str(), e.weight))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                (".", "billing", 1),
+                ("billing", "legacy", 3),
+                ("legacy", "external::requests", 1),
+            ]
+        );
+        assert_eq!(packages.edges[1].edge_types[&EdgeType::Calls], 2);
+        assert_eq!(packages.edges[1].edge_types[&EdgeType::Imports], 1);
+
+        let modules = rollup(&graph, RollupLevel::Module);
+        let ids: Vec<&str> = modules.groups.iter().map(|g| g.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "billing",
+                "billing.invoice",
+                "external::requests",
+                "legacy.db",
+                "main"
+            ]
+        );
+        assert_eq!(modules.edges.len(), 4);
+    }
+}
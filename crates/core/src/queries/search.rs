@@ -0,0 +1,251 @@
+//! Finding symbols without knowing their exact ID
+//!
+//! Node IDs such as `src/billing/invoice.py::InvoiceBuilder` are precise but
+//! tedious to type. [`search()`] takes what the user remembers (`invoice`,
+//! `Inv*Builder`, `invbld`) and ranks every node whose name or ID matches:
+//!
+//! 1. exact matches (case-insensitive), then
+//! 2. prefixes, then
+//! 3. substrings, then
+//! 4. glob matches, when the pattern contains `*`, `?` or `[`, then
+//! 5. fuzzy matches: the pattern's characters appear in order, preferring
+//!    runs of consecutive characters and word starts (`inv_bld` in
+//!    `invoice_builder`).
+//!
+//! Within each kind, matches on the name rank above matches on the ID only.
+
+use crate::graph::{Graph, NodeType};
+use globset::GlobBuilder;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+
+/// How a search result matched, best first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum MatchKind {
+    /// Equal to the pattern, ignoring case
+    Exact,
+    /// Starts with the pattern
+    Prefix,
+    /// Contains the pattern
+    Substring,
+    /// Matches the pattern as a glob
+    Glob,
+    /// Contains the pattern's characters in order
+    Fuzzy,
+}
+
+/// Which part of the node matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum MatchField {
+    /// The symbol name (`InvoiceBuilder`)
+    Name,
+    /// The full ID (`billing/invoice.py::InvoiceBuilder`)
+    Id,
+}
+
+/// One node found by [`search()`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchMatch {
+    /// ID of the matching node
+    pub id: String,
+    /// Its name
+    pub name: String,
+    /// Its type
+    pub node_type: NodeType,
+    /// How it matched
+    pub kind: MatchKind,
+    /// What it matched on
+    pub field: MatchField,
+    /// Fuzzy score (higher is better); 0 for other kinds
+    pub score: u32,
+}
+
+/// Nodes whose name or ID matches `pattern`, best match first
+///
+/// See the [module docs](self) for the ranking. Ties are broken by shorter
+/// ID, then by ID. An empty pattern matches nothing.
+///
+/// # Example
+/// ```no_run
+/// use graph_migrator_core::{parser, queries};
+///
+/// let multi = parser::parse_directory(std::path::Path::new("my_project")).unwrap();
+/// for found in queries::search(&multi.graph, "invbld").iter().take(5) {
+///     println!("{} ({:?})", found.id, found.kind);
+/// }
+/// ```
+pub fn search(graph: &Graph, pattern: &str) -> Vec<SearchMatch> {
+    let pattern = pattern.trim();
+    if pattern.is_empty() {
+        return Vec::new();
+    }
+    let needle = pattern.to_lowercase();
+    let glob = pattern
+        .contains(['*', '?', '['])
+        .then(|| {
+            GlobBuilder::new(pattern)
+                .case_insensitive(true)
+                .literal_separator(false)
+                .build()
+                .ok()
+        })
+        .flatten()
+        .map(|glob| glob.compile_matcher());
+
+    let mut matches: Vec<SearchMatch> = graph
+        .nodes()
+        .filter_map(|node| {
+            let (kind, field, score) = [(MatchField::Name, &node.name), (MatchField::Id, &node.id)]
+                .into_iter()
+                .filter_map(|(field, text)| {
+                    let (kind, score) = match_text(&needle, glob.as_ref(), text)?;
+                    Some((kind, field, score))
+                })
+                .min_by_key(|&(kind, field, score)| (kind, field, Reverse(score)))?;
+            Some(SearchMatch {
+                id: node.id.clone(),
+                name: node.name.clone(),
+                node_type: node.node_type.clone(),
+                kind,
+                field,
+                score,
+            })
+        })
+        .collect();
+    matches.sort_by(|a, b| {
+        (a.kind, a.field, Reverse(a.score), a.id.len(), &a.id).cmp(&(
+            b.kind,
+            b.field,
+            Reverse(b.score),
+            b.id.len(),
+            &b.id,
+        ))
+    });
+    matches
+}
+
+/// How `text` matches the lowercased `needle`, if it does
+fn match_text(
+    needle: &str,
+    glob: Option<&globset::GlobMatcher>,
+    text: &str,
+) -> Option<(MatchKind, u32)> {
+    let haystack = text.to_lowercase();
+    if haystack == needle {
+        Some((MatchKind::Exact, 0))
+    } else if haystack.starts_with(needle) {
+        Some((MatchKind::Prefix, 0))
+    } else if haystack.contains(needle) {
+        Some((MatchKind::Substring, 0))
+    } else if glob.is_some_and(|glob| glob.is_match(text)) {
+        Some((MatchKind::Glob, 0))
+    } else if glob.is_none() {
+        fuzzy_score(needle, text).map(|score| (MatchKind::Fuzzy, score))
+    } else {
+        None
+    }
+}
+
+/// Score of the lowercased `needle` as a subsequence of `text`
+///
+/// Each matched character scores 1, plus 2 if it directly follows the
+/// previous match and 3 if it starts a word (after `_`, `.`, `/`, `:` or
+/// `-`, or an uppercase letter after a lowercase one). Characters are
+/// matched greedily from the left.
+fn fuzzy_score(needle: &str, text: &str) -> Option<u32> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+    for wanted in needle.chars() {
+        let found = (position..chars.len())
+            .find(|&i| chars[i].to_lowercase().eq(std::iter::once(wanted)))?;
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == found) {
+            score += 2;
+        }
+        let word_start = found == 0
+            || matches!(chars[found - 1], '_' | '.' | '/' | ':' | '-')
+            || (chars[found].is_uppercase() && chars[found - 1].is_lowercase());
+        if word_start {
+            score += 3;
+        }
+        previous = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Node;
+
+    fn graph(ids: &[&str]) -> Graph {
+        let mut graph = Graph::new();
+        for id in ids {
+            let (file, name) = id.split_once("::").unwrap();
+            graph.add_node(Node::new(*id, name, NodeType::Function, "python", file));
+        }
+        graph
+    }
+
+    fn ids(matches: &[SearchMatch]) -> Vec<&str> {
+        matches.iter().map(|m| m.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_search_ranks_exact_prefix_substring_fuzzy() {
+        let graph = graph(&[
+            "billing/invoice.py::invoice_builder",
+            "billing/invoice.py::Invoice",
+            "legacy/reports.py::print_invoice",
+            "billing/tax.py::inventory_by_location",
+            "app.py::main",
+        ]);
+
+        let found = search(&graph, "invoice");
+        assert_eq!(
+            ids(&found),
+            vec![
+                "billing/invoice.py::Invoice",
+                "billing/invoice.py::invoice_builder",
+                "legacy/reports.py::print_invoice",
+            ]
+        );
+        assert_eq!(
+            found.iter().map(|m| m.kind).collect::<Vec<_>>(),
+            vec![MatchKind::Exact, MatchKind::Prefix, MatchKind::Substring]
+        );
+
+        // Word starts beat scattered letters
+        let found = search(&graph, "invbld");
+        assert_eq!(ids(&found)[0], "billing/invoice.py::invoice_builder");
+        assert!(found.iter().all(|m| m.kind == MatchKind::Fuzzy));
+
+        // IDs are searched too, below name matches
+        let found = search(&graph, "billing/tax");
+        assert_eq!(found[0].field, MatchField::Id);
+        assert_eq!(ids(&found)[0], "billing/tax.py::inventory_by_location");
+
+        assert!(search(&graph, "  ").is_empty());
+        assert!(search(&graph, "zzz").is_empty());
+    }
+
+    #[test]
+    fn test_search_globs() {
+        let graph = graph(&[
+            "billing/invoice.py::InvoiceBuilder",
+            "billing/invoice.py::InvoiceParser",
+            "legacy/invoice.py::build_invoice",
+        ]);
+
+        let found = search(&graph, "inv*builder");
+        assert_eq!(ids(&found), vec!["billing/invoice.py::InvoiceBuilder"]);
+        assert_eq!(found[0].kind, MatchKind::Glob);
+
+        let found = search(&graph, "legacy/*");
+        assert_eq!(ids(&found), vec!["legacy/invoice.py::build_invoice"]);
+        assert_eq!(found[0].field, MatchField::Id);
+    }
+}
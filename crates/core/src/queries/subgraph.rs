@@ -0,0 +1,141 @@
+//! Bounded neighborhoods around a symbol
+//!
+//! Rendering a whole project graph is rarely useful; dashboards and reviews
+//! want "this function and whatever is within two hops of it". The result
+//! is a standalone [`Graph`] so it can be serialized or queried further.
+
+use super::Adjacency;
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::{Direction, Graph};
+use std::collections::HashMap;
+
+/// Nodes within `depth` dependency edges of `node_id`, and every edge among them
+///
+/// With `direction` set, only dependencies ([`Direction::Outgoing`]) or
+/// dependents ([`Direction::Incoming`]) are followed; `None` follows both.
+/// A depth of 0 returns just the node itself. Edges of any type between
+/// included nodes are kept, so containment is preserved.
+///
+/// # Errors
+/// Returns [`GraphMigratorError::NodeNotFound`] if `node_id` is not in the graph.
+///
+/// # Example
+/// ```no_run
+/// use graph_migrator_core::{parser, queries, GraphData};
+///
+/// let multi = parser::parse_directory(std::path::Path::new("my_project")).unwrap();
+/// let nearby = queries::subgraph(&multi.graph, "db.py::connect", 2, None).unwrap();
+/// println!("{}", serde_json::to_string(&GraphData::from(&nearby)).unwrap());
+/// ```
+pub fn subgraph(
+    graph: &Graph,
+    node_id: &str,
+    depth: usize,
+    direction: Option<Direction>,
+) -> Result<Graph> {
+    let outgoing = Adjacency::dependencies(graph);
+    let incoming = outgoing.reversed();
+    let start = outgoing
+        .position_of(graph, node_id)
+        .ok_or_else(|| GraphMigratorError::NodeNotFound(node_id.to_string()))?;
+
+    let follow: Vec<&Adjacency> = match direction {
+        Some(Direction::Outgoing) => vec![&outgoing],
+        Some(Direction::Incoming) => vec![&incoming],
+        None => vec![&outgoing, &incoming],
+    };
+
+    // Breadth-first so each node is recorded at its shortest distance
+    let mut included = vec![false; outgoing.len()];
+    included[start] = true;
+    let mut frontier = vec![start];
+    for _ in 0..depth {
+        let mut next = Vec::new();
+        for &v in &frontier {
+            for adjacency in &follow {
+                for &(t, _) in &adjacency.outgoing[v] {
+                    if !included[t] {
+                        included[t] = true;
+                        next.push(t);
+                    }
+                }
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        frontier = next;
+    }
+
+    let mut result = Graph::new();
+    let mut mapped = HashMap::new();
+    for (position, &idx) in outgoing.nodes.iter().enumerate() {
+        if included[position] {
+            if let Some(node) = graph.node_weight(idx) {
+                mapped.insert(idx, result.add_node(node.clone()));
+            }
+        }
+    }
+    for (from, to, edge) in graph.edge_endpoints() {
+        if let (Some(&from), Some(&to)) = (mapped.get(&from), mapped.get(&to)) {
+            result.add_edge(from, to, edge.clone());
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::EdgeType;
+    use crate::test_util::{add, link};
+    use std::collections::BTreeSet;
+
+    fn ids(graph: &Graph) -> BTreeSet<&str> {
+        graph.nodes().map(|n| n.name.as_str()).collect()
+    }
+
+    /// main -> handler -> db -> pool, cli -> handler, file -Contains-> handler
+    fn sample() -> Graph {
+        let mut graph = Graph::new();
+        let main = add(&mut graph, "main");
+        let handler = add(&mut graph, "handler");
+        let db = add(&mut graph, "db");
+        let pool = add(&mut graph, "pool");
+        let cli = add(&mut graph, "cli");
+        let file = add(&mut graph, "file");
+        for (from, to, edge_type) in [
+            (main, handler, EdgeType::Calls),
+            (handler, db, EdgeType::Calls),
+            (db, pool, EdgeType::Calls),
+            (cli, handler, EdgeType::Imports),
+            (file, handler, EdgeType::Contains),
+        ] {
+            link(&mut graph, from, to, edge_type);
+        }
+        graph
+    }
+
+    #[test]
+    fn test_subgraph_depth_and_direction() {
+        let graph = sample();
+
+        let nearby = subgraph(&graph, "m.py::handler", 1, None).unwrap();
+        assert_eq!(
+            ids(&nearby),
+            BTreeSet::from(["cli", "db", "handler", "main"])
+        );
+        assert_eq!(nearby.edge_count(), 3);
+
+        let below = subgraph(&graph, "m.py::handler", 5, Some(Direction::Outgoing)).unwrap();
+        assert_eq!(ids(&below), BTreeSet::from(["db", "handler", "pool"]));
+
+        let alone = subgraph(&graph, "m.py::db", 0, None).unwrap();
+        assert_eq!(alone.node_count(), 1);
+
+        assert!(matches!(
+            subgraph(&graph, "m.py::missing", 1, None),
+            Err(GraphMigratorError::NodeNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,336 @@
+//! Suggesting migration units from the shape of the code
+//!
+//! Code that calls and imports itself a lot but the rest of the project
+//! little is a natural unit to migrate in one go. [`suggest_units()`] finds
+//! such clusters with Louvain community detection over the call and import
+//! graph: each symbol starts alone, moves to whichever neighbouring cluster
+//! most increases modularity, and clusters are then merged into single
+//! nodes and the process repeated until nothing moves.
+//!
+//! Suggestions are only proposals; accept one with
+//! [`migration::create_unit()`](crate::migration::create_unit()).
+
+use super::rollup::{group_id, RollupLevel};
+use crate::graph::{EdgeType, Graph, MigrationStatus, NodeType};
+use petgraph::stable_graph::NodeIndex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Passes over the nodes within one level before giving up on convergence
+const MAX_PASSES: usize = 100;
+
+/// A proposed migration unit returned by [`suggest_units()`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnitSuggestion {
+    /// Suggested unit name: the module or directory all members share, or
+    /// else the name of the most connected member
+    pub name: String,
+    /// Member node IDs, sorted
+    pub members: Vec<String>,
+    /// Call and import edges between members
+    pub internal_edges: usize,
+    /// Call and import edges between a member and a non-member
+    pub boundary_edges: usize,
+    /// `internal_edges / (internal_edges + boundary_edges)`
+    pub cohesion: f64,
+}
+
+/// Clusters of symbols that would make cohesive migration units, best first
+///
+/// Only `Calls` and `Imports` edges are considered, in either direction.
+/// External nodes, migration units, `Superseded` tombstones and symbols
+/// already assigned to a unit are left out. Clusters of one symbol are not
+/// suggested. Suggestions are ranked by cohesion, then by size (larger
+/// first), then by name.
+///
+/// # Example
+/// ```no_run
+/// use graph_migrator_core::{parser, queries};
+///
+/// let multi = parser::parse_directory(std::path::Path::new("my_project")).unwrap();
+/// for unit in queries::suggest_units(&multi.graph).iter().take(5) {
+///     println!("{} ({} symbols, cohesion {:.2})", unit.name, unit.members.len(), unit.cohesion);
+/// }
+/// ```
+pub fn suggest_units(graph: &Graph) -> Vec<UnitSuggestion> {
+    let assigned: HashSet<NodeIndex> = graph
+        .edge_endpoints()
+        .filter(|(_, _, edge)| edge.edge_type == EdgeType::PartOfMigration)
+        .map(|(member, _, _)| member)
+        .collect();
+    let mut nodes: Vec<NodeIndex> = graph
+        .node_indices()
+        .filter(|index| {
+            graph.node_weight(*index).is_some_and(|node| {
+                !node.external
+                    && node.node_type != NodeType::MigrationUnit
+                    && node.status != MigrationStatus::Superseded
+                    && !assigned.contains(index)
+            })
+        })
+        .collect();
+    nodes.sort_by(|&a, &b| {
+        let id = |i| graph.node_weight(i).map(|n| n.id.as_str());
+        id(a).cmp(&id(b))
+    });
+    let position: HashMap<NodeIndex, usize> =
+        nodes.iter().enumerate().map(|(i, &idx)| (idx, i)).collect();
+
+    // Undirected, weighted by the number of edges either way
+    let mut weights: Vec<BTreeMap<usize, f64>> = vec![BTreeMap::new(); nodes.len()];
+    for (from, to, edge) in graph.edge_endpoints() {
+        if !matches!(edge.edge_type, EdgeType::Calls | EdgeType::Imports) {
+            continue;
+        }
+        let (Some(&a), Some(&b)) = (position.get(&from), position.get(&to)) else {
+            continue;
+        };
+        if a != b {
+            *weights[a].entry(b).or_default() += 1.0;
+            *weights[b].entry(a).or_default() += 1.0;
+        }
+    }
+    let adjacency: Vec<Vec<(usize, f64)>> = weights
+        .into_iter()
+        .map(|neighbors| neighbors.into_iter().collect())
+        .collect();
+
+    let community = louvain(adjacency.clone());
+    let mut clusters: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (node, &c) in community.iter().enumerate() {
+        clusters.entry(c).or_default().push(node);
+    }
+
+    let mut suggestions: Vec<UnitSuggestion> = clusters
+        .into_values()
+        .filter(|members| members.len() > 1)
+        .map(|members| {
+            let mut internal = 0.0;
+            let mut boundary = 0.0;
+            let mut anchor = (0.0, members[0]);
+            for &member in &members {
+                let mut degree = 0.0;
+                for &(neighbor, weight) in &adjacency[member] {
+                    if community[neighbor] == community[member] {
+                        internal += weight;
+                        degree += weight;
+                    } else {
+                        boundary += weight;
+                    }
+                }
+                if degree > anchor.0 {
+                    anchor = (degree, member);
+                }
+            }
+            // Internal edges were seen from both ends
+            let internal = (internal / 2.0) as usize;
+            let boundary = boundary as usize;
+            UnitSuggestion {
+                name: unit_name(graph, &nodes, &members, anchor.1),
+                members: members
+                    .iter()
+                    .filter_map(|&m| graph.node_weight(nodes[m]).map(|n| n.id.clone()))
+                    .collect(),
+                internal_edges: internal,
+                boundary_edges: boundary,
+                cohesion: internal as f64 / (internal + boundary) as f64,
+            }
+        })
+        .collect();
+    suggestions.sort_by(|a, b| {
+        b.cohesion
+            .total_cmp(&a.cohesion)
+            .then_with(|| b.members.len().cmp(&a.members.len()))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    suggestions
+}
+
+/// The module or directory every member shares, else the anchor's name
+fn unit_name(graph: &Graph, nodes: &[NodeIndex], members: &[usize], anchor: usize) -> String {
+    for level in [RollupLevel::Module, RollupLevel::Directory] {
+        let groups: HashSet<String> = members
+            .iter()
+            .filter_map(|&m| graph.node_weight(nodes[m]))
+            .map(|node| group_id(node, level))
+            .collect();
+        if groups.len() == 1 {
+            return groups.into_iter().next().unwrap_or_default();
+        }
+    }
+    graph
+        .node_weight(nodes[anchor])
+        .map(|node| node.name.clone())
+        .unwrap_or_default()
+}
+
+/// Community of each node, by multi-level Louvain modularity optimization
+///
+/// `adjacency` lists each undirected edge from both ends. Communities are
+/// numbered densely from 0.
+fn louvain(mut adjacency: Vec<Vec<(usize, f64)>>) -> Vec<usize> {
+    let mut membership: Vec<usize> = (0..adjacency.len()).collect();
+    let mut self_loops = vec![0.0; adjacency.len()];
+    loop {
+        let community = one_level(&adjacency, &self_loops);
+        let mut renumbered: HashMap<usize, usize> = HashMap::new();
+        let dense: Vec<usize> = community
+            .iter()
+            .map(|c| {
+                let next = renumbered.len();
+                *renumbered.entry(*c).or_insert(next)
+            })
+            .collect();
+        if renumbered.len() == adjacency.len() {
+            return membership;
+        }
+        for m in &mut membership {
+            *m = dense[*m];
+        }
+
+        // Collapse each community into one node; its internal edges become a self-loop
+        let mut merged: Vec<BTreeMap<usize, f64>> = vec![BTreeMap::new(); renumbered.len()];
+        let mut merged_loops = vec![0.0; renumbered.len()];
+        for (node, neighbors) in adjacency.iter().enumerate() {
+            let c = dense[node];
+            merged_loops[c] += self_loops[node];
+            for &(neighbor, weight) in neighbors {
+                if dense[neighbor] == c {
+                    merged_loops[c] += weight / 2.0;
+                } else {
+                    *merged[c].entry(dense[neighbor]).or_default() += weight;
+                }
+            }
+        }
+        adjacency = merged
+            .into_iter()
+            .map(|neighbors| neighbors.into_iter().collect())
+            .collect();
+        self_loops = merged_loops;
+    }
+}
+
+/// Local moving: each node joins the neighbouring community with the best
+/// modularity gain, in node order, until a pass moves nothing
+fn one_level(adjacency: &[Vec<(usize, f64)>], self_loops: &[f64]) -> Vec<usize> {
+    let degree: Vec<f64> = adjacency
+        .iter()
+        .zip(self_loops)
+        .map(|(neighbors, loops)| neighbors.iter().map(|&(_, w)| w).sum::<f64>() + 2.0 * loops)
+        .collect();
+    let total_weight: f64 = degree.iter().sum();
+    let mut community: Vec<usize> = (0..adjacency.len()).collect();
+    if total_weight == 0.0 {
+        return community;
+    }
+    let mut community_degree = degree.clone();
+
+    for _ in 0..MAX_PASSES {
+        let mut moved = false;
+        for node in 0..adjacency.len() {
+            let own = community[node];
+            community_degree[own] -= degree[node];
+            let mut links: BTreeMap<usize, f64> = BTreeMap::from([(own, 0.0)]);
+            for &(neighbor, weight) in &adjacency[node] {
+                *links.entry(community[neighbor]).or_default() += weight;
+            }
+            let gain = |c: usize| links[&c] - community_degree[c] * degree[node] / total_weight;
+
+            // Stay unless another community is strictly better
+            let mut best = (gain(own), own);
+            for &c in links.keys() {
+                let candidate = gain(c);
+                if candidate > best.0 + 1e-12 {
+                    best = (candidate, c);
+                }
+            }
+            community_degree[best.1] += degree[node];
+            if best.1 != own {
+                community[node] = best.1;
+                moved = true;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+    community
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Edge, Node};
+
+    #[test]
+    fn test_suggest_units_finds_dense_clusters() {
+        let mut graph = Graph::new();
+        let ids = [
+            "billing/invoice.py::build",
+            "billing/invoice.py::total",
+            "billing/tax.py::rate",
+            "legacy/db.py::query",
+            "legacy/db.py::connect",
+            "legacy/db.py::close",
+            "main.py::main",
+            "util.py::unused",
+        ];
+        for id in ids {
+            let (file, name) = id.split_once("::").unwrap();
+            graph.add_node(Node::new(id, name, NodeType::Function, "python", file));
+        }
+        let mut add = |from: usize, to: usize, edge_type: EdgeType| {
+            let from = graph.get_by_id(ids[from]).unwrap();
+            let to = graph.get_by_id(ids[to]).unwrap();
+            graph.add_edge(from, to, Edge::new(edge_type));
+        };
+        // Two triangles joined by a single call
+        add(0, 1, EdgeType::Calls);
+        add(1, 2, EdgeType::Calls);
+        add(0, 2, EdgeType::Imports);
+        add(3, 4, EdgeType::Calls);
+        add(4, 5, EdgeType::Calls);
+        add(3, 5, EdgeType::Calls);
+        add(1, 3, EdgeType::Calls);
+        add(6, 0, EdgeType::Calls);
+        // Ignored edge type
+        add(7, 5, EdgeType::Tests);
+
+        let units = suggest_units(&graph);
+        let members: Vec<Vec<&str>> = units
+            .iter()
+            .map(|u| u.members.iter().map(String::as_str).collect())
+            .collect();
+        assert_eq!(
+            members,
+            vec![
+                vec![
+                    "billing/invoice.py::build",
+                    "billing/invoice.py::total",
+                    "billing/tax.py::rate",
+                    "main.py::main",
+                ],
+                vec![
+                    "legacy/db.py::close",
+                    "legacy/db.py::connect",
+                    "legacy/db.py::query"
+                ],
+            ]
+        );
+        assert_eq!((units[0].internal_edges, units[0].boundary_edges), (4, 1));
+        assert_eq!(units[0].cohesion, 0.8);
+        // No shared module or directory: named after the most connected member
+        assert_eq!(units[0].name, "build");
+        assert_eq!(units[1].name, "legacy.db");
+        assert_eq!(units[1].cohesion, 0.75);
+
+        // Accepted members are not suggested again
+        crate::migration::create_unit(&mut graph, "unit::db", "db", &units[1].members).unwrap();
+        let units = suggest_units(&graph);
+        assert!(!units.is_empty());
+        assert!(units
+            .iter()
+            .flat_map(|u| &u.members)
+            .all(|id| !id.starts_with("legacy/")));
+    }
+}
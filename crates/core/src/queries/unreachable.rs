@@ -0,0 +1,111 @@
+//! Dead-code detection from known entry points
+//!
+//! Code that no entry point (a `main`, a CLI script, a test) can reach is a
+//! candidate for deletion rather than migration. Reachability follows
+//! dependency edges and `Contains` edges, so reaching a class keeps its
+//! methods alive; this errs on the side of reporting less, since dynamic
+//! dispatch is invisible to the parser.
+
+use super::Adjacency;
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::{EdgeType, Graph, NodeType};
+
+/// IDs of functions, methods, and classes not reachable from any entry point
+///
+/// Entry points themselves are never reported. The result is sorted by ID.
+///
+/// # Errors
+/// Returns [`GraphMigratorError::NodeNotFound`] if an entry point ID is not in the graph.
+///
+/// # Example
+/// ```no_run
+/// use graph_migrator_core::{parser, queries};
+///
+/// let multi = parser::parse_directory(std::path::Path::new("my_project")).unwrap();
+/// for id in queries::unreachable_from(&multi.graph, &["app.py::main"]).unwrap() {
+///     println!("unused: {}", id);
+/// }
+/// ```
+pub fn unreachable_from(graph: &Graph, entry_points: &[&str]) -> Result<Vec<String>> {
+    let adjacency = Adjacency::filtered(graph, |t| t.is_dependency() || *t == EdgeType::Contains);
+
+    let mut visited = vec![false; adjacency.len()];
+    let mut stack = Vec::with_capacity(entry_points.len());
+    for &id in entry_points {
+        let position = adjacency
+            .position_of(graph, id)
+            .ok_or_else(|| GraphMigratorError::NodeNotFound(id.to_string()))?;
+        stack.push(position);
+    }
+    while let Some(v) = stack.pop() {
+        if visited[v] {
+            continue;
+        }
+        visited[v] = true;
+        stack.extend(adjacency.outgoing[v].iter().map(|&(t, _)| t));
+    }
+
+    Ok(adjacency
+        .nodes
+        .iter()
+        .enumerate()
+        .filter(|&(p, _)| !visited[p])
+        .filter_map(|(_, &idx)| graph.node_weight(idx))
+        .filter(|node| {
+            matches!(
+                node.node_type,
+                NodeType::Function | NodeType::Method | NodeType::Class
+            )
+        })
+        .map(|node| node.id.clone())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{add_node, link};
+
+    #[test]
+    fn test_reports_symbols_not_reached() {
+        let mut graph = Graph::new();
+        let main = add_node(&mut graph, "m.py::main", NodeType::Function);
+        let helper = add_node(&mut graph, "m.py::helper", NodeType::Function);
+        let model = add_node(&mut graph, "m.py::Model", NodeType::Class);
+        let save = add_node(&mut graph, "m.py::save", NodeType::Method);
+        let orphan = add_node(&mut graph, "m.py::orphan", NodeType::Function);
+        let orphan_callee = add_node(&mut graph, "m.py::orphan_callee", NodeType::Function);
+        add_node(&mut graph, "m.py::CONFIG", NodeType::GlobalVariable);
+        link(&mut graph, main, helper, EdgeType::Calls);
+        link(&mut graph, helper, model, EdgeType::Calls);
+        link(&mut graph, model, save, EdgeType::Contains);
+        link(&mut graph, orphan, orphan_callee, EdgeType::Calls);
+
+        let dead = unreachable_from(&graph, &["m.py::main"]).unwrap();
+
+        // Globals are not candidates; methods of reachable classes stay alive
+        assert_eq!(dead, vec!["m.py::orphan", "m.py::orphan_callee"]);
+    }
+
+    #[test]
+    fn test_multiple_entry_points() {
+        let mut graph = Graph::new();
+        let main = add_node(&mut graph, "m.py::main", NodeType::Function);
+        let test_main = add_node(&mut graph, "m.py::test_main", NodeType::Function);
+        let helper = add_node(&mut graph, "m.py::helper", NodeType::Function);
+        link(&mut graph, test_main, main, EdgeType::Calls);
+        link(&mut graph, main, helper, EdgeType::Calls);
+        add_node(&mut graph, "m.py::unused", NodeType::Function);
+
+        let dead = unreachable_from(&graph, &["m.py::main", "m.py::test_main"]).unwrap();
+        assert_eq!(dead, vec!["m.py::unused"]);
+
+        // With no entry points everything is unreachable
+        assert_eq!(unreachable_from(&graph, &[]).unwrap().len(), 4);
+
+        assert!(matches!(
+            unreachable_from(&graph, &["m.py::nope"]),
+            Err(GraphMigratorError::NodeNotFound(_))
+        ));
+    }
+}
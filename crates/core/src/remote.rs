@@ -0,0 +1,240 @@
+//! Scanning repositories that are not checked out locally
+//!
+//! Scheduled scans run on hosts that have no working copy of the project.
+//! [`checkout()`] fetches a single revision with `git fetch --depth 1`
+//! into either a temporary directory, removed when the [`Checkout`] is
+//! dropped, or a cache directory that later runs update in place instead
+//! of cloning again.
+//!
+//! # Example
+//! ```no_run
+//! use graph_migrator_core::{remote, resolve};
+//!
+//! let checkout = remote::checkout("https://github.com/org/app.git", Some("v2.1"), None).unwrap();
+//! let output = resolve::resolve_directory(checkout.path()).unwrap();
+//! println!("{}: {} nodes", checkout.commit(), output.graph.graph.node_count());
+//! ```
+
+use crate::error::{GraphMigratorError, Result};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::TempDir;
+
+/// A revision of a remote repository checked out on disk
+#[derive(Debug)]
+pub struct Checkout {
+    path: PathBuf,
+    commit: String,
+    /// The directory at `path`, when it is ours to remove
+    temporary: Option<TempDir>,
+}
+
+impl Checkout {
+    /// Root of the working tree
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Full SHA of the checked-out commit
+    pub fn commit(&self) -> &str {
+        &self.commit
+    }
+}
+
+impl Drop for Checkout {
+    fn drop(&mut self) {
+        if let Some(dir) = self.temporary.take() {
+            if let Err(err) = dir.close() {
+                tracing::warn!(path = %self.path.display(), %err, "could not remove checkout");
+            }
+        }
+    }
+}
+
+/// Check out revision `rev` (a commit SHA, branch or tag; the remote's
+/// default branch if `None`) of the repository at `url`
+///
+/// With a `cache` directory, the checkout lives in a subdirectory named
+/// after `url` and is reused by later calls; otherwise it goes to a fresh
+/// temporary directory with a random name, readable by the current user
+/// only. Only the requested commit is fetched, without history.
+///
+/// # Errors
+/// Returns [`GraphMigratorError::Io`] if `git` cannot be run or the
+/// directory cannot be created, and [`GraphMigratorError::Git`] if a git
+/// command fails (e.g. `url` is unreachable or `rev` does not exist) or
+/// `url` or `rev` starts with `-`, which git would take for an option.
+pub fn checkout(url: &str, rev: Option<&str>, cache: Option<&Path>) -> Result<Checkout> {
+    for (what, value) in [("URL", Some(url)), ("revision", rev)] {
+        if let Some(value) = value.filter(|value| value.starts_with('-')) {
+            return Err(GraphMigratorError::Git(format!(
+                "invalid {} {:?}: must not start with '-'",
+                what, value
+            )));
+        }
+    }
+    let (path, temporary) = match cache {
+        Some(cache) => (cache.join(cache_name(url)), None),
+        None => {
+            let dir = tempfile::Builder::new()
+                .prefix("graph-migrator-")
+                .tempdir()
+                .map_err(|e| GraphMigratorError::io(std::env::temp_dir(), e))?;
+            (dir.path().to_path_buf(), Some(dir))
+        }
+    };
+    // Built before cloning so a failure below still cleans up
+    let mut checkout = Checkout {
+        path,
+        commit: String::new(),
+        temporary,
+    };
+    let dir = checkout.path.clone();
+
+    if dir.join(".git").is_dir() {
+        git(
+            &dir,
+            ["remote", "set-url", "--end-of-options", "origin", url],
+        )?;
+    } else {
+        std::fs::create_dir_all(&dir).map_err(|e| GraphMigratorError::io(&dir, e))?;
+        git(&dir, ["init", "--quiet"])?;
+        git(&dir, ["remote", "add", "--end-of-options", "origin", url])?;
+    }
+    git(
+        &dir,
+        [
+            "fetch",
+            "--quiet",
+            "--depth",
+            "1",
+            "--end-of-options",
+            "origin",
+            rev.unwrap_or("HEAD"),
+        ],
+    )?;
+    git(
+        &dir,
+        ["checkout", "--quiet", "--force", "--detach", "FETCH_HEAD"],
+    )?;
+    // Leftovers from an earlier revision in a cached checkout
+    git(&dir, ["clean", "--quiet", "-d", "--force", "-x"])?;
+    checkout.commit = git(&dir, ["rev-parse", "HEAD"])?.trim().to_string();
+
+    tracing::info!(url, commit = checkout.commit, path = %dir.display(), "checked out");
+    Ok(checkout)
+}
+
+/// Commit checked out in the git working tree at `dir`, or `None` if it is
+/// not one (or git is not installed)
+pub fn head_commit(dir: &Path) -> Option<String> {
+    let commit = git(dir, ["rev-parse", "--verify", "--quiet", "HEAD"]).ok()?;
+    Some(commit.trim().to_string()).filter(|sha| !sha.is_empty())
+}
+
+/// Directory name for `url` within a cache: its characters that are not
+/// safe in file names replaced by `_`
+fn cache_name(url: &str) -> String {
+    url.trim_end_matches('/')
+        .trim_end_matches(".git")
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+/// Run `git <args>` in `dir` and return its stdout
+fn git<I, S>(dir: &Path, args: I) -> Result<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| GraphMigratorError::io("git", e))?;
+    if !output.status.success() {
+        return Err(GraphMigratorError::Git(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A repository with two commits of `app.py`; returns the SHA of the first
+    fn upstream(dir: &Path) -> String {
+        let commit = |source: &str| {
+            std::fs::write(dir.join("app.py"), source).unwrap();
+            git(dir, ["add", "app.py"]).unwrap();
+            git(
+                dir,
+                [
+                    "-c",
+                    "user.name=Test",
+                    "-c",
+                    "user.email=test@example.com",
+                    "commit",
+                    "--quiet",
+                    "-m",
+                    "update",
+                ],
+            )
+            .unwrap();
+            git(dir, ["rev-parse", "HEAD"]).unwrap().trim().to_string()
+        };
+        git(dir, ["init", "--quiet"]).unwrap();
+        let first = commit("def old():\n    pass\n");
+        commit("def new():\n    pass\n");
+        first
+    }
+
+    #[test]
+    fn test_checkout_revisions_and_cache() {
+        let remote = tempfile::tempdir().unwrap();
+        let first = upstream(remote.path());
+        let url = format!("file://{}", remote.path().display());
+
+        let latest = checkout(&url, None, None).unwrap();
+        let temporary = latest.path().to_path_buf();
+        let source = std::fs::read_to_string(temporary.join("app.py")).unwrap();
+        assert!(source.contains("def new"));
+        drop(latest);
+        assert!(!temporary.exists());
+
+        let cache = tempfile::tempdir().unwrap();
+        let pinned = checkout(&url, Some(&first), Some(cache.path())).unwrap();
+        assert_eq!(pinned.commit(), first);
+        let source = std::fs::read_to_string(pinned.path().join("app.py")).unwrap();
+        assert!(source.contains("def old"));
+        let cached = pinned.path().to_path_buf();
+        drop(pinned);
+        assert!(cached.exists());
+
+        let reused = checkout(&url, None, Some(cache.path())).unwrap();
+        assert_eq!(reused.path(), cached);
+        assert_ne!(reused.commit(), first);
+        assert_eq!(head_commit(reused.path()).as_deref(), Some(reused.commit()));
+        assert_eq!(head_commit(cache.path()), None);
+
+        let err = checkout(&url, Some("no-such-branch"), None).unwrap_err();
+        assert!(matches!(err, GraphMigratorError::Git(_)));
+
+        // Option-like values never reach git
+        let marker = cache.path().join("pwned");
+        let upload_pack = format!("--upload-pack=touch {}; git-upload-pack", marker.display());
+        for (url, rev) in [(url.as_str(), upload_pack.as_str()), (&upload_pack, "HEAD")] {
+            let err = checkout(url, Some(rev), None).unwrap_err();
+            assert!(err.to_string().contains("must not start with '-'"));
+        }
+        assert!(!marker.exists());
+    }
+}
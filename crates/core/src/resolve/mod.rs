@@ -0,0 +1,1936 @@
+//! Pass 2: import resolution and cross-file call binding
+//!
+//! Pass 1 ([`crate::parser`] + [`crate::import`]) only links calls whose
+//! target is defined in the same file. Everything else is kept as an
+//! [`UnresolvedCall`](parser::UnresolvedCall). This module maps each file to
+//! its dotted module name (relative to the project's source roots, or to
+//! those of its [workspace root](workspace_members()); see
+//! [`ModuleResolver`]), resolves the file's imports to local bindings, and
+//! rewires calls that go through those bindings to `Calls` edges on the
+//! target file's symbol:
+//!
+//! ```python
+//! # main.py
+//! import utils                      # utils   -> module `utils`
+//! from helpers import fmt as f      # f       -> symbol `helpers.fmt`
+//!
+//! def main():
+//!     utils.helper()                # main.py::main -> utils.py::helper
+//!     f("x")                        # main.py::main -> helpers.py::fmt
+//! ```
+//!
+//! Types named in function annotations are bound the same way, to
+//! `References` edges on the classes they name (see
+//! [`bind_type_references()`]).
+//!
+//! Names a module only re-exports (a package `__init__.py` doing
+//! `from .impl import helper`) are followed to the module that defines them.
+//!
+//! `from module import *` binds the names in the module's `__all__`, or its
+//! public top-level names when it declares none. A name bound by a star
+//! import and then rebound (or the other way round) is reported as a
+//! [`ShadowedName`]. With [`Config::star_imports`] set to
+//! [`StarImports::Module`], star imports are not expanded; each one links the
+//! two modules instead (see [`link_star_imports()`]).
+//!
+//! Calls into modules outside the project (`os.getcwd()`) and attribute
+//! calls on objects (`self.save()`) stay unresolved; they are collected with the imports that did not resolve into an
+//! [`UnresolvedReport`]. With [`Config::external_modules`], calls into
+//! external packages are also linked to one synthetic `Module` node per
+//! package (see [`link_external_modules()`]), annotated with versions from
+//! the project's requirement and lock files (see [`crate::deps`]). With
+//! [`Config::link_tests`], test functions get `Tests` edges to the symbols
+//! they call (see [`crate::testmap`]). With [`Config::frameworks`], Django
+//! and Flask routes and model relations become edges (see
+//! [`link_frameworks()`]).
+//!
+//! [`resolve_imports()`] skips all of the above for a fast, file-level view:
+//! only import statements are extracted, and each project module is linked to
+//! the project modules it imports (see [`link_imports()`]).
+
+mod report;
+mod roots;
+mod venv;
+mod workspace;
+
+pub use report::{
+    ReferenceKind, ShadowedName, UnresolvedCounts, UnresolvedReason, UnresolvedReference,
+    UnresolvedReport,
+};
+pub use roots::ModuleResolver;
+pub use venv::editable_roots;
+pub use workspace::{workspace_members, WorkspaceMember, WORKSPACE_ATTRIBUTE};
+
+use workspace::ProjectIndex;
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use petgraph::stable_graph::NodeIndex;
+
+#[cfg(feature = "std-fs")]
+use crate::cache::ParseCache;
+use crate::config::{Config, StarImports};
+#[cfg(feature = "std-fs")]
+use crate::deps::Dependencies;
+#[cfg(feature = "std-fs")]
+use crate::error::Result;
+use crate::frameworks;
+#[cfg(feature = "std-fs")]
+use crate::graph::GraphMetadata;
+use crate::graph::{Edge, EdgeType, Graph, Node, NodeType};
+use crate::import::{FirstPassOutput, ImportMap, ImportStatement};
+#[cfg(feature = "std-fs")]
+use crate::parser::ParseDiagnostic;
+use crate::parser::{self, ParseSession};
+#[cfg(feature = "std-fs")]
+use crate::testmap;
+
+/// Bidirectional map between project files and dotted module names
+///
+/// `pkg/tools.py` is module `pkg.tools`; `pkg/__init__.py` is the package
+/// module `pkg`. Paths are canonicalized so they match node file paths.
+/// Names are relative to the project root, or to the source roots of a
+/// [`ModuleResolver`] for an index built with [`ModuleResolver::index()`].
+#[derive(Debug, Clone, Default)]
+pub struct ModuleIndex {
+    files: HashMap<String, PathBuf>,
+    modules: HashMap<PathBuf, String>,
+    /// Every dotted prefix of an indexed module, so namespace packages count
+    packages: HashSet<String>,
+}
+
+impl ModuleIndex {
+    /// Index `files` relative to the project `root`
+    ///
+    /// Files outside `root` or without a `.py` extension are ignored.
+    pub fn new<P: AsRef<Path>>(root: &Path, files: impl IntoIterator<Item = P>) -> Self {
+        ModuleResolver::new(root).index(files)
+    }
+
+    /// Index `file` (canonical) as `module`, unless either is already indexed
+    fn insert(&mut self, module: String, file: &Path) {
+        // First file wins if a module and a package share a name
+        let mut prefix = module.as_str();
+        while let Some((parent, _)) = prefix.rsplit_once('.') {
+            self.packages.insert(parent.to_string());
+            prefix = parent;
+        }
+        self.files
+            .entry(module.clone())
+            .or_insert_with(|| file.to_path_buf());
+        self.modules.entry(file.to_path_buf()).or_insert(module);
+    }
+
+    /// File defining the dotted module `module`
+    pub fn file_for(&self, module: &str) -> Option<&Path> {
+        self.files.get(module).map(|p| p.as_path())
+    }
+
+    /// Dotted module name of `file`
+    pub fn module_for(&self, file: &Path) -> Option<&str> {
+        self.modules
+            .get(file)
+            .or_else(|| self.modules.get(&canonical(file)))
+            .map(|m| m.as_str())
+    }
+
+    /// Whether `module` is a project module or (possibly namespace) package
+    pub fn contains(&self, module: &str) -> bool {
+        self.files.contains_key(module) || self.packages.contains(module)
+    }
+
+    /// Whether `module`'s top-level package belongs to the project
+    ///
+    /// Unresolved imports of such modules are mistakes rather than external
+    /// dependencies.
+    pub fn is_project_module(&self, module: &str) -> bool {
+        let top = module.split('.').next().unwrap_or(module);
+        !top.is_empty() && self.contains(top)
+    }
+
+    /// Whether `file` is a package's `__init__.py`
+    fn is_package(file: &Path) -> bool {
+        file.file_stem().is_some_and(|stem| stem == "__init__")
+    }
+}
+
+/// Module indexes of `output`'s files under the source roots `config` sets
+/// for the project and its workspace roots
+fn project_index(output: &FirstPassOutput, root: &Path, config: &Config) -> ProjectIndex {
+    let files: Vec<&PathBuf> = output.graph.file_nodes.iter().collect();
+    ProjectIndex::new(root, config, &files)
+}
+
+/// What a name bound by an import statement refers to
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Binding {
+    /// A module (`import a.b as m`, `from pkg import submodule`)
+    Module(String),
+    /// A symbol defined in a module (`from m import name`)
+    Symbol { module: String, name: String },
+}
+
+impl Binding {
+    /// Dotted name of what the binding refers to
+    fn dotted(&self) -> String {
+        match self {
+            Binding::Module(module) => module.clone(),
+            Binding::Symbol { module, name } => join(module, name),
+        }
+    }
+}
+
+/// Local names each file's imports bind, keyed by canonical file path
+type Bindings = HashMap<PathBuf, HashMap<String, Binding>>;
+
+/// Names `from module import *` binds, keyed by the module's canonical file
+type StarNames = HashMap<PathBuf, BTreeSet<String>>;
+
+/// Bind cross-file calls in `output` through the imports of the calling file
+///
+/// Adds a `Calls` edge for every call that resolves to a symbol defined in
+/// another project file and removes it from `output.graph.unresolved_calls`.
+/// Whatever is left, along with imports of modules that are not in the
+/// project, is recorded in `output.unresolved`. Star imports are expanded.
+/// Returns the number of edges added.
+pub fn bind_calls(output: &mut FirstPassOutput, root: &Path) -> usize {
+    bind_calls_with_config(output, root, &Config::default())
+}
+
+/// [`bind_calls()`], handling star imports as [`Config::star_imports`] says
+pub fn bind_calls_with_config(output: &mut FirstPassOutput, root: &Path, config: &Config) -> usize {
+    let index = project_index(output, root, config);
+    let (bindings, shadowed) = bindings_for(&index, output, config);
+    let symbols = symbol_table(&output.graph.graph);
+
+    let graph = &mut output.graph.graph;
+    let mut bound = 0;
+    output.graph.unresolved_calls.retain(|call| {
+        let target = bindings
+            .get(&call.file)
+            .and_then(|names| resolve_name(index.scope(&call.file), names, &call.callee))
+            .and_then(|target| find_symbol(&index, &bindings, &symbols, target));
+        let caller = graph.get_by_id(&call.caller);
+
+        match (caller, target) {
+            (Some(caller), Some(target)) => {
+                graph.add_edge(caller, target, Edge::new(EdgeType::Calls));
+                bound += 1;
+                false
+            }
+            _ => true,
+        }
+    });
+
+    let mut references = unresolved_imports(&index, &output.imports);
+    for call in &output.graph.unresolved_calls {
+        let names = bindings.get(&call.file);
+        references.push(UnresolvedReference {
+            kind: ReferenceKind::Call,
+            name: call.callee.clone(),
+            file: call.file.clone(),
+            line: call.line,
+            caller: Some(call.caller.clone()),
+            reason: call_reason(index.scope(&call.file), names, &call.callee),
+        });
+    }
+    output.unresolved = UnresolvedReport::new(references);
+    output.unresolved.shadowed = shadowed;
+
+    tracing::info!(
+        bound,
+        unresolved = output.unresolved.len(),
+        shadowed = output.unresolved.shadowed.len(),
+        "bound cross-file calls"
+    );
+    bound
+}
+
+/// Bind annotated types in `output` through the imports of the annotating file
+///
+/// Adds a `References` edge for every type reference that resolves to a
+/// class in another project file, following re-exports and type aliases
+/// (`Money: TypeAlias = Decimal | Amount` links to `Amount`), and removes
+/// it from `output.graph.type_references`. Builtins, external types and
+/// names that resolve to something other than a class are left in place;
+/// they are not reported as unresolved. Returns the number of edges added.
+pub fn bind_type_references(output: &mut FirstPassOutput, root: &Path, config: &Config) -> usize {
+    let index = project_index(output, root, config);
+    let (bindings, _) = bindings_for(&index, output, config);
+    let symbols = symbol_table(&output.graph.graph);
+    let aliases: HashMap<PathBuf, &HashMap<String, Vec<String>>> = output
+        .graph
+        .type_aliases
+        .iter()
+        .map(|(file, aliases)| (canonical(file), aliases))
+        .collect();
+
+    let graph = &mut output.graph.graph;
+    let mut bound = 0;
+    output.graph.type_references.retain(|reference| {
+        let Some(function) = graph.get_by_id(&reference.function) else {
+            return true;
+        };
+        let mut seen = HashSet::new();
+        let classes: Vec<NodeIndex> = type_targets(
+            &index,
+            &bindings,
+            &symbols,
+            &aliases,
+            (reference.file.clone(), reference.name.clone()),
+            &mut seen,
+        )
+        .into_iter()
+        .filter(|&target| {
+            target != function
+                && graph
+                    .node_weight(target)
+                    .is_some_and(|node| node.node_type == NodeType::Class)
+        })
+        .collect();
+        if classes.is_empty() {
+            return true;
+        }
+        for class in classes {
+            let linked = graph
+                .edges_between(function, class)
+                .any(|(_, edge)| edge.edge_type == EdgeType::References);
+            if !linked {
+                graph.add_edge(function, class, Edge::new(EdgeType::References));
+                bound += 1;
+            }
+        }
+        false
+    });
+
+    tracing::debug!(bound, "bound type references");
+    bound
+}
+
+/// Prefix of the IDs of nodes created by [`link_external_modules()`]
+pub const EXTERNAL_ID_PREFIX: &str = "external::";
+
+/// Materialize external packages as nodes and link the symbols that use them
+///
+/// Every top-level package reached by an `External` call in
+/// `output.unresolved` (`os`, `numpy`, ...) becomes a `Module` node with
+/// `external: true` and ID `external::<package>`. Each calling symbol gets
+/// one `Imports` edge per package it uses. Call after [`bind_calls()`];
+/// returns the number of edges added.
+pub fn link_external_modules(output: &mut FirstPassOutput, root: &Path, config: &Config) -> usize {
+    let index = project_index(output, root, config);
+    let (bindings, _) = file_bindings(&index, &output.imports, None);
+
+    let mut links = std::collections::BTreeSet::new();
+    for reference in &output.unresolved.references {
+        let (Some(caller), UnresolvedReason::External) = (&reference.caller, reference.reason)
+        else {
+            continue;
+        };
+        let head = reference.name.split('.').next().unwrap_or(&reference.name);
+        let module = match bindings
+            .get(&reference.file)
+            .and_then(|names| names.get(head))
+        {
+            Some(Binding::Module(module)) | Some(Binding::Symbol { module, .. }) => module,
+            None => continue,
+        };
+        let package = module.split('.').next().unwrap_or(module);
+        links.insert((caller.clone(), package.to_string()));
+    }
+
+    let graph = &mut output.graph.graph;
+    let mut added = 0;
+    for (caller, package) in links {
+        let Some(from) = graph.get_by_id(&caller) else {
+            continue;
+        };
+        let id = format!("{}{}", EXTERNAL_ID_PREFIX, package);
+        let to = match graph.get_by_id(&id) {
+            Some(idx) => idx,
+            None => {
+                let mut node = Node::new(id, package, NodeType::Module, "python", PathBuf::new());
+                node.external = true;
+                graph.add_node(node)
+            }
+        };
+        graph.add_edge(from, to, Edge::new(EdgeType::Imports));
+        added += 1;
+    }
+
+    tracing::debug!(added, "linked external modules");
+    added
+}
+
+/// Add the routes and model relations declared through Django and Flask
+///
+/// Reads every parsed file through `session` (see
+/// [`frameworks::extract_wiring_in()`]). Each URL pattern becomes a `Route`
+/// node with ID `<file>::route:<pattern>`, following `config`'s ID settings,
+/// and gets a `RoutesTo` edge to its view. Each relation field adds a
+/// `ForeignKey` edge from its model to the related model. Views and models
+/// are looked up in the declaring file, then through its imports; lazy
+/// references (`"billing.Invoice"`) are looked up in the app's `models`
+/// module. Self-references and names that resolve to nothing are skipped.
+/// Call after [`bind_calls()`]; returns the number of edges added.
+pub fn link_frameworks(
+    output: &mut FirstPassOutput,
+    session: &mut ParseSession,
+    root: &Path,
+    config: &Config,
+) -> usize {
+    let index = project_index(output, root, config);
+    let (bindings, _) = bindings_for(&index, output, config);
+    let symbols = symbol_table(&output.graph.graph);
+    let root = canonical(root);
+
+    let lookup = |file: &Path, name: &str| {
+        if let Some(&idx) = symbols.get(&(file.to_path_buf(), name.to_string())) {
+            return Some(idx);
+        }
+        let target = resolve_name(index.scope(file), bindings.get(file)?, name)?;
+        find_symbol(&index, &bindings, &symbols, target)
+    };
+    let lazy_model = |file: &Path, reference: &str| {
+        let (app, model) = reference.rsplit_once('.')?;
+        let module = join(app, "models");
+        let scope = index.scope(file);
+        let file = scope.file_for(&module).or_else(|| {
+            let suffix = format!(".{}", module);
+            index.scopes().find_map(|scope| {
+                scope
+                    .modules
+                    .iter()
+                    .find(|(_, name)| name.ends_with(&suffix))
+                    .map(|(file, _)| file.as_path())
+            })
+        })?;
+        symbols
+            .get(&(file.to_path_buf(), model.to_string()))
+            .copied()
+    };
+
+    let mut files: Vec<PathBuf> = output.graph.file_nodes.iter().cloned().collect();
+    files.sort();
+    let mut links = Vec::new();
+    let mut routes = Vec::new();
+    let mut unresolved = 0;
+    for path in files {
+        let wiring = match frameworks::extract_wiring_in(session, &path) {
+            Ok(wiring) => wiring,
+            Err(err) => {
+                tracing::warn!(%err, "skipping framework analysis");
+                continue;
+            }
+        };
+        let file = canonical(&path);
+        for route in wiring.routes {
+            match lookup(&file, &route.view) {
+                Some(view) => routes.push((file.clone(), route, view)),
+                None => unresolved += 1,
+            }
+        }
+        for relation in wiring.relations {
+            let model = symbols
+                .get(&(file.clone(), relation.model.clone()))
+                .copied();
+            let target = match relation.target.as_str() {
+                "self" => continue,
+                target => lookup(&file, target).or_else(|| lazy_model(&file, target)),
+            };
+            match (model, target) {
+                (Some(model), Some(target)) if model != target => {
+                    links.push((model, target, EdgeType::ForeignKey));
+                }
+                (Some(_), Some(_)) => {}
+                _ => unresolved += 1,
+            }
+        }
+    }
+
+    let graph = &mut output.graph.graph;
+    for (file, route, view) in routes {
+        let id = synthetic_id(&file, &format!("route:{}", route.pattern), &root, config);
+        let from = match graph.get_by_id(&id) {
+            Some(idx) => idx,
+            None => {
+                let mut node = Node::new(id, route.pattern, NodeType::Route, "python", file);
+                node.line_range = Some((route.line, route.line));
+                node.namespace = config.namespace.clone();
+                graph.add_node(node)
+            }
+        };
+        links.push((from, view, EdgeType::RoutesTo));
+    }
+
+    let mut added = 0;
+    for (from, to, edge_type) in links {
+        let linked = graph
+            .edges_between(from, to)
+            .any(|(_, edge)| edge.edge_type == edge_type);
+        if !linked {
+            graph.add_edge(from, to, Edge::new(edge_type));
+            added += 1;
+        }
+    }
+    tracing::debug!(added, unresolved, "linked framework wiring");
+    added
+}
+
+/// Link each module that star-imports a project module to that module
+///
+/// Used instead of expansion with [`StarImports::Module`]. Both files get a
+/// `Module` node with ID `<file>::module:<dotted name>`, following `config`'s
+/// ID settings, and every `from module import *` of a project module adds
+/// one `Imports` edge between them. Returns the number of edges added.
+pub fn link_star_imports(output: &mut FirstPassOutput, root: &Path, config: &Config) -> usize {
+    let index = project_index(output, root, config);
+    let root = canonical(root);
+
+    let mut links = BTreeSet::new();
+    for (file, statements) in &output.imports {
+        let file = canonical(file);
+        for statement in statements {
+            let ImportStatement::ImportFrom {
+                module,
+                level,
+                names,
+                ..
+            } = statement
+            else {
+                continue;
+            };
+            if !names.iter().any(|item| item.is_star) {
+                continue;
+            }
+            let scope = index.scope(&file);
+            let target = absolute_module(scope, &file, module.as_deref(), *level)
+                .and_then(|module| scope.file_for(&module));
+            if let Some(target) = target.filter(|target| *target != file) {
+                links.insert((file.clone(), target.to_path_buf()));
+            }
+        }
+    }
+
+    let added = add_module_imports(&mut output.graph.graph, &index, links, &root, config);
+    tracing::debug!(added, "linked star imports");
+    added
+}
+
+/// Link every project module to the project modules it imports
+///
+/// Each parsed file gets a `Module` node, as in [`link_star_imports()`], and
+/// each import of a project module, in any [`ImportScope`](crate::import::ImportScope),
+/// one `Imports` edge. `import a.b` links to the deepest of `a.b` and `a`
+/// that is in the project; `from pkg import name` links to the submodule
+/// `pkg.name` if there is one, and to `pkg` otherwise. Imports of modules
+/// outside the project are skipped. Returns the number of edges added.
+pub fn link_imports(output: &mut FirstPassOutput, root: &Path, config: &Config) -> usize {
+    let index = project_index(output, root, config);
+    let root = canonical(root);
+
+    let mut files: Vec<PathBuf> = output
+        .graph
+        .file_nodes
+        .iter()
+        .map(|f| canonical(f))
+        .collect();
+    files.sort();
+    for file in &files {
+        let idx = module_node(&mut output.graph.graph, &index, file, &root, config);
+        if let Some(node) = output.graph.graph.node_weight(idx) {
+            output
+                .graph
+                .node_locations
+                .insert(node.id.clone(), file.clone());
+        }
+    }
+
+    let mut links = BTreeSet::new();
+    for (file, statements) in &output.imports {
+        let file = canonical(file);
+        let scope = index.scope(&file);
+        for statement in statements {
+            let mut targets = Vec::new();
+            match statement {
+                ImportStatement::Import { items, .. } => {
+                    targets.extend(
+                        items
+                            .iter()
+                            .filter_map(|item| deepest_module(scope, &item.name)),
+                    );
+                }
+                ImportStatement::ImportFrom {
+                    module,
+                    level,
+                    names,
+                    ..
+                } => {
+                    let Some(base) = absolute_module(scope, &file, module.as_deref(), *level)
+                    else {
+                        continue;
+                    };
+                    for item in names {
+                        let submodule = (!item.is_star)
+                            .then(|| scope.file_for(&join(&base, &item.name)))
+                            .flatten();
+                        targets.extend(submodule.or_else(|| scope.file_for(&base)));
+                    }
+                }
+            }
+            for target in targets.into_iter().filter(|target| *target != file) {
+                links.insert((file.clone(), target.to_path_buf()));
+            }
+        }
+    }
+
+    let added = add_module_imports(&mut output.graph.graph, &index, links, &root, config);
+    tracing::debug!(modules = files.len(), added, "linked module imports");
+    added
+}
+
+/// The file of the longest dotted prefix of `module` in the project
+fn deepest_module<'a>(index: &'a ModuleIndex, module: &str) -> Option<&'a Path> {
+    let mut name = module;
+    loop {
+        if let Some(file) = index.file_for(name) {
+            return Some(file);
+        }
+        name = name.rsplit_once('.')?.0;
+    }
+}
+
+/// Add one `Imports` edge per `(importing file, imported file)` between
+/// their `Module` nodes, returning how many were new
+fn add_module_imports(
+    graph: &mut Graph,
+    index: &ProjectIndex,
+    links: BTreeSet<(PathBuf, PathBuf)>,
+    root: &Path,
+    config: &Config,
+) -> usize {
+    let mut added = 0;
+    for (from, to) in links {
+        let from = module_node(graph, index, &from, root, config);
+        let to = module_node(graph, index, &to, root, config);
+        let linked = graph
+            .edges_between(from, to)
+            .any(|(_, edge)| edge.edge_type == EdgeType::Imports);
+        if !linked {
+            graph.add_edge(from, to, Edge::new(EdgeType::Imports));
+            added += 1;
+        }
+    }
+    added
+}
+
+/// The `Module` node of project file `file`, added on first use
+fn module_node(
+    graph: &mut Graph,
+    index: &ProjectIndex,
+    file: &Path,
+    root: &Path,
+    config: &Config,
+) -> NodeIndex {
+    let name = index
+        .scope(file)
+        .module_for(file)
+        .unwrap_or_default()
+        .to_string();
+    let id = synthetic_id(file, &format!("module:{}", name), root, config);
+    if let Some(idx) = graph.get_by_id(&id) {
+        return idx;
+    }
+    let mut node = Node::new(id, name, NodeType::Module, "python", file);
+    node.namespace = config.namespace.clone();
+    graph.add_node(node)
+}
+
+/// ID of a node pass 2 adds for `local` in `file`, following `config`'s ID settings
+fn synthetic_id(file: &Path, local: &str, root: &Path, config: &Config) -> String {
+    let mut id = format!("{}::{}", file.display(), local);
+    if config.relative_ids {
+        id = parser::project_relative_id(&id, root).unwrap_or(id);
+    }
+    if let Some(namespace) = &config.namespace {
+        id = parser::namespaced_id(namespace, &id);
+    }
+    id
+}
+
+/// Run both passes over a directory
+///
+/// Equivalent to
+/// [`import::parse_directory_with_imports()`](crate::import::parse_directory_with_imports())
+/// followed by [`bind_calls()`]; honors the project's `migrator.toml` if present.
+///
+/// # Example
+/// ```no_run
+/// use graph_migrator_core::resolve;
+///
+/// let output = resolve::resolve_directory(std::path::Path::new("my_project")).unwrap();
+/// println!(
+///     "{} edges, {} calls left unresolved",
+///     output.graph.graph.edge_count(),
+///     output.graph.unresolved_calls.len()
+/// );
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn resolve_directory(root: &Path) -> Result<FirstPassOutput> {
+    let config = Config::from_project_root(root)?;
+    resolve_directory_with_config(root, &config)
+}
+
+/// Run both passes over a directory using an explicit configuration
+#[cfg(feature = "std-fs")]
+pub fn resolve_directory_with_config(root: &Path, config: &Config) -> Result<FirstPassOutput> {
+    let mut session = pass_session(root, config);
+    let mut imports = ImportMap::new();
+    let graph =
+        parser::parse_directory_with_config_in(&mut session, root, config, Some(&mut imports))?;
+    retain_framework_trees(&mut session, config);
+
+    let mut output = FirstPassOutput::new(graph, imports);
+    bind_calls_with_config(&mut output, root, config);
+    bind_type_references(&mut output, root, config);
+    if config.star_imports == StarImports::Module {
+        link_star_imports(&mut output, root, config);
+    }
+    if config.external_modules {
+        link_external_modules(&mut output, root, config);
+        Dependencies::from_project(root)?.annotate(&mut output.graph.graph);
+    }
+    if config.frameworks {
+        link_frameworks(&mut output, &mut session, root, config);
+    }
+    if config.link_tests {
+        testmap::link_tests(&mut output.graph.graph);
+    }
+    record_scan(&mut output.graph.graph, root, config);
+    Ok(output)
+}
+
+/// Session for pass 1, caching trees only when a later pass reads them again
+#[cfg(feature = "std-fs")]
+fn pass_session(root: &Path, config: &Config) -> ParseSession {
+    let session = if config.frameworks {
+        ParseSession::new()
+    } else {
+        ParseSession::uncached()
+    };
+    session
+        .with_config(config)
+        .with_cache(ParseCache::for_project(root, config))
+}
+
+/// After pass 1, drop the trees [`link_frameworks()`] will not read
+#[cfg(feature = "std-fs")]
+fn retain_framework_trees(session: &mut ParseSession, config: &Config) {
+    if config.frameworks {
+        session.retain(|file| frameworks::may_declare_wiring(file.source.as_bytes()));
+        tracing::debug!(kept = session.len(), "kept trees for the framework pass");
+    }
+}
+
+/// Build only the file-level import graph of a directory
+///
+/// The fast mode for architecture-level planning on large repositories:
+/// files are parsed for their import statements alone, skipping symbol,
+/// call and type extraction and every later pass. The graph has one `Module`
+/// node per file and `Imports` edges between them (see [`link_imports()`]);
+/// [`Config`]'s discovery and ID settings apply as for a full scan.
+///
+/// # Errors
+/// Returns an error if a file cannot be read or parsed.
+#[cfg(feature = "std-fs")]
+pub fn resolve_imports(root: &Path, config: &Config) -> Result<FirstPassOutput> {
+    let (output, diagnostics) = resolve_imports_lenient(root, config);
+    match diagnostics.into_iter().next() {
+        Some(failed) => Err(crate::GraphMigratorError::parse(failed.file, failed.error)),
+        None => Ok(output),
+    }
+}
+
+/// Lenient counterpart of [`resolve_imports()`]
+///
+/// Files that cannot be read or parsed are reported as diagnostics and
+/// left out of the graph.
+#[cfg(feature = "std-fs")]
+pub fn resolve_imports_lenient(
+    root: &Path,
+    config: &Config,
+) -> (FirstPassOutput, Vec<ParseDiagnostic>) {
+    let started = std::time::Instant::now();
+    let mut files = crate::discovery::discover_project(root, config);
+    files.sort();
+    let _span = tracing::info_span!("parse_imports", files = files.len()).entered();
+
+    let mut session = ParseSession::uncached().with_config(config);
+    let mut graph = parser::MultiFileGraph::new();
+    graph.graph.set_edge_policy(config.edge_policy);
+    let mut imports = ImportMap::new();
+    let mut diagnostics = Vec::new();
+    for file in files {
+        match crate::import::extract_imports_in(&mut session, &file) {
+            Ok(statements) => {
+                graph.file_nodes.insert(file.clone());
+                imports.insert(file, statements);
+            }
+            Err(err) => {
+                tracing::debug!(file = %file.display(), %err, "skipping file");
+                diagnostics.push(ParseDiagnostic {
+                    file,
+                    error: err.to_string(),
+                    severity: parser::Severity::Error,
+                });
+            }
+        }
+    }
+
+    let mut output = FirstPassOutput::new(graph, imports);
+    let edges = link_imports(&mut output, root, config);
+    record_scan(&mut output.graph.graph, root, config);
+    tracing::info!(
+        files = output.graph.file_nodes.len(),
+        skipped = diagnostics.len(),
+        edges,
+        elapsed_ms = started.elapsed().as_millis() as u64,
+        "resolved module imports"
+    );
+    (output, diagnostics)
+}
+
+/// Lenient counterpart of [`resolve_directory()`] driven by an explicit configuration
+///
+/// Files that fail to parse are reported as diagnostics (see
+/// [`parser::parse_directory_lenient()`]) and have no imports to bind.
+#[cfg(feature = "std-fs")]
+pub fn resolve_directory_lenient(
+    root: &Path,
+    config: &Config,
+) -> (FirstPassOutput, Vec<ParseDiagnostic>) {
+    let mut session = pass_session(root, config);
+    let mut imports = ImportMap::new();
+    let (graph, diagnostics) =
+        parser::parse_directory_lenient_in(&mut session, root, config, Some(&mut imports));
+    retain_framework_trees(&mut session, config);
+
+    let mut output = FirstPassOutput::new(graph, imports);
+    bind_calls_with_config(&mut output, root, config);
+    bind_type_references(&mut output, root, config);
+    if config.star_imports == StarImports::Module {
+        link_star_imports(&mut output, root, config);
+    }
+    if config.external_modules {
+        link_external_modules(&mut output, root, config);
+        match Dependencies::from_project(root) {
+            Ok(deps) => {
+                deps.annotate(&mut output.graph.graph);
+            }
+            Err(err) => tracing::warn!(%err, "skipping package metadata"),
+        }
+    }
+    if config.frameworks {
+        link_frameworks(&mut output, &mut session, root, config);
+    }
+    if config.link_tests {
+        testmap::link_tests(&mut output.graph.graph);
+    }
+    record_scan(&mut output.graph.graph, root, config);
+    (output, diagnostics)
+}
+
+/// Record what a scan of `root` produced: workspace members, languages and
+/// the configuration it used
+///
+/// Only values that depend on the project's content and configuration are
+/// recorded, so scanning an unchanged tree twice gives identical metadata;
+/// see [`record_provenance()`] for when and where the scan ran.
+#[cfg(feature = "std-fs")]
+fn record_scan(graph: &mut Graph, root: &Path, config: &Config) {
+    let workspace_roots = workspace::tag_members(graph, root, config);
+    let languages: BTreeSet<&str> = graph
+        .nodes()
+        .filter(|node| !node.external)
+        .map(|node| node.language.as_str())
+        .collect();
+    let metadata = GraphMetadata {
+        tool_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        languages: languages.into_iter().map(str::to_string).collect(),
+        workspace_roots,
+        config_hash: Some(config.fingerprint()),
+        ..GraphMetadata::default()
+    };
+    graph.set_metadata(metadata);
+}
+
+/// Add when and where `graph` was scanned to its metadata: the current
+/// time, `root` as resolved by `config`, and the commit checked out there
+///
+/// Not done by the resolve functions, since these values differ between
+/// scans of the same code; call it before writing a graph whose
+/// provenance should be kept. Runs `git rev-parse` in `root`.
+#[cfg(feature = "std-fs")]
+pub fn record_provenance(graph: &mut Graph, root: &Path, config: &Config) {
+    let scanned_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let mut metadata = graph.metadata().clone();
+    metadata.scanned_at = Some(scanned_at);
+    metadata.root = Some(
+        config
+            .path_resolver()
+            .resolve(root)
+            .unwrap_or_else(|_| root.to_path_buf()),
+    );
+    metadata.git_commit = crate::remote::head_commit(root);
+    graph.set_metadata(metadata);
+}
+
+/// File bindings as [`Config::star_imports`] asks, with the names star imports shadow
+fn bindings_for(
+    index: &ProjectIndex,
+    output: &FirstPassOutput,
+    config: &Config,
+) -> (Bindings, Vec<ShadowedName>) {
+    match config.star_imports {
+        StarImports::Expand => expanded_bindings(index, output),
+        StarImports::Module => file_bindings(index, &output.imports, None),
+    }
+}
+
+/// File bindings with star imports of project modules expanded
+///
+/// A module's star-importable names include the ones it star-imports itself,
+/// so expansion repeats until no module's names change.
+fn expanded_bindings(
+    index: &ProjectIndex,
+    output: &FirstPassOutput,
+) -> (Bindings, Vec<ShadowedName>) {
+    let exports: HashMap<PathBuf, &Vec<String>> = output
+        .graph
+        .exports
+        .iter()
+        .map(|(file, names)| (canonical(file), names))
+        .collect();
+    let mut stars = StarNames::new();
+    loop {
+        let (bindings, shadowed) = file_bindings(index, &output.imports, Some(&stars));
+        let next = star_names(&output.graph.graph, &exports, &bindings);
+        if next == stars {
+            return (bindings, shadowed);
+        }
+        stars = next;
+    }
+}
+
+/// Names `from module import *` binds, for every project module
+///
+/// A module's `__all__` when it declares one; otherwise its top-level
+/// functions and classes and the names its imports bind, except those
+/// starting with an underscore.
+fn star_names(
+    graph: &Graph,
+    exports: &HashMap<PathBuf, &Vec<String>>,
+    bindings: &Bindings,
+) -> StarNames {
+    let mut names = StarNames::new();
+    for node in graph.nodes() {
+        if matches!(node.node_type, NodeType::Function | NodeType::Class) && !node.external {
+            names
+                .entry(node.file_path.clone())
+                .or_default()
+                .insert(node.name.clone());
+        }
+    }
+    for (file, bound) in bindings {
+        names
+            .entry(file.clone())
+            .or_default()
+            .extend(bound.keys().cloned());
+    }
+    for (file, public) in names.iter_mut() {
+        match exports.get(file) {
+            Some(exported) => *public = exported.iter().cloned().collect(),
+            None => public.retain(|name| !name.starts_with('_')),
+        }
+    }
+    names
+}
+
+/// Local names bound by each file's imports
+///
+/// Star imports bind the names in `stars`, or nothing without it. Names
+/// bound twice where a star import is involved are returned as shadowed.
+fn file_bindings(
+    index: &ProjectIndex,
+    imports: &ImportMap,
+    stars: Option<&StarNames>,
+) -> (Bindings, Vec<ShadowedName>) {
+    let mut result = HashMap::new();
+    let mut shadowed = Vec::new();
+    for (file, statements) in imports {
+        let file = canonical(file);
+        let scope = index.scope(&file);
+        let mut names = HashMap::new();
+        // Names whose current binding came from a star import
+        let mut starred = HashSet::new();
+        for statement in statements {
+            for (local, binding, star) in statement_bindings(scope, &file, statement, stars) {
+                let previous = names.insert(local.clone(), binding.clone());
+                if let Some(previous) = previous.filter(|previous| *previous != binding) {
+                    if star || starred.contains(&local) {
+                        shadowed.push(ShadowedName {
+                            name: local.clone(),
+                            file: file.clone(),
+                            line: statement.range().start_line,
+                            previous: previous.dotted(),
+                            current: binding.dotted(),
+                        });
+                    }
+                }
+                if star {
+                    starred.insert(local);
+                } else {
+                    starred.remove(&local);
+                }
+            }
+        }
+        result.insert(file, names);
+    }
+    shadowed.sort_by(|a, b| (&a.file, a.line, &a.name).cmp(&(&b.file, b.line, &b.name)));
+    (result, shadowed)
+}
+
+/// Names one import statement binds, each flagged if a star import bound it
+fn statement_bindings(
+    index: &ModuleIndex,
+    file: &Path,
+    statement: &ImportStatement,
+    stars: Option<&StarNames>,
+) -> Vec<(String, Binding, bool)> {
+    let mut bound = Vec::new();
+    match statement {
+        ImportStatement::Import { items, .. } => {
+            for item in items {
+                match &item.alias {
+                    // `import a.b as m` binds `m` to `a.b`
+                    Some(alias) => {
+                        bound.push((alias.clone(), Binding::Module(item.name.clone()), false));
+                    }
+                    // `import a.b` binds `a`; `a.b.f()` is resolved through it
+                    None => {
+                        let head = item.name.split('.').next().unwrap_or(&item.name);
+                        bound.push((head.to_string(), Binding::Module(head.to_string()), false));
+                    }
+                }
+            }
+        }
+        ImportStatement::ImportFrom {
+            module,
+            level,
+            names: imported,
+            ..
+        } => {
+            let Some(module) = absolute_module(index, file, module.as_deref(), *level) else {
+                return bound;
+            };
+            for item in imported {
+                if item.is_star {
+                    let exported = index
+                        .file_for(&module)
+                        .and_then(|target| stars?.get(target));
+                    for name in exported.into_iter().flatten() {
+                        bound.push((name.clone(), from_binding(index, &module, name), true));
+                    }
+                } else {
+                    let local = item.alias.clone().unwrap_or_else(|| item.name.clone());
+                    bound.push((local, from_binding(index, &module, &item.name), false));
+                }
+            }
+        }
+    }
+    bound
+}
+
+/// What `from <module> import <name>` binds
+fn from_binding(index: &ModuleIndex, module: &str, name: &str) -> Binding {
+    let submodule = join(module, name);
+    if index.file_for(&submodule).is_some() {
+        Binding::Module(submodule)
+    } else {
+        Binding::Symbol {
+            module: module.to_string(),
+            name: name.to_string(),
+        }
+    }
+}
+
+/// Resolve `from <dots><module> import` to an absolute dotted module name
+fn absolute_module(
+    index: &ModuleIndex,
+    file: &Path,
+    module: Option<&str>,
+    level: u8,
+) -> Option<String> {
+    if level == 0 {
+        return module.map(str::to_string);
+    }
+
+    // Relative imports start from the package containing `file`
+    let current = index.module_for(file)?;
+    let mut package: Vec<&str> = if current.is_empty() {
+        Vec::new()
+    } else {
+        current.split('.').collect()
+    };
+    if !ModuleIndex::is_package(file) {
+        package.pop();
+    }
+    for _ in 1..level {
+        package.pop()?;
+    }
+
+    let base = package.join(".");
+    Some(match module {
+        Some(module) => join(&base, module),
+        None => base,
+    })
+}
+
+/// Nodes a type name used in a file refers to, expanding type aliases
+///
+/// `reference` is the file and the name as written there. An alias is
+/// expanded in the file that defines it, so its names resolve through
+/// that file's imports.
+fn type_targets(
+    index: &ProjectIndex,
+    bindings: &Bindings,
+    symbols: &HashMap<(PathBuf, String), NodeIndex>,
+    aliases: &HashMap<PathBuf, &HashMap<String, Vec<String>>>,
+    reference: (PathBuf, String),
+    seen: &mut HashSet<(PathBuf, String)>,
+) -> Vec<NodeIndex> {
+    let (file, name) = reference;
+    let target = match symbols.get(&(file.clone(), name.clone())) {
+        Some(&idx) => return vec![idx],
+        None if aliases.get(&file).is_some_and(|a| a.contains_key(&name)) => (file, name),
+        None => {
+            let Some(target) = bindings
+                .get(&file)
+                .and_then(|names| resolve_name(index.scope(&file), names, &name))
+            else {
+                return Vec::new();
+            };
+            if let Some(idx) = find_symbol(index, bindings, symbols, target.clone()) {
+                return vec![idx];
+            }
+            target
+        }
+    };
+    let Some(names) = aliases.get(&target.0).and_then(|a| a.get(&target.1)) else {
+        return Vec::new();
+    };
+    if !seen.insert(target.clone()) {
+        // Aliases defined in terms of each other
+        return Vec::new();
+    }
+    names
+        .iter()
+        .flat_map(|name| {
+            let reference = (target.0.clone(), name.clone());
+            type_targets(index, bindings, symbols, aliases, reference, seen)
+        })
+        .collect()
+}
+
+/// File and symbol a possibly dotted name refers to through imports
+fn resolve_name(
+    index: &ModuleIndex,
+    names: &HashMap<String, Binding>,
+    name: &str,
+) -> Option<(PathBuf, String)> {
+    let (head, rest) = match name.split_once('.') {
+        Some((head, rest)) => (head, Some(rest)),
+        None => (name, None),
+    };
+
+    match (names.get(head)?, rest) {
+        (Binding::Symbol { module, name }, None) => {
+            Some((index.file_for(module)?.to_path_buf(), name.clone()))
+        }
+        (Binding::Module(module), Some(rest)) => {
+            let full = join(module, rest);
+            let (module, name) = full.rsplit_once('.')?;
+            Some((index.file_for(module)?.to_path_buf(), name.to_string()))
+        }
+        // Methods on imported classes and calls of modules are not bound
+        _ => None,
+    }
+}
+
+/// Why a call that was not bound could not be
+fn call_reason(
+    index: &ModuleIndex,
+    names: Option<&HashMap<String, Binding>>,
+    callee: &str,
+) -> UnresolvedReason {
+    if callee == "__import__" || callee == "importlib.import_module" {
+        return UnresolvedReason::Dynamic;
+    }
+    let head = callee.split('.').next().unwrap_or(callee);
+    let module = match names.and_then(|names| names.get(head)) {
+        Some(Binding::Module(module)) | Some(Binding::Symbol { module, .. }) => module,
+        None => return UnresolvedReason::Unbound,
+    };
+    if index.is_project_module(module) {
+        UnresolvedReason::MissingSymbol
+    } else {
+        UnresolvedReason::External
+    }
+}
+
+/// Imports of modules that are not part of the project
+fn unresolved_imports(index: &ProjectIndex, imports: &ImportMap) -> Vec<UnresolvedReference> {
+    let mut references = Vec::new();
+    for (file, statements) in imports {
+        let file = canonical(file);
+        let index = index.scope(&file);
+        for statement in statements {
+            let (modules, range) = match statement {
+                ImportStatement::Import { items, range, .. } => {
+                    let modules = items
+                        .iter()
+                        .map(|item| (item.name.clone(), Some(item.name.clone())))
+                        .collect();
+                    (modules, range)
+                }
+                ImportStatement::ImportFrom {
+                    module,
+                    level,
+                    range,
+                    ..
+                } => {
+                    let written = format!(
+                        "{}{}",
+                        ".".repeat(*level as usize),
+                        module.as_deref().unwrap_or("")
+                    );
+                    let absolute = absolute_module(index, &file, module.as_deref(), *level);
+                    (vec![(written, absolute)], range)
+                }
+            };
+
+            for (written, absolute) in modules {
+                let reason = match absolute {
+                    Some(module) if index.contains(&module) => continue,
+                    Some(module)
+                        if !written.starts_with('.') && !index.is_project_module(&module) =>
+                    {
+                        UnresolvedReason::External
+                    }
+                    _ => UnresolvedReason::MissingModule,
+                };
+                references.push(UnresolvedReference {
+                    kind: ReferenceKind::Import,
+                    name: written,
+                    file: file.clone(),
+                    line: range.start_line,
+                    caller: None,
+                    reason,
+                });
+            }
+        }
+    }
+    references
+}
+
+/// Map (file, symbol name) to the first node defining it
+fn symbol_table(graph: &Graph) -> HashMap<(PathBuf, String), NodeIndex> {
+    let mut symbols = HashMap::new();
+    for idx in graph.node_indices() {
+        if let Some(node) = graph
+            .node_weight(idx)
+            .filter(|node| !matches!(node.node_type, NodeType::Module | NodeType::Route))
+        {
+            symbols
+                .entry((node.file_path.clone(), node.name.clone()))
+                .or_insert(idx);
+        }
+    }
+    symbols
+}
+
+/// Node defining `name` in `file`, following re-exports
+///
+/// A package facade such as `pkg/__init__.py` doing
+/// `from .impl import helper as helper` defines no `helper` node itself.
+/// When `file` has no symbol `name` but binds it through an import, the
+/// lookup continues in the imported module, through any number of facades.
+fn find_symbol(
+    index: &ProjectIndex,
+    bindings: &Bindings,
+    symbols: &HashMap<(PathBuf, String), NodeIndex>,
+    target: (PathBuf, String),
+) -> Option<NodeIndex> {
+    let mut seen = HashSet::new();
+    let mut target = target;
+    loop {
+        if let Some(&idx) = symbols.get(&target) {
+            return Some(idx);
+        }
+        let binding = bindings.get(&target.0)?.get(&target.1)?;
+        let scope = index.scope(&target.0);
+        if !seen.insert(target) {
+            // Circular re-exports
+            return None;
+        }
+        match binding {
+            Binding::Symbol { module, name } => {
+                target = (scope.file_for(module)?.to_path_buf(), name.clone());
+            }
+            Binding::Module(_) => return None,
+        }
+    }
+}
+
+fn join(base: &str, name: &str) -> String {
+    if base.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", base, name)
+    }
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write(dir: &TempDir, name: &str, content: &str) -> PathBuf {
+        let path = dir.path().join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn call_targets(output: &FirstPassOutput, caller: &str) -> Vec<String> {
+        let graph = &output.graph.graph;
+        let idx = graph
+            .node_indices()
+            .find(|&i| graph.node_weight(i).unwrap().name == caller)
+            .unwrap();
+        let mut targets: Vec<String> = graph
+            .edges_of(idx)
+            .filter(|(_, from, _, edge)| *from == idx && edge.edge_type == EdgeType::Calls)
+            .map(|(_, _, to, _)| graph.node_weight(to).unwrap().name.clone())
+            .collect();
+        targets.sort();
+        targets
+    }
+
+    #[test]
+    fn test_module_index() {
+        let dir = TempDir::new().unwrap();
+        let utils = write(&dir, "utils.py", "");
+        let init = write(&dir, "pkg/__init__.py", "");
+        let tools = write(&dir, "pkg/tools.py", "");
+        let notes = write(&dir, "notes.txt", "");
+
+        let index = ModuleIndex::new(dir.path(), [&utils, &init, &tools, &notes]);
+
+        assert_eq!(index.file_for("utils"), Some(canonical(&utils).as_path()));
+        assert_eq!(index.file_for("pkg"), Some(canonical(&init).as_path()));
+        assert_eq!(index.module_for(&tools), Some("pkg.tools"));
+        assert_eq!(index.module_for(&notes), None);
+    }
+
+    #[test]
+    fn test_bind_calls_in_src_layout() {
+        let dir = TempDir::new().unwrap();
+        write(&dir, "src/mypkg/__init__.py", "");
+        write(&dir, "src/mypkg/util.py", "def helper():\n    pass\n");
+        // Namespace package: no `__init__.py`
+        write(
+            &dir,
+            "src/plugins/csv.py",
+            "from mypkg.util import helper\n",
+        );
+        write(
+            &dir,
+            "tests/test_util.py",
+            "from mypkg.util import helper\nimport plugins.csv\n\n\
+             def test_helper():\n    helper()\n    plugins.csv.helper()\n",
+        );
+
+        let output = resolve_directory(dir.path()).unwrap();
+        // Both call sites reach the same function, so one edge links them
+        assert_eq!(call_targets(&output, "test_helper"), vec!["helper"]);
+        assert!(output.unresolved.is_empty());
+    }
+
+    #[test]
+    fn test_bind_calls_per_workspace_root() {
+        let dir = TempDir::new().unwrap();
+        write(
+            &dir,
+            "migrator.toml",
+            "[[workspace]]\npath = \"services/*\"\n",
+        );
+        write(
+            &dir,
+            "services/billing/app/models.py",
+            "def charge():\n    pass\n",
+        );
+        write(
+            &dir,
+            "services/billing/app/main.py",
+            "from app.models import charge\n\ndef run():\n    charge()\n",
+        );
+        write(
+            &dir,
+            "services/search/app/models.py",
+            "def index():\n    pass\n",
+        );
+        write(
+            &dir,
+            "services/search/app/main.py",
+            "from app.models import index\n\ndef serve():\n    index()\n",
+        );
+
+        let output = resolve_directory(dir.path()).unwrap();
+        // Both services have an `app` package; each resolves to its own
+        assert_eq!(call_targets(&output, "run"), vec!["charge"]);
+        assert_eq!(call_targets(&output, "serve"), vec!["index"]);
+        assert!(output.unresolved.is_empty());
+
+        let graph = &output.graph.graph;
+        assert_eq!(
+            graph.metadata().workspace_roots,
+            ["services/billing", "services/search"]
+        );
+        let index = graph.nodes().find(|node| node.name == "index").unwrap();
+        assert_eq!(
+            index.attributes.get(WORKSPACE_ATTRIBUTE),
+            Some(&crate::AttrValue::Text("services/search".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_bind_calls_into_editable_installs() {
+        let dir = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        write(
+            &dir,
+            "libs/common/src/common/util.py",
+            "def helper():\n    pass\n",
+        );
+        write(&outside, "src/shared/log.py", "def emit():\n    pass\n");
+        write(
+            &dir,
+            "app/main.py",
+            "from common.util import helper\nfrom shared.log import emit\n\n\
+             def main():\n    helper()\n    emit()\n",
+        );
+        let pth = format!(
+            "{}\n{}\n",
+            dir.path().join("libs/common/src").display(),
+            outside.path().join("src").display()
+        );
+        write(
+            &dir,
+            ".venv/lib/python3.12/site-packages/_editable.pth",
+            &pth,
+        );
+
+        // Without the virtualenv, `common` looks like a third-party package
+        let output = resolve_directory(dir.path()).unwrap();
+        assert!(call_targets(&output, "main").is_empty());
+
+        write(&dir, "migrator.toml", "[virtualenv]\npath = \".venv\"\n");
+        let output = resolve_directory(dir.path()).unwrap();
+        assert_eq!(call_targets(&output, "main"), vec!["helper"]);
+
+        // Sources outside the project are only there if scanned
+        write(
+            &dir,
+            "migrator.toml",
+            "[virtualenv]\npath = \".venv\"\nscan_editable = true\n",
+        );
+        let output = resolve_directory(dir.path()).unwrap();
+        assert_eq!(call_targets(&output, "main"), vec!["emit", "helper"]);
+        assert!(output.unresolved.is_empty());
+    }
+
+    #[test]
+    fn test_relative_module_resolution() {
+        let dir = TempDir::new().unwrap();
+        let init = write(&dir, "pkg/__init__.py", "");
+        let tools = write(&dir, "pkg/sub/tools.py", "");
+        let index = ModuleIndex::new(dir.path(), [&init, &tools]);
+        let tools = canonical(&tools);
+        let init = canonical(&init);
+
+        assert_eq!(
+            absolute_module(&index, &tools, Some("shared"), 1).as_deref(),
+            Some("pkg.sub.shared")
+        );
+        assert_eq!(
+            absolute_module(&index, &tools, None, 2).as_deref(),
+            Some("pkg")
+        );
+        assert_eq!(
+            absolute_module(&index, &init, Some("x"), 1).as_deref(),
+            Some("pkg.x")
+        );
+        // Beyond the top-level package
+        assert_eq!(absolute_module(&index, &tools, None, 4), None);
+    }
+
+    #[test]
+    fn test_bind_calls_through_imports() {
+        let dir = TempDir::new().unwrap();
+        write(&dir, "utils.py", "def helper():\n    pass\n");
+        write(
+            &dir,
+            "main.py",
+            "import utils\nimport os\nfrom utils import helper as h\n\n\
+             def main():\n    utils.helper()\n    h()\n    os.getcwd()\n    missing()\n",
+        );
+
+        let output = resolve_directory(dir.path()).unwrap();
+
+        assert_eq!(call_targets(&output, "main"), vec!["helper"]);
+        let left: Vec<&str> = output
+            .graph
+            .unresolved_calls
+            .iter()
+            .map(|c| c.callee.as_str())
+            .collect();
+        assert_eq!(left, vec!["os.getcwd", "missing"]);
+    }
+
+    #[test]
+    fn test_unresolved_report_reasons() {
+        let dir = TempDir::new().unwrap();
+        write(&dir, "pkg/__init__.py", "");
+        write(&dir, "pkg/utils.py", "def helper():\n    pass\n");
+        write(
+            &dir,
+            "main.py",
+            "import numpy as np\nimport importlib\nimport pkg.nope\n\
+             from pkg import utils\nfrom .sibling import x\n\n\
+             def main():\n    np.array()\n    importlib.import_module('m')\n    \
+             utils.helpr()\n    utils.helper()\n    print()\n",
+        );
+
+        let output = resolve_directory(dir.path()).unwrap();
+
+        let reasons: Vec<(ReferenceKind, &str, UnresolvedReason)> = output
+            .unresolved
+            .references
+            .iter()
+            .map(|r| (r.kind, r.name.as_str(), r.reason))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                (ReferenceKind::Import, "numpy", UnresolvedReason::External),
+                (
+                    ReferenceKind::Import,
+                    "importlib",
+                    UnresolvedReason::External
+                ),
+                (
+                    ReferenceKind::Import,
+                    "pkg.nope",
+                    UnresolvedReason::MissingModule
+                ),
+                (
+                    ReferenceKind::Import,
+                    ".sibling",
+                    UnresolvedReason::MissingModule
+                ),
+                (ReferenceKind::Call, "np.array", UnresolvedReason::External),
+                (
+                    ReferenceKind::Call,
+                    "importlib.import_module",
+                    UnresolvedReason::Dynamic
+                ),
+                (
+                    ReferenceKind::Call,
+                    "utils.helpr",
+                    UnresolvedReason::MissingSymbol
+                ),
+                (ReferenceKind::Call, "print", UnresolvedReason::Unbound),
+            ]
+        );
+
+        let counts = output.unresolved.counts_by_file();
+        let main = canonical(&dir.path().join("main.py"));
+        assert_eq!(
+            counts[&main],
+            UnresolvedCounts {
+                calls: 4,
+                imports: 4
+            }
+        );
+    }
+
+    #[test]
+    fn test_link_external_modules() {
+        let dir = TempDir::new().unwrap();
+        write(
+            &dir,
+            "main.py",
+            "import os.path\nfrom numpy import array\n\n\
+             def main():\n    os.path.join()\n    os.getcwd()\n    array()\n\n\
+             def other():\n    array()\n",
+        );
+        let config = Config {
+            external_modules: true,
+            ..Config::default()
+        };
+
+        let output = resolve_directory_with_config(dir.path(), &config).unwrap();
+        let graph = &output.graph.graph;
+
+        let numpy = graph.get_by_id("external::numpy").unwrap();
+        let node = graph.node_weight(numpy).unwrap();
+        assert!(node.external);
+        assert_eq!(node.node_type, NodeType::Module);
+        assert_eq!(serde_json::to_value(node).unwrap()["external"], true);
+        assert_eq!(
+            graph
+                .neighbors(numpy, crate::graph::Direction::Incoming)
+                .count(),
+            2
+        );
+
+        // Two calls into `os` from `main` make a single edge
+        let os = graph.get_by_id("external::os").unwrap();
+        let importers: Vec<_> = graph.edges_of(os).collect();
+        assert_eq!(importers.len(), 1);
+        assert_eq!(importers[0].3.edge_type, EdgeType::Imports);
+
+        // Project nodes keep serializing without the flag
+        let main = graph.nodes().find(|n| n.name == "main").unwrap();
+        assert!(serde_json::to_value(main)
+            .unwrap()
+            .get("external")
+            .is_none());
+    }
+
+    #[test]
+    fn test_bind_calls_through_package_facades() {
+        let dir = TempDir::new().unwrap();
+        write(
+            &dir,
+            "pkg/__init__.py",
+            "from .api import helper as helper, Client\n__all__ = [\"helper\", \"Client\"]\n",
+        );
+        write(
+            &dir,
+            "pkg/api.py",
+            "from ._impl import helper\nfrom .client import *\n",
+        );
+        write(&dir, "pkg/_impl.py", "def helper():\n    pass\n");
+        write(&dir, "pkg/client.py", "class Client:\n    pass\n");
+        // Re-exports that point at each other resolve to nothing
+        write(&dir, "loop_a.py", "from loop_b import ghost\n");
+        write(&dir, "loop_b.py", "from loop_a import ghost\n");
+        write(
+            &dir,
+            "main.py",
+            "import pkg\nfrom pkg import helper\nfrom loop_a import ghost\n\n\
+             def main():\n    helper()\n    pkg.helper()\n    pkg.Client()\n    ghost()\n",
+        );
+
+        let output = resolve_directory(dir.path()).unwrap();
+        // `Client` reaches the facade through a star import in `pkg.api`
+        assert_eq!(call_targets(&output, "main"), vec!["Client", "helper"]);
+        let exports = output
+            .graph
+            .exports
+            .iter()
+            .find(|(file, _)| file.ends_with("pkg/__init__.py"))
+            .map(|(_, names)| names.clone());
+        assert_eq!(
+            exports,
+            Some(vec!["helper".to_string(), "Client".to_string()])
+        );
+        assert!(output
+            .graph
+            .unresolved_calls
+            .iter()
+            .any(|call| call.callee == "ghost"));
+    }
+
+    #[test]
+    fn test_star_imports() {
+        let dir = TempDir::new().unwrap();
+        write(
+            &dir,
+            "shapes.py",
+            "__all__ = [\"area\"]\n\ndef area():\n    pass\n\ndef perimeter():\n    pass\n",
+        );
+        write(
+            &dir,
+            "util.py",
+            "def fmt():\n    pass\n\ndef _private():\n    pass\n",
+        );
+        write(&dir, "other.py", "def fmt():\n    pass\n");
+        write(
+            &dir,
+            "main.py",
+            "from shapes import *\nfrom util import *\nfrom other import fmt\n\n\
+             def main():\n    area()\n    perimeter()\n    fmt()\n    _private()\n",
+        );
+
+        let output = resolve_directory(dir.path()).unwrap();
+        assert_eq!(call_targets(&output, "main"), vec!["area", "fmt"]);
+        let mut unbound: Vec<&str> = output
+            .graph
+            .unresolved_calls
+            .iter()
+            .map(|call| call.callee.as_str())
+            .collect();
+        unbound.sort();
+        assert_eq!(unbound, vec!["_private", "perimeter"]);
+        // The explicit import of `fmt` wins over the star-imported one
+        let shadowed = &output.unresolved.shadowed;
+        assert_eq!(shadowed.len(), 1);
+        assert_eq!((shadowed[0].name.as_str(), shadowed[0].line), ("fmt", 3));
+        assert_eq!(
+            (shadowed[0].previous.as_str(), shadowed[0].current.as_str()),
+            ("util.fmt", "other.fmt")
+        );
+
+        let config = Config {
+            relative_ids: true,
+            star_imports: StarImports::Module,
+            ..Config::default()
+        };
+        let output = resolve_directory_with_config(dir.path(), &config).unwrap();
+        assert_eq!(call_targets(&output, "main"), vec!["fmt"]);
+        assert!(output.unresolved.shadowed.is_empty());
+        let graph = &output.graph.graph;
+        let main = graph.get_by_id("main.py::module:main").unwrap();
+        let mut imported: Vec<String> = graph
+            .edges_of(main)
+            .filter(|(_, from, _, edge)| *from == main && edge.edge_type == EdgeType::Imports)
+            .map(|(_, _, to, _)| graph.node_weight(to).unwrap().id.clone())
+            .collect();
+        imported.sort();
+        assert_eq!(
+            imported,
+            vec!["shapes.py::module:shapes", "util.py::module:util"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_imports_only() {
+        let dir = TempDir::new().unwrap();
+        write(
+            &dir,
+            "main.py",
+            "import os\nimport utils\nfrom pkg import sub, VERSION\n\n\
+             def main():\n    import lazy\n    utils.run()\n",
+        );
+        write(&dir, "utils.py", "def run():\n    pass\n");
+        write(&dir, "lazy.py", "");
+        write(&dir, "pkg/__init__.py", "VERSION = 1\n");
+        write(
+            &dir,
+            "pkg/sub.py",
+            "from . import helper\nimport pkg.helper\n",
+        );
+        write(&dir, "pkg/helper.py", "");
+
+        let config = Config {
+            relative_ids: true,
+            ..Config::default()
+        };
+        let output = resolve_imports(dir.path(), &config).unwrap();
+        let graph = &output.graph.graph;
+        assert_eq!(graph.node_count(), 6);
+        assert!(graph.nodes().all(|node| node.node_type == NodeType::Module));
+        let mut imports: Vec<(String, String)> = graph
+            .edge_endpoints()
+            .map(|(from, to, edge)| {
+                assert_eq!(edge.edge_type, EdgeType::Imports);
+                let name = |idx| graph.node_weight(idx).unwrap().name.clone();
+                (name(from), name(to))
+            })
+            .collect();
+        imports.sort();
+        assert_eq!(
+            imports,
+            vec![
+                ("main".to_string(), "lazy".to_string()),
+                ("main".to_string(), "pkg".to_string()),
+                ("main".to_string(), "pkg.sub".to_string()),
+                ("main".to_string(), "utils".to_string()),
+                ("pkg.sub".to_string(), "pkg.helper".to_string()),
+            ]
+        );
+        assert!(graph.get_by_id("pkg/sub.py::module:pkg.sub").is_some());
+        assert_eq!(output.graph.node_locations.len(), 6);
+    }
+
+    #[test]
+    fn test_bind_type_references() {
+        let dir = TempDir::new().unwrap();
+        write(
+            &dir,
+            "models.py",
+            "from typing import TypeAlias\n\n\
+             class Invoice:\n    pass\n\nclass Line:\n    pass\n\n\
+             Lines: TypeAlias = list[Line]\n",
+        );
+        write(
+            &dir,
+            "billing.py",
+            "import models\nfrom models import Invoice, Lines\n\n\
+             def total(invoice: Invoice, lines: Lines) -> models.Line:\n    pass\n\n\
+             def audit(when: datetime) -> None:\n    pass\n",
+        );
+
+        let output = resolve_directory(dir.path()).unwrap();
+        let graph = &output.graph.graph;
+        let total = graph.nodes().find(|n| n.name == "total").unwrap();
+        let total = graph.get_by_id(&total.id).unwrap();
+        let mut referenced: Vec<String> = graph
+            .edges_of(total)
+            .filter(|(_, from, _, edge)| *from == total && edge.edge_type == EdgeType::References)
+            .map(|(_, _, to, _)| graph.node_weight(to).unwrap().name.clone())
+            .collect();
+        referenced.sort();
+        assert_eq!(referenced, vec!["Invoice", "Line"]);
+        assert!(EdgeType::References.is_dependency());
+
+        // `datetime` is left over, and not reported
+        let mut left: Vec<&str> = output
+            .graph
+            .type_references
+            .iter()
+            .map(|r| r.name.as_str())
+            .collect();
+        left.sort();
+        assert_eq!(left, vec!["datetime"]);
+        assert!(output
+            .unresolved
+            .references
+            .iter()
+            .all(|reference| reference.name != "datetime"));
+    }
+
+    #[test]
+    fn test_link_frameworks() {
+        let dir = TempDir::new().unwrap();
+        write(&dir, "shop/__init__.py", "");
+        write(
+            &dir,
+            "shop/views.py",
+            "def index():\n    pass\n\nclass OrderView:\n    pass\n",
+        );
+        write(
+            &dir,
+            "shop/urls.py",
+            "from django.urls import path\nfrom . import views\n\n\
+             urlpatterns = [\n    path(\"\", views.index),\n    \
+             path(\"orders/\", views.OrderView.as_view()),\n    path(\"x/\", missing),\n]\n",
+        );
+        write(&dir, "billing/__init__.py", "");
+        write(&dir, "billing/models.py", "class Invoice:\n    pass\n");
+        write(
+            &dir,
+            "shop/models.py",
+            "class Customer:\n    pass\n\n\
+             class Order:\n    customer = models.ForeignKey(Customer)\n    \
+             invoice = models.OneToOneField(\"billing.Invoice\")\n    \
+             parent = models.ForeignKey(\"self\")\n",
+        );
+        let config = Config {
+            frameworks: true,
+            relative_ids: true,
+            ..Config::default()
+        };
+
+        let output = resolve_directory_with_config(dir.path(), &config).unwrap();
+        let graph = &output.graph.graph;
+        let targets = |id: &str, edge_type: EdgeType| {
+            let idx = graph.get_by_id(id).unwrap();
+            let mut names: Vec<String> = graph
+                .edges_of(idx)
+                .filter(|(_, from, _, edge)| *from == idx && edge.edge_type == edge_type)
+                .map(|(_, _, to, _)| graph.node_weight(to).unwrap().name.clone())
+                .collect();
+            names.sort();
+            names
+        };
+
+        let route = graph.get_by_id("shop/urls.py::route:orders/").unwrap();
+        let route = graph.node_weight(route).unwrap();
+        assert_eq!(route.node_type, NodeType::Route);
+        assert_eq!(route.line_range, Some((6, 6)));
+        assert_eq!(
+            targets("shop/urls.py::route:orders/", EdgeType::RoutesTo),
+            vec!["OrderView"]
+        );
+        assert_eq!(
+            targets("shop/urls.py::route:", EdgeType::RoutesTo),
+            vec!["index"]
+        );
+        assert!(graph.get_by_id("shop/urls.py::route:x/").is_none());
+        assert_eq!(
+            targets("shop/models.py::Order", EdgeType::ForeignKey),
+            vec!["Customer", "Invoice"]
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_checkout_directory() {
+        let fixture = Path::new("tests/test-fixtures/multi-file-project");
+        let dir = TempDir::new().unwrap();
+        let fingerprints: Vec<String> = ["first", "second/nested"]
+            .into_iter()
+            .map(|checkout| {
+                let root = dir.path().join(checkout);
+                fs::create_dir_all(&root).unwrap();
+                for name in ["main.py", "module_a.py", "module_b.py"] {
+                    fs::copy(fixture.join(name), root.join(name)).unwrap();
+                }
+                resolve_directory(&root).unwrap().graph.graph.fingerprint()
+            })
+            .collect();
+        assert_eq!(fingerprints[0], fingerprints[1]);
+    }
+
+    #[test]
+    fn test_scan_metadata_is_reproducible() {
+        let dir = TempDir::new().unwrap();
+        write(&dir, "app.py", "def main():\n    pass\n");
+        let export = || {
+            let graph = resolve_directory(dir.path()).unwrap().graph.graph;
+            serde_json::to_string(&crate::graph::GraphData::from(&graph)).unwrap()
+        };
+        assert_eq!(export(), export());
+
+        let mut graph = resolve_directory(dir.path()).unwrap().graph.graph;
+        assert_eq!(graph.metadata().languages, vec!["python"]);
+        assert_eq!(graph.metadata().scanned_at, None);
+        assert_eq!(graph.metadata().root, None);
+        record_provenance(&mut graph, dir.path(), &Config::default());
+        assert!(graph.metadata().scanned_at.is_some());
+        assert_eq!(graph.metadata().root.as_deref(), Some(dir.path()));
+    }
+}
@@ -0,0 +1,177 @@
+//! What pass 2 could not resolve
+//!
+//! Every call or import that did not become an edge is recorded with the
+//! reason it was left out, so impact analysis can say how much of the code
+//! it is blind to: a file whose calls all go to `requests` is modelled
+//! differently from one that calls a misspelled local helper.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Whether an unresolved reference is a call or an import
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ReferenceKind {
+    Call,
+    Import,
+}
+
+/// Why a reference could not be resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum UnresolvedReason {
+    /// Goes through a module outside the project (stdlib or third-party)
+    External,
+    /// Module chosen at runtime (`importlib.import_module`, `__import__`)
+    Dynamic,
+    /// Names a project module that does not exist
+    MissingModule,
+    /// Goes through a project import, but no top-level function or class matches
+    MissingSymbol,
+    /// Name not bound by any import: builtins, locals, attributes, typos
+    Unbound,
+}
+
+/// A call or import left out of the graph
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnresolvedReference {
+    /// Call or import
+    pub kind: ReferenceKind,
+    /// Callee as written (`os.getcwd`) or imported module (`numpy`, `.shared`)
+    pub name: String,
+    /// File containing the reference
+    pub file: PathBuf,
+    /// 1-indexed line of the reference
+    pub line: usize,
+    /// ID of the calling function (calls only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caller: Option<String>,
+    /// Why the reference was not resolved
+    pub reason: UnresolvedReason,
+}
+
+/// A name bound twice in one file, where a star import is involved
+///
+/// `from a import *` followed by `from b import *` leaves the file calling
+/// whichever module bound the name last, which is rarely obvious from the
+/// call site. Calls are bound to the later binding, as Python would.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShadowedName {
+    /// Local name bound twice
+    pub name: String,
+    /// File containing both imports
+    pub file: PathBuf,
+    /// 1-indexed line of the import that wins
+    pub line: usize,
+    /// What the name was bound to before (`billing.models.Invoice`)
+    pub previous: String,
+    /// What the import on `line` binds it to
+    pub current: String,
+}
+
+/// Unresolved references per file, split by kind
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnresolvedCounts {
+    pub calls: usize,
+    pub imports: usize,
+}
+
+impl UnresolvedCounts {
+    /// Calls plus imports
+    pub fn total(&self) -> usize {
+        self.calls + self.imports
+    }
+}
+
+/// Every reference pass 2 left unresolved, sorted by file and line
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnresolvedReport {
+    pub references: Vec<UnresolvedReference>,
+    /// Names rebound by or over star imports, sorted by file and line
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shadowed: Vec<ShadowedName>,
+}
+
+impl UnresolvedReport {
+    /// Build a report, sorting references by file, line, kind and name
+    pub fn new(mut references: Vec<UnresolvedReference>) -> Self {
+        references.sort_by(|a, b| {
+            (&a.file, a.line, a.kind, &a.name).cmp(&(&b.file, b.line, b.kind, &b.name))
+        });
+        Self {
+            references,
+            shadowed: Vec::new(),
+        }
+    }
+
+    /// Number of unresolved references
+    pub fn len(&self) -> usize {
+        self.references.len()
+    }
+
+    /// Whether everything was resolved
+    pub fn is_empty(&self) -> bool {
+        self.references.is_empty()
+    }
+
+    /// Counts per file
+    pub fn counts_by_file(&self) -> BTreeMap<PathBuf, UnresolvedCounts> {
+        let mut counts: BTreeMap<PathBuf, UnresolvedCounts> = BTreeMap::new();
+        for reference in &self.references {
+            let entry = counts.entry(reference.file.clone()).or_default();
+            match reference.kind {
+                ReferenceKind::Call => entry.calls += 1,
+                ReferenceKind::Import => entry.imports += 1,
+            }
+        }
+        counts
+    }
+
+    /// Counts per reason
+    pub fn counts_by_reason(&self) -> BTreeMap<UnresolvedReason, usize> {
+        let mut counts = BTreeMap::new();
+        for reference in &self.references {
+            *counts.entry(reference.reason).or_default() += 1;
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference(file: &str, line: usize, kind: ReferenceKind) -> UnresolvedReference {
+        UnresolvedReference {
+            kind,
+            name: "os".to_string(),
+            file: PathBuf::from(file),
+            line,
+            caller: None,
+            reason: UnresolvedReason::External,
+        }
+    }
+
+    #[test]
+    fn test_report_sorting_and_counts() {
+        let report = UnresolvedReport::new(vec![
+            reference("b.py", 1, ReferenceKind::Import),
+            reference("a.py", 9, ReferenceKind::Call),
+            reference("a.py", 2, ReferenceKind::Import),
+            reference("a.py", 5, ReferenceKind::Call),
+        ]);
+
+        let lines: Vec<usize> = report.references.iter().map(|r| r.line).collect();
+        assert_eq!(lines, vec![2, 5, 9, 1]);
+
+        let by_file = report.counts_by_file();
+        assert_eq!(
+            by_file[&PathBuf::from("a.py")],
+            UnresolvedCounts {
+                calls: 2,
+                imports: 1
+            }
+        );
+        assert_eq!(by_file[&PathBuf::from("b.py")].total(), 1);
+        assert_eq!(report.counts_by_reason()[&UnresolvedReason::External], 4);
+    }
+}
@@ -0,0 +1,215 @@
+//! Source roots: where dotted module names start
+//!
+//! Python finds `mypkg.sub.mod` by trying each `sys.path` entry in turn. A
+//! project laid out as `src/mypkg/...` is imported as `mypkg`, not
+//! `src.mypkg`, because `src/` is on the path rather than the repository
+//! root. [`ModuleResolver`] applies the same rule to project files, so
+//! imports in src-layout and multi-root projects resolve.
+
+use super::{canonical, ModuleIndex};
+use crate::config::Config;
+use std::path::{Path, PathBuf};
+
+/// Maps between dotted module names and files under `sys.path`-style roots
+///
+/// Roots are searched in order, like `sys.path`: a module defined under two
+/// roots is the one under the earlier root, and a file's module name is
+/// taken relative to the first root containing it. Directories without an
+/// `__init__.py` are implicit namespace packages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleResolver {
+    /// Canonical roots, highest priority first
+    roots: Vec<PathBuf>,
+}
+
+impl ModuleResolver {
+    /// A resolver with `root` as the only source root
+    pub fn new(root: &Path) -> Self {
+        Self::with_roots([root])
+    }
+
+    /// A resolver searching `roots` in order
+    pub fn with_roots<P: AsRef<Path>>(roots: impl IntoIterator<Item = P>) -> Self {
+        Self {
+            roots: roots
+                .into_iter()
+                .map(|root| canonical(root.as_ref()))
+                .collect(),
+        }
+    }
+
+    /// The source roots of project `root`
+    ///
+    /// [`Config::source_roots`], relative to `root`, when set. Otherwise
+    /// `src/` (if it exists and is not itself a package) followed by `root`.
+    /// Either way followed by the source directories of the editable
+    /// installs in the configured virtualenv, if any.
+    pub fn from_config(root: &Path, config: &Config) -> Self {
+        Self::for_root(root, &config.source_roots)
+            .with_fallback_roots(super::venv::project_editable_roots(root, config))
+    }
+
+    /// The source roots of a project or workspace root at `root`
+    ///
+    /// `source_roots`, relative to `root`, when not empty. Otherwise `src/`
+    /// (if it exists and is not itself a package) followed by `root`.
+    pub fn for_root(root: &Path, source_roots: &[PathBuf]) -> Self {
+        if !source_roots.is_empty() {
+            return Self::with_roots(source_roots.iter().map(|dir| root.join(dir)));
+        }
+        let src = root.join("src");
+        if src.is_dir() && !src.join("__init__.py").exists() {
+            Self::with_roots([src.as_path(), root])
+        } else {
+            Self::new(root)
+        }
+    }
+
+    /// This resolver with `roots` searched after its own, like site-packages
+    /// after the project on `sys.path`
+    pub fn with_fallback_roots<P: AsRef<Path>>(
+        mut self,
+        roots: impl IntoIterator<Item = P>,
+    ) -> Self {
+        for root in roots {
+            let root = canonical(root.as_ref());
+            if !self.roots.contains(&root) {
+                self.roots.push(root);
+            }
+        }
+        self
+    }
+
+    /// Source roots, highest priority first
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Dotted module name of `file`, relative to the first root containing it
+    ///
+    /// `None` for files outside every root and files without a `.py`
+    /// extension. A package's `__init__.py` is named after its directory.
+    pub fn module_name(&self, file: &Path) -> Option<String> {
+        let file = canonical(file);
+        self.roots.iter().find_map(|root| module_name(root, &file))
+    }
+
+    /// File on disk defining `module`, searching the roots in order
+    ///
+    /// Tries `<root>/a/b.py`, then `<root>/a/b/__init__.py`, in each root.
+    /// Namespace packages have no file; see
+    /// [`is_namespace_package()`](Self::is_namespace_package).
+    pub fn find_module(&self, module: &str) -> Option<PathBuf> {
+        let relative: PathBuf = module.split('.').collect();
+        self.roots.iter().find_map(|root| {
+            let base = root.join(&relative);
+            [base.with_extension("py"), base.join("__init__.py")]
+                .into_iter()
+                .find(|candidate| candidate.is_file())
+        })
+    }
+
+    /// Whether `module` is a directory without `__init__.py` under some root
+    /// and no root defines it as a regular module or package
+    pub fn is_namespace_package(&self, module: &str) -> bool {
+        let relative: PathBuf = module.split('.').collect();
+        self.find_module(module).is_none()
+            && self.roots.iter().any(|root| root.join(&relative).is_dir())
+    }
+
+    /// Index parsed `files` by the module names they have under these roots
+    ///
+    /// A file under several roots (`src/` inside the repository root) is
+    /// indexed under each name, but [`ModuleIndex::module_for()`] returns the
+    /// name from the first root.
+    pub fn index<P: AsRef<Path>>(&self, files: impl IntoIterator<Item = P>) -> ModuleIndex {
+        let files: Vec<PathBuf> = files
+            .into_iter()
+            .map(|file| canonical(file.as_ref()))
+            .collect();
+        let mut index = ModuleIndex::default();
+        for root in &self.roots {
+            for file in &files {
+                if let Some(module) = module_name(root, file) {
+                    index.insert(module, file);
+                }
+            }
+        }
+        index
+    }
+}
+
+/// Dotted module name of `file` relative to `root` (both canonical)
+fn module_name(root: &Path, file: &Path) -> Option<String> {
+    if file.extension()? != "py" {
+        return None;
+    }
+    let relative = file.strip_prefix(root).ok()?.with_extension("");
+    let mut parts: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    if parts.last().is_some_and(|last| last == "__init__") {
+        parts.pop();
+    }
+    Some(parts.join("."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_src_layout_and_namespace_packages() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        for file in [
+            "src/mypkg/__init__.py",
+            "src/mypkg/sub/mod.py",
+            "src/nspkg/part.py",
+            "tests/test_mod.py",
+            "lib/mypkg/__init__.py",
+        ] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        let src = canonical(&root.join("src"));
+
+        let resolver = ModuleResolver::from_config(root, &Config::default());
+        assert_eq!(resolver.roots(), [src.clone(), canonical(root)]);
+        assert_eq!(
+            resolver.module_name(&root.join("src/mypkg/sub/mod.py")),
+            Some("mypkg.sub.mod".to_string())
+        );
+        assert_eq!(
+            resolver.module_name(&root.join("tests/test_mod.py")),
+            Some("tests.test_mod".to_string())
+        );
+        assert_eq!(
+            resolver.find_module("mypkg.sub.mod"),
+            Some(src.join("mypkg/sub/mod.py"))
+        );
+        assert_eq!(
+            resolver.find_module("mypkg"),
+            Some(src.join("mypkg/__init__.py"))
+        );
+        assert!(resolver.is_namespace_package("nspkg"));
+        assert!(!resolver.is_namespace_package("mypkg"));
+        assert_eq!(resolver.find_module("missing"), None);
+
+        // Configured roots replace the defaults, and their order decides ties
+        let config = Config {
+            source_roots: vec![PathBuf::from("lib"), PathBuf::from("src")],
+            ..Config::default()
+        };
+        let resolver = ModuleResolver::from_config(root, &config);
+        assert_eq!(
+            resolver.find_module("mypkg"),
+            Some(canonical(&root.join("lib/mypkg/__init__.py")))
+        );
+        assert_eq!(resolver.module_name(&root.join("tests/test_mod.py")), None);
+    }
+}
@@ -0,0 +1,186 @@
+//! Virtualenvs: first-party packages installed in editable mode
+//!
+//! `pip install -e libs/common` does not copy `common` into site-packages;
+//! it leaves a pointer to the source there, and Python finds the package
+//! through it. Without the virtualenv, `import common` looks like an
+//! external dependency. With `[virtualenv] path` set, the source directories
+//! of editable installs become extra source roots, searched after the
+//! project's own (see [`ModuleResolver::from_config()`]), so such imports
+//! resolve to the package's files. Editable packages outside the project are
+//! only parsed if `scan_editable` is set too (see
+//! [`discovery::discover_project()`]).
+//!
+//! [`ModuleResolver::from_config()`]: super::ModuleResolver::from_config
+//! [`discovery::discover_project()`]: crate::discovery::discover_project
+//!
+//! Three kinds of pointer are read from site-packages:
+//!
+//! - `.pth` files listing directories (pip with hatchling, flit, pdm, and
+//!   setuptools in compat mode)
+//! - the `MAPPING` of setuptools' `__editable___*_finder.py` import hooks
+//! - legacy `*.egg-link` files (`setup.py develop`)
+
+use crate::config::Config;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Source directories of the packages installed in editable mode in the
+/// virtualenv at `venv`, in file name order
+///
+/// Each is a directory to import from, like a `sys.path` entry: the parent
+/// of a mapped package, not the package itself. Directories that no longer
+/// exist are left out.
+///
+/// # Example
+/// ```no_run
+/// use graph_migrator_core::resolve::editable_roots;
+/// use std::path::Path;
+///
+/// for root in editable_roots(Path::new(".venv")) {
+///     println!("editable source: {}", root.display());
+/// }
+/// ```
+pub fn editable_roots(venv: &Path) -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    for site_packages in site_packages(venv) {
+        let Ok(entries) = std::fs::read_dir(&site_packages) else {
+            continue;
+        };
+        let mut files: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+        files.sort();
+        for file in files {
+            let name = file
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned();
+            let Ok(text) = std::fs::read_to_string(&file) else {
+                continue;
+            };
+            if name.ends_with(".pth") {
+                roots.extend(pth_entries(&text).map(|entry| site_packages.join(entry)));
+            } else if name.ends_with(".egg-link") {
+                roots.extend(
+                    text.lines()
+                        .next()
+                        .map(|line| site_packages.join(line.trim())),
+                );
+            } else if name.starts_with("__editable__") && name.ends_with("_finder.py") {
+                roots.extend(finder_roots(&text));
+            }
+        }
+    }
+    let mut unique = Vec::new();
+    for root in roots {
+        if root.is_dir() && !unique.contains(&root) {
+            unique.push(root);
+        }
+    }
+    unique
+}
+
+/// [`editable_roots()`] of the virtualenv `config` names for project `root`,
+/// if any
+pub(crate) fn project_editable_roots(root: &Path, config: &Config) -> Vec<PathBuf> {
+    match &config.virtualenv.path {
+        Some(venv) => editable_roots(&root.join(venv)),
+        None => Vec::new(),
+    }
+}
+
+/// `site-packages` directories of the virtualenv at `venv`
+/// (`lib/python3.X/site-packages`, or `Lib/site-packages` on Windows)
+fn site_packages(venv: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(venv.join("lib")) {
+        let mut pythons: Vec<PathBuf> = entries
+            .flatten()
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with("python"))
+            .map(|entry| entry.path().join("site-packages"))
+            .collect();
+        pythons.sort();
+        dirs.extend(pythons);
+    }
+    dirs.push(venv.join("Lib").join("site-packages"));
+    dirs.retain(|dir| dir.is_dir());
+    dirs
+}
+
+/// Directory lines of a `.pth` file: not blank, comments or `import` lines
+fn pth_entries(text: &str) -> impl Iterator<Item = &str> {
+    text.lines().map(str::trim).filter(|line| {
+        !line.is_empty()
+            && !line.starts_with('#')
+            && !line.starts_with("import ")
+            && !line.starts_with("import\t")
+    })
+}
+
+/// Import roots of the packages in a setuptools editable finder's `MAPPING`
+fn finder_roots(text: &str) -> Vec<PathBuf> {
+    static ENTRY: OnceLock<Regex> = OnceLock::new();
+    let entry = ENTRY.get_or_init(|| {
+        Regex::new(r#"['"]([^'"]+)['"]\s*:\s*['"]([^'"]+)['"]"#).expect("valid regex")
+    });
+    let Some(mapping) = text.lines().find(|line| line.starts_with("MAPPING")) else {
+        return Vec::new();
+    };
+    entry
+        .captures_iter(mapping)
+        .filter_map(|captures| Path::new(&captures[2]).parent().map(Path::to_path_buf))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_editable_roots_from_each_kind_of_pointer() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        for package in ["libs/common/src/common", "tools/cli/cli", "legacy/old"] {
+            fs::create_dir_all(root.join(package)).unwrap();
+        }
+        let site = root.join(".venv/lib/python3.12/site-packages");
+        fs::create_dir_all(&site).unwrap();
+        let path = |relative: &str| root.join(relative).display().to_string();
+        fs::write(
+            site.join("__editable__.common-0.1.pth"),
+            format!("# comment\n{}\n", path("libs/common/src")),
+        )
+        .unwrap();
+        fs::write(
+            site.join("__editable___cli_1_0_finder.py"),
+            format!(
+                "import sys\nMAPPING: dict[str, str] = {{'cli': '{}'}}\n",
+                path("tools/cli/cli")
+            ),
+        )
+        .unwrap();
+        fs::write(
+            site.join("old.egg-link"),
+            format!("{}\n.\n", path("legacy")),
+        )
+        .unwrap();
+        fs::write(
+            site.join("distutils-precedence.pth"),
+            "import os; os.getcwd()\n",
+        )
+        .unwrap();
+        fs::write(site.join("stale.pth"), path("gone")).unwrap();
+
+        let roots = editable_roots(&root.join(".venv"));
+        assert_eq!(
+            roots,
+            [
+                root.join("libs/common/src"),
+                root.join("tools/cli"),
+                root.join("legacy"),
+            ]
+        );
+    }
+}
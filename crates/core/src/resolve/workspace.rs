@@ -0,0 +1,275 @@
+//! Workspaces: monorepos with several project roots
+//!
+//! A monorepo's services rarely share one `sys.path`. `services/billing/src`
+//! and `services/search/src` are each an import root of their own, and both
+//! may well define an `app` package. `[[workspace]]` entries in
+//! `migrator.toml` name those roots:
+//!
+//! ```toml
+//! [[workspace]]
+//! path = "services/*/src"
+//!
+//! [[workspace]]
+//! path = "libs/common"
+//! source_roots = [".", "../../vendor"]
+//! ```
+//!
+//! The whole project is still scanned into one graph, but imports written in
+//! a file under a workspace root resolve through that root's own source
+//! roots ([`WorkspaceRoot::source_roots`]), so `from app import models` in
+//! billing finds billing's `app`. Files outside every workspace root use the
+//! project's [`Config::source_roots`]. Each node of a workspace root gets
+//! the [`WORKSPACE_ATTRIBUTE`] attribute, and the graph's metadata lists the
+//! roots found.
+//!
+//! [`WorkspaceRoot::source_roots`]: crate::config::WorkspaceRoot::source_roots
+
+use super::{canonical, ModuleIndex, ModuleResolver};
+use crate::config::Config;
+#[cfg(feature = "std-fs")]
+use crate::graph::{AttrValue, Graph};
+#[cfg(feature = "std-fs")]
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Node attribute naming the workspace root a node's file belongs to
+/// (`services/billing/src`)
+pub const WORKSPACE_ATTRIBUTE: &str = "workspace_root";
+
+/// A directory matched by a `[[workspace]]` entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceMember {
+    /// Path relative to the project root, `/`-separated (`services/billing/src`)
+    pub name: String,
+    /// Canonical directory
+    pub dir: PathBuf,
+    /// Where module names under this root start
+    pub resolver: ModuleResolver,
+}
+
+/// The workspace roots of project `root`, in the order of the `[[workspace]]`
+/// entries and, within one entry, of their paths
+///
+/// `*`, `?` and `[...]` in a path component match directory names, one
+/// level each. Entries matching nothing are skipped; a directory matched by
+/// two entries belongs to the first. Editable installs of the project's
+/// virtualenv are importable from every root, after its own source roots.
+///
+/// # Example
+/// ```no_run
+/// use graph_migrator_core::config::Config;
+/// use graph_migrator_core::resolve::workspace_members;
+/// use std::path::Path;
+///
+/// let root = Path::new("monorepo");
+/// let config = Config::from_project_root(root).unwrap();
+/// for member in workspace_members(root, &config) {
+///     println!("{}: {:?}", member.name, member.resolver.roots());
+/// }
+/// ```
+pub fn workspace_members(root: &Path, config: &Config) -> Vec<WorkspaceMember> {
+    let editable = super::venv::project_editable_roots(root, config);
+    let mut members: Vec<WorkspaceMember> = Vec::new();
+    for entry in &config.workspace {
+        for name in expand(root, &entry.path) {
+            let dir = canonical(&root.join(&name));
+            if members.iter().any(|member| member.dir == dir) {
+                continue;
+            }
+            members.push(WorkspaceMember {
+                name,
+                resolver: ModuleResolver::for_root(&dir, &entry.source_roots)
+                    .with_fallback_roots(&editable),
+                dir,
+            });
+        }
+    }
+    members
+}
+
+/// Directories under `root` matching `pattern`, as sorted `/`-separated
+/// relative paths
+fn expand(root: &Path, pattern: &str) -> Vec<String> {
+    let mut matches = vec![String::new()];
+    for component in pattern.split('/').filter(|c| !c.is_empty() && *c != ".") {
+        let is_glob = component.contains(['*', '?', '[']);
+        let matcher = match globset::Glob::new(component) {
+            Ok(glob) if is_glob => Some(glob.compile_matcher()),
+            Ok(_) => None,
+            Err(err) => {
+                tracing::warn!(pattern, %err, "invalid workspace path");
+                return Vec::new();
+            }
+        };
+        let mut next = Vec::new();
+        for base in &matches {
+            let Some(matcher) = &matcher else {
+                next.push(join(base, component));
+                continue;
+            };
+            let Ok(entries) = std::fs::read_dir(root.join(base)) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if entry.path().is_dir() && matcher.is_match(&name) {
+                    next.push(join(base, &name));
+                }
+            }
+        }
+        next.sort();
+        matches = next;
+    }
+    matches.retain(|name| root.join(name).is_dir());
+    matches
+}
+
+fn join(base: &str, name: &str) -> String {
+    if base.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", base, name)
+    }
+}
+
+/// Module indexes of a project's import scopes: the project's own, and one
+/// per workspace root
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ProjectIndex {
+    project: ModuleIndex,
+    /// Canonical directory of each workspace root, with its index
+    members: Vec<(PathBuf, ModuleIndex)>,
+}
+
+impl ProjectIndex {
+    /// Index `files` under the source roots `config` sets for project `root`
+    /// and each of its workspace roots
+    pub(crate) fn new<P: AsRef<Path>>(root: &Path, config: &Config, files: &[P]) -> Self {
+        let files: Vec<PathBuf> = files.iter().map(|file| canonical(file.as_ref())).collect();
+        Self {
+            project: ModuleResolver::from_config(root, config).index(&files),
+            members: workspace_members(root, config)
+                .into_iter()
+                .map(|member| {
+                    let index = member.resolver.index(&files);
+                    (member.dir, index)
+                })
+                .collect(),
+        }
+    }
+
+    /// The index imports written in `file` resolve through
+    pub(crate) fn scope(&self, file: &Path) -> &ModuleIndex {
+        if self.members.is_empty() {
+            return &self.project;
+        }
+        let file = canonical(file);
+        self.members
+            .iter()
+            .find(|(dir, _)| file.starts_with(dir))
+            .map_or(&self.project, |(_, index)| index)
+    }
+
+    /// Every index, the project's first
+    pub(crate) fn scopes(&self) -> impl Iterator<Item = &ModuleIndex> {
+        std::iter::once(&self.project).chain(self.members.iter().map(|(_, index)| index))
+    }
+}
+
+/// Mark every node under a workspace root with [`WORKSPACE_ATTRIBUTE`],
+/// returning the names of the roots
+#[cfg(feature = "std-fs")]
+pub(crate) fn tag_members(graph: &mut Graph, root: &Path, config: &Config) -> Vec<String> {
+    let members = workspace_members(root, config);
+    if members.is_empty() {
+        return Vec::new();
+    }
+    let mut member_of: HashMap<PathBuf, Option<&str>> = HashMap::new();
+    for idx in graph.node_indices().collect::<Vec<_>>() {
+        let Some(node) = graph.node_weight_mut(idx) else {
+            continue;
+        };
+        if node.external {
+            continue;
+        }
+        let member = *member_of.entry(node.file_path.clone()).or_insert_with(|| {
+            let file = canonical(&node.file_path);
+            members
+                .iter()
+                .find(|member| file.starts_with(&member.dir))
+                .map(|member| member.name.as_str())
+        });
+        if let Some(name) = member {
+            node.attributes.insert(
+                WORKSPACE_ATTRIBUTE.to_string(),
+                AttrValue::Text(name.to_string()),
+            );
+        }
+    }
+    members.into_iter().map(|member| member.name).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::WorkspaceRoot;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_workspace_roots_resolve_separately() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        for file in [
+            "services/billing/src/app/models.py",
+            "services/search/src/app/models.py",
+            "libs/common/shared/util.py",
+            "tools/deploy.py",
+        ] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        let config = Config {
+            workspace: vec![
+                WorkspaceRoot {
+                    path: "services/*/src".to_string(),
+                    source_roots: vec![PathBuf::from("."), PathBuf::from("../../../libs/common")],
+                },
+                WorkspaceRoot {
+                    path: "libs/common".to_string(),
+                    source_roots: Vec::new(),
+                },
+            ],
+            ..Config::default()
+        };
+
+        let members = workspace_members(root, &config);
+        let names: Vec<&str> = members.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["services/billing/src", "services/search/src", "libs/common"]
+        );
+
+        let files: Vec<PathBuf> = [
+            "services/billing/src/app/models.py",
+            "services/search/src/app/models.py",
+            "libs/common/shared/util.py",
+            "tools/deploy.py",
+        ]
+        .iter()
+        .map(|file| root.join(file))
+        .collect();
+        let index = ProjectIndex::new(root, &config, &files);
+        for (file, service) in files.iter().zip(["billing", "search"]) {
+            let scope = index.scope(file);
+            let models = scope.file_for("app.models").unwrap();
+            assert!(models.starts_with(canonical(&root.join("services").join(service))));
+            assert!(scope.file_for("shared.util").is_some());
+        }
+        // Outside the workspace roots, module names start at the project root
+        let project = index.scope(&files[3]);
+        assert_eq!(project.module_for(&files[3]), Some("tools.deploy"));
+        assert!(project.file_for("app.models").is_none());
+    }
+}
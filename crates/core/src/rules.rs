@@ -0,0 +1,515 @@
+//! Migration policies checked in CI
+//!
+//! [`check()`] evaluates the `[rules]` section of `migrator.toml` against a
+//! graph and returns one [`Violation`] per problem found:
+//!
+//! - [`Rule::Cycle`] - project symbols that depend on each other in a loop
+//! - [`Rule::ForbiddenDependency`] - a dependency matching a `[[rules.forbid]]` entry
+//! - [`Rule::UnmigratedHotPath`] - a symbol that is not migrated yet although
+//!   its PageRank is at least `hot_path_pagerank`
+//! - [`Rule::OwnerOrder`] - a symbol whose owner has started migrating
+//!   although an owner listed before it in `[[rules.owner_order]]` has not
+//!   finished; owners come from [`enrich::owners()`](crate::enrich::owners())
+//!
+//! Violations can be printed, or exported with
+//! [`export::sarif`](crate::export::sarif) for code scanning UIs.
+//!
+//! Turning rules on in a large legacy codebase usually reports far more than
+//! can be fixed at once. A [`Baseline`] records the violations accepted so
+//! far; [`Baseline::compare()`] separates new violations from those already
+//! known, so CI can fail only when things get worse (`migrator check --ratchet`).
+
+use crate::config::RulesConfig;
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::{Graph, MigrationStatus, Node};
+use crate::queries::{self, Adjacency};
+use globset::{Glob, GlobMatcher};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// A policy [`check()`] can report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    /// Project symbols that depend on each other in a loop
+    Cycle,
+    /// A dependency matching a `[[rules.forbid]]` entry
+    ForbiddenDependency,
+    /// An unmigrated symbol with a PageRank of at least `hot_path_pagerank`
+    UnmigratedHotPath,
+    /// A symbol whose owner started migrating before an owner listed ahead finished
+    OwnerOrder,
+}
+
+impl Rule {
+    /// Every rule, in report order
+    pub const ALL: [Rule; 4] = [
+        Rule::Cycle,
+        Rule::ForbiddenDependency,
+        Rule::UnmigratedHotPath,
+        Rule::OwnerOrder,
+    ];
+
+    /// Stable identifier, e.g. `forbidden-dependency`
+    pub fn id(self) -> &'static str {
+        match self {
+            Rule::Cycle => "cycle",
+            Rule::ForbiddenDependency => "forbidden-dependency",
+            Rule::UnmigratedHotPath => "unmigrated-hot-path",
+            Rule::OwnerOrder => "owner-order",
+        }
+    }
+
+    /// One-line description of what the rule flags
+    pub fn description(self) -> &'static str {
+        match self {
+            Rule::Cycle => "Project symbols depend on each other in a cycle",
+            Rule::ForbiddenDependency => "A dependency crosses a boundary forbidden in [rules]",
+            Rule::UnmigratedHotPath => "A highly central symbol has not been migrated yet",
+            Rule::OwnerOrder => "An owner started migrating before an earlier owner finished",
+        }
+    }
+}
+
+/// One problem found by [`check()`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Violation {
+    /// Rule that was violated
+    pub rule: Rule,
+    /// Node the violation is reported on
+    pub node: String,
+    /// File containing the node, relative to the project root when inside it
+    pub file_path: PathBuf,
+    /// First line of the node's definition, if known
+    pub line: Option<usize>,
+    /// Other nodes involved: the rest of a cycle, or a forbidden dependency's target
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related: Vec<String>,
+    /// Human-readable explanation
+    pub message: String,
+}
+
+impl Violation {
+    fn at(rule: Rule, node: &Node, related: Vec<String>, root: &Path, message: String) -> Self {
+        Self {
+            rule,
+            node: node.id.clone(),
+            file_path: relative_to(&node.file_path, root).to_path_buf(),
+            line: node.line_range.map(|(start, _)| start),
+            related,
+            message,
+        }
+    }
+
+    /// Identity of the violation across scans
+    ///
+    /// Built from the rule and the nodes involved, not from line numbers or
+    /// the message, so unrelated edits do not make a known violation look new.
+    pub fn fingerprint(&self) -> String {
+        let mut fingerprint = format!("{}:{}", self.rule.id(), self.node);
+        if !self.related.is_empty() {
+            fingerprint.push_str("->");
+            fingerprint.push_str(&self.related.join(","));
+        }
+        fingerprint
+    }
+}
+
+/// Violations accepted so far, stored as JSON (`[rules] baseline`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Baseline {
+    /// Accepted violations, compared by [`Violation::fingerprint()`]
+    pub violations: Vec<Violation>,
+}
+
+/// Outcome of comparing current violations against a [`Baseline`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Ratchet {
+    /// Current violations that the baseline does not contain
+    pub new: Vec<Violation>,
+    /// Baseline violations that no longer occur
+    pub fixed: Vec<Violation>,
+}
+
+impl Baseline {
+    /// Accept every violation in `violations`
+    pub fn new(violations: Vec<Violation>) -> Self {
+        Self { violations }
+    }
+
+    /// Read a baseline file; a missing file is an empty baseline
+    ///
+    /// # Errors
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        let text = std::fs::read_to_string(path).map_err(|e| GraphMigratorError::io(path, e))?;
+        serde_json::from_str(&text).map_err(|e| GraphMigratorError::parse(path, e.to_string()))
+    }
+
+    /// Write the baseline as pretty-printed JSON, creating parent directories
+    ///
+    /// # Errors
+    /// Returns an error if the directory or file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(|e| GraphMigratorError::io(dir, e))?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| GraphMigratorError::Export(e.to_string()))?;
+        std::fs::write(path, json + "\n").map_err(|e| GraphMigratorError::io(path, e))
+    }
+
+    /// Split `current` into new violations, and list the baseline's fixed ones
+    ///
+    /// Violations are matched by [`Violation::fingerprint()`].
+    pub fn compare(&self, current: &[Violation]) -> Ratchet {
+        let known: BTreeSet<String> = self.violations.iter().map(Violation::fingerprint).collect();
+        let seen: BTreeSet<String> = current.iter().map(Violation::fingerprint).collect();
+        Ratchet {
+            new: current
+                .iter()
+                .filter(|v| !known.contains(&v.fingerprint()))
+                .cloned()
+                .collect(),
+            fixed: self
+                .violations
+                .iter()
+                .filter(|v| !seen.contains(&v.fingerprint()))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+/// Evaluate every enabled rule against `graph`
+///
+/// `root` is the project root that `[[rules.forbid]]` globs and reported
+/// file paths are relative to. Violations are sorted by rule, node and
+/// message, without duplicates.
+///
+/// `[[rules.owner_order]]` entries compare [`Node::owners`](crate::Node::owners),
+/// so run [`enrich::owners()`](crate::enrich::owners()) first.
+///
+/// # Errors
+/// Returns [`GraphMigratorError::Config`] if a `[[rules.forbid]]` glob is invalid.
+pub fn check(graph: &Graph, config: &RulesConfig, root: &Path) -> Result<Vec<Violation>> {
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let mut violations = Vec::new();
+    if config.cycles {
+        violations.extend(cycles(graph, &root));
+    }
+    violations.extend(forbidden_dependencies(graph, config, &root)?);
+    if let Some(threshold) = config.hot_path_pagerank {
+        violations.extend(unmigrated_hot_paths(graph, threshold, &root));
+    }
+    violations.extend(owner_order(graph, config, &root));
+    violations.sort_by(|a, b| {
+        (a.rule, &a.node, &a.related, &a.message).cmp(&(b.rule, &b.node, &b.related, &b.message))
+    });
+    // Parallel edges (e.g. two calls) report the same dependency once
+    violations.dedup();
+    Ok(violations)
+}
+
+/// One violation per cycle, reported on its smallest member ID
+fn cycles(graph: &Graph, root: &Path) -> Vec<Violation> {
+    use petgraph::graph::DiGraph;
+
+    let adjacency = Adjacency::dependencies(graph);
+    let mut dense: DiGraph<(), ()> = DiGraph::new();
+    let local: Vec<_> = (0..adjacency.len()).map(|_| dense.add_node(())).collect();
+    for (from, targets) in adjacency.outgoing.iter().enumerate() {
+        for &(to, _) in targets {
+            dense.add_edge(local[from], local[to], ());
+        }
+    }
+
+    let mut violations = Vec::new();
+    for component in petgraph::algo::tarjan_scc(&dense) {
+        if component.len() < 2 {
+            continue;
+        }
+        // Dense positions follow ID order, so sorting them sorts the IDs
+        let mut members: Vec<usize> = component.iter().map(|idx| idx.index()).collect();
+        members.sort_unstable();
+        let Some(first) = graph.node_weight(adjacency.nodes[members[0]]) else {
+            continue;
+        };
+        let ids: Vec<&str> = members.iter().map(|&m| adjacency.id(graph, m)).collect();
+        let message = format!(
+            "{} symbols form a dependency cycle: {}",
+            ids.len(),
+            ids.join(", ")
+        );
+        let related = ids[1..].iter().map(|id| id.to_string()).collect();
+        violations.push(Violation::at(Rule::Cycle, first, related, root, message));
+    }
+    violations
+}
+
+/// Dependency edges whose endpoints match a `[[rules.forbid]]` entry
+fn forbidden_dependencies(
+    graph: &Graph,
+    config: &RulesConfig,
+    root: &Path,
+) -> Result<Vec<Violation>> {
+    let compile = |pattern: &str| -> Result<GlobMatcher> {
+        Glob::new(pattern)
+            .map(|glob| glob.compile_matcher())
+            .map_err(|e| {
+                GraphMigratorError::Config(format!("Invalid [[rules.forbid]] glob: {}", e))
+            })
+    };
+    let mut forbidden = Vec::new();
+    for rule in &config.forbid {
+        forbidden.push((compile(&rule.from)?, compile(&rule.to)?, rule));
+    }
+    if forbidden.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut violations = Vec::new();
+    for (from, to, edge) in graph.edge_endpoints() {
+        if from == to || !edge.edge_type.is_dependency() {
+            continue;
+        }
+        let (Some(source), Some(target)) = (graph.node_weight(from), graph.node_weight(to)) else {
+            continue;
+        };
+        if source.external || target.external {
+            continue;
+        }
+        let source_path = relative_to(&source.file_path, root);
+        let target_path = relative_to(&target.file_path, root);
+        for (from_glob, to_glob, rule) in &forbidden {
+            if from_glob.is_match(source_path) && to_glob.is_match(target_path) {
+                let mut message = format!(
+                    "{} depends on {} ({:?}), forbidden from {} to {}",
+                    source.id, target.id, edge.edge_type, rule.from, rule.to
+                );
+                if let Some(reason) = &rule.reason {
+                    message.push_str(": ");
+                    message.push_str(reason);
+                }
+                violations.push(Violation::at(
+                    Rule::ForbiddenDependency,
+                    source,
+                    vec![target.id.clone()],
+                    root,
+                    message,
+                ));
+            }
+        }
+    }
+    Ok(violations)
+}
+
+/// Project symbols at or above `threshold` PageRank that are not migrated
+fn unmigrated_hot_paths(graph: &Graph, threshold: f64, root: &Path) -> Vec<Violation> {
+    let ranks = queries::pagerank(graph, queries::centrality::DEFAULT_DAMPING);
+    graph
+        .nodes()
+        .filter(|node| queries::migration::is_project_symbol(node))
+        .filter(|node| {
+            !matches!(
+                node.status,
+                MigrationStatus::Migrated | MigrationStatus::Superseded
+            )
+        })
+        .filter_map(|node| {
+            let rank = ranks.get(&node.id).copied()?;
+            (rank >= threshold).then(|| {
+                let message = format!(
+                    "{} is still {:?} but has PageRank {:.4} (threshold {})",
+                    node.id, node.status, rank, threshold
+                );
+                Violation::at(Rule::UnmigratedHotPath, node, Vec::new(), root, message)
+            })
+        })
+        .collect()
+}
+
+/// Started symbols of an `after` owner while a `before` owner has unmigrated ones
+fn owner_order(graph: &Graph, config: &RulesConfig, root: &Path) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    for order in &config.owner_order {
+        let remaining = owned_by(graph, &order.before)
+            .filter(|node| {
+                !matches!(
+                    node.status,
+                    MigrationStatus::Migrated | MigrationStatus::Superseded
+                )
+            })
+            .count();
+        if remaining == 0 {
+            continue;
+        }
+        for node in owned_by(graph, &order.after) {
+            if !matches!(
+                node.status,
+                MigrationStatus::InProgress | MigrationStatus::Migrated
+            ) {
+                continue;
+            }
+            let message = format!(
+                "{} ({}) is {:?} but {} symbol(s) owned by {} are not migrated yet",
+                node.id, order.after, node.status, remaining, order.before
+            );
+            violations.push(Violation::at(
+                Rule::OwnerOrder,
+                node,
+                Vec::new(),
+                root,
+                message,
+            ));
+        }
+    }
+    violations
+}
+
+/// Project symbols listing `owner` among their owners
+fn owned_by<'a>(graph: &'a Graph, owner: &'a str) -> impl Iterator<Item = &'a Node> {
+    graph
+        .nodes()
+        .filter(|node| queries::migration::is_project_symbol(node))
+        .filter(move |node| node.owners.iter().any(|o| o == owner))
+}
+
+/// `path` relative to `root`, or unchanged if it lies outside
+fn relative_to<'a>(path: &'a Path, root: &Path) -> &'a Path {
+    path.strip_prefix(root).unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ForbiddenDependency, OwnerOrder};
+    use crate::graph::EdgeType;
+    use crate::test_util::{function, link};
+
+    #[test]
+    fn test_cycles_and_forbidden_dependencies() {
+        let mut graph = Graph::new();
+        let a = function(&mut graph, "src/new/a.py", "a");
+        let b = function(&mut graph, "src/old/b.py", "b");
+        let c = function(&mut graph, "src/old/c.py", "c");
+        link(&mut graph, a, b, EdgeType::Calls);
+        link(&mut graph, b, c, EdgeType::Calls);
+        link(&mut graph, c, b, EdgeType::Calls);
+        // Recursion is not a cycle between symbols
+        link(&mut graph, a, a, EdgeType::Calls);
+
+        let config = RulesConfig {
+            forbid: vec![ForbiddenDependency {
+                from: "src/new/**".to_string(),
+                to: "src/old/**".to_string(),
+                reason: Some("use the new API".to_string()),
+            }],
+            ..RulesConfig::default()
+        };
+        let violations = check(&graph, &config, Path::new(".")).unwrap();
+
+        let found: Vec<(Rule, &str)> = violations
+            .iter()
+            .map(|v| (v.rule, v.node.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (Rule::Cycle, "src/old/b.py::b"),
+                (Rule::ForbiddenDependency, "src/new/a.py::a"),
+            ]
+        );
+        assert!(violations[0].message.contains("src/old/c.py::c"));
+        assert!(violations[1].message.ends_with("use the new API"));
+
+        let off = RulesConfig {
+            cycles: false,
+            ..RulesConfig::default()
+        };
+        assert!(check(&graph, &off, Path::new(".")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_unmigrated_hot_paths() {
+        let mut graph = Graph::new();
+        let hub = function(&mut graph, "core.py", "hub");
+        let done = function(&mut graph, "core.py", "done");
+        for name in ["x", "y", "z"] {
+            let caller = function(&mut graph, "app.py", name);
+            link(&mut graph, caller, hub, EdgeType::Calls);
+            link(&mut graph, caller, done, EdgeType::Calls);
+        }
+        graph.node_weight_mut(done).unwrap().status = MigrationStatus::Migrated;
+
+        let config = RulesConfig {
+            hot_path_pagerank: Some(0.2),
+            ..RulesConfig::default()
+        };
+        let violations = check(&graph, &config, Path::new(".")).unwrap();
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, Rule::UnmigratedHotPath);
+        assert_eq!(violations[0].node, "core.py::hub");
+    }
+
+    #[test]
+    fn test_owner_order() {
+        let mut graph = Graph::new();
+        for (file, owner) in [("core.py", "@platform"), ("pay.py", "@payments")] {
+            for name in ["a", "b"] {
+                let idx = function(&mut graph, file, name);
+                graph.node_weight_mut(idx).unwrap().owners = vec![owner.to_string()];
+            }
+        }
+        graph.update_node_by_id("pay.py::a", |n| n.status = MigrationStatus::InProgress);
+        graph.update_node_by_id("core.py::a", |n| n.status = MigrationStatus::Migrated);
+
+        let config = RulesConfig {
+            owner_order: vec![OwnerOrder {
+                before: "@platform".to_string(),
+                after: "@payments".to_string(),
+            }],
+            ..RulesConfig::default()
+        };
+        let violations = check(&graph, &config, Path::new(".")).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, Rule::OwnerOrder);
+        assert_eq!(violations[0].node, "pay.py::a");
+        assert!(violations[0]
+            .message
+            .contains("1 symbol(s) owned by @platform"));
+
+        graph.update_node_by_id("core.py::b", |n| n.status = MigrationStatus::Migrated);
+        assert!(check(&graph, &config, Path::new(".")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_baseline_ratchet() {
+        let violation = |node: &str, line| Violation {
+            rule: Rule::Cycle,
+            node: node.to_string(),
+            file_path: PathBuf::from("a.py"),
+            line: Some(line),
+            related: vec!["b.py::b".to_string()],
+            message: format!("{} is in a cycle", node),
+        };
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("nested/baseline.json");
+        assert_eq!(Baseline::load(&path).unwrap(), Baseline::default());
+
+        Baseline::new(vec![violation("a.py::old", 1), violation("a.py::fixed", 5)])
+            .save(&path)
+            .unwrap();
+        let baseline = Baseline::load(&path).unwrap();
+
+        // Moving a known violation to another line does not make it new
+        let ratchet = baseline.compare(&[violation("a.py::old", 40), violation("a.py::new", 2)]);
+        assert_eq!(ratchet.new, vec![violation("a.py::new", 2)]);
+        assert_eq!(ratchet.fixed, vec![violation("a.py::fixed", 5)]);
+    }
+}
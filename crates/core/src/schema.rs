@@ -0,0 +1,247 @@
+//! Versioned JSON schema for the graph interchange format
+//!
+//! [`GraphData`](crate::GraphData) is what `scan --format json` writes and
+//! what the state file holds, so external tools read it directly. Its shape
+//! is published as [`GRAPH_SCHEMA`] (JSON Schema 2020-12, also shipped as
+//! `schema/graph.schema.json`) and every document carries a
+//! `schema_version`.
+//!
+//! New optional fields are added without a version bump, so consumers should
+//! ignore properties they don't know. Anything else bumps
+//! [`SCHEMA_VERSION`] and registers an [`Upgrade`] in [`UPGRADES`], so
+//! documents written by older builds keep loading through
+//! [`GraphData::from_json()`](crate::GraphData::from_json()): it detects a
+//! document's version and runs every step from there on, and rejects
+//! documents from newer builds with an error saying so. The state file
+//! keeps a copy of its old version when it is first rewritten (see
+//! `store::save_graph()`), so tracked state survives tool upgrades.
+
+use crate::error::{GraphMigratorError, Result};
+use serde_json::Value;
+
+/// Version of the `GraphData` format written by this build
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// JSON Schema describing `GraphData` at [`SCHEMA_VERSION`]
+pub const GRAPH_SCHEMA: &str = include_str!("../schema/graph.schema.json");
+
+/// One step of the upgrade chain, rewriting a document from version `from`
+/// to `from + 1`
+#[derive(Debug, Clone, Copy)]
+pub struct Upgrade {
+    /// Version the step reads
+    pub from: u32,
+    /// What changed, for logs
+    pub summary: &'static str,
+    /// Rewrite the document in place
+    pub apply: fn(&mut Value) -> Result<()>,
+}
+
+/// Every upgrade step, oldest first: `UPGRADES[n]` upgrades version `n`
+pub const UPGRADES: &[Upgrade] = &[Upgrade {
+    from: 0,
+    summary: "add schema_version",
+    apply: v0_to_v1,
+}];
+
+/// Schema version of a raw document; documents without one are version 0
+///
+/// # Errors
+/// Returns [`GraphMigratorError::InvalidGraph`] if `schema_version` is not a
+/// non-negative integer.
+pub fn version_of(document: &Value) -> Result<u32> {
+    match document.get("schema_version") {
+        None => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| {
+                GraphMigratorError::InvalidGraph(format!("invalid schema_version: {}", version))
+            }),
+    }
+}
+
+/// Upgrade a raw document in place to [`SCHEMA_VERSION`]
+///
+/// Returns the version the document had before upgrading.
+///
+/// # Errors
+/// Returns [`GraphMigratorError::InvalidGraph`] if the document was written
+/// by a newer build, or an upgrade step cannot make sense of it.
+pub fn upgrade(document: &mut Value) -> Result<u32> {
+    let original = version_of(document)?;
+    if original > SCHEMA_VERSION {
+        return Err(GraphMigratorError::InvalidGraph(format!(
+            "schema version {} is newer than this build supports ({}); upgrade migrator",
+            original, SCHEMA_VERSION
+        )));
+    }
+
+    for step in UPGRADES.iter().filter(|step| step.from >= original) {
+        let to = step.from + 1;
+        tracing::info!(
+            from = step.from,
+            to,
+            summary = step.summary,
+            "upgrading graph"
+        );
+        (step.apply)(document).map_err(|err| {
+            let detail = match err {
+                GraphMigratorError::InvalidGraph(detail) => detail,
+                other => other.to_string(),
+            };
+            GraphMigratorError::InvalidGraph(format!(
+                "cannot upgrade schema version {} to {}: {}",
+                step.from, to, detail
+            ))
+        })?;
+        if let Some(object) = document.as_object_mut() {
+            object.insert("schema_version".to_string(), Value::from(to));
+        }
+    }
+    Ok(original)
+}
+
+/// Unversioned documents already have the version 1 shape
+fn v0_to_v1(document: &mut Value) -> Result<()> {
+    if document.is_object() {
+        Ok(())
+    } else {
+        Err(GraphMigratorError::InvalidGraph(
+            "expected a JSON object".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{
+        Blame, Centrality, Edge, EdgePolicy, EdgeType, Effort, Graph, GraphData, GraphMetadata,
+        MigrationStatus, Node, NodeType, PackageInfo, StatusChange,
+    };
+
+    #[test]
+    fn test_upgrade_versions() {
+        let mut legacy = serde_json::json!({ "nodes": [], "edges": [] });
+        assert_eq!(upgrade(&mut legacy).unwrap(), 0);
+        assert_eq!(version_of(&legacy).unwrap(), SCHEMA_VERSION);
+
+        let data = GraphData::from_json(r#"{"nodes": [], "edges": []}"#).unwrap();
+        assert_eq!(data.schema_version, SCHEMA_VERSION);
+
+        let newer = format!(
+            r#"{{"schema_version": {}, "nodes": [], "edges": []}}"#,
+            SCHEMA_VERSION + 1
+        );
+        let err = GraphData::from_json(&newer).unwrap_err();
+        assert!(err.to_string().contains("newer than this build"));
+
+        let err = GraphData::from_json("[]").unwrap_err();
+        assert!(matches!(err, GraphMigratorError::InvalidGraph(_)));
+        assert!(err.to_string().contains("schema version 0 to 1"));
+    }
+
+    /// Every version before this build's has exactly one step, in order
+    #[test]
+    fn test_upgrade_chain_is_complete() {
+        assert_eq!(UPGRADES.len(), SCHEMA_VERSION as usize);
+        for (version, step) in UPGRADES.iter().enumerate() {
+            assert_eq!(step.from as usize, version, "{}", step.summary);
+        }
+    }
+
+    /// The shipped schema lists every field a fully populated document has
+    #[test]
+    fn test_schema_matches_serialized_graph() {
+        let schema: Value = serde_json::from_str(GRAPH_SCHEMA).unwrap();
+        assert_eq!(
+            schema["properties"]["schema_version"]["const"],
+            SCHEMA_VERSION
+        );
+
+        let mut graph = Graph::new().with_edge_policy(EdgePolicy::Count);
+        let mut node = Node::new("m.py::f", "f", NodeType::Function, "python", "m.py");
+        node.line_range = Some((1, 3));
+        node.centrality = Some(Centrality {
+            pagerank: 0.5,
+            betweenness: 0.0,
+        });
+        node.external = true;
+        node.package = Some(PackageInfo {
+            name: "pkg".to_string(),
+            version: Some("1.0".to_string()),
+        });
+        node.status = MigrationStatus::InProgress;
+        node.namespace = Some("repo".to_string());
+        node.history.push(StatusChange {
+            from: MigrationStatus::Pending,
+            to: MigrationStatus::InProgress,
+            at: 1_700_000_000,
+        });
+        node.blame = Some(Blame {
+            last_modified: 1_600_000_000,
+            authors: vec!["Ada".to_string()],
+        });
+        node.churn = Some(4);
+        node.coverage = Some(75.0);
+        node.owners = vec!["@billing".to_string()];
+        node.issue = Some("MIG-1".to_string());
+        node.attributes.insert("team".to_string(), "billing".into());
+        node.effort = Some(Effort {
+            lines: 10,
+            branches: 2,
+            fan_in: 1,
+            fan_out: 3,
+            score: 5.15,
+        });
+        let f = graph.add_node(node);
+        let mut edge = Edge::new(EdgeType::PartOfMigration);
+        edge.attributes.insert("weight".to_string(), 0.5.into());
+        graph.add_edge(f, f, edge);
+        let mut data = GraphData::from(&graph);
+        data.metadata = GraphMetadata {
+            tool_version: Some("0.1.0".to_string()),
+            scanned_at: Some(1_700_000_000),
+            root: Some("/src/app".into()),
+            git_remote: Some("https://example.com/repo.git".to_string()),
+            git_commit: Some("0123abc".to_string()),
+            languages: vec!["python".to_string()],
+            workspace_roots: vec!["services/billing".to_string()],
+            config_hash: Some("ab12".to_string()),
+            file_hashes: [("app.py".to_string(), "cd34".to_string())].into(),
+        };
+        let document = serde_json::to_value(data).unwrap();
+
+        let keys =
+            |value: &Value| -> Vec<String> { value.as_object().unwrap().keys().cloned().collect() };
+        let defined = |name: &str| keys(&schema["$defs"][name]["properties"]);
+        assert_eq!(keys(&document), keys(&schema["properties"]));
+        for key in keys(&document["metadata"]) {
+            assert!(defined("GraphMetadata").contains(&key));
+        }
+        for key in keys(&document["nodes"][0]) {
+            assert!(defined("Node").contains(&key), "Node.{} missing", key);
+        }
+        for key in keys(&document["nodes"][0]["package"]) {
+            assert!(defined("PackageInfo").contains(&key));
+        }
+        for key in keys(&document["nodes"][0]["blame"]) {
+            assert!(defined("Blame").contains(&key));
+        }
+        for key in keys(&document["nodes"][0]["effort"]) {
+            assert!(defined("Effort").contains(&key));
+        }
+        for key in keys(&document["nodes"][0]["history"][0]) {
+            assert!(defined("StatusChange").contains(&key));
+        }
+        for key in keys(&document["edges"][0][2]) {
+            assert!(defined("Edge").contains(&key), "Edge.{} missing", key);
+        }
+
+        let variants = |name: &str| schema["$defs"][name]["enum"].as_array().unwrap().clone();
+        assert!(variants("NodeType").contains(&document["nodes"][0]["node_type"]));
+        assert!(variants("MigrationStatus").contains(&document["nodes"][0]["status"]));
+        assert!(variants("EdgeType").contains(&document["edges"][0][2]["edge_type"]));
+    }
+}
@@ -0,0 +1,713 @@
+//! Memory-mapped binary graph snapshots (`snapshot` feature)
+//!
+//! Loading a multi-hundred-megabyte JSON graph means parsing and allocating
+//! every node before the first query can run. A snapshot stores the columns
+//! of a [`CompactGraph`] in a flat little-endian file instead, and
+//! [`Snapshot::open()`] maps it into memory: opening only checks the header
+//! against the file size, and every accessor then reads straight from the
+//! mapped bytes, so only the pages a query touches are ever read from disk.
+//! [`Snapshot::verify()`] checks the rest of the file up front.
+//!
+//! Layout, after an 8-byte magic and a `u32` version:
+//!
+//! | Section | Contents |
+//! |---------|----------|
+//! | header | node, edge and string counts, text and metadata lengths (`u32` each) |
+//! | strings | `string_count + 1` `u32` offsets, then the UTF-8 text |
+//! | nodes | ID, name, language, file, line start and line end columns (`u32`) |
+//! | | node type, status and external columns (`u8`) |
+//! | edges | `node_count + 1` `u32` offsets, targets (`u32`), edge types (`u8`) |
+//! | incoming edges | the same, per target node and holding sources |
+//! | metadata | the graph's [`GraphMetadata`] as JSON, empty if there is none |
+//!
+//! Snapshots hold what [`CompactGraph`] holds: enrichment data is not kept.
+//! The file must not be modified while a [`Snapshot`] maps it.
+
+use crate::compact::CompactGraph;
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::{Edge, EdgeType, Graph, GraphMetadata, MigrationStatus, Node, NodeType};
+use memmap2::Mmap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// First bytes of every snapshot file
+pub const MAGIC: &[u8; 8] = b"GMSNAP\0\0";
+
+/// Format version written by [`write()`]; other versions are rejected
+pub const VERSION: u32 = 3;
+
+/// Node types by their on-disk code
+const NODE_TYPES: [NodeType; 10] = [
+    NodeType::File,
+    NodeType::Module,
+    NodeType::Class,
+    NodeType::Interface,
+    NodeType::Struct,
+    NodeType::Function,
+    NodeType::Method,
+    NodeType::GlobalVariable,
+    NodeType::MigrationUnit,
+    NodeType::Route,
+];
+
+/// Migration statuses by their on-disk code
+const STATUSES: [MigrationStatus; 5] = [
+    MigrationStatus::Pending,
+    MigrationStatus::InProgress,
+    MigrationStatus::Blocked,
+    MigrationStatus::Migrated,
+    MigrationStatus::Superseded,
+];
+
+/// Edge types by their on-disk code
+const EDGE_TYPES: [EdgeType; 10] = [
+    EdgeType::Contains,
+    EdgeType::Calls,
+    EdgeType::Imports,
+    EdgeType::Inherits,
+    EdgeType::MigratedTo,
+    EdgeType::PartOfMigration,
+    EdgeType::Tests,
+    EdgeType::RoutesTo,
+    EdgeType::ForeignKey,
+    EdgeType::References,
+];
+
+/// Magic, version and the five header counts
+const HEADER_LEN: usize = MAGIC.len() + 6 * 4;
+
+fn code<T: PartialEq>(table: &[T], value: &T) -> u8 {
+    table
+        .iter()
+        .position(|entry| entry == value)
+        .expect("every variant has a snapshot code") as u8
+}
+
+/// Write `graph` in snapshot format
+///
+/// # Errors
+/// Returns [`GraphMigratorError::Export`] if writing to `out` fails.
+pub fn write<W: Write>(graph: &CompactGraph, out: W) -> Result<()> {
+    write_columns(graph, out).map_err(|e| GraphMigratorError::Export(e.to_string()))
+}
+
+fn write_columns<W: Write>(graph: &CompactGraph, mut out: W) -> std::io::Result<()> {
+    let len = |n: usize| u32::try_from(n).expect("compact graphs hold at most u32::MAX items");
+    let metadata = if graph.metadata.is_empty() {
+        Vec::new()
+    } else {
+        serde_json::to_vec(&graph.metadata)?
+    };
+
+    out.write_all(MAGIC)?;
+    for value in [
+        VERSION,
+        len(graph.node_count()),
+        len(graph.edge_count()),
+        len(graph.strings.offsets.len() - 1),
+        len(graph.strings.text.len()),
+        len(metadata.len()),
+    ] {
+        out.write_all(&value.to_le_bytes())?;
+    }
+
+    u32s(&mut out, graph.strings.offsets.iter().copied())?;
+    out.write_all(graph.strings.text.as_bytes())?;
+    for column in [&graph.ids, &graph.names, &graph.languages, &graph.files] {
+        u32s(&mut out, column.iter().copied())?;
+    }
+    u32s(&mut out, graph.lines.iter().map(|&(start, _)| start))?;
+    u32s(&mut out, graph.lines.iter().map(|&(_, end)| end))?;
+
+    let node_types: Vec<u8> = graph
+        .node_types
+        .iter()
+        .map(|t| code(&NODE_TYPES, t))
+        .collect();
+    let statuses: Vec<u8> = graph.statuses.iter().map(|s| code(&STATUSES, s)).collect();
+    let external: Vec<u8> = graph.external.iter().map(|&e| u8::from(e)).collect();
+    out.write_all(&node_types)?;
+    out.write_all(&statuses)?;
+    out.write_all(&external)?;
+
+    u32s(&mut out, graph.edge_offsets.iter().copied())?;
+    u32s(&mut out, graph.edge_targets.iter().copied())?;
+    let edge_types: Vec<u8> = graph
+        .edge_types
+        .iter()
+        .map(|t| code(&EDGE_TYPES, t))
+        .collect();
+    out.write_all(&edge_types)?;
+
+    let (offsets, sources, types) = reverse(graph, &edge_types);
+    u32s(&mut out, offsets.into_iter())?;
+    u32s(&mut out, sources.into_iter())?;
+    out.write_all(&types)?;
+    out.write_all(&metadata)?;
+    out.flush()
+}
+
+/// Incoming edges of `graph` in compressed sparse row form: per-node
+/// offsets, then the sources and edge type codes, sorted by source
+fn reverse(graph: &CompactGraph, edge_types: &[u8]) -> (Vec<u32>, Vec<u32>, Vec<u8>) {
+    let mut offsets = vec![0u32; graph.node_count() + 1];
+    for &target in &graph.edge_targets {
+        offsets[target as usize + 1] += 1;
+    }
+    for node in 0..graph.node_count() {
+        offsets[node + 1] += offsets[node];
+    }
+    let mut next: Vec<u32> = offsets[..graph.node_count()].to_vec();
+    let mut sources = vec![0; graph.edge_count()];
+    let mut types = vec![0; graph.edge_count()];
+    for from in 0..graph.node_count() {
+        let edges = graph.edge_offsets[from] as usize..graph.edge_offsets[from + 1] as usize;
+        for edge in edges {
+            let slot = &mut next[graph.edge_targets[edge] as usize];
+            sources[*slot as usize] = from as u32;
+            types[*slot as usize] = edge_types[edge];
+            *slot += 1;
+        }
+    }
+    (offsets, sources, types)
+}
+
+fn u32s<W: Write>(out: &mut W, values: impl Iterator<Item = u32>) -> std::io::Result<()> {
+    for value in values {
+        out.write_all(&value.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Write a snapshot of `graph` to `path`
+///
+/// # Errors
+/// Returns [`GraphMigratorError::Io`] if the file cannot be created or written.
+pub fn save(graph: &Graph, path: &Path) -> Result<()> {
+    let file = File::create(path).map_err(|e| GraphMigratorError::io(path, e))?;
+    write_columns(&CompactGraph::from_graph(graph), BufWriter::new(file))
+        .map_err(|e| GraphMigratorError::io(path, e))
+}
+
+/// Byte offsets of each section within the mapped file
+#[derive(Debug, Clone, Copy)]
+struct Layout {
+    nodes: usize,
+    edges: usize,
+    string_offsets: usize,
+    text: usize,
+    ids: usize,
+    names: usize,
+    languages: usize,
+    files: usize,
+    line_starts: usize,
+    line_ends: usize,
+    node_types: usize,
+    statuses: usize,
+    external: usize,
+    edge_offsets: usize,
+    edge_targets: usize,
+    edge_types: usize,
+    in_offsets: usize,
+    in_sources: usize,
+    in_types: usize,
+    metadata: usize,
+}
+
+/// A read-only graph mapped from a snapshot file; see the [module docs](self)
+///
+/// Node positions, handles and accessors match [`CompactGraph`].
+#[derive(Debug)]
+pub struct Snapshot {
+    map: Mmap,
+    layout: Layout,
+    path: PathBuf,
+}
+
+impl Snapshot {
+    /// Map the snapshot at `path`, checking its header
+    ///
+    /// Only the header is read: the sections it describes must fill the file
+    /// exactly, but their contents are not checked until read. Accessors
+    /// index the map with bounds checks, so a snapshot corrupted past its
+    /// header makes them panic (or [`find()`](Self::find) miss) rather than
+    /// read out of bounds; call [`verify()`](Self::verify) to rule that out.
+    ///
+    /// # Errors
+    /// Returns [`GraphMigratorError::Io`] if the file cannot be opened or
+    /// mapped, and [`GraphMigratorError::Parse`] if it is not a snapshot of
+    /// this version or is truncated.
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path).map_err(|e| GraphMigratorError::io(path, e))?;
+        // SAFETY: the map is only read through bounds-checked slices, and the
+        // module docs require that the file is not modified while mapped.
+        let map = unsafe { Mmap::map(&file) }.map_err(|e| GraphMigratorError::io(path, e))?;
+        let layout = read_layout(&map).map_err(|detail| GraphMigratorError::parse(path, detail))?;
+        Ok(Self {
+            map,
+            layout,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Check every section: string offsets and text, handles, codes, sorted
+    /// IDs and both edge indexes
+    ///
+    /// Reads the whole file once, without allocating. After it succeeds,
+    /// accessors cannot panic.
+    ///
+    /// # Errors
+    /// Returns [`GraphMigratorError::Parse`] describing the first problem.
+    pub fn verify(&self) -> Result<()> {
+        verify(&self.map, &self.layout)
+            .map_err(|detail| GraphMigratorError::parse(&self.path, detail))
+    }
+
+    fn u32_at(&self, section: usize, index: usize) -> u32 {
+        read_u32(&self.map, section + index * 4)
+    }
+
+    fn string(&self, handle: u32) -> &str {
+        let handle = handle as usize;
+        let start = self.u32_at(self.layout.string_offsets, handle) as usize;
+        let end = self.u32_at(self.layout.string_offsets, handle + 1) as usize;
+        std::str::from_utf8(&self.map[self.layout.text + start..self.layout.text + end])
+            .expect("snapshot strings are UTF-8")
+    }
+
+    /// Provenance of the graph the snapshot was written from
+    ///
+    /// # Errors
+    /// Returns [`GraphMigratorError::Parse`] if the stored metadata is not
+    /// valid.
+    pub fn metadata(&self) -> Result<GraphMetadata> {
+        parse_metadata(&self.map[self.layout.metadata..])
+            .map_err(|detail| GraphMigratorError::parse(&self.path, detail))
+    }
+
+    /// Number of nodes
+    pub fn node_count(&self) -> usize {
+        self.layout.nodes
+    }
+
+    /// Number of edges
+    pub fn edge_count(&self) -> usize {
+        self.layout.edges
+    }
+
+    /// Position of the node with ID `id`
+    pub fn find(&self, id: &str) -> Option<u32> {
+        let (mut low, mut high) = (0, self.node_count());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match self.id(mid as u32).cmp(id) {
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+                std::cmp::Ordering::Equal => return Some(mid as u32),
+            }
+        }
+        None
+    }
+
+    /// Node ID at `node`
+    pub fn id(&self, node: u32) -> &str {
+        self.string(self.u32_at(self.layout.ids, node as usize))
+    }
+
+    /// Symbol name at `node`
+    pub fn name(&self, node: u32) -> &str {
+        self.string(self.u32_at(self.layout.names, node as usize))
+    }
+
+    /// Source language at `node`
+    pub fn language(&self, node: u32) -> &str {
+        self.string(self.u32_at(self.layout.languages, node as usize))
+    }
+
+    /// Defining file at `node`
+    pub fn file_path(&self, node: u32) -> &Path {
+        Path::new(self.string(self.u32_at(self.layout.files, node as usize)))
+    }
+
+    /// Line range at `node`, if known
+    pub fn line_range(&self, node: u32) -> Option<(usize, usize)> {
+        let start = self.u32_at(self.layout.line_starts, node as usize);
+        let end = self.u32_at(self.layout.line_ends, node as usize);
+        match (start, end) {
+            (0, 0) => None,
+            (start, end) => Some((start as usize, end as usize)),
+        }
+    }
+
+    /// Node type at `node`
+    pub fn node_type(&self, node: u32) -> NodeType {
+        NODE_TYPES[self.map[self.layout.node_types + node as usize] as usize].clone()
+    }
+
+    /// Migration status at `node`
+    pub fn status(&self, node: u32) -> MigrationStatus {
+        STATUSES[self.map[self.layout.statuses + node as usize] as usize]
+    }
+
+    /// Whether `node` is an external (non-project) node
+    pub fn is_external(&self, node: u32) -> bool {
+        self.map[self.layout.external + node as usize] != 0
+    }
+
+    /// `(target, edge type)` of every edge leaving `node`, sorted
+    pub fn outgoing(&self, node: u32) -> impl Iterator<Item = (u32, EdgeType)> + '_ {
+        let node = node as usize;
+        let start = self.u32_at(self.layout.edge_offsets, node) as usize;
+        let end = self.u32_at(self.layout.edge_offsets, node + 1) as usize;
+        (start..end).map(|edge| self.edge(edge))
+    }
+
+    /// `(source, edge type)` of every edge entering `node`, sorted by source
+    pub fn incoming(&self, node: u32) -> impl Iterator<Item = (u32, EdgeType)> + '_ {
+        let node = node as usize;
+        let start = self.u32_at(self.layout.in_offsets, node) as usize;
+        let end = self.u32_at(self.layout.in_offsets, node + 1) as usize;
+        (start..end).map(|edge| {
+            (
+                self.u32_at(self.layout.in_sources, edge),
+                EDGE_TYPES[self.map[self.layout.in_types + edge] as usize],
+            )
+        })
+    }
+
+    fn edge(&self, edge: usize) -> (u32, EdgeType) {
+        (
+            self.u32_at(self.layout.edge_targets, edge),
+            EDGE_TYPES[self.map[self.layout.edge_types + edge] as usize],
+        )
+    }
+
+    /// Rebuild a [`Graph`] with the kept fields; invalid metadata is left
+    /// out (see [`metadata()`](Self::metadata))
+    pub fn to_graph(&self) -> Graph {
+        let mut graph = Graph::new();
+        graph.set_metadata(self.metadata().unwrap_or_default());
+        let indices: Vec<_> = (0..self.node_count() as u32)
+            .map(|n| {
+                let mut node = Node::new(
+                    self.id(n),
+                    self.name(n),
+                    self.node_type(n),
+                    self.language(n),
+                    self.file_path(n),
+                );
+                node.line_range = self.line_range(n);
+                node.status = self.status(n);
+                node.external = self.is_external(n);
+                graph.add_node(node)
+            })
+            .collect();
+        for (from, &from_idx) in indices.iter().enumerate() {
+            for (to, edge_type) in self.outgoing(from as u32) {
+                graph.add_edge(from_idx, indices[to as usize], Edge::new(edge_type));
+            }
+        }
+        graph
+    }
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().expect("4-byte slice"))
+}
+
+/// Locate the sections of a snapshot from its header
+fn read_layout(bytes: &[u8]) -> std::result::Result<Layout, String> {
+    if bytes.len() < HEADER_LEN || &bytes[..MAGIC.len()] != MAGIC {
+        return Err("not a graph snapshot".to_string());
+    }
+    let header = |field: usize| read_u32(bytes, MAGIC.len() + field * 4) as usize;
+    if header(0) != VERSION as usize {
+        return Err(format!(
+            "snapshot version {} is not supported (expected {})",
+            header(0),
+            VERSION
+        ));
+    }
+    let (nodes, edges, strings, text_len) = (header(1), header(2), header(3), header(4));
+    let metadata_len = header(5);
+
+    // Section starts, in file order
+    let mut offset = HEADER_LEN;
+    let mut section = |size: usize| {
+        let start = offset;
+        offset += size;
+        start
+    };
+    let layout = Layout {
+        nodes,
+        edges,
+        string_offsets: section((strings + 1) * 4),
+        text: section(text_len),
+        ids: section(nodes * 4),
+        names: section(nodes * 4),
+        languages: section(nodes * 4),
+        files: section(nodes * 4),
+        line_starts: section(nodes * 4),
+        line_ends: section(nodes * 4),
+        node_types: section(nodes),
+        statuses: section(nodes),
+        external: section(nodes),
+        edge_offsets: section((nodes + 1) * 4),
+        edge_targets: section(edges * 4),
+        edge_types: section(edges),
+        in_offsets: section((nodes + 1) * 4),
+        in_sources: section(edges * 4),
+        in_types: section(edges),
+        metadata: section(metadata_len),
+    };
+    if offset != bytes.len() {
+        return Err(format!(
+            "snapshot is {} bytes, but its header describes {}",
+            bytes.len(),
+            offset
+        ));
+    }
+    Ok(layout)
+}
+
+/// Check the contents of every section of a snapshot laid out as `layout`
+fn verify(bytes: &[u8], layout: &Layout) -> std::result::Result<(), String> {
+    let (nodes, edges) = (layout.nodes, layout.edges);
+    let strings = (layout.text - layout.string_offsets) / 4 - 1;
+    let text_len = layout.ids - layout.text;
+
+    let text = std::str::from_utf8(&bytes[layout.text..layout.text + text_len])
+        .map_err(|e| format!("string table is not UTF-8: {}", e))?;
+    check_offsets(bytes, layout.string_offsets, strings, text_len, "string")?;
+    for i in 0..=strings {
+        let at = read_u32(bytes, layout.string_offsets + i * 4) as usize;
+        if !text.is_char_boundary(at) {
+            return Err(format!("string {} starts inside a character", i));
+        }
+    }
+
+    for (column, name) in [
+        (layout.ids, "ID"),
+        (layout.names, "name"),
+        (layout.languages, "language"),
+        (layout.files, "file"),
+    ] {
+        for node in 0..nodes {
+            if read_u32(bytes, column + node * 4) as usize >= strings {
+                return Err(format!("node {} has an invalid {} handle", node, name));
+            }
+        }
+    }
+    let id = |node: usize| {
+        let handle = read_u32(bytes, layout.ids + node * 4) as usize;
+        let start = read_u32(bytes, layout.string_offsets + handle * 4) as usize;
+        let end = read_u32(bytes, layout.string_offsets + (handle + 1) * 4) as usize;
+        &text[start..end]
+    };
+    if let Some(node) = (1..nodes).find(|&node| id(node - 1) >= id(node)) {
+        return Err(format!("node IDs are not sorted at node {}", node));
+    }
+
+    for (column, count, limit, name) in [
+        (layout.node_types, nodes, NODE_TYPES.len(), "node type"),
+        (layout.statuses, nodes, STATUSES.len(), "status"),
+        (layout.external, nodes, 2, "external flag"),
+        (layout.edge_types, edges, EDGE_TYPES.len(), "edge type"),
+        (layout.in_types, edges, EDGE_TYPES.len(), "edge type"),
+    ] {
+        if let Some(i) = bytes[column..column + count]
+            .iter()
+            .position(|&code| code as usize >= limit)
+        {
+            return Err(format!("item {} has an invalid {} code", i, name));
+        }
+    }
+
+    check_offsets(bytes, layout.edge_offsets, nodes, edges, "edge")?;
+    check_offsets(bytes, layout.in_offsets, nodes, edges, "incoming edge")?;
+    for (column, name) in [
+        (layout.edge_targets, "edge"),
+        (layout.in_sources, "incoming edge"),
+    ] {
+        for edge in 0..edges {
+            if read_u32(bytes, column + edge * 4) as usize >= nodes {
+                return Err(format!("{} {} points past the last node", name, edge));
+            }
+        }
+    }
+    parse_metadata(&bytes[layout.metadata..])?;
+    Ok(())
+}
+
+/// The metadata section, empty for a graph without metadata
+fn parse_metadata(bytes: &[u8]) -> std::result::Result<GraphMetadata, String> {
+    if bytes.is_empty() {
+        return Ok(GraphMetadata::default());
+    }
+    serde_json::from_slice(bytes).map_err(|e| format!("invalid metadata: {}", e))
+}
+
+impl crate::view::GraphView for Snapshot {
+    type NodeRef = u32;
+
+    fn node_count(&self) -> usize {
+        Snapshot::node_count(self)
+    }
+
+    fn edge_count(&self) -> usize {
+        Snapshot::edge_count(self)
+    }
+
+    fn node_refs(&self) -> impl Iterator<Item = u32> + '_ {
+        0..self.node_count() as u32
+    }
+
+    fn find(&self, id: &str) -> Option<u32> {
+        Snapshot::find(self, id)
+    }
+
+    fn id(&self, node: u32) -> &str {
+        Snapshot::id(self, node)
+    }
+
+    fn name(&self, node: u32) -> &str {
+        Snapshot::name(self, node)
+    }
+
+    fn node_type(&self, node: u32) -> NodeType {
+        Snapshot::node_type(self, node)
+    }
+
+    fn status(&self, node: u32) -> MigrationStatus {
+        Snapshot::status(self, node)
+    }
+
+    fn file_path(&self, node: u32) -> &Path {
+        Snapshot::file_path(self, node)
+    }
+
+    fn line_range(&self, node: u32) -> Option<(usize, usize)> {
+        Snapshot::line_range(self, node)
+    }
+
+    fn is_external(&self, node: u32) -> bool {
+        Snapshot::is_external(self, node)
+    }
+
+    fn outgoing(&self, node: u32) -> impl Iterator<Item = (u32, EdgeType)> + '_ {
+        Snapshot::outgoing(self, node)
+    }
+
+    fn incoming(&self, node: u32) -> impl Iterator<Item = (u32, EdgeType)> + '_ {
+        Snapshot::incoming(self, node)
+    }
+}
+
+/// Check that `count + 1` offsets start at 0, never decrease and end at `end`
+fn check_offsets(
+    bytes: &[u8],
+    section: usize,
+    count: usize,
+    end: usize,
+    name: &str,
+) -> std::result::Result<(), String> {
+    let mut previous = 0;
+    for i in 0..=count {
+        let at = read_u32(bytes, section + i * 4) as usize;
+        if (i == 0 && at != 0) || at < previous || (i == count && at != end) {
+            return Err(format!("{} offset {} is out of order", name, i));
+        }
+        previous = at;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_open_round_trip_and_corruption() {
+        let mut graph = Graph::new();
+        let mut caller = Node::new(
+            "/project/app.py::main",
+            "main",
+            NodeType::Function,
+            "python",
+            "/project/app.py",
+        );
+        caller.line_range = Some((3, 9));
+        caller.status = MigrationStatus::InProgress;
+        let caller = graph.add_node(caller);
+        let callee = graph.add_node(Node::new(
+            "/project/db.py::Store",
+            "Store",
+            NodeType::Class,
+            "python",
+            "/project/db.py",
+        ));
+        let mut external = Node::new(
+            "external::requests",
+            "requests",
+            NodeType::Module,
+            "python",
+            "",
+        );
+        external.external = true;
+        let external = graph.add_node(external);
+        for (to, edge_type) in [(callee, EdgeType::Calls), (external, EdgeType::Imports)] {
+            graph.add_edge(caller, to, Edge::new(edge_type));
+        }
+        graph.set_metadata(GraphMetadata {
+            git_remote: Some("https://example.com/app.git".to_string()),
+            git_commit: Some("0123abc".to_string()),
+            ..GraphMetadata::default()
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("graph.snap");
+        save(&graph, &path).unwrap();
+        let snapshot = Snapshot::open(&path).unwrap();
+        snapshot.verify().unwrap();
+
+        assert_eq!(snapshot.node_count(), 3);
+        assert_eq!(snapshot.edge_count(), 2);
+        let main = snapshot.find("/project/app.py::main").unwrap();
+        let store = snapshot.find("/project/db.py::Store").unwrap();
+        assert_eq!(snapshot.name(main), "main");
+        assert_eq!(snapshot.line_range(main), Some((3, 9)));
+        assert_eq!(snapshot.status(main), MigrationStatus::InProgress);
+        assert_eq!(snapshot.node_type(store), NodeType::Class);
+        assert_eq!(snapshot.line_range(store), None);
+        assert!(snapshot.is_external(snapshot.find("external::requests").unwrap()));
+        assert!(snapshot.find("missing").is_none());
+        assert_eq!(
+            snapshot.incoming(store).collect::<Vec<_>>(),
+            vec![(main, EdgeType::Calls)]
+        );
+        assert_eq!(snapshot.incoming(main).count(), 0);
+        assert_eq!(snapshot.to_graph().fingerprint(), graph.fingerprint());
+        assert_eq!(&snapshot.metadata().unwrap(), graph.metadata());
+
+        drop(snapshot);
+        let mut bytes = std::fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] = 200;
+        std::fs::write(&path, &bytes).unwrap();
+        // Opening reads the header only; verifying finds the broken metadata
+        let corrupt = Snapshot::open(&path).unwrap();
+        assert!(matches!(
+            corrupt.verify(),
+            Err(GraphMigratorError::Parse { .. })
+        ));
+        drop(corrupt);
+        std::fs::write(&path, &bytes[..last]).unwrap();
+        assert!(matches!(
+            Snapshot::open(&path),
+            Err(GraphMigratorError::Parse { .. })
+        ));
+        std::fs::write(&path, b"{}").unwrap();
+        assert!(Snapshot::open(&path).is_err());
+    }
+}
@@ -0,0 +1,1217 @@
+//! Persisted migration state
+//!
+//! The graph itself is re-derived from source on every run; what cannot be
+//! re-derived is what people record about it: migration statuses,
+//! `MigratedTo` links and migration units. Those are kept in the state file (`[output] graph`
+//! in `migrator.toml`, `.migrator/state.json` by default) as [`GraphData`]
+//! JSON and overlaid on each fresh scan by node ID. Effort estimates and
+//! node and edge attributes are kept as well, filling in whatever a rescan
+//! leaves unset.
+//! Saving merges into the file instead of replacing it, so what was recorded
+//! about symbols a scan did not reach is not lost.
+//!
+//! `Superseded` nodes are tombstones: if their source has been deleted, they
+//! are restored from the state file rather than dropped, so their history
+//! and links outlive the code.
+//!
+//! A file moved without `graph-migrator rename` would lose everything
+//! tracked about it, since its node IDs change with its path.
+//! [`TrackedState::follow_moves()`] detects such moves before the state is
+//! applied: a saved file missing from the scan is matched with a new file
+//! that has the same content, going by the hashes saved with the state, or
+//! failing that defines exactly the same symbols, when neither side has
+//! another candidate.
+
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::{
+    AttrValue, Edge, EdgeType, Effort, Graph, GraphData, MigrationStatus, Node, NodeType,
+    StatusChange,
+};
+use crate::journal::{self, Change, JournalEntry};
+use crate::migration;
+use crate::parser;
+use crate::schema::{self, SCHEMA_VERSION};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Everything a state file records beyond the scanned graph
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrackedState {
+    /// Statuses other than `Pending`, keyed by node ID
+    pub statuses: BTreeMap<String, MigrationStatus>,
+    /// Recorded status changes, keyed by node ID
+    pub history: BTreeMap<String, Vec<StatusChange>>,
+    /// `Superseded` nodes, kept even if a rescan no longer finds them
+    pub tombstones: Vec<Node>,
+    /// `(legacy ID, target ID)` of every `MigratedTo` link
+    pub links: Vec<(String, String)>,
+    /// [`NodeType::MigrationUnit`] nodes
+    pub units: Vec<Node>,
+    /// `(member ID, unit ID)` of every `PartOfMigration` edge
+    pub memberships: Vec<(String, String)>,
+    /// Effort estimates, keyed by node ID
+    pub efforts: BTreeMap<String, Effort>,
+    /// Node attributes, keyed by node ID
+    pub attributes: BTreeMap<String, BTreeMap<String, AttrValue>>,
+    /// Edge attributes, keyed by source ID, target ID and edge type
+    pub edge_attributes: BTreeMap<(String, String, EdgeType), BTreeMap<String, AttrValue>>,
+    /// Symbols of each scanned file, keyed by the file part of their IDs
+    /// (see [`TrackedState::follow_moves()`])
+    pub files: BTreeMap<String, BTreeSet<String>>,
+    /// SHA-256 of each scanned file's content when the state was saved,
+    /// keyed like `files`
+    pub hashes: BTreeMap<String, String>,
+}
+
+impl TrackedState {
+    /// Detect files moved since the state was saved and carry their state
+    /// over to the new node IDs
+    ///
+    /// A saved file that `graph` no longer has is taken to have moved to a
+    /// file `graph` has but the state does not, if the new file's content
+    /// hashes the same as the saved one's and no other missing or new file
+    /// does. Files left unmatched are paired the same way by their non-empty
+    /// sets of symbols, so a file edited as it moved is still found.
+    /// New files are read from their nodes' paths to hash them.
+    /// Returns the `(old, new)` file pairs, sorted.
+    pub fn follow_moves(&mut self, graph: &Graph) -> Vec<(String, String)> {
+        let moves = moved_files(&self.files, &self.hashes, graph);
+        for (old, new) in &moves {
+            tracing::info!(from = %old, to = %new, "detected moved file");
+            let symbols = self.files.remove(old).unwrap_or_default();
+            self.files.insert(new.clone(), symbols);
+            if let Some(hash) = self.hashes.remove(old) {
+                self.hashes.insert(new.clone(), hash);
+            }
+            self.remap(&parser::file_move_map(self.ids(), old, new));
+        }
+        moves
+    }
+
+    /// Every node ID the state refers to, sorted
+    pub fn ids(&self) -> Vec<&str> {
+        let mut ids: Vec<&str> = self
+            .statuses
+            .keys()
+            .chain(self.history.keys())
+            .chain(self.efforts.keys())
+            .chain(self.attributes.keys())
+            .map(String::as_str)
+            .chain(self.tombstones.iter().map(|node| node.id.as_str()))
+            .chain(
+                self.links
+                    .iter()
+                    .flat_map(|(l, t)| [l.as_str(), t.as_str()]),
+            )
+            .chain(self.memberships.iter().map(|(member, _)| member.as_str()))
+            .chain(
+                self.edge_attributes
+                    .keys()
+                    .flat_map(|(from, to, _)| [from.as_str(), to.as_str()]),
+            )
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    /// Rename node IDs throughout the state
+    fn remap(&mut self, map: &HashMap<String, String>) {
+        let rename = |id: &mut String| {
+            if let Some(new) = map.get(id.as_str()) {
+                id.clone_from(new);
+            }
+        };
+        fn rekey<V>(entries: &mut BTreeMap<String, V>, map: &HashMap<String, String>) {
+            *entries = std::mem::take(entries)
+                .into_iter()
+                .map(|(id, value)| (map.get(&id).cloned().unwrap_or(id), value))
+                .collect();
+        }
+        rekey(&mut self.statuses, map);
+        rekey(&mut self.history, map);
+        rekey(&mut self.efforts, map);
+        rekey(&mut self.attributes, map);
+        for node in &mut self.tombstones {
+            rename(&mut node.id);
+        }
+        for (legacy, target) in &mut self.links {
+            rename(legacy);
+            rename(target);
+        }
+        for (member, _) in &mut self.memberships {
+            rename(member);
+        }
+        self.edge_attributes = std::mem::take(&mut self.edge_attributes)
+            .into_iter()
+            .map(|((mut from, mut to, edge_type), attributes)| {
+                rename(&mut from);
+                rename(&mut to);
+                ((from, to, edge_type), attributes)
+            })
+            .collect();
+    }
+}
+
+/// `(old, new)` file pairs of the files in `files` that moved, going by
+/// their saved content `hashes` and the symbols `graph` defines (see
+/// [`TrackedState::follow_moves()`])
+fn moved_files(
+    files: &BTreeMap<String, BTreeSet<String>>,
+    hashes: &BTreeMap<String, String>,
+    graph: &Graph,
+) -> Vec<(String, String)> {
+    let scanned = symbols_by_file(graph.nodes());
+    let mut missing: BTreeSet<&str> = files
+        .keys()
+        .filter(|file| !scanned.contains_key(*file))
+        .map(String::as_str)
+        .collect();
+    let mut added: BTreeSet<&str> = scanned
+        .keys()
+        .filter(|file| !files.contains_key(*file))
+        .map(String::as_str)
+        .collect();
+    if missing.is_empty() || added.is_empty() {
+        return Vec::new();
+    }
+
+    let added_hashes = if missing.iter().any(|file| hashes.contains_key(*file)) {
+        file_hashes(graph, |file| added.contains(file))
+    } else {
+        BTreeMap::new()
+    };
+    // Files matched by content first
+    let mut moves = unique_pairs(
+        missing
+            .iter()
+            .filter_map(|&file| Some((hashes.get(file)?, file))),
+        added_hashes
+            .iter()
+            .map(|(file, hash)| (hash, file.as_str())),
+    );
+    for (old, new) in &moves {
+        missing.remove(old.as_str());
+        added.remove(new.as_str());
+    }
+    // Failing that, by their symbols
+    let non_empty = |(symbols, _): &(&BTreeSet<String>, &str)| !symbols.is_empty();
+    moves.extend(unique_pairs(
+        missing
+            .iter()
+            .map(|&file| (&files[file], file))
+            .filter(non_empty),
+        added
+            .iter()
+            .map(|&file| (&scanned[file], file))
+            .filter(non_empty),
+    ));
+    moves.sort();
+    moves
+}
+
+/// `(old, new)` pairs of files with the same key, for keys exactly one old
+/// and one new file have
+fn unique_pairs<'a, K: Ord + 'a>(
+    old: impl Iterator<Item = (&'a K, &'a str)>,
+    new: impl Iterator<Item = (&'a K, &'a str)>,
+) -> Vec<(String, String)> {
+    let mut candidates: BTreeMap<&K, (Vec<&str>, Vec<&str>)> = BTreeMap::new();
+    for (key, file) in old {
+        candidates.entry(key).or_default().0.push(file);
+    }
+    for (key, file) in new {
+        candidates.entry(key).or_default().1.push(file);
+    }
+    candidates
+        .into_values()
+        .filter_map(|(old, new)| match (old.as_slice(), new.as_slice()) {
+            ([old], [new]) => Some((old.to_string(), new.to_string())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// File and symbol parts of the ID of `node`, if it is a project node that
+/// belongs to a file
+fn file_symbol(node: &Node) -> Option<(&str, &str)> {
+    if node.external || matches!(node.node_type, NodeType::File | NodeType::MigrationUnit) {
+        return None;
+    }
+    node.id.split_once("::")
+}
+
+/// Symbol part of each node ID under the file part of the ID, for project
+/// nodes that belong to a file
+fn symbols_by_file<'a>(
+    nodes: impl Iterator<Item = &'a Node>,
+) -> BTreeMap<String, BTreeSet<String>> {
+    let mut files: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for (file, symbol) in nodes.filter_map(file_symbol) {
+        files
+            .entry(file.to_string())
+            .or_default()
+            .insert(symbol.to_string());
+    }
+    files
+}
+
+/// SHA-256 of the content of each file [`symbols_by_file()`] finds in
+/// `graph` and `wanted` accepts, read from the path of one of its nodes;
+/// files that cannot be read are left out
+fn file_hashes(graph: &Graph, wanted: impl Fn(&str) -> bool) -> BTreeMap<String, String> {
+    let mut paths: BTreeMap<&str, &Path> = BTreeMap::new();
+    for node in graph.nodes() {
+        if let Some((file, _)) = file_symbol(node).filter(|(file, _)| wanted(file)) {
+            paths.entry(file).or_insert(&node.file_path);
+        }
+    }
+    paths
+        .into_iter()
+        .filter_map(|(file, path)| {
+            let content = std::fs::read(path).ok()?;
+            Some((file.to_string(), format!("{:x}", Sha256::digest(content))))
+        })
+        .collect()
+}
+
+/// Read the tracked state from the state file at `path`
+///
+/// A missing file means nothing has been tracked yet.
+///
+/// # Errors
+/// Returns an error if the file exists but cannot be read or is not `GraphData` JSON.
+pub fn load(path: &Path) -> Result<TrackedState> {
+    Ok(read_graph(path)?.map_or_else(TrackedState::default, |graph| tracked(&graph)))
+}
+
+/// [`load()`], with absolute-path node IDs made relative to project `root`
+/// in memory, as [`relativize_ids()`] would rewrite them in the file
+///
+/// For commands that only read the state, which leave the file alone.
+///
+/// # Errors
+/// Same as [`load()`], or if two nodes would end up with the same ID.
+pub fn load_relative(path: &Path, root: &Path) -> Result<TrackedState> {
+    let Some(mut graph) = read_graph(path)? else {
+        return Ok(TrackedState::default());
+    };
+    let map = relative_ids(&graph, root);
+    if !map.is_empty() {
+        graph.remap_ids(&map)?;
+    }
+    Ok(tracked(&graph))
+}
+
+/// What the saved `graph` records beyond the scan
+fn tracked(graph: &Graph) -> TrackedState {
+    TrackedState {
+        statuses: graph
+            .nodes()
+            .filter(|node| !node.status.is_pending())
+            .map(|node| (node.id.clone(), node.status))
+            .collect(),
+        history: graph
+            .nodes()
+            .filter(|node| !node.history.is_empty())
+            .map(|node| (node.id.clone(), node.history.clone()))
+            .collect(),
+        tombstones: graph
+            .nodes()
+            .filter(|node| node.status == MigrationStatus::Superseded)
+            .cloned()
+            .collect(),
+        links: migration::links(graph),
+        units: graph
+            .nodes()
+            .filter(|node| node.node_type == NodeType::MigrationUnit)
+            .cloned()
+            .collect(),
+        memberships: migration::memberships(graph),
+        efforts: graph
+            .nodes()
+            .filter_map(|node| Some((node.id.clone(), node.effort?)))
+            .collect(),
+        attributes: graph
+            .nodes()
+            .filter(|node| !node.attributes.is_empty())
+            .map(|node| (node.id.clone(), node.attributes.clone()))
+            .collect(),
+        edge_attributes: graph
+            .edge_endpoints()
+            .filter(|(_, _, edge)| !edge.attributes.is_empty())
+            .filter_map(|(from, to, edge)| {
+                let id = |index| graph.node_weight(index).map(|node| node.id.clone());
+                let key = (id(from)?, id(to)?, edge.edge_type);
+                Some((key, edge.attributes.clone()))
+            })
+            .collect(),
+        files: symbols_by_file(graph.nodes()),
+        hashes: graph.metadata().file_hashes.clone(),
+    }
+}
+
+/// Overlay tracked state on a freshly scanned graph
+///
+/// Tombstones and units missing from the graph are added back first.
+/// Statuses, links and memberships for other IDs no longer in the graph are
+/// skipped, and saved effort estimates and node and edge attributes only
+/// fill in what the scan did not set.
+/// Returns how many statuses, links and memberships were applied.
+pub fn apply(graph: &mut Graph, state: &TrackedState) -> usize {
+    for restored in state.tombstones.iter().chain(&state.units) {
+        if graph.get_by_id(&restored.id).is_none() {
+            graph.add_node(restored.clone());
+        }
+    }
+    let mut applied = apply_statuses(graph, &state.statuses);
+    for (id, history) in &state.history {
+        graph.update_node_by_id(id, |node| node.history.clone_from(history));
+    }
+    for (id, &effort) in &state.efforts {
+        graph.update_node_by_id(id, |node| {
+            node.effort.get_or_insert(effort);
+        });
+    }
+    for (id, attributes) in &state.attributes {
+        graph.update_node_by_id(id, |node| {
+            for (key, value) in attributes {
+                node.attributes
+                    .entry(key.clone())
+                    .or_insert_with(|| value.clone());
+            }
+        });
+    }
+    for ((from, to, edge_type), attributes) in &state.edge_attributes {
+        let (Some(from), Some(to)) = (graph.get_by_id(from), graph.get_by_id(to)) else {
+            continue;
+        };
+        let edge = graph
+            .edges_between(from, to)
+            .find(|(_, edge)| edge.edge_type == *edge_type)
+            .map(|(index, _)| index);
+        if let Some(edge) = edge.and_then(|index| graph.edge_weight_mut(index)) {
+            for (key, value) in attributes {
+                edge.attributes
+                    .entry(key.clone())
+                    .or_insert_with(|| value.clone());
+            }
+        }
+    }
+    for (legacy, target) in &state.links {
+        match migration::link(graph, legacy, target) {
+            Ok(_) => applied += 1,
+            Err(err) => tracing::debug!(%err, "skipping saved link"),
+        }
+    }
+    for (member, unit) in &state.memberships {
+        match migration::assign(graph, member, unit) {
+            Ok(_) => applied += 1,
+            Err(err) => tracing::debug!(%err, "skipping saved unit membership"),
+        }
+    }
+    applied
+}
+
+/// Statuses other than `Pending` recorded in a state file, keyed by node ID
+///
+/// # Errors
+/// Same as [`load()`].
+pub fn load_statuses(path: &Path) -> Result<BTreeMap<String, MigrationStatus>> {
+    Ok(load(path)?.statuses)
+}
+
+/// Set each node's status from `statuses`
+///
+/// IDs no longer in the graph are ignored. Returns how many nodes were updated.
+pub fn apply_statuses(graph: &mut Graph, statuses: &BTreeMap<String, MigrationStatus>) -> usize {
+    statuses
+        .iter()
+        .filter_map(|(id, &status)| graph.update_node_by_id(id, |node| node.status = status))
+        .count()
+}
+
+/// Rename nodes in the state file at `path`, so tracked statuses follow
+/// moved or renamed symbols
+///
+/// The renames are journaled under `rename` (see [`crate::journal`]), so
+/// [`undo_renames()`] can reverse them. A missing file is left alone.
+/// Returns how many nodes were renamed.
+///
+/// # Errors
+/// Returns an error if the file cannot be read or written, the journal
+/// cannot be appended to, or two nodes would end up with the same ID.
+pub fn remap_ids(path: &Path, map: &HashMap<String, String>) -> Result<usize> {
+    remap(path, map, Some(("rename", None)))
+}
+
+/// Reverse the renames of journal entry `undone` in the state file at
+/// `path`, recording it as undoing that entry
+///
+/// Renames change the state file's IDs rather than the scanned graph, so
+/// they are undone here instead of through [`journal::revert()`] and
+/// [`commit_undo()`]. Returns how many nodes were renamed back.
+///
+/// # Errors
+/// Same as [`remap_ids()`].
+pub fn undo_renames(path: &Path, undone: &JournalEntry) -> Result<usize> {
+    let map: HashMap<String, String> = undone
+        .changes
+        .iter()
+        .filter_map(|change| match change {
+            Change::Rename { old, new } => Some((new.clone(), old.clone())),
+            _ => None,
+        })
+        .collect();
+    remap(path, &map, Some(("undo", Some(undone.seq))))
+}
+
+/// Rename nodes in the state file at `path`, journaling the renames under
+/// `journaled` (command and undone entry) if given
+fn remap(
+    path: &Path,
+    map: &HashMap<String, String>,
+    journaled: Option<(&str, Option<u64>)>,
+) -> Result<usize> {
+    let Some(mut graph) = read_graph(path)? else {
+        return Ok(0);
+    };
+    let mut changes: Vec<Change> = map
+        .iter()
+        .filter(|(old, new)| old != new && graph.get_by_id(old).is_some())
+        .map(|(old, new)| Change::Rename {
+            old: old.clone(),
+            new: new.clone(),
+        })
+        .collect();
+    changes.sort_by(|a, b| a.ids().cmp(&b.ids()));
+    let renamed = graph.remap_ids(map)?;
+    if renamed > 0 {
+        write_graph(path, &graph)?;
+        if let Some((command, undoes)) = journaled {
+            append_entry(path, command, undoes, changes)?;
+        }
+    }
+    Ok(renamed)
+}
+
+/// Rewrite the absolute-path node IDs in the state file at `path` relative
+/// to project `root`
+///
+/// State files written before IDs became root-relative (`relative_ids`)
+/// name nodes by canonical absolute path; rewriting them keeps their state
+/// attached to the IDs scans now produce, where [`TrackedState::follow_moves()`]
+/// would have to guess. `root` matches IDs built under either
+/// [`PathResolver`](crate::paths::PathResolver). A missing file, or one
+/// without such IDs, is left alone. Returns how many nodes were renamed.
+///
+/// Commands that save the state run this once before they load it; the
+/// others read it with [`load_relative()`].
+///
+/// # Errors
+/// Same as [`remap_ids()`].
+pub fn relativize_ids(path: &Path, root: &Path) -> Result<usize> {
+    let Some(graph) = read_graph(path)? else {
+        return Ok(0);
+    };
+    let map = relative_ids(&graph, root);
+    if map.is_empty() {
+        return Ok(0);
+    }
+    // A format upgrade, not an edit to undo
+    remap(path, &map, None)
+}
+
+/// New IDs of the nodes of `graph` with absolute-path IDs under `root`
+fn relative_ids(graph: &Graph, root: &Path) -> HashMap<String, String> {
+    let roots = parser::id_roots(root);
+    graph
+        .nodes()
+        .filter_map(|node| Some((node.id.clone(), parser::relative_id(&node.id, &roots)?)))
+        .collect()
+}
+
+/// Write `graph`, statuses included, as the state file at `path`
+///
+/// What the file already records about nodes `graph` lacks is kept: a
+/// symbol the scan did not reach (its file skipped by `[limits]`, excluded,
+/// or deleted) keeps its status, history and links, unless its file moved
+/// (see [`TrackedState::follow_moves()`]). Units missing from `graph` were
+/// removed and are not kept.
+///
+/// Missing parent directories are created. The file is written to a
+/// temporary sibling and renamed into place, so readers never see a
+/// partial file. If the file being replaced was written by an older
+/// schema version, it is first copied to [`backup_path()`] (unless a
+/// backup of that version already exists), so a tool upgrade never
+/// destroys the only copy of the tracked state.
+///
+/// # Errors
+/// Returns an error if the existing file cannot be read, or the directory
+/// or file cannot be written.
+pub fn save_graph(path: &Path, graph: &Graph) -> Result<()> {
+    let saved = read_graph(path)?;
+    write_graph(path, &merged(saved.as_ref(), graph))
+}
+
+/// `graph` plus the tracked nodes of the `saved` state that it lacks, with
+/// their links and unit memberships
+///
+/// The content hashes of `graph`'s files are recorded in its metadata, for
+/// [`TrackedState::follow_moves()`] to find the files again once moved.
+fn merged(saved: Option<&Graph>, graph: &Graph) -> Graph {
+    let mut merged = graph.clone();
+    let mut metadata = graph.metadata().clone();
+    metadata.file_hashes = file_hashes(graph, |_| true);
+    merged.set_metadata(metadata);
+    let Some(saved) = saved else {
+        return merged;
+    };
+    let saved_files = symbols_by_file(saved.nodes());
+    let moved: BTreeSet<String> = moved_files(&saved_files, &saved.metadata().file_hashes, graph)
+        .into_iter()
+        .map(|(old, _)| old)
+        .collect();
+    let tracked_edge = |edge: &Edge| {
+        matches!(
+            edge.edge_type,
+            EdgeType::MigratedTo | EdgeType::PartOfMigration
+        )
+    };
+    let mut linked: BTreeSet<&str> = BTreeSet::new();
+    for (from, to, _) in saved
+        .edge_endpoints()
+        .filter(|(_, _, edge)| tracked_edge(edge))
+    {
+        linked.extend(saved.node_weight(from).map(|node| node.id.as_str()));
+        linked.extend(saved.node_weight(to).map(|node| node.id.as_str()));
+    }
+    let mut kept = BTreeSet::new();
+    for node in saved.nodes() {
+        let tracked = !node.status.is_pending()
+            || !node.history.is_empty()
+            || linked.contains(node.id.as_str());
+        let file_moved = node
+            .id
+            .split_once("::")
+            .is_some_and(|(file, _)| moved.contains(file));
+        if tracked
+            && !file_moved
+            && node.node_type != NodeType::MigrationUnit
+            && graph.get_by_id(&node.id).is_none()
+        {
+            merged.add_node(node.clone());
+            kept.insert(node.id.as_str());
+        }
+    }
+    for (from, to, edge) in saved
+        .edge_endpoints()
+        .filter(|(_, _, edge)| tracked_edge(edge))
+    {
+        let (Some(from), Some(to)) = (saved.node_weight(from), saved.node_weight(to)) else {
+            continue;
+        };
+        if !kept.contains(from.id.as_str()) && !kept.contains(to.id.as_str()) {
+            continue;
+        }
+        if let (Some(from), Some(to)) = (merged.get_by_id(&from.id), merged.get_by_id(&to.id)) {
+            merged.add_edge(from, to, edge.clone());
+        }
+    }
+    merged
+}
+
+/// Write `graph` as the state file at `path` as it is, replacing the file
+fn write_graph(path: &Path, graph: &Graph) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(|e| GraphMigratorError::io(dir, e))?;
+    }
+    back_up_older_version(path)?;
+    let json = serde_json::to_string_pretty(&GraphData::from(graph))
+        .map_err(|e| GraphMigratorError::Export(e.to_string()))?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).map_err(|e| GraphMigratorError::io(&tmp, e))?;
+    std::fs::rename(&tmp, path).map_err(|e| GraphMigratorError::io(path, e))
+}
+
+/// [`save_graph()`], recording the mutations since the state file was last
+/// written in its journal (see [`crate::journal`])
+///
+/// `command` names what made them (`set-status`). Returns the sequence
+/// number of the new journal entry, or `None` if the tracked state did not
+/// change and nothing was recorded.
+///
+/// # Errors
+/// Returns an error if the state file cannot be read or written, or the
+/// journal cannot be read or appended to.
+pub fn commit(path: &Path, graph: &Graph, command: &str) -> Result<Option<u64>> {
+    record(path, graph, command, None)
+}
+
+/// [`commit()`] the result of [`journal::revert()`]ing `undone`, recording
+/// it as undoing that entry
+///
+/// # Errors
+/// Same as [`commit()`].
+pub fn commit_undo(path: &Path, graph: &Graph, undone: &JournalEntry) -> Result<Option<u64>> {
+    record(path, graph, "undo", Some(undone.seq))
+}
+
+/// Journal of the state file at `path`: `state.json` keeps its journal in
+/// `state.journal.jsonl`
+pub fn journal_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{}.journal.jsonl", stem))
+}
+
+fn record(path: &Path, graph: &Graph, command: &str, undoes: Option<u64>) -> Result<Option<u64>> {
+    let before = read_graph(path)?;
+    let graph = merged(before.as_ref(), graph);
+    let changes = journal::diff(&before.unwrap_or_default(), &graph);
+    // State first: if the append fails, the change is kept without an undo
+    // step, rather than journaled without having happened
+    write_graph(path, &graph)?;
+    if changes.is_empty() && undoes.is_none() {
+        return Ok(None);
+    }
+    append_entry(path, command, undoes, changes).map(Some)
+}
+
+/// Append an entry with the next sequence number to the journal of the
+/// state file at `path`, returning that number
+fn append_entry(
+    path: &Path,
+    command: &str,
+    undoes: Option<u64>,
+    changes: Vec<Change>,
+) -> Result<u64> {
+    let journal = journal_path(path);
+    let next = journal::load(&journal)?
+        .last()
+        .map_or(1, |entry| entry.seq + 1);
+    let at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    journal::append(
+        &journal,
+        &JournalEntry {
+            seq: next,
+            at,
+            command: command.to_string(),
+            undoes,
+            changes,
+        },
+    )?;
+    Ok(next)
+}
+
+/// Where [`save_graph()`] keeps a state file of schema version `version`
+/// before rewriting it: `state.json` becomes `state.v0.json`
+pub fn backup_path(path: &Path, version: u32) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{}.v{}.json", stem, version))
+}
+
+/// Copy the file at `path` to its [`backup_path()`] if it has an older
+/// schema version than this build writes
+fn back_up_older_version(path: &Path) -> Result<()> {
+    let Ok(text) = std::fs::read_to_string(path) else {
+        return Ok(());
+    };
+    let version = serde_json::from_str(&text)
+        .ok()
+        .and_then(|document| schema::version_of(&document).ok());
+    let Some(version) = version.filter(|&v| v < SCHEMA_VERSION) else {
+        return Ok(());
+    };
+    let backup = backup_path(path, version);
+    if !backup.exists() {
+        std::fs::write(&backup, text).map_err(|e| GraphMigratorError::io(&backup, e))?;
+        tracing::info!(
+            backup = %backup.display(),
+            version,
+            "kept a copy of the state file before upgrading it"
+        );
+    }
+    Ok(())
+}
+
+/// The graph stored at `path`, or `None` if there is no file
+fn read_graph(path: &Path) -> Result<Option<Graph>> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let text = std::fs::read_to_string(path).map_err(|e| GraphMigratorError::io(path, e))?;
+    let data = GraphData::from_json(&text).map_err(|e| match e {
+        GraphMigratorError::InvalidGraph(detail) => GraphMigratorError::parse(path, detail),
+        other => other,
+    })?;
+    Ok(Some(Graph::try_from(data)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Node, NodeType};
+
+    #[test]
+    fn test_load_and_apply_statuses() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        assert!(load_statuses(&path).unwrap().is_empty());
+
+        let mut saved = Graph::new();
+        for (id, status) in [
+            ("a.py::old", MigrationStatus::Migrated),
+            ("a.py::gone", MigrationStatus::Blocked),
+            ("a.py::new", MigrationStatus::Pending),
+        ] {
+            let mut node = Node::new(id, id, NodeType::Function, "python", "a.py");
+            node.status = status;
+            saved.add_node(node);
+        }
+        std::fs::write(
+            &path,
+            serde_json::to_string(&GraphData::from(&saved)).unwrap(),
+        )
+        .unwrap();
+
+        let statuses = load_statuses(&path).unwrap();
+        assert_eq!(statuses.len(), 2);
+
+        // A moved symbol keeps its status
+        let moved = HashMap::from([("a.py::old".to_string(), "b.py::old".to_string())]);
+        assert_eq!(remap_ids(&path, &moved).unwrap(), 1);
+        let moved_statuses = load_statuses(&path).unwrap();
+        assert_eq!(moved_statuses["b.py::old"], MigrationStatus::Migrated);
+        assert!(!moved_statuses.contains_key("a.py::old"));
+
+        let mut graph = Graph::new();
+        for id in ["a.py::old", "a.py::new"] {
+            graph.add_node(Node::new(id, id, NodeType::Function, "python", "a.py"));
+        }
+        assert_eq!(apply_statuses(&mut graph, &statuses), 1);
+        let old = graph.find_node_by_id("a.py::old").unwrap();
+        assert_eq!(
+            graph.node_weight(old).unwrap().status,
+            MigrationStatus::Migrated
+        );
+
+        // Round trip through save_graph, creating the directory
+        let nested = dir.path().join(".migrator/state.json");
+        save_graph(&nested, &graph).unwrap();
+        assert_eq!(load_statuses(&nested).unwrap().len(), 1);
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(matches!(
+            load_statuses(&path),
+            Err(GraphMigratorError::Parse { .. })
+        ));
+    }
+
+    #[test]
+    fn test_links_survive_rescan() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        let scan = || {
+            let mut graph = Graph::new();
+            for id in ["old.py::f", "new.py::f", "new.py::g"] {
+                graph.add_node(Node::new(id, id, NodeType::Function, "python", "x.py"));
+            }
+            graph
+        };
+
+        let mut graph = scan();
+        migration::link(&mut graph, "old.py::f", "new.py::f").unwrap();
+        migration::link(&mut graph, "old.py::f", "new.py::g").unwrap();
+        graph.update_node_by_id("old.py::f", |n| n.status = MigrationStatus::Superseded);
+        migration::create_unit(&mut graph, "unit::api", "api", &["new.py::f", "new.py::g"])
+            .unwrap();
+        save_graph(&path, &graph).unwrap();
+
+        let state = load(&path).unwrap();
+        assert_eq!(state.links.len(), 2);
+        assert_eq!(state.units.len(), 1);
+        assert_eq!(state.memberships.len(), 2);
+
+        // new.py::g was deleted since; its link is dropped
+        let mut rescanned = scan();
+        let g = rescanned.get_by_id("new.py::g").unwrap();
+        rescanned.remove_node(g);
+        assert_eq!(apply(&mut rescanned, &state), 3);
+        assert_eq!(
+            migration::links(&rescanned),
+            vec![("old.py::f".to_string(), "new.py::f".to_string())]
+        );
+        assert_eq!(
+            migration::unit_members(&rescanned, "unit::api"),
+            vec!["new.py::f"]
+        );
+    }
+
+    #[test]
+    fn test_follow_moves_carries_state_to_new_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        let scan = |files: &[(&str, &[&str])]| {
+            let mut graph = Graph::new();
+            for (file, symbols) in files {
+                for symbol in *symbols {
+                    let id = format!("{}::{}", file, symbol);
+                    graph.add_node(Node::new(&id, *symbol, NodeType::Function, "python", *file));
+                }
+            }
+            graph
+        };
+
+        let mut graph = scan(&[
+            ("old.py", &["f", "g"]),
+            ("a.py", &["main"]),
+            ("b.py", &["main"]),
+        ]);
+        graph.update_node_by_id("old.py::f", |n| n.status = MigrationStatus::Migrated);
+        graph.update_node_by_id("a.py::main", |n| n.status = MigrationStatus::Blocked);
+        let effort = Effort {
+            lines: 3,
+            branches: 1,
+            fan_in: 0,
+            fan_out: 0,
+            score: 1.0,
+        };
+        graph.update_node_by_id("old.py::g", |n| {
+            n.effort = Some(effort);
+            n.attributes.insert("team".to_string(), "billing".into());
+        });
+        migration::create_unit(&mut graph, "unit::api", "api", &["old.py::g"]).unwrap();
+        let mut call = Edge::new(EdgeType::Calls);
+        call.attributes.insert("weight".to_string(), 2.into());
+        let (f, g) = (
+            graph.get_by_id("old.py::f").unwrap(),
+            graph.get_by_id("old.py::g").unwrap(),
+        );
+        graph.add_edge(f, g, call);
+        save_graph(&path, &graph).unwrap();
+
+        // old.py moved to pkg/new.py; a.py and b.py both moved, ambiguously
+        let mut rescanned = scan(&[
+            ("pkg/new.py", &["f", "g"]),
+            ("c.py", &["main"]),
+            ("d.py", &["main"]),
+        ]);
+        let (f, g) = (
+            rescanned.get_by_id("pkg/new.py::f").unwrap(),
+            rescanned.get_by_id("pkg/new.py::g").unwrap(),
+        );
+        rescanned.add_edge(f, g, Edge::new(EdgeType::Calls));
+        let mut state = load(&path).unwrap();
+        let saved = state.clone();
+        assert_eq!(
+            state.follow_moves(&rescanned),
+            vec![("old.py".to_string(), "pkg/new.py".to_string())]
+        );
+        assert_ne!(state, saved);
+        assert_eq!(load(&path).unwrap(), saved);
+        assert_eq!(apply(&mut rescanned, &state), 2);
+
+        let node = |id| {
+            rescanned
+                .node_weight(rescanned.get_by_id(id).unwrap())
+                .unwrap()
+        };
+        assert_eq!(node("pkg/new.py::f").status, MigrationStatus::Migrated);
+        assert_eq!(node("pkg/new.py::g").effort, Some(effort));
+        assert_eq!(node("pkg/new.py::g").attributes["team"], "billing".into());
+        let (_, call) = rescanned.edges_between(f, g).next().unwrap();
+        assert_eq!(call.attributes["weight"], 2.into());
+        assert_eq!(node("c.py::main").status, MigrationStatus::Pending);
+        assert_eq!(
+            migration::unit_members(&rescanned, "unit::api"),
+            vec!["pkg/new.py::g"]
+        );
+    }
+
+    #[test]
+    fn test_follow_moves_matches_file_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        let scan = |files: &[&str]| {
+            let mut graph = Graph::new();
+            for file in files {
+                let id = format!("{}::main", file);
+                let source = dir.path().join(file);
+                graph.add_node(Node::new(id, "main", NodeType::Function, "python", source));
+            }
+            graph
+        };
+
+        // Same symbols, different content
+        std::fs::write(dir.path().join("a.py"), "def main():\n    return 1\n").unwrap();
+        std::fs::write(dir.path().join("b.py"), "def main():\n    return 2\n").unwrap();
+        let mut graph = scan(&["a.py", "b.py"]);
+        graph.update_node_by_id("a.py::main", |n| n.status = MigrationStatus::Blocked);
+        save_graph(&path, &graph).unwrap();
+        let mut state = load(&path).unwrap();
+        assert_eq!(state.hashes.len(), 2);
+
+        std::fs::rename(dir.path().join("a.py"), dir.path().join("d.py")).unwrap();
+        std::fs::rename(dir.path().join("b.py"), dir.path().join("c.py")).unwrap();
+        let mut rescanned = scan(&["c.py", "d.py"]);
+        assert_eq!(
+            state.follow_moves(&rescanned),
+            vec![
+                ("a.py".to_string(), "d.py".to_string()),
+                ("b.py".to_string(), "c.py".to_string()),
+            ]
+        );
+        apply(&mut rescanned, &state);
+        let main = rescanned.get_by_id("d.py::main").unwrap();
+        assert_eq!(
+            rescanned.node_weight(main).unwrap().status,
+            MigrationStatus::Blocked
+        );
+
+        // Saving keeps the state with the moved files, not the old ones
+        save_graph(&path, &rescanned).unwrap();
+        let saved = load(&path).unwrap();
+        assert_eq!(saved.statuses.keys().collect::<Vec<_>>(), ["d.py::main"]);
+        assert_eq!(saved.hashes.keys().collect::<Vec<_>>(), ["c.py", "d.py"]);
+    }
+
+    #[test]
+    fn test_tombstones_outlive_their_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        let mut graph = Graph::new();
+        for id in ["old.py::f", "new.py::f"] {
+            graph.add_node(Node::new(id, id, NodeType::Function, "python", "x.py"));
+        }
+        migration::link(&mut graph, "old.py::f", "new.py::f").unwrap();
+        migration::transition_at(&mut graph, "old.py::f", MigrationStatus::Superseded, 7).unwrap();
+        save_graph(&path, &graph).unwrap();
+
+        // old.py was deleted; only new.py::f is scanned
+        let mut rescanned = Graph::new();
+        rescanned.add_node(Node::new(
+            "new.py::f",
+            "f",
+            NodeType::Function,
+            "python",
+            "x.py",
+        ));
+        apply(&mut rescanned, &load(&path).unwrap());
+
+        let old = rescanned.get_by_id("old.py::f").unwrap();
+        let node = rescanned.node_weight(old).unwrap();
+        assert_eq!(node.status, MigrationStatus::Superseded);
+        assert_eq!(node.history.len(), 1);
+        assert_eq!(node.history[0].at, 7);
+        assert_eq!(migration::links(&rescanned).len(), 1);
+    }
+
+    #[test]
+    fn test_upgraded_state_keeps_a_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        // Written before documents carried a schema version
+        let legacy = r#"{"nodes": [{"id": "a.py::f", "name": "f", "node_type": "Function",
+            "language": "python", "file_path": "a.py", "line_range": null,
+            "status": "Migrated"}], "edges": []}"#;
+        std::fs::write(&path, legacy).unwrap();
+
+        let state = load(&path).unwrap();
+        assert_eq!(state.statuses["a.py::f"], MigrationStatus::Migrated);
+
+        let graph = read_graph(&path).unwrap().unwrap();
+        save_graph(&path, &graph).unwrap();
+        let backup = backup_path(&path, 0);
+        assert_eq!(backup, dir.path().join("state.v0.json"));
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), legacy);
+        let saved = std::fs::read_to_string(&path).unwrap();
+        let saved: serde_json::Value = serde_json::from_str(&saved).unwrap();
+        assert_eq!(schema::version_of(&saved).unwrap(), SCHEMA_VERSION);
+
+        // Current files are not backed up again
+        save_graph(&path, &graph).unwrap();
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+        assert_eq!(load(&path).unwrap().statuses.len(), 1);
+    }
+
+    #[test]
+    fn test_absolute_ids_are_made_relative() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        let root = std::fs::canonicalize(dir.path()).unwrap();
+        // Written with canonical absolute IDs; both files define only `main`
+        let mut graph = Graph::new();
+        for file in ["x.py", "y.py"] {
+            let file = root.join(file);
+            let id = format!("{}::main", file.display());
+            graph.add_node(Node::new(&id, "main", NodeType::Function, "python", file));
+        }
+        let x = format!("{}::main", root.join("x.py").display());
+        migration::transition(&mut graph, &x, MigrationStatus::Migrated).unwrap();
+        save_graph(&path, &graph).unwrap();
+
+        // Reading relativizes in memory only
+        let written = std::fs::read(&path).unwrap();
+        let state = load_relative(&path, dir.path()).unwrap();
+        assert_eq!(state.statuses["x.py::main"], MigrationStatus::Migrated);
+        assert!(state.files.contains_key("x.py"));
+        assert_eq!(std::fs::read(&path).unwrap(), written);
+
+        assert_eq!(relativize_ids(&path, dir.path()).unwrap(), 2);
+        let state = load(&path).unwrap();
+        assert_eq!(state.statuses.len(), 1);
+        assert_eq!(state.statuses["x.py::main"], MigrationStatus::Migrated);
+        assert_eq!(relativize_ids(&path, dir.path()).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_saving_keeps_state_of_unscanned_nodes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        let scan = |files: &[&str]| {
+            let mut graph = Graph::new();
+            for file in files {
+                let id = format!("{}::f", file);
+                graph.add_node(Node::new(&id, "f", NodeType::Function, "python", *file));
+            }
+            graph
+        };
+        let mut graph = scan(&["a.py", "b.py", "c.py"]);
+        migration::transition(&mut graph, "b.py::f", MigrationStatus::InProgress).unwrap();
+        migration::link(&mut graph, "b.py::f", "c.py::f").unwrap();
+        migration::create_unit(&mut graph, "unit::b", "b", &["b.py::f"]).unwrap();
+        save_graph(&path, &graph).unwrap();
+
+        // a.py and b.py are skipped by the next scan; unit::b was removed
+        let mut rescanned = scan(&["c.py"]);
+        migration::transition(&mut rescanned, "c.py::f", MigrationStatus::InProgress).unwrap();
+        save_graph(&path, &rescanned).unwrap();
+        let state = load(&path).unwrap();
+        assert_eq!(state.statuses["b.py::f"], MigrationStatus::InProgress);
+        assert_eq!(state.history["b.py::f"].len(), 1);
+        assert_eq!(state.statuses["c.py::f"], MigrationStatus::InProgress);
+        assert_eq!(
+            state.links,
+            [("b.py::f".to_string(), "c.py::f".to_string())]
+        );
+        assert!(state.units.is_empty());
+        assert!(!state.files.contains_key("a.py"));
+
+        // Once b.py is scanned again, its state applies as before
+        let mut graph = scan(&["a.py", "b.py", "c.py"]);
+        apply(&mut graph, &state);
+        let b = graph.get_by_id("b.py::f").unwrap();
+        assert_eq!(
+            graph.node_weight(b).unwrap().status,
+            MigrationStatus::InProgress
+        );
+
+        // A moved file's state is not kept under its old IDs
+        let moved = scan(&["c.py", "d.py"]);
+        save_graph(&path, &moved).unwrap();
+        assert!(!load(&path).unwrap().statuses.contains_key("b.py::f"));
+    }
+
+    #[test]
+    fn test_commits_are_journaled_and_undone() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        let mut graph = Graph::new();
+        for id in ["old.py::f", "old.py::g", "new.py::f"] {
+            let (file, name) = id.split_once("::").unwrap();
+            graph.add_node(Node::new(id, name, NodeType::Function, "python", file));
+        }
+        assert_eq!(commit(&path, &graph, "scan").unwrap(), None);
+
+        migration::transition(&mut graph, "old.py::f", MigrationStatus::InProgress).unwrap();
+        migration::link(&mut graph, "old.py::f", "new.py::f").unwrap();
+        assert_eq!(commit(&path, &graph, "link").unwrap(), Some(1));
+        migration::create_unit(&mut graph, "unit::old", "old", &["old.py::g"]).unwrap();
+        migration::transition_all(
+            &mut graph,
+            &["old.py::f", "old.py::g"],
+            MigrationStatus::Migrated,
+        )
+        .unwrap();
+        assert_eq!(commit(&path, &graph, "set-status").unwrap(), Some(2));
+
+        let entries = journal::load(&journal_path(&path)).unwrap();
+        assert_eq!(journal_path(&path), dir.path().join("state.journal.jsonl"));
+        assert_eq!(
+            entries[1]
+                .changes
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "created unit unit::old",
+                "old.py::f: InProgress -> Migrated",
+                "old.py::g: Pending -> Migrated",
+                "assigned old.py::g to unit::old",
+            ]
+        );
+        assert_eq!(journal::history(&entries, "old.py::f").len(), 3);
+
+        // Undo the bulk change, then the link; an undo is never undone
+        for expected in [2, 1] {
+            let entries = journal::load(&journal_path(&path)).unwrap();
+            let last = journal::last_undoable(&entries).unwrap();
+            assert_eq!(last.seq, expected);
+            journal::revert(&mut graph, last).unwrap();
+            commit_undo(&path, &graph, last).unwrap();
+        }
+        let entries = journal::load(&journal_path(&path)).unwrap();
+        assert!(journal::last_undoable(&entries).is_none());
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[2].undoes, Some(2));
+
+        let state = load(&path).unwrap();
+        assert!(state.statuses.is_empty());
+        assert!(state.history.is_empty());
+        assert!(state.links.is_empty());
+        assert!(state.units.is_empty());
+    }
+
+    #[test]
+    fn test_renames_are_journaled_and_undone() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        let mut graph = Graph::new();
+        graph.add_node(Node::new(
+            "a.py::f",
+            "f",
+            NodeType::Function,
+            "python",
+            "a.py",
+        ));
+        migration::transition(&mut graph, "a.py::f", MigrationStatus::InProgress).unwrap();
+        commit(&path, &graph, "set-status").unwrap();
+
+        // Status changes read from annotations are never undone
+        migration::transition(&mut graph, "a.py::f", MigrationStatus::Migrated).unwrap();
+        commit(&path, &graph, journal::ANNOTATIONS_COMMAND).unwrap();
+
+        let map = HashMap::from([("a.py::f".to_string(), "b.py::f".to_string())]);
+        assert_eq!(remap_ids(&path, &map).unwrap(), 1);
+        let entries = journal::load(&journal_path(&path)).unwrap();
+        let last = journal::last_undoable(&entries).unwrap();
+        assert_eq!(last.command, "rename");
+        assert_eq!(last.changes[0].to_string(), "renamed a.py::f -> b.py::f");
+
+        assert_eq!(undo_renames(&path, last).unwrap(), 1);
+        assert_eq!(
+            load(&path).unwrap().statuses["a.py::f"],
+            MigrationStatus::Migrated
+        );
+        let entries = journal::load(&journal_path(&path)).unwrap();
+        assert_eq!(entries.last().unwrap().undoes, Some(last.seq));
+        assert_eq!(
+            journal::last_undoable(&entries).unwrap().command,
+            "set-status"
+        );
+    }
+}
@@ -0,0 +1,32 @@
+//! Graph builders shared by the unit tests
+
+use crate::graph::{Edge, EdgeType, Graph, Node, NodeType};
+use petgraph::stable_graph::NodeIndex;
+
+/// Add function `name` of `m.py`, with ID `m.py::name`
+pub fn add(graph: &mut Graph, name: &str) -> NodeIndex {
+    add_node(graph, &format!("m.py::{}", name), NodeType::Function)
+}
+
+/// Add function `name` of `file`, with ID `file::name`
+pub fn function(graph: &mut Graph, file: &str, name: &str) -> NodeIndex {
+    add_node(graph, &format!("{}::{}", file, name), NodeType::Function)
+}
+
+/// Add a Python node of `node_type` with ID `id`, given as `file::name`
+pub fn add_node(graph: &mut Graph, id: &str, node_type: NodeType) -> NodeIndex {
+    let (file, name) = id.split_once("::").expect("test IDs are file::name");
+    graph.add_node(Node::new(id, name, node_type, "python", file))
+}
+
+/// Add an edge of `edge_type` from `from` to `to`
+pub fn link(graph: &mut Graph, from: NodeIndex, to: NodeIndex, edge_type: EdgeType) {
+    graph.add_edge(from, to, Edge::new(edge_type));
+}
+
+/// [`link()`] between the nodes with IDs `from` and `to`
+pub fn link_ids(graph: &mut Graph, from: &str, to: &str, edge_type: EdgeType) {
+    let from = graph.get_by_id(from).expect("source node exists");
+    let to = graph.get_by_id(to).expect("target node exists");
+    link(graph, from, to, edge_type);
+}
@@ -0,0 +1,215 @@
+//! Which tests guard which symbols
+//!
+//! Test functions are found by pytest's default conventions: functions named
+//! `test*` in files named `test_*.py` or `*_test.py`, or in any file below a
+//! `tests/` or `test/` directory. Files are judged by the path in the node
+//! ID, which directory scans make relative to the project root, so a
+//! project checked out below a `tests/` directory is not all test code.
+//! [`link_tests()`] adds a
+//! [`EdgeType::Tests`] edge from each test to every project symbol it calls
+//! outside the test files, and [`tests_for()`] answers which tests exercise
+//! a symbol, directly or through the code that calls it.
+//!
+//! Only top-level test functions are detected; test methods of `Test*`
+//! classes are not graph nodes.
+
+use crate::error::{GraphMigratorError, Result};
+use crate::graph::{Direction, Edge, EdgeType, Graph, Node, NodeType};
+use crate::queries;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Tests that exercise a symbol
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GuardingTests {
+    /// Tests that call the symbol itself, sorted
+    pub direct: Vec<String>,
+    /// Tests that only reach it through other symbols, sorted
+    pub transitive: Vec<String>,
+}
+
+impl GuardingTests {
+    /// Whether no test reaches the symbol
+    pub fn is_empty(&self) -> bool {
+        self.direct.is_empty() && self.transitive.is_empty()
+    }
+}
+
+/// Whether pytest would collect tests from `path`
+pub fn is_test_file(path: &Path) -> bool {
+    let in_test_dir = path
+        .parent()
+        .into_iter()
+        .flat_map(Path::components)
+        .any(|part| matches!(part.as_os_str().to_str(), Some("tests" | "test")));
+    let named_as_test = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .is_some_and(|stem| stem.starts_with("test_") || stem.ends_with("_test"));
+    let python = path.extension().is_some_and(|ext| ext == "py");
+    python && (in_test_dir || named_as_test)
+}
+
+/// Whether `node` is a test function
+pub fn is_test(node: &Node) -> bool {
+    !node.external
+        && node.node_type == NodeType::Function
+        && node.name.starts_with("test")
+        && in_test_file(node)
+}
+
+/// Whether the file in `node`'s ID is a test file
+fn in_test_file(node: &Node) -> bool {
+    node.id_parts()
+        .is_some_and(|(file, _)| is_test_file(Path::new(file)))
+}
+
+/// Add a `Tests` edge from every test to each project symbol outside test
+/// files that it calls
+///
+/// Existing `Tests` edges are kept, so running the pass twice adds nothing.
+/// Returns the number of edges added.
+pub fn link_tests(graph: &mut Graph) -> usize {
+    let mut links = BTreeSet::new();
+    for (from, to, edge) in graph.edge_endpoints() {
+        if edge.edge_type != EdgeType::Calls || from == to {
+            continue;
+        }
+        let (Some(test), Some(target)) = (graph.node_weight(from), graph.node_weight(to)) else {
+            continue;
+        };
+        if is_test(test) && !target.external && !in_test_file(target) {
+            links.insert((from, to));
+        }
+    }
+
+    let mut added = 0;
+    for (from, to) in links {
+        let linked = graph
+            .edges_between(from, to)
+            .any(|(_, edge)| edge.edge_type == EdgeType::Tests);
+        if !linked {
+            graph.add_edge(from, to, Edge::new(EdgeType::Tests));
+            added += 1;
+        }
+    }
+    tracing::debug!(added, "linked tests");
+    added
+}
+
+/// Tests that exercise `id`: those with a `Tests` edge to it, and those with
+/// a `Tests` edge to anything that transitively depends on it
+///
+/// Run [`link_tests()`] first.
+///
+/// # Errors
+/// Returns [`GraphMigratorError::NodeNotFound`] if `id` is not in the graph.
+///
+/// # Example
+/// ```
+/// use graph_migrator_core::graph::{Edge, EdgeType};
+/// use graph_migrator_core::{testmap, Graph, Node, NodeType};
+///
+/// let mut graph = Graph::new();
+/// let f = graph.add_node(Node::new("app.py::f", "f", NodeType::Function, "python", "app.py"));
+/// let test = graph.add_node(Node::new(
+///     "tests/test_app.py::test_f", "test_f", NodeType::Function, "python", "tests/test_app.py",
+/// ));
+/// graph.add_edge(test, f, Edge::new(EdgeType::Calls));
+///
+/// testmap::link_tests(&mut graph);
+/// let guarding = testmap::tests_for(&graph, "app.py::f").unwrap();
+/// assert_eq!(guarding.direct, vec!["tests/test_app.py::test_f".to_string()]);
+/// ```
+pub fn tests_for(graph: &Graph, id: &str) -> Result<GuardingTests> {
+    let target = graph
+        .get_by_id(id)
+        .ok_or_else(|| GraphMigratorError::NodeNotFound(id.to_string()))?;
+    let testers = |idx| {
+        graph
+            .edges_of(idx)
+            .filter(move |(_, _, to, edge)| *to == idx && edge.edge_type == EdgeType::Tests)
+            .filter_map(|(_, from, _, _)| graph.node_weight(from))
+            .map(|node| node.id.clone())
+    };
+
+    let direct: BTreeSet<String> = testers(target).collect();
+    let mut transitive = BTreeSet::new();
+    for dependent in queries::closure(graph, id, Direction::Incoming, &[])? {
+        if let Some(idx) = graph.get_by_id(&dependent) {
+            transitive.extend(testers(idx).filter(|test| !direct.contains(test)));
+        }
+    }
+    Ok(GuardingTests {
+        direct: direct.into_iter().collect(),
+        transitive: transitive.into_iter().collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{function, link};
+
+    #[test]
+    fn test_detects_pytest_conventions() {
+        assert!(is_test_file(Path::new("tests/unit/helpers.py")));
+        assert!(is_test_file(Path::new("pkg/test_models.py")));
+        assert!(is_test_file(Path::new("pkg/models_test.py")));
+        assert!(!is_test_file(Path::new("pkg/testing.py")));
+        assert!(!is_test_file(Path::new("pkg/contest.py")));
+        assert!(!is_test_file(Path::new("tests/fixtures.json")));
+    }
+
+    #[test]
+    fn test_link_tests_and_tests_for() {
+        let mut graph = Graph::new();
+        let save = function(&mut graph, "app/db.py", "save");
+        let handler = function(&mut graph, "app/api.py", "handler");
+        let test_save = function(&mut graph, "tests/test_db.py", "test_save");
+        let test_handler = function(&mut graph, "tests/test_api.py", "test_handler");
+        let fixture = function(&mut graph, "tests/test_api.py", "make_request");
+        link(&mut graph, handler, save, EdgeType::Calls);
+        link(&mut graph, test_save, save, EdgeType::Calls);
+        link(&mut graph, test_handler, handler, EdgeType::Calls);
+        link(&mut graph, test_handler, fixture, EdgeType::Calls);
+
+        // The helper is test code, not a symbol under test
+        assert_eq!(link_tests(&mut graph), 2);
+        assert_eq!(link_tests(&mut graph), 0);
+
+        let guarding = tests_for(&graph, "app/db.py::save").unwrap();
+        assert_eq!(
+            guarding.direct,
+            vec!["tests/test_db.py::test_save".to_string()]
+        );
+        assert_eq!(
+            guarding.transitive,
+            vec!["tests/test_api.py::test_handler".to_string()]
+        );
+        assert!(tests_for(&graph, "tests/test_api.py::make_request")
+            .unwrap()
+            .is_empty());
+        assert!(tests_for(&graph, "missing").is_err());
+    }
+
+    #[test]
+    fn test_checkout_below_tests_directory() {
+        let mut graph = Graph::new();
+        let mut checked_out = |file: &str, name: &str| {
+            graph.add_node(Node::new(
+                format!("{}::{}", file, name),
+                name,
+                NodeType::Function,
+                "python",
+                format!("/ci/tests/proj/{}", file),
+            ))
+        };
+        let save = checked_out("app/db.py", "save");
+        let test_save = checked_out("test_db.py", "test_save");
+        link(&mut graph, test_save, save, EdgeType::Calls);
+
+        assert!(!is_test(graph.node_weight(save).unwrap()));
+        assert_eq!(link_tests(&mut graph), 1);
+    }
+}
@@ -0,0 +1,293 @@
+//! Read-only access shared by every graph representation
+//!
+//! [`Graph`] holds everything in memory; `compact::CompactGraph` and
+//! `snapshot::Snapshot` (behind the `compact` and `snapshot` features) keep
+//! only identity, status and edges, and other stores may keep less still in memory. [`GraphView`]
+//! is the part they all answer, so an analysis written against it runs on
+//! any of them:
+//!
+//! ```
+//! use graph_migrator_core::graph::EdgeType;
+//! use graph_migrator_core::{Edge, Graph, GraphView, Node, NodeType};
+//!
+//! /// IDs of the nodes nothing depends on
+//! fn roots<G: GraphView>(graph: &G) -> Vec<String> {
+//!     let mut roots: Vec<String> = graph
+//!         .node_refs()
+//!         .filter(|&node| graph.incoming(node).next().is_none())
+//!         .map(|node| graph.id(node).to_string())
+//!         .collect();
+//!     roots.sort();
+//!     roots
+//! }
+//!
+//! let mut graph = Graph::new();
+//! let main = graph.add_node(Node::new("m.py::main", "main", NodeType::Function, "python", "m.py"));
+//! let util = graph.add_node(Node::new("m.py::util", "util", NodeType::Function, "python", "m.py"));
+//! graph.add_edge(main, util, Edge::new(EdgeType::Calls));
+//! assert_eq!(roots(&graph), vec!["m.py::main"]);
+//! ```
+//!
+//! Nodes are addressed by a cheap handle ([`GraphView::NodeRef`]) that is
+//! only meaningful for the view it came from; accessors panic on handles
+//! from elsewhere.
+
+use crate::graph::{EdgeType, Graph, MigrationStatus, Node, NodeType};
+use crate::parser::MultiFileGraph;
+use petgraph::stable_graph::NodeIndex;
+use petgraph::Direction;
+use std::hash::Hash;
+use std::path::Path;
+
+/// Read-only view of a dependency graph
+pub trait GraphView {
+    /// Handle of a node within this view
+    type NodeRef: Copy + Eq + Hash + std::fmt::Debug;
+
+    /// Number of nodes
+    fn node_count(&self) -> usize;
+
+    /// Number of edges
+    fn edge_count(&self) -> usize;
+
+    /// Every node, in no particular order
+    fn node_refs(&self) -> impl Iterator<Item = Self::NodeRef> + '_;
+
+    /// The node with ID `id`
+    fn find(&self, id: &str) -> Option<Self::NodeRef>;
+
+    /// ID of `node`
+    fn id(&self, node: Self::NodeRef) -> &str;
+
+    /// Symbol name of `node`
+    fn name(&self, node: Self::NodeRef) -> &str;
+
+    /// Type of `node`
+    fn node_type(&self, node: Self::NodeRef) -> NodeType;
+
+    /// Migration status of `node`
+    fn status(&self, node: Self::NodeRef) -> MigrationStatus;
+
+    /// File defining `node`
+    fn file_path(&self, node: Self::NodeRef) -> &Path;
+
+    /// Line range of `node`, if known
+    fn line_range(&self, node: Self::NodeRef) -> Option<(usize, usize)>;
+
+    /// Whether `node` is an external (non-project) node
+    fn is_external(&self, node: Self::NodeRef) -> bool;
+
+    /// `(target, edge type)` of every edge leaving `node`
+    fn outgoing(&self, node: Self::NodeRef)
+        -> impl Iterator<Item = (Self::NodeRef, EdgeType)> + '_;
+
+    /// `(source, edge type)` of every edge entering `node`
+    fn incoming(&self, node: Self::NodeRef)
+        -> impl Iterator<Item = (Self::NodeRef, EdgeType)> + '_;
+
+    /// `(target or source, edge type)` of every edge on `node`'s side `direction`
+    fn adjacent(
+        &self,
+        node: Self::NodeRef,
+        direction: Direction,
+    ) -> Box<dyn Iterator<Item = (Self::NodeRef, EdgeType)> + '_> {
+        match direction {
+            Direction::Outgoing => Box::new(self.outgoing(node)),
+            Direction::Incoming => Box::new(self.incoming(node)),
+        }
+    }
+
+    /// `(source, target, edge type)` of every edge
+    fn edge_refs(&self) -> impl Iterator<Item = (Self::NodeRef, Self::NodeRef, EdgeType)> + '_ {
+        self.node_refs().flat_map(move |from| {
+            self.outgoing(from)
+                .map(move |(to, edge_type)| (from, to, edge_type))
+        })
+    }
+}
+
+impl GraphView for Graph {
+    type NodeRef = NodeIndex;
+
+    fn node_count(&self) -> usize {
+        Graph::node_count(self)
+    }
+
+    fn edge_count(&self) -> usize {
+        Graph::edge_count(self)
+    }
+
+    fn node_refs(&self) -> impl Iterator<Item = NodeIndex> + '_ {
+        self.node_indices()
+    }
+
+    fn find(&self, id: &str) -> Option<NodeIndex> {
+        self.get_by_id(id)
+    }
+
+    fn id(&self, node: NodeIndex) -> &str {
+        &node_of(self, node).id
+    }
+
+    fn name(&self, node: NodeIndex) -> &str {
+        &node_of(self, node).name
+    }
+
+    fn node_type(&self, node: NodeIndex) -> NodeType {
+        node_of(self, node).node_type.clone()
+    }
+
+    fn status(&self, node: NodeIndex) -> MigrationStatus {
+        node_of(self, node).status
+    }
+
+    fn file_path(&self, node: NodeIndex) -> &Path {
+        &node_of(self, node).file_path
+    }
+
+    fn line_range(&self, node: NodeIndex) -> Option<(usize, usize)> {
+        node_of(self, node).line_range
+    }
+
+    fn is_external(&self, node: NodeIndex) -> bool {
+        node_of(self, node).external
+    }
+
+    fn outgoing(&self, node: NodeIndex) -> impl Iterator<Item = (NodeIndex, EdgeType)> + '_ {
+        self.edges_directed(node, Direction::Outgoing)
+            .map(|(to, edge)| (to, edge.edge_type))
+    }
+
+    fn incoming(&self, node: NodeIndex) -> impl Iterator<Item = (NodeIndex, EdgeType)> + '_ {
+        self.edges_directed(node, Direction::Incoming)
+            .map(|(from, edge)| (from, edge.edge_type))
+    }
+
+    fn edge_refs(&self) -> impl Iterator<Item = (NodeIndex, NodeIndex, EdgeType)> + '_ {
+        self.edge_endpoints()
+            .map(|(from, to, edge)| (from, to, edge.edge_type))
+    }
+}
+
+/// The node at `index`, which must be in `graph`
+fn node_of(graph: &Graph, index: NodeIndex) -> &Node {
+    graph
+        .node_weight(index)
+        .unwrap_or_else(|| panic!("{:?} is not a node of this graph", index))
+}
+
+impl GraphView for MultiFileGraph {
+    type NodeRef = NodeIndex;
+
+    fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    fn edge_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+
+    fn node_refs(&self) -> impl Iterator<Item = NodeIndex> + '_ {
+        self.graph.node_indices()
+    }
+
+    fn find(&self, id: &str) -> Option<NodeIndex> {
+        self.graph.get_by_id(id)
+    }
+
+    fn id(&self, node: NodeIndex) -> &str {
+        GraphView::id(&self.graph, node)
+    }
+
+    fn name(&self, node: NodeIndex) -> &str {
+        GraphView::name(&self.graph, node)
+    }
+
+    fn node_type(&self, node: NodeIndex) -> NodeType {
+        GraphView::node_type(&self.graph, node)
+    }
+
+    fn status(&self, node: NodeIndex) -> MigrationStatus {
+        GraphView::status(&self.graph, node)
+    }
+
+    fn file_path(&self, node: NodeIndex) -> &Path {
+        GraphView::file_path(&self.graph, node)
+    }
+
+    fn line_range(&self, node: NodeIndex) -> Option<(usize, usize)> {
+        GraphView::line_range(&self.graph, node)
+    }
+
+    fn is_external(&self, node: NodeIndex) -> bool {
+        GraphView::is_external(&self.graph, node)
+    }
+
+    fn outgoing(&self, node: NodeIndex) -> impl Iterator<Item = (NodeIndex, EdgeType)> + '_ {
+        GraphView::outgoing(&self.graph, node)
+    }
+
+    fn incoming(&self, node: NodeIndex) -> impl Iterator<Item = (NodeIndex, EdgeType)> + '_ {
+        GraphView::incoming(&self.graph, node)
+    }
+
+    fn edge_refs(&self) -> impl Iterator<Item = (NodeIndex, NodeIndex, EdgeType)> + '_ {
+        GraphView::edge_refs(&self.graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Edge;
+    use crate::queries;
+
+    fn sample() -> Graph {
+        let mut graph = Graph::new();
+        let mut add = |name: &str| {
+            let id = format!("m.py::{}", name);
+            graph.add_node(Node::new(id, name, NodeType::Function, "python", "m.py"))
+        };
+        let (main, parse, util) = (add("main"), add("parse"), add("util"));
+        graph.add_edge(main, parse, Edge::new(EdgeType::Calls));
+        graph.add_edge(parse, util, Edge::new(EdgeType::Calls));
+        graph.add_edge(main, util, Edge::new(EdgeType::Imports));
+        graph
+    }
+
+    /// Views agree with each other and with the graph they came from
+    fn check<G: GraphView>(view: &G) {
+        assert_eq!(view.node_count(), 3);
+        assert_eq!(view.edge_count(), 3);
+        let util = view.find("m.py::util").unwrap();
+        assert_eq!(view.name(util), "util");
+        assert_eq!(view.node_type(util), NodeType::Function);
+        assert_eq!(view.file_path(util), Path::new("m.py"));
+        assert!(!view.is_external(util));
+        let mut callers: Vec<&str> = view
+            .adjacent(util, Direction::Incoming)
+            .map(|(from, _)| view.id(from))
+            .collect();
+        callers.sort();
+        assert_eq!(callers, vec!["m.py::main", "m.py::parse"]);
+        assert_eq!(view.edge_refs().count(), 3);
+        assert!(view.find("m.py::missing").is_none());
+
+        let dependents = queries::closure(view, "m.py::util", Direction::Incoming, &[]).unwrap();
+        assert_eq!(
+            dependents.into_iter().collect::<Vec<_>>(),
+            vec!["m.py::main", "m.py::parse"]
+        );
+    }
+
+    #[test]
+    fn test_views_answer_alike() {
+        let graph = sample();
+        check(&graph);
+        let mut multi = MultiFileGraph::new();
+        multi.graph = graph.clone();
+        check(&multi);
+        #[cfg(feature = "compact")]
+        check(&crate::compact::CompactGraph::from_graph(&graph));
+    }
+}
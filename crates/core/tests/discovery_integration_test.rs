@@ -22,19 +22,28 @@ fn test_integration_discovery_fixture() {
     assert_eq!(files.len(), 3, "Should find exactly 3 Python files");
 
     // Verify the expected files are found
-    let file_names: Vec<&str> = files.iter()
+    let file_names: Vec<&str> = files
+        .iter()
         .filter_map(|p| p.file_name())
         .filter_map(|n| n.to_str())
         .collect();
 
     assert!(file_names.contains(&"main.py"), "Should find main.py");
-    assert!(file_names.contains(&"test_main.py"), "Should find test_main.py");
+    assert!(
+        file_names.contains(&"test_main.py"),
+        "Should find test_main.py"
+    );
     assert!(file_names.contains(&"setup.py"), "Should find setup.py");
 
     // Verify venv files are excluded
-    assert!(!files.iter().any(|p| p.to_string_lossy().contains("venv")),
-            "Should exclude files in venv/ directory");
+    assert!(
+        !files.iter().any(|p| p.to_string_lossy().contains("venv")),
+        "Should exclude files in venv/ directory"
+    );
 
     // Verify all paths are absolute
-    assert!(files.iter().all(|p| p.is_absolute()), "All paths should be absolute");
+    assert!(
+        files.iter().all(|p| p.is_absolute()),
+        "All paths should be absolute"
+    );
 }
@@ -0,0 +1,96 @@
+//! Golden tests for pass 1
+//!
+//! Every file under `tests/test-fixtures/snapshots/<language>/` is parsed at
+//! the `calls` granularity, and what extraction made of it (nodes, edges and
+//! imports) is compared with its reviewed snapshot in `tests/snapshots/`.
+//!
+//! After changing extraction, run `cargo insta test -p graph-migrator-core`
+//! (or `INSTA_UPDATE=always cargo test --test parser_snapshot_test`) and
+//! review the differences with `cargo insta review` before committing them.
+//! To cover a new construct or language, add a fixture file and accept its
+//! new snapshot the same way.
+
+use graph_migrator_core::graph::Graph;
+use graph_migrator_core::import::{self, ImportStatement};
+use graph_migrator_core::parser::{self, GranularityLevel, ParseSession};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+const FIXTURES: &str = "tests/test-fixtures/snapshots";
+
+/// What pass 1 extracts from one fixture, in a reviewable order
+#[derive(Serialize)]
+struct Extraction {
+    /// `Type id (start-end)`, sorted by ID
+    nodes: Vec<String>,
+    /// `from -> to (Type)`, sorted
+    edges: Vec<String>,
+    /// In source order
+    imports: Vec<ImportStatement>,
+}
+
+/// Parse `path` as the file `name` (fixture-relative, so IDs do not depend
+/// on where the repository is checked out)
+fn extract(path: &Path, name: &Path) -> Extraction {
+    let source = std::fs::read_to_string(path).unwrap();
+    let mut session = ParseSession::new().with_granularity(Some(GranularityLevel::Calls));
+    session.add_source(name, source).unwrap();
+    let multi = parser::parse_files_in(&mut session, &[name]).unwrap();
+    let imports = import::extract_imports_in(&mut session, name).unwrap();
+    Extraction {
+        nodes: nodes(&multi.graph),
+        edges: edges(&multi.graph),
+        imports,
+    }
+}
+
+fn nodes(graph: &Graph) -> Vec<String> {
+    graph
+        .nodes_sorted_by_id()
+        .into_iter()
+        .map(|node| {
+            let lines = node.line_range.map_or(String::new(), |(start, end)| {
+                format!(" ({}-{})", start, end)
+            });
+            format!("{:?} {}{}", node.node_type, node.id, lines)
+        })
+        .collect()
+}
+
+fn edges(graph: &Graph) -> Vec<String> {
+    let mut edges: Vec<String> = graph
+        .edge_endpoints()
+        .map(|(from, to, edge)| {
+            let id = |idx| graph.node_weight(idx).unwrap().id.clone();
+            format!("{} -> {} ({:?})", id(from), id(to), edge.edge_type)
+        })
+        .collect();
+    edges.sort();
+    edges
+}
+
+/// Fixture files, sorted, with their fixture-relative names
+fn fixtures() -> Vec<(PathBuf, PathBuf)> {
+    let mut fixtures = Vec::new();
+    for language in std::fs::read_dir(FIXTURES).unwrap() {
+        let language = language.unwrap().path();
+        for file in std::fs::read_dir(&language).unwrap() {
+            let path = file.unwrap().path();
+            let name = path.strip_prefix(FIXTURES).unwrap().to_path_buf();
+            fixtures.push((path, name));
+        }
+    }
+    fixtures.sort();
+    fixtures
+}
+
+#[test]
+fn test_extraction_matches_snapshots() {
+    let fixtures = fixtures();
+    assert!(!fixtures.is_empty(), "no fixtures in {}", FIXTURES);
+    for (path, name) in fixtures {
+        // e.g. `python__shop.py`
+        let snapshot = name.to_string_lossy().replace(['/', '\\'], "__");
+        insta::assert_yaml_snapshot!(snapshot, extract(&path, &name));
+    }
+}
@@ -0,0 +1,100 @@
+//! Integration test for cross-file call binding
+//!
+//! This test uses the pre-created fixture at `tests/test-fixtures/import-project/`
+//! to verify that calls made through imports become `Calls` edges.
+
+use graph_migrator_core::config::Config;
+use graph_migrator_core::graph::{EdgePolicy, EdgeType};
+use graph_migrator_core::resolve::{self, UnresolvedReason};
+use std::path::Path;
+
+/// `file::name` pairs for every `Calls` edge, with file names only
+fn call_edges(graph: &graph_migrator_core::Graph) -> Vec<(String, String)> {
+    let short = |idx| {
+        let node = graph.node_weight(idx).unwrap();
+        let file = node.file_path.file_name().unwrap().to_string_lossy();
+        format!("{}::{}", file, node.name)
+    };
+    let mut edges: Vec<(String, String)> = graph
+        .edge_endpoints()
+        .filter(|(_, _, edge)| edge.edge_type == EdgeType::Calls)
+        .map(|(from, to, _)| (short(from), short(to)))
+        .collect();
+    edges.sort();
+    edges
+}
+
+#[test]
+fn test_integration_cross_file_calls() {
+    let fixture_path = Path::new("tests/test-fixtures/import-project");
+    assert!(fixture_path.exists(), "Fixture directory should exist");
+
+    let output = resolve::resolve_directory(fixture_path).unwrap();
+
+    let pair = |from: &str, to: &str| (from.to_string(), to.to_string());
+    assert_eq!(
+        call_edges(&output.graph.graph),
+        vec![
+            pair("main.py::main", "helpers.py::format_name"),
+            pair("main.py::main", "tools.py::run"),
+            pair("main.py::main", "utils.py::helper"),
+            pair("tools.py::run", "shared.py::common"),
+            pair("tools.py::run", "shared.py::other"),
+        ]
+    );
+
+    // Only the call into the standard library is left over
+    let unresolved: Vec<&str> = output
+        .graph
+        .unresolved_calls
+        .iter()
+        .map(|call| call.callee.as_str())
+        .collect();
+    assert_eq!(unresolved, vec!["os.getcwd"]);
+
+    // The report adds the `import os` that made the call external
+    let report: Vec<(&str, UnresolvedReason)> = output
+        .unresolved
+        .references
+        .iter()
+        .map(|r| (r.name.as_str(), r.reason))
+        .collect();
+    assert_eq!(
+        report,
+        vec![
+            ("os", UnresolvedReason::External),
+            ("os.getcwd", UnresolvedReason::External),
+        ]
+    );
+
+    // Counting keeps one edge per pair, weighted by its call sites
+    let config = Config {
+        edge_policy: EdgePolicy::Count,
+        ..Config::default()
+    };
+    let counted = resolve::resolve_directory_with_config(fixture_path, &config).unwrap();
+    let graph = &counted.graph.graph;
+    let run_calls: Vec<u32> = graph
+        .edge_endpoints()
+        .filter(|(_, to, edge)| {
+            edge.edge_type == EdgeType::Calls && graph.node_weight(*to).unwrap().name == "run"
+        })
+        .map(|(_, _, edge)| edge.count)
+        .collect();
+    assert_eq!(run_calls, vec![2]);
+}
+
+#[test]
+fn test_integration_lenient_matches_strict() {
+    let fixture_path = Path::new("tests/test-fixtures/import-project");
+
+    let strict = resolve::resolve_directory(fixture_path).unwrap();
+    let (lenient, diagnostics) =
+        resolve::resolve_directory_lenient(fixture_path, &Config::default());
+
+    assert!(diagnostics.is_empty());
+    assert_eq!(
+        call_edges(&lenient.graph.graph),
+        call_edges(&strict.graph.graph)
+    );
+}
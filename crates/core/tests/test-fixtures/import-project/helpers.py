@@ -0,0 +1,2 @@
+def format_name(name):
+    return "<%s>" % name
@@ -0,0 +1,15 @@
+"""Entry point exercising each import form the binder understands."""
+import os
+
+import utils
+from helpers import format_name as fmt
+from pkg import tools
+from pkg.tools import run
+
+
+def main():
+    utils.helper()
+    fmt("x")
+    tools.run()
+    run()
+    os.getcwd()
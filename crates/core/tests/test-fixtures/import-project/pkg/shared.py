@@ -0,0 +1,6 @@
+def common():
+    return "common"
+
+
+def other():
+    return "other"
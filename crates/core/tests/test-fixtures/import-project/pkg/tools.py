@@ -0,0 +1,7 @@
+from . import shared
+from .shared import common
+
+
+def run():
+    common()
+    shared.other()
@@ -0,0 +1,31 @@
+"""Checkout for the shop"""
+
+import os
+from decimal import Decimal as D
+from . import models
+from .pricing import *
+
+
+class Cart:
+    def __init__(self, items: list):
+        self.items = items
+
+    def total(self) -> D:
+        return D(sum(price(item) for item in self.items))
+
+
+def price(item) -> D:
+    if os.environ.get("DISCOUNT"):
+        from .discounts import apply
+        return apply(item.price)
+    return D(item.price)
+
+
+def checkout(cart: Cart) -> D:
+    total = cart.total()
+    log_total(total)
+    return total
+
+
+def log_total(total):
+    print(total)
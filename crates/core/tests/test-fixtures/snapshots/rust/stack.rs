@@ -0,0 +1,36 @@
+use std::fmt;
+
+pub struct Stack<T> {
+    items: Vec<T>,
+}
+
+pub enum Op {
+    Push(u8),
+    Pop,
+}
+
+pub trait Shape {
+    fn area(&self) -> f64;
+}
+
+impl<T> Stack<T> {
+    pub fn new() -> Self {
+        Stack { items: Vec::new() }
+    }
+
+    pub fn push(&mut self, item: T) {
+        self.items.push(item);
+    }
+}
+
+impl fmt::Display for Stack<u8> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.items)
+    }
+}
+
+fn apply(stack: &mut Stack<u8>, op: Op) {
+    if let Op::Push(value) = op {
+        stack.push(value);
+    }
+}
@@ -0,0 +1,31 @@
+import { Item } from "./item";
+
+export interface Priced {
+  price: number;
+}
+
+export abstract class Store {
+  abstract name(): string;
+}
+
+export class Cart {
+  private items: Item[] = [];
+
+  add(item: Item): void {
+    this.items.push(item);
+  }
+
+  total(): number {
+    return sum(this.items);
+  }
+}
+
+export const sum = (items: Priced[]) => items.reduce((acc, item) => acc + item.price, 0);
+
+const format = function (value: number) {
+  return value.toFixed(2);
+};
+
+export function* ids() {
+  yield 1;
+}
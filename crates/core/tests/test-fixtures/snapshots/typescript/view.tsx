@@ -0,0 +1,11 @@
+import { Cart } from "./cart";
+
+export function CartView({ cart }: { cart: Cart }) {
+  return <p>{cart.total()}</p>;
+}
+
+export default class Page {
+  render() {
+    return <CartView cart={new Cart()} />;
+  }
+}